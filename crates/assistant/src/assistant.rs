@@ -35,7 +35,9 @@ use language_model::{
 pub use patch::*;
 pub use prompts::PromptBuilder;
 use prompts::PromptLoadingParams;
-use semantic_index::{CloudEmbeddingProvider, SemanticDb};
+use semantic_index::{
+    CloudEmbeddingProvider, EmbeddingProvider, LocalEmbeddingProvider, SemanticDb,
+};
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use slash_command::search_command::SearchSlashCommandFeatureFlag;
@@ -223,10 +225,43 @@ pub fn init(
                 return Ok(());
             }
 
-            let embedding_provider = CloudEmbeddingProvider::new(client.clone());
+            // Embed locally instead of in the cloud when a model is configured for it. Embeddings
+            // of different models aren't comparable, so each local model gets its own database.
+            let local_model_dir = cx.update(|cx| {
+                SlashCommandSettings::get_global(cx)
+                    .search
+                    .local_embedding_model
+                    .clone()
+            })?;
+            let (embedding_provider, db_name): (Arc<dyn EmbeddingProvider>, _) =
+                if let Some(local_model_dir) = local_model_dir {
+                    if !LocalEmbeddingProvider::is_model_dir(&local_model_dir) {
+                        anyhow::bail!(
+                            "{local_model_dir:?} doesn't contain a model.onnx and a tokenizer.json"
+                        );
+                    }
+                    let (provider, model_digest) = cx
+                        .background_executor()
+                        .spawn(async move {
+                            anyhow::Ok((
+                                LocalEmbeddingProvider::new(&local_model_dir)?,
+                                LocalEmbeddingProvider::model_digest(&local_model_dir)?,
+                            ))
+                        })
+                        .await?;
+                    (
+                        Arc::new(provider),
+                        format!("semantic-index-db.local.{model_digest}.0.mdb"),
+                    )
+                } else {
+                    (
+                        Arc::new(CloudEmbeddingProvider::new(client.clone())),
+                        "semantic-index-db.0.mdb".to_string(),
+                    )
+                };
             let semantic_index = SemanticDb::new(
-                paths::embeddings_dir().join("semantic-index-db.0.mdb"),
-                Arc::new(embedding_provider),
+                paths::embeddings_dir().join(db_name),
+                embedding_provider,
                 &mut cx,
             )
             .await?;
@@ -234,7 +269,7 @@ pub fn init(
             cx.update(|cx| cx.set_global(semantic_index))
         }
     })
-    .detach();
+    .detach_and_log_err(cx);
 
     context_store::init(&client.clone().into());
     prompt_library::init(cx);
@@ -2477,6 +2477,7 @@ impl Context {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
         };
         for message in self.messages(cx) {
             if message.status != MessageStatus::Done {
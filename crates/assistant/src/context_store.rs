@@ -892,6 +892,7 @@ impl ContextStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
@@ -2825,6 +2825,7 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
         })
     }
 
@@ -3422,6 +3423,10 @@ struct AssistantCodeActionProvider {
 }
 
 impl CodeActionProvider for AssistantCodeActionProvider {
+    fn id(&self) -> &'static str {
+        "assistant"
+    }
+
     fn code_actions(
         &self,
         buffer: &Model<Buffer>,
@@ -6,12 +6,13 @@ use assistant_slash_command::{
 use feature_flags::FeatureFlag;
 use gpui::{AppContext, Task, WeakView};
 use language::{CodeLabel, LspAdapterDelegate};
-use semantic_index::{LoadedSearchResult, SemanticDb};
+use semantic_index::{LoadedSearchResult, SearchOptions, SemanticDb};
 use std::{
     fmt::Write,
     sync::{atomic::AtomicBool, Arc},
 };
 use ui::{prelude::*, IconName};
+use util::paths::PathMatcher;
 use workspace::Workspace;
 
 use crate::slash_command::create_label_for_command;
@@ -35,7 +36,11 @@ impl SlashCommand for SearchSlashCommand {
     }
 
     fn label(&self, cx: &AppContext) -> CodeLabel {
-        create_label_for_command("search", &["--n"], cx)
+        create_label_for_command(
+            "search",
+            &["--n", "--include=<glob>", "--exclude=<glob>"],
+            cx,
+        )
     }
 
     fn description(&self) -> String {
@@ -81,6 +86,8 @@ impl SlashCommand for SearchSlashCommand {
         };
 
         let mut limit = None;
+        let mut files_to_include = Vec::new();
+        let mut files_to_exclude = Vec::new();
         let mut query = String::new();
         for part in arguments {
             if let Some(parameter) = part.strip_prefix("--") {
@@ -88,6 +95,14 @@ impl SlashCommand for SearchSlashCommand {
                     limit = Some(count);
                     continue;
                 }
+                if let Some(glob) = parameter.strip_prefix("include=") {
+                    files_to_include.push(glob.to_string());
+                    continue;
+                }
+                if let Some(glob) = parameter.strip_prefix("exclude=") {
+                    files_to_exclude.push(glob.to_string());
+                    continue;
+                }
             }
 
             query.push_str(part);
@@ -99,6 +114,20 @@ impl SlashCommand for SearchSlashCommand {
             return Task::ready(Err(anyhow::anyhow!("missing search query")));
         }
 
+        let mut options = SearchOptions::new(limit.unwrap_or(5));
+        match (
+            PathMatcher::new(&files_to_include),
+            PathMatcher::new(&files_to_exclude),
+        ) {
+            (Ok(include), Ok(exclude)) => {
+                options.files_to_include = include;
+                options.files_to_exclude = exclude;
+            }
+            (Err(error), _) | (_, Err(error)) => {
+                return Task::ready(Err(anyhow::anyhow!("invalid glob: {error}")));
+            }
+        }
+
         let project = workspace.read(cx).project().clone();
         let fs = project.read(cx).fs().clone();
         let Some(project_index) =
@@ -110,7 +139,7 @@ impl SlashCommand for SearchSlashCommand {
         cx.spawn(|cx| async move {
             let results = project_index
                 .read_with(&cx, |project_index, cx| {
-                    project_index.search(vec![query.clone()], limit.unwrap_or(5), cx)
+                    project_index.search_with_options(vec![query.clone()], options, cx)
                 })?
                 .await?;
 
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use gpui::AppContext;
 use schemars::JsonSchema;
@@ -13,6 +15,9 @@ pub struct SlashCommandSettings {
     /// Settings for the `/cargo-workspace` slash command.
     #[serde(default)]
     pub cargo_workspace: CargoWorkspaceCommandSettings,
+    /// Settings for the `/search` slash command.
+    #[serde(default)]
+    pub search: SearchCommandSettings,
 }
 
 /// Settings for the `/docs` slash command.
@@ -31,6 +36,16 @@ pub struct CargoWorkspaceCommandSettings {
     pub enabled: bool,
 }
 
+/// Settings for the `/search` slash command.
+#[derive(Deserialize, Serialize, Debug, Default, Clone, JsonSchema)]
+pub struct SearchCommandSettings {
+    /// The directory of a sentence-embedding model exported to ONNX, containing `model.onnx`
+    /// and `tokenizer.json`, to index the project with on this machine instead of in the cloud.
+    /// Changes take effect after a restart.
+    #[serde(default)]
+    pub local_embedding_model: Option<PathBuf>,
+}
+
 impl Settings for SlashCommandSettings {
     const KEY: Option<&'static str> = Some("slash_commands");
 
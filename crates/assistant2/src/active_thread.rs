@@ -1,20 +1,21 @@
 use std::sync::Arc;
 
-use assistant_tool::ToolWorkingSet;
+use anyhow::anyhow;
+use assistant_tool::{Tool, ToolWorkingSet};
 use collections::HashMap;
 use gpui::{
     list, AnyElement, AppContext, Empty, ListAlignment, ListState, Model, StyleRefinement,
-    Subscription, TextStyleRefinement, View, WeakView,
+    Subscription, Task, TextStyleRefinement, View, WeakView,
 };
 use language::LanguageRegistry;
-use language_model::Role;
+use language_model::{LanguageModelToolUseId, Role};
 use markdown::{Markdown, MarkdownStyle};
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::prelude::*;
 use workspace::Workspace;
 
-use crate::thread::{MessageId, Thread, ThreadError, ThreadEvent};
+use crate::thread::{MessageId, PendingToolUse, Thread, ThreadError, ThreadEvent};
 use crate::ui::ContextPill;
 
 pub struct ActiveThread {
@@ -25,6 +26,8 @@ pub struct ActiveThread {
     messages: Vec<MessageId>,
     list_state: ListState,
     rendered_messages_by_id: HashMap<MessageId, View<Markdown>>,
+    /// The rendered confirmation prompts of the tool uses waiting for the user's approval.
+    tool_confirmations: HashMap<LanguageModelToolUseId, View<Markdown>>,
     last_error: Option<ThreadError>,
     _subscriptions: Vec<Subscription>,
 }
@@ -49,6 +52,7 @@ impl ActiveThread {
             thread: thread.clone(),
             messages: Vec::new(),
             rendered_messages_by_id: HashMap::default(),
+            tool_confirmations: HashMap::default(),
             list_state: ListState::new(0, ListAlignment::Bottom, px(1024.), {
                 let this = cx.view().downgrade();
                 move |ix, cx: &mut WindowContext| {
@@ -88,6 +92,11 @@ impl ActiveThread {
         self.messages.push(*id);
         self.list_state.splice(old_len..old_len, 1);
 
+        let markdown = self.render_markdown(text, cx);
+        self.rendered_messages_by_id.insert(*id, markdown);
+    }
+
+    fn render_markdown(&self, text: String, cx: &mut ViewContext<Self>) -> View<Markdown> {
         let theme_settings = ThemeSettings::get_global(cx);
         let ui_font_size = TextSize::Default.rems(cx);
         let buffer_font_size = theme_settings.buffer_font_size;
@@ -121,7 +130,7 @@ impl ActiveThread {
             ..Default::default()
         };
 
-        let markdown = cx.new_view(|cx| {
+        cx.new_view(|cx| {
             Markdown::new(
                 text,
                 markdown_style,
@@ -129,8 +138,61 @@ impl ActiveThread {
                 None,
                 cx,
             )
+        })
+    }
+
+    /// Remeasures the message, after the tool uses shown below it changed.
+    fn invalidate_message(&mut self, id: MessageId) {
+        if let Some(ix) = self
+            .messages
+            .iter()
+            .position(|message_id| *message_id == id)
+        {
+            self.list_state.splice(ix..ix + 1, 1);
+        }
+    }
+
+    fn run_tool_use(
+        &mut self,
+        tool: Arc<dyn Tool>,
+        tool_use: PendingToolUse,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(tool_use.assistant_message_id, tool_use.id, task, cx);
+        });
+    }
+
+    fn confirm_tool_use(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.tool_confirmations.remove(&tool_use_id);
+        let Some(tool_use) = self.thread.read(cx).pending_tool_use(&tool_use_id).cloned() else {
+            return;
+        };
+        self.invalidate_message(tool_use.assistant_message_id);
+        if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
+            self.run_tool_use(tool, tool_use, cx);
+        }
+    }
+
+    fn reject_tool_use(&mut self, tool_use_id: LanguageModelToolUseId, cx: &mut ViewContext<Self>) {
+        self.tool_confirmations.remove(&tool_use_id);
+        let Some(tool_use) = self.thread.read(cx).pending_tool_use(&tool_use_id).cloned() else {
+            return;
+        };
+        self.invalidate_message(tool_use.assistant_message_id);
+        self.thread.update(cx, |thread, cx| {
+            thread.insert_tool_output(
+                tool_use.assistant_message_id,
+                tool_use.id,
+                Task::ready(Err(anyhow!("The user declined to run this tool."))),
+                cx,
+            );
         });
-        self.rendered_messages_by_id.insert(*id, markdown);
     }
 
     fn handle_thread_event(
@@ -175,17 +237,28 @@ impl ActiveThread {
                     .collect::<Vec<_>>();
 
                 for tool_use in pending_tool_uses {
-                    if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                        let task = tool.run(tool_use.input, self.workspace.clone(), cx);
-
+                    let Some(tool) = self.tools.tool(&tool_use.name, cx) else {
+                        continue;
+                    };
+                    if tool.needs_confirmation() {
                         self.thread.update(cx, |thread, cx| {
-                            thread.insert_tool_output(
-                                tool_use.assistant_message_id,
-                                tool_use.id.clone(),
-                                task,
-                                cx,
-                            );
+                            thread.request_tool_use_confirmation(&tool_use.id, cx);
                         });
+                        // The approval is asked for once the prompt is ready, since it can
+                        // depend on the state of the project, like the diff of an edit.
+                        let prompt =
+                            tool.confirmation_prompt(&tool_use.input, self.workspace.clone(), cx);
+                        cx.spawn(|this, mut cx| async move {
+                            let prompt = prompt.await;
+                            this.update(&mut cx, |this, cx| {
+                                let prompt = this.render_markdown(prompt, cx);
+                                this.tool_confirmations.insert(tool_use.id, prompt);
+                                this.invalidate_message(tool_use.assistant_message_id);
+                            })
+                        })
+                        .detach_and_log_err(cx);
+                    } else {
+                        self.run_tool_use(tool, tool_use, cx);
                     }
                 }
             }
@@ -204,6 +277,19 @@ impl ActiveThread {
         };
 
         let context = self.thread.read(cx).context_for_message(message_id);
+        let tool_confirmations = self
+            .thread
+            .read(cx)
+            .pending_tool_uses()
+            .into_iter()
+            .filter(|tool_use| {
+                tool_use.assistant_message_id == message_id && tool_use.status.needs_confirmation()
+            })
+            .filter_map(|tool_use| {
+                let prompt = self.tool_confirmations.get(&tool_use.id)?.clone();
+                Some((tool_use.id.clone(), tool_use.name.clone(), prompt))
+            })
+            .collect::<Vec<_>>();
 
         let (role_icon, role_name) = match message.role {
             Role::User => (IconName::Person, "You"),
@@ -248,10 +334,59 @@ impl ActiveThread {
                                     .map(|context| ContextPill::new(context.clone())),
                             ),
                         )
-                    }),
+                    })
+                    .children(tool_confirmations.into_iter().map(|(id, name, prompt)| {
+                        self.render_tool_confirmation(id, name, prompt, cx)
+                    })),
             )
             .into_any()
     }
+
+    fn render_tool_confirmation(
+        &self,
+        tool_use_id: LanguageModelToolUseId,
+        tool_name: String,
+        prompt: View<Markdown>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .gap_1()
+            .px_2()
+            .py_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(format!("Allow the assistant to use `{tool_name}`?"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(div().text_ui(cx).child(prompt))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .justify_end()
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("reject-tool-use-{tool_use_id}")),
+                            "Reject",
+                        )
+                        .on_click(cx.listener({
+                            let tool_use_id = tool_use_id.clone();
+                            move |this, _, cx| this.reject_tool_use(tool_use_id.clone(), cx)
+                        })),
+                    )
+                    .child(
+                        Button::new(
+                            SharedString::from(format!("allow-tool-use-{tool_use_id}")),
+                            "Allow",
+                        )
+                        .style(ButtonStyle::Filled)
+                        .on_click(cx.listener(move |this, _, cx| {
+                            this.confirm_tool_use(tool_use_id.clone(), cx)
+                        })),
+                    ),
+            )
+    }
 }
 
 impl Render for ActiveThread {
@@ -259,3 +394,158 @@ impl Render for ActiveThread {
         list(self.list_state.clone()).flex_1().py_1()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::Result;
+    use gpui::{TestAppContext, VisualTestContext};
+    use language_model::LanguageModelToolUse;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use workspace::AppState;
+
+    use super::*;
+    use crate::thread::PendingToolUseStatus;
+
+    struct CountingTool {
+        name: &'static str,
+        needs_confirmation: bool,
+        run_count: Arc<AtomicUsize>,
+    }
+
+    impl Tool for CountingTool {
+        fn name(&self) -> String {
+            self.name.into()
+        }
+
+        fn description(&self) -> String {
+            "Counts how many times it runs.".into()
+        }
+
+        fn needs_confirmation(&self) -> bool {
+            self.needs_confirmation
+        }
+
+        fn run(
+            self: Arc<Self>,
+            _input: serde_json::Value,
+            _workspace: WeakView<Workspace>,
+            _cx: &mut WindowContext,
+        ) -> Task<Result<String>> {
+            self.run_count.fetch_add(1, Ordering::SeqCst);
+            Task::ready(Ok("done".into()))
+        }
+    }
+
+    #[gpui::test]
+    async fn test_tool_use_confirmation(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({})).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        let confirmed_runs = Arc::new(AtomicUsize::new(0));
+        let unconfirmed_runs = Arc::new(AtomicUsize::new(0));
+        let tools = Arc::new(ToolWorkingSet::default());
+        tools.insert(Arc::new(CountingTool {
+            name: "confirmed",
+            needs_confirmation: true,
+            run_count: confirmed_runs.clone(),
+        }));
+        tools.insert(Arc::new(CountingTool {
+            name: "unconfirmed",
+            needs_confirmation: false,
+            run_count: unconfirmed_runs.clone(),
+        }));
+
+        let thread = cx.new_model(|cx| Thread::new(tools.clone(), cx));
+        let active_thread = cx.new_view(|cx| {
+            ActiveThread::new(
+                thread.clone(),
+                workspace.downgrade(),
+                language_registry,
+                tools,
+                cx,
+            )
+        });
+
+        thread.update(cx, |thread, cx| {
+            thread.insert_message(Role::Assistant, "Let me check.", cx);
+            for (id, name) in [
+                ("approved", "confirmed"),
+                ("rejected", "confirmed"),
+                ("automatic", "unconfirmed"),
+            ] {
+                thread.insert_tool_use(LanguageModelToolUse {
+                    id: id.into(),
+                    name: name.into(),
+                    input: json!({}),
+                });
+            }
+            cx.emit(ThreadEvent::UsePendingTools);
+        });
+        cx.run_until_parked();
+
+        // Only the tool that doesn't need confirmation runs right away.
+        assert_eq!(confirmed_runs.load(Ordering::SeqCst), 0);
+        assert_eq!(unconfirmed_runs.load(Ordering::SeqCst), 1);
+        assert!(tool_use_status(&thread, "approved", cx).needs_confirmation());
+        assert!(tool_use_status(&thread, "rejected", cx).needs_confirmation());
+        assert!(matches!(
+            tool_use_status(&thread, "automatic", cx),
+            PendingToolUseStatus::Running { .. }
+        ));
+        active_thread.read_with(cx, |active_thread, _| {
+            assert!(active_thread
+                .tool_confirmations
+                .contains_key(&LanguageModelToolUseId::from("approved")));
+            assert!(active_thread
+                .tool_confirmations
+                .contains_key(&LanguageModelToolUseId::from("rejected")));
+        });
+
+        active_thread.update(cx, |active_thread, cx| {
+            active_thread.confirm_tool_use("approved".into(), cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(confirmed_runs.load(Ordering::SeqCst), 1);
+        assert!(matches!(
+            tool_use_status(&thread, "approved", cx),
+            PendingToolUseStatus::Running { .. }
+        ));
+
+        active_thread.update(cx, |active_thread, cx| {
+            active_thread.reject_tool_use("rejected".into(), cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(confirmed_runs.load(Ordering::SeqCst), 1);
+        match tool_use_status(&thread, "rejected", cx) {
+            PendingToolUseStatus::Error(message) => {
+                assert_eq!(message, "The user declined to run this tool.")
+            }
+            status => panic!("unexpected status {status:?}"),
+        }
+        active_thread.read_with(cx, |active_thread, _| {
+            assert!(active_thread.tool_confirmations.is_empty());
+        });
+    }
+
+    fn tool_use_status(
+        thread: &Model<Thread>,
+        id: &str,
+        cx: &mut VisualTestContext,
+    ) -> PendingToolUseStatus {
+        thread.read_with(cx, |thread, _| {
+            thread.pending_tool_use(&id.into()).unwrap().status.clone()
+        })
+    }
+}
@@ -26,6 +26,7 @@ pub enum ContextKind {
     File,
     FetchedUrl,
     Thread,
+    Resource,
 }
 
 pub fn attach_context_to_message(
@@ -35,6 +36,7 @@ pub fn attach_context_to_message(
     let mut file_context = String::new();
     let mut fetch_context = String::new();
     let mut thread_context = String::new();
+    let mut resource_context = String::new();
 
     for context in context.into_iter() {
         match context.kind {
@@ -54,6 +56,12 @@ pub fn attach_context_to_message(
                 thread_context.push_str(&context.text);
                 thread_context.push('\n');
             }
+            ContextKind::Resource => {
+                resource_context.push_str(&context.name);
+                resource_context.push('\n');
+                resource_context.push_str(&context.text);
+                resource_context.push('\n');
+            }
         }
     }
 
@@ -73,6 +81,11 @@ pub fn attach_context_to_message(
         context_text.push_str(&thread_context);
     }
 
+    if !resource_context.is_empty() {
+        context_text.push_str("The following context server resources are available\n");
+        context_text.push_str(&resource_context);
+    }
+
     if !context_text.is_empty() {
         message.content.push(MessageContent::Text(context_text));
     }
@@ -1,5 +1,6 @@
 mod fetch_context_picker;
 mod file_context_picker;
+mod resource_context_picker;
 mod thread_context_picker;
 
 use std::sync::Arc;
@@ -16,16 +17,20 @@ use workspace::Workspace;
 use crate::context::ContextKind;
 use crate::context_picker::fetch_context_picker::FetchContextPicker;
 use crate::context_picker::file_context_picker::FileContextPicker;
+use crate::context_picker::resource_context_picker::ResourceContextPicker;
 use crate::context_picker::thread_context_picker::ThreadContextPicker;
 use crate::context_store::ContextStore;
 use crate::thread_store::ThreadStore;
 
+pub(crate) use resource_context_picker::resource_text;
+
 #[derive(Debug, Clone)]
 enum ContextPickerMode {
     Default,
     File(View<FileContextPicker>),
     Fetch(View<FetchContextPicker>),
     Thread(View<ThreadContextPicker>),
+    Resource(View<ResourceContextPicker>),
 }
 
 pub(super) struct ContextPicker {
@@ -59,6 +64,11 @@ impl ContextPicker {
                 kind: ContextKind::Thread,
                 icon: IconName::MessageCircle,
             });
+            entries.push(ContextPickerEntry {
+                name: "Resource".into(),
+                kind: ContextKind::Resource,
+                icon: IconName::Server,
+            });
         }
 
         let delegate = ContextPickerDelegate {
@@ -94,6 +104,7 @@ impl FocusableView for ContextPicker {
             ContextPickerMode::File(file_picker) => file_picker.focus_handle(cx),
             ContextPickerMode::Fetch(fetch_picker) => fetch_picker.focus_handle(cx),
             ContextPickerMode::Thread(thread_picker) => thread_picker.focus_handle(cx),
+            ContextPickerMode::Resource(resource_picker) => resource_picker.focus_handle(cx),
         }
     }
 }
@@ -108,6 +119,9 @@ impl Render for ContextPicker {
                 ContextPickerMode::File(file_picker) => parent.child(file_picker.clone()),
                 ContextPickerMode::Fetch(fetch_picker) => parent.child(fetch_picker.clone()),
                 ContextPickerMode::Thread(thread_picker) => parent.child(thread_picker.clone()),
+                ContextPickerMode::Resource(resource_picker) => {
+                    parent.child(resource_picker.clone())
+                }
             })
     }
 }
@@ -189,6 +203,18 @@ impl PickerDelegate for ContextPickerDelegate {
                                 }));
                             }
                         }
+                        ContextKind::Resource => {
+                            if let Some(thread_store) = self.thread_store.as_ref() {
+                                this.mode = ContextPickerMode::Resource(cx.new_view(|cx| {
+                                    ResourceContextPicker::new(
+                                        thread_store.clone(),
+                                        self.context_picker.clone(),
+                                        self.context_store.clone(),
+                                        cx,
+                                    )
+                                }));
+                            }
+                        }
                     }
 
                     cx.focus_self();
@@ -203,7 +229,8 @@ impl PickerDelegate for ContextPickerDelegate {
                 ContextPickerMode::Default => cx.emit(DismissEvent),
                 ContextPickerMode::File(_)
                 | ContextPickerMode::Fetch(_)
-                | ContextPickerMode::Thread(_) => {}
+                | ContextPickerMode::Thread(_)
+                | ContextPickerMode::Resource(_) => {}
             })
             .log_err();
     }
@@ -0,0 +1,443 @@
+use std::sync::Arc;
+
+use context_server::protocol::ServerCapability;
+use context_server::types;
+use fuzzy::StringMatchCandidate;
+use gpui::{AppContext, DismissEvent, FocusHandle, FocusableView, Task, View, WeakModel, WeakView};
+use picker::{Picker, PickerDelegate};
+use ui::{prelude::*, ListItem};
+use url::Url;
+use util::ResultExt as _;
+
+use crate::context::ContextKind;
+use crate::context_picker::ContextPicker;
+use crate::context_store::ContextStore;
+use crate::thread_store::ThreadStore;
+
+pub struct ResourceContextPicker {
+    picker: View<Picker<ResourceContextPickerDelegate>>,
+}
+
+impl ResourceContextPicker {
+    pub fn new(
+        thread_store: WeakModel<ThreadStore>,
+        context_picker: WeakView<ContextPicker>,
+        context_store: WeakModel<ContextStore>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate =
+            ResourceContextPickerDelegate::new(thread_store, context_picker, context_store);
+        let picker = cx.new_view(|cx| {
+            let mut picker = Picker::uniform_list(delegate, cx);
+            picker.delegate.load_resources(cx);
+            picker
+        });
+
+        ResourceContextPicker { picker }
+    }
+}
+
+impl FocusableView for ResourceContextPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for ResourceContextPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        self.picker.clone()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ResourceContextEntry {
+    server_id: Arc<str>,
+    uri: Url,
+    name: SharedString,
+}
+
+pub struct ResourceContextPickerDelegate {
+    thread_store: WeakModel<ThreadStore>,
+    context_picker: WeakView<ContextPicker>,
+    context_store: WeakModel<ContextStore>,
+    resources: Vec<ResourceContextEntry>,
+    matches: Vec<ResourceContextEntry>,
+    selected_index: usize,
+    load_resources_task: Option<Task<()>>,
+}
+
+impl ResourceContextPickerDelegate {
+    pub fn new(
+        thread_store: WeakModel<ThreadStore>,
+        context_picker: WeakView<ContextPicker>,
+        context_store: WeakModel<ContextStore>,
+    ) -> Self {
+        ResourceContextPickerDelegate {
+            thread_store,
+            context_picker,
+            context_store,
+            resources: Vec::new(),
+            matches: Vec::new(),
+            selected_index: 0,
+            load_resources_task: None,
+        }
+    }
+
+    /// Lists the resources of all the servers once, so that typing only filters them.
+    fn load_resources(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let Ok(servers) = self.thread_store.update(cx, |this, cx| {
+            this.context_server_manager().read(cx).servers()
+        }) else {
+            return;
+        };
+
+        self.load_resources_task = Some(cx.spawn(|this, mut cx| async move {
+            let mut resources = Vec::new();
+            for server in servers {
+                let Some(protocol) = server.client() else {
+                    continue;
+                };
+                if !protocol.capable(ServerCapability::Resources) {
+                    continue;
+                }
+                let Some(server_resources) = protocol.list_all_resources().await.log_err() else {
+                    continue;
+                };
+                resources.extend(server_resources.into_iter().map(|resource| {
+                    ResourceContextEntry {
+                        server_id: server.id(),
+                        uri: resource.uri,
+                        name: resource.name.into(),
+                    }
+                }));
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.delegate.resources = resources;
+                this.refresh(cx);
+            })
+            .ok();
+        }));
+    }
+}
+
+impl PickerDelegate for ResourceContextPickerDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search resources…".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No context server provides resources".into()
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let resources = self.resources.clone();
+        let executor = cx.background_executor().clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                resources
+            } else {
+                let candidates = resources
+                    .iter()
+                    .enumerate()
+                    .map(|(id, resource)| StringMatchCandidate::new(id, &resource.name))
+                    .collect::<Vec<_>>();
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    executor,
+                )
+                .await
+                .into_iter()
+                .map(|mat| resources[mat.candidate_id].clone())
+                .collect()
+            };
+
+            this.update(&mut cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .ok();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self.matches.get(self.selected_index).cloned() else {
+            return;
+        };
+        let Ok(context_server_manager) = self
+            .thread_store
+            .update(cx, |this, _cx| this.context_server_manager())
+        else {
+            return;
+        };
+        let Some(protocol) = context_server_manager
+            .read(cx)
+            .get_server(&entry.server_id)
+            .and_then(|server| server.client())
+        else {
+            return;
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let response = protocol.read_resource(entry.uri.clone()).await?;
+            let context_id = this.update(&mut cx, |this, cx| {
+                this.delegate
+                    .context_store
+                    .update(cx, |context_store, _cx| {
+                        context_store.insert_context(
+                            ContextKind::Resource,
+                            entry.name.clone(),
+                            resource_text(response),
+                        )
+                    })
+            })??;
+
+            if protocol.supports_resource_subscriptions() {
+                protocol.subscribe_resource(entry.uri.clone()).await?;
+                this.update(&mut cx, |this, cx| {
+                    this.delegate.context_store.update(cx, |context_store, cx| {
+                        context_store.watch_resource(
+                            context_id,
+                            context_server_manager,
+                            entry.server_id,
+                            entry.uri,
+                            cx,
+                        );
+                    })
+                })??;
+            }
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.context_picker
+            .update(cx, |this, cx| {
+                this.reset_mode();
+                cx.emit(DismissEvent);
+            })
+            .ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let resource = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix).inset(true).toggle_state(selected).child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(resource.name.clone()))
+                    .child(
+                        Label::new(resource.server_id.to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            ),
+        )
+    }
+}
+
+/// Returns the text of a resource's contents, skipping the binary ones.
+pub(crate) fn resource_text(response: types::ResourcesReadResponse) -> String {
+    let mut text = String::new();
+    for contents in response.contents {
+        if let types::ResourceContentsType::Text(contents) = contents {
+            text.push_str(&contents.text);
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use assistant_tool::ToolWorkingSet;
+    use context_server::fake_server::FakeContextServer;
+    use context_server::{ContextServerSettings, ServerConfig};
+    use gpui::{Context as _, TestAppContext, VisualTestContext};
+    use parking_lot::Mutex;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use workspace::{AppState, Workspace};
+
+    use super::*;
+
+    #[gpui::test]
+    async fn test_resource_context_picker(cx: &mut TestAppContext) {
+        let readme = Arc::new(Mutex::new("Version 1".to_string()));
+        let list_cursors = Arc::new(Mutex::new(Vec::new()));
+        let mut server = FakeContextServer::new(json!({ "resources": { "subscribe": true } }), {
+            let readme = readme.clone();
+            let list_cursors = list_cursors.clone();
+            move |method, params| match method {
+                "resources/list" => {
+                    let cursor = params["cursor"].as_str().map(ToString::to_string);
+                    list_cursors.lock().push(cursor.clone());
+                    // The resources are listed in two pages.
+                    Some(match cursor {
+                        None => json!({
+                            "resources": [{ "uri": "file:///schema", "name": "Schema" }],
+                            "nextCursor": "page-2",
+                        }),
+                        Some(_) => json!({
+                            "resources": [{ "uri": "file:///readme", "name": "Readme" }],
+                        }),
+                    })
+                }
+                "resources/read" => Some(json!({
+                    "contents": [{ "uri": params["uri"], "text": *readme.lock() }],
+                })),
+                "resources/subscribe" => Some(json!({})),
+                _ => None,
+            }
+        });
+        let http_client = server.http_client.clone();
+
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            context_server::init(cx);
+            cx.set_http_client(http_client);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<ContextServerSettings>(cx, |settings| {
+                    settings.context_servers.insert(
+                        "docs".into(),
+                        ServerConfig {
+                            url: Some("http://localhost:8080/sse".into()),
+                            ..Default::default()
+                        },
+                    );
+                });
+            });
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/root", json!({})).await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let thread_store = cx
+            .update(|cx| ThreadStore::new(project.clone(), Arc::new(ToolWorkingSet::default()), cx))
+            .await
+            .unwrap();
+        let context_store = cx.new_model(|_| ContextStore::new());
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        assert_eq!(server.receive().await["method"], "initialize");
+        assert_eq!(
+            server.receive().await["method"],
+            "notifications/initialized"
+        );
+        cx.run_until_parked();
+
+        let context_picker = cx.new_view(|cx| {
+            ContextPicker::new(
+                workspace.downgrade(),
+                Some(thread_store.downgrade()),
+                context_store.downgrade(),
+                cx,
+            )
+        });
+        let picker = cx.new_view(|cx| {
+            ResourceContextPicker::new(
+                thread_store.downgrade(),
+                context_picker.downgrade(),
+                context_store.downgrade(),
+                cx,
+            )
+        });
+        let picker = picker.read_with(cx, |picker, _| picker.picker.clone());
+        let match_names = |cx: &mut VisualTestContext| {
+            picker.read_with(cx, |picker, _| {
+                picker
+                    .delegate
+                    .matches
+                    .iter()
+                    .map(|entry| entry.name.to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // All the pages are listed when the picker opens, and typing only filters them.
+        cx.run_until_parked();
+        assert_eq!(match_names(cx), ["Schema", "Readme"]);
+        assert_eq!(*list_cursors.lock(), [None, Some("page-2".to_string())]);
+
+        picker.update(cx, |picker, cx| picker.update_matches("re".into(), cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| picker.update_matches("read".into(), cx));
+        cx.run_until_parked();
+        assert_eq!(match_names(cx), ["Readme"]);
+        assert_eq!(list_cursors.lock().len(), 2);
+
+        picker.update(cx, |picker, cx| picker.delegate.confirm(false, cx));
+        cx.run_until_parked();
+        let context_texts = |cx: &mut VisualTestContext| {
+            context_store.read_with(cx, |context_store, _| {
+                context_store
+                    .context()
+                    .iter()
+                    .map(|context| {
+                        (
+                            context.kind.clone(),
+                            context.name.to_string(),
+                            context.text.to_string(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(
+            context_texts(cx),
+            [(
+                ContextKind::Resource,
+                "Readme".to_string(),
+                "Version 1\n".to_string()
+            )]
+        );
+
+        *readme.lock() = "Version 2".into();
+        server.send(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": { "uri": "file:///readme" },
+        }));
+        cx.run_until_parked();
+        assert_eq!(
+            context_texts(cx),
+            [(
+                ContextKind::Resource,
+                "Readme".to_string(),
+                "Version 2\n".to_string()
+            )]
+        );
+    }
+}
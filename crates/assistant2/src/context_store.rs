@@ -1,10 +1,26 @@
-use gpui::SharedString;
+use std::sync::Arc;
+
+use collections::HashMap;
+use context_server::manager::{self, ContextServerManager};
+use gpui::{Model, ModelContext, SharedString, Subscription, WeakModel};
+use url::Url;
+use util::ResultExt as _;
 
 use crate::context::{Context, ContextId, ContextKind};
+use crate::context_picker::resource_text;
 
 pub struct ContextStore {
     context: Vec<Context>,
     next_context_id: ContextId,
+    resource_watches: HashMap<ContextId, ResourceWatch>,
+}
+
+/// Keeps the context of a context server's resource up to date while it's attached.
+struct ResourceWatch {
+    context_server_manager: WeakModel<ContextServerManager>,
+    server_id: Arc<str>,
+    uri: Url,
+    _subscription: Subscription,
 }
 
 impl ContextStore {
@@ -12,6 +28,7 @@ impl ContextStore {
         Self {
             context: Vec::new(),
             next_context_id: ContextId(0),
+            resource_watches: HashMap::default(),
         }
     }
 
@@ -19,11 +36,13 @@ impl ContextStore {
         &self.context
     }
 
-    pub fn drain(&mut self) -> Vec<Context> {
+    pub fn drain(&mut self, cx: &mut ModelContext<Self>) -> Vec<Context> {
+        self.unwatch_resources(cx);
         self.context.drain(..).collect()
     }
 
-    pub fn clear(&mut self) {
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) {
+        self.unwatch_resources(cx);
         self.context.clear();
     }
 
@@ -32,16 +51,94 @@ impl ContextStore {
         kind: ContextKind,
         name: impl Into<SharedString>,
         text: impl Into<SharedString>,
-    ) {
+    ) -> ContextId {
+        let id = self.next_context_id.post_inc();
         self.context.push(Context {
-            id: self.next_context_id.post_inc(),
+            id,
             name: name.into(),
             kind,
             text: text.into(),
         });
+        id
     }
 
-    pub fn remove_context(&mut self, id: &ContextId) {
+    pub fn remove_context(&mut self, id: &ContextId, cx: &mut ModelContext<Self>) {
         self.context.retain(|context| context.id != *id);
+        if let Some(watch) = self.resource_watches.remove(id) {
+            Self::unwatch_resource(watch, cx);
+        }
+    }
+
+    /// Re-reads the resource of the given context whenever the context server reports that it
+    /// changed, until the context is removed.
+    pub fn watch_resource(
+        &mut self,
+        id: ContextId,
+        context_server_manager: Model<ContextServerManager>,
+        server_id: Arc<str>,
+        uri: Url,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let subscription = cx.subscribe(&context_server_manager, {
+            let watched_server_id = server_id.clone();
+            let watched_uri = uri.clone();
+            move |_this, context_server_manager, event, cx| {
+                let manager::Event::ResourceUpdated { server_id, uri } = event else {
+                    return;
+                };
+                if *server_id != watched_server_id || *uri != watched_uri {
+                    return;
+                }
+                let Some(protocol) = context_server_manager
+                    .read(cx)
+                    .get_server(server_id)
+                    .and_then(|server| server.client())
+                else {
+                    return;
+                };
+                let uri = uri.clone();
+                cx.spawn(|this, mut cx| async move {
+                    let response = protocol.read_resource(uri).await?;
+                    this.update(&mut cx, |this, cx| {
+                        if let Some(context) =
+                            this.context.iter_mut().find(|context| context.id == id)
+                        {
+                            context.text = resource_text(response).into();
+                            cx.notify();
+                        }
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+        });
+
+        self.resource_watches.insert(
+            id,
+            ResourceWatch {
+                context_server_manager: context_server_manager.downgrade(),
+                server_id,
+                uri,
+                _subscription: subscription,
+            },
+        );
+    }
+
+    fn unwatch_resources(&mut self, cx: &mut ModelContext<Self>) {
+        for (_, watch) in self.resource_watches.drain() {
+            Self::unwatch_resource(watch, cx);
+        }
+    }
+
+    fn unwatch_resource(watch: ResourceWatch, cx: &mut ModelContext<Self>) {
+        let Some(protocol) = watch
+            .context_server_manager
+            .upgrade()
+            .and_then(|manager| manager.read(cx).get_server(&watch.server_id))
+            .and_then(|server| server.client())
+        else {
+            return;
+        };
+        cx.spawn(|_, _| async move { protocol.unsubscribe_resource(watch.uri).await.log_err() })
+            .detach();
     }
 }
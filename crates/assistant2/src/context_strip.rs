@@ -80,8 +80,8 @@ impl Render for ContextStrip {
                     let context = context.clone();
                     let context_store = self.context_store.clone();
                     Rc::new(cx.listener(move |_this, _event, cx| {
-                        context_store.update(cx, |this, _cx| {
-                            this.remove_context(&context.id);
+                        context_store.update(cx, |this, cx| {
+                            this.remove_context(&context.id, cx);
                         });
                         cx.notify();
                     }))
@@ -95,7 +95,7 @@ impl Render for ContextStrip {
                         .on_click({
                             let context_store = self.context_store.clone();
                             cx.listener(move |_this, _event, cx| {
-                                context_store.update(cx, |this, _cx| this.clear());
+                                context_store.update(cx, |this, cx| this.clear(cx));
                                 cx.notify();
                             })
                         }),
@@ -2770,6 +2770,7 @@ impl CodegenAlternative {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
             messages: vec![request_message],
         })
     }
@@ -3369,6 +3370,10 @@ struct AssistantCodeActionProvider {
 }
 
 impl CodeActionProvider for AssistantCodeActionProvider {
+    fn id(&self) -> &'static str {
+        "assistant2"
+    }
+
     fn code_actions(
         &self,
         buffer: &Model<Buffer>,
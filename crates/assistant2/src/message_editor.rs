@@ -118,7 +118,7 @@ impl MessageEditor {
             editor.clear(cx);
             text
         });
-        let context = self.context_store.update(cx, |this, _cx| this.drain());
+        let context = self.context_store.update(cx, |this, cx| this.drain(cx));
 
         self.thread.update(cx, |thread, cx| {
             thread.insert_user_message(user_message, context, cx);
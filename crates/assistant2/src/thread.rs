@@ -137,6 +137,22 @@ impl Thread {
         self.pending_tool_uses_by_id.values().collect()
     }
 
+    pub fn pending_tool_use(&self, id: &LanguageModelToolUseId) -> Option<&PendingToolUse> {
+        self.pending_tool_uses_by_id.get(id)
+    }
+
+    /// Marks the tool use as waiting for the user to approve it.
+    pub fn request_tool_use_confirmation(
+        &mut self,
+        tool_use_id: &LanguageModelToolUseId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(tool_use) = self.pending_tool_uses_by_id.get_mut(tool_use_id) {
+            tool_use.status = PendingToolUseStatus::NeedsConfirmation;
+            cx.notify();
+        }
+    }
+
     pub fn insert_user_message(
         &mut self,
         text: impl Into<String>,
@@ -174,6 +190,7 @@ impl Thread {
             tools: Vec::new(),
             stop: Vec::new(),
             temperature: None,
+            max_tokens: None,
         };
 
         for message in &self.messages {
@@ -252,28 +269,7 @@ impl Thread {
                                 }
                             }
                             LanguageModelCompletionEvent::ToolUse(tool_use) => {
-                                if let Some(last_assistant_message) = thread
-                                    .messages
-                                    .iter()
-                                    .rfind(|message| message.role == Role::Assistant)
-                                {
-                                    thread
-                                        .tool_uses_by_message
-                                        .entry(last_assistant_message.id)
-                                        .or_default()
-                                        .push(tool_use.clone());
-
-                                    thread.pending_tool_uses_by_id.insert(
-                                        tool_use.id.clone(),
-                                        PendingToolUse {
-                                            assistant_message_id: last_assistant_message.id,
-                                            id: tool_use.id,
-                                            name: tool_use.name,
-                                            input: tool_use.input,
-                                            status: PendingToolUseStatus::Idle,
-                                        },
-                                    );
-                                }
+                                thread.insert_tool_use(tool_use);
                             }
                         }
 
@@ -388,6 +384,34 @@ impl Thread {
         });
     }
 
+    /// Records a tool use requested by the last Assistant message, which stays pending until its
+    /// output is inserted.
+    pub fn insert_tool_use(&mut self, tool_use: LanguageModelToolUse) {
+        let Some(last_assistant_message) = self
+            .messages
+            .iter()
+            .rfind(|message| message.role == Role::Assistant)
+        else {
+            return;
+        };
+
+        self.tool_uses_by_message
+            .entry(last_assistant_message.id)
+            .or_default()
+            .push(tool_use.clone());
+
+        self.pending_tool_uses_by_id.insert(
+            tool_use.id.clone(),
+            PendingToolUse {
+                assistant_message_id: last_assistant_message.id,
+                id: tool_use.id,
+                name: tool_use.name,
+                input: tool_use.input,
+                status: PendingToolUseStatus::Idle,
+            },
+        );
+    }
+
     pub fn insert_tool_output(
         &mut self,
         assistant_message_id: MessageId,
@@ -489,6 +513,7 @@ pub struct PendingToolUse {
 #[derive(Debug, Clone)]
 pub enum PendingToolUseStatus {
     Idle,
+    NeedsConfirmation,
     Running { _task: Shared<Task<()>> },
     Error(#[allow(unused)] String),
 }
@@ -497,4 +522,8 @@ impl PendingToolUseStatus {
     pub fn is_idle(&self) -> bool {
         matches!(self, PendingToolUseStatus::Idle)
     }
+
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, PendingToolUseStatus::NeedsConfirmation)
+    }
 }
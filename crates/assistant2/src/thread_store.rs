@@ -52,6 +52,10 @@ impl ThreadStore {
         })
     }
 
+    pub fn context_server_manager(&self) -> Model<ContextServerManager> {
+        self.context_server_manager.clone()
+    }
+
     pub fn threads(&self, cx: &ModelContext<Self>) -> Vec<Model<Thread>> {
         let mut threads = self
             .threads
@@ -147,6 +151,7 @@ impl ThreadStore {
                     tool_working_set.remove(&tool_ids);
                 }
             }
+            context_server::manager::Event::ResourceUpdated { .. } => {}
         }
     }
 }
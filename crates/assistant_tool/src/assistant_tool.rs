@@ -27,6 +27,22 @@ pub trait Tool: 'static + Send + Sync {
         serde_json::Value::Object(serde_json::Map::default())
     }
 
+    /// Returns whether the user has to approve each use of the tool before it runs.
+    fn needs_confirmation(&self) -> bool {
+        false
+    }
+
+    /// Returns a Markdown description of what running the tool with the provided input will do,
+    /// which is shown to the user when asking them to approve it.
+    fn confirmation_prompt(
+        &self,
+        _input: &serde_json::Value,
+        _workspace: WeakView<Workspace>,
+        _cx: &mut WindowContext,
+    ) -> Task<String> {
+        Task::ready(format!("Run the `{}` tool?", self.name()))
+    }
+
     /// Runs the tool with the provided input.
     fn run(
         self: Arc<Self>,
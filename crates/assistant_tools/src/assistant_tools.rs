@@ -1,13 +1,50 @@
+mod definition_tool;
+mod diagnostics_tool;
+mod edit_file_tool;
+mod hover_tool;
+mod list_directory_tool;
 mod now_tool;
+mod read_file_tool;
+mod references_tool;
+mod search_project_tool;
+mod symbol_position;
+#[cfg(test)]
+mod test;
 
+use anyhow::{anyhow, Result};
 use assistant_tool::ToolRegistry;
-use gpui::AppContext;
+use gpui::{AppContext, Model, WeakView};
+use project::Project;
+use workspace::Workspace;
 
+use crate::definition_tool::DefinitionTool;
+use crate::diagnostics_tool::DiagnosticsTool;
+use crate::edit_file_tool::EditFileTool;
+use crate::hover_tool::HoverTool;
+use crate::list_directory_tool::ListDirectoryTool;
 use crate::now_tool::NowTool;
+use crate::read_file_tool::ReadFileTool;
+use crate::references_tool::ReferencesTool;
+use crate::search_project_tool::SearchProjectTool;
 
 pub fn init(cx: &mut AppContext) {
     assistant_tool::init(cx);
 
     let registry = ToolRegistry::global(cx);
     registry.register_tool(NowTool);
+    registry.register_tool(ListDirectoryTool);
+    registry.register_tool(ReadFileTool);
+    registry.register_tool(SearchProjectTool);
+    registry.register_tool(EditFileTool);
+    registry.register_tool(DiagnosticsTool);
+    registry.register_tool(DefinitionTool);
+    registry.register_tool(ReferencesTool);
+    registry.register_tool(HoverTool);
+}
+
+fn workspace_project(workspace: &WeakView<Workspace>, cx: &AppContext) -> Result<Model<Project>> {
+    let workspace = workspace
+        .upgrade()
+        .ok_or_else(|| anyhow!("workspace was dropped"))?;
+    Ok(workspace.read(cx).project().clone())
 }
@@ -0,0 +1,137 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::{Point, ToPoint as _};
+
+use crate::symbol_position::{describe_location, SymbolPositionInput};
+use crate::workspace_project;
+
+/// The maximum number of lines of a definition included in the output.
+const MAX_DEFINITION_LINES: u32 = 30;
+
+pub struct DefinitionTool;
+
+impl Tool for DefinitionTool {
+    fn name(&self) -> String {
+        "definition".into()
+    }
+
+    fn description(&self) -> String {
+        "Asks the language servers for the definition of a symbol, returning its location and source code. Use it to look up real signatures instead of guessing them.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SymbolPositionInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SymbolPositionInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match workspace_project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let symbol = input.symbol.clone();
+        let position = input.resolve(&project, cx);
+
+        cx.spawn(|mut cx| async move {
+            let (buffer, position, _lsp_handle) = position.await?;
+            let definitions = project
+                .update(&mut cx, |project, cx| {
+                    project.definition(&buffer, position, cx)
+                })?
+                .await?;
+            if definitions.is_empty() {
+                return Ok(format!("No definition of `{symbol}` was found."));
+            }
+
+            let mut output = String::new();
+            for definition in definitions {
+                let target = definition.target;
+                target.buffer.read_with(&cx, |buffer, cx| {
+                    let start_row = target.range.start.to_point(buffer).row;
+                    let end_row = target
+                        .range
+                        .end
+                        .to_point(buffer)
+                        .row
+                        .min(start_row + MAX_DEFINITION_LINES - 1);
+                    let source = buffer
+                        .text_for_range(
+                            Point::new(start_row, 0)..Point::new(end_row, buffer.line_len(end_row)),
+                        )
+                        .collect::<String>();
+                    writeln!(
+                        output,
+                        "{}\n```\n{source}\n```",
+                        describe_location(buffer, &target.range, cx)
+                    )
+                })??;
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, start_fake_rust_server, test_workspace};
+
+    #[gpui::test]
+    async fn test_definition(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn main() {\n    helper();\n}\n",
+                "b.rs": "pub fn helper() {\n    todo!()\n}\n",
+            }),
+            cx,
+        )
+        .await;
+        let (fake_server, _handle) = start_fake_rust_server(
+            &workspace,
+            "/root/a.rs",
+            lsp::ServerCapabilities {
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+            let position = params.text_document_position_params.position;
+            Ok((position == lsp::Position::new(1, 4)).then(|| {
+                lsp::GotoDefinitionResponse::Scalar(lsp::Location::new(
+                    lsp::Url::from_file_path("/root/b.rs").unwrap(),
+                    lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(2, 1)),
+                ))
+            }))
+        });
+
+        let input = json!({ "path": "root/a.rs", "line": 2, "symbol": "helper" });
+        let output = run_tool(DefinitionTool, input, &workspace, cx).await;
+        assert_eq!(
+            output.unwrap(),
+            "root/b.rs:1: pub fn helper() {\n```\npub fn helper() {\n    todo!()\n}\n```\n"
+        );
+
+        let input = json!({ "path": "root/a.rs", "line": 1, "symbol": "main" });
+        let output = run_tool(DefinitionTool, input, &workspace, cx).await;
+        assert_eq!(output.unwrap(), "No definition of `main` was found.");
+    }
+}
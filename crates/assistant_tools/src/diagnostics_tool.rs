@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::{DiagnosticSeverity, Point};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::workspace_project;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DiagnosticsToolInput {
+    /// The path of the file to get the diagnostics of, starting with the name of one of the project's root directories.
+    ///
+    /// When omitted, the number of errors and warnings of every file that has any is returned instead.
+    #[serde(default)]
+    path: Option<PathBuf>,
+}
+
+pub struct DiagnosticsTool;
+
+impl Tool for DiagnosticsTool {
+    fn name(&self) -> String {
+        "diagnostics".into()
+    }
+
+    fn description(&self) -> String {
+        "Returns the errors and warnings that the language servers currently report for a file of the project, or a summary of them for the whole project. Use it to check edits for mistakes.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(DiagnosticsToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: DiagnosticsToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match workspace_project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        let Some(path) = input.path else {
+            let project = project.read(cx);
+            let mut counts_by_path = BTreeMap::<PathBuf, (usize, usize)>::new();
+            for (project_path, _, summary) in project.diagnostic_summaries(false, cx) {
+                let Some(worktree) = project.worktree_for_id(project_path.worktree_id, cx) else {
+                    continue;
+                };
+                let path = PathBuf::from(worktree.read(cx).root_name()).join(&project_path.path);
+                let counts = counts_by_path.entry(path).or_default();
+                counts.0 += summary.error_count;
+                counts.1 += summary.warning_count;
+            }
+            let mut output = String::new();
+            for (path, (error_count, warning_count)) in counts_by_path {
+                writeln!(
+                    output,
+                    "{}: {error_count} error(s), {warning_count} warning(s)",
+                    path.display()
+                )
+                .ok();
+            }
+            if output.is_empty() {
+                output = "The project has no errors or warnings.".into();
+            }
+            return Task::ready(Ok(output));
+        };
+
+        let Some(project_path) = project.read(cx).find_project_path(&path, cx) else {
+            return Task::ready(Err(anyhow!("{} is not in the project", path.display())));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            buffer.read_with(&cx, |buffer, _| {
+                let snapshot = buffer.snapshot();
+                let mut output = String::new();
+                for entry in snapshot.diagnostics_in_range::<_, Point>(0..snapshot.len(), false) {
+                    if !entry.diagnostic.is_primary {
+                        continue;
+                    }
+                    let severity = match entry.diagnostic.severity {
+                        DiagnosticSeverity::ERROR => "error",
+                        DiagnosticSeverity::WARNING => "warning",
+                        DiagnosticSeverity::INFORMATION => "info",
+                        _ => "hint",
+                    };
+                    writeln!(
+                        output,
+                        "{}:{}:{}: {severity}: {}",
+                        path.display(),
+                        entry.range.start.row + 1,
+                        entry.range.start.column + 1,
+                        entry.diagnostic.message
+                    )?;
+                }
+                if output.is_empty() {
+                    output = format!("{} has no diagnostics.", path.display());
+                }
+                Ok(output)
+            })?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, start_fake_rust_server, test_workspace};
+
+    #[gpui::test]
+    async fn test_diagnostics(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn main() {\n    let x = y;\n}\n",
+                "b.rs": "fn unused() {}\n",
+                "c.rs": "",
+            }),
+            cx,
+        )
+        .await;
+
+        let output = run_tool(DiagnosticsTool, json!({}), &workspace, cx).await;
+        assert_eq!(output.unwrap(), "The project has no errors or warnings.");
+
+        let (fake_server, _handle) = start_fake_rust_server(
+            &workspace,
+            "/root/a.rs",
+            lsp::ServerCapabilities::default(),
+            cx,
+        )
+        .await;
+        fake_server.notify::<lsp::notification::PublishDiagnostics>(
+            lsp::PublishDiagnosticsParams {
+                uri: lsp::Url::from_file_path("/root/a.rs").unwrap(),
+                version: None,
+                diagnostics: vec![
+                    lsp::Diagnostic {
+                        range: lsp::Range::new(
+                            lsp::Position::new(1, 12),
+                            lsp::Position::new(1, 13),
+                        ),
+                        severity: Some(lsp::DiagnosticSeverity::ERROR),
+                        message: "cannot find value `y` in this scope".into(),
+                        ..Default::default()
+                    },
+                    lsp::Diagnostic {
+                        range: lsp::Range::new(lsp::Position::new(1, 8), lsp::Position::new(1, 9)),
+                        severity: Some(lsp::DiagnosticSeverity::WARNING),
+                        message: "unused variable: `x`".into(),
+                        ..Default::default()
+                    },
+                ],
+            },
+        );
+        fake_server.notify::<lsp::notification::PublishDiagnostics>(
+            lsp::PublishDiagnosticsParams {
+                uri: lsp::Url::from_file_path("/root/b.rs").unwrap(),
+                version: None,
+                diagnostics: vec![lsp::Diagnostic {
+                    range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 9)),
+                    severity: Some(lsp::DiagnosticSeverity::WARNING),
+                    message: "function `unused` is never used".into(),
+                    ..Default::default()
+                }],
+            },
+        );
+        cx.run_until_parked();
+
+        let output = run_tool(DiagnosticsTool, json!({}), &workspace, cx).await;
+        assert_eq!(
+            output.unwrap(),
+            "root/a.rs: 1 error(s), 1 warning(s)\nroot/b.rs: 0 error(s), 1 warning(s)\n"
+        );
+
+        let output = run_tool(
+            DiagnosticsTool,
+            json!({ "path": "root/a.rs" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output.unwrap(),
+            "root/a.rs:2:9: warning: unused variable: `x`\n\
+             root/a.rs:2:13: error: cannot find value `y` in this scope\n"
+        );
+
+        let output = run_tool(
+            DiagnosticsTool,
+            json!({ "path": "root/b.rs" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output.unwrap(),
+            "root/b.rs:1:4: warning: function `unused` is never used\n"
+        );
+
+        let output = run_tool(
+            DiagnosticsTool,
+            json!({ "path": "root/c.rs" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "root/c.rs has no diagnostics.");
+    }
+}
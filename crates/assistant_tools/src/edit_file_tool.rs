@@ -0,0 +1,235 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tool::Tool;
+use gpui::{Model, Task, WeakView, WindowContext};
+use language::Buffer;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+
+use crate::workspace_project;
+
+/// The number of unchanged lines shown around the edit when asking the user to approve it.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EditFileToolInput {
+    /// The path of the file to edit, starting with the name of one of the project's root directories.
+    path: PathBuf,
+    /// The text to replace, which has to occur exactly once in the file.
+    /// Include enough surrounding lines to make it unique.
+    old_text: String,
+    /// The text to replace `old_text` with.
+    new_text: String,
+}
+
+pub struct EditFileTool;
+
+impl Tool for EditFileTool {
+    fn name(&self) -> String {
+        "edit_file".into()
+    }
+
+    fn description(&self) -> String {
+        "Edits a file of the project by replacing text in it. The user reviews each edit before it's applied, so make one edit per use of the tool. The edited file is left unsaved so that the user can inspect or undo the edit.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(EditFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn needs_confirmation(&self) -> bool {
+        true
+    }
+
+    fn confirmation_prompt(
+        &self,
+        input: &serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<String> {
+        let Ok(input) = serde_json::from_value::<EditFileToolInput>(input.clone()) else {
+            return Task::ready("Apply the proposed edit?".into());
+        };
+        let open_buffer = match open_buffer(&input.path, &workspace, cx) {
+            Ok(open_buffer) => open_buffer,
+            Err(err) => return Task::ready(unpreviewable_edit_prompt(&input.path, err)),
+        };
+
+        cx.spawn(|cx| async move {
+            let diff = async {
+                let buffer = open_buffer.await?;
+                let old_text = buffer.read_with(&cx, |buffer, _| buffer.text())?;
+                let range = unique_occurrence(&old_text, &input.old_text)?;
+                let mut new_text = old_text.clone();
+                new_text.replace_range(range, &input.new_text);
+                anyhow::Ok(
+                    TextDiff::from_lines(&old_text, &new_text)
+                        .unified_diff()
+                        .context_radius(CONTEXT_LINES)
+                        .to_string(),
+                )
+            };
+            match diff.await {
+                Ok(diff) => format!("`{}`\n```diff\n{diff}```", input.path.display()),
+                Err(err) => unpreviewable_edit_prompt(&input.path, err),
+            }
+        })
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: EditFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let open_buffer = match open_buffer(&input.path, &workspace, cx) {
+            Ok(open_buffer) => open_buffer,
+            Err(err) => return Task::ready(Err(err)),
+        };
+
+        cx.spawn(|mut cx| async move {
+            let buffer = open_buffer
+                .await
+                .with_context(|| format!("failed to open {}", input.path.display()))?;
+            buffer.update(&mut cx, |buffer, cx| {
+                let range = unique_occurrence(&buffer.text(), &input.old_text)
+                    .with_context(|| format!("failed to edit {}", input.path.display()))?;
+                buffer.edit([(range, input.new_text)], None, cx);
+                anyhow::Ok(())
+            })??;
+            Ok(format!("Edited {}.", input.path.display()))
+        })
+    }
+}
+
+fn open_buffer(
+    path: &Path,
+    workspace: &WeakView<workspace::Workspace>,
+    cx: &mut WindowContext,
+) -> Result<Task<Result<Model<Buffer>>>> {
+    let project = workspace_project(workspace, cx)?;
+    let project_path = project
+        .read(cx)
+        .find_project_path(path, cx)
+        .ok_or_else(|| anyhow!("{} is not in the project", path.display()))?;
+    Ok(project.update(cx, |project, cx| project.open_buffer(project_path, cx)))
+}
+
+fn unpreviewable_edit_prompt(path: &Path, err: anyhow::Error) -> String {
+    format!(
+        "Edit `{}`? The edit can't be previewed: {err}.",
+        path.display()
+    )
+}
+
+/// Returns the range of the only occurrence of `old_text` in `text`.
+fn unique_occurrence(text: &str, old_text: &str) -> Result<Range<usize>> {
+    if old_text.is_empty() {
+        return Err(anyhow!("the text to replace is empty"));
+    }
+    let mut occurrences = text.match_indices(old_text);
+    let (start, _) = occurrences
+        .next()
+        .ok_or_else(|| anyhow!("the text to replace was not found"))?;
+    if occurrences.next().is_some() {
+        return Err(anyhow!(
+            "the text to replace occurs more than once, include more of its surrounding lines"
+        ));
+    }
+    Ok(start..start + old_text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::read_file_tool::ReadFileTool;
+    use crate::test::{init_test, run_tool, test_workspace};
+
+    #[test]
+    fn test_unique_occurrence() {
+        let text = "fn one() {}\nfn two() {}\nfn two() {}\n";
+        assert_eq!(unique_occurrence(text, "fn one() {}").unwrap(), 0..11);
+        assert!(unique_occurrence(text, "fn two() {}").is_err());
+        assert!(unique_occurrence(text, "fn three() {}").is_err());
+        assert!(unique_occurrence(text, "").is_err());
+    }
+
+    #[gpui::test]
+    async fn test_edit_file(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn one() {}\nfn two() {}\n",
+            }),
+            cx,
+        )
+        .await;
+
+        let output = run_tool(
+            EditFileTool,
+            json!({ "path": "root/a.rs", "old_text": "fn two() {}", "new_text": "fn second() {}" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "Edited root/a.rs.");
+        let a = run_tool(ReadFileTool, json!({ "path": "root/a.rs" }), &workspace, cx).await;
+        assert_eq!(a.unwrap(), "fn one() {}\nfn second() {}\n");
+
+        let output = run_tool(
+            EditFileTool,
+            json!({ "path": "root/a.rs", "old_text": "fn two", "new_text": "fn three" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap_err().to_string(), "failed to edit root/a.rs");
+        let a = run_tool(ReadFileTool, json!({ "path": "root/a.rs" }), &workspace, cx).await;
+        assert_eq!(a.unwrap(), "fn one() {}\nfn second() {}\n");
+    }
+
+    #[gpui::test]
+    async fn test_edit_file_confirmation_prompt(cx: &mut TestAppContext) {
+        init_test(cx);
+        let text = (1..=9)
+            .map(|line| format!("line {line}\n"))
+            .collect::<String>();
+        let (workspace, cx) = test_workspace(json!({ "a.txt": text }), cx).await;
+        let prompt = |input: serde_json::Value, cx: &mut gpui::VisualTestContext| {
+            cx.update(|cx| EditFileTool.confirmation_prompt(&input, workspace.downgrade(), cx))
+        };
+
+        // The diff shows where the edit is, along with the lines around it.
+        let output = prompt(
+            json!({ "path": "root/a.txt", "old_text": "line 5", "new_text": "line five" }),
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output,
+            "`root/a.txt`\n```diff\n@@ -2,7 +2,7 @@\n line 2\n line 3\n line 4\n-line 5\n+line five\n line 6\n line 7\n line 8\n```"
+        );
+
+        let output = prompt(
+            json!({ "path": "root/a.txt", "old_text": "line", "new_text": "row" }),
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output,
+            "Edit `root/a.txt`? The edit can't be previewed: the text to replace occurs more than once, include more of its surrounding lines."
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+
+use crate::symbol_position::SymbolPositionInput;
+use crate::workspace_project;
+
+pub struct HoverTool;
+
+impl Tool for HoverTool {
+    fn name(&self) -> String {
+        "hover".into()
+    }
+
+    fn description(&self) -> String {
+        "Asks the language servers for the hover information of a symbol, which usually contains its type or signature and its documentation.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SymbolPositionInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SymbolPositionInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match workspace_project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let symbol = input.symbol.clone();
+        let position = input.resolve(&project, cx);
+
+        cx.spawn(|mut cx| async move {
+            let (buffer, position, _lsp_handle) = position.await?;
+            let hovers = project
+                .update(&mut cx, |project, cx| project.hover(&buffer, position, cx))?
+                .await;
+            let text = hovers
+                .iter()
+                .flat_map(|hover| &hover.contents)
+                .map(|block| block.text.trim())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            if text.is_empty() {
+                Ok(format!("No hover information is available for `{symbol}`."))
+            } else {
+                Ok(text)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, start_fake_rust_server, test_workspace};
+
+    #[gpui::test]
+    async fn test_hover(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn main() {\n    let count = 1;\n}\n",
+            }),
+            cx,
+        )
+        .await;
+        let (fake_server, _handle) = start_fake_rust_server(
+            &workspace,
+            "/root/a.rs",
+            lsp::ServerCapabilities {
+                hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        fake_server.handle_request::<lsp::request::HoverRequest, _, _>(|params, _| async move {
+            let position = params.text_document_position_params.position;
+            Ok((position == lsp::Position::new(1, 8)).then(|| lsp::Hover {
+                contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                    kind: lsp::MarkupKind::Markdown,
+                    value: "```rust\nlet count: i32\n```\n".into(),
+                }),
+                range: None,
+            }))
+        });
+
+        let input = json!({ "path": "root/a.rs", "line": 2, "symbol": "count" });
+        let output = run_tool(HoverTool, input, &workspace, cx).await;
+        assert_eq!(output.unwrap(), "```rust\nlet count: i32\n```");
+
+        let input = json!({ "path": "root/a.rs", "line": 1, "symbol": "main" });
+        let output = run_tool(HoverTool, input, &workspace, cx).await;
+        assert_eq!(
+            output.unwrap(),
+            "No hover information is available for `main`."
+        );
+    }
+}
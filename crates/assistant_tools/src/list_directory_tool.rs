@@ -0,0 +1,159 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::workspace_project;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListDirectoryToolInput {
+    /// The path of the directory to list, starting with the name of one of the project's root directories.
+    ///
+    /// For example, to list the `src` directory of a project whose root directory is `zed`, use `zed/src`.
+    /// To list a root directory itself, use its name on its own.
+    path: PathBuf,
+}
+
+pub struct ListDirectoryTool;
+
+impl Tool for ListDirectoryTool {
+    fn name(&self) -> String {
+        "list_directory".into()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files and directories inside of a directory of the project. Directories are listed with a trailing slash.".into()
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ListDirectoryToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ListDirectoryToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        Task::ready(workspace_project(&workspace, cx).and_then(|project| {
+            let project = project.read(cx);
+            let project_path = project
+                .find_project_path(&input.path, cx)
+                .ok_or_else(|| anyhow!("{} is not in the project", input.path.display()))?;
+            let worktree = project
+                .worktree_for_id(project_path.worktree_id, cx)
+                .ok_or_else(|| anyhow!("{} is not in the project", input.path.display()))?;
+            let snapshot = worktree.read(cx).snapshot();
+            let entry = snapshot
+                .entry_for_path(&project_path.path)
+                .ok_or_else(|| anyhow!("{} does not exist", input.path.display()))?;
+            if !entry.is_dir() {
+                return Err(anyhow!("{} is not a directory", input.path.display()));
+            }
+
+            let root_name = Path::new(snapshot.root_name());
+            let mut output = String::new();
+            for child in snapshot.child_entries(&project_path.path) {
+                let suffix = if child.is_dir() { "/" } else { "" };
+                writeln!(output, "{}{suffix}", root_name.join(&child.path).display())?;
+            }
+            if output.is_empty() {
+                output = format!("{} is empty.", input.path.display());
+            }
+            Ok(output)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, test_workspace};
+
+    #[gpui::test]
+    async fn test_list_directory(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "README.md": "",
+                "empty": {},
+                "src": {
+                    "main.rs": "",
+                    "util": { "mod.rs": "" },
+                },
+            }),
+            cx,
+        )
+        .await;
+
+        let output = run_tool(ListDirectoryTool, json!({ "path": "root" }), &workspace, cx).await;
+        assert_eq!(output.unwrap(), "root/README.md\nroot/empty/\nroot/src/\n");
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/src" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "root/src/main.rs\nroot/src/util/\n");
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/empty" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "root/empty is empty.");
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/README.md" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "root/README.md is not a directory"
+        );
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "root/missing" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "root/missing does not exist"
+        );
+
+        let output = run_tool(
+            ListDirectoryTool,
+            json!({ "path": "other" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "other is not in the project"
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::Point;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::workspace_project;
+
+/// The maximum length of the returned text, in bytes. Longer reads are cut at the end of the
+/// last line that fits.
+const MAX_OUTPUT_LEN: usize = 64 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadFileToolInput {
+    /// The path of the file to read, starting with the name of one of the project's root directories.
+    ///
+    /// For example, to read `src/main.rs` in a project whose root directory is `zed`, use `zed/src/main.rs`.
+    path: PathBuf,
+    /// The 1-based line to start reading at. Defaults to the first line of the file.
+    #[serde(default)]
+    start_line: Option<u32>,
+    /// The 1-based line to stop reading at, inclusive. Defaults to the last line of the file.
+    #[serde(default)]
+    end_line: Option<u32>,
+}
+
+pub struct ReadFileTool;
+
+impl Tool for ReadFileTool {
+    fn name(&self) -> String {
+        "read_file".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Reads the contents of a file of the project, or of a range of its lines. Unsaved changes to the file are included. At most {} KiB are returned at once, so read large files a range at a time. Private files, such as `.env` files, can't be read.", MAX_OUTPUT_LEN / 1024)
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(ReadFileToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: ReadFileToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match workspace_project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let Some(project_path) = project.read(cx).find_project_path(&input.path, cx) else {
+            return Task::ready(Err(anyhow!(
+                "{} is not in the project",
+                input.path.display()
+            )));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+
+        cx.spawn(|cx| async move {
+            let buffer = open_buffer.await?;
+            buffer.read_with(&cx, |buffer, _| {
+                if buffer.file().map_or(false, |file| file.is_private()) {
+                    return Err(anyhow!(
+                        "{} is a private file, which can't be read",
+                        input.path.display()
+                    ));
+                }
+                let max_row = buffer.max_point().row;
+                let start_row = input
+                    .start_line
+                    .map_or(0, |line| line.saturating_sub(1))
+                    .min(max_row);
+                let end_row = input
+                    .end_line
+                    .map_or(max_row, |line| line.saturating_sub(1))
+                    .min(max_row);
+                if start_row > end_row {
+                    return Err(anyhow!("the start line is after the end line"));
+                }
+                let range = Point::new(start_row, 0)..Point::new(end_row, buffer.line_len(end_row));
+                let mut text = buffer.text_for_range(range).collect::<String>();
+                if text.len() > MAX_OUTPUT_LEN {
+                    let mut end = MAX_OUTPUT_LEN;
+                    while !text.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    end = text[..end].rfind('\n').map_or(end, |newline| newline + 1);
+                    let last_line = start_row + text[..end].matches('\n').count() as u32;
+                    text.truncate(end);
+                    text.push_str(&format!(
+                        "\n[The output was truncated after line {last_line}. Read the rest with a start_line of {}.]",
+                        last_line + 1
+                    ));
+                }
+                Ok(text)
+            })?
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, test_workspace};
+
+    #[gpui::test]
+    async fn test_read_file(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "lines.txt": "one\ntwo\nthree\nfour\n",
+            }),
+            cx,
+        )
+        .await;
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/lines.txt" }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "one\ntwo\nthree\nfour\n");
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/lines.txt", "start_line": 2, "end_line": 3 }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "two\nthree");
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/lines.txt", "start_line": 3, "end_line": 100 }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(output.unwrap(), "three\nfour\n");
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/lines.txt", "start_line": 3, "end_line": 2 }),
+            &workspace,
+            cx,
+        )
+        .await;
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "the start line is after the end line"
+        );
+
+        let output = run_tool(ReadFileTool, json!({ "path": "other.txt" }), &workspace, cx).await;
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "other.txt is not in the project"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_read_private_and_large_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                ".env": "SECRET=1\n",
+                "large.txt": "0123456789\n".repeat(10_000),
+            }),
+            cx,
+        )
+        .await;
+
+        let output = run_tool(ReadFileTool, json!({ "path": "root/.env" }), &workspace, cx).await;
+        assert_eq!(
+            output.unwrap_err().to_string(),
+            "root/.env is a private file, which can't be read"
+        );
+
+        let lines_per_page = MAX_OUTPUT_LEN / 11;
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/large.txt" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        let (text, note) = output.rsplit_once("\n\n").unwrap();
+        assert_eq!(text.len(), lines_per_page * 11 - 1);
+        assert_eq!(
+            note,
+            format!(
+                "[The output was truncated after line {lines_per_page}. Read the rest with a start_line of {}.]",
+                lines_per_page + 1
+            )
+        );
+
+        let output = run_tool(
+            ReadFileTool,
+            json!({ "path": "root/large.txt", "start_line": 9_999 }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "0123456789\n0123456789\n");
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+
+use crate::symbol_position::{describe_location, SymbolPositionInput};
+use crate::workspace_project;
+
+/// The maximum number of references included in the output.
+const MAX_REFERENCES: usize = 100;
+
+pub struct ReferencesTool;
+
+impl Tool for ReferencesTool {
+    fn name(&self) -> String {
+        "references".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Asks the language servers for the references to a symbol, returning the path, 1-based line and text of each of them. At most {MAX_REFERENCES} references are returned.")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SymbolPositionInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SymbolPositionInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match workspace_project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let symbol = input.symbol.clone();
+        let position = input.resolve(&project, cx);
+
+        cx.spawn(|mut cx| async move {
+            let (buffer, position, _lsp_handle) = position.await?;
+            let references = project
+                .update(&mut cx, |project, cx| {
+                    project.references(&buffer, position, cx)
+                })?
+                .await?;
+            if references.is_empty() {
+                return Ok(format!("No references to `{symbol}` were found."));
+            }
+
+            let mut output = String::new();
+            for reference in references.iter().take(MAX_REFERENCES) {
+                reference.buffer.read_with(&cx, |buffer, cx| {
+                    writeln!(
+                        output,
+                        "{}",
+                        describe_location(buffer, &reference.range, cx)
+                    )
+                })??;
+            }
+            if references.len() > MAX_REFERENCES {
+                writeln!(
+                    output,
+                    "\nOnly {MAX_REFERENCES} of the {} references are shown.",
+                    references.len()
+                )?;
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, start_fake_rust_server, test_workspace};
+
+    #[gpui::test]
+    async fn test_references(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn main() {\n    helper();\n    helper();\n}\n",
+                "b.rs": "pub fn helper() {}\n",
+            }),
+            cx,
+        )
+        .await;
+        let (fake_server, _handle) = start_fake_rust_server(
+            &workspace,
+            "/root/b.rs",
+            lsp::ServerCapabilities {
+                references_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        fake_server.handle_request::<lsp::request::References, _, _>(|params, _| async move {
+            let position = params.text_document_position.position;
+            if position != lsp::Position::new(0, 7) {
+                return Ok(Some(Vec::new()));
+            }
+            let a = lsp::Url::from_file_path("/root/a.rs").unwrap();
+            Ok(Some(vec![
+                lsp::Location::new(
+                    a.clone(),
+                    lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 10)),
+                ),
+                lsp::Location::new(
+                    a,
+                    lsp::Range::new(lsp::Position::new(2, 4), lsp::Position::new(2, 10)),
+                ),
+            ]))
+        });
+
+        let input = json!({ "path": "root/b.rs", "line": 1, "symbol": "helper" });
+        let output = run_tool(ReferencesTool, input, &workspace, cx).await;
+        assert_eq!(
+            output.unwrap(),
+            "root/a.rs:2: helper();\nroot/a.rs:3: helper();\n"
+        );
+
+        let input = json!({ "path": "root/a.rs", "line": 1, "symbol": "main" });
+        let output = run_tool(ReferencesTool, input, &workspace, cx).await;
+        assert_eq!(output.unwrap(), "No references to `main` were found.");
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use assistant_tool::Tool;
+use gpui::{Task, WeakView, WindowContext};
+use language::{Point, ToPoint as _};
+use project::search::{SearchQuery, SearchResult};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::PathMatcher;
+
+use crate::workspace_project;
+
+/// The maximum number of matching lines included in the output.
+const MAX_MATCHES: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchProjectToolInput {
+    /// The text to search for.
+    query: String,
+    /// Whether the query is a regular expression.
+    #[serde(default)]
+    regex: bool,
+    /// Whether the search is case-sensitive.
+    #[serde(default)]
+    case_sensitive: bool,
+    /// Glob patterns for the paths to search in, such as `**/*.rs`. The paths only start with the
+    /// name of their root directory when the project has several of them.
+    /// When empty, every file of the project is searched.
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+pub struct SearchProjectTool;
+
+impl Tool for SearchProjectTool {
+    fn name(&self) -> String {
+        "search_project".into()
+    }
+
+    fn description(&self) -> String {
+        format!("Searches the contents of the project's files, returning the matching lines prefixed with their path and 1-based line number. At most {MAX_MATCHES} lines are returned, so prefer specific queries. Private files, such as `.env` files, aren't searched.")
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        let schema = schemars::schema_for!(SearchProjectToolInput);
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: serde_json::Value,
+        workspace: WeakView<workspace::Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<String>> {
+        let input: SearchProjectToolInput = match serde_json::from_value(input) {
+            Ok(input) => input,
+            Err(err) => return Task::ready(Err(anyhow!(err))),
+        };
+        let project = match workspace_project(&workspace, cx) {
+            Ok(project) => project,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let query = PathMatcher::new(&input.include)
+            .map_err(|err| anyhow!(err))
+            .and_then(|files_to_include| {
+                if input.regex {
+                    SearchQuery::regex(
+                        input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        files_to_include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                } else {
+                    SearchQuery::text(
+                        input.query,
+                        false,
+                        input.case_sensitive,
+                        false,
+                        files_to_include,
+                        PathMatcher::default(),
+                        None,
+                    )
+                }
+            });
+        let query = match query {
+            Ok(query) => query,
+            Err(err) => return Task::ready(Err(err)),
+        };
+        let results = project.update(cx, |project, cx| project.search(query, cx));
+
+        cx.spawn(|cx| async move {
+            let mut output = String::new();
+            let mut match_count = 0;
+            let mut truncated = false;
+            while let Ok(result) = results.recv().await {
+                let SearchResult::Buffer { buffer, ranges } = result else {
+                    truncated = true;
+                    break;
+                };
+                buffer.read_with(&cx, |buffer, cx| {
+                    // Private files, such as `.env` files, are left out of the results.
+                    let Some(path) = buffer
+                        .file()
+                        .filter(|file| !file.is_private())
+                        .map(|file| file.full_path(cx))
+                    else {
+                        return anyhow::Ok(());
+                    };
+                    let mut last_row = None;
+                    for range in ranges {
+                        let row = range.start.to_point(buffer).row;
+                        if last_row == Some(row) {
+                            continue;
+                        }
+                        last_row = Some(row);
+                        if match_count == MAX_MATCHES {
+                            truncated = true;
+                            break;
+                        }
+                        let line = buffer
+                            .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                            .collect::<String>();
+                        writeln!(output, "{}:{}: {}", path.display(), row + 1, line.trim())?;
+                        match_count += 1;
+                    }
+                    Ok(())
+                })??;
+                if truncated {
+                    break;
+                }
+            }
+
+            if match_count == 0 {
+                return Ok("No matches found.".into());
+            }
+            if truncated {
+                writeln!(
+                    output,
+                    "\nOnly the first {match_count} matches are shown. Refine the query to see the others."
+                )?;
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, run_tool, test_workspace};
+
+    #[gpui::test]
+    async fn test_search_project(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn needle() {}\nfn other() {}\nfn needle_too() { needle() }\n",
+                "b.txt": "Needle\n",
+                ".env": "NEEDLE=1\n",
+            }),
+            cx,
+        )
+        .await;
+
+        let output = run_tool(
+            SearchProjectTool,
+            json!({ "query": "needle" }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            sorted_lines(&output),
+            [
+                "root/a.rs:1: fn needle() {}",
+                "root/a.rs:3: fn needle_too() { needle() }",
+                "root/b.txt:1: Needle",
+            ]
+        );
+
+        let output = run_tool(
+            SearchProjectTool,
+            json!({ "query": "needle", "case_sensitive": true, "include": ["**/*.txt"] }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "No matches found.");
+
+        let output = run_tool(
+            SearchProjectTool,
+            json!({ "query": r"fn \w+\(\)", "regex": true, "include": ["**/*.rs"] }),
+            &workspace,
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            sorted_lines(&output),
+            [
+                "root/a.rs:1: fn needle() {}",
+                "root/a.rs:2: fn other() {}",
+                "root/a.rs:3: fn needle_too() { needle() }",
+            ]
+        );
+    }
+
+    fn sorted_lines(output: &str) -> Vec<&str> {
+        let mut lines = output.lines().collect::<Vec<_>>();
+        lines.sort();
+        lines
+    }
+}
@@ -0,0 +1,224 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use gpui::{AppContext, Model, Task, WindowContext};
+use language::{Anchor, Buffer, Point, ToPoint as _};
+use project::lsp_store::OpenLspBufferHandle;
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The input of the tools that look up a symbol with the language servers.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SymbolPositionInput {
+    /// The path of the file in which the symbol appears, starting with the name of one of the project's root directories.
+    pub path: PathBuf,
+    /// The 1-based line on which the symbol appears.
+    pub line: u32,
+    /// The name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// Which occurrence of the symbol to use, counting from 1, when it appears more than once on the line.
+    #[serde(default)]
+    pub occurrence: Option<u32>,
+}
+
+impl SymbolPositionInput {
+    /// Opens the file, registering it with the language servers, and returns the position of the
+    /// symbol on the line. The buffer stays registered for as long as the returned handle is alive.
+    pub fn resolve(
+        self,
+        project: &Model<Project>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<(Model<Buffer>, Point, OpenLspBufferHandle)>> {
+        let Some(project_path) = project.read(cx).find_project_path(&self.path, cx) else {
+            return Task::ready(Err(anyhow!(
+                "{} is not in the project",
+                self.path.display()
+            )));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        let lsp_store = project.read(cx).lsp_store();
+        cx.spawn(|mut cx| async move {
+            let buffer = open_buffer.await?;
+            let position = buffer.read_with(&cx, |buffer, _| {
+                let row = self.line.saturating_sub(1);
+                if row > buffer.max_point().row {
+                    return Err(anyhow!(
+                        "{} only has {} lines",
+                        self.path.display(),
+                        buffer.max_point().row + 1
+                    ));
+                }
+                let line = buffer
+                    .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                    .collect::<String>();
+                let column = self.column_in_line(&line)?;
+                Ok(Point::new(row, column as u32))
+            })??;
+            let lsp_handle = lsp_store.update(&mut cx, |lsp_store, cx| {
+                lsp_store.register_buffer_with_language_servers(&buffer, cx)
+            })?;
+            Ok((buffer, position, lsp_handle))
+        })
+    }
+
+    /// Returns the column of the requested occurrence of the symbol in the line, only counting
+    /// occurrences that aren't part of a longer identifier.
+    fn column_in_line(&self, line: &str) -> Result<usize> {
+        let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+        let occurrences = line
+            .match_indices(&self.symbol)
+            .map(|(column, _)| column)
+            .filter(|&column| {
+                let before = line[..column].chars().next_back();
+                let after = line[column + self.symbol.len()..].chars().next();
+                let starts_word = !self.symbol.starts_with(is_identifier_char)
+                    || !before.is_some_and(is_identifier_char);
+                let ends_word = !self.symbol.ends_with(is_identifier_char)
+                    || !after.is_some_and(is_identifier_char);
+                starts_word && ends_word
+            })
+            .collect::<Vec<_>>();
+
+        match (occurrences.as_slice(), self.occurrence) {
+            ([], _) => Err(anyhow!(
+                "`{}` does not appear on line {}",
+                self.symbol,
+                self.line
+            )),
+            ([column], None) => Ok(*column),
+            (_, None) => Err(anyhow!(
+                "`{}` appears {} times on line {}, specify which occurrence to use",
+                self.symbol,
+                occurrences.len(),
+                self.line
+            )),
+            (_, Some(occurrence)) => occurrence
+                .checked_sub(1)
+                .and_then(|ix| occurrences.get(ix as usize).copied())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "`{}` only appears {} time(s) on line {}",
+                        self.symbol,
+                        occurrences.len(),
+                        self.line
+                    )
+                }),
+        }
+    }
+}
+
+/// Describes a range of a buffer as its path and 1-based line, followed by the text of that line.
+pub fn describe_location(buffer: &Buffer, range: &Range<Anchor>, cx: &AppContext) -> String {
+    let row = range.start.to_point(buffer).row;
+    let path = buffer
+        .file()
+        .map(|file| file.full_path(cx).display().to_string())
+        .unwrap_or_else(|| "untitled".into());
+    let line = buffer
+        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+        .collect::<String>();
+    format!("{path}:{}: {}", row + 1, line.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+
+    use super::*;
+    use crate::test::{init_test, test_workspace};
+
+    #[gpui::test]
+    async fn test_resolve(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (workspace, cx) = test_workspace(
+            json!({
+                "a.rs": "fn main() {\n    let helper_count = helper() + helper();\n}\n",
+            }),
+            cx,
+        )
+        .await;
+        let project = workspace.read_with(cx, |workspace, _| workspace.project().clone());
+        let position = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 1, "symbol": "main" }),
+            cx,
+        )
+        .await;
+        assert_eq!(position.unwrap(), Point::new(0, 3));
+        let position = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 2, "symbol": "helper_count" }),
+            cx,
+        )
+        .await;
+        assert_eq!(position.unwrap(), Point::new(1, 8));
+        let position = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 2, "symbol": "helper", "occurrence": 2 }),
+            cx,
+        )
+        .await;
+        assert_eq!(position.unwrap(), Point::new(1, 34));
+
+        let error = resolve(
+            &project,
+            json!({ "path": "other/a.rs", "line": 1, "symbol": "main" }),
+            cx,
+        )
+        .await;
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "other/a.rs is not in the project"
+        );
+        let error = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 9, "symbol": "main" }),
+            cx,
+        )
+        .await;
+        assert_eq!(error.unwrap_err().to_string(), "root/a.rs only has 4 lines");
+        let error = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 1, "symbol": "ma" }),
+            cx,
+        )
+        .await;
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "`ma` does not appear on line 1"
+        );
+        let error = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 2, "symbol": "helper" }),
+            cx,
+        )
+        .await;
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "`helper` appears 2 times on line 2, specify which occurrence to use"
+        );
+        let error = resolve(
+            &project,
+            json!({ "path": "root/a.rs", "line": 2, "symbol": "helper", "occurrence": 3 }),
+            cx,
+        )
+        .await;
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "`helper` only appears 2 time(s) on line 2"
+        );
+    }
+
+    async fn resolve(
+        project: &Model<Project>,
+        input: serde_json::Value,
+        cx: &mut VisualTestContext,
+    ) -> Result<Point> {
+        let input: SymbolPositionInput = serde_json::from_value(input).unwrap();
+        let task = cx.update(|cx| input.resolve(project, cx));
+        task.await.map(|(_, position, _)| position)
+    }
+}
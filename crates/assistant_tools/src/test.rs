@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use assistant_tool::Tool;
+use futures::StreamExt as _;
+use gpui::{TestAppContext, View, VisualTestContext};
+use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+use lsp::FakeLanguageServer;
+use project::lsp_store::OpenLspBufferHandle;
+use project::{FakeFs, Project};
+use workspace::{AppState, Workspace};
+
+pub fn init_test(cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        AppState::test(cx);
+        language::init(cx);
+        workspace::init_settings(cx);
+        Project::init_settings(cx);
+    });
+}
+
+/// Opens a workspace for a project with a single root directory, `/root`,
+/// whose files are the ones in `tree`.
+pub async fn test_workspace(
+    tree: serde_json::Value,
+    cx: &mut TestAppContext,
+) -> (View<Workspace>, &mut VisualTestContext) {
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/root", tree).await;
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+    (workspace, cx)
+}
+
+pub async fn run_tool(
+    tool: impl Tool,
+    input: serde_json::Value,
+    workspace: &View<Workspace>,
+    cx: &mut VisualTestContext,
+) -> Result<String> {
+    let task = cx.update(|cx| Arc::new(tool).run(input, workspace.downgrade(), cx));
+    task.await
+}
+
+/// Registers a fake Rust language server with the given capabilities, and starts it by opening
+/// the file at `abs_path`. The server keeps running for as long as the returned handle is alive.
+pub async fn start_fake_rust_server(
+    workspace: &View<Workspace>,
+    abs_path: &str,
+    capabilities: lsp::ServerCapabilities,
+    cx: &mut VisualTestContext,
+) -> (FakeLanguageServer, OpenLspBufferHandle) {
+    let project = workspace.read_with(cx, |workspace, _| workspace.project().clone());
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    )));
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities,
+            ..Default::default()
+        },
+    );
+    let (_, handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp(abs_path, cx)
+        })
+        .await
+        .unwrap();
+    (fake_servers.next().await.unwrap(), handle)
+}
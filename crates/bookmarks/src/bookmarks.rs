@@ -0,0 +1,420 @@
+mod name_bookmark;
+
+use std::{path::Path, sync::Arc};
+
+use editor::{scroll::Autoscroll, Editor};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    ParentElement, Render, Styled, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::Point;
+use name_bookmark::NameBookmarkModal;
+use picker::{Picker, PickerDelegate};
+use project::{Bookmark, Project, ProjectPath};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{searchable::Direction, ModalView, Pane, Workspace};
+
+actions!(bookmarks, [Toggle, GoToNext, GoToPrevious, NameBookmark]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(register).detach();
+}
+
+fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace, _: &Toggle, cx| {
+        let project = workspace.project().clone();
+        let weak_workspace = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            BookmarksPicker::new(weak_workspace, project, cx)
+        });
+    });
+    workspace.register_action(|workspace, _: &GoToNext, cx| {
+        go_to_bookmark(workspace, Direction::Next, cx)
+    });
+    workspace.register_action(|workspace, _: &GoToPrevious, cx| {
+        go_to_bookmark(workspace, Direction::Prev, cx)
+    });
+    workspace.register_action(|workspace, _: &NameBookmark, cx| {
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let bookmark_store = workspace.project().read(cx).bookmark_store().clone();
+        let Some((buffer, row)) = editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest::<Point>(cx).head();
+            let (buffer, point, _) = editor.buffer().read(cx).point_to_buffer_point(head, cx)?;
+            Some((buffer, point.row))
+        }) else {
+            return;
+        };
+        workspace.toggle_modal(cx, move |cx| {
+            NameBookmarkModal::new(bookmark_store, buffer, row, cx)
+        });
+    });
+}
+
+/// Jumps to the bookmark after or before the cursor of the active editor,
+/// wrapping around at the end of the project's bookmarks.
+fn go_to_bookmark(
+    workspace: &mut Workspace,
+    direction: Direction,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let bookmark_store = workspace.project().read(cx).bookmark_store().clone();
+    let bookmarks = bookmark_store.read(cx).bookmarks();
+    let cursor = workspace.active_item_as::<Editor>(cx).and_then(|editor| {
+        editor.update(cx, |editor, cx| {
+            let head = editor.selections.newest::<Point>(cx).head();
+            let (buffer, point, _) = editor.buffer().read(cx).point_to_buffer_point(head, cx)?;
+            let abs_path = bookmark_store.read(cx).abs_path_for_buffer(&buffer, cx)?;
+            Some((abs_path, point.row))
+        })
+    });
+
+    let target = match (cursor, direction) {
+        (Some((abs_path, row)), Direction::Next) => bookmarks
+            .iter()
+            .find(|bookmark| (&bookmark.abs_path, bookmark.row) > (&abs_path, row))
+            .or(bookmarks.first()),
+        (Some((abs_path, row)), Direction::Prev) => bookmarks
+            .iter()
+            .rev()
+            .find(|bookmark| (&bookmark.abs_path, bookmark.row) < (&abs_path, row))
+            .or(bookmarks.last()),
+        (None, Direction::Next) => bookmarks.first(),
+        (None, Direction::Prev) => bookmarks.last(),
+    };
+    if let Some(target) = target {
+        open_bookmark(workspace, target, None, cx).detach_and_log_err(cx);
+    }
+}
+
+fn open_bookmark(
+    workspace: &mut Workspace,
+    bookmark: &Bookmark,
+    pane: Option<WeakView<Pane>>,
+    cx: &mut ViewContext<Workspace>,
+) -> Task<anyhow::Result<()>> {
+    let project_path = workspace
+        .project()
+        .read(cx)
+        .find_worktree(&bookmark.abs_path, cx)
+        .map(|(worktree, path)| ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path.into(),
+        });
+    let open_task = match project_path {
+        Some(project_path) => workspace.open_path(project_path, pane, true, cx),
+        None => workspace.open_abs_path(bookmark.abs_path.to_path_buf(), false, cx),
+    };
+    let position = Point::new(bookmark.row, 0);
+    cx.spawn(|_, mut cx| async move {
+        let item = open_task.await?;
+        if let Some(editor) = item.downcast::<Editor>() {
+            editor.update(&mut cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                    selections.select_ranges([position..position])
+                });
+            })?;
+        }
+        Ok(())
+    })
+}
+
+pub struct BookmarksPicker {
+    picker: View<Picker<BookmarksDelegate>>,
+}
+
+impl BookmarksPicker {
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = BookmarksDelegate::new(workspace, cx.view().downgrade(), project, cx);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl ModalView for BookmarksPicker {}
+
+impl EventEmitter<DismissEvent> for BookmarksPicker {}
+
+impl FocusableView for BookmarksPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for BookmarksPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct BookmarksDelegate {
+    workspace: WeakView<Workspace>,
+    bookmarks_picker: WeakView<BookmarksPicker>,
+    bookmarks: Vec<Bookmark>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl BookmarksDelegate {
+    fn new(
+        workspace: WeakView<Workspace>,
+        bookmarks_picker: WeakView<BookmarksPicker>,
+        project: Model<Project>,
+        cx: &AppContext,
+    ) -> Self {
+        let project = project.read(cx);
+        let show_worktree_root_name = project.visible_worktrees(cx).count() > 1;
+        let bookmarks = project.bookmark_store().read(cx).bookmarks();
+        let candidates = bookmarks
+            .iter()
+            .enumerate()
+            .map(|(id, bookmark)| {
+                let path = match project.find_worktree(&bookmark.abs_path, cx) {
+                    Some((worktree, path)) if show_worktree_root_name => {
+                        Path::new(worktree.read(cx).root_name()).join(path)
+                    }
+                    Some((_, path)) => path,
+                    None => bookmark.abs_path.to_path_buf(),
+                };
+                let location = format!("{}:{}", path.display(), bookmark.row + 1);
+                let label = match &bookmark.name {
+                    Some(name) => format!("{name} {location}"),
+                    None => location,
+                };
+                StringMatchCandidate::new(id, &label)
+            })
+            .collect();
+
+        Self {
+            workspace,
+            bookmarks_picker,
+            bookmarks,
+            candidates,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BookmarksDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Search bookmarks...".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        if self.bookmarks.is_empty() {
+            "No bookmarks".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(bookmark) = self
+            .matches
+            .get(self.selected_index)
+            .map(|mat| self.bookmarks[mat.candidate_id].clone())
+        else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = secondary.then(|| workspace.adjacent_pane(cx).downgrade());
+                open_bookmark(workspace, &bookmark, pane, cx).detach_and_log_err(cx);
+            })
+            .log_err();
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.bookmarks_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let bookmark = &self.bookmarks[mat.candidate_id];
+        let icon_color = if bookmark.name.is_some() {
+            Color::Accent
+        } else {
+            Color::Muted
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Bookmark).color(icon_color))
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_go_to_next_and_previous_bookmark(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "one\ntwo\nthree\nfour\n",
+                "b.txt": "five\nsix\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        project.update(cx, |project, cx| {
+            project.bookmark_store().update(cx, |store, cx| {
+                store.set_bookmarks(
+                    vec![
+                        Bookmark {
+                            abs_path: Path::new("/dir/a.txt").into(),
+                            row: 1,
+                            name: None,
+                        },
+                        Bookmark {
+                            abs_path: Path::new("/dir/a.txt").into(),
+                            row: 3,
+                            name: Some("four".into()),
+                        },
+                        Bookmark {
+                            abs_path: Path::new("/dir/b.txt").into(),
+                            row: 1,
+                            name: None,
+                        },
+                    ],
+                    cx,
+                )
+            })
+        });
+
+        let go_to_next = |cx: &mut VisualTestContext| {
+            cx.dispatch_action(GoToNext);
+            cx.run_until_parked();
+            active_position(&workspace, cx)
+        };
+        assert_eq!(go_to_next(cx), ("a.txt".into(), Point::new(1, 0)));
+        assert_eq!(go_to_next(cx), ("a.txt".into(), Point::new(3, 0)));
+        assert_eq!(go_to_next(cx), ("b.txt".into(), Point::new(1, 0)));
+        assert_eq!(go_to_next(cx), ("a.txt".into(), Point::new(1, 0)));
+
+        cx.dispatch_action(GoToPrevious);
+        cx.run_until_parked();
+        assert_eq!(
+            active_position(&workspace, cx),
+            ("b.txt".into(), Point::new(1, 0))
+        );
+    }
+
+    fn active_position(workspace: &View<Workspace>, cx: &mut VisualTestContext) -> (String, Point) {
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            editor.update(cx, |editor, cx| {
+                let path = editor
+                    .buffer()
+                    .read(cx)
+                    .as_singleton()
+                    .unwrap()
+                    .read(cx)
+                    .file()
+                    .unwrap()
+                    .path()
+                    .to_string_lossy()
+                    .to_string();
+                (path, editor.selections.newest::<Point>(cx).head())
+            })
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            language::init(cx);
+            crate::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
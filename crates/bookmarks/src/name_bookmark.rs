@@ -0,0 +1,104 @@
+use editor::Editor;
+use gpui::{
+    div, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    SharedString, Subscription, View, ViewContext, VisualContext,
+};
+use language::Buffer;
+use project::BookmarkStore;
+use ui::prelude::*;
+use workspace::ModalView;
+
+/// Prompts for the name of the bookmark on a line, adding the bookmark if
+/// there isn't one yet. Confirming an empty name makes the bookmark anonymous.
+pub struct NameBookmarkModal {
+    name_editor: View<Editor>,
+    bookmark_store: Model<BookmarkStore>,
+    buffer: Model<Buffer>,
+    row: u32,
+    _subscription: Subscription,
+}
+
+impl ModalView for NameBookmarkModal {}
+
+impl EventEmitter<DismissEvent> for NameBookmarkModal {}
+
+impl FocusableView for NameBookmarkModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.name_editor.focus_handle(cx)
+    }
+}
+
+impl NameBookmarkModal {
+    pub fn new(
+        bookmark_store: Model<BookmarkStore>,
+        buffer: Model<Buffer>,
+        row: u32,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let name = bookmark_store
+            .read(cx)
+            .bookmark_at_row(&buffer, row, cx)
+            .and_then(|bookmark| bookmark.name);
+        let name_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Bookmark name", cx);
+            if let Some(name) = name {
+                editor.set_text(name.to_string(), cx);
+                editor.select_all(&editor::actions::SelectAll, cx);
+            }
+            editor
+        });
+        let subscription = cx.subscribe(&name_editor, |_, _, event: &editor::EditorEvent, cx| {
+            if let editor::EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            name_editor,
+            bookmark_store,
+            buffer,
+            row,
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let name = self.name_editor.read(cx).text(cx);
+        let name = name.trim();
+        let name = (!name.is_empty()).then(|| SharedString::from(name.to_string()));
+        self.bookmark_store.update(cx, |store, cx| {
+            store.name_bookmark(&self.buffer, self.row, name, cx)
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for NameBookmarkModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("NameBookmark")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.name_editor.clone()),
+            )
+            .child(
+                h_flex().px_2().py_1().child(
+                    Label::new(format!("Name the bookmark on line {}", self.row + 1))
+                        .color(Color::Muted),
+                ),
+            )
+    }
+}
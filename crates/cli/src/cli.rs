@@ -13,6 +13,8 @@ pub enum CliRequest {
     Open {
         paths: Vec<String>,
         urls: Vec<String>,
+        /// Pairs of `[old, new]` files to compare.
+        diff_paths: Vec<[String; 2]>,
         wait: bool,
         open_new_workspace: Option<bool>,
         env: Option<HashMap<String, String>>,
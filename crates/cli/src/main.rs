@@ -47,6 +47,12 @@ struct Args {
     /// Use `path:line:row` syntax to open a file at a specific location.
     /// Non-existing paths and directories will ignore `:line:row` suffix.
     paths_with_position: Vec<String>,
+    /// Compare two files side by side.
+    ///
+    /// May be repeated to compare several pairs of files. Combined with `--wait`,
+    /// this makes Zed usable as `git difftool`.
+    #[arg(long, num_args = 2, value_names = ["OLD_PATH", "NEW_PATH"], action = clap::ArgAction::Append)]
+    diff: Vec<String>,
     /// Print Zed's version and the app path.
     #[arg(short, long)]
     version: bool,
@@ -96,6 +102,12 @@ fn parse_path_with_position(argument_str: &str) -> anyhow::Result<String> {
     Ok(canonicalized.to_string(|path| path.to_string_lossy().to_string()))
 }
 
+fn canonicalize_path(argument_str: &str) -> anyhow::Result<String> {
+    let path = fs::canonicalize(argument_str)
+        .with_context(|| format!("canonicalizing path {argument_str}"))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 fn main() -> Result<()> {
     // Exit flatpak sandbox if needed
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
@@ -184,6 +196,14 @@ fn main() -> Result<()> {
         }
     }
 
+    let mut diff_paths = vec![];
+    for pair in args.diff.chunks(2) {
+        let [old_path, new_path] = pair else {
+            anyhow::bail!("--diff expects exactly two paths");
+        };
+        diff_paths.push([canonicalize_path(old_path)?, canonicalize_path(new_path)?]);
+    }
+
     if let Some(_) = args.dev_server_token {
         return Err(anyhow::anyhow!(
             "Dev servers were removed in v0.157.x please upgrade to SSH remoting: https://zed.dev/docs/remote-development"
@@ -199,6 +219,7 @@ fn main() -> Result<()> {
             tx.send(CliRequest::Open {
                 paths,
                 urls,
+                diff_paths,
                 wait: args.wait,
                 open_new_workspace,
                 env,
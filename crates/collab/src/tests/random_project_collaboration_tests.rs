@@ -7,7 +7,7 @@ use collections::{BTreeMap, HashMap};
 use editor::Bias;
 use fs::{FakeFs, Fs as _};
 use futures::StreamExt;
-use git::repository::GitFileStatus;
+use git::repository::{GitFileStatus, UnmergedStatus};
 use gpui::{BackgroundExecutor, Model, TestAppContext};
 use language::{
     range_to_lsp, FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, PointUtf16,
@@ -1465,7 +1465,7 @@ fn generate_git_operation(rng: &mut StdRng, client: &TestClient) -> GitOperation
                         match rng.gen_range(0..3_u32) {
                             0 => GitFileStatus::Added,
                             1 => GitFileStatus::Modified,
-                            2 => GitFileStatus::Conflict,
+                            2 => GitFileStatus::Conflict(UnmergedStatus::BothModified),
                             _ => unreachable!(),
                         },
                     )
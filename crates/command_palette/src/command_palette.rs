@@ -20,7 +20,7 @@ use postage::{sink::Sink, stream::Stream};
 use settings::Settings;
 use ui::{h_flex, prelude::*, v_flex, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
 use util::ResultExt;
-use workspace::{ModalView, Workspace, WorkspaceSettings};
+use workspace::{searchable::Direction, ModalView, Workspace, WorkspaceSettings};
 use zed_actions::{command_palette::Toggle, OpenZedUrl};
 
 pub fn init(cx: &mut AppContext) {
@@ -60,12 +60,31 @@ impl CommandPalette {
     }
 
     pub fn toggle(workspace: &mut Workspace, query: &str, cx: &mut ViewContext<Workspace>) {
+        Self::toggle_palette(workspace, query, false, cx)
+    }
+
+    /// Toggles the palette as a command line, in which moving up from an empty query recalls
+    /// the queries of previously confirmed intercepted commands.
+    pub fn toggle_with_history(
+        workspace: &mut Workspace,
+        query: &str,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::toggle_palette(workspace, query, true, cx)
+    }
+
+    fn toggle_palette(
+        workspace: &mut Workspace,
+        query: &str,
+        recall_history: bool,
+        cx: &mut ViewContext<Workspace>,
+    ) {
         let Some(previous_focus_handle) = cx.focused() else {
             return;
         };
         let telemetry = workspace.client().telemetry().clone();
         workspace.toggle_modal(cx, move |cx| {
-            CommandPalette::new(previous_focus_handle, telemetry, query, cx)
+            CommandPalette::new(previous_focus_handle, telemetry, query, recall_history, cx)
         });
     }
 
@@ -73,6 +92,7 @@ impl CommandPalette {
         previous_focus_handle: FocusHandle,
         telemetry: Arc<Telemetry>,
         query: &str,
+        recall_history: bool,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let filter = CommandPaletteFilter::try_global(cx);
@@ -88,6 +108,7 @@ impl CommandPalette {
                 Some(Command {
                     name: humanize_action_name(action.name()),
                     action,
+                    intercepted_query: None,
                 })
             })
             .collect();
@@ -97,6 +118,7 @@ impl CommandPalette {
             commands,
             telemetry,
             previous_focus_handle,
+            recall_history,
         );
 
         let picker = cx.new_view(|cx| {
@@ -139,11 +161,18 @@ pub struct CommandPaletteDelegate {
         Task<()>,
         postage::dispatch::Receiver<(Vec<Command>, Vec<StringMatch>)>,
     )>,
+    /// Whether moving up from an empty query recalls the interceptor's history.
+    recall_history: bool,
+    /// The position in the interceptor's history of the query being shown, if
+    /// the history is being navigated.
+    history_ix: Option<usize>,
 }
 
 struct Command {
     name: String,
     action: Box<dyn Action>,
+    /// The query that produced this command, if it came from the interceptor.
+    intercepted_query: Option<String>,
 }
 
 impl Clone for Command {
@@ -151,6 +180,7 @@ impl Clone for Command {
         Self {
             name: self.name.clone(),
             action: self.action.boxed_clone(),
+            intercepted_query: self.intercepted_query.clone(),
         }
     }
 }
@@ -169,6 +199,7 @@ impl CommandPaletteDelegate {
         commands: Vec<Command>,
         telemetry: Arc<Telemetry>,
         previous_focus_handle: FocusHandle,
+        recall_history: bool,
     ) -> Self {
         Self {
             command_palette,
@@ -179,6 +210,8 @@ impl CommandPaletteDelegate {
             telemetry,
             previous_focus_handle,
             updating_matches: None,
+            recall_history,
+            history_ix: None,
         }
     }
 
@@ -217,6 +250,7 @@ impl CommandPaletteDelegate {
             commands.push(Command {
                 name: string.clone(),
                 action,
+                intercepted_query: Some(query.clone()),
             });
             matches.insert(
                 0,
@@ -328,6 +362,39 @@ impl PickerDelegate for CommandPaletteDelegate {
         })
     }
 
+    fn select_history(
+        &mut self,
+        direction: Direction,
+        query: &str,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        if !self.recall_history {
+            return None;
+        }
+        let history = CommandPaletteInterceptor::try_global(cx)?.history(cx);
+        // Only navigate the history from an empty query, and stop as soon as
+        // the recalled query is edited.
+        let ix = match self.history_ix {
+            None if query.is_empty() && direction == Direction::Prev => {
+                history.len().checked_sub(1)?
+            }
+            Some(ix) if history.get(ix).map(String::as_str) == Some(query) => match direction {
+                Direction::Prev => ix.saturating_sub(1),
+                Direction::Next if ix + 1 < history.len() => ix + 1,
+                Direction::Next => {
+                    self.history_ix = None;
+                    return Some(String::new());
+                }
+            },
+            _ => {
+                self.history_ix = None;
+                return None;
+            }
+        };
+        self.history_ix = Some(ix);
+        history.get(ix).cloned()
+    }
+
     fn finalize_update_matches(
         &mut self,
         query: String,
@@ -375,6 +442,11 @@ impl PickerDelegate for CommandPaletteDelegate {
         HitCounts::update_global(cx, |hit_counts, _cx| {
             *hit_counts.0.entry(command.name).or_default() += 1;
         });
+        if let Some(query) = command.intercepted_query {
+            CommandPaletteInterceptor::update_global(cx, |interceptor, cx| {
+                interceptor.record(&query, cx)
+            });
+        }
         let action = command.action;
         cx.focus(&self.previous_focus_handle);
         self.dismissed(cx);
@@ -447,12 +519,12 @@ impl std::fmt::Debug for Command {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
 
     use super::*;
     use editor::Editor;
     use go_to_line::GoToLine;
-    use gpui::TestAppContext;
+    use gpui::{TestAppContext, VisualTestContext};
     use language::Point;
     use project::Project;
     use settings::KeymapFile;
@@ -545,6 +617,86 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_intercepted_command_history(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text("abc", cx);
+            editor
+        });
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+            editor.update(cx, |editor, cx| editor.focus(cx))
+        });
+
+        let history = Rc::new(RefCell::new(Vec::<String>::new()));
+        cx.update(|cx| {
+            CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
+                interceptor.set(Box::new(|query, _| {
+                    (query == "bs").then(|| CommandInterceptResult {
+                        action: editor::actions::Backspace.boxed_clone(),
+                        string: "bs".to_string(),
+                        positions: Vec::new(),
+                    })
+                }));
+                interceptor.set_history(
+                    Box::new({
+                        let history = history.clone();
+                        move |_| history.borrow().clone()
+                    }),
+                    Box::new({
+                        let history = history.clone();
+                        move |query, _| history.borrow_mut().push(query.to_string())
+                    }),
+                );
+            });
+        });
+
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("bs");
+        cx.simulate_keystrokes("enter");
+        assert_eq!(*history.borrow(), ["bs"]);
+        editor.update(cx, |editor, cx| assert_eq!(editor.text(cx), "ab"));
+
+        // Commands that weren't intercepted aren't remembered.
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("enter");
+        assert_eq!(*history.borrow(), ["bs"]);
+        editor.update(cx, |editor, cx| assert_eq!(editor.text(cx), "a"));
+
+        let active_picker = |cx: &mut VisualTestContext| {
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .active_modal::<CommandPalette>(cx)
+                    .unwrap()
+                    .read(cx)
+                    .picker
+                    .clone()
+            })
+        };
+
+        // The history is only recalled in a palette opened as a command line.
+        cx.simulate_keystrokes("cmd-shift-p up");
+        active_picker(cx).update(cx, |palette, cx| assert_eq!(palette.query(cx), ""));
+        cx.simulate_keystrokes("cmd-shift-p");
+
+        workspace.update(cx, |workspace, cx| {
+            CommandPalette::toggle_with_history(workspace, "", cx)
+        });
+        cx.simulate_keystrokes("up");
+        let palette = active_picker(cx);
+        palette.update(cx, |palette, cx| assert_eq!(palette.query(cx), "bs"));
+        cx.simulate_keystrokes("down");
+        palette.update(cx, |palette, cx| assert_eq!(palette.query(cx), ""));
+        cx.simulate_keystrokes("up enter");
+        editor.update(cx, |editor, cx| assert_eq!(editor.text(cx), ""));
+    }
+
     #[gpui::test]
     async fn test_go_to_line(cx: &mut TestAppContext) {
         let app_state = init_test(cx);
@@ -594,6 +746,8 @@ mod tests {
                         "bindings": {
                             "cmd-n": "workspace::NewFile",
                             "enter": "menu::Confirm",
+                            "up": "menu::SelectPrev",
+                            "down": "menu::SelectNext",
                             "cmd-shift-p": "command_palette::Toggle"
                         }
                     }
@@ -106,9 +106,11 @@ pub struct CommandInterceptResult {
 
 /// An interceptor for the command palette.
 #[derive(Default)]
-pub struct CommandPaletteInterceptor(
-    Option<Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>>,
-);
+pub struct CommandPaletteInterceptor {
+    handler: Option<Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>>,
+    history: Option<Box<dyn Fn(&AppContext) -> Vec<String>>>,
+    record: Option<Box<dyn Fn(&str, &mut AppContext)>>,
+}
 
 #[derive(Default)]
 struct GlobalCommandPaletteInterceptor(CommandPaletteInterceptor);
@@ -132,14 +134,31 @@ impl CommandPaletteInterceptor {
 
     /// Intercepts the given query from the command palette.
     pub fn intercept(&self, query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
-        let handler = self.0.as_ref()?;
+        let handler = self.handler.as_ref()?;
 
         (handler)(query, cx)
     }
 
-    /// Clears the global interceptor.
+    /// Returns the queries of previously confirmed intercepted commands, from
+    /// the oldest to the most recent.
+    pub fn history(&self, cx: &AppContext) -> Vec<String> {
+        self.history
+            .as_ref()
+            .map_or_else(Vec::new, |history| (history)(cx))
+    }
+
+    /// Records the query of an intercepted command that was confirmed.
+    pub fn record(&self, query: &str, cx: &mut AppContext) {
+        if let Some(record) = self.record.as_ref() {
+            (record)(query, cx)
+        }
+    }
+
+    /// Clears the global interceptor and its history.
     pub fn clear(&mut self) {
-        self.0 = None;
+        self.handler = None;
+        self.history = None;
+        self.record = None;
     }
 
     /// Sets the global interceptor.
@@ -149,6 +168,17 @@ impl CommandPaletteInterceptor {
         &mut self,
         handler: Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>,
     ) {
-        self.0 = Some(handler);
+        self.handler = Some(handler);
+    }
+
+    /// Sets how the queries of confirmed intercepted commands are remembered,
+    /// so that they can be recalled in the command palette.
+    pub fn set_history(
+        &mut self,
+        history: Box<dyn Fn(&AppContext) -> Vec<String>>,
+        record: Box<dyn Fn(&str, &mut AppContext)>,
+    ) {
+        self.history = Some(history);
+        self.record = Some(record);
     }
 }
@@ -1,7 +1,11 @@
 use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
-use futures::{channel::oneshot, io::BufWriter, select, AsyncRead, AsyncWrite, FutureExt};
+use futures::{
+    channel::oneshot, future::LocalBoxFuture, io::BufWriter, select, AsyncRead, AsyncWrite, Future,
+    FutureExt, StreamExt as _,
+};
 use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use http_client::{AsyncBody, HttpClient, Method};
 use parking_lot::Mutex;
 use postage::barrier;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -20,7 +24,8 @@ use std::{
     },
     time::{Duration, Instant},
 };
-use util::TryFutureExt;
+use url::Url;
+use util::{ResultExt as _, TryFutureExt};
 
 const JSON_RPC_VERSION: &str = "2.0";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
@@ -34,6 +39,8 @@ pub const INTERNAL_ERROR: i32 = -32603;
 
 type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
 type NotificationHandler = Box<dyn Send + FnMut(Value, AsyncAppContext)>;
+type RequestHandler =
+    Box<dyn Send + FnMut(Value, AsyncAppContext) -> LocalBoxFuture<'static, Result<Value>>>;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -48,6 +55,7 @@ pub struct Client {
     outbound_tx: channel::Sender<String>,
     name: Arc<str>,
     notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
@@ -79,6 +87,26 @@ struct Request<'a, T> {
     params: T,
 }
 
+#[derive(Deserialize)]
+struct AnyRequest<'a> {
+    #[allow(dead_code)]
+    jsonrpc: &'a str,
+    id: RequestId,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct OutgoingResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct AnyResponse<'a> {
     jsonrpc: &'a str,
@@ -125,6 +153,8 @@ struct AnyNotification<'a> {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Error {
+    #[serde(default)]
+    code: i32,
     message: String,
 }
 
@@ -177,15 +207,18 @@ impl Client {
 
         let notification_handlers =
             Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
         let response_handlers =
             Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
 
         let stdout_input_task = cx.spawn({
-            let notification_handlers = notification_handlers.clone();
-            let response_handlers = response_handlers.clone();
-            move |cx| {
-                Self::handle_input(stdout, notification_handlers, response_handlers, cx).log_err()
-            }
+            let handlers = MessageHandlers {
+                notification_handlers: notification_handlers.clone(),
+                request_handlers: request_handlers.clone(),
+                response_handlers: response_handlers.clone(),
+                outbound_tx: outbound_tx.clone(),
+            };
+            move |cx| Self::handle_input(stdout, handlers, cx).log_err()
         });
         let stderr_input_task = cx.spawn(|_| Self::handle_stderr(stderr).log_err());
         let input_task = cx.spawn(|_| async move {
@@ -205,6 +238,7 @@ impl Client {
         let mut context_server = Self {
             server_id,
             notification_handlers,
+            request_handlers,
             response_handlers,
             name: "".into(),
             next_id: Default::default(),
@@ -222,16 +256,83 @@ impl Client {
         Ok(context_server)
     }
 
+    /// Creates a new Client instance for a context server that is already running.
+    ///
+    /// This uses the HTTP with Server-Sent Events transport of the Model Context Protocol:
+    /// the client opens an event stream at the given URL, on which the server announces the
+    /// endpoint that messages should be POSTed to, and then sends its own messages.
+    /// The headers are added to every request, for example to authenticate with the server.
+    pub fn new_sse(
+        server_id: ContextServerId,
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        log::info!("connecting to context server (url={url})");
+
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let (output_done_tx, output_done_rx) = barrier::channel();
+        let (endpoint_tx, endpoint_rx) = oneshot::channel();
+
+        let notification_handlers =
+            Arc::new(Mutex::new(HashMap::<_, NotificationHandler>::default()));
+        let request_handlers = Arc::new(Mutex::new(HashMap::<_, RequestHandler>::default()));
+        let response_handlers =
+            Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+
+        let input_task = cx.spawn({
+            let url = url.clone();
+            let headers = headers.clone();
+            let http_client = http_client.clone();
+            let handlers = MessageHandlers {
+                notification_handlers: notification_handlers.clone(),
+                request_handlers: request_handlers.clone(),
+                response_handlers: response_handlers.clone(),
+                outbound_tx: outbound_tx.clone(),
+            };
+            move |cx| {
+                Self::handle_sse_input(url, headers, http_client, endpoint_tx, handlers, cx)
+                    .log_err()
+            }
+        });
+        let output_task = cx.background_executor().spawn({
+            Self::handle_sse_output(
+                endpoint_rx,
+                headers,
+                http_client,
+                outbound_rx,
+                output_done_tx,
+                response_handlers.clone(),
+            )
+            .log_err()
+        });
+
+        Ok(Self {
+            server_id,
+            notification_handlers,
+            request_handlers,
+            response_handlers,
+            name: url.host_str().unwrap_or_default().into(),
+            next_id: Default::default(),
+            outbound_tx,
+            executor: cx.background_executor().clone(),
+            io_tasks: Mutex::new(Some((input_task, output_task))),
+            output_done_rx: Mutex::new(Some(output_done_rx)),
+            server: Arc::new(Mutex::new(None)),
+        })
+    }
+
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC messages, and dispatches them to the appropriate handlers.
+    /// It processes responses (which are matched to pending requests), notifications
+    /// (which trigger registered handlers) and requests from the server (which are
+    /// answered by registered handlers).
     async fn handle_input<Stdout>(
         stdout: Stdout,
-        notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
-        response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        handlers: MessageHandlers,
         cx: AsyncAppContext,
     ) -> anyhow::Result<()>
     where
@@ -246,27 +347,123 @@ impl Client {
                 return Ok(());
             }
 
-            let content = buffer.trim();
+            handlers.handle_message(buffer.trim(), &cx);
 
-            if !content.is_empty() {
-                if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
-                    if let Some(handlers) = response_handlers.lock().as_mut() {
-                        if let Some(handler) = handlers.remove(&response.id) {
-                            handler(Ok(content.to_string()));
-                        }
-                    }
-                } else if let Ok(notification) = serde_json::from_str::<AnyNotification>(content) {
-                    let mut notification_handlers = notification_handlers.lock();
-                    if let Some(handler) =
-                        notification_handlers.get_mut(notification.method.as_str())
-                    {
-                        handler(notification.params.unwrap_or(Value::Null), cx.clone());
-                    }
+            smol::future::yield_now().await;
+        }
+    }
+
+    /// Handles the event stream of a context server connected over HTTP.
+    ///
+    /// The first `endpoint` event tells where to POST messages to, and is forwarded to the
+    /// output task. Every `message` event carries a JSON-RPC response or notification.
+    /// Once the stream ends or fails, the pending requests fail, as no response can arrive anymore.
+    async fn handle_sse_input(
+        url: Url,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        endpoint_tx: oneshot::Sender<Url>,
+        handlers: MessageHandlers,
+        cx: AsyncAppContext,
+    ) -> anyhow::Result<()> {
+        let _fail_pending_requests = util::defer({
+            let response_handlers = handlers.response_handlers.clone();
+            move || {
+                let Some(response_handlers) = response_handlers.lock().take() else {
+                    return;
+                };
+                for (_, handler) in response_handlers {
+                    handler(Err(Error {
+                        code: INTERNAL_ERROR,
+                        message: "context server closed its event stream".into(),
+                    }));
                 }
             }
+        });
+        let mut request = http_client::Request::builder()
+            .method(Method::GET)
+            .uri(url.as_str())
+            .header("Accept", "text/event-stream");
+        for (name, value) in &headers {
+            request = request.header(name, value);
+        }
+        let response = http_client.send(request.body(AsyncBody::empty())?).await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "failed to connect to context server at {url}: {}",
+                response.status()
+            );
+        }
 
+        let mut endpoint_tx = Some(endpoint_tx);
+        let mut parser = SseParser::default();
+        let mut lines = BufReader::new(response.into_body()).lines();
+        while let Some(line) = lines.next().await {
+            let Some(event) = parser.push_line(&line?) else {
+                continue;
+            };
+            match event.name.as_str() {
+                "endpoint" => {
+                    let endpoint = sse_endpoint(&url, event.data.trim())?;
+                    log::debug!("context server endpoint: {endpoint}");
+                    if let Some(endpoint_tx) = endpoint_tx.take() {
+                        endpoint_tx.send(endpoint).ok();
+                    }
+                }
+                "" | "message" => handlers.handle_message(event.data.trim(), &cx),
+                name => log::debug!("ignoring context server event {name:?}"),
+            }
             smol::future::yield_now().await;
         }
+        Ok(())
+    }
+
+    /// POSTs the outbound messages to the endpoint announced by a context server connected over HTTP.
+    async fn handle_sse_output(
+        endpoint_rx: oneshot::Receiver<Url>,
+        headers: HashMap<String, String>,
+        http_client: Arc<dyn HttpClient>,
+        outbound_rx: channel::Receiver<String>,
+        output_done_tx: barrier::Sender,
+        response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    ) -> anyhow::Result<()> {
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        let endpoint = endpoint_rx
+            .await
+            .context("context server closed its event stream without sending an endpoint")?;
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing message: {}", message);
+
+            let mut request = http_client::Request::builder()
+                .method(Method::POST)
+                .uri(endpoint.as_str())
+                .header("Content-Type", "application/json");
+            for (name, value) in &headers {
+                request = request.header(name, value);
+            }
+            let response = http_client
+                .send(request.body(AsyncBody::from(message))?)
+                .await;
+            match response {
+                Ok(response) if !response.status().is_success() => {
+                    log::error!(
+                        "context server rejected message with status {}",
+                        response.status()
+                    );
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    Err::<(), _>(error).log_err();
+                }
+            }
+        }
+        drop(output_done_tx);
+        Ok(())
     }
 
     /// Handles the stderr output from the context server.
@@ -375,7 +572,7 @@ impl Client {
                             Err(anyhow!("Invalid response: no result or error"))
                         }
                     }
-                    Err(_) => anyhow::bail!("cancelled")
+                    Err(error) => Err(anyhow!(error.message))
                 }
             }
             _ = timeout => {
@@ -407,6 +604,21 @@ impl Client {
             .insert(method, Box::new(f));
     }
 
+    /// Registers a handler for the requests with the given method that the context server sends.
+    ///
+    /// The value that the returned future resolves to is sent back as the result of the request,
+    /// or as an error if it fails.
+    pub fn on_request<F, Fut>(&self, method: &'static str, mut f: F)
+    where
+        F: 'static + Send + FnMut(Value, AsyncAppContext) -> Fut,
+        Fut: 'static + Future<Output = Result<Value>>,
+    {
+        self.request_handlers.lock().insert(
+            method,
+            Box::new(move |params, cx| f(params, cx).boxed_local()),
+        );
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -424,6 +636,127 @@ impl Drop for Client {
     }
 }
 
+/// The handlers of the messages that a context server sends to the client.
+struct MessageHandlers {
+    notification_handlers: Arc<Mutex<HashMap<&'static str, NotificationHandler>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
+    response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    outbound_tx: channel::Sender<String>,
+}
+
+impl MessageHandlers {
+    /// Dispatches a JSON-RPC message received from the server to its handler.
+    fn handle_message(&self, content: &str, cx: &AsyncAppContext) {
+        if content.is_empty() {
+            return;
+        }
+
+        if let Ok(request) = serde_json::from_str::<AnyRequest>(content) {
+            self.handle_request(request, cx);
+        } else if let Ok(response) = serde_json::from_str::<AnyResponse>(content) {
+            if let Some(handlers) = self.response_handlers.lock().as_mut() {
+                if let Some(handler) = handlers.remove(&response.id) {
+                    handler(Ok(content.to_string()));
+                }
+            }
+        } else if let Ok(notification) = serde_json::from_str::<AnyNotification>(content) {
+            let mut notification_handlers = self.notification_handlers.lock();
+            if let Some(handler) = notification_handlers.get_mut(notification.method.as_str()) {
+                handler(notification.params.unwrap_or(Value::Null), cx.clone());
+            }
+        }
+    }
+
+    fn handle_request(&self, request: AnyRequest, cx: &AsyncAppContext) {
+        let response = self
+            .request_handlers
+            .lock()
+            .get_mut(request.method.as_str())
+            .map(|handler| handler(request.params.unwrap_or(Value::Null), cx.clone()));
+        let outbound_tx = self.outbound_tx.clone();
+        cx.spawn(|_| {
+            async move {
+                let (result, error) = match response {
+                    Some(response) => match response.await {
+                        Ok(result) => (Some(result), None),
+                        Err(error) => (
+                            None,
+                            Some(Error {
+                                code: INTERNAL_ERROR,
+                                message: error.to_string(),
+                            }),
+                        ),
+                    },
+                    None => (
+                        None,
+                        Some(Error {
+                            code: METHOD_NOT_FOUND,
+                            message: format!("method not found: {}", request.method),
+                        }),
+                    ),
+                };
+                let response = serde_json::to_string(&OutgoingResponse {
+                    jsonrpc: JSON_RPC_VERSION,
+                    id: request.id,
+                    result,
+                    error,
+                })?;
+                outbound_tx.try_send(response)?;
+                anyhow::Ok(())
+            }
+            .log_err()
+        })
+        .detach();
+    }
+}
+
+/// An event received on a Server-Sent Events stream.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct SseEvent {
+    name: String,
+    data: String,
+}
+
+/// Assembles the lines of a Server-Sent Events stream into events.
+#[derive(Default)]
+struct SseParser {
+    event: SseEvent,
+    has_data: bool,
+}
+
+impl SseParser {
+    /// Processes a line of the stream, returning the event it completes, if any.
+    fn push_line(&mut self, line: &str) -> Option<SseEvent> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            if !self.has_data {
+                self.event = SseEvent::default();
+                return None;
+            }
+            self.has_data = false;
+            return Some(std::mem::take(&mut self.event));
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event.name = value.to_string(),
+            "data" => {
+                if self.has_data {
+                    self.event.data.push('\n');
+                }
+                self.event.data.push_str(value);
+                self.has_data = true;
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
 impl fmt::Display for ContextServerId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.0.fmt(f)
@@ -438,3 +771,301 @@ impl fmt::Debug for Client {
             .finish_non_exhaustive()
     }
 }
+
+/// Resolves the endpoint announced by a context server against the URL of its event stream.
+///
+/// The endpoint has to be on the same origin as the event stream, so that the configured headers,
+/// which can hold credentials, are only ever sent to the server they were configured for.
+fn sse_endpoint(url: &Url, data: &str) -> anyhow::Result<Url> {
+    let endpoint = url
+        .join(data)
+        .context("context server sent an invalid endpoint")?;
+    if endpoint.origin() != url.origin() {
+        anyhow::bail!("context server sent an endpoint on another origin: {endpoint}");
+    }
+    Ok(endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{channel::mpsc, StreamExt as _, TryStreamExt as _};
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        let mut events = Vec::new();
+        for line in [
+            ": a comment",
+            "event: endpoint",
+            "data: /messages?session=1",
+            "",
+            "",
+            "data: {\"a\":",
+            "data:1}\r",
+            "",
+        ] {
+            events.extend(parser.push_line(line));
+        }
+        assert_eq!(
+            events,
+            [
+                SseEvent {
+                    name: "endpoint".into(),
+                    data: "/messages?session=1".into(),
+                },
+                SseEvent {
+                    name: String::new(),
+                    data: "{\"a\":\n1}".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sse_endpoint() {
+        let url = Url::parse("https://example.com:8080/sse?token=1").unwrap();
+        assert_eq!(
+            sse_endpoint(&url, "/messages?session=1").unwrap().as_str(),
+            "https://example.com:8080/messages?session=1"
+        );
+        assert_eq!(
+            sse_endpoint(&url, "https://example.com:8080/messages")
+                .unwrap()
+                .as_str(),
+            "https://example.com:8080/messages"
+        );
+        for endpoint in [
+            "https://attacker.com/messages",
+            "//attacker.com:8080/messages",
+            "http://example.com:8080/messages",
+            "https://example.com/messages",
+        ] {
+            assert_eq!(
+                sse_endpoint(&url, endpoint).unwrap_err().to_string(),
+                format!(
+                    "context server sent an endpoint on another origin: {}",
+                    url.join(endpoint).unwrap()
+                )
+            );
+        }
+    }
+
+    #[gpui::test]
+    async fn test_sse_transport(cx: &mut TestAppContext) {
+        let (events_tx, events_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+        events_tx
+            .unbounded_send(Ok(b"event: endpoint\ndata: /messages\n\n".to_vec()))
+            .unwrap();
+        let events_rx = Mutex::new(Some(events_rx));
+
+        let http_client = FakeHttpClient::create(move |request| {
+            let events_rx = if request.method() == Method::GET {
+                events_rx.lock().take()
+            } else {
+                None
+            };
+            let events_tx = events_tx.clone();
+            async move {
+                assert_eq!(request.headers()["Authorization"], "Bearer token");
+                if request.method() == Method::GET {
+                    assert_eq!(request.uri().to_string(), "http://localhost:8080/sse");
+                    let events_rx = events_rx.expect("connected twice");
+                    return Ok(Response::builder()
+                        .body(AsyncBody::from_reader(events_rx.into_async_read()))?);
+                }
+
+                assert_eq!(request.uri().to_string(), "http://localhost:8080/messages");
+                let mut body = String::new();
+                futures::AsyncReadExt::read_to_string(&mut request.into_body(), &mut body).await?;
+                let request: Value = serde_json::from_str(&body)?;
+                assert_eq!(request["method"], "ping");
+                let response = serde_json::json!({
+                    "jsonrpc": JSON_RPC_VERSION,
+                    "id": request["id"],
+                    "result": {},
+                });
+                events_tx
+                    .unbounded_send(Ok(format!("event: message\ndata: {response}\n\n").into()))?;
+                Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+            }
+        });
+
+        let client = Client::new_sse(
+            ContextServerId("test".into()),
+            Url::parse("http://localhost:8080/sse").unwrap(),
+            HashMap::from_iter([("Authorization".to_string(), "Bearer token".to_string())]),
+            http_client,
+            cx.to_async(),
+        )
+        .unwrap();
+
+        let response = client.request::<Value>("ping", ()).await.unwrap();
+        assert_eq!(response, serde_json::json!({}));
+    }
+
+    #[gpui::test]
+    async fn test_server_requests(cx: &mut TestAppContext) {
+        let (events_tx, events_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+        let events_rx = Mutex::new(Some(events_rx));
+        let (posted_tx, mut posted_rx) = mpsc::unbounded::<Value>();
+        events_tx
+            .unbounded_send(Ok(b"event: endpoint\ndata: /messages\n\n".to_vec()))
+            .unwrap();
+        for request in [
+            r#"{"jsonrpc":"2.0","id":"a","method":"roots/list"}"#,
+            r#"{"jsonrpc":"2.0","id":"b","method":"unknown"}"#,
+        ] {
+            events_tx
+                .unbounded_send(Ok(format!("event: message\ndata: {request}\n\n").into()))
+                .unwrap();
+        }
+
+        let http_client = FakeHttpClient::create(move |request| {
+            let events_rx = if request.method() == Method::GET {
+                events_rx.lock().take()
+            } else {
+                None
+            };
+            let posted_tx = posted_tx.clone();
+            async move {
+                if let Some(events_rx) = events_rx {
+                    return Ok(Response::builder()
+                        .body(AsyncBody::from_reader(events_rx.into_async_read()))?);
+                }
+                let mut body = String::new();
+                futures::AsyncReadExt::read_to_string(&mut request.into_body(), &mut body).await?;
+                posted_tx.unbounded_send(serde_json::from_str(&body)?)?;
+                Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+            }
+        });
+
+        let client = Client::new_sse(
+            ContextServerId("test".into()),
+            Url::parse("http://localhost:8080/sse").unwrap(),
+            HashMap::default(),
+            http_client,
+            cx.to_async(),
+        )
+        .unwrap();
+        client.on_request("roots/list", |_, _| async {
+            Ok(serde_json::json!({ "roots": [] }))
+        });
+
+        let mut responses = vec![
+            posted_rx.next().await.unwrap(),
+            posted_rx.next().await.unwrap(),
+        ];
+        responses.sort_by_key(|response| response["id"].to_string());
+        assert_eq!(
+            responses[0],
+            serde_json::json!({ "jsonrpc": "2.0", "id": "a", "result": { "roots": [] } })
+        );
+        assert_eq!(responses[1]["id"], "b");
+        assert_eq!(responses[1]["error"]["code"], METHOD_NOT_FOUND);
+        drop(events_tx);
+    }
+
+    #[gpui::test]
+    async fn test_sse_transport_over_http(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let url = serve_sse(|request| match request["method"].as_str() {
+            Some("ping") => Some(serde_json::json!({
+                "jsonrpc": JSON_RPC_VERSION,
+                "id": request["id"],
+                "result": {},
+            })),
+            _ => None,
+        });
+
+        let client = Client::new_sse(
+            ContextServerId("test".into()),
+            url,
+            HashMap::default(),
+            Arc::new(reqwest_client::ReqwestClient::new()),
+            cx.to_async(),
+        )
+        .unwrap();
+
+        let response = client.request::<Value>("ping", ()).await.unwrap();
+        assert_eq!(response, serde_json::json!({}));
+
+        // The server closes the event stream instead of answering, so the request fails
+        // rather than waiting for the timeout, and so do the requests sent afterwards.
+        let error = client.request::<Value>("hang up", ()).await.unwrap_err();
+        assert_eq!(error.to_string(), "context server closed its event stream");
+        let error = client.request::<Value>("ping", ()).await.unwrap_err();
+        assert_eq!(error.to_string(), "server shut down");
+    }
+
+    /// Serves the HTTP with Server-Sent Events transport on a local port, returning the URL of
+    /// its event stream. Each message POSTed to the server is passed to `respond`, and the
+    /// message it returns is sent on the event stream, which is closed when it returns `None`.
+    fn serve_sse(mut respond: impl 'static + Send + FnMut(Value) -> Option<Value>) -> Url {
+        use std::io::{BufRead as _, BufReader, Read as _, Write as _};
+        use std::net::{TcpListener, TcpStream};
+
+        fn read_request(connection: &TcpStream) -> (String, Vec<u8>) {
+            let mut reader = BufReader::new(connection);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                let header = header.trim_end();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            (request_line, body)
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/sse", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            let mut event_stream = None;
+            for connection in listener.incoming() {
+                let mut connection = connection.unwrap();
+                let (request_line, body) = read_request(&connection);
+                if request_line.starts_with("GET /sse ") {
+                    connection
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+                              event: endpoint\ndata: /messages\n\n",
+                        )
+                        .unwrap();
+                    event_stream = Some(connection);
+                    continue;
+                }
+
+                assert!(request_line.starts_with("POST /messages "));
+                connection
+                    .write_all(
+                        b"HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    )
+                    .unwrap();
+                let event_stream = event_stream.as_mut().expect("posted before connecting");
+                match respond(serde_json::from_slice(&body).unwrap()) {
+                    Some(message) => {
+                        write!(event_stream, "event: message\ndata: {message}\n\n").unwrap();
+                    }
+                    None => {
+                        event_stream.shutdown(std::net::Shutdown::Both).ok();
+                        return;
+                    }
+                }
+            }
+        });
+        url
+    }
+}
@@ -1,9 +1,12 @@
 pub mod client;
 mod context_server_tool;
 mod extension_context_server;
+#[cfg(any(test, feature = "test-support"))]
+pub mod fake_server;
 pub mod manager;
 pub mod protocol;
 mod registry;
+mod sampling;
 pub mod types;
 
 use command_palette_hooks::CommandPaletteFilter;
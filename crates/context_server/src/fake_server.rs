@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use futures::{channel::mpsc, StreamExt as _, TryStreamExt as _};
+use http_client::{AsyncBody, FakeHttpClient, HttpClient, Method, Response};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+
+use crate::types;
+
+/// A context server that's reached over SSE, for tests.
+///
+/// Install its [`FakeContextServer::http_client`] with `AppContext::set_http_client` and connect
+/// to any URL. Its requests are answered by the handler, except for `initialize`, which is
+/// answered with the given capabilities.
+pub struct FakeContextServer {
+    events_tx: mpsc::UnboundedSender<std::io::Result<Vec<u8>>>,
+    posted_rx: mpsc::UnboundedReceiver<Value>,
+    pub http_client: Arc<dyn HttpClient>,
+}
+
+impl FakeContextServer {
+    /// Creates a server that answers requests with the result returned by `handle_request`,
+    /// which is given the method and the parameters of each request. It doesn't answer the
+    /// requests for which it returns `None`.
+    pub fn new(
+        capabilities: Value,
+        handle_request: impl 'static + Send + Sync + Fn(&str, &Value) -> Option<Value>,
+    ) -> Self {
+        let (events_tx, events_rx) = mpsc::unbounded::<std::io::Result<Vec<u8>>>();
+        events_tx
+            .unbounded_send(Ok(b"event: endpoint\ndata: /messages\n\n".to_vec()))
+            .unwrap();
+        let events_rx = Mutex::new(Some(events_rx));
+        let (posted_tx, posted_rx) = mpsc::unbounded::<Value>();
+        let handle_request = Arc::new(handle_request);
+
+        let http_client = FakeHttpClient::create({
+            let events_tx = events_tx.clone();
+            move |request| {
+                let events_rx = if request.method() == Method::GET {
+                    events_rx.lock().take()
+                } else {
+                    None
+                };
+                let events_tx = events_tx.clone();
+                let posted_tx = posted_tx.clone();
+                let capabilities = capabilities.clone();
+                let handle_request = handle_request.clone();
+                async move {
+                    if let Some(events_rx) = events_rx {
+                        return Ok(Response::builder()
+                            .body(AsyncBody::from_reader(events_rx.into_async_read()))?);
+                    }
+
+                    let mut body = String::new();
+                    futures::AsyncReadExt::read_to_string(&mut request.into_body(), &mut body)
+                        .await?;
+                    let message: Value = serde_json::from_str(&body)?;
+                    let result = match (message["method"].as_str(), message.get("id")) {
+                        (Some("initialize"), Some(_)) => Some(json!({
+                            "protocolVersion": types::LATEST_PROTOCOL_VERSION,
+                            "capabilities": capabilities,
+                            "serverInfo": { "name": "fake", "version": "1.0.0" },
+                        })),
+                        (Some(method), Some(_)) => handle_request(method, &message["params"]),
+                        _ => None,
+                    };
+                    if let Some(result) = result {
+                        let response = json!({
+                            "jsonrpc": "2.0",
+                            "id": message["id"],
+                            "result": result,
+                        });
+                        events_tx
+                            .unbounded_send(Ok(
+                                format!("event: message\ndata: {response}\n\n").into()
+                            ))?;
+                    }
+                    posted_tx.unbounded_send(message)?;
+                    Ok(Response::builder().status(202).body(AsyncBody::empty())?)
+                }
+            }
+        });
+
+        Self {
+            events_tx,
+            posted_rx,
+            http_client,
+        }
+    }
+
+    /// Sends a request or a notification to the client.
+    pub fn send(&self, message: Value) {
+        self.events_tx
+            .unbounded_send(Ok(format!("event: message\ndata: {message}\n\n").into()))
+            .unwrap();
+    }
+
+    /// Returns the next message the client sent, whether it's a request, a notification or a
+    /// response to one of the server's requests.
+    pub async fn receive(&mut self) -> Value {
+        self.posted_rx.next().await.unwrap()
+    }
+}
@@ -17,14 +17,17 @@
 use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context as _, Result};
 use collections::HashMap;
 use command_palette_hooks::CommandPaletteFilter;
-use gpui::{AsyncAppContext, EventEmitter, Model, ModelContext, Subscription, Task, WeakModel};
+use gpui::{
+    AppContext, AsyncAppContext, EventEmitter, Model, ModelContext, Subscription, Task, WeakModel,
+};
 use log;
 use parking_lot::RwLock;
 use project::Project;
 use settings::{Settings, SettingsStore};
+use url::Url;
 use util::ResultExt as _;
 
 use crate::{ContextServerSettings, ServerConfig};
@@ -61,27 +64,73 @@ impl ContextServer {
         self.client.read().clone()
     }
 
-    pub async fn start(self: Arc<Self>, cx: &AsyncAppContext) -> Result<()> {
+    pub async fn start(
+        self: Arc<Self>,
+        manager: WeakModel<ContextServerManager>,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
         log::info!("starting context server {}", self.id);
-        let Some(command) = &self.config.command else {
-            bail!("no command specified for server {}", self.id);
+        let client = if let Some(url) = &self.config.url {
+            let url = Url::parse(url)
+                .with_context(|| format!("invalid url for context server {}", self.id))?;
+            let http_client = cx.update(|cx| cx.http_client())?;
+            Client::new_sse(
+                client::ContextServerId(self.id.clone()),
+                url,
+                self.config.headers.clone().unwrap_or_default(),
+                http_client,
+                cx.clone(),
+            )?
+        } else {
+            let Some(command) = &self.config.command else {
+                bail!("no command or url specified for server {}", self.id);
+            };
+            Client::new(
+                client::ContextServerId(self.id.clone()),
+                client::ModelContextServerBinary {
+                    executable: Path::new(&command.path).to_path_buf(),
+                    args: command.args.clone(),
+                    env: command.env.clone(),
+                },
+                cx.clone(),
+            )?
         };
-        let client = Client::new(
-            client::ContextServerId(self.id.clone()),
-            client::ModelContextServerBinary {
-                executable: Path::new(&command.path).to_path_buf(),
-                args: command.args.clone(),
-                env: command.env.clone(),
-            },
-            cx.clone(),
-        )?;
-
-        let protocol = crate::protocol::ModelContextProtocol::new(client);
+
+        let mut protocol = crate::protocol::ModelContextProtocol::new(client);
+        protocol.on_create_message({
+            let server_id = self.id.clone();
+            move |request, cx| crate::sampling::create_message(server_id.clone(), request, cx)
+        });
+        protocol.on_list_roots({
+            let manager = manager.clone();
+            move |cx| {
+                let roots = manager.read_with(&cx, |manager, cx| manager.roots(cx));
+                async move {
+                    Ok(types::ListRootsResponse {
+                        roots: roots?,
+                        meta: None,
+                    })
+                }
+            }
+        });
         let client_info = types::Implementation {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
         let initialized_protocol = protocol.initialize(client_info).await?;
+        initialized_protocol.on_resource_updated({
+            let server_id = self.id.clone();
+            move |uri, mut cx| {
+                manager
+                    .update(&mut cx, |_, cx| {
+                        cx.emit(Event::ResourceUpdated {
+                            server_id: server_id.clone(),
+                            uri,
+                        })
+                    })
+                    .ok();
+            }
+        });
 
         log::debug!(
             "context server {} initialized: {:?}",
@@ -112,8 +161,17 @@ pub struct ContextServerManager {
 }
 
 pub enum Event {
-    ServerStarted { server_id: Arc<str> },
-    ServerStopped { server_id: Arc<str> },
+    ServerStarted {
+        server_id: Arc<str>,
+    },
+    ServerStopped {
+        server_id: Arc<str>,
+    },
+    /// A resource that was subscribed to with [`crate::protocol::InitializedContextServerProtocol::subscribe_resource`] changed.
+    ResourceUpdated {
+        server_id: Arc<str>,
+        uri: Url,
+    },
 }
 
 impl EventEmitter<Event> for ContextServerManager {}
@@ -132,6 +190,12 @@ impl ContextServerManager {
                 cx.observe_global::<SettingsStore>(|this, cx| {
                     this.available_context_servers_changed(cx);
                 }),
+                cx.subscribe(&project, |this, _project, event, _cx| match event {
+                    project::Event::WorktreeAdded(_) | project::Event::WorktreeRemoved(_) => {
+                        this.roots_changed();
+                    }
+                    _ => {}
+                }),
             ],
             project,
             registry,
@@ -173,6 +237,29 @@ impl ContextServerManager {
         }
     }
 
+    /// The roots that are exposed to the context servers, which are the project's visible worktrees.
+    fn roots(&self, cx: &AppContext) -> Vec<types::Root> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                let worktree = worktree.read(cx);
+                Some(types::Root {
+                    uri: Url::from_file_path(worktree.abs_path()).ok()?,
+                    name: Some(worktree.root_name().to_string()),
+                })
+            })
+            .collect()
+    }
+
+    fn roots_changed(&self) {
+        for server in self.servers() {
+            if let Some(protocol) = server.client() {
+                protocol.notify_roots_list_changed().log_err();
+            }
+        }
+    }
+
     pub fn get_server(&self, id: &str) -> Option<Arc<ContextServer>> {
         self.servers
             .get(id)
@@ -191,7 +278,7 @@ impl ContextServerManager {
                 server.stop()?;
                 let config = server.config();
                 let new_server = Arc::new(ContextServer::new(id.clone(), config));
-                new_server.clone().start(&cx).await?;
+                new_server.clone().start(this.clone(), &cx).await?;
                 this.update(&mut cx, |this, cx| {
                     this.servers.insert(id.clone(), new_server);
                     cx.emit(Event::ServerStopped {
@@ -234,7 +321,7 @@ impl ContextServerManager {
             registry.read_with(&cx, |registry, _| registry.context_server_factories())?
         {
             let config = desired_servers.entry(id).or_default();
-            if config.command.is_none() {
+            if config.command.is_none() && config.url.is_none() {
                 if let Some(extension_command) = factory(project.clone(), &cx).await.log_err() {
                     config.command = Some(extension_command);
                 }
@@ -276,7 +363,7 @@ impl ContextServerManager {
         }
 
         for (id, server) in servers_to_start {
-            if server.start(&cx).await.log_err().is_some() {
+            if server.start(this.clone(), &cx).await.log_err().is_some() {
                 this.update(&mut cx, |_, cx| {
                     cx.emit(Event::ServerStarted { server_id: id })
                 })?;
@@ -286,3 +373,159 @@ impl ContextServerManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::{Context as _, TestAppContext};
+    use language_model::{LanguageModelRegistry, MessageContent, Role};
+    use project::FakeFs;
+    use serde_json::json;
+    use workspace::AppState;
+
+    use super::*;
+    use crate::fake_server::FakeContextServer;
+
+    #[gpui::test]
+    async fn test_roots_and_sampling(cx: &mut TestAppContext) {
+        let mut server = FakeContextServer::new(json!({}), |_, _| None);
+        let http_client = server.http_client.clone();
+
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            LanguageModelRegistry::test(cx);
+            cx.set_http_client(http_client);
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<ContextServerSettings>(cx, |settings| {
+                    settings.context_servers.insert(
+                        "test".into(),
+                        ServerConfig {
+                            url: Some("http://localhost:8080/sse".into()),
+                            ..Default::default()
+                        },
+                    );
+                });
+            });
+        });
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/one", json!({})).await;
+        fs.insert_tree("/two", json!({})).await;
+        fs.insert_tree("/three", json!({})).await;
+        let project = Project::test(fs, ["/one".as_ref(), "/two".as_ref()], cx).await;
+        let registry = cx.update(ContextServerFactoryRegistry::default_global);
+        let _manager = cx.new_model(|cx| ContextServerManager::new(registry, project.clone(), cx));
+        let cx = cx.add_empty_window();
+        cx.update(|cx| cx.activate_window());
+
+        let initialize = server.receive().await;
+        assert_eq!(initialize["method"], "initialize");
+        assert_eq!(
+            initialize["params"]["capabilities"],
+            json!({ "sampling": {}, "roots": { "listChanged": true } })
+        );
+        assert_eq!(
+            server.receive().await["method"],
+            "notifications/initialized"
+        );
+
+        server.send(json!({ "jsonrpc": "2.0", "id": "roots", "method": "roots/list" }));
+        assert_eq!(
+            server.receive().await,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "roots",
+                "result": {
+                    "roots": [
+                        { "uri": "file:///one", "name": "one" },
+                        { "uri": "file:///two", "name": "two" },
+                    ],
+                },
+            })
+        );
+
+        cx.run_until_parked();
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree("/three", true, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            server.receive().await["method"],
+            "notifications/roots/list_changed"
+        );
+
+        let sampling_request = |id: &str, max_tokens: Option<u32>| {
+            let mut request = json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "sampling/createMessage",
+                "params": {
+                    "messages": [{ "role": "user", "content": { "type": "text", "text": "Hi" } }],
+                    "systemPrompt": "Be brief",
+                    "modelPreferences": { "hints": [{ "name": "claude" }, { "name": "FAKE" }] },
+                },
+            });
+            if let Some(max_tokens) = max_tokens {
+                request["params"]["maxTokens"] = max_tokens.into();
+            }
+            request
+        };
+
+        server.send(sampling_request("allowed", Some(20)));
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        let model = cx.update(|cx| {
+            LanguageModelRegistry::read_global(cx)
+                .active_model()
+                .unwrap()
+        });
+        let fake_model = model.as_fake();
+        let completion = fake_model.pending_completions().pop().unwrap();
+        assert_eq!(completion.max_tokens, Some(20));
+        assert_eq!(
+            completion
+                .messages
+                .iter()
+                .map(|message| (message.role, message.content.clone()))
+                .collect::<Vec<_>>(),
+            [
+                (Role::System, vec![MessageContent::Text("Be brief".into())]),
+                (Role::User, vec![MessageContent::Text("Hi".into())]),
+            ]
+        );
+        fake_model.stream_last_completion_response("Hello!".into());
+        fake_model.end_last_completion_stream();
+        assert_eq!(
+            server.receive().await,
+            json!({
+                "jsonrpc": "2.0",
+                "id": "allowed",
+                "result": {
+                    "role": "assistant",
+                    "content": { "type": "text", "text": "Hello!" },
+                    "model": "Fake",
+                    "stopReason": "endTurn",
+                },
+            })
+        );
+
+        server.send(sampling_request("denied", Some(20)));
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(1);
+        let response = server.receive().await;
+        assert_eq!(response["id"], "denied");
+        assert!(response["error"].is_object());
+        assert!(fake_model.pending_completions().is_empty());
+
+        server.send(sampling_request("no-limit", None));
+        let response = server.receive().await;
+        assert_eq!(response["id"], "no-limit");
+        assert!(response["error"].is_object());
+        assert!(!cx.has_pending_prompt());
+    }
+}
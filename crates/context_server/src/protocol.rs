@@ -7,17 +7,55 @@
 
 use anyhow::Result;
 use collections::HashMap;
+use futures::Future;
+use gpui::AsyncAppContext;
+use serde::{de::DeserializeOwned, Serialize};
+use url::Url;
 
 use crate::client::Client;
 use crate::types;
 
 pub struct ModelContextProtocol {
     inner: Client,
+    supports_sampling: bool,
+    supports_roots: bool,
 }
 
 impl ModelContextProtocol {
     pub fn new(inner: Client) -> Self {
-        Self { inner }
+        Self {
+            inner,
+            supports_sampling: false,
+            supports_roots: false,
+        }
+    }
+
+    /// Answers the server's `sampling/createMessage` requests with the given handler,
+    /// and advertises the sampling capability to the server.
+    pub fn on_create_message<F, Fut>(&mut self, f: F)
+    where
+        F: 'static + Send + FnMut(types::CreateMessageRequest, AsyncAppContext) -> Fut,
+        Fut: 'static + Future<Output = Result<types::CreateMessageResult>>,
+    {
+        self.supports_sampling = true;
+        on_request(&self.inner, types::RequestType::CreateMessage, f);
+    }
+
+    /// Answers the server's `roots/list` requests with the given handler,
+    /// and advertises the roots capability to the server.
+    ///
+    /// Call [`InitializedContextServerProtocol::notify_roots_list_changed`] when the roots change.
+    pub fn on_list_roots<F, Fut>(&mut self, mut f: F)
+    where
+        F: 'static + Send + FnMut(AsyncAppContext) -> Fut,
+        Fut: 'static + Future<Output = Result<types::ListRootsResponse>>,
+    {
+        self.supports_roots = true;
+        on_request(
+            &self.inner,
+            types::RequestType::ListRoots,
+            move |_: serde_json::Value, cx| f(cx),
+        );
     }
 
     fn supported_protocols() -> Vec<types::ProtocolVersion> {
@@ -34,8 +72,10 @@ impl ModelContextProtocol {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
             capabilities: types::ClientCapabilities {
                 experimental: None,
-                sampling: None,
-                roots: None,
+                sampling: self.supports_sampling.then(|| serde_json::json!({})),
+                roots: self.supports_roots.then_some(types::RootsCapabilities {
+                    list_changed: Some(true),
+                }),
             },
             meta: None,
             client_info,
@@ -69,6 +109,19 @@ impl ModelContextProtocol {
     }
 }
 
+fn on_request<P, R, F, Fut>(client: &Client, request_type: types::RequestType, mut f: F)
+where
+    P: DeserializeOwned,
+    R: Serialize,
+    F: 'static + Send + FnMut(P, AsyncAppContext) -> Fut,
+    Fut: 'static + Future<Output = Result<R>>,
+{
+    client.on_request(request_type.as_str(), move |params, cx| {
+        let response = serde_json::from_value(params).map(|params| f(params, cx));
+        async move { Ok(serde_json::to_value(response?.await?)?) }
+    });
+}
+
 pub struct InitializedContextServerProtocol {
     inner: Client,
     pub initialize: types::InitializeResponse,
@@ -136,6 +189,121 @@ impl InitializedContextServerProtocol {
         Ok(response)
     }
 
+    /// List all the MCP resources, requesting the following pages until there are none left.
+    pub async fn list_all_resources(&self) -> Result<Vec<types::Resource>> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let mut resources = Vec::new();
+        let mut cursor = None;
+        loop {
+            let params = match cursor {
+                Some(cursor) => serde_json::json!({ "cursor": cursor }),
+                None => serde_json::json!({}),
+            };
+            let response: types::ResourcesListResponse = self
+                .inner
+                .request(types::RequestType::ResourcesList.as_str(), params)
+                .await?;
+            resources.extend(response.resources);
+            match response.next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => return Ok(resources),
+            }
+        }
+    }
+
+    /// List the MCP resource templates.
+    pub async fn list_resource_templates(&self) -> Result<types::ListResourceTemplatesResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let response: types::ListResourceTemplatesResponse = self
+            .inner
+            .request(
+                types::RequestType::ListResourceTemplates.as_str(),
+                serde_json::json!({}),
+            )
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Reads the contents of an MCP resource.
+    pub async fn read_resource(&self, uri: Url) -> Result<types::ResourcesReadResponse> {
+        self.check_capability(ServerCapability::Resources)?;
+
+        let params = types::ResourcesReadParams { uri, meta: None };
+
+        let response: types::ResourcesReadResponse = self
+            .inner
+            .request(types::RequestType::ResourcesRead.as_str(), params)
+            .await?;
+
+        Ok(response)
+    }
+
+    /// Whether the server sends notifications when a resource it was subscribed to changes.
+    pub fn supports_resource_subscriptions(&self) -> bool {
+        self.initialize
+            .capabilities
+            .resources
+            .as_ref()
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// Asks the server to notify the client when the given resource changes.
+    ///
+    /// The notifications are delivered to the handler registered with [`Self::on_resource_updated`].
+    pub async fn subscribe_resource(&self, uri: Url) -> Result<()> {
+        if !self.supports_resource_subscriptions() {
+            return Err(anyhow::anyhow!(
+                "Server does not support resource subscriptions"
+            ));
+        }
+
+        let params = types::ResourcesSubscribeParams { uri, meta: None };
+
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesSubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Stops the notifications about a resource that was subscribed to.
+    pub async fn unsubscribe_resource(&self, uri: Url) -> Result<()> {
+        let params = types::ResourcesUnsubscribeParams { uri, meta: None };
+
+        self.inner
+            .request::<serde_json::Value>(types::RequestType::ResourcesUnsubscribe.as_str(), params)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Registers a handler for the notifications about changes to subscribed resources.
+    pub fn on_resource_updated<F>(&self, mut f: F)
+    where
+        F: 'static + Send + FnMut(Url, AsyncAppContext),
+    {
+        self.inner.on_notification(
+            types::NotificationType::ResourcesUpdated.as_str(),
+            move |params, cx| match serde_json::from_value::<types::ResourcesUpdatedParams>(params)
+            {
+                Ok(params) => f(params.uri, cx),
+                Err(error) => log::error!("invalid resource update notification: {error}"),
+            },
+        );
+    }
+
+    /// Tells the server that the roots returned by the `roots/list` handler have changed.
+    pub fn notify_roots_list_changed(&self) -> Result<()> {
+        self.inner.notify(
+            types::NotificationType::RootsListChanged.as_str(),
+            serde_json::json!({}),
+        )
+    }
+
     /// Executes a prompt with the given arguments and returns the result.
     pub async fn run_prompt<P: AsRef<str>>(
         &self,
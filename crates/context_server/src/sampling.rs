@@ -0,0 +1,177 @@
+//! Answers the `sampling/createMessage` requests of context servers.
+//!
+//! Servers use sampling to have the client's language model generate text for them. Every
+//! request is shown to the user, who has to allow it before it's sent to a model. The model is
+//! the first one matching the server's hints, or the one that is selected in the assistant.
+
+use std::fmt::Write as _;
+use std::iter;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use futures::StreamExt as _;
+use gpui::{AppContext, AsyncAppContext, PromptLevel};
+use language_model::{
+    LanguageModel, LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
+    MessageContent, Role,
+};
+
+use crate::types;
+
+pub async fn create_message(
+    server_id: Arc<str>,
+    request: types::CreateMessageRequest,
+    mut cx: AsyncAppContext,
+) -> Result<types::CreateMessageResult> {
+    let hints = request
+        .model_preferences
+        .as_ref()
+        .and_then(|preferences| preferences.hints.as_deref())
+        .unwrap_or_default();
+    let model = cx
+        .update(|cx| select_model(hints, cx))?
+        .ok_or_else(|| anyhow!("no language model is selected"))?;
+
+    let mut messages = Vec::new();
+    if let Some(system_prompt) = request.system_prompt.filter(|prompt| !prompt.is_empty()) {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![MessageContent::Text(system_prompt)],
+            cache: false,
+        });
+    }
+    for message in request.messages {
+        let types::MessageContent::Text { text, .. } = message.content else {
+            bail!("only text messages are supported");
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                types::Role::User => Role::User,
+                types::Role::Assistant => Role::Assistant,
+            },
+            content: vec![MessageContent::Text(text)],
+            cache: false,
+        });
+    }
+
+    let model_name = model.name().0;
+    let mut detail = String::new();
+    for message in &messages {
+        writeln!(detail, "{}: {}", message.role, message.string_contents()).ok();
+    }
+    let answer = cx
+        .update(|cx| {
+            let window = cx
+                .active_window()
+                .ok_or_else(|| anyhow!("no window to ask for permission in"))?;
+            window.update(cx, |_, cx| {
+                cx.prompt(
+                    PromptLevel::Info,
+                    &format!(
+                        "The context server {server_id} wants to send the following messages to {model_name}."
+                    ),
+                    Some(&detail),
+                    &["Allow", "Deny"],
+                )
+            })
+        })??
+        .await?;
+    if answer != 0 {
+        bail!("the user denied the request");
+    }
+
+    let request = LanguageModelRequest {
+        messages,
+        tools: Vec::new(),
+        stop: request.stop_sequences.unwrap_or_default(),
+        temperature: request.temperature.map(|temperature| temperature as f32),
+        max_tokens: Some(request.max_tokens),
+    };
+    let mut stream = model.stream_completion_text(request, &cx).await?.stream;
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        text.push_str(&chunk?);
+    }
+
+    Ok(types::CreateMessageResult {
+        role: types::Role::Assistant,
+        content: types::MessageContent::Text {
+            text,
+            annotations: None,
+        },
+        model: model_name.to_string(),
+        stop_reason: Some("endTurn".into()),
+    })
+}
+
+/// Returns the model to sample with, which is the first model of the active provider whose
+/// name contains one of the hints, trying the hints in order, or else the active model.
+/// Requests never go to another provider than the one selected by the user.
+///
+/// The cost, speed and intelligence priorities of the server aren't used, as the models don't
+/// describe themselves in those terms.
+fn select_model(hints: &[types::ModelHint], cx: &AppContext) -> Option<Arc<dyn LanguageModel>> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let active_model = registry.active_model()?;
+    let models = iter::once(active_model.clone())
+        .chain(
+            registry
+                .active_provider()
+                .into_iter()
+                .flat_map(|provider| provider.provided_models(cx)),
+        )
+        .collect::<Vec<_>>();
+    let names = models
+        .iter()
+        .map(|model| format!("{} {}", model.id().0, model.name().0))
+        .collect::<Vec<_>>();
+    match preferred_model(hints, &names) {
+        Some(ix) => Some(models[ix].clone()),
+        None => Some(active_model),
+    }
+}
+
+/// Returns the index of the first name that contains one of the hints, ignoring case, trying
+/// the hints in order.
+fn preferred_model(hints: &[types::ModelHint], names: &[String]) -> Option<usize> {
+    hints
+        .iter()
+        .filter_map(|hint| hint.name.as_deref())
+        .filter(|hint| !hint.is_empty())
+        .find_map(|hint| {
+            let hint = hint.to_lowercase();
+            names
+                .iter()
+                .position(|name| name.to_lowercase().contains(&hint))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_model() {
+        let names = ["claude-3-5-sonnet Claude 3.5 Sonnet", "gpt-4o GPT 4o"].map(String::from);
+        let hints = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| types::ModelHint {
+                    name: Some(name.to_string()),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(preferred_model(&hints(&["sonnet"]), &names), Some(0));
+        assert_eq!(preferred_model(&hints(&["GPT-4O"]), &names), Some(1));
+        assert_eq!(preferred_model(&hints(&["gemini", "gpt"]), &names), Some(1));
+        assert_eq!(preferred_model(&hints(&["gpt", "claude"]), &names), Some(1));
+        assert_eq!(preferred_model(&hints(&["", "claude"]), &names), Some(0));
+        assert_eq!(preferred_model(&hints(&["gemini"]), &names), None);
+        assert_eq!(
+            preferred_model(&[types::ModelHint { name: None }], &names),
+            None
+        );
+        assert_eq!(preferred_model(&[], &names), None);
+    }
+}
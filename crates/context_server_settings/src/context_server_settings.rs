@@ -18,6 +18,16 @@ pub struct ServerConfig {
     ///
     /// This will override the command set by an extension.
     pub command: Option<ServerCommand>,
+    /// The URL of an already running context server to connect to over HTTP,
+    /// such as `http://localhost:8080/sse`.
+    ///
+    /// This is the endpoint of the server's event stream. When set, the command is ignored.
+    pub url: Option<String>,
+    /// Headers to send with every HTTP request to the context server, such as an
+    /// `Authorization` header.
+    ///
+    /// Only used when `url` is set.
+    pub headers: Option<HashMap<String, String>>,
     /// The settings for this context server.
     ///
     /// Consult the documentation for the context server to see what settings
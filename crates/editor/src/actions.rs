@@ -211,9 +211,12 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptBoth,
         AcceptInlineCompletion,
+        AcceptOurs,
         AcceptPartialCopilotSuggestion,
         AcceptPartialInlineCompletion,
+        AcceptTheirs,
         AddSelectionAbove,
         AddSelectionBelow,
         ApplyAllDiffHunks,
@@ -257,7 +260,9 @@ gpui::actions!(
         FindAllReferences,
         Fold,
         FoldAll,
+        FoldComments,
         FoldFunctionBodies,
+        FoldImports,
         FoldRecursive,
         FoldSelectedRanges,
         ToggleFold,
@@ -274,6 +279,8 @@ gpui::actions!(
         GoToImplementationSplit,
         GoToPrevDiagnostic,
         GoToPrevHunk,
+        GoToSubtypes,
+        GoToSupertypes,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
         HalfPageDown,
@@ -366,6 +373,7 @@ gpui::actions!(
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBookmark,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleHunkDiff,
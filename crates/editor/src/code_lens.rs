@@ -0,0 +1,614 @@
+use std::{ops::RangeInclusive, path::PathBuf, sync::Arc, time::Duration};
+
+use collections::{BTreeMap, HashMap, HashSet};
+use futures::future::join_all;
+use gpui::{Model, WeakView};
+use language::{
+    language_settings::language_settings, point_from_lsp, Bias, Buffer, BufferId, Location, Point,
+    ToPoint as _,
+};
+use multi_buffer::ToPoint as _;
+use project::{CodeLens, TaskSourceKind};
+use serde::Deserialize;
+use task::{TaskContext, TaskTemplate};
+use ui::{h_flex, prelude::*, ButtonStyle, ViewContext};
+use util::ResultExt;
+
+use crate::{
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle, RenderBlock},
+    Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(250);
+const RESOLVE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Commands that rust-analyzer puts in its lenses, but expects the client to implement.
+const RUN_SINGLE_COMMAND: &str = "rust-analyzer.runSingle";
+const SHOW_REFERENCES_COMMAND: &str = "rust-analyzer.showReferences";
+/// Lenses with this command are not shown, as there is no debugger to start.
+const DEBUG_SINGLE_COMMAND: &str = "rust-analyzer.debugSingle";
+
+/// The argument of rust-analyzer's `runSingle` command.
+#[derive(Debug, Deserialize)]
+struct Runnable {
+    label: String,
+    #[serde(flatten)]
+    args: RunnableArgs,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", content = "args", rename_all = "lowercase")]
+enum RunnableArgs {
+    Cargo(CargoRunnableArgs),
+    Shell(ShellRunnableArgs),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CargoRunnableArgs {
+    #[serde(default)]
+    environment: HashMap<String, String>,
+    cwd: PathBuf,
+    override_cargo: Option<String>,
+    cargo_args: Vec<String>,
+    #[serde(default)]
+    executable_args: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ShellRunnableArgs {
+    #[serde(default)]
+    environment: HashMap<String, String>,
+    cwd: PathBuf,
+    program: String,
+    args: Vec<String>,
+}
+
+impl Runnable {
+    fn into_task_template(self) -> TaskTemplate {
+        let (command, args, env, cwd) = match self.args {
+            RunnableArgs::Cargo(cargo) => {
+                let mut args = cargo.cargo_args;
+                if !cargo.executable_args.is_empty() {
+                    args.push("--".to_string());
+                    args.extend(cargo.executable_args);
+                }
+                let command = cargo.override_cargo.unwrap_or_else(|| "cargo".to_string());
+                (command, args, cargo.environment, cargo.cwd)
+            }
+            RunnableArgs::Shell(shell) => (shell.program, shell.args, shell.environment, shell.cwd),
+        };
+        TaskTemplate {
+            label: self.label,
+            command,
+            args,
+            env,
+            cwd: Some(cwd.to_string_lossy().into_owned()),
+            ..TaskTemplate::default()
+        }
+    }
+}
+
+/// Whether clicking the lens does anything: either the editor implements its command,
+/// or the server can execute it.
+fn is_clickable(lens: &CodeLens) -> bool {
+    lens.executable
+        || lens.lsp_lens.command.as_ref().map_or(false, |command| {
+            matches!(
+                command.command.as_str(),
+                RUN_SINGLE_COMMAND | SHOW_REFERENCES_COMMAND
+            )
+        })
+}
+
+/// Whether the lens is shown: it has to be resolved, and not be one that can't be run here.
+fn is_shown(lens: &CodeLens) -> bool {
+    lens.lsp_lens
+        .command
+        .as_ref()
+        .map_or(false, |command| command.command != DEBUG_SINGLE_COMMAND)
+}
+
+/// Requests code lenses for the buffers of the editor that have them enabled,
+/// and replaces their code lens blocks. Only the visible lenses get resolved.
+pub(super) fn refresh_code_lenses(editor: &mut Editor, cx: &mut ViewContext<Editor>) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+
+    let mut enabled_buffers = Vec::new();
+    let mut disabled_buffer_ids = Vec::new();
+    for buffer in editor.buffer.read(cx).all_buffers() {
+        let buffer_ref = buffer.read(cx);
+        let settings = language_settings(
+            buffer_ref.language().map(|language| language.name()),
+            buffer_ref.file(),
+            cx,
+        );
+        if settings.code_lens {
+            enabled_buffers.push(buffer);
+        } else {
+            disabled_buffer_ids.push(buffer_ref.remote_id());
+        }
+    }
+
+    let blocks_to_remove = disabled_buffer_ids
+        .into_iter()
+        .filter_map(|buffer_id| {
+            editor.code_lenses.remove(&buffer_id);
+            editor.code_lens_blocks.remove(&buffer_id)
+        })
+        .flatten()
+        .map(|(_, block_id)| block_id)
+        .collect::<HashSet<_>>();
+    if !blocks_to_remove.is_empty() {
+        editor.remove_blocks(blocks_to_remove, None, cx);
+    }
+
+    let semantics_provider = editor.semantics_provider.clone()?;
+    if enabled_buffers.is_empty() {
+        editor.code_lens_task = None;
+        return None;
+    }
+
+    editor.code_lens_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let tasks = editor
+            .update(&mut cx, |_, cx| {
+                enabled_buffers
+                    .into_iter()
+                    .filter_map(|buffer| {
+                        let task = semantics_provider.code_lens(&buffer, cx)?;
+                        Some((buffer, task))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        for (buffer, task) in tasks {
+            let Some(mut lenses) = task.await.log_err() else {
+                continue;
+            };
+            editor
+                .update(&mut cx, |editor, cx| {
+                    let buffer_id = buffer.read(cx).remote_id();
+                    if let Some(old_lenses) = editor.code_lenses.get(&buffer_id) {
+                        fill_resolved_commands(&mut lenses, old_lenses);
+                    }
+                    editor.code_lenses.insert(buffer_id, lenses);
+                    show_code_lenses(editor, &buffer, cx)
+                })
+                .ok()?;
+        }
+        editor
+            .update(&mut cx, |editor, cx| {
+                resolve_visible_code_lenses(editor, cx)
+            })
+            .ok()?
+    }));
+    None
+}
+
+/// Resolves the lenses that came without a command and are on the visible lines of the editor,
+/// then shows them.
+pub(super) fn resolve_visible_code_lenses(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.code_lenses.is_empty() {
+        return None;
+    }
+    let semantics_provider = editor.semantics_provider.clone()?;
+
+    let mut lenses_to_resolve = Vec::new();
+    for (buffer, visible_rows) in visible_buffer_rows(editor, cx) {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let Some(lenses) = editor.code_lenses.get(&buffer_snapshot.remote_id()) else {
+            continue;
+        };
+        for lens in lenses {
+            let row = lens.range.start.to_point(&buffer_snapshot).row;
+            if lens.lsp_lens.command.is_none() && visible_rows.contains(&row) {
+                lenses_to_resolve.push((buffer_snapshot.remote_id(), buffer.clone(), lens.clone()));
+            }
+        }
+    }
+    if lenses_to_resolve.is_empty() {
+        return None;
+    }
+
+    editor.code_lens_resolve_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(RESOLVE_DEBOUNCE).await;
+
+        let (buffers, tasks): (Vec<_>, Vec<_>) = editor
+            .update(&mut cx, |_, cx| {
+                lenses_to_resolve
+                    .into_iter()
+                    .filter_map(|(buffer_id, buffer, lens)| {
+                        let task =
+                            semantics_provider.resolve_code_lens(buffer.clone(), lens, cx)?;
+                        Some(((buffer_id, buffer), task))
+                    })
+                    .unzip()
+            })
+            .ok()?;
+        let mut resolved_lenses = HashMap::<BufferId, (Model<Buffer>, Vec<CodeLens>)>::default();
+        for ((buffer_id, buffer), lens) in buffers.into_iter().zip(join_all(tasks).await) {
+            let Some(lens) = lens.log_err() else {
+                continue;
+            };
+            resolved_lenses
+                .entry(buffer_id)
+                .or_insert_with(|| (buffer, Vec::new()))
+                .1
+                .push(lens);
+        }
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer_id, (buffer, resolved_lenses)) in resolved_lenses {
+                    let Some(lenses) = editor.code_lenses.get_mut(&buffer_id) else {
+                        continue;
+                    };
+                    fill_resolved_commands(lenses, &resolved_lenses);
+                    show_code_lenses(editor, &buffer, cx);
+                }
+            })
+            .ok()
+    }));
+    None
+}
+
+/// Gives the unresolved lenses the command of the same lens from `resolved_lenses`,
+/// so that lenses which did not change are not resolved again.
+fn fill_resolved_commands(lenses: &mut [CodeLens], resolved_lenses: &[CodeLens]) {
+    for lens in lenses
+        .iter_mut()
+        .filter(|lens| lens.lsp_lens.command.is_none())
+    {
+        if let Some(resolved_lens) = resolved_lenses.iter().find(|resolved_lens| {
+            resolved_lens.lsp_lens.command.is_some()
+                && resolved_lens.server_id == lens.server_id
+                && resolved_lens.lsp_lens.range == lens.lsp_lens.range
+                && resolved_lens.lsp_lens.data == lens.lsp_lens.data
+        }) {
+            lens.lsp_lens.command = resolved_lens.lsp_lens.command.clone();
+            lens.executable = resolved_lens.executable;
+        }
+    }
+}
+
+/// The rows of each buffer that are on screen, the same way inlay hints compute them.
+fn visible_buffer_rows(
+    editor: &Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Vec<(Model<Buffer>, RangeInclusive<u32>)> {
+    let multibuffer = editor.buffer.read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+    let visible_start = editor
+        .scroll_manager
+        .anchor()
+        .anchor
+        .to_point(&multibuffer_snapshot);
+    let visible_end = multibuffer_snapshot.clip_point(
+        visible_start + Point::new(editor.visible_line_count().unwrap_or(0.).ceil() as u32, 0),
+        Bias::Left,
+    );
+    multibuffer
+        .range_to_buffer_ranges(visible_start..visible_end, cx)
+        .into_iter()
+        .map(|(buffer, range, _)| {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            let rows = range.start.to_point(&buffer_snapshot).row
+                ..=range.end.to_point(&buffer_snapshot).row;
+            (buffer, rows)
+        })
+        .collect()
+}
+
+/// Shows one block above each line of the buffer that has shown lenses. Blocks that stay on
+/// the same line are updated in place, so that the lines below them don't move.
+fn show_code_lenses(editor: &mut Editor, buffer: &Model<Buffer>, cx: &mut ViewContext<Editor>) {
+    let buffer_id = buffer.read(cx).remote_id();
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let multibuffer = editor.buffer.read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+
+    let mut lenses_by_row = BTreeMap::<u32, Vec<CodeLens>>::default();
+    for lens in editor
+        .code_lenses
+        .get(&buffer_id)
+        .into_iter()
+        .flatten()
+        .filter(|lens| is_shown(lens))
+    {
+        let row = lens.range.start.to_point(&buffer_snapshot).row;
+        lenses_by_row.entry(row).or_default().push(lens.clone());
+    }
+
+    // Edits can move several blocks onto the same point, so each point keeps all of its blocks:
+    // one of them is reused and the others are removed.
+    let mut old_blocks = HashMap::<Point, Vec<_>>::default();
+    for (anchor, block_id) in editor
+        .code_lens_blocks
+        .remove(&buffer_id)
+        .unwrap_or_default()
+    {
+        old_blocks
+            .entry(anchor.to_point(&multibuffer_snapshot))
+            .or_default()
+            .push((anchor, block_id));
+    }
+
+    let editor_handle = cx.view().downgrade();
+    let mut blocks = Vec::new();
+    let mut inserted_anchors = Vec::new();
+    let mut new_blocks = Vec::new();
+    let mut renderers = HashMap::default();
+    for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+        let context = excerpt_range.context.start.to_point(&buffer_snapshot)
+            ..excerpt_range.context.end.to_point(&buffer_snapshot);
+        for (row, lenses) in lenses_by_row.range(context.start.row..=context.end.row) {
+            let line_start = Point::new(*row, buffer_snapshot.indent_size_for_line(*row).len);
+            if line_start < context.start || line_start > context.end {
+                continue;
+            }
+            let Some(anchor) = multibuffer_snapshot
+                .anchor_in_excerpt(excerpt_id, buffer_snapshot.anchor_before(line_start))
+            else {
+                continue;
+            };
+            let render = render_code_lenses(lenses.clone(), buffer.clone(), editor_handle.clone());
+            if let Some((old_anchor, block_id)) = old_blocks
+                .get_mut(&anchor.to_point(&multibuffer_snapshot))
+                .and_then(|blocks| blocks.pop())
+            {
+                renderers.insert(block_id, render);
+                new_blocks.push((old_anchor, block_id));
+            } else {
+                inserted_anchors.push(anchor);
+                blocks.push(BlockProperties {
+                    placement: BlockPlacement::Above(anchor),
+                    height: 1,
+                    style: BlockStyle::Flex,
+                    render,
+                    priority: 0,
+                });
+            }
+        }
+    }
+
+    let blocks_to_remove = old_blocks
+        .into_values()
+        .flatten()
+        .map(|(_, block_id)| block_id)
+        .collect::<HashSet<_>>();
+    if !blocks_to_remove.is_empty() {
+        editor.remove_blocks(blocks_to_remove, None, cx);
+    }
+    if !renderers.is_empty() {
+        editor.replace_blocks(renderers, None, cx);
+    }
+    if !blocks.is_empty() {
+        let block_ids = editor.insert_blocks(blocks, None, cx);
+        new_blocks.extend(inserted_anchors.into_iter().zip(block_ids));
+    }
+    if !new_blocks.is_empty() {
+        editor.code_lens_blocks.insert(buffer_id, new_blocks);
+    }
+    cx.notify();
+}
+
+fn render_code_lenses(
+    lenses: Vec<CodeLens>,
+    buffer: Model<Buffer>,
+    editor: WeakView<Editor>,
+) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(lenses.iter().enumerate().flat_map(|(ix, lens)| {
+                let separator = (ix > 0).then(|| {
+                    Label::new("|")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .into_any_element()
+                });
+                let title = lens.title().unwrap_or_default().to_string();
+                if !is_clickable(lens) {
+                    let label = Label::new(title)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .into_any_element();
+                    return separator.into_iter().chain(Some(label));
+                }
+                let button = Button::new(("code-lens", ix), title)
+                    .label_size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .style(ButtonStyle::Transparent)
+                    .on_click({
+                        let lens = lens.clone();
+                        let buffer = buffer.clone();
+                        let editor = editor.clone();
+                        move |_, cx| {
+                            editor
+                                .update(cx, |editor, cx| {
+                                    run_code_lens(editor, buffer.clone(), lens.clone(), cx)
+                                })
+                                .ok();
+                        }
+                    })
+                    .into_any_element();
+                separator.into_iter().chain(Some(button))
+            }))
+            .into_any_element()
+    })
+}
+
+/// Runs the command of the lens: the ones that rust-analyzer leaves to the client are handled
+/// here, and the other ones are executed on the language server, opening the edits they make.
+fn run_code_lens(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    lens: CodeLens,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    let command = lens.lsp_lens.command.clone()?;
+    match command.command.as_str() {
+        RUN_SINGLE_COMMAND => return spawn_runnable(editor, &buffer, command.arguments, cx),
+        SHOW_REFERENCES_COMMAND => return show_references(editor, command, cx),
+        _ => {}
+    }
+    if !lens.executable {
+        return None;
+    }
+
+    let semantics_provider = editor.semantics_provider.clone()?;
+    let workspace = editor.workspace()?.downgrade();
+    let title = lens.title().unwrap_or_default().to_string();
+    let apply_code_lens = semantics_provider.apply_code_lens(buffer, lens, cx)?;
+    cx.spawn(|editor, cx| async move {
+        let project_transaction = apply_code_lens.await?;
+        Editor::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+    })
+    .detach_and_log_err(cx);
+    Some(())
+}
+
+/// Spawns the runnable of a `runSingle` lens as a task.
+fn spawn_runnable(
+    editor: &Editor,
+    buffer: &Model<Buffer>,
+    arguments: Option<Vec<serde_json::Value>>,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    let workspace = editor.workspace()?;
+    let runnable = arguments
+        .into_iter()
+        .flatten()
+        .next()
+        .and_then(|argument| serde_json::from_value::<Runnable>(argument).log_err())?;
+    let task_source_kind = TaskSourceKind::Language {
+        name: buffer.read(cx).language()?.name().0,
+    };
+    let resolved_task = runnable
+        .into_task_template()
+        .resolve_task(&task_source_kind.to_id_base(), &TaskContext::default())?;
+    workspace.update(cx, |workspace, cx| {
+        workspace::tasks::schedule_resolved_task(
+            workspace,
+            task_source_kind,
+            resolved_task,
+            false,
+            cx,
+        );
+    });
+    Some(())
+}
+
+/// Opens the locations of a `showReferences` lens, whose arguments are
+/// the document, the position of the lens, and the locations.
+fn show_references(
+    editor: &Editor,
+    command: lsp::Command,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    let project = editor.project.clone()?;
+    let workspace = editor.workspace()?.downgrade();
+    let locations =
+        command.arguments?.get(2).cloned().and_then(|locations| {
+            serde_json::from_value::<Vec<lsp::Location>>(locations).log_err()
+        })?;
+    let open_buffers = locations
+        .into_iter()
+        .filter_map(|location| {
+            let path = location.uri.to_file_path().ok()?;
+            let project_path = project.read(cx).find_project_path(&path, cx)?;
+            let open_buffer =
+                project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+            Some((open_buffer, location.range))
+        })
+        .collect::<Vec<_>>();
+    if open_buffers.is_empty() {
+        return None;
+    }
+
+    let title = command.title;
+    cx.spawn(|_, mut cx| async move {
+        let mut locations = Vec::new();
+        for (open_buffer, range) in open_buffers {
+            let buffer = open_buffer.await?;
+            let range = buffer.update(&mut cx, |buffer, _| {
+                let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+                let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+                buffer.anchor_after(start)..buffer.anchor_before(end)
+            })?;
+            locations.push(Location { buffer, range });
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            Editor::open_locations_in_multibuffer(workspace, locations, title, false, cx)
+        })
+    })
+    .detach_and_log_err(cx);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_runnable_task_template() {
+        let runnable = serde_json::from_value::<Runnable>(json!({
+            "label": "test tests::it_works",
+            "kind": "cargo",
+            "location": null,
+            "args": {
+                "environment": { "RUST_BACKTRACE": "short" },
+                "cwd": "/project",
+                "workspaceRoot": "/project",
+                "overrideCargo": null,
+                "cargoArgs": ["test", "--package", "foo", "--lib"],
+                "executableArgs": ["tests::it_works", "--exact", "--show-output"],
+            },
+        }))
+        .unwrap();
+        let template = runnable.into_task_template();
+        assert_eq!(template.label, "test tests::it_works");
+        assert_eq!(template.command, "cargo");
+        assert_eq!(
+            template.args,
+            [
+                "test",
+                "--package",
+                "foo",
+                "--lib",
+                "--",
+                "tests::it_works",
+                "--exact",
+                "--show-output"
+            ]
+        );
+        assert_eq!(template.env.get("RUST_BACKTRACE").unwrap(), "short");
+        assert_eq!(template.cwd.as_deref(), Some("/project"));
+
+        let runnable = serde_json::from_value::<Runnable>(json!({
+            "label": "run script",
+            "kind": "shell",
+            "args": {
+                "cwd": "/project",
+                "program": "./run.sh",
+                "args": ["--fast"],
+            },
+        }))
+        .unwrap();
+        let template = runnable.into_task_template();
+        assert_eq!(template.command, "./run.sh");
+        assert_eq!(template.args, ["--fast"]);
+        assert!(template.env.is_empty());
+    }
+}
@@ -46,7 +46,7 @@ use inlay_map::{InlayMap, InlaySnapshot};
 pub use inlay_map::{InlayOffset, InlayPoint};
 use invisibles::{is_invisible, replacement};
 use language::{
-    language_settings::language_settings, ChunkRenderer, OffsetUtf16, Point,
+    language_settings::language_settings, BufferId, ChunkRenderer, HighlightId, OffsetUtf16, Point,
     Subscription as BufferSubscription,
 };
 use lsp::DiagnosticSeverity;
@@ -54,6 +54,7 @@ use multi_buffer::{
     Anchor, AnchorRangeExt, MultiBuffer, MultiBufferPoint, MultiBufferRow, MultiBufferSnapshot,
     ToOffset, ToPoint,
 };
+use project::FoldingRangeKind;
 use serde::Deserialize;
 use std::{
     any::TypeId,
@@ -85,6 +86,12 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+/// Syntax highlights reported by language servers, keyed by the buffer they belong to.
+/// The ranges of each buffer are sorted and non-overlapping.
+type SemanticHighlights = TreeMap<BufferId, Arc<Vec<(Range<Anchor>, HighlightId)>>>;
+/// Folding ranges reported by language servers, keyed by the buffer they belong to.
+/// The ranges of each buffer are sorted by their start, and replace the indentation based folds of the buffer.
+type LspFoldingRanges = TreeMap<BufferId, Arc<Vec<(Range<Anchor>, Option<FoldingRangeKind>)>>>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -108,6 +115,10 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Syntax highlights from language servers, overriding the tree-sitter ones.
+    semantic_highlights: SemanticHighlights,
+    /// Foldable ranges from language servers, superseding the indentation based ones.
+    lsp_folding_ranges: LspFoldingRanges,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -160,6 +171,8 @@ impl DisplayMap {
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_highlights: Default::default(),
+            lsp_folding_ranges: Default::default(),
             clip_at_line_ends: false,
             masked: false,
         }
@@ -187,6 +200,8 @@ impl DisplayMap {
             crease_snapshot: self.crease_map.snapshot(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_highlights: self.semantic_highlights.clone(),
+            lsp_folding_ranges: self.lsp_folding_ranges.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
             fold_placeholder: self.fold_placeholder.clone(),
@@ -489,6 +504,31 @@ impl DisplayMap {
         }
     }
 
+    pub(crate) fn set_semantic_highlights(
+        &mut self,
+        buffer_id: BufferId,
+        highlights: Vec<(Range<Anchor>, HighlightId)>,
+    ) {
+        self.semantic_highlights
+            .insert(buffer_id, Arc::new(highlights));
+    }
+
+    pub(crate) fn clear_semantic_highlights(&mut self, buffer_id: BufferId) -> bool {
+        self.semantic_highlights.remove(&buffer_id).is_some()
+    }
+
+    pub(crate) fn set_lsp_folding_ranges(
+        &mut self,
+        buffer_id: BufferId,
+        ranges: Vec<(Range<Anchor>, Option<FoldingRangeKind>)>,
+    ) {
+        self.lsp_folding_ranges.insert(buffer_id, Arc::new(ranges));
+    }
+
+    pub(crate) fn clear_lsp_folding_ranges(&mut self, buffer_id: BufferId) -> bool {
+        self.lsp_folding_ranges.remove(&buffer_id).is_some()
+    }
+
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
         let highlights = self.text_highlights.get(&type_id)?;
         Some((highlights.0, &highlights.1))
@@ -565,6 +605,7 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_highlights: Option<&'a SemanticHighlights>,
     pub styles: HighlightStyles,
 }
 
@@ -699,6 +740,8 @@ pub struct DisplaySnapshot {
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_highlights: SemanticHighlights,
+    lsp_folding_ranges: LspFoldingRanges,
     clip_at_line_ends: bool,
     masked: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -886,6 +929,7 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_highlights: Some(&self.semantic_highlights),
                 styles: highlight_styles,
             },
         )
@@ -1195,6 +1239,76 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Whether a fold starts at the given row, according to the language server folding ranges
+    /// of its buffer if there are any, or to the indentation of its lines otherwise.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        match self.lsp_folding_ranges_for_row(buffer_row) {
+            Some(ranges) => self
+                .lsp_folding_range_for_buffer_row(ranges, buffer_row)
+                .is_some(),
+            None => self.starts_indent(buffer_row),
+        }
+    }
+
+    /// The language server folding ranges of the given kind in all buffers, as multibuffer points.
+    pub fn lsp_folding_ranges_of_kind(
+        &self,
+        kind: FoldingRangeKind,
+    ) -> impl Iterator<Item = Range<Point>> + '_ {
+        self.lsp_folding_ranges
+            .iter()
+            .flat_map(|(_, ranges)| ranges.iter())
+            .filter(move |(_, range_kind)| *range_kind == Some(kind))
+            .map(|(range, _)| {
+                let range = range.to_point(&self.buffer_snapshot);
+                let start = Point::new(
+                    range.start.row,
+                    self.buffer_snapshot
+                        .line_len(MultiBufferRow(range.start.row)),
+                );
+                let end = Point::new(
+                    range.end.row,
+                    self.buffer_snapshot.line_len(MultiBufferRow(range.end.row)),
+                );
+                start..end
+            })
+            .filter(|range| range.start.row < range.end.row)
+    }
+
+    fn lsp_folding_ranges_for_row(
+        &self,
+        buffer_row: MultiBufferRow,
+    ) -> Option<&[(Range<Anchor>, Option<FoldingRangeKind>)]> {
+        let (buffer, _) = self.buffer_snapshot.buffer_line_for_row(buffer_row)?;
+        let ranges = self.lsp_folding_ranges.get(&buffer.remote_id())?;
+        Some(ranges.as_slice())
+    }
+
+    /// The largest of the given folding ranges that starts at the given row.
+    fn lsp_folding_range_for_buffer_row(
+        &self,
+        ranges: &[(Range<Anchor>, Option<FoldingRangeKind>)],
+        buffer_row: MultiBufferRow,
+    ) -> Option<Range<Point>> {
+        let start_ix = ranges.partition_point(|(range, _)| {
+            range.start.to_point(&self.buffer_snapshot).row < buffer_row.0
+        });
+        ranges[start_ix..]
+            .iter()
+            .map(|(range, _)| range.to_point(&self.buffer_snapshot))
+            .take_while(|range| range.start.row == buffer_row.0)
+            .filter(|range| range.end.row > buffer_row.0)
+            .max_by_key(|range| range.end.row)
+            .map(|range| {
+                let start = Point::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
+                let end = Point::new(
+                    range.end.row,
+                    self.buffer_snapshot.line_len(MultiBufferRow(range.end.row)),
+                );
+                start..end
+            })
+    }
+
     pub fn crease_for_buffer_row(&self, buffer_row: MultiBufferRow) -> Option<Crease<Point>> {
         let start = MultiBufferPoint::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
         if let Some(crease) = self
@@ -1231,6 +1345,18 @@ impl DisplaySnapshot {
                     render_toggle: render_toggle.clone(),
                 }),
             }
+        } else if let Some(ranges) = self.lsp_folding_ranges_for_row(buffer_row) {
+            if self.is_line_folded(buffer_row) {
+                return None;
+            }
+            let range = self.lsp_folding_range_for_buffer_row(ranges, buffer_row)?;
+            Some(Crease::Inline {
+                range,
+                placeholder: self.fold_placeholder.clone(),
+                render_toggle: None,
+                render_trailer: None,
+                metadata: None,
+            })
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
@@ -2116,6 +2242,138 @@ pub mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_chunks_with_semantic_highlights(cx: &mut gpui::TestAppContext) {
+        cx.background_executor
+            .set_block_on_ticks(usize::MAX..=usize::MAX);
+
+        let text = r#"
+            fn a() {}
+            fn b() { a(); }
+        "#
+        .unindent();
+
+        let theme = SyntaxTheme::new_test(vec![
+            ("string", Hsla::red()),
+            ("punctuation", Hsla::blue()),
+            ("keyword", Hsla::green()),
+            ("function", Hsla::white()),
+        ]);
+        let string_id = HighlightId(0);
+        let function_id = HighlightId(3);
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_highlights_query(
+                r#"
+                "fn" @keyword
+                ["(" ")" "{" "}" ";"] @punctuation
+                "#,
+            )
+            .unwrap(),
+        );
+        language.set_theme(&theme);
+
+        cx.update(|cx| init_test(cx, |_| {}));
+
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.condition(&buffer, |buf, _| !buf.is_parsing()).await;
+        let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+        let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+        let buffer_snapshot = buffer.read_with(cx, |buffer, cx| buffer.snapshot(cx));
+
+        let map = cx.new_model(|cx| {
+            DisplayMap::new(
+                buffer,
+                font("Courier"),
+                px(16.0),
+                None,
+                true,
+                1,
+                1,
+                0,
+                FoldPlaceholder::test(),
+                cx,
+            )
+        });
+
+        // Semantic highlights apply to unhighlighted text and replace the syntax highlights
+        // they overlap, including adjacent ones.
+        let range = |start: Point, end: Point| {
+            buffer_snapshot.anchor_after(start)..buffer_snapshot.anchor_before(end)
+        };
+        map.update(cx, |map, _| {
+            map.set_semantic_highlights(
+                buffer_id,
+                vec![
+                    (range(Point::new(0, 3), Point::new(0, 4)), function_id),
+                    (range(Point::new(1, 0), Point::new(1, 2)), string_id),
+                    (range(Point::new(1, 9), Point::new(1, 10)), function_id),
+                    (range(Point::new(1, 10), Point::new(1, 12)), string_id),
+                ],
+            )
+        });
+        pretty_assertions::assert_eq!(
+            cx.update(|cx| syntax_chunks(DisplayRow(0)..DisplayRow(2), &map, &theme, cx)),
+            [
+                ("fn".into(), Some(Hsla::green())),
+                (" ".into(), None),
+                ("a".into(), Some(Hsla::white())),
+                ("()".into(), Some(Hsla::blue())),
+                (" ".into(), None),
+                ("{}".into(), Some(Hsla::blue())),
+                ("\n".into(), None),
+                ("fn".into(), Some(Hsla::red())),
+                (" b".into(), None),
+                ("()".into(), Some(Hsla::blue())),
+                (" ".into(), None),
+                ("{".into(), Some(Hsla::blue())),
+                (" ".into(), None),
+                ("a".into(), Some(Hsla::white())),
+                ("()".into(), Some(Hsla::red())),
+                (";".into(), Some(Hsla::blue())),
+                (" ".into(), None),
+                ("}".into(), Some(Hsla::blue())),
+                ("\n".into(), None),
+            ]
+        );
+
+        // Chunks that aren't language-aware have neither syntax nor semantic highlights.
+        let snapshot = map.update(cx, |map, cx| map.snapshot(cx));
+        assert!(snapshot
+            .chunks(
+                DisplayRow(0)..DisplayRow(2),
+                false,
+                HighlightStyles::default()
+            )
+            .all(|chunk| chunk.syntax_highlight_id.is_none()));
+
+        // Clearing the semantic highlights restores the syntax highlights.
+        map.update(cx, |map, _| {
+            assert!(map.clear_semantic_highlights(buffer_id));
+        });
+        pretty_assertions::assert_eq!(
+            cx.update(|cx| syntax_chunks(DisplayRow(1)..DisplayRow(2), &map, &theme, cx)),
+            [
+                ("fn".into(), Some(Hsla::green())),
+                (" b".into(), None),
+                ("()".into(), Some(Hsla::blue())),
+                (" ".into(), None),
+                ("{".into(), Some(Hsla::blue())),
+                (" a".into(), None),
+                ("();".into(), Some(Hsla::blue())),
+                (" ".into(), None),
+                ("}".into(), Some(Hsla::blue())),
+                ("\n".into(), None),
+            ]
+        );
+    }
+
     #[gpui::test]
     async fn test_chunks_with_diagnostics_across_blocks(cx: &mut gpui::TestAppContext) {
         cx.background_executor
@@ -1,6 +1,7 @@
+use super::SemanticHighlights;
 use collections::BTreeMap;
 use gpui::HighlightStyle;
-use language::Chunk;
+use language::{Chunk, HighlightId};
 use multi_buffer::{Anchor, MultiBufferChunks, MultiBufferSnapshot, ToOffset as _};
 use std::{
     any::TypeId,
@@ -21,6 +22,10 @@ pub struct CustomHighlightsChunks<'a> {
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
     active_highlights: BTreeMap<TypeId, HighlightStyle>,
     text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+
+    semantic_highlight_endpoints: Peekable<vec::IntoIter<SemanticHighlightEndpoint>>,
+    active_semantic_highlight: Option<HighlightId>,
+    semantic_highlights: Option<&'a SemanticHighlights>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -31,13 +36,24 @@ struct HighlightEndpoint {
     style: HighlightStyle,
 }
 
+/// Semantic highlights never overlap, so unlike [`HighlightEndpoint`]s at most one is active at a time.
+#[derive(Debug, Copy, Clone)]
+struct SemanticHighlightEndpoint {
+    offset: usize,
+    is_start: bool,
+    highlight_id: HighlightId,
+}
+
 impl<'a> CustomHighlightsChunks<'a> {
     pub fn new(
         range: Range<usize>,
         language_aware: bool,
         text_highlights: Option<&'a TreeMap<TypeId, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>>,
+        semantic_highlights: Option<&'a SemanticHighlights>,
         multibuffer_snapshot: &'a MultiBufferSnapshot,
     ) -> Self {
+        // Semantic highlights replace syntax highlights, so they only apply to language-aware chunks.
+        let semantic_highlights = semantic_highlights.filter(|_| language_aware);
         Self {
             buffer_chunks: multibuffer_snapshot.chunks(range.clone(), language_aware),
             buffer_chunk: None,
@@ -50,6 +66,14 @@ impl<'a> CustomHighlightsChunks<'a> {
                 multibuffer_snapshot,
             ),
             active_highlights: Default::default(),
+
+            semantic_highlights,
+            semantic_highlight_endpoints: create_semantic_highlight_endpoints(
+                &range,
+                semantic_highlights,
+                multibuffer_snapshot,
+            ),
+            active_semantic_highlight: None,
             multibuffer_snapshot,
         }
     }
@@ -57,10 +81,16 @@ impl<'a> CustomHighlightsChunks<'a> {
     pub fn seek(&mut self, new_range: Range<usize>) {
         self.highlight_endpoints =
             create_highlight_endpoints(&new_range, self.text_highlights, self.multibuffer_snapshot);
+        self.semantic_highlight_endpoints = create_semantic_highlight_endpoints(
+            &new_range,
+            self.semantic_highlights,
+            self.multibuffer_snapshot,
+        );
         self.offset = new_range.start;
         self.buffer_chunks.seek(new_range);
         self.buffer_chunk.take();
-        self.active_highlights.clear()
+        self.active_highlights.clear();
+        self.active_semantic_highlight = None;
     }
 }
 
@@ -112,6 +142,45 @@ fn create_highlight_endpoints(
     highlight_endpoints.into_iter().peekable()
 }
 
+fn create_semantic_highlight_endpoints(
+    range: &Range<usize>,
+    semantic_highlights: Option<&SemanticHighlights>,
+    buffer: &MultiBufferSnapshot,
+) -> iter::Peekable<vec::IntoIter<SemanticHighlightEndpoint>> {
+    let mut highlight_endpoints = Vec::new();
+    if let Some(semantic_highlights) = semantic_highlights {
+        let start = buffer.anchor_after(range.start);
+        let end = buffer.anchor_after(range.end);
+        for highlights in semantic_highlights.values() {
+            let start_ix =
+                highlights.partition_point(|(range, _)| range.end.cmp(&start, buffer).is_le());
+            for (range, highlight_id) in &highlights[start_ix..] {
+                if range.start.cmp(&end, buffer).is_ge() {
+                    break;
+                }
+
+                let start_offset = range.start.to_offset(buffer);
+                let end_offset = range.end.to_offset(buffer);
+                if start_offset < end_offset {
+                    highlight_endpoints.push(SemanticHighlightEndpoint {
+                        offset: start_offset,
+                        is_start: true,
+                        highlight_id: *highlight_id,
+                    });
+                    highlight_endpoints.push(SemanticHighlightEndpoint {
+                        offset: end_offset,
+                        is_start: false,
+                        highlight_id: *highlight_id,
+                    });
+                }
+            }
+        }
+        // Adjacent highlights end before the next one starts.
+        highlight_endpoints.sort_by_key(|endpoint| (endpoint.offset, endpoint.is_start));
+    }
+    highlight_endpoints.into_iter().peekable()
+}
+
 impl<'a> Iterator for CustomHighlightsChunks<'a> {
     type Item = Chunk<'a>;
 
@@ -130,6 +199,15 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
                 break;
             }
         }
+        while let Some(endpoint) = self.semantic_highlight_endpoints.peek().copied() {
+            if endpoint.offset <= self.offset {
+                self.active_semantic_highlight = endpoint.is_start.then_some(endpoint.highlight_id);
+                self.semantic_highlight_endpoints.next();
+            } else {
+                next_highlight_endpoint = next_highlight_endpoint.min(endpoint.offset);
+                break;
+            }
+        }
 
         let chunk = self
             .buffer_chunk
@@ -148,6 +226,9 @@ impl<'a> Iterator for CustomHighlightsChunks<'a> {
             text: prefix,
             ..chunk.clone()
         };
+        if let Some(highlight_id) = self.active_semantic_highlight {
+            prefix.syntax_highlight_id = Some(highlight_id);
+        }
         if !self.active_highlights.is_empty() {
             let mut highlight_style = HighlightStyle::default();
             for active_highlight in self.active_highlights.values() {
@@ -17,10 +17,12 @@ mod blame_entry_tooltip;
 mod blink_manager;
 mod clangd_ext;
 mod code_context_menus;
+mod code_lens;
 pub mod display_map;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -31,6 +33,7 @@ mod inlay_hint_cache;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod merge_conflicts;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
@@ -38,6 +41,8 @@ mod proposed_changes_editor;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
+mod sticky_scroll;
 pub mod tasks;
 
 #[cfg(test)]
@@ -61,7 +66,8 @@ use convert_case::{Case, Casing};
 use display_map::*;
 pub use display_map::{DisplayPoint, FoldPlaceholder};
 pub use editor_settings::{
-    CurrentLineHighlight, EditorSettings, ScrollBeyondLastLine, SearchSettings, ShowScrollbar,
+    CurrentLineHighlight, EditorSettings, MinimapRender, ScrollBeyondLastLine, SearchSettings,
+    ShowMinimap, ShowScrollbar,
 };
 pub use editor_settings_controls::*;
 use element::LineWithInvisibles;
@@ -130,8 +136,9 @@ use multi_buffer::{
 use project::{
     lsp_store::{FormatTarget, FormatTrigger, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
-    CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
-    LspStore, Project, ProjectItem, ProjectTransaction, TaskSourceKind,
+    BookmarkStoreEvent, BufferSemanticTokens, CodeAction, CodeLens, Completion, CompletionIntent,
+    DocumentHighlight, DocumentLink, FoldingRange, FoldingRangeKind, InlayHint, Location,
+    LocationLink, LspStore, Project, ProjectItem, ProjectTransaction, TaskSourceKind,
 };
 use rand::prelude::*;
 use rpc::{proto::*, ErrorExt};
@@ -156,6 +163,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+pub use sticky_scroll::StickyHeader;
 pub use sum_tree::Bias;
 use sum_tree::TreeMap;
 use text::{BufferId, OffsetUtf16, Rope};
@@ -632,6 +640,16 @@ pub struct Editor {
     code_actions_task: Option<Task<Result<()>>>,
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
+    semantic_tokens_task: Option<Task<Option<()>>>,
+    folding_ranges_task: Option<Task<Option<()>>>,
+    code_lens_task: Option<Task<Option<()>>>,
+    code_lens_resolve_task: Option<Task<Option<()>>>,
+    code_lenses: HashMap<BufferId, Vec<CodeLens>>,
+    code_lens_blocks: HashMap<BufferId, Vec<(Anchor, CustomBlockId)>>,
+    merge_conflicts_task: Option<Task<Option<()>>>,
+    merge_conflicts: Vec<merge_conflicts::ConflictRegion<Anchor>>,
+    merge_conflict_blocks: Vec<CustomBlockId>,
+    sticky_headers_cache: Option<sticky_scroll::StickyHeadersCache>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
     pending_rename: Option<RenameState>,
     searchable: bool,
@@ -959,6 +977,8 @@ pub enum GotoDefinitionKind {
     Declaration,
     Type,
     Implementation,
+    Supertypes,
+    Subtypes,
 }
 
 #[derive(Debug, Clone)]
@@ -1155,6 +1175,16 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        semantic_tokens::refresh_semantic_tokens(editor, cx);
+                    } else if let project::Event::RefreshCodeLens = event {
+                        code_lens::refresh_code_lenses(editor, cx);
+                    } else if let project::Event::LanguageServerAdded(..) = event {
+                        semantic_tokens::refresh_semantic_tokens(editor, cx);
+                        folding_ranges::refresh_folding_ranges(editor, cx);
+                        code_lens::refresh_code_lenses(editor, cx);
+                    } else if let project::Event::WorktreeUpdatedGitRepositories(_) = event {
+                        merge_conflicts::refresh_merge_conflicts(editor, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1182,6 +1212,12 @@ impl Editor {
                         editor.tasks_update_task = Some(editor.refresh_runnables(cx));
                     }));
                 }
+                let bookmark_store = project.read(cx).bookmark_store().clone();
+                project_subscriptions.push(
+                    cx.subscribe(&bookmark_store, |_, _, _: &BookmarkStoreEvent, cx| {
+                        cx.notify()
+                    }),
+                );
             }
         }
 
@@ -1266,6 +1302,16 @@ impl Editor {
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            semantic_tokens_task: None,
+            folding_ranges_task: None,
+            code_lens_task: None,
+            code_lens_resolve_task: None,
+            code_lenses: HashMap::default(),
+            code_lens_blocks: HashMap::default(),
+            merge_conflicts_task: None,
+            merge_conflicts: Vec::new(),
+            merge_conflict_blocks: Vec::new(),
+            sticky_headers_cache: None,
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: EditorSettings::get_global(cx)
@@ -1344,6 +1390,10 @@ impl Editor {
             text_style_refinement: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
+        semantic_tokens::refresh_semantic_tokens(&mut this, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, cx);
+        code_lens::refresh_code_lenses(&mut this, cx);
+        merge_conflicts::refresh_merge_conflicts(&mut this, cx);
         this._subscriptions.extend(project_subscriptions);
 
         this.end_selection(cx);
@@ -4263,11 +4313,15 @@ impl Editor {
         self.available_code_actions.take();
     }
 
+    /// Adds a provider of code actions, replacing the one with the same id, so that
+    /// registering a provider again doesn't list its actions twice.
     pub fn push_code_action_provider(
         &mut self,
         provider: Rc<dyn CodeActionProvider>,
         cx: &mut ViewContext<Self>,
     ) {
+        self.code_action_providers
+            .retain(|existing| existing.id() != provider.id());
         self.code_action_providers.push(provider);
         self.refresh_code_actions(cx);
     }
@@ -5082,6 +5136,63 @@ impl Editor {
             }))
     }
 
+    fn render_bookmark_indicator(
+        &self,
+        row: DisplayRow,
+        name: Option<SharedString>,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(
+            ("bookmark_indicator", row.0 as usize),
+            ui::IconName::Bookmark,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Accent)
+        .when_some(name, |button, name| {
+            button.tooltip(move |cx| Tooltip::text(name.clone(), cx))
+        })
+        .on_click(cx.listener(move |editor, _e, cx| {
+            let snapshot = editor.snapshot(cx);
+            let point = DisplayPoint::new(row, 0).to_point(&snapshot.display_snapshot);
+            editor.toggle_bookmarks_at_points([point], cx);
+        }))
+    }
+
+    pub fn toggle_bookmark(&mut self, _: &ToggleBookmark, cx: &mut ViewContext<Self>) {
+        let heads = self
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .map(|selection| selection.head())
+            .collect::<Vec<_>>();
+        self.toggle_bookmarks_at_points(heads, cx);
+    }
+
+    fn toggle_bookmarks_at_points(
+        &mut self,
+        points: impl IntoIterator<Item = Point>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.as_ref() else {
+            return;
+        };
+        let bookmark_store = project.read(cx).bookmark_store().clone();
+        let mut toggled_rows = HashSet::default();
+        for point in points {
+            let Some((buffer, buffer_point, _)) =
+                self.buffer.read(cx).point_to_buffer_point(point, cx)
+            else {
+                continue;
+            };
+            if toggled_rows.insert((buffer.read(cx).remote_id(), buffer_point.row)) {
+                bookmark_store.update(cx, |store, cx| {
+                    store.toggle_bookmark(&buffer, buffer_point.row, cx)
+                });
+            }
+        }
+    }
+
     #[cfg(feature = "test-support")]
     pub fn context_menu_visible(&self) -> bool {
         self.context_menu
@@ -9336,6 +9447,22 @@ impl Editor {
         self.go_to_definition_of_kind(GotoDefinitionKind::Implementation, true, cx)
     }
 
+    pub fn go_to_supertypes(
+        &mut self,
+        _: &GoToSupertypes,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Navigated>> {
+        self.go_to_definition_of_kind(GotoDefinitionKind::Supertypes, false, cx)
+    }
+
+    pub fn go_to_subtypes(
+        &mut self,
+        _: &GoToSubtypes,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Navigated>> {
+        self.go_to_definition_of_kind(GotoDefinitionKind::Subtypes, false, cx)
+    }
+
     pub fn go_to_type_definition(
         &mut self,
         _: &GoToTypeDefinition,
@@ -9584,6 +9711,8 @@ impl Editor {
                     .update(&mut cx, |editor, cx| {
                         let tab_kind = match kind {
                             Some(GotoDefinitionKind::Implementation) => "Implementations",
+                            Some(GotoDefinitionKind::Supertypes) => "Supertypes",
+                            Some(GotoDefinitionKind::Subtypes) => "Subtypes",
                             _ => "Definitions",
                         };
                         let title = definitions
@@ -10617,6 +10746,25 @@ impl Editor {
         self.fold_creases(creases, true, cx);
     }
 
+    pub fn fold_comments(&mut self, _: &actions::FoldComments, cx: &mut ViewContext<Self>) {
+        self.fold_lsp_folding_ranges(FoldingRangeKind::Comment, cx);
+    }
+
+    pub fn fold_imports(&mut self, _: &actions::FoldImports, cx: &mut ViewContext<Self>) {
+        self.fold_lsp_folding_ranges(FoldingRangeKind::Imports, cx);
+    }
+
+    /// Folds the language server folding ranges of the given kind, see the `lsp_folding_ranges` setting.
+    fn fold_lsp_folding_ranges(&mut self, kind: FoldingRangeKind, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let creases = display_map
+            .lsp_folding_ranges_of_kind(kind)
+            .map(|range| Crease::simple(range, display_map.fold_placeholder.clone()))
+            .collect();
+
+        self.fold_creases(creases, true, cx);
+    }
+
     pub fn fold_recursive(&mut self, _: &actions::FoldRecursive, cx: &mut ViewContext<Self>) {
         let mut to_fold = Vec::new();
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
@@ -11915,35 +12063,31 @@ impl Editor {
         let mut results = Vec::new();
         for (color_fetcher, ranges) in self.background_highlights.values() {
             let color = color_fetcher(theme);
-            let start_ix = match ranges.binary_search_by(|probe| {
-                let cmp = probe
-                    .end
-                    .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
-                if cmp.is_gt() {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
-            }) {
-                Ok(i) | Err(i) => i,
-            };
-            for range in &ranges[start_ix..] {
-                if range
-                    .start
-                    .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
-                    .is_ge()
-                {
-                    break;
-                }
-
-                let start = range.start.to_display_point(display_snapshot);
-                let end = range.end.to_display_point(display_snapshot);
-                results.push((start..end, color))
-            }
+            results.extend(
+                background_highlight_display_ranges(ranges, &search_range, display_snapshot)
+                    .map(|range| (range, color)),
+            );
         }
         results
     }
 
+    /// Returns the background highlights of type `T` that intersect the given range.
+    pub fn background_highlights_of_type_in_range<T: 'static>(
+        &self,
+        search_range: Range<Anchor>,
+        display_snapshot: &DisplaySnapshot,
+        theme: &ThemeColors,
+    ) -> Vec<(Range<DisplayPoint>, Hsla)> {
+        let Some((color_fetcher, ranges)) = self.background_highlights.get(&TypeId::of::<T>())
+        else {
+            return Vec::new();
+        };
+        let color = color_fetcher(theme);
+        background_highlight_display_ranges(ranges, &search_range, display_snapshot)
+            .map(|range| (range, color))
+            .collect()
+    }
+
     pub fn background_highlight_row_ranges<T: 'static>(
         &self,
         search_range: Range<Anchor>,
@@ -12181,6 +12325,10 @@ impl Editor {
                 }
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lenses(self, cx);
+                merge_conflicts::refresh_merge_conflicts(self, cx);
                 if *singleton_buffer_edited {
                     if let Some(project) = &self.project {
                         let project = project.read(cx);
@@ -12242,12 +12390,30 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lenses(self, cx);
+                merge_conflicts::refresh_merge_conflicts(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
                 let buffer = self.buffer.read(cx);
                 self.registered_buffers
                     .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+                self.code_lenses
+                    .retain(|buffer_id, _| buffer.buffer(*buffer_id).is_some());
+                let mut code_lens_blocks = HashSet::default();
+                self.code_lens_blocks.retain(|buffer_id, block_ids| {
+                    let retain = buffer.buffer(*buffer_id).is_some();
+                    if !retain {
+                        code_lens_blocks.extend(block_ids.drain(..).map(|(_, block_id)| block_id));
+                    }
+                    retain
+                });
+                if !code_lens_blocks.is_empty() {
+                    self.remove_blocks(code_lens_blocks, None, cx);
+                }
+                merge_conflicts::refresh_merge_conflicts(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::ExcerptsEdited { ids } => {
@@ -12263,6 +12429,9 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lenses(self, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -12292,6 +12461,9 @@ impl Editor {
 
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(cx));
+        semantic_tokens::refresh_semantic_tokens(self, cx);
+        folding_ranges::refresh_folding_ranges(self, cx);
+        code_lens::refresh_code_lenses(self, cx);
         self.refresh_inline_completion(true, false, cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
@@ -12951,7 +13123,11 @@ fn get_unstaged_changes_for_buffers(
         this.update(&mut cx, |this, cx| {
             for change_set in change_sets {
                 if let Some(change_set) = change_set.log_err() {
-                    this.diff_map.add_change_set(change_set, cx);
+                    // Don't replace a change set that was explicitly provided for this buffer.
+                    let buffer_id = change_set.read(cx).buffer_id;
+                    if !this.diff_map.diff_bases.contains_key(&buffer_id) {
+                        this.diff_map.add_change_set(change_set, cx);
+                    }
                 }
             }
         })
@@ -13348,6 +13524,39 @@ pub trait SemanticsProvider {
 
     fn supports_inlay_hints(&self, buffer: &Model<Buffer>, cx: &AppContext) -> bool;
 
+    fn semantic_tokens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<BufferSemanticTokens>>>;
+
+    /// Returns `None` when no language server provides folding ranges for the buffer.
+    fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<FoldingRange>>>>;
+
+    fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<CodeLens>>>>;
+
+    fn resolve_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<CodeLens>>>;
+
+    fn apply_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<ProjectTransaction>>>;
+
     fn document_highlights(
         &self,
         buffer: &Model<Buffer>,
@@ -13355,6 +13564,14 @@ pub trait SemanticsProvider {
         cx: &mut AppContext,
     ) -> Option<Task<Result<Vec<DocumentHighlight>>>>;
 
+    /// The language server link covering the position, resolved so that it has a target if possible.
+    fn document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Option<DocumentLink>>>>;
+
     fn definitions(
         &self,
         buffer: &Model<Buffer>,
@@ -13419,6 +13636,9 @@ pub trait CompletionProvider {
 }
 
 pub trait CodeActionProvider {
+    /// Identifies the provider among the ones of an editor.
+    fn id(&self) -> &'static str;
+
     fn code_actions(
         &self,
         buffer: &Model<Buffer>,
@@ -13437,6 +13657,10 @@ pub trait CodeActionProvider {
 }
 
 impl CodeActionProvider for Model<Project> {
+    fn id(&self) -> &'static str {
+        "project"
+    }
+
     fn code_actions(
         &self,
         buffer: &Model<Buffer>,
@@ -13695,6 +13919,17 @@ impl SemanticsProvider for Model<Project> {
         }))
     }
 
+    fn document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Option<DocumentLink>>>> {
+        Some(self.update(cx, |project, cx| {
+            document_link_at(project, buffer, position, cx)
+        }))
+    }
+
     fn definitions(
         &self,
         buffer: &Model<Buffer>,
@@ -13707,6 +13942,9 @@ impl SemanticsProvider for Model<Project> {
             GotoDefinitionKind::Declaration => project.declaration(&buffer, position, cx),
             GotoDefinitionKind::Type => project.type_definition(&buffer, position, cx),
             GotoDefinitionKind::Implementation => project.implementation(&buffer, position, cx),
+            GotoDefinitionKind::Supertypes | GotoDefinitionKind::Subtypes => {
+                type_hierarchy_links(project, buffer, position, kind, cx)
+            }
         }))
     }
 
@@ -13723,6 +13961,52 @@ impl SemanticsProvider for Model<Project> {
             )
     }
 
+    fn semantic_tokens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<BufferSemanticTokens>>> {
+        Some(self.update(cx, |project, cx| project.semantic_tokens(buffer, cx)))
+    }
+
+    fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<FoldingRange>>>> {
+        self.update(cx, |project, cx| project.folding_ranges(buffer, cx))
+    }
+
+    fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<Vec<CodeLens>>>> {
+        Some(self.update(cx, |project, cx| project.code_lens(buffer, cx)))
+    }
+
+    fn resolve_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<CodeLens>>> {
+        Some(self.update(cx, |project, cx| {
+            project.resolve_code_lens(buffer, lens, cx)
+        }))
+    }
+
+    fn apply_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut AppContext,
+    ) -> Option<Task<Result<ProjectTransaction>>> {
+        Some(self.update(cx, |project, cx| {
+            project.apply_code_lens(buffer, lens, true, cx)
+        }))
+    }
+
     fn inlay_hints(
         &self,
         buffer_handle: Model<Buffer>,
@@ -13770,6 +14054,71 @@ impl SemanticsProvider for Model<Project> {
     }
 }
 
+/// Resolves the type hierarchy item at the given position and links it to its supertypes or subtypes.
+fn type_hierarchy_links(
+    project: &mut Project,
+    buffer: &Model<Buffer>,
+    position: text::Anchor,
+    kind: GotoDefinitionKind,
+    cx: &mut ModelContext<Project>,
+) -> Task<Result<Vec<LocationLink>>> {
+    let prepare = project.prepare_type_hierarchy(buffer, position, cx);
+    cx.spawn(|project, mut cx| async move {
+        let mut links = Vec::new();
+        for item in prepare.await? {
+            let related_items = project
+                .update(&mut cx, |project, cx| {
+                    if kind == GotoDefinitionKind::Supertypes {
+                        project.supertypes(&item, cx)
+                    } else {
+                        project.subtypes(&item, cx)
+                    }
+                })?
+                .await?;
+            let origin = Location {
+                buffer: item.location.buffer.clone(),
+                range: item.selection_range.clone(),
+            };
+            links.extend(related_items.into_iter().map(|related| LocationLink {
+                origin: Some(origin.clone()),
+                target: Location {
+                    buffer: related.location.buffer,
+                    range: related.selection_range,
+                },
+            }));
+        }
+        Ok(links)
+    })
+}
+
+fn document_link_at(
+    project: &mut Project,
+    buffer: &Model<Buffer>,
+    position: text::Anchor,
+    cx: &mut ModelContext<Project>,
+) -> Task<Result<Option<DocumentLink>>> {
+    let links = project.document_links(buffer, cx);
+    let buffer = buffer.clone();
+    cx.spawn(|project, mut cx| async move {
+        let links = links.await?;
+        let link = buffer.read_with(&cx, |buffer, _| {
+            links.into_iter().find(|link| {
+                link.range.start.cmp(&position, buffer).is_le()
+                    && link.range.end.cmp(&position, buffer).is_ge()
+            })
+        })?;
+        match link {
+            Some(link) if link.target().is_none() => project
+                .update(&mut cx, |project, cx| {
+                    project.resolve_document_link(buffer, link, cx)
+                })?
+                .await
+                .map(Some),
+            link => Ok(link),
+        }
+    })
+}
+
 fn inlay_hint_settings(
     location: Anchor,
     snapshot: &MultiBufferSnapshot,
@@ -13780,6 +14129,37 @@ fn inlay_hint_settings(
     language_settings(language, file, cx).inlay_hints
 }
 
+fn background_highlight_display_ranges<'a>(
+    ranges: &'a [Range<Anchor>],
+    search_range: &'a Range<Anchor>,
+    display_snapshot: &'a DisplaySnapshot,
+) -> impl Iterator<Item = Range<DisplayPoint>> + 'a {
+    let start_ix = match ranges.binary_search_by(|probe| {
+        let cmp = probe
+            .end
+            .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
+        if cmp.is_gt() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    };
+    ranges[start_ix..]
+        .iter()
+        .take_while(|range| {
+            range
+                .start
+                .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
+                .is_lt()
+        })
+        .map(|range| {
+            range.start.to_display_point(display_snapshot)
+                ..range.end.to_display_point(display_snapshot)
+        })
+}
+
 fn consume_contiguous_rows(
     contiguous_row_selections: &mut Vec<Selection<Point>>,
     selection: &Selection<Point>,
@@ -13974,7 +14354,7 @@ impl EditorSnapshot {
             }
         }
 
-        is_foldable |= self.starts_indent(buffer_row);
+        is_foldable |= self.starts_fold(buffer_row);
 
         if folded || (is_foldable && (row_contains_cursor || self.gutter_hovered)) {
             Some(
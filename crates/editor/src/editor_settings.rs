@@ -14,6 +14,7 @@ pub struct EditorSettings {
     pub hover_popover_delay: u64,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
     pub gutter: Gutter,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
@@ -35,6 +36,7 @@ pub struct EditorSettings {
     pub auto_signature_help: bool,
     pub show_signature_help_after_edits: bool,
     pub jupyter: Jupyter,
+    pub sticky_scroll: StickyScroll,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
@@ -91,6 +93,12 @@ pub struct JupyterContent {
     pub enabled: Option<bool>,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Toolbar {
     pub breadcrumbs: bool,
@@ -109,6 +117,13 @@ pub struct Scrollbar {
     pub axes: ScrollbarAxes,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub show: ShowMinimap,
+    pub width: f32,
+    pub render: MinimapRender,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -134,6 +149,32 @@ pub enum ShowScrollbar {
     Never,
 }
 
+/// When to show the minimap in the editor.
+///
+/// Default: never
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowMinimap {
+    /// Always show the minimap.
+    Always,
+    /// Reserve space for the minimap, but only show it while the mouse is over it.
+    OnHover,
+    /// Never show the minimap.
+    Never,
+}
+
+/// How the minimap renders the buffer's text.
+///
+/// Default: characters
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MinimapRender {
+    /// Render the text as tiny, syntax-colored characters.
+    Characters,
+    /// Render each word as a block of its syntax color.
+    Blocks,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -222,6 +263,8 @@ pub struct EditorSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
     /// Whether the editor will scroll beyond the last line.
@@ -302,6 +345,9 @@ pub struct EditorSettingsContent {
 
     /// Jupyter REPL settings.
     pub jupyter: Option<JupyterContent>,
+
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
 }
 
 // Toolbar related settings
@@ -322,6 +368,20 @@ pub struct ToolbarContent {
     pub selections_menu: Option<bool>,
 }
 
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the first lines of the scopes enclosing the top of the
+    /// viewport, such as impls and functions, at the top of the editor.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The maximum number of nested scopes to pin.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
 /// Scrollbar related settings
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct ScrollbarContent {
@@ -353,6 +413,23 @@ pub struct ScrollbarContent {
     pub axes: Option<ScrollbarAxesContent>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// When to show the minimap next to the editor's scrollbar.
+    ///
+    /// Default: never
+    pub show: Option<ShowMinimap>,
+    /// The width of the minimap, in pixels.
+    ///
+    /// Default: 100
+    pub width: Option<f32>,
+    /// Whether the minimap renders the text as characters or as blocks.
+    ///
+    /// Default: characters
+    pub render: Option<MinimapRender>,
+}
+
 /// Forcefully enable or disable the scrollbar for each axis
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
 pub struct ScrollbarAxesContent {
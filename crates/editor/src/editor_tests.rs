@@ -13575,6 +13575,521 @@ async fn test_active_indent_guide_non_matching_indent(cx: &mut gpui::TestAppCont
     );
 }
 
+#[gpui::test]
+async fn test_lsp_folding_ranges_without_server_support(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.lsp_folding_ranges = Some(true);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(lsp::ServerCapabilities::default(), cx).await;
+    cx.set_state(indoc! {"
+        fn main() {
+            ˇlet a = 1;
+        }
+    "});
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+
+    // The server doesn't provide folding ranges, so folds follow the indentation.
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        assert!(snapshot.starts_fold(MultiBufferRow(0)));
+        assert!(!snapshot.starts_fold(MultiBufferRow(1)));
+        assert!(snapshot.crease_for_buffer_row(MultiBufferRow(0)).is_some());
+    });
+}
+
+#[gpui::test]
+async fn test_lsp_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.lsp_folding_ranges = Some(true);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+    let folding_ranges = Arc::new(Mutex::new(Vec::new()));
+    cx.handle_request::<lsp::request::FoldingRangeRequest, _, _>({
+        let folding_ranges = folding_ranges.clone();
+        move |_, _, _| {
+            let folding_ranges = folding_ranges.lock().clone();
+            async move { Ok(Some(folding_ranges)) }
+        }
+    });
+    let range = |start_line, end_line, kind| lsp::FoldingRange {
+        start_line,
+        start_character: None,
+        end_line,
+        end_character: None,
+        kind,
+        collapsed_text: None,
+    };
+
+    // An empty response means that nothing can be folded, rather than that folds follow the
+    // indentation.
+    cx.set_state(indoc! {"
+        use std::fmt;
+        use std::io;
+
+        fn main() {
+            // a
+            // b
+            ˇlet a = 1;
+        }
+    "});
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        assert!(!snapshot.starts_fold(MultiBufferRow(3)));
+        assert!(snapshot.crease_for_buffer_row(MultiBufferRow(3)).is_none());
+    });
+
+    *folding_ranges.lock() = vec![
+        range(0, 1, Some(lsp::FoldingRangeKind::Imports)),
+        range(3, 7, None),
+        range(4, 5, Some(lsp::FoldingRangeKind::Comment)),
+    ];
+    cx.update_editor(|editor, cx| editor.handle_input("2", cx));
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        assert!(snapshot.starts_fold(MultiBufferRow(0)));
+        assert!(snapshot.starts_fold(MultiBufferRow(3)));
+        assert!(snapshot.starts_fold(MultiBufferRow(4)));
+        assert!(!snapshot.starts_fold(MultiBufferRow(6)));
+
+        editor.fold_comments(&FoldComments, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::fmt;
+                use std::io;
+
+                fn main() {
+                    // a⋯
+                    2let a = 1;
+                }
+            "}
+        );
+
+        editor.fold_imports(&FoldImports, cx);
+        assert_eq!(
+            editor.display_text(cx),
+            indoc! {"
+                use std::fmt;⋯
+
+                fn main() {
+                    // a⋯
+                    2let a = 1;
+                }
+            "}
+        );
+    });
+}
+
+#[gpui::test]
+fn test_push_code_action_provider_replaces_same_id(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    struct Provider(&'static str);
+
+    impl CodeActionProvider for Provider {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+
+        fn code_actions(
+            &self,
+            _: &Model<Buffer>,
+            _: Range<text::Anchor>,
+            _: &mut WindowContext,
+        ) -> Task<Result<Vec<CodeAction>>> {
+            Task::ready(Ok(Vec::new()))
+        }
+
+        fn apply_code_action(
+            &self,
+            _: Model<Buffer>,
+            _: CodeAction,
+            _: ExcerptId,
+            _: bool,
+            _: &mut WindowContext,
+        ) -> Task<Result<ProjectTransaction>> {
+            Task::ready(Ok(ProjectTransaction::default()))
+        }
+    }
+
+    let editor = cx.add_window(|cx| {
+        let buffer = MultiBuffer::build_simple("abc", cx);
+        build_editor(buffer, cx)
+    });
+    _ = editor.update(cx, |editor, cx| {
+        editor.push_code_action_provider(Rc::new(Provider("a")), cx);
+        editor.push_code_action_provider(Rc::new(Provider("b")), cx);
+        // Registering a provider again, e.g. when its editor moves to another pane, replaces it.
+        editor.push_code_action_provider(Rc::new(Provider("a")), cx);
+        assert_eq!(
+            editor
+                .code_action_providers
+                .iter()
+                .map(|provider| provider.id())
+                .collect::<Vec<_>>(),
+            ["b", "a"]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_code_lens_blocks_update_in_place(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.code_lens = Some(true);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            code_lens_provider: Some(lsp::CodeLensOptions {
+                resolve_provider: None,
+            }),
+            ..lsp::ServerCapabilities::default()
+        },
+        cx,
+    )
+    .await;
+
+    fn lens(row: u32, title: &str) -> lsp::CodeLens {
+        lsp::CodeLens {
+            range: lsp::Range::new(lsp::Position::new(row, 3), lsp::Position::new(row, 6)),
+            command: Some(lsp::Command {
+                title: title.into(),
+                command: "rust-analyzer.runSingle".into(),
+                arguments: None,
+            }),
+            data: None,
+        }
+    }
+
+    cx.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _, _| async move {
+        Ok(Some(vec![lens(0, "Run"), lens(4, "Run")]))
+    });
+    cx.set_state(indoc! {"
+        fn one() {
+            ˇa();
+        }
+
+        fn two() {
+            b();
+        }
+    "});
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    let block_ids = cx.update_editor(|editor, _| {
+        editor
+            .code_lens_blocks
+            .values()
+            .flatten()
+            .map(|(_, block_id)| *block_id)
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(block_ids.len(), 2);
+
+    // Lenses that stay on their lines, even if these move, keep their blocks.
+    cx.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _, _| async move {
+        Ok(Some(vec![lens(0, "Run"), lens(5, "Run again")]))
+    });
+    cx.simulate_keystrokes("enter");
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| {
+        assert_eq!(
+            editor
+                .code_lens_blocks
+                .values()
+                .flatten()
+                .map(|(_, block_id)| *block_id)
+                .collect::<Vec<_>>(),
+            block_ids
+        );
+    });
+
+    // Lenses that are gone remove their blocks.
+    cx.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _, _| async move {
+        Ok(Some(vec![lens(5, "Run again")]))
+    });
+    cx.simulate_keystrokes("x");
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| {
+        assert_eq!(
+            editor
+                .code_lens_blocks
+                .values()
+                .flatten()
+                .map(|(_, block_id)| *block_id)
+                .collect::<Vec<_>>(),
+            vec![block_ids[1]]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_code_lens_blocks_on_the_same_point(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.code_lens = Some(true);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            code_lens_provider: Some(lsp::CodeLensOptions {
+                resolve_provider: None,
+            }),
+            ..lsp::ServerCapabilities::default()
+        },
+        cx,
+    )
+    .await;
+
+    fn lens(row: u32) -> lsp::CodeLens {
+        lsp::CodeLens {
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 3)),
+            command: Some(lsp::Command {
+                title: "Run".into(),
+                command: "rust-analyzer.runSingle".into(),
+                arguments: None,
+            }),
+            data: None,
+        }
+    }
+
+    cx.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _, _| async move {
+        Ok(Some(vec![lens(0), lens(1)]))
+    });
+    cx.set_state("ˇone();\ntwo();\n");
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| {
+        assert_eq!(editor.code_lens_blocks.values().flatten().count(), 2);
+    });
+
+    // Deleting the first line moves both blocks onto the same point, and only one of them stays.
+    cx.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _, _| async move {
+        Ok(Some(vec![lens(0)]))
+    });
+    cx.set_selections_state("«one();\nˇ»two();\n");
+    cx.simulate_keystrokes("backspace");
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert_eq!(editor.text(cx), "two();\n");
+        assert_eq!(editor.code_lens_blocks.values().flatten().count(), 1);
+        // One row for the block, and the two rows of the buffer.
+        assert_eq!(editor.snapshot(cx).max_point().row(), DisplayRow(2));
+    });
+}
+
+#[gpui::test]
+async fn test_code_lens_resolve_visible_and_refresh(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.code_lens = Some(true);
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            code_lens_provider: Some(lsp::CodeLensOptions {
+                resolve_provider: Some(true),
+            }),
+            ..lsp::ServerCapabilities::default()
+        },
+        cx,
+    )
+    .await;
+
+    let lens_requests = Arc::new(AtomicUsize::new(0));
+    cx.handle_request::<lsp::request::CodeLensRequest, _, _>({
+        let lens_requests = lens_requests.clone();
+        move |_, _, _| {
+            lens_requests.fetch_add(1, atomic::Ordering::SeqCst);
+            async move {
+                Ok(Some(
+                    [0, 150]
+                        .into_iter()
+                        .map(|row| lsp::CodeLens {
+                            range: lsp::Range::new(
+                                lsp::Position::new(row, 0),
+                                lsp::Position::new(row, 3),
+                            ),
+                            command: None,
+                            data: Some(json!(row)),
+                        })
+                        .collect(),
+                ))
+            }
+        }
+    });
+    let resolved_rows = Arc::new(Mutex::new(Vec::new()));
+    cx.handle_request::<lsp::request::CodeLensResolve, _, _>({
+        let resolved_rows = resolved_rows.clone();
+        move |_, mut lens, _| {
+            resolved_rows
+                .lock()
+                .push(lens.data.as_ref().unwrap().as_u64().unwrap());
+            lens.command = Some(lsp::Command {
+                title: "Run".into(),
+                command: "rust-analyzer.runSingle".into(),
+                arguments: None,
+            });
+            async move { Ok(lens) }
+        }
+    });
+
+    cx.set_state(&format!("ˇ{}", "a();\n".repeat(200)));
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    assert_eq!(lens_requests.load(atomic::Ordering::SeqCst), 1);
+    assert_eq!(*resolved_rows.lock(), [0]);
+    cx.update_editor(|editor, _| {
+        assert_eq!(editor.code_lens_blocks.values().flatten().count(), 1);
+    });
+
+    // Lenses are resolved once they are scrolled into view.
+    cx.update_editor(|editor, cx| {
+        editor.set_scroll_position(gpui::Point::new(0., 150.), cx);
+    });
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    assert_eq!(*resolved_rows.lock(), [0, 150]);
+    cx.update_editor(|editor, _| {
+        assert_eq!(editor.code_lens_blocks.values().flatten().count(), 2);
+    });
+
+    // A refresh requested by the server fetches the lenses again,
+    // but keeps the commands of the lenses that did not change.
+    cx.lsp
+        .request::<lsp::request::CodeLensRefresh>(())
+        .await
+        .expect("code lens refresh request failed");
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.run_until_parked();
+    assert_eq!(lens_requests.load(atomic::Ordering::SeqCst), 2);
+    assert_eq!(*resolved_rows.lock(), [0, 150]);
+    cx.update_editor(|editor, _| {
+        assert_eq!(editor.code_lens_blocks.values().flatten().count(), 2);
+    });
+}
+
+#[gpui::test]
+async fn test_sticky_headers(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_outline_query(
+            r#"
+            (impl_item "impl" @context type: (_) @name) @item
+            (function_item "fn" @context name: (_) @name) @item
+            "#,
+        )
+        .unwrap(),
+    );
+
+    let text = r#"
+        impl Foo {
+            fn one() {
+                a();
+                b();
+            }
+
+            fn two() {
+                c();
+            }
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+    editor
+        .condition::<crate::EditorEvent>(cx, |editor, cx| !editor.buffer.read(cx).is_parsing(cx))
+        .await;
+
+    editor.update(cx, |editor, cx| {
+        let snapshot = editor.snapshot(cx).display_snapshot;
+        let header_rows = |top_row: u32, max_depth: usize| {
+            crate::sticky_scroll::sticky_headers(&snapshot, DisplayRow(top_row), max_depth)
+                .into_iter()
+                .map(|header| header.row.0)
+                .collect::<Vec<_>>()
+        };
+
+        // Scopes whose first line is still visible aren't pinned.
+        assert_eq!(header_rows(0, 5), Vec::<u32>::new());
+        // Each pinned header covers the line below the previous one.
+        assert_eq!(header_rows(1, 5), vec![0, 1]);
+        assert_eq!(header_rows(2, 5), vec![0, 1]);
+        assert_eq!(header_rows(2, 1), vec![0]);
+        // Scopes that have ended are no longer pinned.
+        assert_eq!(header_rows(5, 5), vec![0]);
+        assert_eq!(header_rows(7, 5), vec![0, 6]);
+
+        editor.jump_to_sticky_header(
+            &StickyHeader {
+                row: MultiBufferRow(6),
+            },
+            1,
+            cx,
+        );
+        assert_eq!(
+            editor.selections.ranges::<Point>(cx),
+            [Point::new(6, 4)..Point::new(6, 4)]
+        );
+    });
+
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.sticky_scroll = Some(crate::editor_settings::StickyScrollContent {
+                    enabled: Some(true),
+                    max_depth: None,
+                });
+            });
+        });
+    });
+    let header_rows = |editor: &mut Editor, top_row: u32, cx: &mut ViewContext<Editor>| {
+        let snapshot = editor.snapshot(cx).display_snapshot;
+        editor
+            .sticky_headers(&snapshot, DisplayRow(top_row), cx)
+            .into_iter()
+            .map(|header| header.row.0)
+            .collect::<Vec<_>>()
+    };
+    editor.update(cx, |editor, cx| {
+        assert_eq!(header_rows(editor, 2, cx), vec![0, 1]);
+        // The cached headers are reused for the same top row.
+        assert_eq!(header_rows(editor, 2, cx), vec![0, 1]);
+        assert_eq!(header_rows(editor, 7, cx), vec![0, 6]);
+        editor.buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(0, 0)..Point::new(0, 0), "\n")], None, cx)
+        });
+    });
+    editor
+        .condition::<crate::EditorEvent>(cx, |editor, cx| !editor.buffer.read(cx).is_parsing(cx))
+        .await;
+    // Edits invalidate the cached headers.
+    editor.update(cx, |editor, cx| {
+        assert_eq!(header_rows(editor, 7, cx), vec![1, 7]);
+    });
+}
+
 #[gpui::test]
 fn test_crease_insertion_and_rendering(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -14363,6 +14878,90 @@ async fn test_multi_buffer_with_single_excerpt_folding(cx: &mut gpui::TestAppCon
     );
 }
 
+#[gpui::test]
+async fn test_merge_conflict_resolution(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".git": {},
+            "main.rs": indoc! {"
+                one
+                <<<<<<< HEAD
+                two
+                =======
+                three
+                >>>>>>> branch
+                four
+                <<<<<<< HEAD
+                five
+                =======
+                six
+                >>>>>>> branch
+            "},
+        }),
+    )
+    .await;
+    fs.set_status_for_repo_via_git_operation(
+        Path::new("/root/.git"),
+        &[(
+            Path::new("main.rs"),
+            ::git::repository::GitFileStatus::Conflict(
+                ::git::repository::UnmergedStatus::BothModified,
+            ),
+        )],
+    );
+    let project = Project::test(fs, ["/root".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/root/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) =
+        cx.add_window_view(|cx| build_editor_with_project(project.clone(), buffer, cx));
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+
+    let second_block_id = editor.update(cx, |editor, _| editor.merge_conflict_blocks[1]);
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.merge_conflicts.len(), 2);
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(2, 0)..Point::new(2, 0)])
+        });
+        editor.accept_theirs(&AcceptTheirs, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                one
+                three
+                four
+                <<<<<<< HEAD
+                five
+                =======
+                six
+                >>>>>>> branch
+            "}
+        );
+    });
+    cx.executor().advance_clock(Duration::from_millis(100));
+    cx.run_until_parked();
+
+    editor.update(cx, |editor, cx| {
+        // The block of the unchanged conflict is kept.
+        assert_eq!(editor.merge_conflicts.len(), 1);
+        assert_eq!(editor.merge_conflict_blocks, [second_block_id]);
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(4, 0)..Point::new(4, 0)])
+        });
+        editor.accept_both(&AcceptBoth, cx);
+        assert_eq!(editor.text(cx), "one\nthree\nfour\nfive\nsix\n");
+    });
+}
+
 #[gpui::test]
 fn test_inline_completion_text(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
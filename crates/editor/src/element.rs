@@ -5,8 +5,8 @@ use crate::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, MinimapRender, MultiCursorModifier,
+        ScrollBeyondLastLine, ShowMinimap, ShowScrollbar,
     },
     git::blame::{CommitDetails, GitBlame},
     hover_popover::{
@@ -17,6 +17,7 @@ use crate::{
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
     scroll::{axis_pair, scroll_amount::ScrollAmount, AxisPair},
+    sticky_scroll::sticky_header_text,
     BlockId, ChunkReplacement, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode, EditorSettings,
     EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GutterDimensions, HalfPageDown,
@@ -185,6 +186,9 @@ impl EditorElement {
         register_action(view, cx, Editor::select_page_down);
         register_action(view, cx, Editor::select_page_up);
         register_action(view, cx, Editor::cancel);
+        register_action(view, cx, Editor::accept_ours);
+        register_action(view, cx, Editor::accept_theirs);
+        register_action(view, cx, Editor::accept_both);
         register_action(view, cx, Editor::newline);
         register_action(view, cx, Editor::newline_above);
         register_action(view, cx, Editor::newline_below);
@@ -335,6 +339,12 @@ impl EditorElement {
                 .go_to_implementation_split(a, cx)
                 .detach_and_log_err(cx);
         });
+        register_action(view, cx, |editor, a, cx| {
+            editor.go_to_supertypes(a, cx).detach_and_log_err(cx);
+        });
+        register_action(view, cx, |editor, a, cx| {
+            editor.go_to_subtypes(a, cx).detach_and_log_err(cx);
+        });
         register_action(view, cx, |editor, a, cx| {
             editor.go_to_type_definition(a, cx).detach_and_log_err(cx);
         });
@@ -349,6 +359,8 @@ impl EditorElement {
         register_action(view, cx, Editor::fold_at_level);
         register_action(view, cx, Editor::fold_all);
         register_action(view, cx, Editor::fold_function_bodies);
+        register_action(view, cx, Editor::fold_comments);
+        register_action(view, cx, Editor::fold_imports);
         register_action(view, cx, Editor::fold_at);
         register_action(view, cx, Editor::fold_recursive);
         register_action(view, cx, Editor::toggle_fold);
@@ -360,6 +372,7 @@ impl EditorElement {
         register_action(view, cx, Editor::fold_selected_ranges);
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
+        register_action(view, cx, Editor::toggle_bookmark);
         register_action(view, cx, Editor::open_excerpts);
         register_action(view, cx, Editor::open_excerpts_in_split);
         register_action(view, cx, Editor::open_proposed_changes_editor);
@@ -1176,6 +1189,285 @@ impl EditorElement {
         cursor_layouts
     }
 
+    fn minimap_width(&self, snapshot: &EditorSnapshot, cx: &WindowContext) -> Pixels {
+        let settings = EditorSettings::get_global(cx).minimap;
+        if snapshot.mode == EditorMode::Full && settings.show != ShowMinimap::Never {
+            px(settings.width.max(0.))
+        } else {
+            Pixels::ZERO
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        width: Pixels,
+        scroll_position: gpui::Point<f32>,
+        max_scroll_top: f32,
+        height_in_lines: f32,
+        line_height: Pixels,
+        font_size: Pixels,
+        em_advance: Pixels,
+        cx: &mut WindowContext,
+    ) -> Option<MinimapLayout> {
+        if width == Pixels::ZERO {
+            return None;
+        }
+
+        let settings = EditorSettings::get_global(cx).minimap;
+        let minimap_bounds = Bounds::from_corners(
+            point(self.scrollbar_left(&bounds) - width, bounds.top()),
+            point(self.scrollbar_left(&bounds), bounds.bottom()),
+        );
+        let hitbox = cx.insert_hitbox(minimap_bounds, false);
+
+        let row_count = snapshot.max_point().row().next_row().as_f32();
+        let minimap_rows = minimap_bounds.size.height / MinimapLayout::LINE_HEIGHT;
+        let geometry = MinimapGeometry::new(
+            scroll_position.y,
+            max_scroll_top,
+            row_count,
+            minimap_rows,
+            height_in_lines,
+        );
+        let start_row = DisplayRow(geometry.top_row as u32);
+        let end_row =
+            DisplayRow(((geometry.top_row + minimap_rows).ceil() as u32).min(row_count as u32));
+
+        // Render the text at the minimap's scale, so that columns line up with the editor.
+        let scale = MinimapLayout::LINE_HEIGHT / line_height;
+        let column_width = em_advance * scale;
+        let max_columns = (width / column_width).ceil() as usize;
+
+        let mut rows = vec![Vec::<(String, Hsla)>::new()];
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            let color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            for (ix, text) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    rows.push(Vec::new());
+                }
+                if !text.is_empty() {
+                    rows.last_mut().unwrap().push((text.to_string(), color));
+                }
+            }
+        }
+
+        let lines = rows
+            .into_iter()
+            .take((start_row..end_row).len())
+            .map(|runs| match settings.render {
+                MinimapRender::Characters => {
+                    let mut text = String::new();
+                    let mut text_runs = Vec::new();
+                    for (run, color) in runs {
+                        let remaining_columns = max_columns.saturating_sub(text.chars().count());
+                        let len = text.len();
+                        text.extend(run.chars().take(remaining_columns));
+                        if text.len() > len {
+                            text_runs.push(TextRun {
+                                len: text.len() - len,
+                                font: self.style.text.font(),
+                                color,
+                                background_color: None,
+                                underline: None,
+                                strikethrough: None,
+                            });
+                        }
+                    }
+                    MinimapLine::Characters(
+                        cx.text_system()
+                            .shape_line(text.into(), font_size * scale, &text_runs)
+                            .unwrap(),
+                    )
+                }
+                MinimapRender::Blocks => {
+                    let mut blocks = Vec::<(Range<usize>, Hsla)>::new();
+                    let mut column = 0;
+                    for (run, color) in runs {
+                        for character in run.chars() {
+                            if column >= max_columns {
+                                break;
+                            }
+                            if !character.is_whitespace() {
+                                match blocks.last_mut() {
+                                    Some((columns, block_color))
+                                        if columns.end == column && *block_color == color =>
+                                    {
+                                        columns.end += 1;
+                                    }
+                                    _ => blocks.push((column..column + 1, color)),
+                                }
+                            }
+                            column += 1;
+                        }
+                    }
+                    MinimapLine::Blocks(blocks)
+                }
+            })
+            .collect();
+
+        let (highlights, markers) = self.layout_minimap_markers(
+            snapshot,
+            start_row..end_row,
+            minimap_bounds,
+            geometry.top_row,
+            cx,
+        );
+
+        Some(MinimapLayout {
+            hitbox,
+            show: settings.show,
+            geometry,
+            start_row,
+            column_width,
+            lines,
+            highlights,
+            markers,
+        })
+    }
+
+    /// Lays out the search results, selected symbol occurrences, diagnostics
+    /// and git hunks that the scrollbar shows, for the rows in the minimap.
+    fn layout_minimap_markers(
+        &self,
+        snapshot: &EditorSnapshot,
+        rows: Range<DisplayRow>,
+        minimap_bounds: Bounds<Pixels>,
+        top_row: f32,
+        cx: &mut WindowContext,
+    ) -> (Vec<PaintQuad>, Vec<PaintQuad>) {
+        const EDGE_MARKER_WIDTH: Pixels = px(2.);
+
+        let scrollbar_settings = EditorSettings::get_global(cx).scrollbar;
+        let theme = cx.theme().clone();
+        let row_bounds = |start: DisplayRow, end: DisplayRow, x_range: Range<Pixels>| {
+            Bounds::from_corners(
+                point(
+                    x_range.start,
+                    minimap_bounds.top() + (start.as_f32() - top_row) * MinimapLayout::LINE_HEIGHT,
+                ),
+                point(
+                    x_range.end,
+                    minimap_bounds.top()
+                        + (end.next_row().as_f32() - top_row) * MinimapLayout::LINE_HEIGHT,
+                ),
+            )
+        };
+        let full_width = minimap_bounds.left()..minimap_bounds.right();
+        let left_edge = minimap_bounds.left()..minimap_bounds.left() + EDGE_MARKER_WIDTH;
+        let right_edge = minimap_bounds.right() - EDGE_MARKER_WIDTH..minimap_bounds.right();
+
+        let start_anchor = snapshot
+            .buffer_snapshot
+            .anchor_before(DisplayPoint::new(rows.start, 0).to_offset(snapshot, Bias::Left));
+        let end_anchor = if rows.end > snapshot.max_point().row() {
+            Anchor::max()
+        } else {
+            snapshot
+                .buffer_snapshot
+                .anchor_before(DisplayPoint::new(rows.end, 0).to_offset(snapshot, Bias::Right))
+        };
+
+        let mut highlights = Vec::new();
+        let editor = self.editor.read(cx);
+        let mut highlight_ranges = Vec::new();
+        if scrollbar_settings.search_results {
+            highlight_ranges.extend(
+                editor.background_highlights_of_type_in_range::<BufferSearchHighlights>(
+                    start_anchor..end_anchor,
+                    &snapshot.display_snapshot,
+                    theme.colors(),
+                ),
+            );
+        }
+        if scrollbar_settings.selected_symbol {
+            highlight_ranges.extend(
+                editor.background_highlights_of_type_in_range::<DocumentHighlightRead>(
+                    start_anchor..end_anchor,
+                    &snapshot.display_snapshot,
+                    theme.colors(),
+                ),
+            );
+            highlight_ranges.extend(
+                editor.background_highlights_of_type_in_range::<DocumentHighlightWrite>(
+                    start_anchor..end_anchor,
+                    &snapshot.display_snapshot,
+                    theme.colors(),
+                ),
+            );
+        }
+        for (range, color) in highlight_ranges {
+            highlights.push(fill(
+                row_bounds(range.start.row(), range.end.row(), full_width.clone()),
+                color,
+            ));
+        }
+
+        let mut markers = Vec::new();
+        if scrollbar_settings.git_diff {
+            for hunk in snapshot
+                .diff_map
+                .diff_hunks_in_range(start_anchor..end_anchor, &snapshot.buffer_snapshot)
+            {
+                let start_row = MultiBufferPoint::new(hunk.row_range.start.0, 0)
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let mut end_row = MultiBufferPoint::new(hunk.row_range.end.0, 0)
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                if end_row != start_row {
+                    end_row.0 -= 1;
+                }
+                let color = match hunk_status(&hunk) {
+                    DiffHunkStatus::Added => theme.status().created,
+                    DiffHunkStatus::Modified => theme.status().modified,
+                    DiffHunkStatus::Removed => theme.status().deleted,
+                };
+                markers.push(fill(
+                    row_bounds(start_row, end_row, left_edge.clone()),
+                    color,
+                ));
+            }
+        }
+
+        if scrollbar_settings.diagnostics {
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<_, Point>(start_anchor..end_anchor, false)
+                // Paint the most severe diagnostics last.
+                .sorted_by_key(|diagnostic| cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let start_row = diagnostic
+                    .range
+                    .start
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let end_row = diagnostic
+                    .range
+                    .end
+                    .to_display_point(&snapshot.display_snapshot)
+                    .row();
+                let color = match diagnostic.diagnostic.severity {
+                    DiagnosticSeverity::ERROR => theme.status().error,
+                    DiagnosticSeverity::WARNING => theme.status().warning,
+                    DiagnosticSeverity::INFORMATION => theme.status().info,
+                    _ => theme.status().hint,
+                };
+                markers.push(fill(
+                    row_bounds(start_row, end_row, right_edge.clone()),
+                    color,
+                ));
+            }
+        }
+
+        (highlights, markers)
+    }
+
     fn layout_scrollbars(
         &self,
         snapshot: &EditorSnapshot,
@@ -1598,6 +1890,98 @@ impl EditorElement {
         Some(element)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        start_row: DisplayRow,
+        hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        gutter_dimensions: &GutterDimensions,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        cx: &mut WindowContext,
+    ) -> Option<AnyElement> {
+        let headers = self.editor.update(cx, |editor, cx| {
+            editor.sticky_headers(&snapshot.display_snapshot, start_row, cx)
+        });
+        if headers.is_empty() {
+            return None;
+        }
+
+        let include_line_numbers = snapshot.show_line_numbers.unwrap_or_else(|| {
+            EditorSettings::get_global(cx).gutter.line_numbers && snapshot.mode == EditorMode::Full
+        });
+        let colors = cx.theme().colors();
+        let border_color = colors.border_variant;
+        let hover_background = colors.editor_active_line_background;
+        let line_number_color = colors.editor_line_number;
+
+        let mut element = v_flex()
+            .id("sticky-headers")
+            .w(text_hitbox.right() - hitbox.left())
+            .bg(self.style.background)
+            .border_b_1()
+            .border_color(border_color)
+            .font(self.style.text.font())
+            .children(headers.into_iter().enumerate().map(|(depth, header)| {
+                let (text, highlights) =
+                    sticky_header_text(&snapshot.buffer_snapshot, &header, &self.style.syntax, cx);
+                let editor = self.editor.clone();
+                h_flex()
+                    .id(("sticky-header", depth))
+                    .w_full()
+                    .h(line_height)
+                    .cursor_pointer()
+                    .hover(move |style| style.bg(hover_background))
+                    .child(
+                        h_flex()
+                            .flex_none()
+                            .w(gutter_dimensions.width)
+                            .pr(gutter_dimensions.right_padding)
+                            .justify_end()
+                            .text_color(line_number_color)
+                            .when(include_line_numbers, |this| {
+                                this.child((header.row.0 + 1).to_string())
+                            }),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .pl(gutter_dimensions.margin)
+                            .child(
+                                div()
+                                    .ml(-scroll_pixel_position.x)
+                                    .whitespace_nowrap()
+                                    .child(
+                                        gpui::StyledText::new(text)
+                                            .with_highlights(&self.style.text, highlights),
+                                    ),
+                            ),
+                    )
+                    .on_mouse_down(MouseButton::Left, move |_, cx| {
+                        // Keep the editor from handling the click as well.
+                        cx.prevent_default();
+                        editor.update(cx, |editor, cx| {
+                            editor.jump_to_sticky_header(&header, depth, cx)
+                        });
+                    })
+            }))
+            .into_any_element();
+
+        element.prepaint_as_root(
+            hitbox.origin,
+            size(
+                AvailableSpace::Definite(text_hitbox.right() - hitbox.left()),
+                AvailableSpace::MinContent,
+            ),
+            cx,
+        );
+
+        Some(element)
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_blame_entries(
         &self,
@@ -1793,7 +2177,7 @@ impl EditorElement {
         rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
         snapshot: &EditorSnapshot,
         cx: &mut WindowContext,
-    ) -> Vec<AnyElement> {
+    ) -> Vec<(DisplayRow, AnyElement)> {
         self.editor.update(cx, |editor, cx| {
             let active_task_indicator_row =
                 if let Some(crate::CodeContextMenu::CodeActions(CodeActionsMenu {
@@ -1867,7 +2251,81 @@ impl EditorElement {
                         rows_with_hunk_bounds,
                         cx,
                     );
-                    Some(button)
+                    Some((display_row, button))
+                })
+                .collect_vec()
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmark_indicators(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        occupied_rows: &HashSet<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            let Some(project) = editor.project.as_ref() else {
+                return Vec::new();
+            };
+            let bookmark_store = project.read(cx).bookmark_store().read(cx);
+            let start =
+                snapshot.display_point_to_point(DisplayPoint::new(range.start, 0), Bias::Left);
+            let end = snapshot.display_point_to_point(DisplayPoint::new(range.end, 0), Bias::Right);
+
+            let mut bookmarks = Vec::new();
+            for excerpt in snapshot.buffer_snapshot.excerpts_for_range(start..end) {
+                let buffer = excerpt.buffer();
+                let excerpt_range = excerpt.buffer_range();
+                let excerpt_start = buffer.summary_for_anchor::<Point>(&excerpt_range.start);
+                let excerpt_end = buffer.summary_for_anchor::<Point>(&excerpt_range.end);
+                for (anchor, name) in bookmark_store.bookmarks_for_buffer(buffer.remote_id()) {
+                    if !anchor.is_valid(buffer) {
+                        continue;
+                    }
+                    let buffer_point = buffer.summary_for_anchor::<Point>(&anchor);
+                    if buffer_point < excerpt_start || buffer_point > excerpt_end {
+                        continue;
+                    }
+                    let multibuffer_point = excerpt.map_point_from_buffer(buffer_point);
+                    let multibuffer_row = MultiBufferRow(multibuffer_point.row);
+                    if snapshot.is_line_folded(multibuffer_row)
+                        && multibuffer_row
+                            .0
+                            .checked_sub(1)
+                            .map_or(false, |previous_row| {
+                                snapshot.is_line_folded(MultiBufferRow(previous_row))
+                            })
+                    {
+                        continue;
+                    }
+                    let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    if range.contains(&display_row) && !occupied_rows.contains(&display_row) {
+                        bookmarks.push((display_row, name.cloned()));
+                    }
+                }
+            }
+
+            bookmarks
+                .into_iter()
+                .map(|(display_row, name)| {
+                    let button = editor.render_bookmark_indicator(display_row, name, cx);
+                    prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        cx,
+                    )
                 })
                 .collect_vec()
         })
@@ -3812,6 +4270,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark_indicator in layout.bookmark_indicators.iter_mut() {
+                bookmark_indicator.paint(cx);
+            }
+
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(cx);
             }
@@ -4323,6 +4785,154 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        let Some(minimap) = layout.minimap.take() else {
+            return;
+        };
+        let hitbox = minimap.hitbox.clone();
+        let geometry = minimap.geometry;
+        let slider_bounds = minimap.slider_bounds();
+        let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+        let is_hovered = hitbox.is_hovered(cx);
+
+        if minimap.show != ShowMinimap::OnHover || is_hovered || is_dragging {
+            cx.paint_layer(hitbox.bounds, |cx| {
+                cx.with_content_mask(
+                    Some(ContentMask {
+                        bounds: hitbox.bounds,
+                    }),
+                    |cx| {
+                        for highlight in &minimap.highlights {
+                            cx.paint_quad(highlight.clone());
+                        }
+
+                        for (ix, line) in minimap.lines.iter().enumerate() {
+                            let row = DisplayRow(minimap.start_row.0 + ix as u32);
+                            let line_origin = point(hitbox.left(), minimap.y_for_row(row));
+                            match line {
+                                MinimapLine::Characters(line) => {
+                                    line.paint(line_origin, MinimapLayout::LINE_HEIGHT, cx)
+                                        .log_err();
+                                }
+                                MinimapLine::Blocks(blocks) => {
+                                    for (columns, color) in blocks {
+                                        cx.paint_quad(fill(
+                                            Bounds::new(
+                                                line_origin
+                                                    + point(
+                                                        minimap.column_width * columns.start as f32,
+                                                        MinimapLayout::LINE_HEIGHT * 0.15,
+                                                    ),
+                                                size(
+                                                    minimap.column_width * columns.len() as f32,
+                                                    MinimapLayout::LINE_HEIGHT * 0.7,
+                                                ),
+                                            ),
+                                            color.opacity(0.6),
+                                        ));
+                                    }
+                                }
+                            }
+                        }
+
+                        for marker in &minimap.markers {
+                            cx.paint_quad(marker.clone());
+                        }
+
+                        let slider_color =
+                            if is_dragging || slider_bounds.contains(&cx.mouse_position()) {
+                                cx.theme().colors().scrollbar_thumb_hover_background
+                            } else {
+                                cx.theme().colors().scrollbar_thumb_background
+                            };
+                        cx.paint_quad(fill(slider_bounds, slider_color));
+                    },
+                );
+            });
+        }
+
+        cx.set_cursor_style(CursorStyle::Arrow, &hitbox);
+
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = hitbox.clone();
+            let show = minimap.show;
+            let mut was_hovered = is_hovered;
+            let mut mouse_position = cx.mouse_position();
+            move |event: &MouseMoveEvent, phase, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if event.pressed_button == Some(MouseButton::Left)
+                        && editor.scroll_manager.is_dragging_minimap()
+                    {
+                        let mut position = editor.scroll_position(cx);
+                        position.y += (event.position.y - mouse_position.y).0
+                            * geometry.rows_per_slider_pixel;
+                        position.y = position.y.max(0.);
+                        editor.set_scroll_position(position, cx);
+                        cx.stop_propagation();
+                    } else if editor.scroll_manager.is_dragging_minimap() {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                    }
+
+                    let is_hovered = hitbox.is_hovered(cx);
+                    if show == ShowMinimap::OnHover && is_hovered != was_hovered {
+                        cx.notify();
+                    }
+                    was_hovered = is_hovered;
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if is_dragging {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseDownEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(cx)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the slider centers the viewport on the clicked row.
+                        let y = event.position.y;
+                        if y < slider_bounds.top() || slider_bounds.bottom() < y {
+                            let row =
+                                geometry.top_row + (y - hitbox.top()) / MinimapLayout::LINE_HEIGHT;
+                            let mut position = editor.scroll_position(cx);
+                            position.y = (row - geometry.visible_rows / 2.).max(0.);
+                            editor.set_scroll_position(position, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -4565,6 +5175,14 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_headers(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        if let Some(mut sticky_headers) = layout.sticky_headers.take() {
+            cx.paint_layer(layout.hitbox.bounds, |cx| {
+                sticky_headers.paint(cx);
+            })
+        }
+    }
+
     fn paint_blocks(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
         for mut block in layout.blocks.drain(..) {
             block.element.paint(cx);
@@ -5719,7 +6337,15 @@ impl Element for EditorElement {
                         self.max_line_number_width(&snapshot, cx),
                         cx,
                     );
-                    let text_width = bounds.size.width - gutter_dimensions.width;
+                    let minimap_width = self.minimap_width(&snapshot, cx);
+                    let text_width = bounds.size.width
+                        - gutter_dimensions.width
+                        - if minimap_width > Pixels::ZERO {
+                            // Keep the vertical scrollbar from covering the minimap.
+                            minimap_width + style.scrollbar_width
+                        } else {
+                            Pixels::ZERO
+                        };
 
                     let editor_width = text_width - gutter_dimensions.margin - em_width;
 
@@ -6116,6 +6742,19 @@ impl Element for EditorElement {
                         );
                     });
 
+                    let sticky_headers = cx.with_element_namespace("sticky_headers", |cx| {
+                        self.layout_sticky_headers(
+                            &snapshot,
+                            start_row,
+                            &hitbox,
+                            &text_hitbox,
+                            &gutter_dimensions,
+                            line_height,
+                            scroll_pixel_position,
+                            cx,
+                        )
+                    });
+
                     let cursors = self.collect_cursors(&snapshot, cx);
                     let visible_row_range = start_row..end_row;
                     let non_visible_cursors = cursors
@@ -6146,6 +6785,19 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap = self.layout_minimap(
+                        &snapshot,
+                        bounds,
+                        minimap_width,
+                        scroll_position,
+                        max_scroll_top,
+                        height_in_lines,
+                        line_height,
+                        font_size,
+                        em_advance,
+                        cx,
+                    );
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
                     let expanded_add_hunks_by_rows = self.editor.update(cx, |editor, _| {
@@ -6245,20 +6897,41 @@ impl Element for EditorElement {
                         }
                     }
 
-                    let test_indicators = if gutter_settings.runnables {
-                        self.layout_run_indicators(
-                            line_height,
-                            start_row..end_row,
-                            scroll_pixel_position,
-                            &gutter_dimensions,
-                            &gutter_hitbox,
-                            &rows_with_hunk_bounds,
-                            &snapshot,
-                            cx,
-                        )
-                    } else {
-                        Vec::new()
-                    };
+                    let (test_indicator_rows, test_indicators): (Vec<_>, Vec<_>) =
+                        if gutter_settings.runnables {
+                            self.layout_run_indicators(
+                                line_height,
+                                start_row..end_row,
+                                scroll_pixel_position,
+                                &gutter_dimensions,
+                                &gutter_hitbox,
+                                &rows_with_hunk_bounds,
+                                &snapshot,
+                                cx,
+                            )
+                            .into_iter()
+                            .unzip()
+                        } else {
+                            Default::default()
+                        };
+
+                    // Bookmarks yield their gutter slot to the run and code actions
+                    // indicators, which are interactive.
+                    let mut occupied_rows = HashSet::from_iter(test_indicator_rows);
+                    if code_actions_indicator.is_some() {
+                        occupied_rows.extend(newest_selection_head.map(|head| head.row()));
+                    }
+                    let bookmark_indicators = self.layout_bookmark_indicators(
+                        line_height,
+                        start_row..end_row,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        &occupied_rows,
+                        &snapshot,
+                        cx,
+                    );
 
                     self.layout_signature_help(
                         &hitbox,
@@ -6374,6 +7047,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -6384,12 +7058,14 @@ impl Element for EditorElement {
                         blamed_display_rows,
                         inline_blame,
                         blocks,
+                        sticky_headers,
                         cursors,
                         visible_cursors,
                         selections,
                         inline_completion_popover,
                         mouse_context_menu,
                         test_indicators,
+                        bookmark_indicators,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
@@ -6476,6 +7152,8 @@ impl Element for EditorElement {
                         });
                     }
 
+                    self.paint_sticky_headers(layout, cx);
+                    self.paint_minimap(layout, cx);
                     self.paint_scrollbars(layout, cx);
                     self.paint_inline_completion_popover(layout, cx);
                     self.paint_mouse_context_menu(layout, cx);
@@ -6557,6 +7235,7 @@ pub struct EditorLayout {
     gutter_dimensions: GutterDimensions,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: AxisPair<Option<ScrollbarLayout>>,
+    minimap: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -6569,6 +7248,7 @@ pub struct EditorLayout {
     blamed_display_rows: Option<Vec<AnyElement>>,
     inline_blame: Option<AnyElement>,
     blocks: Vec<BlockLayout>,
+    sticky_headers: Option<AnyElement>,
     highlighted_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     highlighted_gutter_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     redacted_ranges: Vec<Range<DisplayPoint>>,
@@ -6577,6 +7257,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    bookmark_indicators: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
     inline_completion_popover: Option<AnyElement>,
@@ -6591,6 +7272,93 @@ impl EditorLayout {
     }
 }
 
+/// The vertical layout of the minimap, relative to the editor's scroll position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MinimapGeometry {
+    /// The display row at the top of the minimap.
+    top_row: f32,
+    /// The editor's scroll position.
+    scroll_top: f32,
+    /// The number of rows visible in the editor.
+    visible_rows: f32,
+    /// How many rows the editor scrolls by when the slider is dragged by one pixel.
+    rows_per_slider_pixel: f32,
+}
+
+impl MinimapGeometry {
+    fn new(
+        scroll_top: f32,
+        max_scroll_top: f32,
+        row_count: f32,
+        minimap_rows: f32,
+        visible_rows: f32,
+    ) -> Self {
+        // When the buffer doesn't fit in the minimap, the minimap scrolls along
+        // with the editor, so that both reach their end at the same time.
+        let minimap_scroll_per_row = if row_count > minimap_rows && max_scroll_top > 0. {
+            (row_count - minimap_rows) / max_scroll_top
+        } else {
+            0.
+        };
+        let top_row = scroll_top.clamp(0., max_scroll_top.max(0.)) * minimap_scroll_per_row;
+
+        let slider_pixels_per_row = MinimapLayout::LINE_HEIGHT.0 * (1. - minimap_scroll_per_row);
+        let rows_per_slider_pixel = if slider_pixels_per_row > 0.01 {
+            1. / slider_pixels_per_row
+        } else {
+            1. / MinimapLayout::LINE_HEIGHT.0
+        };
+
+        Self {
+            top_row,
+            scroll_top,
+            visible_rows,
+            rows_per_slider_pixel,
+        }
+    }
+}
+
+struct MinimapLayout {
+    hitbox: Hitbox,
+    show: ShowMinimap,
+    geometry: MinimapGeometry,
+    start_row: DisplayRow,
+    column_width: Pixels,
+    lines: Vec<MinimapLine>,
+    /// Search results and selected symbol occurrences, painted behind the text.
+    highlights: Vec<PaintQuad>,
+    /// Git hunks and diagnostics, painted along the edges.
+    markers: Vec<PaintQuad>,
+}
+
+enum MinimapLine {
+    Characters(ShapedLine),
+    /// Ranges of columns containing words, and their colors.
+    Blocks(Vec<(Range<usize>, Hsla)>),
+}
+
+impl MinimapLayout {
+    const LINE_HEIGHT: Pixels = px(3.);
+
+    fn y_for_row(&self, row: DisplayRow) -> Pixels {
+        self.hitbox.top() + (row.as_f32() - self.geometry.top_row) * Self::LINE_HEIGHT
+    }
+
+    fn slider_bounds(&self) -> Bounds<Pixels> {
+        Bounds::new(
+            point(
+                self.hitbox.left(),
+                self.hitbox.top()
+                    + (self.geometry.scroll_top - self.geometry.top_row) * Self::LINE_HEIGHT,
+            ),
+            size(
+                self.hitbox.size.width,
+                self.geometry.visible_rows * Self::LINE_HEIGHT,
+            ),
+        )
+    }
+}
+
 struct ColoredRange<T> {
     start: T,
     end: T,
@@ -7186,9 +7954,10 @@ mod tests {
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
-    use gpui::{TestAppContext, VisualTestContext};
+    use gpui::{hsla, SnapshotTolerance, TestAppContext, VisualTestContext};
     use language::language_settings;
     use log::info;
+    use settings::SettingsStore;
     use std::num::NonZeroU32;
     use util::test::sample_text;
 
@@ -7637,6 +8406,134 @@ mod tests {
         }
     }
 
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.minimap = Some(crate::editor_settings::MinimapContent {
+                        show: Some(ShowMinimap::Always),
+                        width: Some(80.),
+                        render: Some(MinimapRender::Blocks),
+                    });
+                });
+            });
+        });
+
+        let window = cx.add_window(|cx| {
+            let buffer = MultiBuffer::build_simple(&sample_text(10, 6, 'a'), cx);
+            Editor::new(EditorMode::Full, buffer, None, true, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|cx| editor.read(cx).style().unwrap().clone());
+        let scrollbar_width = style.scrollbar_width;
+
+        let (_, state) = cx.draw(point(px(0.), px(0.)), size(px(500.), px(300.)), |_| {
+            EditorElement::new(&editor, style)
+        });
+        let minimap = state.minimap.as_ref().unwrap();
+        assert_eq!(minimap.hitbox.size.width, px(80.));
+        assert_eq!(
+            minimap.hitbox.right(),
+            state.hitbox.right() - scrollbar_width
+        );
+        assert!(state.text_hitbox.right() <= minimap.hitbox.left());
+
+        // The whole buffer fits in the minimap, so every row is shown, with
+        // each word as a single block.
+        assert_eq!(minimap.geometry.top_row, 0.);
+        assert_eq!(minimap.lines.len(), 10);
+        for line in &minimap.lines {
+            match line {
+                MinimapLine::Blocks(blocks) => assert_eq!(
+                    blocks
+                        .iter()
+                        .map(|(columns, _)| columns.clone())
+                        .collect::<Vec<_>>(),
+                    vec![0..6]
+                ),
+                MinimapLine::Characters(_) => panic!("expected blocks"),
+            }
+        }
+        assert_eq!(minimap.slider_bounds().top(), minimap.hitbox.top());
+    }
+
+    #[test]
+    fn test_minimap_geometry() {
+        let line_height = MinimapLayout::LINE_HEIGHT.0;
+
+        // A buffer that fits in the minimap doesn't scroll it, and the slider
+        // moves with the rows.
+        let geometry = MinimapGeometry::new(20., 49., 50., 100., 10.);
+        assert_eq!(geometry.top_row, 0.);
+        assert_eq!(geometry.rows_per_slider_pixel, 1. / line_height);
+
+        // A longer buffer scrolls the minimap, so that it reaches its end
+        // along with the editor.
+        let geometry = MinimapGeometry::new(0., 999., 1000., 200., 10.);
+        assert_eq!(geometry.top_row, 0.);
+        let geometry = MinimapGeometry::new(999., 999., 1000., 200., 10.);
+        assert_eq!(geometry.top_row, 800.);
+        let geometry = MinimapGeometry::new(499.5, 999., 1000., 200., 10.);
+        assert_eq!(geometry.top_row, 400.);
+        assert!(
+            (geometry.rows_per_slider_pixel - 999. / (199. * line_height)).abs() < 0.001,
+            "{geometry:?}"
+        );
+    }
+
+    #[gpui::test]
+    fn test_editor_element_snapshot(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.cursor_blink = Some(false);
+                    settings.scrollbar = Some(crate::editor_settings::ScrollbarContent {
+                        show: Some(ShowScrollbar::Never),
+                        git_diff: None,
+                        search_results: None,
+                        selected_symbol: None,
+                        diagnostics: None,
+                        cursors: None,
+                        axes: None,
+                    });
+                });
+            });
+        });
+
+        struct HighlightedRow;
+
+        // Only empty lines are shown, so that the rendering doesn't depend on the fonts
+        // available: the active line with the cursor at its start, and a highlighted row.
+        cx.assert_view_snapshot(
+            size(px(64.), px(96.)),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots/editor_element.png"),
+            SnapshotTolerance::default(),
+            |cx| {
+                let buffer = MultiBuffer::build_simple("\n\n\n", cx);
+                let highlighted_row = buffer.read(cx).snapshot(cx).anchor_before(Point::new(3, 0));
+                let mut editor = Editor::new(EditorMode::Full, buffer, None, true, cx);
+                editor.set_show_gutter(false, cx);
+                editor.set_show_wrap_guides(false, cx);
+                editor.set_show_indent_guides(false, cx);
+                editor.change_selections(None, cx, |s| {
+                    s.select_ranges([Point::new(1, 0)..Point::new(1, 0)])
+                });
+                editor.highlight_rows::<HighlightedRow>(
+                    highlighted_row..highlighted_row,
+                    hsla(0., 0.5, 0.5, 1.),
+                    false,
+                    cx,
+                );
+                cx.focus(&editor.focus_handle);
+                editor
+            },
+        );
+    }
+
     fn collect_invisibles_from_new_editor(
         cx: &mut TestAppContext,
         editor_mode: EditorMode,
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use gpui::Model;
+use language::{language_settings::language_settings, Buffer};
+use project::FoldingRange;
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Requests folding ranges for the buffers of the editor that have them enabled,
+/// and replaces their language server folding ranges in the display map.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+
+    let mut enabled_buffers = Vec::new();
+    let mut disabled_buffer_ids = Vec::new();
+    for buffer in editor.buffer.read(cx).all_buffers() {
+        let buffer_ref = buffer.read(cx);
+        let settings = language_settings(
+            buffer_ref.language().map(|language| language.name()),
+            buffer_ref.file(),
+            cx,
+        );
+        if settings.lsp_folding_ranges {
+            enabled_buffers.push(buffer);
+        } else {
+            disabled_buffer_ids.push(buffer_ref.remote_id());
+        }
+    }
+
+    let cleared = editor.display_map.update(cx, |display_map, _| {
+        disabled_buffer_ids
+            .into_iter()
+            .fold(false, |cleared, buffer_id| {
+                display_map.clear_lsp_folding_ranges(buffer_id) || cleared
+            })
+    });
+    if cleared {
+        cx.notify();
+    }
+
+    let semantics_provider = editor.semantics_provider.clone()?;
+    if enabled_buffers.is_empty() {
+        editor.folding_ranges_task = None;
+        return None;
+    }
+
+    editor.folding_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let tasks = editor
+            .update(&mut cx, |editor, cx| {
+                let mut tasks = Vec::new();
+                let mut cleared = false;
+                for buffer in enabled_buffers {
+                    match semantics_provider.folding_ranges(&buffer, cx) {
+                        Some(task) => tasks.push((buffer, task)),
+                        // Without a server providing folding ranges, fold by indentation.
+                        None => {
+                            let buffer_id = buffer.read(cx).remote_id();
+                            cleared |= editor.display_map.update(cx, |display_map, _| {
+                                display_map.clear_lsp_folding_ranges(buffer_id)
+                            });
+                        }
+                    }
+                }
+                if cleared {
+                    cx.notify();
+                }
+                tasks
+            })
+            .ok()?;
+        for (buffer, task) in tasks {
+            let Some(ranges) = task.await.log_err() else {
+                continue;
+            };
+            editor
+                .update(&mut cx, |editor, cx| {
+                    apply_folding_ranges(editor, &buffer, ranges, cx)
+                })
+                .ok()?;
+        }
+        Some(())
+    }));
+    None
+}
+
+fn apply_folding_ranges(
+    editor: &mut Editor,
+    buffer: &Model<Buffer>,
+    ranges: Vec<FoldingRange>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let buffer_id = buffer.read(cx).remote_id();
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let multibuffer = editor.buffer.read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+    let mut folding_ranges = Vec::new();
+    for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+        let context = excerpt_range.context;
+        for FoldingRange { range, kind } in &ranges {
+            // Ranges starting outside of the excerpt can't be folded from it,
+            // while the ones ending past it are folded up to its end.
+            if range.start.cmp(&context.start, &buffer_snapshot).is_lt()
+                || range.start.cmp(&context.end, &buffer_snapshot).is_ge()
+            {
+                continue;
+            }
+            let end = if range.end.cmp(&context.end, &buffer_snapshot).is_gt() {
+                context.end
+            } else {
+                range.end
+            };
+            let (Some(start), Some(end)) = (
+                multibuffer_snapshot.anchor_in_excerpt(excerpt_id, range.start),
+                multibuffer_snapshot.anchor_in_excerpt(excerpt_id, end),
+            ) else {
+                continue;
+            };
+            folding_ranges.push((start..end, *kind));
+        }
+    }
+    folding_ranges.sort_by(|(a, _), (b, _)| {
+        a.start
+            .cmp(&b.start, &multibuffer_snapshot)
+            .then_with(|| b.end.cmp(&a.end, &multibuffer_snapshot))
+    });
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_lsp_folding_ranges(buffer_id, folding_ranges)
+    });
+    cx.notify();
+}
@@ -4,6 +4,7 @@ use crate::{
     scroll::ScrollAmount,
     Anchor, Editor, EditorSettings, EditorSnapshot, FindAllReferences, GoToDefinition,
     GoToTypeDefinition, GotoDefinitionKind, InlayId, Navigated, PointForPosition, SelectPhase,
+    SemanticsProvider,
 };
 use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
 use language::{Bias, ToOffset};
@@ -14,7 +15,7 @@ use project::{
     ResolveState, ResolvedPath,
 };
 use settings::Settings;
-use std::ops::Range;
+use std::{ops::Range, rc::Rc};
 use theme::ActiveTheme as _;
 use util::{maybe, ResultExt, TryFutureExt as _};
 
@@ -535,6 +536,22 @@ pub fn show_link_definition(
                         });
 
                         Some((range, vec![HoverLink::File(filename)]))
+                    } else if let Some((link_range, link)) = find_document_link(
+                        &buffer,
+                        project.clone(),
+                        provider.clone(),
+                        buffer_position,
+                        &mut cx,
+                    )
+                    .await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+
+                        Some((range, vec![link]))
                     } else if let Some(provider) = provider {
                         let task = cx.update(|cx| {
                             provider.definitions(&buffer, buffer_position, preferred_kind, cx)
@@ -800,6 +817,34 @@ pub(crate) async fn find_file(
     None
 }
 
+/// Finds the link reported by a language server at the position, for targets that only the
+/// server knows how to resolve. File targets are opened as paths, and any other as URLs.
+pub(crate) async fn find_document_link(
+    buffer: &Model<language::Buffer>,
+    project: Option<Model<Project>>,
+    provider: Option<Rc<dyn SemanticsProvider>>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let task = cx
+        .update(|cx| provider?.document_link(buffer, position, cx))
+        .ok()??;
+    let link = task.await.log_err()??;
+    let target = link.target()?.clone();
+    if target.scheme() == "file" {
+        let path = target.to_file_path().ok()?;
+        let resolved_path = project?
+            .update(cx, |project, cx| {
+                project.resolve_abs_file_path(&path.to_string_lossy(), cx)
+            })
+            .ok()?
+            .await?;
+        Some((link.range, HoverLink::File(resolved_path)))
+    } else {
+        Some((link.range, HoverLink::Url(target.to_string())))
+    }
+}
+
 fn surrounding_filename(
     snapshot: language::BufferSnapshot,
     position: text::Anchor,
@@ -904,7 +949,13 @@ mod tests {
     use gpui::Modifiers;
     use indoc::indoc;
     use language::language_settings::InlayHintSettings;
-    use lsp::request::{GotoDefinition, GotoTypeDefinition};
+    use lsp::request::{
+        DocumentLinkRequest, DocumentLinkResolve, GotoDefinition, GotoTypeDefinition,
+    };
+    use std::sync::{
+        atomic::{self, AtomicUsize},
+        Arc,
+    };
     use util::assert_set_eq;
     use workspace::item::Item;
 
@@ -1474,6 +1525,86 @@ mod tests {
         assert_eq!(cx.opened_url(), Some("https://zed.dev/releases".into()));
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            use ˇserde::Serialize;
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            use «serde»::Serialize;
+        "});
+
+        // The server only knows where the link goes once it is resolved.
+        let link_requests = Arc::new(AtomicUsize::new(0));
+        let mut requests = cx.handle_request::<DocumentLinkRequest, _, _>({
+            let link_requests = link_requests.clone();
+            move |_, _, _| {
+                link_requests.fetch_add(1, atomic::Ordering::SeqCst);
+                async move {
+                    Ok(Some(vec![lsp::DocumentLink {
+                        range: link_range,
+                        target: None,
+                        tooltip: None,
+                        data: None,
+                    }]))
+                }
+            }
+        });
+        let mut resolve_requests =
+            cx.handle_request::<DocumentLinkResolve, _, _>(|_, link, _| async move {
+                Ok(lsp::DocumentLink {
+                    target: Some(lsp::Url::parse("https://docs.rs/serde").unwrap()),
+                    ..link
+                })
+            });
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            use seˇrde::Serialize;
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        requests.next().await;
+        resolve_requests.next().await;
+        cx.background_executor.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            use «serde»::Serialize;
+        "});
+
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        assert_eq!(cx.opened_url(), Some("https://docs.rs/serde".into()));
+
+        // Hovering elsewhere reuses the links fetched for this version of the buffer.
+        let other_coord = cx.pixel_position(indoc! {"
+            use serde::Seˇrialize;
+        "});
+        cx.simulate_mouse_move(other_coord, None, Modifiers::secondary_key());
+        cx.background_executor.run_until_parked();
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        cx.background_executor.run_until_parked();
+        assert_eq!(link_requests.load(atomic::Ordering::SeqCst), 1);
+
+        // Edits make them be fetched again.
+        cx.update_buffer(|buffer, cx| {
+            let end = buffer.len();
+            buffer.edit([(end..end, "\n")], None, cx)
+        });
+        cx.simulate_mouse_move(other_coord, None, Modifiers::secondary_key());
+        cx.background_executor.run_until_parked();
+        assert_eq!(link_requests.load(atomic::Ordering::SeqCst), 2);
+    }
+
     #[gpui::test]
     async fn test_surrounding_filename(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
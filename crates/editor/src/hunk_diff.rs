@@ -201,6 +201,16 @@ impl Editor {
         self.diff_map.expand_all = true;
     }
 
+    /// Displays the diff hunks of the given change set, for editors that are not
+    /// diffing their buffers against the index.
+    pub fn add_change_set(
+        &mut self,
+        change_set: Model<BufferChangeSet>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.diff_map.add_change_set(change_set, cx);
+    }
+
     pub(super) fn toggle_hovered_hunk(
         &mut self,
         hovered_hunk: &HoveredHunk,
@@ -524,6 +524,33 @@ fn deserialize_selection(
     })
 }
 
+/// Returns the replacement for a search match. Structural queries need the syntax tree of
+/// the buffer containing the match, instead of just its text.
+fn replacement_for_match(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<Arc<str>> {
+    if query.is_structural() {
+        let range = range.to_offset(snapshot);
+        let (buffer, start) = snapshot.point_to_buffer_offset(range.start)?;
+        return query
+            .replacement_for_range(buffer, start..start + range.len())
+            .map(Arc::from);
+    }
+
+    let text = snapshot.text_for_range(range.clone()).collect::<Vec<_>>();
+    let text: Cow<_> = if text.len() == 1 {
+        text.first().cloned().unwrap().into()
+    } else {
+        let joined_chunks = text.join("");
+        joined_chunks.into()
+    };
+    query
+        .replacement_for(&text)
+        .map(|replacement| Arc::from(&*replacement))
+}
+
 fn deserialize_anchor(buffer: &MultiBufferSnapshot, anchor: proto::EditorAnchor) -> Option<Anchor> {
     let excerpt_id = ExcerptId::from_proto(anchor.excerpt_id);
     Some(Anchor {
@@ -1316,19 +1343,10 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(&snapshot, identifier, query) {
             self.transact(cx, |this, cx| {
-                this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                this.edit([(identifier.clone(), replacement)], cx);
             });
         }
     }
@@ -1338,20 +1356,11 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
-                edits.push((m.clone(), Arc::from(&*replacement)));
+            if let Some(replacement) = replacement_for_match(&snapshot, m, query) {
+                edits.push((m.clone(), replacement));
             }
         }
 
@@ -1561,7 +1570,7 @@ pub fn entry_git_aware_label_color(
         match git_status {
             Some(GitFileStatus::Added) => Color::Created,
             Some(GitFileStatus::Modified) => Color::Modified,
-            Some(GitFileStatus::Conflict) => Color::Conflict,
+            Some(GitFileStatus::Conflict(_)) => Color::Conflict,
             None => entry_label_color(selected),
         }
     }
@@ -1816,4 +1825,52 @@ mod tests {
             });
         }
     }
+
+    #[gpui::test]
+    async fn test_structural_replace(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let text = "fn main() {\n    foo(a, b);\n    foo(\n        bar(1),\n        c,\n    );\n    foo(a);\n}\n";
+        let buffer =
+            cx.new_model(|cx| language::Buffer::local(text, cx).with_language(rust_language(), cx));
+        let (editor, cx) = cx.add_window_view(|cx| Editor::for_buffer(buffer, None, cx));
+        cx.run_until_parked();
+
+        let query = Arc::new(
+            SearchQuery::structural(
+                "foo($A, $B)",
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap()
+            .with_replacement("bar($B, $A)".to_string()),
+        );
+        let matches = editor
+            .update(cx, |editor, cx| editor.find_matches(query.clone(), cx))
+            .await;
+        assert_eq!(matches.len(), 2);
+
+        // The captures of the match are substituted, no matter how the match is formatted.
+        editor.update(cx, |editor, cx| {
+            editor.replace(&matches[1], &query, cx);
+            assert_eq!(
+                editor.text(cx),
+                "fn main() {\n    foo(a, b);\n    bar(c, bar(1));\n    foo(a);\n}\n"
+            );
+        });
+        cx.run_until_parked();
+
+        let matches = editor
+            .update(cx, |editor, cx| editor.find_matches(query.clone(), cx))
+            .await;
+        editor.update(cx, |editor, cx| {
+            editor.replace_all(&mut matches.iter(), &query, cx);
+            assert_eq!(
+                editor.text(cx),
+                "fn main() {\n    bar(b, a);\n    bar(c, bar(1));\n    foo(a);\n}\n"
+            );
+        });
+    }
 }
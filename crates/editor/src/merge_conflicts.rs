@@ -0,0 +1,482 @@
+use std::{mem, ops::Range, sync::Arc, time::Duration};
+
+use collections::HashMap;
+use git::repository::GitFileStatus;
+use gpui::WeakView;
+use multi_buffer::{
+    Anchor, ExcerptId, MultiBufferRow, MultiBufferSnapshot, ToOffset as _, ToPoint as _,
+};
+use text::{Point, ToOffset as _};
+use ui::{h_flex, prelude::*, ButtonStyle, ViewContext};
+
+use crate::{
+    display_map::{BlockContext, BlockPlacement, BlockProperties, BlockStyle, RenderBlock},
+    AcceptBoth, AcceptOurs, AcceptTheirs, Editor, EditorMode,
+};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+enum ConflictOursHighlight {}
+enum ConflictBaseHighlight {}
+enum ConflictTheirsHighlight {}
+
+/// A region of a buffer delimited by git's conflict markers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct ConflictRegion<T> {
+    /// From the start of the `<<<<<<<` line through the end of the `>>>>>>>` line.
+    pub range: Range<T>,
+    pub ours: Range<T>,
+    /// Only present for conflicts written in the `diff3` style.
+    pub base: Option<Range<T>>,
+    pub theirs: Range<T>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+/// Finds the conflict regions of the text. Unterminated regions are ignored.
+pub(crate) fn parse_conflicts(text: &str) -> Vec<ConflictRegion<usize>> {
+    fn is_marker(line: &str, marker: &str) -> bool {
+        line.strip_prefix(marker).map_or(false, |rest| {
+            rest.is_empty() || rest.starts_with([' ', '\t'])
+        })
+    }
+
+    struct PendingConflict {
+        start: usize,
+        ours_start: usize,
+        base: Option<(usize, usize)>,
+        separator: Option<(usize, usize)>,
+    }
+
+    let mut conflicts = Vec::new();
+    let mut pending: Option<PendingConflict> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        if is_marker(line, OURS_MARKER) {
+            pending = Some(PendingConflict {
+                start: line_start,
+                ours_start: offset,
+                base: None,
+                separator: None,
+            });
+            continue;
+        }
+        let Some(conflict) = pending.as_mut() else {
+            continue;
+        };
+        if let Some((separator_start, theirs_start)) = conflict.separator {
+            if is_marker(line, THEIRS_MARKER) {
+                conflicts.push(ConflictRegion {
+                    range: conflict.start..offset,
+                    ours: conflict.ours_start
+                        ..conflict
+                            .base
+                            .map_or(separator_start, |(base_marker_start, _)| base_marker_start),
+                    base: conflict
+                        .base
+                        .map(|(_, base_start)| base_start..separator_start),
+                    theirs: theirs_start..line_start,
+                });
+                pending = None;
+            }
+        } else if line == SEPARATOR_MARKER {
+            conflict.separator = Some((line_start, offset));
+        } else if conflict.base.is_none() && is_marker(line, BASE_MARKER) {
+            conflict.base = Some((line_start, offset));
+        }
+    }
+    conflicts
+}
+
+/// Finds the conflict regions of the buffers that git reports as unmerged,
+/// then highlights them and shows resolution buttons above each of them.
+pub(super) fn refresh_merge_conflicts(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+
+    let conflicted_buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            buffer
+                .read(cx)
+                .project_path(cx)
+                .and_then(|path| project.read(cx).entry_for_path(&path, cx))
+                .map_or(false, |entry| {
+                    matches!(entry.git_status, Some(GitFileStatus::Conflict(_)))
+                })
+        })
+        .map(|buffer| (buffer.clone(), buffer.read(cx).snapshot()))
+        .collect::<Vec<_>>();
+    if conflicted_buffers.is_empty() {
+        editor.merge_conflicts_task = None;
+        if !editor.merge_conflicts.is_empty() {
+            show_merge_conflicts(editor, Vec::new(), cx);
+        }
+        return None;
+    }
+
+    editor.merge_conflicts_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+        let conflicts = cx
+            .background_executor()
+            .spawn(async move {
+                conflicted_buffers
+                    .into_iter()
+                    .map(|(buffer, snapshot)| {
+                        let anchor_range = |range: Range<usize>| {
+                            snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end)
+                        };
+                        let regions = parse_conflicts(&snapshot.text())
+                            .into_iter()
+                            .map(|region| ConflictRegion {
+                                range: anchor_range(region.range),
+                                ours: anchor_range(region.ours),
+                                base: region.base.map(anchor_range),
+                                theirs: anchor_range(region.theirs),
+                            })
+                            .collect::<Vec<_>>();
+                        (buffer, snapshot, regions)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multibuffer = editor.buffer.read(cx);
+                let multibuffer_snapshot = multibuffer.snapshot(cx);
+                let mut merge_conflicts = Vec::new();
+                for (buffer, buffer_snapshot, regions) in conflicts {
+                    for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(&buffer, cx)
+                    {
+                        let context = &excerpt_range.context;
+                        merge_conflicts.extend(regions.iter().filter_map(|region| {
+                            if region.range.start.to_offset(&buffer_snapshot)
+                                < context.start.to_offset(&buffer_snapshot)
+                                || region.range.end.to_offset(&buffer_snapshot)
+                                    > context.end.to_offset(&buffer_snapshot)
+                            {
+                                return None;
+                            }
+                            let anchor_range = |range: &Range<text::Anchor>| {
+                                excerpt_anchor_range(&multibuffer_snapshot, excerpt_id, range)
+                            };
+                            Some(ConflictRegion {
+                                range: anchor_range(&region.range)?,
+                                ours: anchor_range(&region.ours)?,
+                                base: match &region.base {
+                                    Some(base) => Some(anchor_range(base)?),
+                                    None => None,
+                                },
+                                theirs: anchor_range(&region.theirs)?,
+                            })
+                        }));
+                    }
+                }
+                show_merge_conflicts(editor, merge_conflicts, cx)
+            })
+            .ok()
+    }));
+    None
+}
+
+fn excerpt_anchor_range(
+    snapshot: &MultiBufferSnapshot,
+    excerpt_id: ExcerptId,
+    range: &Range<text::Anchor>,
+) -> Option<Range<Anchor>> {
+    Some(
+        snapshot.anchor_in_excerpt(excerpt_id, range.start)?
+            ..snapshot.anchor_in_excerpt(excerpt_id, range.end)?,
+    )
+}
+
+/// Replaces the conflict highlights of the editor, and updates the blocks of the conflict
+/// regions that changed. The blocks of unchanged regions are kept, so that the lines below
+/// them don't move.
+fn show_merge_conflicts(
+    editor: &mut Editor,
+    conflicts: Vec<ConflictRegion<Anchor>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    editor.clear_row_highlights::<ConflictOursHighlight>();
+    editor.clear_row_highlights::<ConflictBaseHighlight>();
+    editor.clear_row_highlights::<ConflictTheirsHighlight>();
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut old_conflicts = mem::take(&mut editor.merge_conflicts)
+        .into_iter()
+        .zip(mem::take(&mut editor.merge_conflict_blocks))
+        .map(|(conflict, block_id)| (conflict_offsets(&conflict, &snapshot), (conflict, block_id)))
+        .collect::<HashMap<_, _>>();
+
+    let editor_handle = cx.view().downgrade();
+    let mut merge_conflicts = Vec::with_capacity(conflicts.len());
+    let mut block_ids = Vec::with_capacity(conflicts.len());
+    let mut blocks = Vec::new();
+    for conflict in conflicts {
+        if let Some((old_conflict, block_id)) =
+            old_conflicts.remove(&conflict_offsets(&conflict, &snapshot))
+        {
+            merge_conflicts.push(old_conflict);
+            block_ids.push(Some(block_id));
+        } else {
+            blocks.push(BlockProperties {
+                placement: BlockPlacement::Above(conflict.range.start),
+                height: 1,
+                style: BlockStyle::Flex,
+                render: render_conflict_actions(conflict.range.start, editor_handle.clone()),
+                priority: 0,
+            });
+            merge_conflicts.push(conflict);
+            block_ids.push(None);
+        }
+    }
+
+    if !old_conflicts.is_empty() {
+        editor.remove_blocks(
+            old_conflicts
+                .into_values()
+                .map(|(_, block_id)| block_id)
+                .collect(),
+            None,
+            cx,
+        );
+    }
+    let mut inserted_block_ids = if blocks.is_empty() {
+        Vec::new()
+    } else {
+        editor.insert_blocks(blocks, None, cx)
+    }
+    .into_iter();
+    editor.merge_conflict_blocks = block_ids
+        .into_iter()
+        .filter_map(|block_id| block_id.or_else(|| inserted_block_ids.next()))
+        .collect();
+
+    let status_colors = cx.theme().status().clone();
+    for conflict in &merge_conflicts {
+        let theirs_start = conflict.base.as_ref().unwrap_or(&conflict.ours).end;
+        editor.highlight_rows::<ConflictOursHighlight>(
+            rows_before(conflict.range.start, conflict.ours.end, &snapshot),
+            status_colors.created_background,
+            false,
+            cx,
+        );
+        if let Some(base) = &conflict.base {
+            editor.highlight_rows::<ConflictBaseHighlight>(
+                rows_before(conflict.ours.end, base.end, &snapshot),
+                status_colors.hidden_background,
+                false,
+                cx,
+            );
+        }
+        editor.highlight_rows::<ConflictTheirsHighlight>(
+            rows_before(theirs_start, conflict.range.end, &snapshot),
+            status_colors.info_background,
+            false,
+            cx,
+        );
+    }
+    editor.merge_conflicts = merge_conflicts;
+    cx.notify();
+}
+
+fn conflict_offsets(
+    conflict: &ConflictRegion<Anchor>,
+    snapshot: &MultiBufferSnapshot,
+) -> ConflictRegion<usize> {
+    let offsets =
+        |range: &Range<Anchor>| range.start.to_offset(snapshot)..range.end.to_offset(snapshot);
+    ConflictRegion {
+        range: offsets(&conflict.range),
+        ours: offsets(&conflict.ours),
+        base: conflict.base.as_ref().map(offsets),
+        theirs: offsets(&conflict.theirs),
+    }
+}
+
+/// The range from `start` to the end of the line preceding `end`, when `end` is at the start of a line.
+fn rows_before(start: Anchor, end: Anchor, snapshot: &MultiBufferSnapshot) -> Range<Anchor> {
+    let start_point = start.to_point(snapshot);
+    let end_point = end.to_point(snapshot);
+    if end_point.column == 0 && end_point.row > start_point.row {
+        let row = end_point.row - 1;
+        start..snapshot.anchor_before(Point::new(row, snapshot.line_len(MultiBufferRow(row))))
+    } else {
+        start..end
+    }
+}
+
+fn render_conflict_actions(position: Anchor, editor: WeakView<Editor>) -> RenderBlock {
+    Arc::new(move |cx: &mut BlockContext| {
+        let actions = [
+            ("Accept Ours", ConflictResolution::Ours),
+            ("Accept Theirs", ConflictResolution::Theirs),
+            ("Accept Both", ConflictResolution::Both),
+        ];
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(
+                actions
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(ix, (label, resolution))| {
+                        let separator = (ix > 0).then(|| {
+                            Label::new("|")
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .into_any_element()
+                        });
+                        let editor = editor.clone();
+                        let button = Button::new(("resolve-conflict", ix), label)
+                            .label_size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .style(ButtonStyle::Transparent)
+                            .on_click(move |_, cx| {
+                                editor
+                                    .update(cx, |editor, cx| {
+                                        resolve_conflict_at(editor, position, resolution, cx)
+                                    })
+                                    .ok();
+                            })
+                            .into_any_element();
+                        separator.into_iter().chain(Some(button))
+                    }),
+            )
+            .into_any_element()
+    })
+}
+
+/// Replaces the conflict region containing `position` with the chosen side(s).
+fn resolve_conflict_at(
+    editor: &mut Editor,
+    position: Anchor,
+    resolution: ConflictResolution,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let position = position.to_offset(&snapshot);
+    let conflict = editor.merge_conflicts.iter().find(|conflict| {
+        conflict.range.start.to_offset(&snapshot) <= position
+            && position <= conflict.range.end.to_offset(&snapshot)
+    })?;
+    let ours = snapshot
+        .text_for_range(conflict.ours.clone())
+        .collect::<String>();
+    let theirs = snapshot
+        .text_for_range(conflict.theirs.clone())
+        .collect::<String>();
+    let text = match resolution {
+        ConflictResolution::Ours => ours,
+        ConflictResolution::Theirs => theirs,
+        ConflictResolution::Both => ours + &theirs,
+    };
+    let range = conflict.range.clone();
+    editor.transact(cx, |editor, cx| {
+        editor.edit([(range, text)], cx);
+    });
+    Some(())
+}
+
+impl Editor {
+    pub fn accept_ours(&mut self, _: &AcceptOurs, cx: &mut ViewContext<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Ours, cx);
+    }
+
+    pub fn accept_theirs(&mut self, _: &AcceptTheirs, cx: &mut ViewContext<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Theirs, cx);
+    }
+
+    pub fn accept_both(&mut self, _: &AcceptBoth, cx: &mut ViewContext<Self>) {
+        self.resolve_conflict_at_cursor(ConflictResolution::Both, cx);
+    }
+
+    fn resolve_conflict_at_cursor(
+        &mut self,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let head = self.selections.newest_anchor().head();
+        resolve_conflict_at(self, head, resolution, cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = indoc! {"
+            one
+            <<<<<<< HEAD
+            two
+            =======
+            three
+            >>>>>>> branch
+            four
+            <<<<<<< ours
+            five
+            ||||||| base
+            six
+            =======
+            >>>>>>> theirs
+            <<<<<<< unterminated
+            seven
+        "};
+        let conflicts = parse_conflicts(text);
+        let texts = conflicts
+            .iter()
+            .map(|conflict| {
+                (
+                    &text[conflict.range.clone()],
+                    &text[conflict.ours.clone()],
+                    conflict.base.clone().map(|base| &text[base]),
+                    &text[conflict.theirs.clone()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            [
+                (
+                    "<<<<<<< HEAD\ntwo\n=======\nthree\n>>>>>>> branch\n",
+                    "two\n",
+                    None,
+                    "three\n"
+                ),
+                (
+                    "<<<<<<< ours\nfive\n||||||| base\nsix\n=======\n>>>>>>> theirs\n",
+                    "five\n",
+                    Some("six\n"),
+                    ""
+                ),
+            ]
+        );
+    }
+}
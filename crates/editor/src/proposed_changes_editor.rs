@@ -463,6 +463,48 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
         }
     }
 
+    fn semantic_tokens(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<project::BufferSemanticTokens>>> {
+        None
+    }
+
+    fn folding_ranges(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Vec<project::FoldingRange>>>> {
+        None
+    }
+
+    fn code_lens(
+        &self,
+        _: &Model<Buffer>,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Vec<project::CodeLens>>>> {
+        None
+    }
+
+    fn resolve_code_lens(
+        &self,
+        _: Model<Buffer>,
+        _: project::CodeLens,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<project::CodeLens>>> {
+        None
+    }
+
+    fn apply_code_lens(
+        &self,
+        _: Model<Buffer>,
+        _: project::CodeLens,
+        _: &mut AppContext,
+    ) -> Option<Task<gpui::Result<project::ProjectTransaction>>> {
+        None
+    }
+
     fn document_highlights(
         &self,
         buffer: &Model<Buffer>,
@@ -473,6 +515,16 @@ impl SemanticsProvider for BranchBufferSemanticsProvider {
         self.0.document_highlights(&buffer, position, cx)
     }
 
+    fn document_link(
+        &self,
+        buffer: &Model<Buffer>,
+        position: text::Anchor,
+        cx: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Option<project::DocumentLink>>>> {
+        let buffer = self.to_base(&buffer, &[position], cx)?;
+        self.0.document_link(&buffer, position, cx)
+    }
+
     fn definitions(
         &self,
         buffer: &Model<Buffer>,
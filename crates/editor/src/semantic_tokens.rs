@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+use collections::HashMap;
+use gpui::Model;
+use language::{language_settings::language_settings, Buffer, HighlightMap};
+use project::{BufferSemanticTokens, SemanticToken};
+use theme::ActiveTheme;
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Requests semantic tokens for the buffers of the editor that have them enabled,
+/// and replaces their semantic highlights in the display map.
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+
+    let mut enabled_buffers = Vec::new();
+    let mut disabled_buffer_ids = Vec::new();
+    for buffer in editor.buffer.read(cx).all_buffers() {
+        let buffer_ref = buffer.read(cx);
+        let settings = language_settings(
+            buffer_ref.language().map(|language| language.name()),
+            buffer_ref.file(),
+            cx,
+        );
+        if settings.semantic_tokens {
+            enabled_buffers.push(buffer);
+        } else {
+            disabled_buffer_ids.push(buffer_ref.remote_id());
+        }
+    }
+
+    let cleared = editor.display_map.update(cx, |display_map, _| {
+        disabled_buffer_ids
+            .into_iter()
+            .fold(false, |cleared, buffer_id| {
+                display_map.clear_semantic_highlights(buffer_id) || cleared
+            })
+    });
+    if cleared {
+        cx.notify();
+    }
+
+    let semantics_provider = editor.semantics_provider.clone()?;
+    if enabled_buffers.is_empty() {
+        editor.semantic_tokens_task = None;
+        return None;
+    }
+
+    editor.semantic_tokens_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+
+        let tasks = editor
+            .update(&mut cx, |_, cx| {
+                enabled_buffers
+                    .into_iter()
+                    .filter_map(|buffer| {
+                        let task = semantics_provider.semantic_tokens(&buffer, cx)?;
+                        Some((buffer, task))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .ok()?;
+        for (buffer, task) in tasks {
+            let Some(tokens) = task.await.log_err() else {
+                continue;
+            };
+            editor
+                .update(&mut cx, |editor, cx| {
+                    apply_semantic_tokens(editor, &buffer, &tokens, cx)
+                })
+                .ok()?;
+        }
+        Some(())
+    }));
+    None
+}
+
+fn apply_semantic_tokens(
+    editor: &mut Editor,
+    buffer: &Model<Buffer>,
+    tokens: &BufferSemanticTokens,
+    cx: &mut ViewContext<Editor>,
+) {
+    // Resolve each distinct combination of token type and modifiers against the theme only once.
+    let mut names = Vec::new();
+    let mut name_ixs = HashMap::default();
+    let token_name_ixs = tokens
+        .tokens
+        .iter()
+        .map(|token| {
+            *name_ixs
+                .entry((token.token_type, token.token_modifiers))
+                .or_insert_with(|| {
+                    names.push(highlight_name(tokens, token));
+                    names.len() as u32 - 1
+                })
+        })
+        .collect::<Vec<_>>();
+    let highlight_map = HighlightMap::new(
+        &names.iter().map(String::as_str).collect::<Vec<_>>(),
+        cx.theme().syntax(),
+    );
+
+    let buffer_id = buffer.read(cx).remote_id();
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let multibuffer = editor.buffer.read(cx);
+    let multibuffer_snapshot = multibuffer.snapshot(cx);
+    let mut highlights = Vec::new();
+    for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+        let context = excerpt_range.context;
+        let start_ix = tokens.tokens.partition_point(|token| {
+            token
+                .range
+                .end
+                .cmp(&context.start, &buffer_snapshot)
+                .is_le()
+        });
+        for (token, name_ix) in tokens.tokens[start_ix..]
+            .iter()
+            .zip(&token_name_ixs[start_ix..])
+        {
+            if token
+                .range
+                .start
+                .cmp(&context.end, &buffer_snapshot)
+                .is_ge()
+            {
+                break;
+            }
+
+            // Leave the tree-sitter highlight in place if the theme has no style for the token.
+            let highlight_id = highlight_map.get(*name_ix);
+            if highlight_id.is_default() {
+                continue;
+            }
+            let (Some(start), Some(end)) = (
+                multibuffer_snapshot.anchor_in_excerpt(excerpt_id, token.range.start),
+                multibuffer_snapshot.anchor_in_excerpt(excerpt_id, token.range.end),
+            ) else {
+                continue;
+            };
+            highlights.push((start..end, highlight_id));
+        }
+    }
+    highlights.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &multibuffer_snapshot));
+
+    editor.display_map.update(cx, |display_map, _| {
+        display_map.set_semantic_highlights(buffer_id, highlights)
+    });
+    cx.notify();
+}
+
+/// The theme syntax style name of a token: its type, translated to the capture names
+/// used by tree-sitter highlight queries, followed by its modifiers, e.g. `variable.mutable`.
+fn highlight_name(tokens: &BufferSemanticTokens, token: &SemanticToken) -> String {
+    let mut name = match tokens.token_type(token).unwrap_or_default() {
+        "class" | "interface" | "struct" | "typeParameter" => "type",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "method" => "function.method",
+        "macro" => "function.special",
+        "event" => "property",
+        "modifier" => "keyword",
+        "regexp" => "string.regex",
+        "decorator" => "attribute",
+        token_type => token_type,
+    }
+    .to_string();
+    for modifier in tokens.token_modifiers(token) {
+        name.push('.');
+        name.push_str(match modifier {
+            "documentation" => "doc",
+            modifier => modifier,
+        });
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_highlight_name() {
+        let tokens = BufferSemanticTokens {
+            token_types: Arc::from(["method".to_string(), "variable".to_string()]),
+            token_modifiers: Arc::from(["mutable".to_string(), "documentation".to_string()]),
+            tokens: Vec::new(),
+        };
+        let token = |token_type, token_modifiers| SemanticToken {
+            range: text::Anchor::MIN..text::Anchor::MAX,
+            token_type,
+            token_modifiers,
+        };
+
+        assert_eq!(highlight_name(&tokens, &token(0, 0)), "function.method");
+        assert_eq!(highlight_name(&tokens, &token(1, 0b01)), "variable.mutable");
+        assert_eq!(
+            highlight_name(&tokens, &token(1, 0b11)),
+            "variable.mutable.doc"
+        );
+        assert_eq!(highlight_name(&tokens, &token(7, 0)), "");
+    }
+}
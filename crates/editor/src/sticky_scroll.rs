@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+use gpui::{AppContext, HighlightStyle};
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot, ToPoint};
+use settings::Settings;
+use text::Point;
+use theme::SyntaxTheme;
+use ui::ViewContext;
+
+use crate::{
+    scroll::Autoscroll, DisplayPoint, DisplayRow, DisplaySnapshot, Editor, EditorMode,
+    EditorSettings,
+};
+
+/// A syntax scope whose first line has scrolled above the viewport, and is
+/// pinned at the top of the editor instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StickyHeader {
+    /// The row of the scope's first line.
+    pub row: MultiBufferRow,
+}
+
+/// The sticky headers of the last layout, which are reused until the buffer
+/// or the lines they cover change.
+pub(crate) struct StickyHeadersCache {
+    covered_rows: Vec<u32>,
+    edit_count: usize,
+    non_text_state_update_count: usize,
+    headers: Vec<StickyHeader>,
+}
+
+impl Editor {
+    /// Returns the enclosing scopes to pin above the given top display row,
+    /// from the outermost to the innermost.
+    pub fn sticky_headers(
+        &mut self,
+        snapshot: &DisplaySnapshot,
+        top_row: DisplayRow,
+        cx: &AppContext,
+    ) -> Vec<StickyHeader> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled
+            || self.mode != EditorMode::Full
+            || !self.buffer.read(cx).is_singleton()
+        {
+            self.sticky_headers_cache = None;
+            return Vec::new();
+        }
+
+        // Looking up the enclosing symbols is expensive, so only do it again
+        // when the lines that the headers would cover or the buffer change.
+        let buffer = &snapshot.buffer_snapshot;
+        let covered_rows = (top_row.0..=snapshot.max_point().row().0)
+            .take(settings.max_depth)
+            .map(|row| DisplayPoint::new(DisplayRow(row), 0).to_point(snapshot).row)
+            .collect::<Vec<_>>();
+        if let Some(cache) = &self.sticky_headers_cache {
+            if cache.covered_rows == covered_rows
+                && cache.edit_count == buffer.edit_count()
+                && cache.non_text_state_update_count == buffer.non_text_state_update_count()
+            {
+                return cache.headers.clone();
+            }
+        }
+
+        let headers = sticky_headers(snapshot, top_row, settings.max_depth);
+        self.sticky_headers_cache = Some(StickyHeadersCache {
+            covered_rows,
+            edit_count: buffer.edit_count(),
+            non_text_state_update_count: buffer.non_text_state_update_count(),
+            headers: headers.clone(),
+        });
+        headers
+    }
+
+    pub fn jump_to_sticky_header(
+        &mut self,
+        header: &StickyHeader,
+        depth: usize,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let point = Point::new(header.row.0, buffer.indent_size_for_line(header.row).len);
+        // Leave room for the headers of the scopes enclosing this one.
+        self.change_selections(Some(Autoscroll::top_relative(depth)), cx, |selections| {
+            selections.select_ranges([point..point])
+        });
+        cx.focus_self();
+    }
+}
+
+/// Computes the sticky headers for a viewport whose first line is `top_row`.
+///
+/// Every pinned header hides one more line of the viewport, so the scope for
+/// each depth is looked up at the line that the header will cover.
+pub(crate) fn sticky_headers(
+    snapshot: &DisplaySnapshot,
+    top_row: DisplayRow,
+    max_depth: usize,
+) -> Vec<StickyHeader> {
+    let buffer = &snapshot.buffer_snapshot;
+    let max_row = snapshot.max_point().row();
+    let mut headers: Vec<StickyHeader> = Vec::new();
+    while headers.len() < max_depth {
+        let covered_row = DisplayRow(top_row.0 + headers.len() as u32);
+        if covered_row > max_row {
+            break;
+        }
+        let covered_point = DisplayPoint::new(covered_row, 0).to_point(snapshot);
+        let Some((_, symbols)) = buffer.symbols_containing(covered_point, None) else {
+            break;
+        };
+        let scope_rows = symbols
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.range.start.to_point(buffer).row,
+                    symbol.range.end.to_point(buffer).row,
+                )
+            })
+            .take_while(|(_, end_row)| *end_row >= covered_point.row)
+            .map(|(start_row, _)| MultiBufferRow(start_row))
+            .collect::<Vec<_>>();
+
+        // Stop once the scopes pinned so far no longer enclose the covered line.
+        if scope_rows.len() <= headers.len()
+            || scope_rows
+                .iter()
+                .zip(&headers)
+                .any(|(row, header)| *row != header.row)
+        {
+            break;
+        }
+        let row = scope_rows[headers.len()];
+        if row.0 >= covered_point.row {
+            break;
+        }
+        headers.push(StickyHeader { row });
+    }
+    headers
+}
+
+/// Returns the text of a header's line with tabs expanded, along with its
+/// syntax highlights.
+pub(crate) fn sticky_header_text(
+    buffer: &MultiBufferSnapshot,
+    header: &StickyHeader,
+    syntax_theme: &SyntaxTheme,
+    cx: &AppContext,
+) -> (String, Vec<(Range<usize>, HighlightStyle)>) {
+    let start = Point::new(header.row.0, 0);
+    let end = Point::new(header.row.0, buffer.line_len(header.row));
+    let tab_size = buffer.settings_at(start, cx).tab_size.get() as usize;
+
+    let mut text = String::new();
+    let mut highlights = Vec::new();
+    for chunk in buffer.chunks(start..end, true) {
+        let chunk_start = text.len();
+        for character in chunk.text.chars() {
+            if character == '\t' {
+                let column = text.chars().count();
+                text.extend(std::iter::repeat(' ').take(tab_size - column % tab_size));
+            } else {
+                text.push(character);
+            }
+        }
+        if let Some(style) = chunk
+            .syntax_highlight_id
+            .and_then(|highlight_id| highlight_id.style(syntax_theme))
+        {
+            highlights.push((chunk_start..text.len(), style));
+        }
+    }
+    (text, highlights)
+}
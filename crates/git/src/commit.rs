@@ -1,7 +1,39 @@
-use crate::Oid;
+use crate::{
+    parse_git_remote_url, repository::CommitSummary, BuildCommitPermalinkParams,
+    GitHostingProviderRegistry, Oid, PullRequest,
+};
 use anyhow::{anyhow, Result};
 use collections::HashMap;
-use std::path::Path;
+use std::{path::Path, sync::Arc};
+use url::Url;
+
+/// A page of a repository's history, along with the remote needed to link to its commits.
+#[derive(Debug, Clone, Default)]
+pub struct CommitLog {
+    pub commits: Vec<CommitSummary>,
+    pub remote_url: Option<String>,
+}
+
+/// Links to a commit on the Git hosting provider of the repository's remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitLinks {
+    pub permalink: Url,
+    pub pull_request: Option<PullRequest>,
+}
+
+/// Builds the links for a commit, if the remote URL belongs to a known hosting provider.
+pub fn commit_links(
+    provider_registry: Arc<GitHostingProviderRegistry>,
+    remote_url: &str,
+    sha: &str,
+    message: &str,
+) -> Option<CommitLinks> {
+    let (provider, remote) = parse_git_remote_url(provider_registry, remote_url)?;
+    Some(CommitLinks {
+        permalink: provider.build_commit_permalink(&remote, BuildCommitPermalinkParams { sha }),
+        pull_request: provider.extract_pull_request(&remote, message),
+    })
+}
 
 pub fn get_messages(working_directory: &Path, shas: &[Oid]) -> Result<HashMap<Oid, String>> {
     if shas.is_empty() {
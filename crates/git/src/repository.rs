@@ -9,6 +9,7 @@ use rope::Rope;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    mem,
     path::{Component, Path, PathBuf},
     sync::Arc,
 };
@@ -23,6 +24,37 @@ pub struct Branch {
     pub unix_timestamp: Option<i64>,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub sha: SharedString,
+    /// The first line of the commit message.
+    pub subject: SharedString,
+    pub author_name: SharedString,
+    pub author_email: SharedString,
+    /// Timestamp of the commit, normalized to Unix Epoch format.
+    pub commit_timestamp: i64,
+}
+
+/// A file changed by a commit, with its contents before and after the commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFileDiff {
+    pub path: RepoPath,
+    /// `None` when the commit added the file, or when it is binary or too large.
+    pub old_text: Option<String>,
+    /// `None` when the commit deleted the file, or when it is binary or too large.
+    pub new_text: Option<String>,
+    /// Whether either side of the change is binary, in which case no text is loaded.
+    pub is_binary: bool,
+    /// Whether either side of the change is larger than [`MAX_COMMIT_DIFF_TEXT_LEN`], in which
+    /// case no text is loaded.
+    pub is_too_large: bool,
+    /// Whether the commit deleted the file.
+    pub is_deleted: bool,
+}
+
+/// The size in bytes above which the contents of a file changed by a commit aren't loaded.
+pub const MAX_COMMIT_DIFF_TEXT_LEN: usize = 1024 * 1024;
+
 pub trait GitRepository: Send + Sync {
     fn reload_index(&self);
 
@@ -46,6 +78,31 @@ pub trait GitRepository: Send + Sync {
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
 
+    /// Returns the paths whose index contents differ from HEAD.
+    fn staged_paths(&self) -> Result<Vec<RepoPath>>;
+    /// Updates the index with the working copy of the given paths, removing the deleted ones.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+    /// Resets the index entries of the given paths to their contents in HEAD.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+    /// Commits the index, replacing the HEAD commit instead of adding a new one when amending.
+    fn commit(&self, message: &str, amend: bool) -> Result<()>;
+
+    fn stash(&self, message: Option<&str>) -> Result<()>;
+    fn stash_pop(&self) -> Result<()>;
+
+    /// Returns the commits reachable from HEAD, most recent first, starting after the commit
+    /// with the given SHA when there is one. When a path is given, only the commits that changed
+    /// it are returned.
+    fn log(
+        &self,
+        path: Option<&RepoPath>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>>;
+
+    /// Returns the files changed by the given commit, relative to its first parent.
+    fn commit_diff(&self, sha: &str) -> Result<Vec<CommitFileDiff>>;
+
     /// Returns the path to the repository, typically the `.git` folder.
     fn dot_git_dir(&self) -> PathBuf;
 }
@@ -225,6 +282,234 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn staged_paths(&self) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+        let mut paths = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+            .map(|path| RepoPath::new(path.to_path_buf()))
+            .collect::<Vec<_>>();
+        paths.sort_unstable();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        let working_directory = repo
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let mut index = repo.index()?;
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            if working_directory.join(path).exists() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        // Without a HEAD commit, the entries are removed from the index.
+        let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repo.reset_default(
+            head.as_ref().map(|head| head.as_object()),
+            paths.iter().map(|path| path.as_path()),
+        )?;
+        Ok(())
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<()> {
+        let repo = self.repository.lock();
+        let signature = repo.signature()?;
+        let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        if !amend && head_tree.map_or(tree.is_empty(), |head_tree| head_tree.id() == tree.id()) {
+            anyhow::bail!("nothing to commit");
+        }
+        if amend {
+            let head = repo.head()?.peel_to_commit()?;
+            head.amend(
+                Some("HEAD"),
+                None,
+                Some(&signature),
+                None,
+                Some(message),
+                Some(&tree),
+            )?;
+        } else {
+            let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+            let parents = parent.iter().collect::<Vec<_>>();
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn stash(&self, message: Option<&str>) -> Result<()> {
+        let mut repo = self.repository.lock();
+        let signature = repo.signature()?;
+        repo.stash_save2(&signature, message, None)?;
+        Ok(())
+    }
+
+    fn stash_pop(&self) -> Result<()> {
+        let mut repo = self.repository.lock();
+        repo.stash_pop(0, None)?;
+        Ok(())
+    }
+
+    fn log(
+        &self,
+        path: Option<&RepoPath>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        // Walk a separate handle, so that long walks don't block the other operations.
+        let repo = git2::Repository::open(self.repository.lock().path())?;
+        if repo.head().is_err() {
+            return Ok(Vec::new());
+        }
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push_head()?;
+
+        // Resume the walk after the last commit of the previous page, without looking
+        // at the commits before it. If HEAD moved away from that commit, the walk can't
+        // be resumed.
+        if let Some(after) = after {
+            let after_oid = git2::Oid::from_str(after)?;
+            let mut found = false;
+            for oid in revwalk.by_ref() {
+                if oid? == after_oid {
+                    found = true;
+                    break;
+                }
+            }
+            if !found {
+                return Err(anyhow::anyhow!(
+                    "commit {after} is not in the history of HEAD"
+                ));
+            }
+        }
+
+        // Compares the entries of the path in the trees of the commit and its first parent,
+        // which is much cheaper than diffing the trees.
+        let touches_path = |commit: &git2::Commit, path: &RepoPath| -> Result<bool> {
+            let entry_id = |tree: &git2::Tree| {
+                tree.get_path(path.as_path())
+                    .ok()
+                    .map(|entry| (entry.id(), entry.filemode()))
+            };
+            let parent_entry = match commit.parents().next() {
+                Some(parent) => entry_id(&parent.tree()?),
+                None => None,
+            };
+            Ok(entry_id(&commit.tree()?) != parent_entry)
+        };
+
+        revwalk
+            .map(|oid| repo.find_commit(oid?).map_err(anyhow::Error::from))
+            .filter(|commit| match (commit, path) {
+                (Ok(commit), Some(path)) => touches_path(commit, path).unwrap_or(false),
+                _ => true,
+            })
+            .take(limit)
+            .map(|commit| {
+                let commit = commit?;
+                let author = commit.author();
+                let commit_timestamp = commit.time().seconds();
+                Ok(CommitSummary {
+                    sha: commit.id().to_string().into(),
+                    subject: String::from_utf8_lossy(commit.summary_bytes().unwrap_or_default())
+                        .to_string()
+                        .into(),
+                    author_name: String::from_utf8_lossy(author.name_bytes())
+                        .to_string()
+                        .into(),
+                    author_email: String::from_utf8_lossy(author.email_bytes())
+                        .to_string()
+                        .into(),
+                    commit_timestamp,
+                })
+            })
+            .collect()
+    }
+
+    fn commit_diff(&self, sha: &str) -> Result<Vec<CommitFileDiff>> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(git2::Oid::from_str(sha)?)?;
+        let tree = commit.tree()?;
+        let parent_tree = commit
+            .parents()
+            .next()
+            .map(|parent| parent.tree())
+            .transpose()?;
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+        let load_blob = |id: git2::Oid| -> Result<Option<git2::Blob>> {
+            if id.is_zero() {
+                Ok(None)
+            } else {
+                Ok(Some(repo.find_blob(id)?))
+            }
+        };
+
+        diff.deltas()
+            .filter_map(|delta| {
+                // Submodules have no contents to show.
+                if delta.new_file().mode() == git2::FileMode::Commit
+                    || delta.old_file().mode() == git2::FileMode::Commit
+                {
+                    return None;
+                }
+                let path = delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())?;
+                Some((path.to_path_buf(), delta))
+            })
+            .map(|(path, delta)| {
+                let old_blob = load_blob(delta.old_file().id())?;
+                let new_blob = load_blob(delta.new_file().id())?;
+                let is_binary = old_blob
+                    .iter()
+                    .chain(&new_blob)
+                    .any(|blob| blob.is_binary());
+                let is_too_large = !is_binary
+                    && old_blob
+                        .iter()
+                        .chain(&new_blob)
+                        .any(|blob| blob.size() > MAX_COMMIT_DIFF_TEXT_LEN);
+                let load_text = |blob: Option<git2::Blob>| {
+                    blob.filter(|_| !is_binary && !is_too_large)
+                        .map(|blob| String::from_utf8_lossy(blob.content()).into_owned())
+                };
+                Ok(CommitFileDiff {
+                    path: RepoPath::new(path),
+                    old_text: load_text(old_blob),
+                    new_text: load_text(new_blob),
+                    is_binary,
+                    is_too_large,
+                    is_deleted: delta.status() == git2::Delta::Deleted,
+                })
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -241,6 +526,11 @@ pub struct FakeGitRepositoryState {
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
+    pub staged_paths: HashSet<RepoPath>,
+    /// Most recent first.
+    pub commits: Vec<CommitSummary>,
+    pub commit_diffs: HashMap<SharedString, Vec<CommitFileDiff>>,
+    pub stashes: Vec<HashSet<RepoPath>>,
 }
 
 impl FakeGitRepository {
@@ -259,8 +549,18 @@ impl FakeGitRepositoryState {
             worktree_statuses: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
+            staged_paths: Default::default(),
+            commits: Default::default(),
+            commit_diffs: Default::default(),
+            stashes: Default::default(),
         }
     }
+
+    fn emit_change_event(&self) {
+        self.event_emitter
+            .try_send(self.dot_git_dir.clone())
+            .expect("Dropped repo change event");
+    }
 }
 
 impl GitRepository for FakeGitRepository {
@@ -358,6 +658,138 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn staged_paths(&self) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        let mut paths = state.staged_paths.iter().cloned().collect::<Vec<_>>();
+        paths.sort_unstable();
+        Ok(paths)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        state.staged_paths.extend(paths.iter().cloned());
+        state.emit_change_event();
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            state.staged_paths.remove(path);
+        }
+        state.emit_change_event();
+        Ok(())
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        if amend && state.commits.is_empty() {
+            anyhow::bail!("no commit to amend");
+        }
+        if !amend && state.staged_paths.is_empty() {
+            anyhow::bail!("nothing to commit");
+        }
+        let commit = CommitSummary {
+            sha: format!("{:040x}", state.commits.len() + 1).into(),
+            subject: message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+                .into(),
+            author_name: "Fake Author".into(),
+            author_email: "fake@example.com".into(),
+            commit_timestamp: 0,
+        };
+        let mut diffs = if amend {
+            let amended = mem::replace(&mut state.commits[0], commit.clone());
+            state.commit_diffs.remove(&amended.sha).unwrap_or_default()
+        } else {
+            state.commits.insert(0, commit.clone());
+            Vec::new()
+        };
+        let mut staged_paths = mem::take(&mut state.staged_paths)
+            .into_iter()
+            .collect::<Vec<_>>();
+        staged_paths.sort_unstable();
+        for path in staged_paths {
+            state.worktree_statuses.remove(&path);
+            diffs.retain(|diff| diff.path != path);
+            let new_text = state.index_contents.get(path.as_path()).cloned();
+            diffs.push(CommitFileDiff {
+                is_deleted: new_text.is_none(),
+                new_text,
+                old_text: None,
+                is_binary: false,
+                is_too_large: false,
+                path,
+            });
+        }
+        state.commit_diffs.insert(commit.sha, diffs);
+        state.emit_change_event();
+        Ok(())
+    }
+
+    fn stash(&self, _message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        let stash = mem::take(&mut state.staged_paths);
+        state.stashes.push(stash);
+        state.emit_change_event();
+        Ok(())
+    }
+
+    fn stash_pop(&self) -> Result<()> {
+        let mut state = self.state.lock();
+        let stash = state.stashes.pop().context("no stash to pop")?;
+        state.staged_paths.extend(stash);
+        state.emit_change_event();
+        Ok(())
+    }
+
+    fn log(
+        &self,
+        path: Option<&RepoPath>,
+        after: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<CommitSummary>> {
+        let state = self.state.lock();
+        let skip = match after {
+            Some(after) => {
+                state
+                    .commits
+                    .iter()
+                    .position(|commit| commit.sha.as_ref() == after)
+                    .with_context(|| format!("no commit with sha {after}"))?
+                    + 1
+            }
+            None => 0,
+        };
+        Ok(state
+            .commits
+            .iter()
+            .skip(skip)
+            .filter(|commit| {
+                path.map_or(true, |path| {
+                    state
+                        .commit_diffs
+                        .get(&commit.sha)
+                        .map_or(false, |diffs| diffs.iter().any(|diff| &diff.path == path))
+                })
+            })
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    fn commit_diff(&self, sha: &str) -> Result<Vec<CommitFileDiff>> {
+        let state = self.state.lock();
+        state
+            .commit_diffs
+            .get(sha)
+            .cloned()
+            .with_context(|| format!("no commit with sha {sha}"))
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -393,7 +825,35 @@ fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
 pub enum GitFileStatus {
     Added,
     Modified,
-    Conflict,
+    /// Directories only count the conflicts they contain, so they report them as
+    /// [`UnmergedStatus::BothModified`].
+    Conflict(UnmergedStatus),
+}
+
+/// How each side of a merge changed an unmerged file, see the "Short Format" section of git-status(1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum UnmergedStatus {
+    BothModified,
+    BothAdded,
+    BothDeleted,
+    AddedByUs,
+    AddedByThem,
+    DeletedByUs,
+    DeletedByThem,
+}
+
+impl UnmergedStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnmergedStatus::BothModified => "both modified",
+            UnmergedStatus::BothAdded => "both added",
+            UnmergedStatus::BothDeleted => "both deleted",
+            UnmergedStatus::AddedByUs => "added by us",
+            UnmergedStatus::AddedByThem => "added by them",
+            UnmergedStatus::DeletedByUs => "deleted by us",
+            UnmergedStatus::DeletedByThem => "deleted by them",
+        }
+    }
 }
 
 impl GitFileStatus {
@@ -407,9 +867,8 @@ impl GitFileStatus {
         }
 
         match (this, other) {
-            (Some(GitFileStatus::Conflict), _) | (_, Some(GitFileStatus::Conflict)) => {
-                Some(GitFileStatus::Conflict)
-            }
+            (Some(GitFileStatus::Conflict(status)), _)
+            | (_, Some(GitFileStatus::Conflict(status))) => Some(GitFileStatus::Conflict(status)),
             (Some(GitFileStatus::Modified), _) | (_, Some(GitFileStatus::Modified)) => {
                 Some(GitFileStatus::Modified)
             }
@@ -476,3 +935,190 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit_files(repo: &git2::Repository, message: &str, files: &[(&str, &[u8])]) {
+        let workdir = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            std::fs::write(workdir.join(path), content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_stage_commit_and_stash() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        commit_files(&repo, "one", &[("a.txt", b"a")]);
+        let repo = RealGitRepository::new(repo, None, Arc::new(GitHostingProviderRegistry::new()));
+        let a = RepoPath::new("a.txt".into());
+        let b = RepoPath::new("b.txt".into());
+        let subjects = || {
+            repo.log(None, None, 10)
+                .unwrap()
+                .into_iter()
+                .map(|commit| commit.subject.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        std::fs::write(dir.path().join("a.txt"), "aa").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "b").unwrap();
+        let error = repo.commit("empty", false).unwrap_err();
+        assert_eq!(error.to_string(), "nothing to commit");
+        assert_eq!(subjects(), ["one"]);
+
+        repo.stage_paths(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(repo.staged_paths().unwrap(), [a.clone(), b.clone()]);
+        repo.unstage_paths(&[b.clone()]).unwrap();
+        assert_eq!(repo.staged_paths().unwrap(), [a.clone()]);
+
+        repo.commit("two", false).unwrap();
+        assert_eq!(subjects(), ["two", "one"]);
+        assert!(repo.staged_paths().unwrap().is_empty());
+        let head = repo.log(None, None, 1).unwrap().remove(0);
+        let paths = |sha: &str| {
+            repo.commit_diff(sha)
+                .unwrap()
+                .into_iter()
+                .map(|diff| diff.path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paths(&head.sha), [a.clone()]);
+
+        // Amending replaces the last commit, adding the staged changes to it.
+        repo.stage_paths(&[b.clone()]).unwrap();
+        repo.commit("two, amended", true).unwrap();
+        assert_eq!(subjects(), ["two, amended", "one"]);
+        let head = repo.log(None, None, 1).unwrap().remove(0);
+        assert_eq!(paths(&head.sha), [a.clone(), b.clone()]);
+
+        std::fs::write(dir.path().join("a.txt"), "aaa").unwrap();
+        repo.stage_paths(&[a.clone()]).unwrap();
+        repo.stash(Some("wip")).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "aa"
+        );
+        assert!(repo.staged_paths().unwrap().is_empty());
+        repo.stash_pop().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "aaa"
+        );
+        assert!(repo.stash_pop().is_err());
+    }
+
+    #[test]
+    fn test_log_and_commit_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        commit_files(&repo, "one", &[("a.txt", b"a"), ("b.txt", b"b")]);
+        commit_files(&repo, "two", &[("a.txt", b"aa")]);
+        commit_files(&repo, "three", &[("b.txt", b"bb")]);
+        commit_files(&repo, "four", &[("a.txt", b"aaa"), ("image.png", b"\0png")]);
+        let repo = RealGitRepository::new(repo, None, Arc::new(GitHostingProviderRegistry::new()));
+
+        let subjects = |commits: &[CommitSummary]| {
+            commits
+                .iter()
+                .map(|commit| commit.subject.to_string())
+                .collect::<Vec<_>>()
+        };
+        let log = repo.log(None, None, 10).unwrap();
+        assert_eq!(subjects(&log), ["four", "three", "two", "one"]);
+
+        // Pages resume after the last commit of the previous one.
+        let page = repo.log(None, None, 2).unwrap();
+        assert_eq!(subjects(&page), ["four", "three"]);
+        let page = repo.log(None, Some(page[1].sha.as_ref()), 2).unwrap();
+        assert_eq!(subjects(&page), ["two", "one"]);
+
+        let path = RepoPath::new("a.txt".into());
+        let page = repo.log(Some(&path), None, 2).unwrap();
+        assert_eq!(subjects(&page), ["four", "two"]);
+        let page = repo
+            .log(Some(&path), Some(page[1].sha.as_ref()), 2)
+            .unwrap();
+        assert_eq!(subjects(&page), ["one"]);
+
+        // Pages can't resume after a commit that isn't in the history of HEAD anymore.
+        let missing_sha = "0123456789abcdef0123456789abcdef01234567";
+        assert!(repo.log(None, Some(missing_sha), 2).is_err());
+
+        // Binary files are listed without their contents.
+        let diff = repo.commit_diff(&log[0].sha).unwrap();
+        assert_eq!(
+            diff,
+            [
+                CommitFileDiff {
+                    path: RepoPath::new("a.txt".into()),
+                    old_text: Some("aa".into()),
+                    new_text: Some("aaa".into()),
+                    is_binary: false,
+                    is_too_large: false,
+                    is_deleted: false,
+                },
+                CommitFileDiff {
+                    path: RepoPath::new("image.png".into()),
+                    old_text: None,
+                    new_text: None,
+                    is_binary: true,
+                    is_too_large: false,
+                    is_deleted: false,
+                },
+            ]
+        );
+
+        // Large files are listed without their contents too.
+        {
+            let git_repo = repo.repository.lock();
+            let mut index = git_repo.index().unwrap();
+            index.remove_path(Path::new("b.txt")).unwrap();
+            index.write().unwrap();
+            let large_text = "a\n".repeat(MAX_COMMIT_DIFF_TEXT_LEN);
+            commit_files(&git_repo, "five", &[("large.txt", large_text.as_bytes())]);
+        }
+        let head = repo.log(None, None, 1).unwrap().remove(0);
+        assert_eq!(
+            repo.commit_diff(&head.sha).unwrap(),
+            [
+                CommitFileDiff {
+                    path: RepoPath::new("b.txt".into()),
+                    old_text: Some("bb".into()),
+                    new_text: None,
+                    is_binary: false,
+                    is_too_large: false,
+                    is_deleted: true,
+                },
+                CommitFileDiff {
+                    path: RepoPath::new("large.txt".into()),
+                    old_text: None,
+                    new_text: None,
+                    is_binary: false,
+                    is_too_large: true,
+                    is_deleted: false,
+                },
+            ]
+        );
+    }
+}
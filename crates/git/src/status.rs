@@ -1,4 +1,4 @@
-use crate::repository::{GitFileStatus, RepoPath};
+use crate::repository::{GitFileStatus, RepoPath, UnmergedStatus};
 use anyhow::{anyhow, Result};
 use std::{
     path::{Path, PathBuf},
@@ -53,14 +53,9 @@ impl GitStatus {
             .filter_map(|entry| {
                 if entry.is_char_boundary(3) {
                     let (status, path) = entry.split_at(3);
-                    let status = status.trim();
                     Some((
                         RepoPath(PathBuf::from(path)),
-                        match status {
-                            "A" | "??" => GitFileStatus::Added,
-                            "M" => GitFileStatus::Modified,
-                            _ => return None,
-                        },
+                        file_status_from_porcelain(status.trim())?,
                     ))
                 } else {
                     None
@@ -88,3 +83,49 @@ impl Default for GitStatus {
         }
     }
 }
+
+/// Maps the `XY` code of a `git status --porcelain` entry to a file status.
+fn file_status_from_porcelain(status: &str) -> Option<GitFileStatus> {
+    match status {
+        "A" | "??" => Some(GitFileStatus::Added),
+        "M" => Some(GitFileStatus::Modified),
+        // Unmerged paths, see the "Short Format" section of git-status(1).
+        "DD" => Some(GitFileStatus::Conflict(UnmergedStatus::BothDeleted)),
+        "AU" => Some(GitFileStatus::Conflict(UnmergedStatus::AddedByUs)),
+        "UD" => Some(GitFileStatus::Conflict(UnmergedStatus::DeletedByThem)),
+        "UA" => Some(GitFileStatus::Conflict(UnmergedStatus::AddedByThem)),
+        "DU" => Some(GitFileStatus::Conflict(UnmergedStatus::DeletedByUs)),
+        "AA" => Some(GitFileStatus::Conflict(UnmergedStatus::BothAdded)),
+        "UU" => Some(GitFileStatus::Conflict(UnmergedStatus::BothModified)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_status_from_porcelain() {
+        assert_eq!(file_status_from_porcelain("??"), Some(GitFileStatus::Added));
+        assert_eq!(
+            file_status_from_porcelain("M"),
+            Some(GitFileStatus::Modified)
+        );
+        for (code, status) in [
+            ("DD", UnmergedStatus::BothDeleted),
+            ("AU", UnmergedStatus::AddedByUs),
+            ("UD", UnmergedStatus::DeletedByThem),
+            ("UA", UnmergedStatus::AddedByThem),
+            ("DU", UnmergedStatus::DeletedByUs),
+            ("AA", UnmergedStatus::BothAdded),
+            ("UU", UnmergedStatus::BothModified),
+        ] {
+            assert_eq!(
+                file_status_from_porcelain(code),
+                Some(GitFileStatus::Conflict(status))
+            );
+        }
+        assert_eq!(file_status_from_porcelain("!!"), None);
+    }
+}
@@ -0,0 +1,445 @@
+use std::path::Path;
+
+use git::{
+    commit::{commit_links, CommitLinks},
+    repository::{CommitSummary, RepoPath},
+    GitHostingProviderRegistry,
+};
+use gpui::*;
+use menu::{Confirm, SelectNext, SelectPrev};
+use project::{Project, ProjectPath, WorktreeId};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{item::Item, notifications::DetachAndPromptErr, Workspace};
+
+use crate::commit_view::CommitView;
+use crate::{ViewFileHistory, ViewHistory};
+
+/// The number of commits requested from the repository at a time.
+const PAGE_SIZE: usize = 100;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+            workspace.register_action(CommitHistory::view_history);
+            workspace.register_action(CommitHistory::view_file_history);
+        },
+    )
+    .detach();
+}
+
+struct HistoryEntry {
+    commit: CommitSummary,
+    links: Option<CommitLinks>,
+}
+
+/// A paginated log of the commits of a repository, or of the commits that changed one of its files.
+pub struct CommitHistory {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    repository: ProjectPath,
+    path: Option<RepoPath>,
+    entries: Vec<HistoryEntry>,
+    selected_index: Option<usize>,
+    has_more: bool,
+    load_task: Option<Task<()>>,
+    /// The error of the last page that failed to load, after which no more pages are loaded.
+    load_error: Option<SharedString>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl CommitHistory {
+    fn view_history(workspace: &mut Workspace, _: &ViewHistory, cx: &mut ViewContext<Workspace>) {
+        let Some(repository) = active_repository(workspace, cx) else {
+            return;
+        };
+        Self::deploy(workspace, repository, None, cx);
+    }
+
+    fn view_file_history(
+        workspace: &mut Workspace,
+        _: &ViewFileHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().read(cx);
+        let Some((repository, path)) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+            .and_then(|path| repository_for_path(project, path.worktree_id, &path.path, cx))
+        else {
+            return;
+        };
+        Self::deploy(workspace, repository, Some(path), cx);
+    }
+
+    fn deploy(
+        workspace: &mut Workspace,
+        repository: ProjectPath,
+        path: Option<RepoPath>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items_of_type::<Self>()
+            .find(|history| {
+                let history = history.read(cx);
+                history.repository == repository && history.path == path
+            });
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, cx);
+            return;
+        }
+
+        let workspace_handle = cx.view().downgrade();
+        let project = workspace.project().clone();
+        let history = cx.new_view(|cx| Self::new(workspace_handle, project, repository, path, cx));
+        workspace.add_item_to_active_pane(Box::new(history), None, true, cx);
+    }
+
+    pub fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        repository: ProjectPath,
+        path: Option<RepoPath>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut this = Self {
+            workspace,
+            project,
+            repository,
+            path,
+            entries: Vec::new(),
+            selected_index: None,
+            has_more: true,
+            load_task: None,
+            load_error: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+        this.load_next_page(cx);
+        this
+    }
+
+    fn load_next_page(&mut self, cx: &mut ViewContext<Self>) {
+        if self.load_task.is_some() || !self.has_more || self.load_error.is_some() {
+            return;
+        }
+
+        let log = self.project.read(cx).git_log(
+            self.repository.clone(),
+            self.path.clone(),
+            self.entries
+                .last()
+                .map(|entry| entry.commit.sha.to_string()),
+            PAGE_SIZE,
+            cx,
+        );
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let log = log.await;
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                let log = match log {
+                    Ok(log) => log,
+                    Err(error) => {
+                        this.load_error = Some(format!("Failed to load history: {error}").into());
+                        cx.notify();
+                        return;
+                    }
+                };
+                let provider_registry = GitHostingProviderRegistry::try_global(cx);
+                this.has_more = log.commits.len() == PAGE_SIZE;
+                this.entries.extend(log.commits.into_iter().map(|commit| {
+                    HistoryEntry {
+                        links: provider_registry
+                            .clone()
+                            .zip(log.remote_url.as_deref())
+                            .and_then(|(registry, remote_url)| {
+                                commit_links(registry, remote_url, &commit.sha, &commit.subject)
+                            }),
+                        commit,
+                    }
+                }));
+                cx.notify();
+            })
+            .ok();
+        }));
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let index = self
+            .selected_index
+            .map_or(0, |index| (index + 1).min(self.entries.len() - 1));
+        self.select(index, cx);
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let index = self
+            .selected_index
+            .map_or(0, |index| index.saturating_sub(1));
+        self.select(index, cx);
+    }
+
+    fn select(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        self.selected_index = Some(index);
+        self.scroll_handle
+            .scroll_to_item(index, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(index) = self.selected_index {
+            self.open_commit(index, cx);
+        }
+    }
+
+    fn open_commit(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        if index >= self.entries.len() {
+            return;
+        }
+        self.selected_index = Some(index);
+        cx.notify();
+
+        let entry = &self.entries[index];
+        CommitView::open(
+            entry.commit.clone(),
+            entry.links.clone(),
+            self.repository.clone(),
+            self.project.clone(),
+            self.workspace.clone(),
+            cx,
+        )
+        .detach_and_prompt_err("Failed to open commit", cx, |_, _| None);
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("CommitHistory");
+        dispatch_context.add("menu");
+
+        dispatch_context
+    }
+
+    fn title(&self) -> SharedString {
+        match self.path.as_ref().and_then(|path| path.file_name()) {
+            Some(file_name) => format!("History: {}", file_name.to_string_lossy()).into(),
+            None => "History".into(),
+        }
+    }
+
+    fn render_entry(&self, index: usize, cx: &ViewContext<Self>) -> impl IntoElement {
+        let entry = &self.entries[index];
+        let commit = &entry.commit;
+        let short_sha = commit.sha.get(..7).unwrap_or(&commit.sha).to_string();
+
+        ListItem::new(index)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(self.selected_index == Some(index))
+            .on_click(cx.listener(move |this, _, cx| this.open_commit(index, cx)))
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .child(
+                        div()
+                            .font_buffer(cx)
+                            .child(Label::new(short_sha).color(Color::Muted)),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .overflow_hidden()
+                            .child(Label::new(commit.subject.clone()).single_line()),
+                    )
+                    .child(Label::new(commit.author_name.clone()).color(Color::Muted))
+                    .child(
+                        Label::new(format_commit_timestamp(commit.commit_timestamp))
+                            .color(Color::Muted),
+                    )
+                    .when_some(entry.links.clone(), |this, links| {
+                        this.when_some(links.pull_request, |this, pull_request| {
+                            this.child(
+                                Button::new(
+                                    ("pull-request", index),
+                                    format!("#{}", pull_request.number),
+                                )
+                                .label_size(LabelSize::Small)
+                                .style(ButtonStyle::Subtle)
+                                .tooltip(|cx| Tooltip::text("Open Pull Request", cx))
+                                .on_click(move |_, cx| {
+                                    cx.stop_propagation();
+                                    cx.open_url(pull_request.url.as_str())
+                                }),
+                            )
+                        })
+                        .child(
+                            IconButton::new(("open-commit", index), IconName::ExternalLink)
+                                .icon_size(IconSize::Small)
+                                .icon_color(Color::Muted)
+                                .tooltip(|cx| Tooltip::text("Open Commit in Browser", cx))
+                                .on_click(move |_, cx| {
+                                    cx.stop_propagation();
+                                    cx.open_url(links.permalink.as_str())
+                                }),
+                        )
+                    }),
+            )
+    }
+}
+
+/// Returns the work directory of the repository containing the active item, or of the first
+/// repository in the project when the active item isn't in one.
+pub(crate) fn active_repository(workspace: &Workspace, cx: &AppContext) -> Option<ProjectPath> {
+    let project = workspace.project().read(cx);
+    workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+        .and_then(|path| repository_for_path(project, path.worktree_id, &path.path, cx))
+        .map(|(repository, _)| repository)
+        .or_else(|| {
+            project.visible_worktrees(cx).find_map(|worktree| {
+                let snapshot = worktree.read(cx).snapshot();
+                let (work_directory, _) = snapshot.repositories().next()?;
+                Some(ProjectPath {
+                    worktree_id: snapshot.id(),
+                    path: work_directory.clone(),
+                })
+            })
+        })
+}
+
+/// Returns the work directory of the innermost repository containing the given worktree-relative
+/// path, along with the path relative to that repository.
+pub(crate) fn repository_for_path(
+    project: &Project,
+    worktree_id: WorktreeId,
+    path: &Path,
+    cx: &AppContext,
+) -> Option<(ProjectPath, RepoPath)> {
+    let worktree = project.worktree_for_id(worktree_id, cx)?;
+    let snapshot = worktree.read(cx).snapshot();
+    let (work_directory, repository) = snapshot
+        .repositories()
+        .filter(|(work_directory, _)| path.starts_with(work_directory))
+        .last()?;
+    let repo_path = repository.relativize(&snapshot, path).log_err()?;
+    Some((
+        ProjectPath {
+            worktree_id,
+            path: work_directory.clone(),
+        },
+        repo_path,
+    ))
+}
+
+pub(crate) fn format_commit_timestamp(timestamp: i64) -> String {
+    match OffsetDateTime::from_unix_timestamp(timestamp) {
+        Ok(timestamp) => time_format::format_localized_timestamp(
+            timestamp,
+            OffsetDateTime::now_utc(),
+            UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC),
+            time_format::TimestampFormat::Relative,
+        ),
+        Err(_) => "Error parsing date".to_string(),
+    }
+}
+
+impl Render for CommitHistory {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let content = if self.entries.is_empty() {
+            h_flex()
+                .size_full()
+                .justify_center()
+                .child(match &self.load_error {
+                    Some(error) => Label::new(error.clone()).color(Color::Error),
+                    None if self.load_task.is_some() => {
+                        Label::new("Loading history…").color(Color::Muted)
+                    }
+                    None => Label::new("No commits").color(Color::Muted),
+                })
+                .into_any_element()
+        } else {
+            uniform_list(
+                cx.view().clone(),
+                "commit-history",
+                self.entries.len(),
+                |this, range, cx| {
+                    if range.end + PAGE_SIZE / 4 >= this.entries.len() {
+                        this.load_next_page(cx);
+                    }
+                    range.map(|index| this.render_entry(index, cx)).collect()
+                },
+            )
+            .size_full()
+            .track_scroll(self.scroll_handle.clone())
+            .into_any_element()
+        };
+
+        v_flex()
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .p_2()
+            .bg(cx.theme().colors().editor_background)
+            .child(content)
+            .when(!self.entries.is_empty(), |this| {
+                this.when_some(self.load_error.clone(), |this, error| {
+                    this.child(Label::new(error).color(Color::Error))
+                })
+            })
+    }
+}
+
+impl FocusableView for CommitHistory {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<()> for CommitHistory {}
+
+impl Item for CommitHistory {
+    type Event = ();
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::GitBranch))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(self.title())
+    }
+
+    fn tab_tooltip_text(&self, _cx: &AppContext) -> Option<SharedString> {
+        Some(match &self.path {
+            Some(path) => format!("History of {}", path.display()).into(),
+            None => "Repository History".into(),
+        })
+    }
+
+    fn clone_on_split(
+        &self,
+        _: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| {
+            Self::new(
+                self.workspace.clone(),
+                self.project.clone(),
+                self.repository.clone(),
+                self.path.clone(),
+                cx,
+            )
+        }))
+    }
+}
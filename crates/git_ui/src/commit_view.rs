@@ -0,0 +1,316 @@
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent, MultiBuffer, DEFAULT_MULTIBUFFER_CONTEXT};
+use git::{
+    commit::CommitLinks,
+    repository::{CommitFileDiff, CommitSummary},
+};
+use gpui::*;
+use language::{Buffer, Capability, DiskState, Point};
+use project::{buffer_store::BufferChangeSet, MTime, Project, ProjectPath};
+use ui::{prelude::*, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    searchable::SearchableItemHandle,
+    Workspace,
+};
+
+use crate::commit_history::format_commit_timestamp;
+
+/// A read-only multibuffer showing the changes introduced by a single commit.
+pub struct CommitView {
+    commit: CommitSummary,
+    links: Option<CommitLinks>,
+    editor: View<Editor>,
+}
+
+impl CommitView {
+    pub fn open(
+        commit: CommitSummary,
+        links: Option<CommitLinks>,
+        repository: ProjectPath,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<()>> {
+        let diff = project
+            .read(cx)
+            .commit_diff(repository.clone(), commit.sha.to_string(), cx);
+        cx.spawn(|mut cx| async move {
+            let files = diff.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let existing = workspace
+                    .active_pane()
+                    .read(cx)
+                    .items_of_type::<Self>()
+                    .find(|view| view.read(cx).commit.sha == commit.sha);
+                if let Some(existing) = existing {
+                    workspace.activate_item(&existing, true, true, cx);
+                } else {
+                    let view =
+                        cx.new_view(|cx| Self::new(commit, links, files, repository, project, cx));
+                    workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+                }
+            })
+        })
+    }
+
+    fn new(
+        commit: CommitSummary,
+        links: Option<CommitLinks>,
+        mut files: Vec<CommitFileDiff>,
+        repository: ProjectPath,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(Capability::ReadOnly));
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer.clone(), None, true, cx);
+            editor.set_read_only(true);
+            editor.set_expand_all_diff_hunks();
+            editor
+        });
+        cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+            cx.emit(event.clone())
+        })
+        .detach();
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let languages = project.read(cx).languages().clone();
+        let worktree = project.read(cx).worktree_for_id(repository.worktree_id, cx);
+        let mtime = MTime::from_seconds_and_nanos(commit.commit_timestamp.max(0) as u64, 0);
+        let mut diffs = Vec::new();
+        for file in files {
+            // Give the buffers the files of the worktree, so that their headers show the paths.
+            let disk_state = if file.is_deleted {
+                DiskState::Deleted
+            } else {
+                DiskState::Present { mtime }
+            };
+            let buffer_file = worktree.as_ref().map(|worktree| {
+                Arc::new(project::File {
+                    worktree: worktree.clone(),
+                    path: repository.path.join(file.path.as_path()).into(),
+                    disk_state,
+                    entry_id: None,
+                    is_local: worktree.read(cx).is_local(),
+                    is_private: false,
+                }) as Arc<dyn language::File>
+            });
+            let show_contents = !file.is_binary && !file.is_too_large;
+            let text = if file.is_binary {
+                "Binary file changed".to_string()
+            } else if file.is_too_large {
+                "Large file changed".to_string()
+            } else {
+                file.new_text.unwrap_or_default()
+            };
+            let buffer = cx.new_model(|cx| {
+                let mut buffer = Buffer::local(text, cx);
+                if let Some(buffer_file) = buffer_file {
+                    buffer.file_updated(buffer_file, cx);
+                }
+                buffer.set_language_registry(languages.clone());
+                buffer.set_capability(Capability::ReadOnly, cx);
+                buffer
+            });
+            if !show_contents {
+                diffs.push((buffer, None));
+                continue;
+            }
+            let snapshot = buffer.read(cx).text_snapshot();
+            let mut diff_computed = None;
+            let change_set = cx.new_model(|cx| {
+                let mut change_set = BufferChangeSet::new(&snapshot);
+                diff_computed =
+                    Some(change_set.set_base_text(file.old_text.unwrap_or_default(), snapshot, cx));
+                change_set
+            });
+
+            cx.spawn({
+                let languages = languages.clone();
+                let buffer = buffer.clone();
+                let path = file.path;
+                |_, mut cx| async move {
+                    if let Ok(language) = languages.language_for_file_path(&path).await {
+                        buffer.update(&mut cx, |buffer, cx| {
+                            buffer.set_language(Some(language), cx)
+                        })?;
+                    }
+                    anyhow::Ok(())
+                }
+            })
+            .detach();
+
+            diffs.push((buffer, Some((change_set, diff_computed))));
+        }
+
+        cx.spawn(|this, mut cx| async move {
+            for (buffer, diff) in diffs {
+                let Some((change_set, diff_computed)) = diff else {
+                    // Binary and large files are shown as a single line saying that they changed.
+                    multibuffer.update(&mut cx, |multibuffer, cx| {
+                        let max_point = buffer.read(cx).max_point();
+                        multibuffer.push_excerpts_with_context_lines(
+                            buffer,
+                            vec![Point::zero()..max_point],
+                            0,
+                            cx,
+                        );
+                    })?;
+                    continue;
+                };
+                if let Some(diff_computed) = diff_computed {
+                    diff_computed.await.ok();
+                }
+                this.update(&mut cx, |this, cx| {
+                    let snapshot = buffer.read(cx).text_snapshot();
+                    let hunk_ranges = change_set
+                        .read(cx)
+                        .diff_to_buffer
+                        .hunks_in_row_range(0..u32::MAX, &snapshot)
+                        .map(|hunk| hunk.buffer_range)
+                        .collect::<Vec<_>>();
+                    multibuffer.update(cx, |multibuffer, cx| {
+                        multibuffer.push_excerpts_with_context_lines(
+                            buffer,
+                            hunk_ranges,
+                            DEFAULT_MULTIBUFFER_CONTEXT,
+                            cx,
+                        );
+                    });
+                    this.editor
+                        .update(cx, |editor, cx| editor.add_change_set(change_set, cx));
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+
+        Self {
+            commit,
+            links,
+            editor,
+        }
+    }
+
+    fn render_header(&self, cx: &ViewContext<Self>) -> impl IntoElement {
+        let commit = &self.commit;
+        h_flex()
+            .w_full()
+            .px_3()
+            .py_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .child(Label::new(commit.subject.clone()))
+                    .child(
+                        Label::new(format!(
+                            "{} <{}> · {} · {}",
+                            commit.author_name,
+                            commit.author_email,
+                            format_commit_timestamp(commit.commit_timestamp),
+                            commit.sha
+                        ))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+            .when_some(self.links.clone(), |this, links| {
+                this.when_some(links.pull_request, |this, pull_request| {
+                    this.child(
+                        Button::new("pull-request", format!("#{}", pull_request.number))
+                            .style(ButtonStyle::Subtle)
+                            .tooltip(|cx| Tooltip::text("Open Pull Request", cx))
+                            .on_click(move |_, cx| cx.open_url(pull_request.url.as_str())),
+                    )
+                })
+                .child(
+                    IconButton::new("open-commit", IconName::ExternalLink)
+                        .icon_color(Color::Muted)
+                        .tooltip(|cx| Tooltip::text("Open Commit in Browser", cx))
+                        .on_click(move |_, cx| cx.open_url(links.permalink.as_str())),
+                )
+            })
+    }
+}
+
+impl Render for CommitView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_header(cx))
+            .child(self.editor.clone())
+    }
+}
+
+impl FocusableView for CommitView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for CommitView {}
+
+impl Item for CommitView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(
+            format!(
+                "{} {}",
+                self.commit.sha.get(..7).unwrap_or_default(),
+                self.commit.subject
+            )
+            .into(),
+        )
+    }
+
+    fn tab_tooltip_text(&self, _cx: &AppContext) -> Option<SharedString> {
+        Some(self.commit.subject.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn as_searchable(&self, _: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, Item::deactivated);
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, cx))
+    }
+}
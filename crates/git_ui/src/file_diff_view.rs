@@ -0,0 +1,623 @@
+use std::{
+    any::TypeId,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use collections::HashSet;
+use editor::{
+    actions::{GoToHunk, GoToPrevHunk},
+    display_map::{DisplayRow, ToDisplayPoint},
+    DisplayPoint, Editor, EditorEvent,
+};
+use futures::{channel::mpsc, StreamExt};
+use gpui::*;
+use language::{Buffer, BufferEvent, Point};
+use project::{buffer_store::BufferChangeSet, Project, ProjectPath};
+use ui::{prelude::*, Indicator, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    searchable::SearchableItemHandle,
+    Workspace,
+};
+
+/// Compares two arbitrary files side by side.
+///
+/// Both sides are regular project buffers, so they can be edited and saved. Each side's gutter
+/// shows its hunks relative to the other side, and scrolling one side keeps the other aligned.
+pub struct FileDiffView {
+    old_path: PathBuf,
+    new_path: PathBuf,
+    old_side: DiffSide,
+    new_side: DiffSide,
+    active_side: Side,
+    ignored_scroll: Option<Side>,
+    recalculate_diffs_tx: mpsc::UnboundedSender<()>,
+    _recalculate_diffs_task: Task<Result<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct DiffSide {
+    buffer: Model<Buffer>,
+    editor: View<Editor>,
+    /// This side's buffer, diffed against the other side's text.
+    change_set: Model<BufferChangeSet>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Old,
+    New,
+}
+
+impl Side {
+    fn other(self) -> Self {
+        match self {
+            Side::Old => Side::New,
+            Side::New => Side::Old,
+        }
+    }
+}
+
+impl FileDiffView {
+    /// Opens a comparison of two files in the active pane.
+    ///
+    /// Files outside the project are added to it as single-file worktrees, which are `visible`
+    /// in the project panel if requested.
+    pub fn open(
+        old_path: PathBuf,
+        new_path: PathBuf,
+        visible: bool,
+        workspace: &Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Task<Result<View<Self>>> {
+        let project = workspace.project().clone();
+        let old_project_path =
+            Workspace::project_path_for_path(project.clone(), &old_path, visible, cx);
+        let new_project_path =
+            Workspace::project_path_for_path(project.clone(), &new_path, visible, cx);
+        cx.spawn(|workspace, mut cx| async move {
+            let (_, old_project_path) = old_project_path.await?;
+            let (_, new_project_path) = new_project_path.await?;
+            let old_buffer = open_buffer(&project, old_project_path, &mut cx).await?;
+            let new_buffer = open_buffer(&project, new_project_path, &mut cx).await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let view = cx.new_view(|cx| {
+                    Self::new(old_path, new_path, old_buffer, new_buffer, project, cx)
+                });
+                workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, cx);
+                view
+            })
+        })
+    }
+
+    fn new(
+        old_path: PathBuf,
+        new_path: PathBuf,
+        old_buffer: Model<Buffer>,
+        new_buffer: Model<Buffer>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let old_side = DiffSide::new(old_buffer.clone(), &new_buffer, project.clone(), cx);
+        let new_side = DiffSide::new(new_buffer.clone(), &old_buffer, project, cx);
+
+        let (recalculate_diffs_tx, mut recalculate_diffs_rx) = mpsc::unbounded();
+        let recalculate_diffs_task = cx.spawn(|this, mut cx| async move {
+            while recalculate_diffs_rx.next().await.is_some() {
+                // Debounce, so that typing doesn't recompute the diffs on every keystroke.
+                cx.background_executor()
+                    .timer(Duration::from_millis(50))
+                    .await;
+                while let Ok(Some(())) = recalculate_diffs_rx.try_next() {}
+
+                let (old_diff, new_diff) = this.update(&mut cx, |this, cx| {
+                    (
+                        this.old_side.recalculate_diff(&this.new_side.buffer, cx),
+                        this.new_side.recalculate_diff(&this.old_side.buffer, cx),
+                    )
+                })?;
+                old_diff.await.ok();
+                new_diff.await.ok();
+            }
+            Ok(())
+        });
+
+        let mut subscriptions = Vec::new();
+        for (side, diff_side) in [(Side::Old, &old_side), (Side::New, &new_side)] {
+            subscriptions.push(cx.subscribe(
+                &diff_side.buffer,
+                |this, _, event: &BufferEvent, cx| match event {
+                    BufferEvent::Edited
+                    | BufferEvent::Reloaded
+                    | BufferEvent::DirtyChanged
+                    | BufferEvent::Saved => {
+                        if matches!(event, BufferEvent::Edited | BufferEvent::Reloaded) {
+                            this.recalculate_diffs_tx.unbounded_send(()).ok();
+                        }
+                        cx.emit(EditorEvent::TitleChanged);
+                    }
+                    BufferEvent::FileHandleChanged => cx.emit(EditorEvent::TitleChanged),
+                    _ => {}
+                },
+            ));
+            subscriptions.push(cx.subscribe(
+                &diff_side.editor,
+                move |this, _, event: &EditorEvent, cx| match event {
+                    EditorEvent::Focused => this.active_side = side,
+                    EditorEvent::ScrollPositionChanged { local: true, .. } => {
+                        this.sync_scroll_position(side, cx)
+                    }
+                    _ => {}
+                },
+            ));
+        }
+
+        Self {
+            old_path,
+            new_path,
+            old_side,
+            new_side,
+            active_side: Side::New,
+            ignored_scroll: None,
+            recalculate_diffs_tx,
+            _recalculate_diffs_task: recalculate_diffs_task,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn side(&self, side: Side) -> &DiffSide {
+        match side {
+            Side::Old => &self.old_side,
+            Side::New => &self.new_side,
+        }
+    }
+
+    fn active_editor(&self) -> &View<Editor> {
+        &self.side(self.active_side).editor
+    }
+
+    fn buffers(&self) -> HashSet<Model<Buffer>> {
+        HashSet::from_iter([self.old_side.buffer.clone(), self.new_side.buffer.clone()])
+    }
+
+    /// Scrolls the other side so that the rows corresponding to the top of `source` are visible.
+    fn sync_scroll_position(&mut self, source: Side, cx: &mut ViewContext<Self>) {
+        if self.ignored_scroll == Some(source) {
+            self.ignored_scroll = None;
+            return;
+        }
+
+        let source_side = self.side(source);
+        let target_side = self.side(source.other());
+        let source_snapshot = source_side.buffer.read(cx).snapshot();
+        let target_snapshot = target_side.buffer.read(cx).snapshot();
+        let hunks = source_side
+            .change_set
+            .read(cx)
+            .diff_to_buffer
+            .hunks_in_row_range(0..u32::MAX, &source_snapshot)
+            .map(|hunk| {
+                let base_start = hunk.diff_base_byte_range.start.min(target_snapshot.len());
+                let base_end = hunk.diff_base_byte_range.end.min(target_snapshot.len());
+                let target_rows = target_snapshot.offset_to_point(base_start).row
+                    ..target_snapshot.offset_to_point(base_end).row;
+                (hunk.row_range, target_rows)
+            })
+            .collect::<Vec<_>>();
+
+        let source_row = source_side.editor.update(cx, |editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let scroll_top = editor.scroll_position(cx).y;
+            let point = DisplayPoint::new(DisplayRow(scroll_top as u32), 0).to_point(&snapshot);
+            point.row as f32 + scroll_top.fract()
+        });
+        let target_row = map_row(&hunks, source_row);
+
+        let target_editor = target_side.editor.clone();
+        let target = source.other();
+        target_editor.update(cx, |editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let max_row = snapshot.buffer_snapshot.max_point().row;
+            let target_row = target_row.min(max_row as f32);
+            let display_row = Point::new(target_row as u32, 0)
+                .to_display_point(&snapshot)
+                .row()
+                .as_f32()
+                + target_row.fract();
+            let scroll_position = editor.scroll_position(cx);
+            if (scroll_position.y - display_row).abs() > 0.01 {
+                self.ignored_scroll = Some(target);
+                editor.set_scroll_position(point(scroll_position.x, display_row), cx);
+            }
+        });
+    }
+
+    fn go_to_hunk(&mut self, action: &dyn Action, cx: &mut ViewContext<Self>) {
+        let focus_handle = self.active_editor().focus_handle(cx);
+        cx.focus(&focus_handle);
+        focus_handle.dispatch_action(action, cx);
+    }
+
+    fn render_side_header(&self, side: Side, cx: &ViewContext<Self>) -> impl IntoElement {
+        let path = match side {
+            Side::Old => &self.old_path,
+            Side::New => &self.new_path,
+        };
+        let is_dirty = self.side(side).buffer.read(cx).is_dirty();
+        h_flex()
+            .flex_1()
+            .min_w_0()
+            .gap_1()
+            .child(
+                Label::new(path.to_string_lossy().to_string())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .single_line()
+                    .text_ellipsis(),
+            )
+            .when(is_dirty, |this| {
+                this.child(Indicator::dot().color(Color::Info))
+            })
+    }
+}
+
+impl DiffSide {
+    fn new(
+        buffer: Model<Buffer>,
+        other_buffer: &Model<Buffer>,
+        project: Model<Project>,
+        cx: &mut ViewContext<FileDiffView>,
+    ) -> Self {
+        let snapshot = buffer.read(cx).text_snapshot();
+        let base_text = other_buffer.read(cx).text();
+        let change_set = cx.new_model(|cx| {
+            let mut change_set = BufferChangeSet::new(&snapshot);
+            let _ = change_set.set_base_text(base_text, snapshot, cx);
+            change_set
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), Some(project), cx);
+            editor.add_change_set(change_set.clone(), cx);
+            editor
+        });
+        Self {
+            buffer,
+            editor,
+            change_set,
+        }
+    }
+
+    fn recalculate_diff(
+        &self,
+        other_buffer: &Model<Buffer>,
+        cx: &mut AppContext,
+    ) -> futures::channel::oneshot::Receiver<()> {
+        let base_text = other_buffer.read(cx).text();
+        let snapshot = self.buffer.read(cx).text_snapshot();
+        self.change_set.update(cx, |change_set, cx| {
+            change_set.set_base_text(base_text, snapshot, cx)
+        })
+    }
+}
+
+async fn open_buffer(
+    project: &Model<Project>,
+    project_path: ProjectPath,
+    cx: &mut AsyncWindowContext,
+) -> Result<Model<Buffer>> {
+    project
+        .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+        .await
+}
+
+/// Maps a (possibly fractional) row on one side of the diff to the other side, given the hunks
+/// as pairs of row ranges on this side and on the other side.
+///
+/// Rows between hunks are offset by the size difference of the preceding hunks, while rows
+/// inside a hunk are mapped proportionally onto the other side's range.
+fn map_row(hunks: &[(Range<u32>, Range<u32>)], row: f32) -> f32 {
+    let mut source_end = 0;
+    let mut target_end = 0;
+    for (source, target) in hunks {
+        if row < source.start as f32 {
+            break;
+        }
+        if row < source.end as f32 {
+            let progress = (row - source.start as f32) / source.len() as f32;
+            return target.start as f32 + progress * target.len() as f32;
+        }
+        source_end = source.end;
+        target_end = target.end;
+    }
+    target_end as f32 + (row - source_end as f32)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .to_string()
+}
+
+impl Render for FileDiffView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border_variant;
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_3()
+                    .py_1()
+                    .gap_2()
+                    .border_b_1()
+                    .border_color(border_color)
+                    .child(self.render_side_header(Side::Old, cx))
+                    .child(self.render_side_header(Side::New, cx))
+                    .child(
+                        IconButton::new("previous-hunk", IconName::ArrowUp)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Previous Hunk", &GoToPrevHunk, cx))
+                            .on_click(
+                                cx.listener(|this, _, cx| this.go_to_hunk(&GoToPrevHunk, cx)),
+                            ),
+                    )
+                    .child(
+                        IconButton::new("next-hunk", IconName::ArrowDown)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::for_action("Next Hunk", &GoToHunk, cx))
+                            .on_click(cx.listener(|this, _, cx| this.go_to_hunk(&GoToHunk, cx))),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .w_full()
+                    .child(
+                        div()
+                            .flex_1()
+                            .h_full()
+                            .min_w_0()
+                            .border_r_1()
+                            .border_color(border_color)
+                            .child(self.old_side.editor.clone()),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .h_full()
+                            .min_w_0()
+                            .child(self.new_side.editor.clone()),
+                    ),
+            )
+    }
+}
+
+impl FocusableView for FileDiffView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.active_editor().focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for FileDiffView {}
+
+impl Item for FileDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _cx: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff))
+    }
+
+    fn tab_content_text(&self, _cx: &WindowContext) -> Option<SharedString> {
+        Some(
+            format!(
+                "{} ↔ {}",
+                file_name(&self.old_path),
+                file_name(&self.new_path)
+            )
+            .into(),
+        )
+    }
+
+    fn tab_tooltip_text(&self, _cx: &AppContext) -> Option<SharedString> {
+        Some(format!("{} ↔ {}", self.old_path.display(), self.new_path.display()).into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.old_side.buffer.read(cx).is_dirty() || self.new_side.buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.old_side.buffer.read(cx).has_conflict() || self.new_side.buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let dirty_buffers = self
+            .buffers()
+            .into_iter()
+            .filter(|buffer| buffer.read(cx).is_dirty())
+            .collect();
+        project.update(cx, |project, cx| project.save_buffers(dirty_buffers, cx))
+    }
+
+    fn save_as(
+        &mut self,
+        _project: Model<Project>,
+        _path: ProjectPath,
+        _cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        Task::ready(Err(anyhow!(
+            "cannot save a file comparison under a new path"
+        )))
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let reload = project.update(cx, |project, cx| {
+            project.reload_buffers(self.buffers(), true, cx)
+        });
+        cx.background_executor().spawn(async move {
+            reload.await?;
+            Ok(())
+        })
+    }
+
+    fn as_searchable(&self, _: &View<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.active_editor().clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.active_editor().to_any())
+        } else {
+            None
+        }
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.old_side.editor.update(cx, Item::deactivated);
+        self.new_side.editor.update(cx, Item::deactivated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_file_diff_view(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "old.txt": "one\ntwo\nthree\nfour\n",
+                "new.txt": "one\nTWO\nthree\nfour\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let view = workspace
+            .update(cx, |workspace, cx| {
+                FileDiffView::open(
+                    "/root/old.txt".into(),
+                    "/root/new.txt".into(),
+                    false,
+                    workspace,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(hunk_rows(&view, Side::Old, cx), [1..2]);
+        assert_eq!(hunk_rows(&view, Side::New, cx), [1..2]);
+
+        // Editing either side recomputes the hunks of both sides.
+        let new_buffer = view.update(cx, |view, _| view.new_side.buffer.clone());
+        new_buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 0)..Point::new(1, 3), "two")], None, cx);
+            buffer.edit([(Point::new(4, 0)..Point::new(4, 0), "five\n")], None, cx);
+        });
+        cx.executor().advance_clock(Duration::from_millis(50));
+        cx.run_until_parked();
+        assert_eq!(hunk_rows(&view, Side::Old, cx), [4..4]);
+        assert_eq!(hunk_rows(&view, Side::New, cx), [4..5]);
+        assert!(view.update(cx, |view, cx| view.is_dirty(cx)));
+
+        // Saving writes the edited side to disk.
+        view.update(cx, |view, cx| view.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        assert!(!view.update(cx, |view, cx| view.is_dirty(cx)));
+        assert_eq!(
+            fs.load("/root/new.txt".as_ref()).await.unwrap(),
+            "one\ntwo\nthree\nfour\nfive\n"
+        );
+        assert_eq!(
+            fs.load("/root/old.txt".as_ref()).await.unwrap(),
+            "one\ntwo\nthree\nfour\n"
+        );
+    }
+
+    fn hunk_rows(
+        view: &View<FileDiffView>,
+        side: Side,
+        cx: &mut VisualTestContext,
+    ) -> Vec<Range<u32>> {
+        view.update(cx, |view, cx| {
+            let side = view.side(side);
+            let snapshot = side.buffer.read(cx).snapshot();
+            side.change_set
+                .read(cx)
+                .diff_to_buffer
+                .hunks_in_row_range(0..u32::MAX, &snapshot)
+                .map(|hunk| hunk.row_range)
+                .collect()
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[test]
+    fn test_map_row() {
+        // Old side has an extra line at row 2, and rows 5..7 were replaced by a single row.
+        let hunks = [(2..3, 2..2), (5..7, 4..5)];
+
+        assert_eq!(map_row(&hunks, 0.), 0.);
+        assert_eq!(map_row(&hunks, 1.5), 1.5);
+        // Deleted rows collapse onto the point where they were removed.
+        assert_eq!(map_row(&hunks, 2.5), 2.);
+        assert_eq!(map_row(&hunks, 3.), 2.);
+        assert_eq!(map_row(&hunks, 4.), 3.);
+        // Rows inside a modified hunk map proportionally.
+        assert_eq!(map_row(&hunks, 5.), 4.);
+        assert_eq!(map_row(&hunks, 6.), 4.5);
+        assert_eq!(map_row(&hunks, 7.), 5.);
+        assert_eq!(map_row(&hunks, 10.25), 8.25);
+
+        // The inverse mapping, from the new side's point of view.
+        let hunks = [(2..2, 2..3), (4..5, 5..7)];
+        assert_eq!(map_row(&hunks, 1.), 1.);
+        assert_eq!(map_row(&hunks, 2.), 3.);
+        assert_eq!(map_row(&hunks, 4.5), 6.);
+        assert_eq!(map_row(&hunks, 8.25), 10.25);
+    }
+}
@@ -9,23 +9,29 @@ use std::{
     time::Duration,
 };
 
-use git::repository::GitFileStatus;
+use git::repository::{GitFileStatus, RepoPath, UnmergedStatus};
 
 use util::{ResultExt, TryFutureExt};
 
 use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
 use gpui::*;
-use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, WorktreeId};
+use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath, WorktreeId};
 use serde::{Deserialize, Serialize};
 use settings::Settings as _;
 use ui::{
     prelude::*, Checkbox, Divider, DividerColor, ElevationIndex, Scrollbar, ScrollbarState, Tooltip,
 };
 use workspace::dock::{DockPosition, Panel, PanelEvent};
+use workspace::notifications::DetachAndPromptErr;
 use workspace::Workspace;
 
+use crate::commit_history::{active_repository, repository_for_path};
 use crate::{git_status_icon, settings::GitPanelSettings};
-use crate::{CommitAllChanges, CommitStagedChanges, DiscardAll, StageAll, UnstageAll};
+use crate::{
+    AmendCommit, CommitAllChanges, CommitStagedChanges, DiscardAll, PopStash, StageAll,
+    StashChanges, UnstageAll,
+};
 
 actions!(git_panel, [ToggleFocus]);
 
@@ -51,6 +57,7 @@ pub struct GitStatusEntry {}
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct EntryDetails {
+    worktree_id: WorktreeId,
     filename: String,
     display_name: String,
     path: Arc<Path>,
@@ -72,7 +79,8 @@ struct SerializedGitPanel {
 }
 
 pub struct GitPanel {
-    _workspace: WeakView<Workspace>,
+    workspace: WeakView<Workspace>,
+    commit_editor: View<Editor>,
     current_modifiers: Modifiers,
     focus_handle: FocusHandle,
     fs: Arc<dyn Fs>,
@@ -84,6 +92,11 @@ pub struct GitPanel {
     selected_item: Option<usize>,
     show_scrollbar: bool,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
+    /// Worktree-relative paths of the files that are staged in the index.
+    staged_entries: HashMap<WorktreeId, HashSet<Arc<Path>>>,
+    reload_staged_entries_task: Task<()>,
+    /// Files with unresolved merge conflicts, listed above the other entries.
+    conflicted_entries: Vec<(ProjectPath, UnmergedStatus)>,
 
     // The entries that are currently shown in the panel, aka
     // not hidden by folding or such
@@ -125,16 +138,27 @@ impl GitPanel {
                 | project::Event::WorktreeAdded(_)
                 | project::Event::WorktreeOrderChanged => {
                     this.update_visible_entries(None, cx);
+                    this.reload_staged_entries(cx);
                     cx.notify();
                 }
+                project::Event::WorktreeUpdatedGitRepositories(_) => {
+                    this.reload_staged_entries(cx);
+                }
                 _ => {}
             })
             .detach();
 
             let scroll_handle = UniformListScrollHandle::new();
 
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(6, cx);
+                editor.set_placeholder_text("Add a message", cx);
+                editor
+            });
+
             let mut this = Self {
-                _workspace: weak_workspace,
+                workspace: weak_workspace,
+                commit_editor,
                 focus_handle: cx.focus_handle(),
                 fs,
                 pending_serialization: Task::ready(None),
@@ -142,6 +166,9 @@ impl GitPanel {
                 visible_entries: Vec::new(),
                 current_modifiers: cx.modifiers(),
                 expanded_dir_ids: Default::default(),
+                staged_entries: Default::default(),
+                reload_staged_entries_task: Task::ready(()),
+                conflicted_entries: Vec::new(),
 
                 width: Some(px(360.)),
                 scrollbar_state: ScrollbarState::new(scroll_handle.clone()).parent_view(cx.view()),
@@ -151,6 +178,7 @@ impl GitPanel {
                 hide_scrollbar_task: None,
             };
             this.update_visible_entries(None, cx);
+            this.reload_staged_entries(cx);
             this
         });
 
@@ -254,14 +282,104 @@ impl GitPanel {
 }
 
 impl GitPanel {
-    fn stage_all(&mut self, _: &StageAll, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement stage all
-        println!("Stage all triggered");
+    fn stage_all(&mut self, _: &StageAll, cx: &mut ViewContext<Self>) {
+        let changes = self.changed_paths_by_repository(cx);
+        let project = self.project.clone();
+        let task = cx.spawn(|this, mut cx| async move {
+            for (repository, paths) in changes {
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.stage_paths(repository, paths, cx)
+                    })?
+                    .await?;
+            }
+            this.update(&mut cx, |this, cx| this.reload_staged_entries(cx))
+        });
+        task.detach_and_prompt_err("Failed to stage changes", cx, |_, _| None);
     }
 
-    fn unstage_all(&mut self, _: &UnstageAll, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement unstage all
-        println!("Unstage all triggered");
+    fn unstage_all(&mut self, _: &UnstageAll, cx: &mut ViewContext<Self>) {
+        let mut changes = HashMap::<ProjectPath, Vec<RepoPath>>::default();
+        for (worktree_id, paths) in &self.staged_entries {
+            for path in paths {
+                if let Some((repository, repo_path)) =
+                    repository_for_path(self.project.read(cx), *worktree_id, path, cx)
+                {
+                    changes.entry(repository).or_default().push(repo_path);
+                }
+            }
+        }
+
+        let project = self.project.clone();
+        let task = cx.spawn(|this, mut cx| async move {
+            for (repository, paths) in changes {
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.unstage_paths(repository, paths, cx)
+                    })?
+                    .await?;
+            }
+            this.update(&mut cx, |this, cx| this.reload_staged_entries(cx))
+        });
+        task.detach_and_prompt_err("Failed to unstage changes", cx, |_, _| None);
+    }
+
+    fn toggle_staged_for_entry(
+        &mut self,
+        worktree_id: WorktreeId,
+        path: Arc<Path>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.project.read(cx).is_read_only(cx) {
+            return;
+        }
+        let Some((repository, repo_path)) =
+            repository_for_path(self.project.read(cx), worktree_id, &path, cx)
+        else {
+            return;
+        };
+
+        let is_staged = self.is_staged(worktree_id, &path);
+        let task = self.project.update(cx, |project, cx| {
+            if is_staged {
+                project.unstage_paths(repository, vec![repo_path], cx)
+            } else {
+                project.stage_paths(repository, vec![repo_path], cx)
+            }
+        });
+        let task = cx.spawn(|this, mut cx| async move {
+            task.await?;
+            this.update(&mut cx, |this, cx| this.reload_staged_entries(cx))
+        });
+        task.detach_and_prompt_err("Failed to update the index", cx, |_, _| None);
+    }
+
+    fn stash_changes(&mut self, _: &StashChanges, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let task = self
+            .project
+            .update(cx, |project, cx| project.stash(repository, None, cx));
+        let task = cx.spawn(|this, mut cx| async move {
+            task.await?;
+            this.update(&mut cx, |this, cx| this.reload_staged_entries(cx))
+        });
+        task.detach_and_prompt_err("Failed to stash changes", cx, |_, _| None);
+    }
+
+    fn pop_stash(&mut self, _: &PopStash, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.active_repository(cx) else {
+            return;
+        };
+        let task = self
+            .project
+            .update(cx, |project, cx| project.stash_pop(repository, cx));
+        let task = cx.spawn(|this, mut cx| async move {
+            task.await?;
+            this.update(&mut cx, |this, cx| this.reload_staged_entries(cx))
+        });
+        task.detach_and_prompt_err("Failed to pop stash", cx, |_, _| None);
     }
 
     fn discard_all(&mut self, _: &DiscardAll, _cx: &mut ViewContext<Self>) {
@@ -270,20 +388,184 @@ impl GitPanel {
     }
 
     /// Commit all staged changes
-    fn commit_staged_changes(&mut self, _: &CommitStagedChanges, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement commit all staged
-        println!("Commit staged changes triggered");
+    fn commit_staged_changes(&mut self, _: &CommitStagedChanges, cx: &mut ViewContext<Self>) {
+        let repositories = self.repositories_with_staged_entries(cx);
+        self.commit(repositories, Vec::new(), false, cx);
     }
 
     /// Commit all changes, regardless of whether they are staged or not
-    fn commit_all_changes(&mut self, _: &CommitAllChanges, _cx: &mut ViewContext<Self>) {
-        // TODO: Implement commit all changes
-        println!("Commit all changes triggered");
+    fn commit_all_changes(&mut self, _: &CommitAllChanges, cx: &mut ViewContext<Self>) {
+        let changes = self.changed_paths_by_repository(cx);
+        let repositories = changes
+            .iter()
+            .map(|(repository, _)| repository.clone())
+            .chain(self.repositories_with_staged_entries(cx))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        self.commit(repositories, changes, false, cx);
+    }
+
+    /// Amend the last commit with the staged changes, using the message in the commit editor
+    fn amend_commit(&mut self, _: &AmendCommit, cx: &mut ViewContext<Self>) {
+        let repositories = self.active_repository(cx).into_iter().collect();
+        self.commit(repositories, Vec::new(), true, cx);
+    }
+
+    fn commit(
+        &mut self,
+        repositories: Vec<ProjectPath>,
+        paths_to_stage: Vec<(ProjectPath, Vec<RepoPath>)>,
+        amend: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let message = self.commit_editor.read(cx).text(cx);
+        if message.trim().is_empty() || repositories.is_empty() {
+            return;
+        }
+
+        let project = self.project.clone();
+        let task = cx.spawn(|this, mut cx| async move {
+            for (repository, paths) in paths_to_stage {
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.stage_paths(repository, paths, cx)
+                    })?
+                    .await?;
+            }
+            for repository in repositories {
+                project
+                    .update(&mut cx, |project, cx| {
+                        project.commit(repository, message.clone(), amend, cx)
+                    })?
+                    .await?;
+            }
+            this.update(&mut cx, |this, cx| {
+                this.commit_editor.update(cx, |editor, cx| editor.clear(cx));
+                this.reload_staged_entries(cx);
+            })
+        });
+        task.detach_and_prompt_err("Failed to commit", cx, |_, _| None);
+    }
+
+    fn is_staged(&self, worktree_id: WorktreeId, path: &Path) -> bool {
+        self.staged_entries
+            .get(&worktree_id)
+            .map_or(false, |paths| paths.contains(path))
     }
 
     fn all_staged(&self) -> bool {
-        // TODO: Implement all_staged
-        true
+        let mut changed_files = self
+            .visible_entries
+            .iter()
+            .flat_map(|(worktree_id, entries, _)| {
+                entries
+                    .iter()
+                    .filter(|entry| entry.is_file() && entry.git_status.is_some())
+                    .map(move |entry| (*worktree_id, entry))
+            })
+            .peekable();
+        changed_files.peek().is_some()
+            && changed_files.all(|(worktree_id, entry)| self.is_staged(worktree_id, &entry.path))
+    }
+
+    /// The work directories of every git repository in the visible worktrees.
+    fn repositories(&self, cx: &AppContext) -> Vec<ProjectPath> {
+        self.project
+            .read(cx)
+            .visible_worktrees(cx)
+            .flat_map(|worktree| {
+                let snapshot = worktree.read(cx).snapshot();
+                let worktree_id = snapshot.id();
+                snapshot
+                    .repositories()
+                    .map(|(work_directory, _)| ProjectPath {
+                        worktree_id,
+                        path: work_directory.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn repositories_with_staged_entries(&self, cx: &AppContext) -> Vec<ProjectPath> {
+        self.repositories(cx)
+            .into_iter()
+            .filter(|repository| {
+                self.staged_entries
+                    .get(&repository.worktree_id)
+                    .map_or(false, |paths| {
+                        paths.iter().any(|path| path.starts_with(&repository.path))
+                    })
+            })
+            .collect()
+    }
+
+    /// The repository that actions on a single repository, like amending or stashing, apply to.
+    fn active_repository(&self, cx: &AppContext) -> Option<ProjectPath> {
+        let workspace = self.workspace.upgrade()?;
+        active_repository(workspace.read(cx), cx)
+    }
+
+    fn changed_paths_by_repository(&self, cx: &AppContext) -> Vec<(ProjectPath, Vec<RepoPath>)> {
+        let mut changes = HashMap::<ProjectPath, Vec<RepoPath>>::default();
+        for (worktree_id, entries, _) in &self.visible_entries {
+            for entry in entries {
+                if !entry.is_file() || entry.git_status.is_none() {
+                    continue;
+                }
+                if let Some((repository, repo_path)) =
+                    repository_for_path(self.project.read(cx), *worktree_id, &entry.path, cx)
+                {
+                    changes.entry(repository).or_default().push(repo_path);
+                }
+            }
+        }
+        changes.into_iter().collect()
+    }
+
+    fn reload_staged_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let project = self.project.read(cx);
+        let staged_paths = self
+            .repositories(cx)
+            .into_iter()
+            .map(|repository| {
+                let task = project.staged_paths(repository.clone(), cx);
+                async move { (repository, task.await) }
+            })
+            .collect::<Vec<_>>();
+
+        self.reload_staged_entries_task = cx.spawn(|this, mut cx| async move {
+            let mut staged_entries = HashMap::<WorktreeId, HashSet<Arc<Path>>>::default();
+            for (repository, paths) in futures::future::join_all(staged_paths).await {
+                let Some(paths) = paths.log_err() else {
+                    continue;
+                };
+                staged_entries
+                    .entry(repository.worktree_id)
+                    .or_default()
+                    .extend(
+                        paths
+                            .into_iter()
+                            .map(|path| Arc::from(repository.path.join(path.as_path()))),
+                    );
+            }
+            this.update(&mut cx, |this, cx| {
+                this.staged_entries = staged_entries;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn open_conflicted_file(&mut self, path: ProjectPath, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(path, None, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
     }
 
     fn no_entries(&self) -> bool {
@@ -363,6 +645,7 @@ impl GitPanel {
                     let display_name = entry.path.to_string_lossy().into_owned();
 
                     let details = EntryDetails {
+                        worktree_id: *worktree_id,
                         filename,
                         display_name,
                         kind: entry.kind,
@@ -386,6 +669,7 @@ impl GitPanel {
     ) {
         let project = self.project.read(cx);
         self.visible_entries.clear();
+        self.conflicted_entries.clear();
         for worktree in project.visible_worktrees(cx) {
             let snapshot = worktree.read(cx).snapshot();
             let worktree_id = snapshot.id();
@@ -402,6 +686,21 @@ impl GitPanel {
 
             snapshot.propagate_git_statuses(&mut visible_worktree_entries);
             project::sort_worktree_entries(&mut visible_worktree_entries);
+            self.conflicted_entries.extend(
+                visible_worktree_entries
+                    .iter()
+                    .filter(|entry| entry.is_file())
+                    .filter_map(|entry| match entry.git_status {
+                        Some(GitFileStatus::Conflict(status)) => Some((
+                            ProjectPath {
+                                worktree_id,
+                                path: entry.path.clone(),
+                            },
+                            status,
+                        )),
+                        _ => None,
+                    }),
+            );
 
             if !visible_worktree_entries.is_empty() {
                 self.visible_entries
@@ -455,6 +754,7 @@ impl GitPanel {
         let focus_handle = self.focus_handle(cx).clone();
 
         let changes_string = format!("{} changes", self.entry_count());
+        let all_staged = self.all_staged();
 
         h_flex()
             .h(px(32.))
@@ -464,7 +764,17 @@ impl GitPanel {
             .child(
                 h_flex()
                     .gap_2()
-                    .child(Checkbox::new("all-changes", true.into()).disabled(true))
+                    .child(
+                        Checkbox::new("all-changes", all_staged.into())
+                            .disabled(self.no_entries())
+                            .on_click(cx.listener(move |this, _, cx| {
+                                if all_staged {
+                                    this.unstage_all(&UnstageAll, cx)
+                                } else {
+                                    this.stage_all(&StageAll, cx)
+                                }
+                            })),
+                    )
                     .child(div().text_buffer(cx).text_ui_sm(cx).child(changes_string)),
             )
             .child(div().flex_grow())
@@ -486,9 +796,9 @@ impl GitPanel {
                             .icon_size(IconSize::Small)
                             .disabled(true),
                     )
-                    .child(if self.all_staged() {
+                    .child(if all_staged {
                         self.panel_button("unstage-all", "Unstage All").on_click(
-                            cx.listener(move |_, _, cx| cx.dispatch_action(Box::new(DiscardAll))),
+                            cx.listener(move |_, _, cx| cx.dispatch_action(Box::new(UnstageAll))),
                         )
                     } else {
                         self.panel_button("stage-all", "Stage All").on_click(
@@ -540,8 +850,7 @@ impl GitPanel {
                 .bg(cx.theme().colors().editor_background)
                 .font_buffer(cx)
                 .text_ui_sm(cx)
-                .text_color(cx.theme().colors().text_muted)
-                .child("Add a message")
+                .child(self.commit_editor.clone())
                 .gap_1()
                 .child(div().flex_grow())
                 .child(h_flex().child(div().gap_1().flex_grow()).child(
@@ -550,9 +859,7 @@ impl GitPanel {
                     } else {
                         commit_staged_button
                     },
-                ))
-                .cursor(CursorStyle::OperationNotAllowed)
-                .opacity(0.5),
+                )),
         )
     }
 
@@ -650,6 +957,56 @@ impl GitPanel {
             .children(self.render_scrollbar(cx))
     }
 
+    fn render_conflicts(&self, cx: &ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .w_full()
+            .child(
+                h_flex()
+                    .h(px(28.))
+                    .px(px(12.))
+                    .gap_2()
+                    .child(
+                        Label::new("Conflicts")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(self.conflicted_entries.len().to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .children(
+                self.conflicted_entries
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, (path, status))| {
+                        let project_path = path.clone();
+                        h_flex()
+                            .id(("conflict", ix))
+                            .h(px(28.))
+                            .w_full()
+                            .px(px(12.))
+                            .items_center()
+                            .gap_2()
+                            .text_ui_sm(cx)
+                            .cursor_pointer()
+                            .hover(|style| style.bg(cx.theme().colors().ghost_element_hover))
+                            .child(git_status_icon(GitFileStatus::Conflict(*status)))
+                            .child(path.path.to_string_lossy().into_owned())
+                            .child(
+                                Label::new(status.label())
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .tooltip(|cx| Tooltip::text("Open File to Resolve Conflicts", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.open_conflicted_file(project_path.clone(), cx)
+                            }))
+                    }),
+            )
+    }
+
     fn render_entry(
         &self,
         id: ProjectEntryId,
@@ -658,7 +1015,9 @@ impl GitPanel {
     ) -> impl IntoElement {
         let id = id.to_proto() as usize;
         let checkbox_id = ElementId::Name(format!("checkbox_{}", id).into());
-        let is_staged = ToggleState::Selected;
+        let is_staged: ToggleState = self.is_staged(details.worktree_id, &details.path).into();
+        let worktree_id = details.worktree_id;
+        let path = details.path.clone();
 
         h_flex()
             .id(id)
@@ -671,7 +1030,9 @@ impl GitPanel {
             .font_buffer(cx)
             .text_ui_sm(cx)
             .when(!details.is_dir(), |this| {
-                this.child(Checkbox::new(checkbox_id, is_staged))
+                this.child(Checkbox::new(checkbox_id, is_staged).on_click(cx.listener(
+                    move |this, _, cx| this.toggle_staged_for_entry(worktree_id, path.clone(), cx),
+                )))
             })
             .when_some(details.status, |this, status| {
                 this.child(git_status_icon(status))
@@ -703,6 +1064,9 @@ impl Render for GitPanel {
                     .on_action(cx.listener(|this, &CommitAllChanges, cx| {
                         this.commit_all_changes(&CommitAllChanges, cx)
                     }))
+                    .on_action(cx.listener(Self::amend_commit))
+                    .on_action(cx.listener(Self::stash_changes))
+                    .on_action(cx.listener(Self::pop_stash))
             })
             .on_hover(cx.listener(|this, hovered, cx| {
                 if *hovered {
@@ -720,6 +1084,10 @@ impl Render for GitPanel {
             .bg(ElevationIndex::Surface.bg(cx))
             .child(self.render_panel_header(cx))
             .child(self.render_divider(cx))
+            .when(!self.conflicted_entries.is_empty(), |this| {
+                this.child(self.render_conflicts(cx))
+                    .child(self.render_divider(cx))
+            })
             .child(if !self.no_entries() {
                 self.render_entries(cx).into_any_element()
             } else {
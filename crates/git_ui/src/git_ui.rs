@@ -4,6 +4,9 @@ use gpui::{actions, AppContext, Hsla};
 use settings::GitPanelSettings;
 use ui::{Color, Icon, IconName, IntoElement};
 
+pub mod commit_history;
+pub mod commit_view;
+pub mod file_diff_view;
 pub mod git_panel;
 mod settings;
 
@@ -14,12 +17,18 @@ actions!(
         UnstageAll,
         DiscardAll,
         CommitStagedChanges,
-        CommitAllChanges
+        CommitAllChanges,
+        AmendCommit,
+        StashChanges,
+        PopStash,
+        ViewHistory,
+        ViewFileHistory
     ]
 );
 
 pub fn init(cx: &mut AppContext) {
     GitPanelSettings::register(cx);
+    commit_history::init(cx);
 }
 
 const ADDED_COLOR: Hsla = Hsla {
@@ -48,6 +57,8 @@ pub fn git_status_icon(status: GitFileStatus) -> impl IntoElement {
         GitFileStatus::Modified => {
             Icon::new(IconName::SquareDot).color(Color::Custom(MODIFIED_COLOR))
         }
-        GitFileStatus::Conflict => Icon::new(IconName::Warning).color(Color::Custom(REMOVED_COLOR)),
+        GitFileStatus::Conflict(_) => {
+            Icon::new(IconName::Warning).color(Color::Custom(REMOVED_COLOR))
+        }
     }
 }
@@ -1,15 +1,25 @@
 use crate::{
-    Action, AnyView, AnyWindowHandle, AppCell, AppContext, AsyncAppContext, AvailableSpace,
-    BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem, Context, DrawPhase, Drawable,
-    Element, Empty, Entity, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Model,
-    ModelContext, Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent,
-    MouseUpEvent, Pixels, Platform, Point, Render, Result, Size, Task, TestDispatcher,
-    TestPlatform, TestScreenCaptureSource, TestWindow, TextSystem, View, ViewContext,
-    VisualContext, WindowBounds, WindowContext, WindowHandle, WindowOptions,
+    point, rasterize_scene, Action, AnyView, AnyWindowHandle, AppCell, AppContext, AsyncAppContext,
+    AvailableSpace, BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem, Context,
+    DevicePixels, DrawPhase, Drawable, Element, Empty, Entity, EventEmitter, ForegroundExecutor,
+    Global, InputEvent, Keystroke, Model, ModelContext, Modifiers, ModifiersChangedEvent,
+    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Platform, Point, Render,
+    Result, Size, Task, TestDispatcher, TestPlatform, TestScreenCaptureSource, TestWindow,
+    TextSystem, View, ViewContext, VisualContext, WindowBounds, WindowContext, WindowHandle,
+    WindowOptions,
 };
 use anyhow::{anyhow, bail};
 use futures::{channel::oneshot, Stream, StreamExt};
-use std::{cell::RefCell, future::Future, ops::Deref, rc::Rc, sync::Arc, time::Duration};
+use image::RgbaImage;
+use std::{
+    cell::RefCell,
+    future::Future,
+    ops::Deref,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+    time::Duration,
+};
 
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
 /// an implementation of `Context` with additional methods that are useful in tests.
@@ -243,6 +253,39 @@ impl TestAppContext {
         (view, cx)
     }
 
+    /// Opens a window of the given size containing the view built by `build_root_view`, and
+    /// asserts that it renders like the reference image at `reference_path`.
+    ///
+    /// See [`VisualTestContext::assert_matches_snapshot`] for how references are recorded.
+    pub fn assert_view_snapshot<F, V>(
+        &mut self,
+        size: Size<Pixels>,
+        reference_path: impl AsRef<Path>,
+        tolerance: SnapshotTolerance,
+        build_root_view: F,
+    ) where
+        F: FnOnce(&mut ViewContext<V>) -> V,
+        V: 'static + Render,
+    {
+        let window = self
+            .app
+            .borrow_mut()
+            .open_window(
+                WindowOptions {
+                    window_bounds: Some(WindowBounds::Windowed(Bounds::new(
+                        point(Pixels::ZERO, Pixels::ZERO),
+                        size,
+                    ))),
+                    ..Default::default()
+                },
+                |cx| cx.new_view(build_root_view),
+            )
+            .unwrap();
+        let mut cx = VisualTestContext::from_window(*window.deref(), self);
+        cx.run_until_parked();
+        cx.assert_matches_snapshot(reference_path, tolerance);
+    }
+
     /// returns the TextSystem
     pub fn text_system(&self) -> &Arc<TextSystem> {
         &self.text_system
@@ -826,6 +869,52 @@ impl VisualTestContext {
         self.background_executor.run_until_parked();
     }
 
+    /// Draws the window and rasterizes the resulting scene on the CPU, at the window's scale
+    /// factor.
+    ///
+    /// Text and icons are rasterized by the platform's text system, so they can differ slightly
+    /// from one machine to another.
+    pub fn render_to_image(&mut self) -> RgbaImage {
+        self.update(|cx| {
+            cx.draw();
+            let size = cx
+                .window
+                .viewport_size
+                .scale(cx.scale_factor())
+                .map(|dimension| DevicePixels(dimension.0.ceil() as i32));
+            let atlas = cx
+                .window
+                .platform_window
+                .as_test()
+                .unwrap()
+                .0
+                .lock()
+                .sprite_atlas
+                .clone();
+            rasterize_scene(&cx.window.rendered_frame.scene, size, &atlas)
+        })
+    }
+
+    /// Asserts that the window renders like the PNG at `reference_path`, within `tolerance`.
+    ///
+    /// When the reference doesn't exist, or the `UPDATE_SNAPSHOTS` environment variable is set,
+    /// the current rendering is written to `reference_path` instead. A missing reference still
+    /// fails the test, so that it gets checked in. On a mismatch, the rendering is written next to
+    /// the reference with an `.actual.png` extension.
+    pub fn assert_matches_snapshot(
+        &mut self,
+        reference_path: impl AsRef<Path>,
+        tolerance: SnapshotTolerance,
+    ) {
+        let actual = self.render_to_image();
+        let update = std::env::var("UPDATE_SNAPSHOTS").map_or(false, |value| !value.is_empty());
+        if let Err(error) =
+            compare_with_reference(&actual, reference_path.as_ref(), tolerance, update)
+        {
+            panic!("{error}");
+        }
+    }
+
     /// Simulates the user closing the window.
     /// Returns true if the window was closed.
     pub fn simulate_close(&mut self) -> bool {
@@ -870,6 +959,100 @@ impl VisualTestContext {
     }
 }
 
+/// How much a rendering may differ from its reference image in
+/// [`VisualTestContext::assert_matches_snapshot`].
+#[derive(Clone, Copy, Debug)]
+pub struct SnapshotTolerance {
+    /// The largest difference, out of 255, allowed in any channel of a matching pixel.
+    pub max_channel_difference: u8,
+    /// The fraction of pixels, between 0 and 1, that may differ by more than
+    /// `max_channel_difference`.
+    pub max_differing_pixels: f32,
+}
+
+impl SnapshotTolerance {
+    /// Requires every pixel to match exactly.
+    pub const EXACT: Self = Self {
+        max_channel_difference: 0,
+        max_differing_pixels: 0.,
+    };
+}
+
+impl Default for SnapshotTolerance {
+    /// Allows for small differences in antialiasing and glyph rasterization.
+    fn default() -> Self {
+        Self {
+            max_channel_difference: 8,
+            max_differing_pixels: 0.005,
+        }
+    }
+}
+
+fn compare_with_reference(
+    actual: &RgbaImage,
+    reference_path: &Path,
+    tolerance: SnapshotTolerance,
+    update: bool,
+) -> anyhow::Result<()> {
+    if update || !reference_path.exists() {
+        if let Some(parent) = reference_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        actual.save(reference_path)?;
+        if update {
+            return Ok(());
+        }
+        bail!(
+            "no reference image existed, so the current rendering was written to {reference_path:?}. \
+             Check it in and re-run the test."
+        );
+    }
+
+    let reference = image::open(reference_path)?.to_rgba8();
+    let error = if reference.dimensions() != actual.dimensions() {
+        format!(
+            "rendering is {:?}, but the reference image {reference_path:?} is {:?}",
+            actual.dimensions(),
+            reference.dimensions()
+        )
+    } else {
+        let differing_pixels = actual
+            .pixels()
+            .zip(reference.pixels())
+            .filter(|(actual, reference)| {
+                actual
+                    .0
+                    .iter()
+                    .zip(reference.0.iter())
+                    .any(|(a, b)| a.abs_diff(*b) > tolerance.max_channel_difference)
+            })
+            .count();
+        let allowed = (tolerance.max_differing_pixels * actual.pixels().len() as f32) as usize;
+        if differing_pixels <= allowed {
+            return Ok(());
+        }
+        format!(
+            "{differing_pixels} pixels differ from the reference image {reference_path:?}, \
+             but at most {allowed} may"
+        )
+    };
+
+    let actual_path = actual_image_path(reference_path);
+    actual.save(&actual_path)?;
+    Err(anyhow!(
+        "{error}. The rendering was written to {actual_path:?}."
+    ))
+}
+
+fn actual_image_path(reference_path: &Path) -> PathBuf {
+    let mut file_name = reference_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_os_string();
+    file_name.push(".actual.png");
+    reference_path.with_file_name(file_name)
+}
+
 impl Context for VisualTestContext {
     type Result<T> = <TestAppContext as Context>::Result<T>;
 
@@ -998,3 +1181,90 @@ impl AnyWindowHandle {
         self.update(cx, |_, cx| cx.new_view(build_view)).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use serde_json::json;
+    use util::test::temp_tree;
+
+    #[test]
+    fn test_compare_with_reference() {
+        let dir = temp_tree(json!({}));
+        let reference_path = dir.path().join("snapshots/square.png");
+        let actual_path = dir.path().join("snapshots/square.actual.png");
+        let image = RgbaImage::from_fn(10, 10, |x, _| {
+            if x < 5 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 0, 255, 255])
+            }
+        });
+
+        // A missing reference is recorded, but still fails.
+        let error =
+            compare_with_reference(&image, &reference_path, SnapshotTolerance::EXACT, false)
+                .unwrap_err();
+        assert!(error.to_string().contains("no reference image existed"));
+        assert_eq!(image::open(&reference_path).unwrap().to_rgba8(), image);
+
+        compare_with_reference(&image, &reference_path, SnapshotTolerance::EXACT, false).unwrap();
+        assert!(!actual_path.exists());
+
+        // Small differences are allowed by the tolerance.
+        let mut slightly_different = image.clone();
+        slightly_different.put_pixel(0, 0, Rgba([250, 0, 0, 255]));
+        compare_with_reference(
+            &slightly_different,
+            &reference_path,
+            SnapshotTolerance::default(),
+            false,
+        )
+        .unwrap();
+        let error = compare_with_reference(
+            &slightly_different,
+            &reference_path,
+            SnapshotTolerance::EXACT,
+            false,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("1 pixels differ from the reference image"));
+        assert_eq!(
+            image::open(&actual_path).unwrap().to_rgba8(),
+            slightly_different
+        );
+
+        let mut different = image.clone();
+        different.put_pixel(0, 0, Rgba([0, 255, 0, 255]));
+        let error = compare_with_reference(
+            &different,
+            &reference_path,
+            SnapshotTolerance::default(),
+            false,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("1 pixels differ from the reference image"));
+        assert_eq!(image::open(&actual_path).unwrap().to_rgba8(), different);
+        assert_eq!(image::open(&reference_path).unwrap().to_rgba8(), image);
+
+        let smaller = RgbaImage::new(5, 5);
+        let error = compare_with_reference(
+            &smaller,
+            &reference_path,
+            SnapshotTolerance::default(),
+            false,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("rendering is (5, 5)"));
+
+        // Updating overwrites the reference and succeeds.
+        compare_with_reference(&different, &reference_path, SnapshotTolerance::EXACT, true)
+            .unwrap();
+        assert_eq!(image::open(&reference_path).unwrap().to_rgba8(), different);
+    }
+}
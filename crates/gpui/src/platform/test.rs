@@ -1,11 +1,13 @@
 mod dispatcher;
 mod display;
 mod platform;
+mod rasterizer;
 mod window;
 
 pub(crate) use dispatcher::*;
 pub(crate) use display::*;
 pub(crate) use platform::*;
+pub(crate) use rasterizer::*;
 pub(crate) use window::*;
 
 pub use platform::TestScreenCaptureSource;
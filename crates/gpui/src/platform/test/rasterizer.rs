@@ -0,0 +1,791 @@
+//! A CPU implementation of the scene renderer, so that tests can look at what a window would
+//! display without a GPU.
+//!
+//! The math mirrors the blade shaders (`shaders.wgsl`), but blending happens in sRGB space and
+//! path edges are not antialiased, so the output is close to, but not identical with, what's
+//! drawn on screen.
+
+use super::TestAtlas;
+use crate::{
+    AtlasTextureKind, Background, BackgroundTag, Bounds, ContentMask, Corners, DevicePixels,
+    MonochromeSprite, Path, PolychromeSprite, PrimitiveBatch, Quad, Rgba, ScaledPixels, Scene,
+    Shadow, Size, TransformationMatrix, Underline,
+};
+use image::RgbaImage;
+use std::{f32::consts::PI, ops::Range};
+
+/// Rasterizes the given scene into an image of the given size, looking up sprites in `atlas`.
+pub(crate) fn rasterize_scene(
+    scene: &Scene,
+    size: Size<DevicePixels>,
+    atlas: &TestAtlas,
+) -> RgbaImage {
+    let mut canvas = Canvas::new(size);
+    for batch in scene.batches() {
+        match batch {
+            PrimitiveBatch::Shadows(shadows) => {
+                for shadow in shadows {
+                    canvas.draw_shadow(shadow);
+                }
+            }
+            PrimitiveBatch::Quads(quads) => {
+                for quad in quads {
+                    canvas.draw_quad(quad);
+                }
+            }
+            PrimitiveBatch::Paths(paths) => {
+                for path in paths {
+                    canvas.draw_path(path);
+                }
+            }
+            PrimitiveBatch::Underlines(underlines) => {
+                for underline in underlines {
+                    canvas.draw_underline(underline);
+                }
+            }
+            PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                for sprite in sprites {
+                    canvas.draw_monochrome_sprite(sprite, atlas);
+                }
+            }
+            PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                for sprite in sprites {
+                    canvas.draw_polychrome_sprite(sprite, atlas);
+                }
+            }
+            // Surfaces are only produced by macOS video capture.
+            PrimitiveBatch::Surfaces(_) => {}
+        }
+    }
+    canvas.into_image()
+}
+
+/// A premultiplied RGBA pixel buffer.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(size: Size<DevicePixels>) -> Self {
+        let width = size.width.0.max(0) as u32;
+        let height = size.height.0.max(0) as u32;
+        Self {
+            width,
+            height,
+            pixels: vec![[0.; 4]; (width * height) as usize],
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width, self.height);
+        for (pixel, [r, g, b, a]) in image.pixels_mut().zip(self.pixels) {
+            let unpremultiply = if a > 0. { 1. / a } else { 0. };
+            let channel = |value: f32| (value * 255.).round().clamp(0., 255.) as u8;
+            pixel.0 = [
+                channel(r * unpremultiply),
+                channel(g * unpremultiply),
+                channel(b * unpremultiply),
+                channel(a),
+            ];
+        }
+        image
+    }
+
+    /// Composites a straight-alpha color over the pixel at the given coordinates.
+    fn blend(&mut self, x: u32, y: u32, color: Rgba, coverage: f32) {
+        let alpha = color.a * coverage.clamp(0., 1.);
+        if alpha <= 0. {
+            return;
+        }
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        let source = [color.r * alpha, color.g * alpha, color.b * alpha, alpha];
+        for (destination, source) in pixel.iter_mut().zip(source) {
+            *destination = source + *destination * (1. - alpha);
+        }
+    }
+
+    /// The pixels whose centers may lie within `bounds`, clipped to the content mask and canvas.
+    fn pixel_ranges(
+        &self,
+        bounds: &Bounds<ScaledPixels>,
+        content_mask: &ContentMask<ScaledPixels>,
+    ) -> (Range<u32>, Range<u32>) {
+        let clipped = bounds.intersect(&content_mask.bounds);
+        let x_start = clipped.origin.x.0.floor().max(0.) as u32;
+        let y_start = clipped.origin.y.0.floor().max(0.) as u32;
+        let x_end = (clipped.origin.x.0 + clipped.size.width.0)
+            .ceil()
+            .clamp(0., self.width as f32) as u32;
+        let y_end = (clipped.origin.y.0 + clipped.size.height.0)
+            .ceil()
+            .clamp(0., self.height as f32) as u32;
+        (x_start..x_end.max(x_start), y_start..y_end.max(y_start))
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let (xs, ys) = self.pixel_ranges(&quad.bounds, &quad.content_mask);
+        let border_color = quad.border_color.to_rgb();
+        let half_size = [
+            quad.bounds.size.width.0 / 2.,
+            quad.bounds.size.height.0 / 2.,
+        ];
+        let center = [
+            quad.bounds.origin.x.0 + half_size[0],
+            quad.bounds.origin.y.0 + half_size[1],
+        ];
+        for y in ys {
+            for x in xs.clone() {
+                let point = pixel_center(x, y);
+                let center_to_point = [point[0] - center[0], point[1] - center[1]];
+                let corner_radius = pick_corner_radius(center_to_point, &quad.corner_radii);
+                let distance = rounded_rect_distance(center_to_point, half_size, corner_radius);
+
+                let background = background_color(&quad.background, &quad.bounds, point);
+                let vertical_border = if center_to_point[0] > 0. {
+                    quad.border_widths.right.0
+                } else {
+                    quad.border_widths.left.0
+                };
+                let horizontal_border = if center_to_point[1] > 0. {
+                    quad.border_widths.bottom.0
+                } else {
+                    quad.border_widths.top.0
+                };
+                let inset_size = [
+                    half_size[0] - corner_radius - vertical_border,
+                    half_size[1] - corner_radius - horizontal_border,
+                ];
+                let point_to_inset_corner = [
+                    center_to_point[0].abs() - inset_size[0],
+                    center_to_point[1].abs() - inset_size[1],
+                ];
+                let border_width = if point_to_inset_corner[0] < 0. && point_to_inset_corner[1] < 0.
+                {
+                    0.
+                } else if point_to_inset_corner[1] > point_to_inset_corner[0] {
+                    horizontal_border
+                } else {
+                    vertical_border
+                };
+
+                let mut color = background;
+                if border_width > 0. {
+                    let inset_distance = distance + border_width;
+                    let blended_border = over(background, border_color);
+                    color = mix(blended_border, background, saturate(0.5 - inset_distance));
+                }
+                self.blend(x, y, color, saturate(0.5 - distance));
+            }
+        }
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let blur_radius = shadow.blur_radius.0;
+        let margin = ScaledPixels(3. * blur_radius);
+        let bounds = shadow.bounds.dilate(margin);
+        let (xs, ys) = self.pixel_ranges(&bounds, &shadow.content_mask);
+        let color = shadow.color.to_rgb();
+        let half_size = [
+            shadow.bounds.size.width.0 / 2.,
+            shadow.bounds.size.height.0 / 2.,
+        ];
+        let center = [
+            shadow.bounds.origin.x.0 + half_size[0],
+            shadow.bounds.origin.y.0 + half_size[1],
+        ];
+        for y in ys {
+            for x in xs.clone() {
+                let point = pixel_center(x, y);
+                let center_to_point = [point[0] - center[0], point[1] - center[1]];
+                let corner_radius = pick_corner_radius(center_to_point, &shadow.corner_radii);
+                let alpha = if blur_radius == 0. {
+                    saturate(0.5 - rounded_rect_distance(center_to_point, half_size, corner_radius))
+                } else {
+                    // The signal is only non-zero in a limited range, so don't waste samples.
+                    let low = center_to_point[1] - half_size[1];
+                    let high = center_to_point[1] + half_size[1];
+                    let start = (-3. * blur_radius).clamp(low, high);
+                    let end = (3. * blur_radius).clamp(low, high);
+                    let step = (end - start) / 4.;
+                    let mut sample_y = start + step * 0.5;
+                    let mut alpha = 0.;
+                    for _ in 0..4 {
+                        let blur = blur_along_x(
+                            center_to_point[0],
+                            center_to_point[1] - sample_y,
+                            blur_radius,
+                            corner_radius,
+                            half_size,
+                        );
+                        alpha += blur * gaussian(sample_y, blur_radius) * step;
+                        sample_y += step;
+                    }
+                    alpha
+                };
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        let (xs, ys) = self.pixel_ranges(&underline.bounds, &underline.content_mask);
+        let color = underline.color.to_rgb();
+        let thickness = underline.thickness.0;
+        let height = underline.bounds.size.height.0;
+        for y in ys {
+            for x in xs.clone() {
+                let alpha = if underline.wavy && height > 0. && thickness > 0. {
+                    let point = pixel_center(x, y);
+                    let half_thickness = thickness * 0.5;
+                    let st = [
+                        (point[0] - underline.bounds.origin.x.0) / height,
+                        (point[1] - underline.bounds.origin.y.0) / height - 0.5,
+                    ];
+                    let frequency = (PI * (3. * thickness)) / 8.;
+                    let amplitude = 1. / (2. * thickness);
+                    let sine = (st[0] * frequency).sin() * amplitude;
+                    let d_sine = (st[0] * frequency).cos() * amplitude * frequency;
+                    let distance = (st[1] - sine) / (1. + d_sine * d_sine).sqrt();
+                    let distance_in_pixels = distance * height;
+                    let distance_from_top_border = distance_in_pixels - half_thickness;
+                    let distance_from_bottom_border = distance_in_pixels + half_thickness;
+                    saturate(0.5 - (-distance_from_bottom_border).max(distance_from_top_border))
+                } else {
+                    1.
+                };
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let (xs, ys) = self.pixel_ranges(&path.bounds, &path.content_mask);
+        if xs.is_empty() || ys.is_empty() {
+            return;
+        }
+
+        // Accumulate coverage like the path rasterization pass, then apply the even-odd rule.
+        let width = xs.len();
+        let mut winding = vec![0f32; width * ys.len()];
+        for triangle in path.vertices.chunks_exact(3) {
+            let positions = triangle
+                .iter()
+                .map(|vertex| [vertex.xy_position.x.0, vertex.xy_position.y.0])
+                .collect::<Vec<_>>();
+            let st = triangle
+                .iter()
+                .map(|vertex| [vertex.st_position.x, vertex.st_position.y])
+                .collect::<Vec<_>>();
+            let Some(gradients) = barycentric_gradients(&positions) else {
+                continue;
+            };
+            let ds = [st[1][0] - st[0][0], st[2][0] - st[0][0]];
+            let dt = [st[1][1] - st[0][1], st[2][1] - st[0][1]];
+            let ds_dx = ds[0] * gradients.u[0] + ds[1] * gradients.v[0];
+            let ds_dy = ds[0] * gradients.u[1] + ds[1] * gradients.v[1];
+            let dt_dx = dt[0] * gradients.u[0] + dt[1] * gradients.v[0];
+            let dt_dy = dt[0] * gradients.u[1] + dt[1] * gradients.v[1];
+
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let point = pixel_center(x, y);
+                    let relative = [point[0] - positions[0][0], point[1] - positions[0][1]];
+                    let u = gradients.u[0] * relative[0] + gradients.u[1] * relative[1];
+                    let v = gradients.v[0] * relative[0] + gradients.v[1] * relative[1];
+                    if u < 0. || v < 0. || u + v > 1. {
+                        continue;
+                    }
+                    let s = st[0][0] + ds[0] * u + ds[1] * v;
+                    let t = st[0][1] + dt[0] * u + dt[1] * v;
+                    let f = s * s - t;
+                    let gradient = [2. * s * ds_dx - dt_dx, 2. * s * ds_dy - dt_dy];
+                    let gradient_length = gradient[0].hypot(gradient[1]);
+                    let coverage = if gradient_length > 0. {
+                        saturate(0.5 - f / gradient_length)
+                    } else if f <= 0. {
+                        1.
+                    } else {
+                        0.
+                    };
+                    let index = (y - ys.start) as usize * width + (x - xs.start) as usize;
+                    winding[index] += coverage;
+                }
+            }
+        }
+
+        for y in ys.clone() {
+            for x in xs.clone() {
+                let index = (y - ys.start) as usize * width + (x - xs.start) as usize;
+                let mask = 1. - (1. - winding[index] % 2.).abs();
+                if mask > 0. {
+                    let color = background_color(&path.color, &path.bounds, pixel_center(x, y));
+                    self.blend(x, y, color, mask);
+                }
+            }
+        }
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, atlas: &TestAtlas) {
+        let Some(texture) = atlas.texture(&sprite.tile) else {
+            return;
+        };
+        let color = sprite.color.to_rgb();
+        let transformation = sprite.transformation;
+        let bounds = transformed_bounds(&sprite.bounds, &transformation);
+        let (xs, ys) = self.pixel_ranges(&bounds, &sprite.content_mask);
+        let Some(inverse) = invert(&transformation) else {
+            return;
+        };
+        for y in ys {
+            for x in xs.clone() {
+                let point = apply(&inverse, pixel_center(x, y));
+                let Some(alpha) =
+                    texture.sample(&sprite.bounds, point, |pixel| pixel[0] as f32 / 255.)
+                else {
+                    continue;
+                };
+                self.blend(x, y, color, alpha);
+            }
+        }
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, atlas: &TestAtlas) {
+        let Some(texture) = atlas.texture(&sprite.tile) else {
+            return;
+        };
+        let (xs, ys) = self.pixel_ranges(&sprite.bounds, &sprite.content_mask);
+        let half_size = [
+            sprite.bounds.size.width.0 / 2.,
+            sprite.bounds.size.height.0 / 2.,
+        ];
+        let center = [
+            sprite.bounds.origin.x.0 + half_size[0],
+            sprite.bounds.origin.y.0 + half_size[1],
+        ];
+        for y in ys {
+            for x in xs.clone() {
+                let point = pixel_center(x, y);
+                let Some(mut color) = texture.sample(&sprite.bounds, point, |pixel| {
+                    // Polychrome textures are stored as BGRA.
+                    Rgba {
+                        r: pixel[2] as f32 / 255.,
+                        g: pixel[1] as f32 / 255.,
+                        b: pixel[0] as f32 / 255.,
+                        a: pixel[3] as f32 / 255.,
+                    }
+                }) else {
+                    continue;
+                };
+                if sprite.grayscale {
+                    let luminance = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
+                    color.r = luminance;
+                    color.g = luminance;
+                    color.b = luminance;
+                }
+                let center_to_point = [point[0] - center[0], point[1] - center[1]];
+                let corner_radius = pick_corner_radius(center_to_point, &sprite.corner_radii);
+                let distance = rounded_rect_distance(center_to_point, half_size, corner_radius);
+                self.blend(x, y, color, sprite.opacity * saturate(0.5 - distance));
+            }
+        }
+    }
+}
+
+/// The pixels of a sprite's tile, as stored in the [`TestAtlas`].
+pub(crate) struct TestAtlasTexture {
+    pub(crate) kind: AtlasTextureKind,
+    pub(crate) size: Size<DevicePixels>,
+    pub(crate) bytes: Vec<u8>,
+}
+
+impl TestAtlasTexture {
+    /// Samples the texel under `point`, stretching the texture over `bounds`.
+    fn sample<T>(
+        &self,
+        bounds: &Bounds<ScaledPixels>,
+        point: [f32; 2],
+        read: impl FnOnce(&[u8]) -> T,
+    ) -> Option<T> {
+        let bytes_per_pixel = match self.kind {
+            AtlasTextureKind::Monochrome => 1,
+            AtlasTextureKind::Polychrome => 4,
+            AtlasTextureKind::Path => return None,
+        };
+        let width = self.size.width.0 as f32;
+        let height = self.size.height.0 as f32;
+        let u = (point[0] - bounds.origin.x.0) / bounds.size.width.0;
+        let v = (point[1] - bounds.origin.y.0) / bounds.size.height.0;
+        if !(0. ..1.).contains(&u) || !(0. ..1.).contains(&v) {
+            return None;
+        }
+        let x = (u * width) as usize;
+        let y = (v * height) as usize;
+        let offset = (y * self.size.width.0 as usize + x) * bytes_per_pixel;
+        self.bytes.get(offset..offset + bytes_per_pixel).map(read)
+    }
+}
+
+fn pixel_center(x: u32, y: u32) -> [f32; 2] {
+    [x as f32 + 0.5, y as f32 + 0.5]
+}
+
+fn saturate(value: f32) -> f32 {
+    value.clamp(0., 1.)
+}
+
+fn pick_corner_radius(center_to_point: [f32; 2], radii: &Corners<ScaledPixels>) -> f32 {
+    if center_to_point[0] < 0. {
+        if center_to_point[1] < 0. {
+            radii.top_left.0
+        } else {
+            radii.bottom_left.0
+        }
+    } else if center_to_point[1] < 0. {
+        radii.top_right.0
+    } else {
+        radii.bottom_right.0
+    }
+}
+
+/// The signed distance from a point (relative to the center) to a rounded rectangle.
+fn rounded_rect_distance(
+    center_to_point: [f32; 2],
+    half_size: [f32; 2],
+    corner_radius: f32,
+) -> f32 {
+    let rounded_edge_to_point = [
+        center_to_point[0].abs() - half_size[0] + corner_radius,
+        center_to_point[1].abs() - half_size[1] + corner_radius,
+    ];
+    rounded_edge_to_point[0]
+        .max(0.)
+        .hypot(rounded_edge_to_point[1].max(0.))
+        + rounded_edge_to_point[0]
+            .max(rounded_edge_to_point[1])
+            .min(0.)
+        - corner_radius
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * PI).sqrt() * sigma)
+}
+
+/// An approximation of the error function, as used by the shaders.
+fn erf(value: f32) -> f32 {
+    let sign = value.signum();
+    let a = value.abs();
+    let r1 = 1. + (0.278393 + (0.230389 + (0.000972 + 0.078108 * a) * a) * a) * a;
+    let r2 = r1 * r1;
+    sign - sign / (r2 * r2)
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: [f32; 2]) -> f32 {
+    let delta = (half_size[1] - corner - y.abs()).min(0.);
+    let curved = half_size[0] - corner + (corner * corner - delta * delta).max(0.).sqrt();
+    let scale = 0.5f32.sqrt() / sigma;
+    let start = 0.5 + 0.5 * erf((x - curved) * scale);
+    let end = 0.5 + 0.5 * erf((x + curved) * scale);
+    end - start
+}
+
+fn background_color(
+    background: &Background,
+    bounds: &Bounds<ScaledPixels>,
+    point: [f32; 2],
+) -> Rgba {
+    match background.tag {
+        BackgroundTag::Solid => background.solid.to_rgb(),
+        BackgroundTag::LinearGradient => {
+            let radians = (background.angle % 360. - 90.).to_radians();
+            let mut direction = [radians.cos(), radians.sin()];
+            let size = [bounds.size.width.0, bounds.size.height.0];
+            // Expand the short side to be the same as the long side.
+            if size[0] > size[1] {
+                direction[1] *= size[1] / size[0];
+            } else {
+                direction[0] *= size[0] / size[1];
+            }
+            let half_size = [size[0] / 2., size[1] / 2.];
+            let center_to_point = [
+                point[0] - bounds.origin.x.0 - half_size[0],
+                point[1] - bounds.origin.y.0 - half_size[1],
+            ];
+            let mut t = (center_to_point[0] * direction[0] + center_to_point[1] * direction[1])
+                / direction[0].hypot(direction[1]);
+            if direction[0].abs() > direction[1].abs() {
+                t = (t + half_size[0]) / size[0];
+            } else {
+                t = (t + half_size[1]) / size[1];
+            }
+            let [start, end] = &background.colors;
+            t = (t - start.percentage) / (end.percentage - start.percentage);
+            mix(start.color.to_rgb(), end.color.to_rgb(), saturate(t))
+        }
+    }
+}
+
+/// Composites `above` over `below`, both with straight alpha.
+fn over(below: Rgba, above: Rgba) -> Rgba {
+    let alpha = above.a + below.a * (1. - above.a);
+    if alpha <= 0. {
+        return Rgba::default();
+    }
+    let channel = |above_channel: f32, below_channel: f32| {
+        (above_channel * above.a + below_channel * below.a * (1. - above.a)) / alpha
+    };
+    Rgba {
+        r: channel(above.r, below.r),
+        g: channel(above.g, below.g),
+        b: channel(above.b, below.b),
+        a: alpha,
+    }
+}
+
+fn mix(from: Rgba, to: Rgba, t: f32) -> Rgba {
+    let lerp = |from: f32, to: f32| from + (to - from) * t;
+    Rgba {
+        r: lerp(from.r, to.r),
+        g: lerp(from.g, to.g),
+        b: lerp(from.b, to.b),
+        a: lerp(from.a, to.a),
+    }
+}
+
+/// How the barycentric coordinates `u` and `v` of a triangle change with `x` and `y`.
+struct BarycentricGradients {
+    u: [f32; 2],
+    v: [f32; 2],
+}
+
+fn barycentric_gradients(positions: &[[f32; 2]]) -> Option<BarycentricGradients> {
+    let e1 = [
+        positions[1][0] - positions[0][0],
+        positions[1][1] - positions[0][1],
+    ];
+    let e2 = [
+        positions[2][0] - positions[0][0],
+        positions[2][1] - positions[0][1],
+    ];
+    let determinant = e1[0] * e2[1] - e1[1] * e2[0];
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    Some(BarycentricGradients {
+        u: [e2[1] / determinant, -e2[0] / determinant],
+        v: [-e1[1] / determinant, e1[0] / determinant],
+    })
+}
+
+fn apply(transformation: &TransformationMatrix, point: [f32; 2]) -> [f32; 2] {
+    let [[a, b], [c, d]] = transformation.rotation_scale;
+    let [tx, ty] = transformation.translation;
+    [
+        a * point[0] + b * point[1] + tx,
+        c * point[0] + d * point[1] + ty,
+    ]
+}
+
+fn invert(transformation: &TransformationMatrix) -> Option<TransformationMatrix> {
+    let [[a, b], [c, d]] = transformation.rotation_scale;
+    let determinant = a * d - b * c;
+    if determinant.abs() < f32::EPSILON {
+        return None;
+    }
+    let rotation_scale = [
+        [d / determinant, -b / determinant],
+        [-c / determinant, a / determinant],
+    ];
+    let [tx, ty] = transformation.translation;
+    Some(TransformationMatrix {
+        rotation_scale,
+        translation: [
+            -(rotation_scale[0][0] * tx + rotation_scale[0][1] * ty),
+            -(rotation_scale[1][0] * tx + rotation_scale[1][1] * ty),
+        ],
+    })
+}
+
+fn transformed_bounds(
+    bounds: &Bounds<ScaledPixels>,
+    transformation: &TransformationMatrix,
+) -> Bounds<ScaledPixels> {
+    let corners = [
+        bounds.origin,
+        bounds.top_right(),
+        bounds.bottom_left(),
+        bounds.bottom_right(),
+    ]
+    .map(|corner| apply(transformation, [corner.x.0, corner.y.0]));
+    let min_x = corners.iter().map(|p| p[0]).fold(f32::INFINITY, f32::min);
+    let min_y = corners.iter().map(|p| p[1]).fold(f32::INFINITY, f32::min);
+    let max_x = corners
+        .iter()
+        .map(|p| p[0])
+        .fold(f32::NEG_INFINITY, f32::max);
+    let max_y = corners
+        .iter()
+        .map(|p| p[1])
+        .fold(f32::NEG_INFINITY, f32::max);
+    Bounds::from_corners(
+        crate::point(ScaledPixels(min_x), ScaledPixels(min_y)),
+        crate::point(ScaledPixels(max_x), ScaledPixels(max_y)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hsla, point, size, Edges, Hsla};
+
+    fn quad(bounds: Bounds<ScaledPixels>, background: Hsla) -> Quad {
+        Quad {
+            order: 0,
+            pad: 0,
+            bounds,
+            content_mask: ContentMask {
+                bounds: Bounds::new(
+                    point(ScaledPixels(0.), ScaledPixels(0.)),
+                    size(ScaledPixels(1000.), ScaledPixels(1000.)),
+                ),
+            },
+            background: background.into(),
+            border_color: Hsla::transparent_black(),
+            corner_radii: Corners::default(),
+            border_widths: Edges::default(),
+        }
+    }
+
+    fn scaled_bounds(x: f32, y: f32, width: f32, height: f32) -> Bounds<ScaledPixels> {
+        Bounds::new(
+            point(ScaledPixels(x), ScaledPixels(y)),
+            size(ScaledPixels(width), ScaledPixels(height)),
+        )
+    }
+
+    fn rasterize(scene: &mut Scene, width: i32, height: i32) -> RgbaImage {
+        scene.finish();
+        rasterize_scene(
+            scene,
+            size(DevicePixels(width), DevicePixels(height)),
+            &TestAtlas::new(),
+        )
+    }
+
+    #[test]
+    fn test_rasterize_quads() {
+        let red = hsla(0., 1., 0.5, 1.);
+        let blue = hsla(240. / 360., 1., 0.5, 1.);
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(scaled_bounds(0., 0., 10., 10.), red));
+        let mut bordered = quad(scaled_bounds(4., 4., 4., 4.), blue);
+        bordered.border_widths = Edges::all(ScaledPixels(1.));
+        bordered.border_color = red;
+        scene.insert_primitive(bordered);
+
+        let image = rasterize(&mut scene, 12, 12);
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(9, 9).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(10, 10).0, [0, 0, 0, 0]);
+        // The border is drawn in the outermost pixel of the quad, around a blue center.
+        assert_eq!(image.get_pixel(4, 4).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(5, 5).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(6, 6).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(7, 7).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_rasterize_content_mask_and_rounded_corners() {
+        let green = hsla(120. / 360., 1., 0.5, 1.);
+        let mut scene = Scene::default();
+        let mut clipped = quad(scaled_bounds(0., 0., 10., 10.), green);
+        clipped.content_mask.bounds = scaled_bounds(0., 0., 5., 10.);
+        scene.insert_primitive(clipped);
+        let mut rounded = quad(scaled_bounds(10., 0., 10., 10.), green);
+        rounded.corner_radii = Corners::all(ScaledPixels(5.));
+        scene.insert_primitive(rounded);
+
+        let image = rasterize(&mut scene, 20, 10);
+        assert_eq!(image.get_pixel(4, 5).0[3], 255);
+        assert_eq!(image.get_pixel(5, 5).0[3], 0);
+        // The corner is cut off, while the inside of the circle is filled.
+        assert_eq!(image.get_pixel(10, 0).0[3], 0);
+        assert_eq!(image.get_pixel(11, 5).0[3], 255);
+    }
+
+    #[test]
+    fn test_rasterize_translucent_quads() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(quad(scaled_bounds(0., 0., 4., 4.), hsla(0., 0., 1., 1.)));
+        scene.insert_primitive(quad(scaled_bounds(0., 0., 4., 4.), hsla(0., 0., 0., 0.5)));
+
+        let image = rasterize(&mut scene, 4, 4);
+        let [r, g, b, a] = image.get_pixel(1, 1).0;
+        assert_eq!(a, 255);
+        assert!(r.abs_diff(128) <= 1 && r == g && g == b);
+    }
+
+    #[test]
+    fn test_rasterize_path() {
+        let mut path = Path::new(point(crate::px(0.), crate::px(0.)));
+        path.line_to(point(crate::px(8.), crate::px(0.)));
+        path.line_to(point(crate::px(8.), crate::px(8.)));
+        path.line_to(point(crate::px(0.), crate::px(0.)));
+        let mut path = path.scale(1.);
+        path.color = hsla(0., 0., 0., 1.).into();
+        path.content_mask.bounds = scaled_bounds(0., 0., 10., 10.);
+
+        let mut scene = Scene::default();
+        scene.insert_primitive(path);
+        let image = rasterize(&mut scene, 10, 10);
+        // Only the triangle above the diagonal is filled.
+        assert_eq!(image.get_pixel(6, 1).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 6).0[3], 0);
+        assert_eq!(image.get_pixel(9, 9).0[3], 0);
+    }
+
+    #[test]
+    fn test_shadow_falls_off() {
+        let mut scene = Scene::default();
+        scene.insert_primitive(Shadow {
+            order: 0,
+            blur_radius: ScaledPixels(2.),
+            bounds: scaled_bounds(10., 10., 10., 10.),
+            corner_radii: Corners::default(),
+            content_mask: ContentMask {
+                bounds: scaled_bounds(0., 0., 30., 30.),
+            },
+            color: hsla(0., 0., 0., 1.),
+        });
+
+        let image = rasterize(&mut scene, 30, 30);
+        let alpha = |x, y| image.get_pixel(x, y).0[3];
+        assert!(alpha(15, 15) > 240);
+        assert!(alpha(15, 15) > alpha(10, 15));
+        assert!(alpha(10, 15) > alpha(7, 15));
+        assert_eq!(alpha(2, 15), 0);
+    }
+
+    struct Swatch;
+
+    impl crate::Render for Swatch {
+        fn render(&mut self, _: &mut crate::ViewContext<Self>) -> impl crate::IntoElement {
+            use crate::{div, px, ParentElement, Styled};
+            div()
+                .size_full()
+                .bg(hsla(0., 0., 1., 1.))
+                .child(div().size(px(10.)).bg(hsla(0., 1., 0.5, 1.)))
+        }
+    }
+
+    #[crate::test]
+    fn test_render_window_to_image(cx: &mut crate::TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_| Swatch);
+        let image = cx.render_to_image();
+        // Test windows have a scale factor of 2.
+        assert_eq!(image.get_pixel(5, 5).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(19, 19).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(25, 5).0, [255, 255, 255, 255]);
+    }
+}
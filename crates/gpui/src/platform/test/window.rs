@@ -1,3 +1,4 @@
+use super::rasterizer::TestAtlasTexture;
 use crate::{
     AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GPUSpecs,
     Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
@@ -19,7 +20,7 @@ pub(crate) struct TestWindowState {
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
-    sprite_atlas: Arc<dyn PlatformAtlas>,
+    pub(crate) sprite_atlas: Arc<TestAtlas>,
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
     active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
@@ -284,6 +285,7 @@ impl PlatformWindow for TestWindow {
 pub(crate) struct TestAtlasState {
     next_id: u32,
     tiles: HashMap<AtlasKey, AtlasTile>,
+    textures: HashMap<AtlasTextureId, Arc<TestAtlasTexture>>,
 }
 
 pub(crate) struct TestAtlas(Mutex<TestAtlasState>);
@@ -293,8 +295,14 @@ impl TestAtlas {
         TestAtlas(Mutex::new(TestAtlasState {
             next_id: 0,
             tiles: HashMap::default(),
+            textures: HashMap::default(),
         }))
     }
+
+    /// Returns the pixels that were stored for the given tile, so they can be rasterized.
+    pub(crate) fn texture(&self, tile: &AtlasTile) -> Option<Arc<TestAtlasTexture>> {
+        self.0.lock().textures.get(&tile.texture_id).cloned()
+    }
 }
 
 impl PlatformAtlas for TestAtlas {
@@ -311,23 +319,31 @@ impl PlatformAtlas for TestAtlas {
         }
         drop(state);
 
-        let Some((size, _)) = build()? else {
+        let Some((size, bytes)) = build()? else {
             return Ok(None);
         };
 
         let mut state = self.0.lock();
         state.next_id += 1;
-        let texture_id = state.next_id;
+        let texture_id = AtlasTextureId {
+            index: state.next_id,
+            kind: key.texture_kind(),
+        };
         state.next_id += 1;
         let tile_id = state.next_id;
 
+        state.textures.insert(
+            texture_id,
+            Arc::new(TestAtlasTexture {
+                kind: texture_id.kind,
+                size,
+                bytes: bytes.into_owned(),
+            }),
+        );
         state.tiles.insert(
             key.clone(),
             crate::AtlasTile {
-                texture_id: AtlasTextureId {
-                    index: texture_id,
-                    kind: crate::AtlasTextureKind::Path,
-                },
+                texture_id,
                 tile_id: TileId(tile_id),
                 padding: 0,
                 bounds: crate::Bounds {
@@ -342,6 +358,8 @@ impl PlatformAtlas for TestAtlas {
 
     fn remove(&self, key: &AtlasKey) {
         let mut state = self.0.lock();
-        state.tiles.remove(key);
+        if let Some(tile) = state.tiles.remove(key) {
+            state.textures.remove(&tile.texture_id);
+        }
     }
 }
@@ -0,0 +1,1051 @@
+mod hierarchy_panel_settings;
+
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Context as _;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor, EditorMode};
+use gpui::{
+    actions, uniform_list, Action, AppContext, AsyncWindowContext, ClickEvent, EventEmitter,
+    FocusHandle, FocusableView, KeyContext, ListSizingBehavior, Model, MouseButton, ScrollStrategy,
+    Task, UniformListScrollHandle, View, WeakView,
+};
+use language::{Anchor, Buffer, OffsetRangeExt};
+use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
+use project::{CallHierarchyItem, Fs, Location, Project};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{prelude::*, ListItem, Tooltip};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+pub use hierarchy_panel_settings::HierarchyPanelSettings;
+
+actions!(
+    hierarchy_panel,
+    [
+        CollapseSelectedEntry,
+        ExpandSelectedEntry,
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSubtypes,
+        ShowSupertypes,
+        ToggleFocus,
+    ]
+);
+
+const HIERARCHY_PANEL_KEY: &str = "HierarchyPanel";
+
+pub fn init_settings(cx: &mut AppContext) {
+    HierarchyPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<HierarchyPanel>(cx);
+            })
+            .register_action(|workspace, _: &ShowIncomingCalls, cx| {
+                show_hierarchy(workspace, HierarchyKind::IncomingCalls, cx);
+            })
+            .register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+                show_hierarchy(workspace, HierarchyKind::OutgoingCalls, cx);
+            })
+            .register_action(|workspace, _: &ShowSupertypes, cx| {
+                show_hierarchy(workspace, HierarchyKind::Supertypes, cx);
+            })
+            .register_action(|workspace, _: &ShowSubtypes, cx| {
+                show_hierarchy(workspace, HierarchyKind::Subtypes, cx);
+            });
+    })
+    .detach();
+}
+
+/// Opens the hierarchy panel for the symbol under the newest cursor of the active editor.
+fn show_hierarchy(workspace: &mut Workspace, kind: HierarchyKind, cx: &mut ViewContext<Workspace>) {
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+        .filter(|editor| editor.read(cx).mode() == EditorMode::Full)
+    else {
+        return;
+    };
+    let head = editor.read(cx).selections.newest_anchor().head();
+    let Some((buffer, position)) = editor
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .text_anchor_for_position(head, cx)
+    else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<HierarchyPanel>(cx) {
+        panel.update(cx, |panel, cx| panel.show(kind, buffer, position, cx));
+    }
+}
+
+/// The relation displayed by the panel's tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyKind {
+    /// Children of a function are the functions calling it.
+    IncomingCalls,
+    /// Children of a function are the functions it calls.
+    OutgoingCalls,
+    /// Children of a type are the types it extends or implements.
+    Supertypes,
+    /// Children of a type are the types extending or implementing it.
+    Subtypes,
+}
+
+impl HierarchyKind {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "Incoming Calls",
+            Self::OutgoingCalls => "Outgoing Calls",
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    fn is_type_hierarchy(&self) -> bool {
+        matches!(self, Self::Supertypes | Self::Subtypes)
+    }
+
+    fn prepare(
+        &self,
+        project: &Model<Project>,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut AppContext,
+    ) -> Task<anyhow::Result<Vec<CallHierarchyItem>>> {
+        project.update(cx, |project, cx| match self {
+            Self::IncomingCalls | Self::OutgoingCalls => {
+                project.prepare_call_hierarchy(buffer, position, cx)
+            }
+            Self::Supertypes | Self::Subtypes => {
+                project.prepare_type_hierarchy(buffer, position, cx)
+            }
+        })
+    }
+
+    fn children(
+        &self,
+        project: &Model<Project>,
+        item: &CallHierarchyItem,
+        cx: &mut AppContext,
+    ) -> Task<anyhow::Result<Vec<(CallHierarchyItem, Vec<Location>)>>> {
+        if self.is_type_hierarchy() {
+            let task = project.update(cx, |project, cx| match self {
+                Self::Supertypes => project.supertypes(item, cx),
+                _ => project.subtypes(item, cx),
+            });
+            cx.background_executor().spawn(async move {
+                Ok(task
+                    .await?
+                    .into_iter()
+                    .map(|item| (item, Vec::new()))
+                    .collect())
+            })
+        } else {
+            let task = project.update(cx, |project, cx| match self {
+                Self::IncomingCalls => project.incoming_calls(item, cx),
+                _ => project.outgoing_calls(item, cx),
+            });
+            cx.background_executor().spawn(async move {
+                Ok(task
+                    .await?
+                    .into_iter()
+                    .map(|call| (call.item, call.call_sites))
+                    .collect())
+            })
+        }
+    }
+}
+
+struct HierarchyNode {
+    item: CallHierarchyItem,
+    /// Where the relation to the parent node occurs, e.g. the call expressions.
+    call_sites: Vec<Location>,
+    depth: usize,
+    /// `None` until the children were fetched from the language server.
+    children: Option<Vec<usize>>,
+    expanded: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedHierarchyPanel {
+    width: Option<Pixels>,
+}
+
+pub struct HierarchyPanel {
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    focus_handle: FocusHandle,
+    pending_serialization: Task<Option<()>>,
+    scroll_handle: UniformListScrollHandle,
+    kind: HierarchyKind,
+    /// The symbol the hierarchy was requested for, used to re-query it with a different kind.
+    origin: Option<(Model<Buffer>, Anchor)>,
+    nodes: Vec<HierarchyNode>,
+    roots: Vec<usize>,
+    visible_entries: Vec<usize>,
+    selected_entry: Option<usize>,
+    prepare_task: Task<()>,
+    fetch_tasks: HashMap<usize, Task<()>>,
+}
+
+#[derive(Debug)]
+pub enum Event {
+    Focus,
+}
+
+impl HierarchyPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(HIERARCHY_PANEL_KEY) })
+            .await
+            .context("loading hierarchy panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedHierarchyPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let project = workspace.project().clone();
+        let workspace = cx.view().downgrade();
+        cx.new_view(|cx| {
+            let focus_handle = cx.focus_handle();
+            cx.on_focus(&focus_handle, |_, cx| cx.emit(Event::Focus))
+                .detach();
+            Self {
+                fs,
+                width: None,
+                project,
+                workspace,
+                focus_handle,
+                pending_serialization: Task::ready(None),
+                scroll_handle: UniformListScrollHandle::new(),
+                kind: HierarchyKind::IncomingCalls,
+                origin: None,
+                nodes: Vec::new(),
+                roots: Vec::new(),
+                visible_entries: Vec::new(),
+                selected_entry: None,
+                prepare_task: Task::ready(()),
+                fetch_tasks: HashMap::default(),
+            }
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        HIERARCHY_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedHierarchyPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn dispatch_context(&self) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    /// Replaces the panel's contents with the hierarchy of the symbol at the given position.
+    pub fn show(
+        &mut self,
+        kind: HierarchyKind,
+        buffer: Model<Buffer>,
+        position: Anchor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.kind = kind;
+        self.origin = Some((buffer.clone(), position));
+        self.nodes.clear();
+        self.roots.clear();
+        self.visible_entries.clear();
+        self.selected_entry = None;
+        self.fetch_tasks.clear();
+
+        let prepare = kind.prepare(&self.project, &buffer, position, cx);
+        self.prepare_task = cx.spawn(|panel, mut cx| async move {
+            let Some(items) = prepare.await.log_err() else {
+                return;
+            };
+            panel
+                .update(&mut cx, |panel, cx| {
+                    for item in items {
+                        let ix = panel.push_node(item, Vec::new(), 0);
+                        panel.roots.push(ix);
+                    }
+                    // Roots are always shown expanded, as they are the symbols the user asked about.
+                    for root in panel.roots.clone() {
+                        panel.expand_node(root, cx);
+                    }
+                    if !panel.roots.is_empty() {
+                        panel.selected_entry = Some(0);
+                    }
+                    panel.update_visible_entries(cx);
+                })
+                .ok();
+        });
+        cx.notify();
+    }
+
+    fn set_kind(&mut self, kind: HierarchyKind, cx: &mut ViewContext<Self>) {
+        if self.kind == kind {
+            return;
+        }
+        if let Some((buffer, position)) = self.origin.clone() {
+            self.show(kind, buffer, position, cx);
+        } else {
+            self.kind = kind;
+            cx.notify();
+        }
+    }
+
+    fn push_node(
+        &mut self,
+        item: CallHierarchyItem,
+        call_sites: Vec<Location>,
+        depth: usize,
+    ) -> usize {
+        self.nodes.push(HierarchyNode {
+            item,
+            call_sites,
+            depth,
+            children: None,
+            expanded: false,
+        });
+        self.nodes.len() - 1
+    }
+
+    fn expand_node(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(node) = self.nodes.get_mut(node_ix) else {
+            return;
+        };
+        node.expanded = true;
+        if node.children.is_some() || self.fetch_tasks.contains_key(&node_ix) {
+            self.update_visible_entries(cx);
+            return;
+        }
+
+        let depth = node.depth + 1;
+        let children = self.kind.children(&self.project, &node.item, cx);
+        let task = cx.spawn(|panel, mut cx| async move {
+            let children = children.await.log_err().unwrap_or_default();
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.fetch_tasks.remove(&node_ix);
+                    let child_ixs = children
+                        .into_iter()
+                        .map(|(item, call_sites)| panel.push_node(item, call_sites, depth))
+                        .collect();
+                    if let Some(node) = panel.nodes.get_mut(node_ix) {
+                        node.children = Some(child_ixs);
+                    }
+                    panel.update_visible_entries(cx);
+                })
+                .ok();
+        });
+        self.fetch_tasks.insert(node_ix, task);
+        self.update_visible_entries(cx);
+    }
+
+    fn collapse_node(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        if let Some(node) = self.nodes.get_mut(node_ix) {
+            node.expanded = false;
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn toggle_expanded(&mut self, node_ix: usize, cx: &mut ViewContext<Self>) {
+        if self.nodes.get(node_ix).map_or(false, |node| node.expanded) {
+            self.collapse_node(node_ix, cx);
+        } else {
+            self.expand_node(node_ix, cx);
+        }
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let selected_node = self
+            .selected_entry
+            .and_then(|ix| self.visible_entries.get(ix).copied());
+
+        self.visible_entries.clear();
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(node_ix) = stack.pop() {
+            self.visible_entries.push(node_ix);
+            let node = &self.nodes[node_ix];
+            if node.expanded {
+                if let Some(children) = &node.children {
+                    stack.extend(children.iter().rev().copied());
+                }
+            }
+        }
+
+        self.selected_entry = selected_node
+            .and_then(|node_ix| self.visible_entries.iter().position(|ix| *ix == node_ix))
+            .or_else(|| (!self.visible_entries.is_empty()).then_some(0));
+        cx.notify();
+    }
+
+    fn selected_node(&self) -> Option<usize> {
+        self.selected_entry
+            .and_then(|ix| self.visible_entries.get(ix).copied())
+    }
+
+    fn select_entry(&mut self, entry_ix: usize, cx: &mut ViewContext<Self>) {
+        self.selected_entry = Some(entry_ix);
+        self.scroll_handle
+            .scroll_to_item(entry_ix, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        let next = self.selected_entry.map_or(0, |ix| ix + 1);
+        if next < self.visible_entries.len() {
+            self.select_entry(next, cx);
+        }
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        let prev = self.selected_entry.map_or(0, |ix| ix.saturating_sub(1));
+        if prev < self.visible_entries.len() {
+            self.select_entry(prev, cx);
+        }
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+        if !self.visible_entries.is_empty() {
+            self.select_entry(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
+        if let Some(last) = self.visible_entries.len().checked_sub(1) {
+            self.select_entry(last, cx);
+        }
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            self.expand_node(node_ix, cx);
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(node_ix) = self.selected_node() else {
+            return;
+        };
+        if self.nodes[node_ix].expanded {
+            self.collapse_node(node_ix, cx);
+        } else if let Some(parent_entry) = self.parent_entry(node_ix) {
+            self.select_entry(parent_entry, cx);
+        }
+    }
+
+    fn parent_entry(&self, node_ix: usize) -> Option<usize> {
+        let entry_ix = self.visible_entries.iter().position(|ix| *ix == node_ix)?;
+        let depth = self.nodes[node_ix].depth;
+        self.visible_entries[..entry_ix]
+            .iter()
+            .rposition(|ix| self.nodes[*ix].depth < depth)
+    }
+
+    fn confirm(&mut self, _: &Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(node_ix) = self.selected_node() {
+            self.open_node(node_ix, true, cx);
+        }
+    }
+
+    /// Opens the node's call site, if any, or the symbol itself.
+    fn open_node(&mut self, node_ix: usize, focus: bool, cx: &mut ViewContext<Self>) {
+        let Some(node) = self.nodes.get(node_ix) else {
+            return;
+        };
+        let (buffer, range) = match node.call_sites.first() {
+            Some(call_site) => (call_site.buffer.clone(), call_site.range.clone()),
+            None => (
+                node.item.location.buffer.clone(),
+                node.item.selection_range.clone(),
+            ),
+        };
+        self.open_location(buffer, range, focus, cx);
+    }
+
+    fn open_location(
+        &mut self,
+        buffer: Model<Buffer>,
+        range: Range<Anchor>,
+        focus: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let range = range.to_offset(buffer.read(cx));
+        workspace.update(cx, |workspace, cx| {
+            let pane = workspace.active_pane().clone();
+            let editor = workspace.open_project_item::<Editor>(pane, buffer, focus, focus, cx);
+            editor.update(cx, |editor, cx| {
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([range.start..range.start])
+                });
+            });
+        });
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let kind_button = |id: &'static str, kind: HierarchyKind, cx: &mut ViewContext<Self>| {
+            Button::new(id, kind.label())
+                .label_size(LabelSize::Small)
+                .toggle_state(self.kind == kind)
+                .on_click(cx.listener(move |panel, _, cx| panel.set_kind(kind, cx)))
+        };
+
+        let header = h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border);
+        if self.kind.is_type_hierarchy() {
+            header
+                .child(kind_button("supertypes", HierarchyKind::Supertypes, cx))
+                .child(kind_button("subtypes", HierarchyKind::Subtypes, cx))
+        } else {
+            header
+                .child(kind_button(
+                    "incoming-calls",
+                    HierarchyKind::IncomingCalls,
+                    cx,
+                ))
+                .child(kind_button(
+                    "outgoing-calls",
+                    HierarchyKind::OutgoingCalls,
+                    cx,
+                ))
+        }
+    }
+
+    fn render_entry(&self, entry_ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let node_ix = self.visible_entries[entry_ix];
+        let node = &self.nodes[node_ix];
+        let settings = HierarchyPanelSettings::get_global(cx);
+        let is_selected = self.selected_entry == Some(entry_ix);
+        let is_loading = self.fetch_tasks.contains_key(&node_ix);
+        let has_children = node
+            .children
+            .as_ref()
+            .map_or(true, |children| !children.is_empty());
+
+        let path = node
+            .item
+            .location
+            .buffer
+            .read(cx)
+            .file()
+            .map(|file| file.path().to_string_lossy().into_owned());
+        let call_count = node.call_sites.len();
+
+        ListItem::new(("hierarchy-entry", node_ix))
+            .indent_level(node.depth)
+            .indent_step_size(px(settings.indent_size))
+            .toggle_state(is_selected)
+            .toggle(has_children.then_some(node.expanded))
+            .on_toggle(cx.listener(move |panel, _, cx| panel.toggle_expanded(node_ix, cx)))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(node.item.name().to_string()))
+                    .when_some(node.item.detail(), |this, detail| {
+                        this.child(
+                            Label::new(detail.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .when(call_count > 1, |this| {
+                        this.child(
+                            Label::new(format!("×{call_count}"))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .when(is_loading, |this| {
+                        this.child(
+                            Label::new("Loading…")
+                                .size(LabelSize::Small)
+                                .color(Color::Placeholder),
+                        )
+                    }),
+            )
+            .when_some(path, |this, path| {
+                let tooltip_path = path.clone();
+                this.end_slot(
+                    Label::new(path)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .single_line(),
+                )
+                .tooltip(move |cx| Tooltip::text(tooltip_path.clone(), cx))
+            })
+            .on_click(cx.listener(move |panel, event: &ClickEvent, cx| {
+                if event.down.button == MouseButton::Right {
+                    return;
+                }
+                if let Some(entry_ix) = panel.visible_entries.iter().position(|ix| *ix == node_ix) {
+                    panel.selected_entry = Some(entry_ix);
+                }
+                panel.open_node(node_ix, event.down.click_count > 1, cx);
+                cx.notify();
+            }))
+    }
+
+    fn render_empty_state(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let message = if self.origin.is_none() {
+            "Run a \"Show Incoming Calls\" or \"Show Supertypes\" action on a symbol".to_string()
+        } else if self.nodes.is_empty() {
+            if self.kind.is_type_hierarchy() {
+                "No type hierarchy for this symbol".to_string()
+            } else {
+                "No call hierarchy for this symbol".to_string()
+            }
+        } else {
+            format!("No {}", self.kind.label().to_lowercase())
+        };
+        v_flex()
+            .flex_1()
+            .justify_center()
+            .items_center()
+            .p_4()
+            .child(
+                Label::new(message)
+                    .size(LabelSize::Small)
+                    .color(Color::Placeholder),
+            )
+            .text_ui_sm(cx)
+    }
+}
+
+impl Render for HierarchyPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let entry_count = self.visible_entries.len();
+        v_flex()
+            .id("hierarchy-panel")
+            .key_context(self.dispatch_context())
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .overflow_hidden()
+            .bg(cx.theme().colors().panel_background)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .child(self.render_header(cx))
+            .map(|this| {
+                if entry_count == 0 {
+                    this.child(self.render_empty_state(cx))
+                } else {
+                    this.child(
+                        uniform_list(cx.view().clone(), "hierarchy-entries", entry_count, {
+                            |panel, range, cx| {
+                                range
+                                    .map(|entry_ix| panel.render_entry(entry_ix, cx))
+                                    .collect()
+                            }
+                        })
+                        .size_full()
+                        .with_sizing_behavior(ListSizingBehavior::Infer)
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                }
+            })
+    }
+}
+
+impl FocusableView for HierarchyPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<Event> for HierarchyPanel {}
+
+impl EventEmitter<PanelEvent> for HierarchyPanel {}
+
+impl Panel for HierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "Hierarchy Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        HierarchyPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<HierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| HierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        HierarchyPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Route)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt as _;
+    use gpui::{TestAppContext, VisualTestContext, WindowHandle};
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, Point};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    use super::*;
+
+    const SELECTED_MARKER: &str = "  <==== selected";
+
+    #[gpui::test]
+    async fn test_call_hierarchy_panel(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "fn a() {\n    d();\n}\nfn d() {}\n",
+                "b.rs": "fn b() {\n    a();\n}\n",
+                "c.rs": "fn c() {\n    b();\n    b();\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(rust_lang());
+        let mut fake_servers = language_registry.register_fake_lsp(
+            "Rust",
+            FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let (buffer, _handle) = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+            })
+            .await
+            .unwrap();
+        let fake_server = fake_servers.next().await.unwrap();
+
+        fn range(row: u32, column: u32) -> lsp::Range {
+            lsp::Range::new(
+                lsp::Position::new(row, column),
+                lsp::Position::new(row, column + 1),
+            )
+        }
+        fn item(name: &str, path: &str, row: u32) -> lsp::CallHierarchyItem {
+            lsp::CallHierarchyItem {
+                name: name.to_string(),
+                kind: lsp::SymbolKind::FUNCTION,
+                tags: None,
+                detail: None,
+                uri: lsp::Url::from_file_path(path).unwrap(),
+                range: range(row, 3),
+                selection_range: range(row, 3),
+                data: None,
+            }
+        }
+        let a_item = item("a", "/dir/a.rs", 0);
+        fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+            let a_item = a_item.clone();
+            move |_, _| {
+                let a_item = a_item.clone();
+                async move { Ok(Some(vec![a_item])) }
+            }
+        });
+        fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+            move |params, _| async move {
+                let calls = match params.item.name.as_str() {
+                    "a" => vec![lsp::CallHierarchyIncomingCall {
+                        from: item("b", "/dir/b.rs", 0),
+                        from_ranges: vec![range(1, 4)],
+                    }],
+                    "b" => vec![lsp::CallHierarchyIncomingCall {
+                        from: item("c", "/dir/c.rs", 0),
+                        from_ranges: vec![range(1, 4), range(2, 4)],
+                    }],
+                    _ => Vec::new(),
+                };
+                Ok(Some(calls))
+            },
+        );
+        fake_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+            move |params, _| async move {
+                let calls = match params.item.name.as_str() {
+                    "a" => vec![lsp::CallHierarchyOutgoingCall {
+                        to: item("d", "/dir/a.rs", 3),
+                        from_ranges: vec![range(1, 4)],
+                    }],
+                    _ => Vec::new(),
+                };
+                Ok(Some(calls))
+            },
+        );
+
+        let workspace = add_hierarchy_panel(&project, cx).await;
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = hierarchy_panel(&workspace, cx);
+
+        // The root is expanded right away.
+        panel.update(cx, |panel, cx| {
+            let position = buffer.read(cx).anchor_before(3);
+            panel.show(HierarchyKind::IncomingCalls, buffer.clone(), position, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            display_entries(&panel, cx),
+            [format!("a{SELECTED_MARKER}"), "  b".to_string()]
+        );
+
+        // Other nodes are expanded on demand.
+        panel.update(cx, |panel, cx| {
+            panel.select_next(&SelectNext, cx);
+            panel.expand_selected_entry(&ExpandSelectedEntry, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            display_entries(&panel, cx),
+            [
+                "a".to_string(),
+                format!("  b{SELECTED_MARKER}"),
+                "    c".to_string(),
+            ]
+        );
+
+        // Collapsing a node hides its children, and collapsing it again selects its parent.
+        panel.update(cx, |panel, cx| {
+            panel.collapse_selected_entry(&CollapseSelectedEntry, cx);
+        });
+        assert_eq!(
+            display_entries(&panel, cx),
+            ["a".to_string(), format!("  b{SELECTED_MARKER}")]
+        );
+        panel.update(cx, |panel, cx| {
+            panel.collapse_selected_entry(&CollapseSelectedEntry, cx);
+        });
+        assert_eq!(
+            display_entries(&panel, cx),
+            [format!("a{SELECTED_MARKER}"), "  b".to_string()]
+        );
+
+        // Confirming a node opens its first call site, or the symbol itself for roots.
+        panel.update(cx, |panel, cx| {
+            panel.select_last(&SelectLast, cx);
+            panel.expand_selected_entry(&ExpandSelectedEntry, cx);
+            panel.select_last(&SelectLast, cx);
+            panel.confirm(&Confirm, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            active_location(&workspace, cx),
+            (Path::new("c.rs").into(), Point::new(1, 4))
+        );
+        panel.update(cx, |panel, cx| {
+            panel.select_first(&SelectFirst, cx);
+            panel.confirm(&Confirm, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            active_location(&workspace, cx),
+            (Path::new("a.rs").into(), Point::new(0, 3))
+        );
+
+        // Switching the direction queries the hierarchy of the same symbol again.
+        panel.update(cx, |panel, cx| {
+            panel.set_kind(HierarchyKind::OutgoingCalls, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            display_entries(&panel, cx),
+            [format!("a{SELECTED_MARKER}"), "  d".to_string()]
+        );
+        panel.update(cx, |panel, cx| {
+            panel.set_kind(HierarchyKind::IncomingCalls, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            display_entries(&panel, cx),
+            [format!("a{SELECTED_MARKER}"), "  b".to_string()]
+        );
+    }
+
+    fn display_entries(panel: &View<HierarchyPanel>, cx: &mut VisualTestContext) -> Vec<String> {
+        panel.update(cx, |panel, _| {
+            panel
+                .visible_entries
+                .iter()
+                .enumerate()
+                .map(|(entry_ix, node_ix)| {
+                    let node = &panel.nodes[*node_ix];
+                    let marker = if panel.selected_entry == Some(entry_ix) {
+                        SELECTED_MARKER
+                    } else {
+                        ""
+                    };
+                    format!("{}{}{marker}", "  ".repeat(node.depth), node.item.name())
+                })
+                .collect()
+        })
+    }
+
+    /// Returns the path of the active editor's file and the position of its cursor.
+    fn active_location(
+        workspace: &WindowHandle<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> (Arc<Path>, Point) {
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .active_item_as::<Editor>(cx)
+                    .expect("no active editor")
+            })
+            .unwrap();
+        editor.update(cx, |editor, cx| {
+            let buffer = editor.buffer().read(cx).as_singleton().unwrap();
+            let path = buffer.read(cx).file().unwrap().path().clone();
+            (path, editor.selections.newest::<Point>(cx).head())
+        })
+    }
+
+    async fn add_hierarchy_panel(
+        project: &Model<Project>,
+        cx: &mut TestAppContext,
+    ) -> WindowHandle<Workspace> {
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let panel = window
+            .update(cx, |_, cx| cx.spawn(HierarchyPanel::load))
+            .unwrap()
+            .await
+            .expect("Failed to load hierarchy panel");
+        window
+            .update(cx, |workspace, cx| workspace.add_panel(panel, cx))
+            .unwrap();
+        window
+    }
+
+    fn hierarchy_panel(
+        workspace: &WindowHandle<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<HierarchyPanel> {
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .panel::<HierarchyPanel>(cx)
+                    .expect("no hierarchy panel")
+            })
+            .unwrap()
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings = SettingsStore::test(cx);
+            cx.set_global(settings);
+
+            theme::init(theme::LoadThemes::JustBase, cx);
+
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            super::init(cx);
+        });
+    }
+}
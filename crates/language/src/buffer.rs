@@ -3256,6 +3256,90 @@ impl BufferSnapshot {
             .filter_map(|(range, obj)| (obj == TextObject::InsideFunction).then_some(range))
     }
 
+    /// Returns the ranges of natural-language text that intersect the given
+    /// range, sorted and non-overlapping.
+    ///
+    /// These are the comments and strings captured by each grammar's
+    /// highlights query, along with anything captured as `@spell` (such as
+    /// Markdown paragraphs). Escape sequences, inline code and anything
+    /// captured as `@nospell` are left out.
+    pub fn spell_check_ranges<T: ToOffset>(&self, range: Range<T>) -> Vec<Range<usize>> {
+        let range = range.to_offset(self);
+        let mut captures = self.syntax.captures(range.clone(), &self.text, |grammar| {
+            grammar.highlights_query.as_ref()
+        });
+        let capture_kinds = captures
+            .grammars()
+            .iter()
+            .map(|grammar| {
+                grammar
+                    .highlights_query
+                    .as_ref()
+                    .map(|query| {
+                        query
+                            .capture_names()
+                            .iter()
+                            .map(|name| match *name {
+                                "comment" | "comment.doc" | "string" | "spell" => Some(true),
+                                "nospell" | "text.literal" | "link_uri" => Some(false),
+                                name if name.starts_with("string.") => Some(false),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut included = Vec::<Range<usize>>::new();
+        let mut excluded = Vec::<Range<usize>>::new();
+        while let Some(capture) = captures.peek() {
+            let node_range = capture.node.byte_range();
+            let node_range = node_range.start.max(range.start)..node_range.end.min(range.end);
+            match capture_kinds[capture.grammar_index].get(capture.index as usize) {
+                Some(Some(true)) => included.push(node_range),
+                Some(Some(false)) => excluded.push(node_range),
+                _ => {}
+            }
+            captures.advance();
+        }
+
+        included.sort_unstable_by_key(|range| (range.start, range.end));
+        excluded.sort_unstable_by_key(|range| (range.start, range.end));
+        let mut excluded = excluded.into_iter().peekable();
+        let mut ranges = Vec::<Range<usize>>::new();
+        for range in included {
+            match ranges.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => ranges.push(range),
+            }
+        }
+
+        let mut result = Vec::with_capacity(ranges.len());
+        for mut range in ranges {
+            while let Some(excluded_range) = excluded.peek() {
+                if excluded_range.end <= range.start {
+                    excluded.next();
+                } else if excluded_range.start < range.end {
+                    if excluded_range.start > range.start {
+                        result.push(range.start..excluded_range.start);
+                    }
+                    range.start = excluded_range.end.min(range.end);
+                    if excluded_range.end > range.end {
+                        break;
+                    }
+                    excluded.next();
+                } else {
+                    break;
+                }
+            }
+            if range.start < range.end {
+                result.push(range);
+            }
+        }
+        result
+    }
+
     /// For each grammar in the language, runs the provided
     /// [`tree_sitter::Query`] against the given range.
     pub fn matches(
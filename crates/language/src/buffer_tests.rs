@@ -701,6 +701,49 @@ async fn test_outline(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+async fn test_spell_check_ranges(cx: &mut gpui::TestAppContext) {
+    let language = rust_lang()
+        .with_highlights_query(
+            r#"
+            (line_comment) @comment
+            (string_literal) @string
+            (escape_sequence) @string.escape
+            (identifier) @variable
+            "#,
+        )
+        .unwrap();
+    let text = r#"
+        // Teh comment
+        fn main() {
+            let greeting = "helo\nworld";
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let ranges = |range: Range<usize>| {
+            snapshot
+                .spell_check_ranges(range)
+                .into_iter()
+                .map(|range| snapshot.text_for_range(range).collect::<String>())
+                .map(|text| text.trim_end().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ranges(0..snapshot.len()),
+            ["// Teh comment", "\"helo", "world\""]
+        );
+
+        let greeting = snapshot.text().find("helo").unwrap();
+        assert_eq!(ranges(greeting..greeting + 2), ["he"]);
+    });
+}
+
 #[gpui::test]
 async fn test_outline_nodes_with_newlines(cx: &mut gpui::TestAppContext) {
     let text = r#"
@@ -13,6 +13,7 @@ mod language_registry;
 pub mod language_settings;
 mod outline;
 pub mod proto;
+mod structural_search;
 mod syntax_map;
 mod task_context;
 mod toolchain;
@@ -78,6 +79,7 @@ pub use language_registry::{
 };
 pub use lsp::LanguageServerId;
 pub use outline::*;
+pub use structural_search::{StructuralMatch, StructuralPattern};
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer, TreeSitterOptions};
 pub use text::{AnchorRangeExt, LineEnding};
 pub use tree_sitter::{Node, Parser, Tree, TreeCursor};
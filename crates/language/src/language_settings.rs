@@ -136,6 +136,14 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code with semantic tokens from language servers,
+    /// on top of the tree-sitter highlights.
+    pub semantic_tokens: bool,
+    /// Whether to fold code using the folding ranges reported by language servers,
+    /// instead of the indentation of its lines.
+    pub lsp_folding_ranges: bool,
+    /// Whether to show the code lenses of language servers above the lines they apply to.
+    pub code_lens: bool,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
     /// Whether to pop the completions menu while typing in an editor without
@@ -380,6 +388,23 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to highlight code with semantic tokens from language servers that support them.
+    /// Semantic tokens are layered on top of the tree-sitter highlights, and can
+    /// distinguish things like mutable bindings or unsafe calls.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
+    /// Whether to fold code using the folding ranges reported by language servers that support them,
+    /// instead of the indentation of its lines. Server ranges cover things like import blocks,
+    /// multiline comments and `#region` markers.
+    ///
+    /// Default: false
+    pub lsp_folding_ranges: Option<bool>,
+    /// Whether to show the code lenses of language servers that support them above the lines they apply to,
+    /// e.g. actions to run a test or the number of references to a function. Clicking a lens runs its command.
+    ///
+    /// Default: false
+    pub code_lens: Option<bool>,
     /// Whether indentation of pasted content should be adjusted based on the context.
     ///
     /// Default: true
@@ -1166,6 +1191,9 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.lsp_folding_ranges, src.lsp_folding_ranges);
+    merge(&mut settings.code_lens, src.code_lens);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
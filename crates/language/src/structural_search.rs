@@ -0,0 +1,586 @@
+//! Structural search: finding code by the shape of its syntax tree instead of its text.
+//!
+//! A structural pattern is a snippet of code that may contain metavariables. `$NAME` matches
+//! any single syntax node, and `$$$NAME` matches any sequence of sibling nodes, such as the
+//! arguments of a call. A metavariable that occurs more than once has to match the same text
+//! each time, except for `$_` and `$$$_`, which match anything. For example, `foo($A, $B)`
+//! matches `foo(x + 1, bar())` no matter how it is formatted.
+//!
+//! Patterns are parsed with the grammar of each syntax layer they're matched against, so one
+//! pattern works across languages whose syntax it's valid in.
+
+use crate::{with_parser, BufferSnapshot, LanguageName};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use parking_lot::Mutex;
+use std::{fmt, ops::Range, sync::Arc};
+use tree_sitter::{Node, Tree};
+
+/// The identifiers metavariables are replaced with before parsing a pattern, since `$` isn't
+/// valid in the identifiers of most languages.
+const METAVARIABLE_PLACEHOLDER_PREFIX: &str = "__zed_var_";
+const SEQUENCE_METAVARIABLE_PLACEHOLDER_PREFIX: &str = "__zed_vars_";
+const ANONYMOUS_METAVARIABLE: &str = "_";
+
+/// A code pattern with metavariables, matched against syntax trees.
+pub struct StructuralPattern {
+    source: String,
+    /// The pattern with its metavariables replaced by placeholder identifiers.
+    placeholder_source: String,
+    metavariables: HashMap<String, Metavariable>,
+    /// The longest word of the pattern outside of metavariables, which every match contains.
+    literal: Option<String>,
+    trees: Mutex<HashMap<LanguageName, Option<Arc<Tree>>>>,
+}
+
+#[derive(Clone, Debug)]
+struct Metavariable {
+    name: String,
+    is_sequence: bool,
+}
+
+/// A range of a buffer matched by a [`StructuralPattern`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    /// The ranges matched by the named metavariables of the pattern.
+    pub captures: Vec<(String, Range<usize>)>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StructuralPattern")
+            .field(&self.source)
+            .finish()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        if pattern.trim().is_empty() {
+            return Err(anyhow!("structural pattern is empty"));
+        }
+
+        let mut placeholder_source = String::with_capacity(pattern.len());
+        let mut literal_source = String::with_capacity(pattern.len());
+        let mut metavariables = HashMap::default();
+        for token in tokenize(pattern) {
+            match token {
+                PatternToken::Text(text) => {
+                    placeholder_source.push_str(text);
+                    literal_source.push_str(text);
+                }
+                PatternToken::Metavariable { name, is_sequence } => {
+                    let placeholder = placeholder(name, is_sequence);
+                    placeholder_source.push_str(&placeholder);
+                    literal_source.push(' ');
+                    metavariables.insert(
+                        placeholder,
+                        Metavariable {
+                            name: name.to_string(),
+                            is_sequence,
+                        },
+                    );
+                }
+            }
+        }
+
+        if metavariables.contains_key(placeholder_source.trim()) {
+            return Err(anyhow!(
+                "structural pattern needs code besides a metavariable"
+            ));
+        }
+
+        let literal = literal_source
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .max_by_key(|word| word.len())
+            .filter(|word| word.len() > 1)
+            .map(str::to_string);
+
+        Ok(Self {
+            source: pattern.to_string(),
+            placeholder_source,
+            metavariables,
+            literal,
+            trees: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns text that every match of the pattern contains, for skipping files quickly.
+    pub fn literal(&self) -> Option<&str> {
+        self.literal.as_deref()
+    }
+
+    /// Finds the non-overlapping matches of the pattern whose nodes lie within the range.
+    pub fn search(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let Some(tree) = self.tree_for_language(layer.language) else {
+                continue;
+            };
+            let Some(pattern_root) = pattern_root(&tree, &self.placeholder_source) else {
+                continue;
+            };
+
+            let matcher = Matcher {
+                pattern: self,
+                buffer,
+            };
+            let mut bindings = Vec::new();
+            let mut cursor = layer.node().walk();
+            'traversal: loop {
+                let node = cursor.node();
+                let mut descend = node.end_byte() > range.start && node.start_byte() < range.end;
+                if descend
+                    && node.kind_id() == pattern_root.kind_id()
+                    && node.start_byte() >= range.start
+                    && node.end_byte() <= range.end
+                {
+                    bindings.clear();
+                    if matcher.match_node(pattern_root, node, &mut bindings) {
+                        matches.push(StructuralMatch {
+                            range: node.byte_range(),
+                            captures: bindings
+                                .iter()
+                                .map(|(name, range)| (name.to_string(), range.clone()))
+                                .collect(),
+                        });
+                        descend = false;
+                    }
+                }
+
+                if descend && cursor.goto_first_child() {
+                    continue;
+                }
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        break 'traversal;
+                    }
+                }
+            }
+        }
+
+        // Matches in injected languages may overlap the ones of their parent layer.
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        let mut end = 0;
+        matches.retain(|mat| {
+            let keep = mat.range.start >= end;
+            if keep {
+                end = mat.range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Returns the replacement for a match, with the metavariables of the replacement
+    /// substituted by the text they captured. Unknown metavariables are kept as is.
+    pub fn replace(
+        &self,
+        replacement: &str,
+        buffer: &BufferSnapshot,
+        mat: &StructuralMatch,
+    ) -> String {
+        let mut result = String::with_capacity(replacement.len());
+        for token in tokenize(replacement) {
+            match token {
+                PatternToken::Text(text) => result.push_str(text),
+                PatternToken::Metavariable { name, is_sequence } => {
+                    match mat
+                        .captures
+                        .iter()
+                        .find(|(captured_name, _)| captured_name == name)
+                    {
+                        Some((_, range)) => {
+                            result.extend(buffer.text_for_range(range.clone()));
+                        }
+                        None => {
+                            result.push_str(if is_sequence { "$$$" } else { "$" });
+                            result.push_str(name);
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    fn tree_for_language(&self, language: &Arc<crate::Language>) -> Option<Arc<Tree>> {
+        self.trees
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| {
+                let grammar = language.grammar()?;
+                let tree = with_parser(|parser| {
+                    parser.set_language(&grammar.ts_language).ok()?;
+                    parser.parse(&self.placeholder_source, None)
+                })?;
+                Some(Arc::new(tree))
+            })
+            .clone()
+    }
+
+    fn metavariable(&self, node: Node) -> Option<&Metavariable> {
+        self.metavariables
+            .get(self.placeholder_source.get(node.byte_range())?)
+    }
+}
+
+/// Returns the deepest node spanning the whole pattern, unless the pattern has syntax errors.
+fn pattern_root<'a>(tree: &'a Tree, source: &str) -> Option<Node<'a>> {
+    let start = source.len() - source.trim_start().len();
+    let end = source.trim_end().len();
+    let covers_pattern = |node: &Node| node.start_byte() <= start && node.end_byte() >= end;
+
+    let mut node = tree.root_node();
+    loop {
+        let mut cursor = node.walk();
+        let Some(child) = node.children(&mut cursor).find(covers_pattern) else {
+            break;
+        };
+        node = child;
+    }
+    (covers_pattern(&node) && !node.has_error()).then_some(node)
+}
+
+struct Matcher<'a> {
+    pattern: &'a StructuralPattern,
+    buffer: &'a BufferSnapshot,
+}
+
+impl<'a> Matcher<'a> {
+    fn match_node(
+        &self,
+        pattern_node: Node,
+        node: Node,
+        bindings: &mut Vec<(&'a str, Range<usize>)>,
+    ) -> bool {
+        if let Some(metavariable) = self.pattern.metavariable(pattern_node) {
+            return self.bind(metavariable, node.byte_range(), bindings);
+        }
+        if pattern_node.kind_id() != node.kind_id() {
+            return false;
+        }
+
+        let pattern_children = significant_children(pattern_node);
+        let children = significant_children(node);
+        if pattern_children.is_empty() {
+            return children.is_empty()
+                && self
+                    .buffer
+                    .text_for_range(node.byte_range())
+                    .eq_by_chars(&self.pattern.placeholder_source[pattern_node.byte_range()]);
+        }
+        self.match_siblings(&pattern_children, &children, bindings)
+    }
+
+    fn match_siblings(
+        &self,
+        pattern_nodes: &[Node],
+        nodes: &[Node],
+        bindings: &mut Vec<(&'a str, Range<usize>)>,
+    ) -> bool {
+        let Some((pattern_node, pattern_rest)) = pattern_nodes.split_first() else {
+            return nodes.is_empty();
+        };
+        let bindings_len = bindings.len();
+
+        if let Some(metavariable) = self
+            .pattern
+            .metavariable(*pattern_node)
+            .filter(|metavariable| metavariable.is_sequence)
+        {
+            // Match as few nodes as possible, backtracking when the rest doesn't match.
+            for count in 0..=nodes.len() {
+                let range = match (nodes.first(), nodes[..count].last()) {
+                    (Some(first), Some(last)) => first.start_byte()..last.end_byte(),
+                    (Some(first), None) => first.start_byte()..first.start_byte(),
+                    (None, _) => 0..0,
+                };
+                if self.bind(metavariable, range, bindings)
+                    && self.match_siblings(pattern_rest, &nodes[count..], bindings)
+                {
+                    return true;
+                }
+                bindings.truncate(bindings_len);
+            }
+            return false;
+        }
+
+        let Some((node, rest)) = nodes.split_first() else {
+            return false;
+        };
+        if self.match_node(*pattern_node, *node, bindings)
+            && self.match_siblings(pattern_rest, rest, bindings)
+        {
+            return true;
+        }
+        bindings.truncate(bindings_len);
+        false
+    }
+
+    fn bind(
+        &self,
+        metavariable: &'a Metavariable,
+        range: Range<usize>,
+        bindings: &mut Vec<(&'a str, Range<usize>)>,
+    ) -> bool {
+        if metavariable.name == ANONYMOUS_METAVARIABLE {
+            return true;
+        }
+        if let Some((_, bound_range)) = bindings.iter().find(|(name, _)| *name == metavariable.name)
+        {
+            let bound_text = self
+                .buffer
+                .text_for_range(bound_range.clone())
+                .collect::<String>();
+            return self.buffer.text_for_range(range).eq_by_chars(&bound_text);
+        }
+        bindings.push((&metavariable.name, range));
+        true
+    }
+}
+
+/// Returns the children of the node, without comments and other extras. Trailing commas
+/// are left out too, so that formatting doesn't affect matches.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let mut children = node
+        .children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect::<Vec<_>>();
+    if let [.., comma, closing] = children.as_slice() {
+        if !comma.is_named()
+            && comma.kind() == ","
+            && !closing.is_named()
+            && matches!(closing.kind(), ")" | "]" | "}" | ">")
+        {
+            children.remove(children.len() - 2);
+        }
+    }
+    children
+}
+
+trait EqByChars {
+    fn eq_by_chars(self, text: &str) -> bool;
+}
+
+impl<'a, T: Iterator<Item = &'a str>> EqByChars for T {
+    fn eq_by_chars(self, mut text: &str) -> bool {
+        for chunk in self {
+            match text.strip_prefix(chunk) {
+                Some(rest) => text = rest,
+                None => return false,
+            }
+        }
+        text.is_empty()
+    }
+}
+
+fn placeholder(name: &str, is_sequence: bool) -> String {
+    if is_sequence {
+        format!("{SEQUENCE_METAVARIABLE_PLACEHOLDER_PREFIX}{name}")
+    } else {
+        format!("{METAVARIABLE_PLACEHOLDER_PREFIX}{name}")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum PatternToken<'a> {
+    Text(&'a str),
+    Metavariable { name: &'a str, is_sequence: bool },
+}
+
+/// Splits a pattern or replacement into text and metavariables.
+fn tokenize(pattern: &str) -> Vec<PatternToken> {
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut offset = 0;
+    while let Some(dollar) = pattern[offset..].find('$').map(|ix| offset + ix) {
+        let dollars = pattern[dollar..]
+            .find(|c| c != '$')
+            .unwrap_or(pattern.len() - dollar);
+        let name_start = dollar + dollars;
+        let name_len = pattern[name_start..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(pattern.len() - name_start);
+        let name = &pattern[name_start..name_start + name_len];
+        let starts_like_identifier = name
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_ascii_alphabetic() || c == '_');
+
+        if (dollars == 1 || dollars == 3) && starts_like_identifier {
+            if text_start < dollar {
+                tokens.push(PatternToken::Text(&pattern[text_start..dollar]));
+            }
+            tokens.push(PatternToken::Metavariable {
+                name,
+                is_sequence: dollars == 3,
+            });
+            text_start = name_start + name_len;
+        }
+        offset = name_start + name_len;
+    }
+    if text_start < pattern.len() {
+        tokens.push(PatternToken::Text(&pattern[text_start..]));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Buffer, Language, LanguageConfig, LanguageMatcher};
+    use gpui::{Context as _, TestAppContext};
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("foo($A, $$$REST) + $ 1 $$B"),
+            vec![
+                PatternToken::Text("foo("),
+                PatternToken::Metavariable {
+                    name: "A",
+                    is_sequence: false
+                },
+                PatternToken::Text(", "),
+                PatternToken::Metavariable {
+                    name: "REST",
+                    is_sequence: true
+                },
+                PatternToken::Text(") + $ 1 $$B"),
+            ]
+        );
+        assert_eq!(
+            tokenize("$_"),
+            vec![PatternToken::Metavariable {
+                name: "_",
+                is_sequence: false
+            }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(StructuralPattern::new("  ").is_err());
+        assert!(StructuralPattern::new(" $A ").is_err());
+        assert_eq!(
+            StructuralPattern::new("$A.unwrap_or($B)")
+                .unwrap()
+                .literal(),
+            Some("unwrap_or")
+        );
+    }
+
+    #[gpui::test]
+    async fn test_structural_search(cx: &mut TestAppContext) {
+        let text = r#"
+            fn main() {
+                foo(a, b);
+                foo(
+                    bar(1),
+                    // a comment
+                    c.d(),
+                );
+                foo(a);
+                x.foo(a, b);
+                assert_eq!(y, y);
+                assert_eq!(y, z);
+                log(1, 2, 3);
+            }
+        "#
+        .unindent();
+        let buffer = cx.new_model(|cx| {
+            Buffer::local(text.clone(), cx).with_language(Arc::new(rust_lang()), cx)
+        });
+        cx.run_until_parked();
+        let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+
+        let search = |pattern: &str| {
+            let pattern = StructuralPattern::new(pattern).unwrap();
+            pattern
+                .search(&snapshot, 0..snapshot.len())
+                .into_iter()
+                .map(|mat| {
+                    (
+                        text[mat.range.clone()].to_string(),
+                        mat.captures
+                            .iter()
+                            .map(|(name, range)| format!("{name}={}", &text[range.clone()]))
+                            .collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search("foo($A, $B)"),
+            vec![
+                ("foo(a, b)".to_string(), vec!["A=a".into(), "B=b".into()]),
+                (
+                    "foo(\n        bar(1),\n        // a comment\n        c.d(),\n    )"
+                        .to_string(),
+                    vec!["A=bar(1)".into(), "B=c.d()".into()]
+                ),
+            ]
+        );
+        assert_eq!(
+            search("$X.foo($_, $_)"),
+            vec![("x.foo(a, b)".to_string(), vec!["X=x".into()])]
+        );
+        assert_eq!(
+            search("assert_eq!($A, $A)"),
+            vec![("assert_eq!(y, y)".to_string(), vec!["A=y".into()])]
+        );
+        assert_eq!(
+            search("log($$$ARGS)"),
+            vec![("log(1, 2, 3)".to_string(), vec!["ARGS=1, 2, 3".into()])]
+        );
+        assert_eq!(
+            search("log($FIRST, $$$REST)"),
+            vec![(
+                "log(1, 2, 3)".to_string(),
+                vec!["FIRST=1".into(), "REST=2, 3".into()]
+            )]
+        );
+
+        let pattern = StructuralPattern::new("foo($A, $B)").unwrap();
+        let matches = pattern.search(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            pattern.replace("bar($B, $A, $C)", &snapshot, &matches[0]),
+            "bar(b, a, $C)"
+        );
+
+        // Only nodes within the range match.
+        let start = text.find("foo(a);").unwrap();
+        assert_eq!(
+            StructuralPattern::new("foo($A)")
+                .unwrap()
+                .search(&snapshot, start..start + "foo(a)".len()),
+            vec![StructuralMatch {
+                range: start..start + "foo(a)".len(),
+                captures: vec![("A".into(), start + 4..start + 5)],
+            }]
+        );
+    }
+
+    fn rust_lang() -> Language {
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+    }
+}
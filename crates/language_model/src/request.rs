@@ -237,6 +237,8 @@ pub struct LanguageModelRequest {
     pub tools: Vec<LanguageModelRequestTool>,
     pub stop: Vec<String>,
     pub temperature: Option<f32>,
+    /// The maximum number of tokens to generate, which can't exceed the model's own limit.
+    pub max_tokens: Option<u32>,
 }
 
 impl LanguageModelRequest {
@@ -263,7 +265,12 @@ impl LanguageModelRequest {
             stream,
             stop: self.stop,
             temperature: self.temperature.unwrap_or(1.0),
-            max_tokens: max_output_tokens,
+            max_tokens: match (self.max_tokens, max_output_tokens) {
+                (Some(max_tokens), Some(max_output_tokens)) => {
+                    Some(max_tokens.min(max_output_tokens))
+                }
+                (max_tokens, max_output_tokens) => max_tokens.or(max_output_tokens),
+            },
             tools: Vec::new(),
             tool_choice: None,
         }
@@ -289,7 +296,7 @@ impl LanguageModelRequest {
             generation_config: Some(google_ai::GenerationConfig {
                 candidate_count: Some(1),
                 stop_sequences: Some(self.stop),
-                max_output_tokens: None,
+                max_output_tokens: self.max_tokens.map(|max_tokens| max_tokens as usize),
                 temperature: self.temperature.map(|t| t as f64).or(Some(1.0)),
                 top_p: None,
                 top_k: None,
@@ -391,7 +398,9 @@ impl LanguageModelRequest {
         anthropic::Request {
             model,
             messages: new_messages,
-            max_tokens: max_output_tokens,
+            max_tokens: self.max_tokens.map_or(max_output_tokens, |max_tokens| {
+                max_tokens.min(max_output_tokens)
+            }),
             system: Some(system_message),
             tools: self
                 .tools
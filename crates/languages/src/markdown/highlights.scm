@@ -14,3 +14,5 @@
 (fenced_code_block
   (info_string
     (language) @text.literal))
+
+(paragraph) @spell
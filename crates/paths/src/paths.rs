@@ -266,6 +266,14 @@ pub fn languages_dir() -> &'static PathBuf {
     LANGUAGES_DIR.get_or_init(|| support_dir().join("languages"))
 }
 
+/// Returns the path to the dictionaries directory.
+///
+/// This is where Hunspell dictionaries used for spell checking are looked up first.
+pub fn dictionaries_dir() -> &'static PathBuf {
+    static DICTIONARIES_DIR: OnceLock<PathBuf> = OnceLock::new();
+    DICTIONARIES_DIR.get_or_init(|| config_dir().join("dictionaries"))
+}
+
 /// Returns the path to the Copilot directory.
 pub fn copilot_dir() -> &'static PathBuf {
     static COPILOT_DIR: OnceLock<PathBuf> = OnceLock::new();
@@ -305,6 +313,11 @@ pub fn local_tasks_file_relative_path() -> &'static Path {
     Path::new(".zed/tasks.json")
 }
 
+/// Returns the relative path to a `dictionary.txt` file within a project.
+pub fn local_dictionary_file_relative_path() -> &'static Path {
+    Path::new(".zed/dictionary.txt")
+}
+
 /// Returns the relative path to a `.vscode/tasks.json` file within a project.
 pub fn local_vscode_tasks_file_relative_path() -> &'static Path {
     Path::new(".vscode/tasks.json")
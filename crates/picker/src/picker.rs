@@ -10,7 +10,7 @@ use head::Head;
 use serde::Deserialize;
 use std::{sync::Arc, time::Duration};
 use ui::{prelude::*, v_flex, Color, Divider, Label, ListItem, ListItemSpacing};
-use workspace::ModalView;
+use workspace::{searchable::Direction, ModalView};
 
 mod head;
 pub mod highlighted_match_with_paths;
@@ -100,6 +100,16 @@ pub trait PickerDelegate: Sized + 'static {
     fn confirm_update_query(&mut self, _cx: &mut ViewContext<Picker<Self>>) -> Option<String> {
         None
     }
+    /// Override to have moving the selection up or down replace the query with
+    /// a previous one instead, such as when recalling a history of queries.
+    fn select_history(
+        &mut self,
+        _direction: Direction,
+        _query: &str,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        None
+    }
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>);
     /// Instead of interacting with currently selected entry, treats editor input literally,
     /// performing some kind of action on it.
@@ -294,6 +304,11 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     pub fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        let query = self.query(cx);
+        if let Some(query) = self.delegate.select_history(Direction::Next, &query, cx) {
+            self.set_query(query, cx);
+            return;
+        }
         let count = self.delegate.match_count();
         if count > 0 {
             let index = self.delegate.selected_index();
@@ -304,6 +319,11 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        let query = self.query(cx);
+        if let Some(query) = self.delegate.select_history(Direction::Prev, &query, cx) {
+            self.set_query(query, cx);
+            return;
+        }
         let count = self.delegate.match_count();
         if count > 0 {
             let index = self.delegate.selected_index();
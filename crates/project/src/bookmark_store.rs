@@ -0,0 +1,371 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use collections::HashMap;
+use gpui::{AppContext, EventEmitter, Model, ModelContext, SharedString, Subscription, WeakModel};
+use language::{Buffer, BufferEvent, BufferId, File};
+use text::{Anchor, Point, ToPoint as _};
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    worktree_store::WorktreeStore,
+};
+
+/// A bookmarked line in a project file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub abs_path: Arc<Path>,
+    /// The bookmarked row, which follows edits while the file is open.
+    pub row: u32,
+    /// The name of the bookmark, or `None` for an anonymous bookmark.
+    pub name: Option<SharedString>,
+}
+
+pub enum BookmarkStoreEvent {
+    BookmarksChanged,
+}
+
+/// Tracks the line bookmarks of a project.
+///
+/// While a bookmarked file is open, its bookmarks are anchored in the buffer
+/// so that they move along with edits. Bookmarks of files that aren't open
+/// only remember their row.
+pub struct BookmarkStore {
+    buffer_store: Model<BufferStore>,
+    worktree_store: Model<WorktreeStore>,
+    files: BTreeMap<Arc<Path>, FileBookmarks>,
+    paths_by_buffer: HashMap<BufferId, Arc<Path>>,
+    _subscription: Subscription,
+}
+
+#[derive(Default)]
+struct FileBookmarks {
+    /// Sorted by row, with at most one bookmark per row.
+    bookmarks: Vec<StoredBookmark>,
+    buffer: Option<OpenBuffer>,
+}
+
+struct OpenBuffer {
+    buffer: WeakModel<Buffer>,
+    _subscription: Subscription,
+}
+
+struct StoredBookmark {
+    row: u32,
+    anchor: Option<Anchor>,
+    name: Option<SharedString>,
+}
+
+impl EventEmitter<BookmarkStoreEvent> for BookmarkStore {}
+
+impl BookmarkStore {
+    pub fn new(
+        buffer_store: Model<BufferStore>,
+        worktree_store: Model<WorktreeStore>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let subscription = cx.subscribe(&buffer_store, |this, _, event, cx| match event {
+            BufferStoreEvent::BufferAdded(buffer) => {
+                if let Some(abs_path) = this.abs_path_for_buffer(buffer, cx) {
+                    if this.files.contains_key(&abs_path) {
+                        this.attach_buffer(buffer, abs_path, cx);
+                    }
+                }
+            }
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                if let Some(abs_path) = this.paths_by_buffer.remove(buffer_id) {
+                    if let Some(file) = this.files.get_mut(&abs_path) {
+                        file.buffer = None;
+                        for bookmark in &mut file.bookmarks {
+                            bookmark.anchor = None;
+                        }
+                    }
+                }
+            }
+            BufferStoreEvent::BufferChangedFilePath { buffer, old_file } => {
+                let old_path = old_file
+                    .as_ref()
+                    .and_then(|old_file| this.abs_path_for_file(old_file.as_ref(), cx));
+                let new_path = this.abs_path_for_buffer(buffer, cx);
+                if let Some((old_path, new_path)) = old_path.zip(new_path) {
+                    if let Some(file) = this.files.remove(&old_path) {
+                        this.paths_by_buffer
+                            .insert(buffer.read(cx).remote_id(), new_path.clone());
+                        this.files.insert(new_path, file);
+                        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+                    }
+                }
+            }
+        });
+
+        Self {
+            buffer_store,
+            worktree_store,
+            files: BTreeMap::new(),
+            paths_by_buffer: HashMap::default(),
+            _subscription: subscription,
+        }
+    }
+
+    /// Returns all bookmarks in the project, ordered by path and row.
+    pub fn bookmarks(&self) -> Vec<Bookmark> {
+        self.files
+            .iter()
+            .flat_map(|(abs_path, file)| {
+                file.bookmarks.iter().map(|bookmark| Bookmark {
+                    abs_path: abs_path.clone(),
+                    row: bookmark.row,
+                    name: bookmark.name.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the positions and names of the bookmarks in the given buffer.
+    pub fn bookmarks_for_buffer(
+        &self,
+        buffer_id: BufferId,
+    ) -> impl Iterator<Item = (Anchor, Option<&SharedString>)> {
+        self.paths_by_buffer
+            .get(&buffer_id)
+            .and_then(|abs_path| self.files.get(abs_path))
+            .into_iter()
+            .flat_map(|file| {
+                file.bookmarks
+                    .iter()
+                    .filter_map(|bookmark| Some((bookmark.anchor?, bookmark.name.as_ref())))
+            })
+    }
+
+    /// Returns the bookmark on the given row of a buffer, if any.
+    pub fn bookmark_at_row(
+        &self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        cx: &AppContext,
+    ) -> Option<Bookmark> {
+        let abs_path = self.abs_path_for_buffer(buffer, cx)?;
+        let file = self.files.get(&abs_path)?;
+        let bookmark = file.bookmarks.iter().find(|bookmark| bookmark.row == row)?;
+        Some(Bookmark {
+            abs_path,
+            row,
+            name: bookmark.name.clone(),
+        })
+    }
+
+    /// Adds an anonymous bookmark on the given row of a buffer, or removes
+    /// the bookmark that is already there.
+    pub fn toggle_bookmark(
+        &mut self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = self.abs_path_for_buffer(buffer, cx) else {
+            return;
+        };
+        if self.remove_bookmark(&abs_path, row, cx) {
+            return;
+        }
+        self.insert_bookmark(buffer, abs_path, row, None, cx);
+    }
+
+    /// Names the bookmark on the given row of a buffer, adding it if needed.
+    pub fn name_bookmark(
+        &mut self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        name: Option<SharedString>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = self.abs_path_for_buffer(buffer, cx) else {
+            return;
+        };
+        self.insert_bookmark(buffer, abs_path, row, name, cx);
+    }
+
+    /// Removes the bookmark on the given row of a file, returning whether
+    /// there was one.
+    pub fn remove_bookmark(
+        &mut self,
+        abs_path: &Path,
+        row: u32,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let Some(file) = self.files.get_mut(abs_path) else {
+            return false;
+        };
+        let Some(ix) = file
+            .bookmarks
+            .iter()
+            .position(|bookmark| bookmark.row == row)
+        else {
+            return false;
+        };
+        file.bookmarks.remove(ix);
+        if file.bookmarks.is_empty() {
+            self.files.remove(abs_path);
+            self.paths_by_buffer
+                .retain(|_, path| path.as_ref() != abs_path);
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        true
+    }
+
+    /// Replaces all bookmarks, such as when restoring them from a previous
+    /// session.
+    pub fn set_bookmarks(&mut self, bookmarks: Vec<Bookmark>, cx: &mut ModelContext<Self>) {
+        self.files.clear();
+        self.paths_by_buffer.clear();
+        for bookmark in bookmarks {
+            let file = self.files.entry(bookmark.abs_path).or_default();
+            if let Err(ix) = file
+                .bookmarks
+                .binary_search_by_key(&bookmark.row, |bookmark| bookmark.row)
+            {
+                file.bookmarks.insert(
+                    ix,
+                    StoredBookmark {
+                        row: bookmark.row,
+                        anchor: None,
+                        name: bookmark.name,
+                    },
+                );
+            }
+        }
+
+        let open_buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter_map(|buffer| Some((self.abs_path_for_buffer(&buffer, cx)?, buffer)))
+            .filter(|(abs_path, _)| self.files.contains_key(abs_path))
+            .collect::<Vec<_>>();
+        for (abs_path, buffer) in open_buffers {
+            self.attach_buffer(&buffer, abs_path, cx);
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    fn insert_bookmark(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: Arc<Path>,
+        row: u32,
+        name: Option<SharedString>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let is_attached = self
+            .files
+            .get(&abs_path)
+            .and_then(|file| file.buffer.as_ref())
+            .map_or(false, |open_buffer| {
+                open_buffer.buffer == buffer.downgrade()
+            });
+        if !is_attached {
+            self.files.entry(abs_path.clone()).or_default();
+            self.attach_buffer(buffer, abs_path.clone(), cx);
+        }
+
+        let anchor = buffer.read(cx).anchor_after(Point::new(row, 0));
+        let file = self.files.entry(abs_path).or_default();
+        match file
+            .bookmarks
+            .binary_search_by_key(&row, |bookmark| bookmark.row)
+        {
+            Ok(ix) => file.bookmarks[ix].name = name,
+            Err(ix) => file.bookmarks.insert(
+                ix,
+                StoredBookmark {
+                    row,
+                    anchor: Some(anchor),
+                    name,
+                },
+            ),
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    fn attach_buffer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: Arc<Path>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(file) = self.files.get_mut(&abs_path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        for bookmark in &mut file.bookmarks {
+            let row = bookmark.row.min(snapshot.max_point().row);
+            bookmark.anchor = Some(snapshot.anchor_after(Point::new(row, 0)));
+        }
+        let subscription = cx.subscribe(buffer, {
+            let abs_path = abs_path.clone();
+            move |this, buffer, event, cx| {
+                if let BufferEvent::Edited = event {
+                    this.refresh_rows(&buffer, &abs_path, cx);
+                }
+            }
+        });
+        file.buffer = Some(OpenBuffer {
+            buffer: buffer.downgrade(),
+            _subscription: subscription,
+        });
+        self.paths_by_buffer
+            .insert(buffer.read(cx).remote_id(), abs_path.clone());
+        self.refresh_rows(buffer, &abs_path, cx);
+    }
+
+    /// Updates the rows of a file's bookmarks after its buffer was edited,
+    /// merging bookmarks whose lines were joined.
+    fn refresh_rows(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: &Path,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(file) = self.files.get_mut(abs_path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let mut changed = false;
+        for bookmark in &mut file.bookmarks {
+            if let Some(anchor) = bookmark.anchor {
+                let row = anchor.to_point(&snapshot).row;
+                changed |= row != bookmark.row;
+                bookmark.row = row;
+            }
+        }
+        if changed {
+            file.bookmarks.sort_by_key(|bookmark| bookmark.row);
+            file.bookmarks.dedup_by(|later, earlier| {
+                if later.row == earlier.row {
+                    earlier.name = earlier.name.take().or(later.name.take());
+                    true
+                } else {
+                    false
+                }
+            });
+            cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        }
+    }
+
+    /// Returns the absolute path that bookmarks in the given buffer are keyed by.
+    pub fn abs_path_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Option<Arc<Path>> {
+        self.abs_path_for_file(buffer.read(cx).file()?.as_ref(), cx)
+    }
+
+    fn abs_path_for_file(&self, file: &dyn File, cx: &AppContext) -> Option<Arc<Path>> {
+        let worktree = self
+            .worktree_store
+            .read(cx)
+            .worktree_for_id(file.worktree_id(cx), cx)?;
+        let abs_path = worktree.read(cx).absolutize(file.path()).ok()?;
+        Some(abs_path.into())
+    }
+}
@@ -2,9 +2,10 @@ mod signature_help;
 
 use crate::{
     lsp_store::{LocalLspStore, LspStore},
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
+    CallHierarchyCall, CallHierarchyItem, CodeAction, CodeLens, CoreCompletion, DocumentHighlight,
+    DocumentLink, FoldingRange, FoldingRangeKind, Hover, HoverBlock, HoverBlockKind, InlayHint,
     InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, ProjectTransaction, ResolveState,
+    LocationLink, MarkupContent, ProjectTransaction, ResolveState, TypeHierarchyItem,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -18,7 +19,7 @@ use language::{
     point_from_lsp, point_to_lsp,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     AdapterServerCapabilities, CodeActionKind, CodeActionOptions, CompletionContext,
@@ -140,6 +141,39 @@ pub(crate) struct GetReferences {
     pub position: PointUtf16,
 }
 
+#[derive(Debug)]
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetIncomingCalls {
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetOutgoingCalls {
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSupertypes {
+    pub lsp_item: lsp::TypeHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetSubtypes {
+    pub lsp_item: lsp::TypeHierarchyItem,
+}
+
+#[derive(Debug)]
+pub(crate) struct GetFoldingRanges;
+
 #[derive(Debug)]
 pub(crate) struct GetDocumentHighlights {
     pub position: PointUtf16,
@@ -166,6 +200,12 @@ pub(crate) struct GetCodeActions {
     pub range: Range<Anchor>,
     pub kinds: Option<Vec<lsp::CodeActionKind>>,
 }
+#[derive(Debug)]
+pub(crate) struct GetCodeLens;
+
+#[derive(Debug)]
+pub(crate) struct GetDocumentLinks;
+
 #[derive(Debug)]
 pub(crate) struct OnTypeFormatting {
     pub position: PointUtf16,
@@ -1219,16 +1259,16 @@ impl LspCommand for GetReferences {
 }
 
 #[async_trait(?Send)]
-impl LspCommand for GetDocumentHighlights {
-    type Response = Vec<DocumentHighlight>;
-    type LspRequest = lsp::request::DocumentHighlightRequest;
-    type ProtoRequest = proto::GetDocumentHighlights;
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<CallHierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
 
     fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
-        capabilities
-            .server_capabilities
-            .document_highlight_provider
-            .is_some()
+        !matches!(
+            &capabilities.server_capabilities.call_hierarchy_provider,
+            None | Some(lsp::CallHierarchyServerCapability::Simple(false))
+        )
     }
 
     fn to_lsp(
@@ -1237,8 +1277,8 @@ impl LspCommand for GetDocumentHighlights {
         _: &Buffer,
         _: &Arc<LanguageServer>,
         _: &AppContext,
-    ) -> lsp::DocumentHighlightParams {
-        lsp::DocumentHighlightParams {
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
             text_document_position_params: lsp::TextDocumentPositionParams {
                 text_document: lsp::TextDocumentIdentifier {
                     uri: lsp::Url::from_file_path(path).unwrap(),
@@ -1246,41 +1286,29 @@ impl LspCommand for GetDocumentHighlights {
                 position: point_to_lsp(self.position),
             },
             work_done_progress_params: Default::default(),
-            partial_result_params: Default::default(),
         }
     }
 
     async fn response_from_lsp(
         self,
-        lsp_highlights: Option<Vec<lsp::DocumentHighlight>>,
-        _: Model<LspStore>,
+        message: Option<Vec<lsp::CallHierarchyItem>>,
+        lsp_store: Model<LspStore>,
         buffer: Model<Buffer>,
-        _: LanguageServerId,
-        mut cx: AsyncAppContext,
-    ) -> Result<Vec<DocumentHighlight>> {
-        buffer.update(&mut cx, |buffer, _| {
-            let mut lsp_highlights = lsp_highlights.unwrap_or_default();
-            lsp_highlights.sort_unstable_by_key(|h| (h.range.start, Reverse(h.range.end)));
-            lsp_highlights
-                .into_iter()
-                .map(|lsp_highlight| {
-                    let start = buffer
-                        .clip_point_utf16(point_from_lsp(lsp_highlight.range.start), Bias::Left);
-                    let end = buffer
-                        .clip_point_utf16(point_from_lsp(lsp_highlight.range.end), Bias::Left);
-                    DocumentHighlight {
-                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
-                        kind: lsp_highlight
-                            .kind
-                            .unwrap_or(lsp::DocumentHighlightKind::READ),
-                    }
-                })
-                .collect()
-        })
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        hierarchy_items_from_lsp(
+            message.unwrap_or_default(),
+            lsp_store,
+            buffer,
+            server_id,
+            cx,
+        )
+        .await
     }
 
-    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentHighlights {
-        proto::GetDocumentHighlights {
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
             project_id,
             buffer_id: buffer.remote_id().into(),
             position: Some(language::proto::serialize_anchor(
@@ -1291,7 +1319,7 @@ impl LspCommand for GetDocumentHighlights {
     }
 
     async fn from_proto(
-        message: proto::GetDocumentHighlights,
+        message: proto::PrepareCallHierarchy,
         _: Model<LspStore>,
         buffer: Model<Buffer>,
         mut cx: AsyncAppContext,
@@ -1311,227 +1339,1207 @@ impl LspCommand for GetDocumentHighlights {
     }
 
     fn response_to_proto(
-        response: Vec<DocumentHighlight>,
-        _: &mut LspStore,
-        _: PeerId,
+        response: Vec<CallHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
         _: &clock::Global,
-        _: &mut AppContext,
-    ) -> proto::GetDocumentHighlightsResponse {
-        let highlights = response
-            .into_iter()
-            .map(|highlight| proto::DocumentHighlight {
-                start: Some(serialize_anchor(&highlight.range.start)),
-                end: Some(serialize_anchor(&highlight.range.end)),
-                kind: match highlight.kind {
-                    DocumentHighlightKind::TEXT => proto::document_highlight::Kind::Text.into(),
-                    DocumentHighlightKind::WRITE => proto::document_highlight::Kind::Write.into(),
-                    DocumentHighlightKind::READ => proto::document_highlight::Kind::Read.into(),
-                    _ => proto::document_highlight::Kind::Text.into(),
-                },
-            })
-            .collect();
-        proto::GetDocumentHighlightsResponse { highlights }
+        cx: &mut AppContext,
+    ) -> proto::PrepareCallHierarchyResponse {
+        proto::PrepareCallHierarchyResponse {
+            items: hierarchy_items_to_proto(&response, lsp_store, peer_id, cx),
+        }
     }
 
     async fn response_from_proto(
         self,
-        message: proto::GetDocumentHighlightsResponse,
-        _: Model<LspStore>,
-        buffer: Model<Buffer>,
-        mut cx: AsyncAppContext,
-    ) -> Result<Vec<DocumentHighlight>> {
-        let mut highlights = Vec::new();
-        for highlight in message.highlights {
-            let start = highlight
-                .start
-                .and_then(deserialize_anchor)
-                .ok_or_else(|| anyhow!("missing target start"))?;
-            let end = highlight
-                .end
-                .and_then(deserialize_anchor)
-                .ok_or_else(|| anyhow!("missing target end"))?;
-            buffer
-                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
-                .await?;
-            let kind = match proto::document_highlight::Kind::from_i32(highlight.kind) {
-                Some(proto::document_highlight::Kind::Text) => DocumentHighlightKind::TEXT,
-                Some(proto::document_highlight::Kind::Read) => DocumentHighlightKind::READ,
-                Some(proto::document_highlight::Kind::Write) => DocumentHighlightKind::WRITE,
-                None => DocumentHighlightKind::TEXT,
-            };
-            highlights.push(DocumentHighlight {
-                range: start..end,
-                kind,
-            });
-        }
-        Ok(highlights)
+        message: proto::PrepareCallHierarchyResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        hierarchy_items_from_proto(message.items, lsp_store, cx).await
     }
 
-    fn buffer_id_from_proto(message: &proto::GetDocumentHighlights) -> Result<BufferId> {
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
         BufferId::new(message.buffer_id)
     }
 }
 
 #[async_trait(?Send)]
-impl LspCommand for GetSignatureHelp {
-    type Response = Option<SignatureHelp>;
-    type LspRequest = lsp::SignatureHelpRequest;
-    type ProtoRequest = proto::GetSignatureHelp;
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
 
-    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
-        capabilities
-            .server_capabilities
-            .signature_help_provider
-            .is_some()
+    fn status(&self) -> Option<String> {
+        Some("Finding incoming calls...".to_owned())
     }
 
     fn to_lsp(
         &self,
-        path: &Path,
+        _: &Path,
         _: &Buffer,
         _: &Arc<LanguageServer>,
-        _cx: &AppContext,
-    ) -> lsp::SignatureHelpParams {
-        let url_result = lsp::Url::from_file_path(path);
-        if url_result.is_err() {
-            log::error!("an invalid file path has been specified");
-        }
-
-        lsp::SignatureHelpParams {
-            text_document_position_params: lsp::TextDocumentPositionParams {
-                text_document: lsp::TextDocumentIdentifier {
-                    uri: url_result.expect("invalid file path"),
-                },
-                position: point_to_lsp(self.position),
-            },
-            context: None,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyIncomingCallsParams {
+        lsp::CallHierarchyIncomingCallsParams {
+            item: self.lsp_item.clone(),
             work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         }
     }
 
     async fn response_from_lsp(
         self,
-        message: Option<lsp::SignatureHelp>,
-        _: Model<LspStore>,
+        message: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        lsp_store: Model<LspStore>,
         buffer: Model<Buffer>,
-        _: LanguageServerId,
+        server_id: LanguageServerId,
         mut cx: AsyncAppContext,
-    ) -> Result<Self::Response> {
-        let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
-        Ok(message.and_then(|message| SignatureHelp::new(message, language)))
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, _) = language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut calls = Vec::new();
+        for call in message.unwrap_or_default() {
+            let item = call_hierarchy_item_from_lsp(
+                call.from,
+                &lsp_adapter,
+                server_id,
+                &lsp_store,
+                &mut cx,
+            )
+            .await?;
+            // Incoming call sites are reported relative to the caller.
+            let caller_buffer = item.location.buffer.clone();
+            let call_sites = caller_buffer.update(&mut cx, |buffer, _| {
+                call.from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: caller_buffer.clone(),
+                        range: anchor_range_from_lsp(buffer, range),
+                    })
+                    .collect()
+            })?;
+            calls.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(calls)
     }
 
-    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> Self::ProtoRequest {
-        let offset = buffer.point_utf16_to_offset(self.position);
-        proto::GetSignatureHelp {
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
             project_id,
-            buffer_id: buffer.remote_id().to_proto(),
-            position: Some(serialize_anchor(&buffer.anchor_after(offset))),
-            version: serialize_version(&buffer.version()),
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.lsp_item).unwrap(),
         }
     }
 
     async fn from_proto(
-        payload: Self::ProtoRequest,
+        message: proto::GetIncomingCalls,
         _: Model<LspStore>,
-        buffer: Model<Buffer>,
-        mut cx: AsyncAppContext,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
     ) -> Result<Self> {
-        buffer
-            .update(&mut cx, |buffer, _| {
-                buffer.wait_for_version(deserialize_version(&payload.version))
-            })?
-            .await
-            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
-        let buffer_snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
         Ok(Self {
-            position: payload
-                .position
-                .and_then(deserialize_anchor)
-                .context("invalid position")?
-                .to_point_utf16(&buffer_snapshot),
+            lsp_item: serde_json::from_slice(&message.lsp_item)?,
         })
     }
 
     fn response_to_proto(
-        response: Self::Response,
-        _: &mut LspStore,
-        _: PeerId,
-        _: &Global,
-        _: &mut AppContext,
-    ) -> proto::GetSignatureHelpResponse {
-        proto::GetSignatureHelpResponse {
-            signature_help: response
-                .map(|signature_help| lsp_to_proto_signature(signature_help.original_data)),
+        response: Vec<CallHierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetIncomingCallsResponse {
+        proto::GetIncomingCallsResponse {
+            calls: call_hierarchy_calls_to_proto(&response, lsp_store, peer_id, cx),
         }
     }
 
     async fn response_from_proto(
         self,
-        response: proto::GetSignatureHelpResponse,
-        _: Model<LspStore>,
-        buffer: Model<Buffer>,
-        mut cx: AsyncAppContext,
-    ) -> Result<Self::Response> {
-        let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
-        Ok(response
-            .signature_help
-            .map(proto_to_lsp_signature)
-            .and_then(|lsp_help| SignatureHelp::new(lsp_help, language)))
+        message: proto::GetIncomingCallsResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message.calls, lsp_store, cx).await
     }
 
-    fn buffer_id_from_proto(message: &Self::ProtoRequest) -> Result<BufferId> {
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
         BufferId::new(message.buffer_id)
     }
 }
 
 #[async_trait(?Send)]
-impl LspCommand for GetHover {
-    type Response = Option<Hover>;
-    type LspRequest = lsp::request::HoverRequest;
-    type ProtoRequest = proto::GetHover;
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
 
-    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
-        match capabilities.server_capabilities.hover_provider {
-            Some(lsp::HoverProviderCapability::Simple(enabled)) => enabled,
-            Some(lsp::HoverProviderCapability::Options(_)) => true,
-            None => false,
-        }
+    fn status(&self) -> Option<String> {
+        Some("Finding outgoing calls...".to_owned())
     }
 
     fn to_lsp(
         &self,
-        path: &Path,
+        _: &Path,
         _: &Buffer,
         _: &Arc<LanguageServer>,
         _: &AppContext,
-    ) -> lsp::HoverParams {
-        lsp::HoverParams {
-            text_document_position_params: lsp::TextDocumentPositionParams {
-                text_document: lsp::TextDocumentIdentifier {
-                    uri: lsp::Url::from_file_path(path).unwrap(),
-                },
-                position: point_to_lsp(self.position),
-            },
+    ) -> lsp::CallHierarchyOutgoingCallsParams {
+        lsp::CallHierarchyOutgoingCallsParams {
+            item: self.lsp_item.clone(),
             work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
         }
     }
 
     async fn response_from_lsp(
         self,
-        message: Option<lsp::Hover>,
-        _: Model<LspStore>,
+        message: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        lsp_store: Model<LspStore>,
         buffer: Model<Buffer>,
-        _: LanguageServerId,
+        server_id: LanguageServerId,
         mut cx: AsyncAppContext,
-    ) -> Result<Self::Response> {
-        let Some(hover) = message else {
-            return Ok(None);
-        };
-
-        let (language, range) = buffer.update(&mut cx, |buffer, _| {
-            (
-                buffer.language().cloned(),
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let (lsp_adapter, _) = language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+        let mut calls = Vec::new();
+        for call in message.unwrap_or_default() {
+            let item =
+                call_hierarchy_item_from_lsp(call.to, &lsp_adapter, server_id, &lsp_store, &mut cx)
+                    .await?;
+            // Outgoing call sites are reported relative to the item whose calls were requested.
+            let call_sites = buffer.update(&mut cx, |origin_buffer, _| {
+                call.from_ranges
+                    .into_iter()
+                    .map(|range| Location {
+                        buffer: buffer.clone(),
+                        range: anchor_range_from_lsp(origin_buffer, range),
+                    })
+                    .collect()
+            })?;
+            calls.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(calls)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.lsp_item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            lsp_item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetOutgoingCallsResponse {
+        proto::GetOutgoingCallsResponse {
+            calls: call_hierarchy_calls_to_proto(&response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetOutgoingCallsResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message.calls, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            &capabilities.server_capabilities.type_hierarchy_provider,
+            None | Some(lsp::OneOf::Left(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        hierarchy_items_from_lsp(
+            message.unwrap_or_default(),
+            lsp_store,
+            buffer,
+            server_id,
+            cx,
+        )
+        .await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareTypeHierarchyResponse {
+        proto::PrepareTypeHierarchyResponse {
+            items: hierarchy_items_to_proto(&response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareTypeHierarchyResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding supertypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySupertypesParams {
+        lsp::TypeHierarchySupertypesParams {
+            item: self.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        hierarchy_items_from_lsp(
+            message.unwrap_or_default(),
+            lsp_store,
+            buffer,
+            server_id,
+            cx,
+        )
+        .await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.lsp_item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            lsp_item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetSupertypesResponse {
+        proto::GetSupertypesResponse {
+            items: hierarchy_items_to_proto(&response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSupertypesResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding subtypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySubtypesParams {
+        lsp::TypeHierarchySubtypesParams {
+            item: self.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<Vec<lsp::TypeHierarchyItem>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        hierarchy_items_from_lsp(
+            message.unwrap_or_default(),
+            lsp_store,
+            buffer,
+            server_id,
+            cx,
+        )
+        .await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            lsp_item: serde_json::to_vec(&self.lsp_item).unwrap(),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Model<LspStore>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Ok(Self {
+            lsp_item: serde_json::from_slice(&message.lsp_item)?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        lsp_store: &mut LspStore,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetSubtypesResponse {
+        proto::GetSubtypesResponse {
+            items: hierarchy_items_to_proto(&response, lsp_store, peer_id, cx),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSubtypesResponse,
+        lsp_store: Model<LspStore>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        hierarchy_items_from_proto(message.items, lsp_store, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn anchor_range_from_lsp(buffer: &Buffer, range: lsp::Range) -> Range<Anchor> {
+    let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+    let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+    buffer.anchor_after(start)..buffer.anchor_before(end)
+}
+
+fn location_to_proto(
+    location: &Location,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::Location {
+    lsp_store
+        .buffer_store()
+        .update(cx, |buffer_store, cx| {
+            buffer_store.create_buffer_for_peer(&location.buffer, peer_id, cx)
+        })
+        .detach_and_log_err(cx);
+    proto::Location {
+        start: Some(serialize_anchor(&location.range.start)),
+        end: Some(serialize_anchor(&location.range.end)),
+        buffer_id: location.buffer.read(cx).remote_id().into(),
+    }
+}
+
+async fn location_from_proto(
+    location: proto::Location,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<Location> {
+    let buffer_id = BufferId::new(location.buffer_id)?;
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location start"))?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing location end"))?;
+    buffer
+        .update(cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+        .await?;
+    Ok(Location {
+        buffer,
+        range: start..end,
+    })
+}
+
+async fn call_hierarchy_item_from_lsp(
+    lsp_item: lsp::CallHierarchyItem,
+    lsp_adapter: &CachedLspAdapter,
+    server_id: LanguageServerId,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<CallHierarchyItem> {
+    let buffer = lsp_store
+        .update(cx, |lsp_store, cx| {
+            lsp_store.open_local_buffer_via_lsp(
+                lsp_item.uri.clone(),
+                server_id,
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+    let (range, selection_range) = buffer.update(cx, |buffer, _| {
+        (
+            anchor_range_from_lsp(buffer, lsp_item.range),
+            anchor_range_from_lsp(buffer, lsp_item.selection_range),
+        )
+    })?;
+    Ok(CallHierarchyItem {
+        location: Location { buffer, range },
+        selection_range,
+        lsp_item,
+    })
+}
+
+fn call_hierarchy_item_to_proto(
+    item: &CallHierarchyItem,
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::CallHierarchyItem {
+    proto::CallHierarchyItem {
+        location: Some(location_to_proto(&item.location, lsp_store, peer_id, cx)),
+        selection_start: Some(serialize_anchor(&item.selection_range.start)),
+        selection_end: Some(serialize_anchor(&item.selection_range.end)),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+async fn call_hierarchy_item_from_proto(
+    item: proto::CallHierarchyItem,
+    lsp_store: &Model<LspStore>,
+    cx: &mut AsyncAppContext,
+) -> Result<CallHierarchyItem> {
+    let location = item
+        .location
+        .ok_or_else(|| anyhow!("missing call hierarchy item location"))?;
+    let location = location_from_proto(location, lsp_store, cx).await?;
+    let selection_start = item
+        .selection_start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing selection start"))?;
+    let selection_end = item
+        .selection_end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing selection end"))?;
+    location
+        .buffer
+        .update(cx, |buffer, _| {
+            buffer.wait_for_anchors([selection_start, selection_end])
+        })?
+        .await?;
+    Ok(CallHierarchyItem {
+        location,
+        selection_range: selection_start..selection_end,
+        lsp_item: serde_json::from_slice(&item.lsp_item)?,
+    })
+}
+
+async fn hierarchy_items_from_lsp(
+    lsp_items: Vec<lsp::CallHierarchyItem>,
+    lsp_store: Model<LspStore>,
+    buffer: Model<Buffer>,
+    server_id: LanguageServerId,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<CallHierarchyItem>> {
+    let (lsp_adapter, _) = language_server_for_buffer(&lsp_store, &buffer, server_id, &mut cx)?;
+    let mut items = Vec::new();
+    for lsp_item in lsp_items {
+        items.push(
+            call_hierarchy_item_from_lsp(lsp_item, &lsp_adapter, server_id, &lsp_store, &mut cx)
+                .await?,
+        );
+    }
+    Ok(items)
+}
+
+fn hierarchy_items_to_proto(
+    items: &[CallHierarchyItem],
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> Vec<proto::CallHierarchyItem> {
+    items
+        .iter()
+        .map(|item| call_hierarchy_item_to_proto(item, lsp_store, peer_id, cx))
+        .collect()
+}
+
+async fn hierarchy_items_from_proto(
+    proto_items: Vec<proto::CallHierarchyItem>,
+    lsp_store: Model<LspStore>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<CallHierarchyItem>> {
+    let mut items = Vec::new();
+    for item in proto_items {
+        items.push(call_hierarchy_item_from_proto(item, &lsp_store, &mut cx).await?);
+    }
+    Ok(items)
+}
+
+fn call_hierarchy_calls_to_proto(
+    calls: &[CallHierarchyCall],
+    lsp_store: &mut LspStore,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> Vec<proto::CallHierarchyCall> {
+    calls
+        .iter()
+        .map(|call| proto::CallHierarchyCall {
+            item: Some(call_hierarchy_item_to_proto(
+                &call.item, lsp_store, peer_id, cx,
+            )),
+            call_sites: call
+                .call_sites
+                .iter()
+                .map(|location| location_to_proto(location, lsp_store, peer_id, cx))
+                .collect(),
+        })
+        .collect()
+}
+
+async fn call_hierarchy_calls_from_proto(
+    proto_calls: Vec<proto::CallHierarchyCall>,
+    lsp_store: Model<LspStore>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<CallHierarchyCall>> {
+    let mut calls = Vec::new();
+    for call in proto_calls {
+        let item = call
+            .item
+            .ok_or_else(|| anyhow!("missing call hierarchy item"))?;
+        let item = call_hierarchy_item_from_proto(item, &lsp_store, &mut cx).await?;
+        let mut call_sites = Vec::new();
+        for location in call.call_sites {
+            call_sites.push(location_from_proto(location, &lsp_store, &mut cx).await?);
+        }
+        calls.push(CallHierarchyCall { item, call_sites });
+    }
+    Ok(calls)
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        !matches!(
+            capabilities.server_capabilities.folding_range_provider,
+            None | Some(lsp::FoldingRangeProviderCapability::Simple(false))
+        )
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer.read_with(&cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut ranges = lsp_ranges
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|lsp_range| {
+                    lsp_range.start_line < lsp_range.end_line && lsp_range.end_line <= max_row
+                })
+                .map(|lsp_range| {
+                    let start =
+                        Point::new(lsp_range.start_line, buffer.line_len(lsp_range.start_line));
+                    let end = Point::new(lsp_range.end_line, buffer.line_len(lsp_range.end_line));
+                    FoldingRange {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: lsp_range.kind.map(|kind| match kind {
+                            lsp::FoldingRangeKind::Comment => FoldingRangeKind::Comment,
+                            lsp::FoldingRangeKind::Imports => FoldingRangeKind::Imports,
+                            lsp::FoldingRangeKind::Region => FoldingRangeKind::Region,
+                        }),
+                    }
+                })
+                .collect::<Vec<_>>();
+            ranges.sort_by(|a, b| {
+                a.range
+                    .start
+                    .cmp(&b.range.start, buffer)
+                    .then_with(|| b.range.end.cmp(&a.range.end, buffer))
+            });
+            ranges
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Vec<FoldingRange>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: response
+                .into_iter()
+                .map(|range| proto::FoldingRange {
+                    start: Some(serialize_anchor(&range.range.start)),
+                    end: Some(serialize_anchor(&range.range.end)),
+                    kind: range.kind.map(|kind| {
+                        match kind {
+                            FoldingRangeKind::Comment => proto::folding_range::Kind::Comment,
+                            FoldingRangeKind::Imports => proto::folding_range::Kind::Imports,
+                            FoldingRangeKind::Region => proto::folding_range::Kind::Region,
+                        }
+                        .into()
+                    }),
+                })
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        let mut ranges = Vec::new();
+        for range in message.ranges {
+            let start = range
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing folding range start"))?;
+            let end = range
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing folding range end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            let kind = range
+                .kind
+                .and_then(proto::folding_range::Kind::from_i32)
+                .map(|kind| match kind {
+                    proto::folding_range::Kind::Comment => FoldingRangeKind::Comment,
+                    proto::folding_range::Kind::Imports => FoldingRangeKind::Imports,
+                    proto::folding_range::Kind::Region => FoldingRangeKind::Region,
+                });
+            ranges.push(FoldingRange {
+                range: start..end,
+                kind,
+            });
+        }
+        Ok(ranges)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentHighlights {
+    type Response = Vec<DocumentHighlight>;
+    type LspRequest = lsp::request::DocumentHighlightRequest;
+    type ProtoRequest = proto::GetDocumentHighlights;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_highlight_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentHighlightParams {
+        lsp::DocumentHighlightParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_highlights: Option<Vec<lsp::DocumentHighlight>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentHighlight>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let mut lsp_highlights = lsp_highlights.unwrap_or_default();
+            lsp_highlights.sort_unstable_by_key(|h| (h.range.start, Reverse(h.range.end)));
+            lsp_highlights
+                .into_iter()
+                .map(|lsp_highlight| {
+                    let start = buffer
+                        .clip_point_utf16(point_from_lsp(lsp_highlight.range.start), Bias::Left);
+                    let end = buffer
+                        .clip_point_utf16(point_from_lsp(lsp_highlight.range.end), Bias::Left);
+                    DocumentHighlight {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: lsp_highlight
+                            .kind
+                            .unwrap_or(lsp::DocumentHighlightKind::READ),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentHighlights {
+        proto::GetDocumentHighlights {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentHighlights,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<DocumentHighlight>,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentHighlightsResponse {
+        let highlights = response
+            .into_iter()
+            .map(|highlight| proto::DocumentHighlight {
+                start: Some(serialize_anchor(&highlight.range.start)),
+                end: Some(serialize_anchor(&highlight.range.end)),
+                kind: match highlight.kind {
+                    DocumentHighlightKind::TEXT => proto::document_highlight::Kind::Text.into(),
+                    DocumentHighlightKind::WRITE => proto::document_highlight::Kind::Write.into(),
+                    DocumentHighlightKind::READ => proto::document_highlight::Kind::Read.into(),
+                    _ => proto::document_highlight::Kind::Text.into(),
+                },
+            })
+            .collect();
+        proto::GetDocumentHighlightsResponse { highlights }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentHighlightsResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentHighlight>> {
+        let mut highlights = Vec::new();
+        for highlight in message.highlights {
+            let start = highlight
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing target start"))?;
+            let end = highlight
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing target end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            let kind = match proto::document_highlight::Kind::from_i32(highlight.kind) {
+                Some(proto::document_highlight::Kind::Text) => DocumentHighlightKind::TEXT,
+                Some(proto::document_highlight::Kind::Read) => DocumentHighlightKind::READ,
+                Some(proto::document_highlight::Kind::Write) => DocumentHighlightKind::WRITE,
+                None => DocumentHighlightKind::TEXT,
+            };
+            highlights.push(DocumentHighlight {
+                range: start..end,
+                kind,
+            });
+        }
+        Ok(highlights)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentHighlights) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSignatureHelp {
+    type Response = Option<SignatureHelp>;
+    type LspRequest = lsp::SignatureHelpRequest;
+    type ProtoRequest = proto::GetSignatureHelp;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .signature_help_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _cx: &AppContext,
+    ) -> lsp::SignatureHelpParams {
+        let url_result = lsp::Url::from_file_path(path);
+        if url_result.is_err() {
+            log::error!("an invalid file path has been specified");
+        }
+
+        lsp::SignatureHelpParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: url_result.expect("invalid file path"),
+                },
+                position: point_to_lsp(self.position),
+            },
+            context: None,
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SignatureHelp>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
+        Ok(message.and_then(|message| SignatureHelp::new(message, language)))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> Self::ProtoRequest {
+        let offset = buffer.point_utf16_to_offset(self.position);
+        proto::GetSignatureHelp {
+            project_id,
+            buffer_id: buffer.remote_id().to_proto(),
+            position: Some(serialize_anchor(&buffer.anchor_after(offset))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        payload: Self::ProtoRequest,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+        let buffer_snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+        Ok(Self {
+            position: payload
+                .position
+                .and_then(deserialize_anchor)
+                .context("invalid position")?
+                .to_point_utf16(&buffer_snapshot),
+        })
+    }
+
+    fn response_to_proto(
+        response: Self::Response,
+        _: &mut LspStore,
+        _: PeerId,
+        _: &Global,
+        _: &mut AppContext,
+    ) -> proto::GetSignatureHelpResponse {
+        proto::GetSignatureHelpResponse {
+            signature_help: response
+                .map(|signature_help| lsp_to_proto_signature(signature_help.original_data)),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        response: proto::GetSignatureHelpResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let language = buffer.update(&mut cx, |buffer, _| buffer.language().cloned())?;
+        Ok(response
+            .signature_help
+            .map(proto_to_lsp_signature)
+            .and_then(|lsp_help| SignatureHelp::new(lsp_help, language)))
+    }
+
+    fn buffer_id_from_proto(message: &Self::ProtoRequest) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetHover {
+    type Response = Option<Hover>;
+    type LspRequest = lsp::request::HoverRequest;
+    type ProtoRequest = proto::GetHover;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match capabilities.server_capabilities.hover_provider {
+            Some(lsp::HoverProviderCapability::Simple(enabled)) => enabled,
+            Some(lsp::HoverProviderCapability::Options(_)) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::HoverParams {
+        lsp::HoverParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::Hover>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self::Response> {
+        let Some(hover) = message else {
+            return Ok(None);
+        };
+
+        let (language, range) = buffer.update(&mut cx, |buffer, _| {
+            (
+                buffer.language().cloned(),
                 hover.range.map(|range| {
                     let token_start =
                         buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
@@ -2256,6 +3264,332 @@ impl GetCodeActions {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .code_lens_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_lenses: Option<Vec<lsp::CodeLens>>,
+        lsp_store: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        let capabilities = lsp_store.read_with(&cx, |lsp_store, _| {
+            lsp_store
+                .language_server_for_id(server_id)
+                .map(|server| server.capabilities())
+        })?;
+        buffer.read_with(&cx, |buffer, _| {
+            let mut lenses = lsp_lenses
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_lens| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_lens.range.start), Bias::Left);
+                    let end =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_lens.range.end), Bias::Left);
+                    let executable = capabilities.as_ref().map_or(false, |capabilities| {
+                        Self::can_execute_lens(capabilities, &lsp_lens)
+                    });
+                    CodeLens {
+                        server_id,
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        lsp_lens,
+                        executable,
+                    }
+                })
+                .collect::<Vec<_>>();
+            lenses.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            lenses
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        lenses: Vec<CodeLens>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: lenses.iter().map(Self::serialize_code_lens).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(Self::deserialize_code_lens)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetCodeLens {
+    pub fn can_resolve_lens(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+
+    /// Whether the command of the lens is one that the server can run with `workspace/executeCommand`.
+    pub fn can_execute_lens(capabilities: &ServerCapabilities, lens: &lsp::CodeLens) -> bool {
+        let Some(command) = lens.command.as_ref() else {
+            return false;
+        };
+        capabilities
+            .execute_command_provider
+            .as_ref()
+            .map_or(false, |options| options.commands.contains(&command.command))
+    }
+
+    pub fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+            executable: lens.executable,
+        }
+    }
+
+    pub fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens: serde_json::from_slice(&lens.lsp_lens)?,
+            executable: lens.executable,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_links: Option<Vec<lsp::DocumentLink>>,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer.read_with(&cx, |buffer, _| {
+            let mut links = lsp_links
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_link| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_link.range.start), Bias::Left);
+                    let end =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_link.range.end), Bias::Left);
+                    DocumentLink {
+                        server_id,
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        lsp_link,
+                    }
+                })
+                .collect::<Vec<_>>();
+            links.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            links
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        links: Vec<DocumentLink>,
+        _: &mut LspStore,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: links.iter().map(Self::serialize_document_link).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<LspStore>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(Self::deserialize_document_link)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetDocumentLinks {
+    pub fn can_resolve_link(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .document_link_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+
+    pub fn serialize_document_link(link: &DocumentLink) -> proto::DocumentLink {
+        proto::DocumentLink {
+            server_id: link.server_id.0 as u64,
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+        }
+    }
+
+    pub fn deserialize_document_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(DocumentLink {
+            server_id: LanguageServerId(link.server_id as usize),
+            range: start..end,
+            lsp_link: serde_json::from_slice(&link.lsp_link)?,
+        })
+    }
+}
+
 #[async_trait(?Send)]
 impl LspCommand for OnTypeFormatting {
     type Response = Option<Transaction>;
@@ -1,5 +1,6 @@
 use crate::{
     buffer_store::{BufferStore, BufferStoreEvent},
+    debounced_delay::DebouncedDelay,
     deserialize_code_actions,
     environment::ProjectEnvironment,
     lsp_command::{self, *},
@@ -10,8 +11,9 @@ use crate::{
     toolchain_store::{EmptyToolchainStore, ToolchainStoreEvent},
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
     yarn::YarnPathStore,
-    CodeAction, Completion, CoreCompletion, Hover, InlayHint, ProjectItem as _, ProjectPath,
-    ProjectTransaction, ResolveState, Symbol, ToolchainStore,
+    BufferSemanticTokens, CodeAction, CodeLens, Completion, CoreCompletion, DocumentLink,
+    FoldingRange, Hover, InlayHint, ProjectItem as _, ProjectPath, ProjectTransaction,
+    ResolveState, SemanticToken, Symbol, ToolchainStore,
 };
 use anyhow::{anyhow, Context as _, Result};
 use async_trait::async_trait;
@@ -94,6 +96,7 @@ pub use worktree::{
 
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_THROTTLE_TIMEOUT: Duration = Duration::from_millis(100);
+const DIAGNOSTICS_PULL_DEBOUNCE: Duration = Duration::from_millis(250);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatTrigger {
@@ -171,6 +174,12 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, HashMap<LanguageServerId, CachedSemanticTokens>>, // buffer_id -> server_id -> last full tokens, to request deltas against
+    diagnostic_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>, // server_id -> document -> result id of the last pulled report
+    buffers_to_pull_diagnostics: HashSet<BufferId>,
+    servers_to_pull_workspace_diagnostics: HashSet<LanguageServerId>,
+    diagnostics_pull_debouncer: DebouncedDelay<LspStore>,
+    workspace_diagnostics_pulls: HashMap<LanguageServerId, Task<()>>,
     _subscription: gpui::Subscription,
     registered_buffers: HashMap<BufferId, usize>,
 }
@@ -819,6 +828,67 @@ impl LocalLspStore {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::CodeLensRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            cx.emit(LspStoreEvent::RefreshCodeLens);
+                            this.downstream_client.as_ref().map(|(client, project_id)| {
+                                client.send(proto::RefreshCodeLens {
+                                    project_id: *project_id,
+                                })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |this, cx| {
+                            let buffer_ids = this
+                                .as_local()
+                                .map(|local| local.registered_buffers.keys().copied().collect())
+                                .unwrap_or_else(Vec::new);
+                            this.pull_diagnostics_debounced(buffer_ids, [server_id], cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -1957,6 +2027,11 @@ impl LocalLspStore {
     ) {
         buffer.update(cx, |buffer, cx| {
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
+            // A closed document gets a full report again the next time it is pulled.
+            for result_ids in self.diagnostic_result_ids.values_mut() {
+                result_ids.remove(&file_url);
+            }
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
                     .notify::<lsp::notification::DidCloseTextDocument>(
@@ -2761,8 +2836,11 @@ pub struct LspStore {
     _maintain_buffer_languages: Task<()>,
     diagnostic_summaries:
         HashMap<WorktreeId, HashMap<Arc<Path>, HashMap<LanguageServerId, DiagnosticSummary>>>,
+    document_links: HashMap<BufferId, (clock::Global, DocumentLinksTask)>,
 }
 
+type DocumentLinksTask = Shared<Task<Result<Vec<DocumentLink>, Arc<anyhow::Error>>>>;
+
 pub enum LspStoreEvent {
     LanguageServerAdded(LanguageServerId, LanguageServerName, Option<WorktreeId>),
     LanguageServerRemoved(LanguageServerId),
@@ -2778,6 +2856,8 @@ pub enum LspStoreEvent {
     },
     Notification(String),
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     DiagnosticsUpdated {
         language_server_id: LanguageServerId,
         path: ProjectPath,
@@ -2829,8 +2909,13 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_resolve_document_link);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
+        client.add_model_request_handler(Self::handle_get_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_code_lens);
         client.add_model_request_handler(Self::handle_on_type_formatting);
         client.add_model_request_handler(Self::handle_apply_additional_edits_for_completion);
         client.add_model_request_handler(Self::handle_register_buffer_with_language_servers);
@@ -2842,10 +2927,19 @@ impl LspStore {
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentHighlights>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<lsp_ext_command::ExpandMacro>);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_get_document_links);
     }
 
     pub fn as_remote(&self) -> Option<&RemoteLspStore> {
@@ -2945,6 +3039,12 @@ impl LspStore {
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 buffers_being_formatted: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                diagnostic_result_ids: Default::default(),
+                buffers_to_pull_diagnostics: Default::default(),
+                servers_to_pull_workspace_diagnostics: Default::default(),
+                diagnostics_pull_debouncer: DebouncedDelay::new(),
+                workspace_diagnostics_pulls: Default::default(),
                 prettier_store,
                 environment,
                 http_client,
@@ -2966,6 +3066,7 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            document_links: Default::default(),
             active_entry: None,
 
             _maintain_workspace_config,
@@ -3021,6 +3122,7 @@ impl LspStore {
             language_server_statuses: Default::default(),
             nonce: StdRng::from_entropy().gen(),
             diagnostic_summaries: Default::default(),
+            document_links: Default::default(),
             active_entry: None,
             toolchain_store,
             _maintain_workspace_config,
@@ -3068,7 +3170,9 @@ impl LspStore {
                     }
                 }
             }
-            BufferStoreEvent::BufferDropped(_) => {}
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                self.document_links.remove(buffer_id);
+            }
         }
     }
 
@@ -3155,6 +3259,7 @@ impl LspStore {
     ) {
         match event {
             language::BufferEvent::Edited { .. } => {
+                self.document_links.remove(&buffer.read(cx).remote_id());
                 self.on_buffer_edited(buffer, cx);
             }
 
@@ -3206,7 +3311,8 @@ impl LspStore {
             }
             let refcount = local.registered_buffers.entry(buffer_id).or_insert(0);
             *refcount += 1;
-            if *refcount == 1 {
+            let newly_registered = *refcount == 1;
+            if newly_registered {
                 local.register_buffer_with_language_servers(buffer, cx);
             }
 
@@ -3225,6 +3331,9 @@ impl LspStore {
                 }
             })
             .detach();
+            if newly_registered {
+                self.pull_diagnostics_debounced([buffer_id], [], cx);
+            }
         } else if let Some((upstream_client, upstream_project_id)) = self.upstream_client() {
             let buffer_id = buffer.read(cx).remote_id().to_proto();
             cx.background_executor()
@@ -3801,6 +3910,211 @@ impl LspStore {
         }
     }
 
+    /// Fetches the code lenses of the buffer. The ones that come without a command are left
+    /// unresolved, so that only the lenses that get shown are resolved with `resolve_code_lens`.
+    pub fn code_lens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        let Some(server_id) = self
+            .as_local()
+            .and_then(|local| {
+                local
+                    .language_servers_for_buffer(buffer.read(cx), cx)
+                    .find(|(_, server)| server.capabilities().code_lens_provider.is_some())
+                    .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            })
+            .or_else(|| {
+                self.upstream_client()
+                    .is_some()
+                    .then_some(LanguageServerToQuery::Primary)
+            })
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, GetCodeLens, cx)
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        lens: CodeLens,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(GetCodeLens::serialize_code_lens(&lens)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("code lens proto request")?;
+                match response.lens {
+                    Some(resolved_lens) => GetCodeLens::deserialize_code_lens(resolved_lens)
+                        .context("code lens proto resolve response conversion"),
+                    None => Ok(lens),
+                }
+            })
+        } else {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_local_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(lens));
+            };
+            if !GetCodeLens::can_resolve_lens(&lang_server.capabilities()) {
+                return Task::ready(Ok(lens));
+            }
+
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await
+                    .context("code lens resolve LSP request")?;
+                let executable =
+                    GetCodeLens::can_execute_lens(&lang_server.capabilities(), &lsp_lens);
+                Ok(CodeLens {
+                    lsp_lens,
+                    executable,
+                    ..lens
+                })
+            })
+        }
+    }
+
+    /// Fetches the links of the buffer, reusing the ones fetched for the same version of it.
+    pub fn document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        let links = match self.document_links.get(&buffer_id) {
+            Some((cached_version, links)) if *cached_version == version => links.clone(),
+            _ => {
+                let request = self.request_document_links(buffer, cx);
+                let links = cx
+                    .background_executor()
+                    .spawn(async move { request.await.map_err(Arc::new) })
+                    .shared();
+                self.document_links
+                    .insert(buffer_id, (version, links.clone()));
+                links
+            }
+        };
+        cx.background_executor()
+            .spawn(async move { links.await.map_err(|error| anyhow!("{error:#}")) })
+    }
+
+    fn request_document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        let Some(server_id) = self
+            .as_local()
+            .and_then(|local| {
+                local
+                    .language_servers_for_buffer(buffer.read(cx), cx)
+                    .find(|(_, server)| server.capabilities().document_link_provider.is_some())
+                    .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            })
+            .or_else(|| {
+                self.upstream_client()
+                    .is_some()
+                    .then_some(LanguageServerToQuery::Primary)
+            })
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        self.request_lsp(buffer.clone(), server_id, GetDocumentLinks, cx)
+    }
+
+    pub fn resolve_document_link(
+        &self,
+        link: DocumentLink,
+        buffer_handle: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        if let Some((upstream_client, project_id)) = self.upstream_client() {
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                link: Some(GetDocumentLinks::serialize_document_link(&link)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = upstream_client
+                    .request(request)
+                    .await
+                    .context("document link proto request")?;
+                match response.link {
+                    Some(resolved_link) => {
+                        GetDocumentLinks::deserialize_document_link(resolved_link)
+                            .context("document link proto resolve response conversion")
+                    }
+                    None => Ok(link),
+                }
+            })
+        } else {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_local_buffer(buffer, link.server_id, cx)
+            else {
+                return Task::ready(Ok(link));
+            };
+            if !GetDocumentLinks::can_resolve_link(&lang_server.capabilities()) {
+                return Task::ready(Ok(link));
+            }
+
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(link.lsp_link.clone())
+                    .await
+                    .context("document link resolve LSP request")?;
+                Ok(DocumentLink { lsp_link, ..link })
+            })
+        }
+    }
+
+    /// Runs the command of a resolved code lens with `workspace/executeCommand`, collecting the edits it makes.
+    pub fn apply_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(command) = lens.lsp_lens.command else {
+            return Task::ready(Err(anyhow!("code lens has no command")));
+        };
+        if !lens.executable {
+            return Task::ready(Err(anyhow!(
+                "code lens command {:?} is not supported by the language server",
+                command.command
+            )));
+        }
+        // A code action consisting only of a command is executed the same way as the lens.
+        let action = CodeAction {
+            server_id: lens.server_id,
+            range: lens.range,
+            lsp_action: lsp::CodeAction {
+                title: command.title.clone(),
+                command: Some(command),
+                ..lsp::CodeAction::default()
+            },
+        };
+        self.apply_code_action(buffer_handle, action, push_to_history, cx)
+    }
+
     pub(crate) fn linked_edit(
         &mut self,
         buffer: &Model<Buffer>,
@@ -4546,6 +4860,172 @@ impl LspStore {
         }
     }
 
+    /// Returns `None` when no language server of the buffer provides folding ranges.
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<Task<Result<Vec<FoldingRange>>>> {
+        let server_id = self
+            .as_local()
+            .and_then(|local| {
+                local
+                    .language_servers_for_buffer(buffer.read(cx), cx)
+                    .find(|(_, server)| {
+                        !matches!(
+                            server.capabilities().folding_range_provider,
+                            None | Some(lsp::FoldingRangeProviderCapability::Simple(false))
+                        )
+                    })
+                    .map(|(_, server)| LanguageServerToQuery::Other(server.server_id()))
+            })
+            .or_else(|| {
+                self.upstream_client()
+                    .is_some()
+                    .then_some(LanguageServerToQuery::Primary)
+            })?;
+
+        Some(self.request_lsp(buffer.clone(), server_id, GetFoldingRanges, cx))
+    }
+
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<BufferSemanticTokens>> {
+        if let Some((client, project_id)) = self.upstream_client() {
+            let request = proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer.read(cx).remote_id().into(),
+                version: serialize_version(&buffer.read(cx).version()),
+            };
+            let buffer = buffer.clone();
+            return cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("semantic tokens proto request")?;
+                semantic_tokens_from_proto(response, &buffer, &mut cx).await
+            });
+        }
+
+        let Some(local) = self.as_local() else {
+            return Task::ready(Ok(BufferSemanticTokens::default()));
+        };
+        let Some(abs_path) = File::from_dyn(buffer.read(cx).file()).map(|file| file.abs_path(cx))
+        else {
+            return Task::ready(Ok(BufferSemanticTokens::default()));
+        };
+        let Some((server, options)) = self
+            .language_servers_for_local_buffer(buffer.read(cx), cx)
+            .find_map(|(_, server)| {
+                let options = match server.capabilities().semantic_tokens_provider? {
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
+                        options
+                    }
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
+                        options,
+                    ) => options.semantic_tokens_options,
+                };
+                Some((server.clone(), options))
+            })
+        else {
+            return Task::ready(Ok(BufferSemanticTokens::default()));
+        };
+        let supports_delta = match options.full {
+            None | Some(lsp::SemanticTokensFullOptions::Bool(false)) => {
+                return Task::ready(Ok(BufferSemanticTokens::default()));
+            }
+            Some(lsp::SemanticTokensFullOptions::Bool(true)) => false,
+            Some(lsp::SemanticTokensFullOptions::Delta { delta }) => delta.unwrap_or(false),
+        };
+
+        // Tokens are reported against the latest version of the buffer sent to the server.
+        let buffer_id = buffer.read(cx).remote_id();
+        let server_id = server.server_id();
+        let Some(snapshot) = local
+            .buffer_snapshots
+            .get(&buffer_id)
+            .and_then(|snapshots| snapshots.get(&server_id)?.last())
+            .map(|snapshot| snapshot.snapshot.clone())
+        else {
+            return Task::ready(Ok(BufferSemanticTokens::default()));
+        };
+        let previous_tokens = local
+            .semantic_tokens
+            .get(&buffer_id)
+            .and_then(|tokens| tokens.get(&server_id))
+            .filter(|_| supports_delta)
+            .cloned();
+
+        cx.spawn(move |this, mut cx| async move {
+            let text_document = lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(&abs_path)
+                    .map_err(|_| anyhow!("failed to convert {abs_path:?} to a URL"))?,
+            );
+            let (result_id, data) = if let Some(previous_tokens) = previous_tokens {
+                let response = server
+                    .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document,
+                            previous_result_id: previous_tokens.result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await
+                    .context("semantic tokens delta LSP request")?;
+                match response {
+                    Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                        (tokens.result_id, tokens.data)
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => (
+                        delta.result_id,
+                        apply_semantic_token_edits(previous_tokens.data, delta.edits),
+                    ),
+                    Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => (
+                        None,
+                        apply_semantic_token_edits(previous_tokens.data, edits),
+                    ),
+                    None => (None, Vec::new()),
+                }
+            } else {
+                let response = server
+                    .request::<lsp::request::SemanticTokensFullRequest>(lsp::SemanticTokensParams {
+                        text_document,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    })
+                    .await
+                    .context("semantic tokens LSP request")?;
+                match response {
+                    Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                        (tokens.result_id, tokens.data)
+                    }
+                    Some(lsp::SemanticTokensResult::Partial(partial)) => (None, partial.data),
+                    None => (None, Vec::new()),
+                }
+            };
+
+            let tokens = semantic_tokens_from_lsp(&data, &options.legend, &snapshot);
+            this.update(&mut cx, |this, _| {
+                if let Some(local) = this.as_local_mut() {
+                    let cached_tokens = local.semantic_tokens.entry(buffer_id).or_default();
+                    match result_id {
+                        Some(result_id) if supports_delta => {
+                            cached_tokens
+                                .insert(server_id, CachedSemanticTokens { result_id, data });
+                        }
+                        _ => {
+                            cached_tokens.remove(&server_id);
+                        }
+                    }
+                }
+            })?;
+            Ok(tokens)
+        })
+    }
+
     pub fn signature_help<T: ToPointUtf16>(
         &mut self,
         buffer: &Model<Buffer>,
@@ -4917,6 +5397,7 @@ impl LspStore {
         cx: &mut ModelContext<Self>,
     ) -> Option<()> {
         let buffer = buffer.read(cx);
+        let buffer_id = buffer.remote_id();
         let file = File::from_dyn(buffer.file())?;
         let abs_path = file.as_local()?.abs_path(cx);
         let uri = lsp::Url::from_file_path(abs_path).unwrap();
@@ -5011,6 +5492,7 @@ impl LspStore {
                 .log_err();
         }
 
+        self.pull_diagnostics_debounced([buffer_id], [], cx);
         None
     }
 
@@ -5045,13 +5527,267 @@ impl LspStore {
             }
         }
 
-        for language_server_id in local.language_server_ids_for_buffer(buffer.read(cx), cx) {
+        let language_server_ids = local.language_server_ids_for_buffer(buffer.read(cx), cx);
+        for &language_server_id in &language_server_ids {
             self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
         }
 
+        // Saving can change the diagnostics of other documents too.
+        self.pull_diagnostics_debounced([buffer.read(cx).remote_id()], language_server_ids, cx);
         None
     }
 
+    /// Schedules a pull of the diagnostics of the given buffers, and of the whole workspace
+    /// of the given servers, from the language servers that report diagnostics on request
+    /// instead of publishing them.
+    fn pull_diagnostics_debounced(
+        &mut self,
+        buffer_ids: impl IntoIterator<Item = BufferId>,
+        workspace_server_ids: impl IntoIterator<Item = LanguageServerId>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        local.buffers_to_pull_diagnostics.extend(buffer_ids);
+        local
+            .servers_to_pull_workspace_diagnostics
+            .extend(workspace_server_ids);
+        local
+            .diagnostics_pull_debouncer
+            .fire_new(DIAGNOSTICS_PULL_DEBOUNCE, cx, |this, cx| {
+                this.pull_diagnostics(cx)
+            });
+    }
+
+    fn pull_diagnostics(&mut self, cx: &mut ModelContext<Self>) -> Task<()> {
+        let Some(local) = self.as_local_mut() else {
+            return Task::ready(());
+        };
+        let buffer_ids = mem::take(&mut local.buffers_to_pull_diagnostics);
+        let server_ids = mem::take(&mut local.servers_to_pull_workspace_diagnostics);
+
+        let mut pulls = Vec::new();
+        for buffer_id in buffer_ids {
+            if let Some(buffer) = self.buffer_store.read(cx).get(buffer_id) {
+                pulls.extend(self.pull_document_diagnostics(&buffer, cx));
+            }
+        }
+        for server_id in server_ids {
+            self.pull_workspace_diagnostics(server_id, cx);
+        }
+        cx.spawn(|_, _| async move {
+            join_all(pulls).await;
+        })
+    }
+
+    fn pull_document_diagnostics(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Vec<Task<()>> {
+        let Some(local) = self.as_local() else {
+            return Vec::new();
+        };
+        let buffer = buffer.read(cx);
+        let Some(uri) = File::from_dyn(buffer.file())
+            .and_then(|file| file.as_local())
+            .and_then(|file| lsp::Url::from_file_path(file.abs_path(cx)).log_err())
+        else {
+            return Vec::new();
+        };
+
+        local
+            .language_servers_for_buffer(buffer, cx)
+            .filter_map(|(adapter, server)| {
+                let options = diagnostic_options(server)?;
+                let server_id = server.server_id();
+                // Pulled diagnostics are relative to the last version of the document sent to the server.
+                let version = local
+                    .buffer_snapshots
+                    .get(&buffer.remote_id())?
+                    .get(&server_id)?
+                    .last()?
+                    .version;
+                let previous_result_id = local
+                    .diagnostic_result_ids
+                    .get(&server_id)
+                    .and_then(|result_ids| result_ids.get(&uri))
+                    .cloned();
+                let request = server.request::<lsp::request::DocumentDiagnosticRequest>(
+                    lsp::DocumentDiagnosticParams {
+                        text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                        identifier: options.identifier,
+                        previous_result_id,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    },
+                );
+                let adapter = adapter.clone();
+                let uri = uri.clone();
+                Some(cx.spawn(move |this, mut cx| async move {
+                    let Some(result) = request.await.log_err() else {
+                        return;
+                    };
+                    let (report, related_documents) = match result {
+                        lsp::DocumentDiagnosticReportResult::Report(
+                            lsp::DocumentDiagnosticReport::Full(report),
+                        ) => (
+                            Some(lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            )),
+                            report.related_documents,
+                        ),
+                        lsp::DocumentDiagnosticReportResult::Report(
+                            lsp::DocumentDiagnosticReport::Unchanged(report),
+                        ) => (
+                            Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            )),
+                            report.related_documents,
+                        ),
+                        lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                            (None, report.related_documents)
+                        }
+                    };
+                    this.update(&mut cx, |this, cx| {
+                        let reports = report
+                            .map(|report| (uri, Some(version), report))
+                            .into_iter()
+                            .chain(
+                                related_documents
+                                    .into_iter()
+                                    .flatten()
+                                    .map(|(uri, report)| (uri, None, report)),
+                            );
+                        for (uri, version, report) in reports {
+                            this.apply_pulled_diagnostics(
+                                server_id, &adapter, uri, version, report, cx,
+                            );
+                        }
+                    })
+                    .ok();
+                }))
+            })
+            .collect()
+    }
+
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<()> {
+        let local = self.as_local_mut()?;
+        let LanguageServerState::Running {
+            adapter, server, ..
+        } = local.language_servers.get(&server_id)?
+        else {
+            return None;
+        };
+        let options = diagnostic_options(server)?;
+        if !options.workspace_diagnostics {
+            return None;
+        }
+
+        let previous_result_ids = local
+            .diagnostic_result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .map(|(uri, result_id)| lsp::PreviousResultId {
+                uri: uri.clone(),
+                value: result_id.clone(),
+            })
+            .collect();
+        let request = server.request::<lsp::request::WorkspaceDiagnosticRequest>(
+            lsp::WorkspaceDiagnosticParams {
+                identifier: options.identifier,
+                previous_result_ids,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+        );
+        let adapter = adapter.clone();
+        // Replacing the previous pull drops it, cancelling the request if it is still pending.
+        let pull = cx.spawn(move |this, mut cx| async move {
+            let Some(result) = request.await.log_err() else {
+                return;
+            };
+            let items = match result {
+                lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                        ),
+                    };
+                    let version = version.map(|version| version as i32);
+                    this.apply_pulled_diagnostics(server_id, &adapter, uri, version, report, cx);
+                }
+            })
+            .ok();
+        });
+        self.as_local_mut()?
+            .workspace_diagnostics_pulls
+            .insert(server_id, pull);
+        Some(())
+    }
+
+    /// Records the result id of a pulled diagnostic report, and replaces the diagnostics
+    /// of the document with it unless the server reported them as unchanged.
+    fn apply_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(local) = self.as_local_mut() else {
+            return;
+        };
+        let result_ids = local.diagnostic_result_ids.entry(server_id).or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(uri.clone(), result_id),
+                    None => result_ids.remove(&uri),
+                };
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+            }
+        }
+    }
+
     pub(crate) async fn refresh_workspace_configurations(
         this: &WeakModel<Self>,
         mut cx: AsyncAppContext,
@@ -5203,6 +5939,10 @@ impl LspStore {
                 local
                     .last_workspace_edits_by_language_server
                     .remove(&server_id_to_remove);
+                local.diagnostic_result_ids.remove(&server_id_to_remove);
+                local
+                    .workspace_diagnostics_pulls
+                    .remove(&server_id_to_remove);
                 local.language_servers.remove(&server_id_to_remove);
                 cx.emit(LspStoreEvent::LanguageServerRemoved(server_id_to_remove));
             }
@@ -6570,6 +7310,52 @@ impl LspStore {
         Ok(proto::Ack {})
     }
 
+    async fn handle_get_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+        let tokens = this
+            .update(&mut cx, |lsp_store, cx| {
+                lsp_store.semantic_tokens(&buffer, cx)
+            })?
+            .await
+            .context("semantic tokens fetch")?;
+        Ok(semantic_tokens_to_proto(tokens))
+    }
+
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshSemanticTokens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_refresh_code_lens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |_, cx| {
+            cx.emit(LspStoreEvent::RefreshCodeLens);
+        })?;
+        Ok(proto::Ack {})
+    }
+
     async fn handle_inlay_hints(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::InlayHints>,
@@ -6646,6 +7432,82 @@ impl LspStore {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = envelope
+            .payload
+            .lens
+            .ok_or_else(|| anyhow!("missing code lens"))?;
+        let lens = GetCodeLens::deserialize_code_lens(lens)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let resolved_lens = this
+            .update(&mut cx, |this, cx| this.resolve_code_lens(lens, buffer, cx))?
+            .await
+            .context("resolving code lens")?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(GetCodeLens::serialize_code_lens(&resolved_lens)),
+        })
+    }
+
+    async fn handle_get_document_links(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetDocumentLinks>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetDocumentLinksResponse> {
+        let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let buffer_id = GetDocumentLinks::buffer_id_from_proto(&envelope.payload)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        GetDocumentLinks::from_proto(envelope.payload, this.clone(), buffer.clone(), cx.clone())
+            .await?;
+        // Unlike other requests, links come from whichever server provides them,
+        // rather than from the primary server.
+        let links = this
+            .update(&mut cx, |this, cx| this.document_links(&buffer, cx))?
+            .await?;
+        this.update(&mut cx, |this, cx| {
+            GetDocumentLinks::response_to_proto(
+                links,
+                this,
+                sender_id,
+                &buffer.read(cx).version(),
+                cx,
+            )
+        })
+    }
+
+    async fn handle_resolve_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = envelope
+            .payload
+            .link
+            .ok_or_else(|| anyhow!("missing document link"))?;
+        let link = GetDocumentLinks::deserialize_document_link(link)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        let resolved_link = this
+            .update(&mut cx, |this, cx| {
+                this.resolve_document_link(link, buffer, cx)
+            })?
+            .await
+            .context("resolving document link")?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(GetDocumentLinks::serialize_document_link(&resolved_link)),
+        })
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -7096,6 +7958,8 @@ impl LspStore {
             });
         }
         local.language_server_watched_paths.remove(&server_id);
+        local.diagnostic_result_ids.remove(&server_id);
+        local.workspace_diagnostics_pulls.remove(&server_id);
         let server_state = local.language_servers.remove(&server_id);
         cx.notify();
         cx.emit(LspStoreEvent::LanguageServerRemoved(server_id));
@@ -7407,6 +8271,7 @@ impl LspStore {
         }
 
         // Tell the language server about every open buffer in the worktree that matches the language.
+        let mut opened_buffer_ids = Vec::new();
         self.buffer_store.clone().update(cx, |buffer_store, cx| {
             for buffer_handle in buffer_store.buffers() {
                 let buffer = buffer_handle.read(cx);
@@ -7465,6 +8330,7 @@ impl LspStore {
                             },
                         )
                         .log_err();
+                    opened_buffer_ids.push(buffer.remote_id());
                 }
 
                 buffer_handle.update(cx, |buffer, cx| {
@@ -7486,6 +8352,7 @@ impl LspStore {
                 });
             }
         });
+        self.pull_diagnostics_debounced(opened_buffer_ids, [server_id], cx);
 
         cx.notify();
     }
@@ -8079,6 +8946,12 @@ struct LspBufferSnapshot {
     snapshot: TextBufferSnapshot,
 }
 
+#[derive(Clone)]
+struct CachedSemanticTokens {
+    result_id: String,
+    data: Vec<lsp::SemanticToken>,
+}
+
 /// A prompt requested by LSP server.
 #[derive(Clone, Debug)]
 pub struct LanguageServerPromptRequest {
@@ -8600,6 +9473,147 @@ async fn populate_labels_for_symbols(
     }
 }
 
+/// Applies the edits of a `textDocument/semanticTokens/full/delta` response.
+/// Edit offsets count the integers of the encoded token data, not the tokens themselves.
+fn apply_semantic_token_edits(
+    tokens: Vec<lsp::SemanticToken>,
+    mut edits: Vec<lsp::SemanticTokensEdit>,
+) -> Vec<lsp::SemanticToken> {
+    fn encode(token: lsp::SemanticToken) -> [u32; 5] {
+        [
+            token.delta_line,
+            token.delta_start,
+            token.length,
+            token.token_type,
+            token.token_modifiers_bitset,
+        ]
+    }
+
+    let mut data = tokens.into_iter().flat_map(encode).collect::<Vec<_>>();
+    edits.sort_by_key(|edit| edit.start);
+    for edit in edits.into_iter().rev() {
+        let start = (edit.start as usize).min(data.len());
+        let end = (start + edit.delete_count as usize).min(data.len());
+        let inserted = edit.data.unwrap_or_default().into_iter().flat_map(encode);
+        data.splice(start..end, inserted);
+    }
+    data.chunks_exact(5)
+        .map(|token| lsp::SemanticToken {
+            delta_line: token[0],
+            delta_start: token[1],
+            length: token[2],
+            token_type: token[3],
+            token_modifiers_bitset: token[4],
+        })
+        .collect()
+}
+
+fn semantic_tokens_from_lsp(
+    data: &[lsp::SemanticToken],
+    legend: &lsp::SemanticTokensLegend,
+    snapshot: &TextBufferSnapshot,
+) -> BufferSemanticTokens {
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut line = 0;
+    let mut start = 0;
+    for token in data {
+        if token.delta_line > 0 {
+            line += token.delta_line;
+            start = token.delta_start;
+        } else {
+            start += token.delta_start;
+        }
+        let token_start =
+            snapshot.clip_point_utf16(Unclipped(PointUtf16::new(line, start)), Bias::Left);
+        let token_end = snapshot.clip_point_utf16(
+            Unclipped(PointUtf16::new(line, start + token.length)),
+            Bias::Left,
+        );
+        tokens.push(SemanticToken {
+            range: snapshot.anchor_after(token_start)..snapshot.anchor_before(token_end),
+            token_type: token.token_type,
+            token_modifiers: token.token_modifiers_bitset,
+        });
+    }
+    BufferSemanticTokens {
+        token_types: legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect(),
+        token_modifiers: legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect(),
+        tokens,
+    }
+}
+
+fn semantic_tokens_to_proto(tokens: BufferSemanticTokens) -> proto::GetSemanticTokensResponse {
+    proto::GetSemanticTokensResponse {
+        token_types: tokens.token_types.to_vec(),
+        token_modifiers: tokens.token_modifiers.to_vec(),
+        tokens: tokens
+            .tokens
+            .into_iter()
+            .map(|token| proto::SemanticToken {
+                start: Some(serialize_anchor(&token.range.start)),
+                end: Some(serialize_anchor(&token.range.end)),
+                token_type: token.token_type,
+                token_modifiers: token.token_modifiers,
+            })
+            .collect(),
+    }
+}
+
+async fn semantic_tokens_from_proto(
+    response: proto::GetSemanticTokensResponse,
+    buffer: &Model<Buffer>,
+    cx: &mut AsyncAppContext,
+) -> Result<BufferSemanticTokens> {
+    let mut tokens = Vec::with_capacity(response.tokens.len());
+    for token in response.tokens {
+        let start = token
+            .start
+            .and_then(deserialize_anchor)
+            .context("missing semantic token start")?;
+        let end = token
+            .end
+            .and_then(deserialize_anchor)
+            .context("missing semantic token end")?;
+        tokens.push(SemanticToken {
+            range: start..end,
+            token_type: token.token_type,
+            token_modifiers: token.token_modifiers,
+        });
+    }
+    buffer
+        .update(cx, |buffer, _| {
+            buffer.wait_for_anchors(
+                tokens
+                    .iter()
+                    .flat_map(|token| [token.range.start, token.range.end]),
+            )
+        })?
+        .await?;
+    Ok(BufferSemanticTokens {
+        token_types: response.token_types.into(),
+        token_modifiers: response.token_modifiers.into(),
+        tokens,
+    })
+}
+
+/// The options of a server that provides diagnostics on request, if it does.
+fn diagnostic_options(server: &lsp::LanguageServer) -> Option<lsp::DiagnosticOptions> {
+    match server.capabilities().diagnostic_provider? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(options.diagnostic_options)
+        }
+    }
+}
+
 fn include_text(server: &lsp::LanguageServer) -> Option<bool> {
     match server.capabilities().text_document_sync.as_ref()? {
         lsp::TextDocumentSyncCapability::Kind(kind) => match *kind {
@@ -1,3 +1,4 @@
+pub mod bookmark_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -48,8 +49,8 @@ use itertools::Itertools;
 use language::{
     language_settings::InlayHintKind, proto::split_operations, Buffer, BufferEvent,
     CachedLspAdapter, Capability, CodeLabel, Documentation, File as _, Language, LanguageName,
-    LanguageRegistry, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction,
-    Unclipped,
+    LanguageRegistry, ParseStatus, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList,
+    Transaction, Unclipped,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, LanguageServer,
@@ -102,6 +103,7 @@ pub use worktree::{
     FS_WATCH_LATENCY,
 };
 
+pub use bookmark_store::{Bookmark, BookmarkStore, BookmarkStoreEvent};
 pub use buffer_store::ProjectTransaction;
 pub use lsp_store::{
     DiagnosticSummary, LanguageServerLogType, LanguageServerProgress, LanguageServerPromptRequest,
@@ -154,6 +156,7 @@ pub struct Project {
     worktree_store: Model<WorktreeStore>,
     buffer_store: Model<BufferStore>,
     image_store: Model<ImageStore>,
+    bookmark_store: Model<BookmarkStore>,
     lsp_store: Model<LspStore>,
     _subscriptions: Vec<gpui::Subscription>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
@@ -272,6 +275,8 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
+    RefreshCodeLens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
 }
@@ -393,6 +398,45 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A command shown above a line of a buffer, as returned by `textDocument/codeLens`.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer that this code lens applies to.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server.
+    /// Its command is only present once the lens is resolved.
+    pub lsp_lens: lsp::CodeLens,
+    /// Whether the server lists the command of the lens in its `executeCommandProvider`,
+    /// so that it can be run with `workspace/executeCommand`.
+    pub executable: bool,
+}
+
+impl CodeLens {
+    pub fn title(&self) -> Option<&str> {
+        Some(self.lsp_lens.command.as_ref()?.title.as_str())
+    }
+}
+
+/// A range of a buffer linking to a file or a URL, as returned by `textDocument/documentLink`.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    /// The id of the language server that produced this link.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer that this link covers.
+    pub range: Range<Anchor>,
+    /// The raw document link provided by the language server.
+    /// Its target may only be present once the link is resolved.
+    pub lsp_link: lsp::DocumentLink,
+}
+
+impl DocumentLink {
+    pub fn target(&self) -> Option<&lsp::Url> {
+        self.lsp_link.target.as_ref()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -446,6 +490,101 @@ pub struct LocationLink {
     pub target: Location,
 }
 
+/// A symbol that can be the root of a call hierarchy, as returned by `textDocument/prepareCallHierarchy`.
+#[derive(Debug, Clone)]
+pub struct CallHierarchyItem {
+    /// The full range of the symbol, e.g. the whole function including its body.
+    pub location: Location,
+    /// The range that should be revealed when navigating to the symbol, e.g. its name.
+    pub selection_range: Range<Anchor>,
+    /// The raw item provided by the language server, sent back to it when resolving calls.
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+impl CallHierarchyItem {
+    pub fn name(&self) -> &str {
+        &self.lsp_item.name
+    }
+
+    pub fn kind(&self) -> lsp::SymbolKind {
+        self.lsp_item.kind
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.lsp_item.detail.as_deref()
+    }
+}
+
+/// A symbol that can be the root of a type hierarchy, as returned by `textDocument/prepareTypeHierarchy`.
+/// The LSP specification defines type hierarchy items with the same shape as call hierarchy items.
+pub type TypeHierarchyItem = CallHierarchyItem;
+
+/// An incoming or outgoing call of a [`CallHierarchyItem`].
+#[derive(Debug, Clone)]
+pub struct CallHierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// The locations of the call expressions.
+    /// For incoming calls these are inside the caller, for outgoing calls inside the item whose calls were requested.
+    pub call_sites: Vec<Location>,
+}
+
+/// The semantic tokens of a buffer, as reported by its primary language server.
+#[derive(Clone, Debug, Default)]
+pub struct BufferSemanticTokens {
+    /// The token types of the server's legend.
+    pub token_types: Arc<[String]>,
+    /// The token modifiers of the server's legend.
+    pub token_modifiers: Arc<[String]>,
+    /// The tokens, sorted by their position in the buffer and non-overlapping.
+    pub tokens: Vec<SemanticToken>,
+}
+
+impl BufferSemanticTokens {
+    pub fn token_type(&self, token: &SemanticToken) -> Option<&str> {
+        self.token_types
+            .get(token.token_type as usize)
+            .map(String::as_str)
+    }
+
+    pub fn token_modifiers<'a>(
+        &'a self,
+        token: &'a SemanticToken,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.token_modifiers
+            .iter()
+            .take(u32::BITS as usize)
+            .enumerate()
+            .filter(|(ix, _)| token.token_modifiers & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticToken {
+    pub range: Range<language::Anchor>,
+    /// An index into [`BufferSemanticTokens::token_types`].
+    pub token_type: u32,
+    /// A bitset of indices into [`BufferSemanticTokens::token_modifiers`].
+    pub token_modifiers: u32,
+}
+
+/// A foldable region of a buffer, as reported by `textDocument/foldingRange`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FoldingRange {
+    /// Spans from the end of the range's first line to the end of its last line,
+    /// so that the first line stays visible when the range is folded.
+    pub range: Range<language::Anchor>,
+    pub kind: Option<FoldingRangeKind>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FoldingRangeKind {
+    Comment,
+    Imports,
+    Region,
+}
+
 #[derive(Debug)]
 pub struct DocumentHighlight {
     pub range: Range<language::Anchor>,
@@ -621,6 +760,10 @@ impl Project {
             cx.subscribe(&image_store, Self::on_image_store_event)
                 .detach();
 
+            let bookmark_store = cx.new_model(|cx| {
+                BookmarkStore::new(buffer_store.clone(), worktree_store.clone(), cx)
+            });
+
             let prettier_store = cx.new_model(|cx| {
                 PrettierStore::new(
                     node.clone(),
@@ -683,6 +826,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                bookmark_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -756,6 +900,9 @@ impl Project {
             });
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let bookmark_store = cx.new_model(|cx| {
+                BookmarkStore::new(buffer_store.clone(), worktree_store.clone(), cx)
+            });
             let toolchain_store = cx.new_model(|cx| {
                 ToolchainStore::remote(SSH_PROJECT_ID, ssh.read(cx).proto_client(), cx)
             });
@@ -801,6 +948,7 @@ impl Project {
                 worktree_store,
                 buffer_store,
                 image_store,
+                bookmark_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -950,6 +1098,8 @@ impl Project {
         let image_store = cx.new_model(|cx| {
             ImageStore::remote(worktree_store.clone(), client.clone().into(), remote_id, cx)
         })?;
+        let bookmark_store = cx
+            .new_model(|cx| BookmarkStore::new(buffer_store.clone(), worktree_store.clone(), cx))?;
 
         let lsp_store = cx.new_model(|cx| {
             let mut lsp_store = LspStore::new_remote(
@@ -1014,6 +1164,7 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
                 image_store,
+                bookmark_store,
                 worktree_store: worktree_store.clone(),
                 lsp_store: lsp_store.clone(),
                 active_entry: None,
@@ -2191,6 +2342,8 @@ impl Project {
                 };
             }
             LspStoreEvent::RefreshInlayHints => cx.emit(Event::RefreshInlayHints),
+            LspStoreEvent::RefreshSemanticTokens => cx.emit(Event::RefreshSemanticTokens),
+            LspStoreEvent::RefreshCodeLens => cx.emit(Event::RefreshCodeLens),
             LspStoreEvent::LanguageServerPrompt(prompt) => {
                 cx.emit(Event::LanguageServerPrompt(prompt.clone()))
             }
@@ -2724,6 +2877,96 @@ impl Project {
         )
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetIncomingCalls {
+                lsp_item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetOutgoingCalls {
+                lsp_item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &mut self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSupertypes {
+                lsp_item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &mut self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetSubtypes {
+                lsp_item: item.lsp_item.clone(),
+            },
+            cx,
+        )
+    }
+
     pub fn references<T: ToPointUtf16>(
         &mut self,
         buffer: &Model<Buffer>,
@@ -3018,6 +3261,76 @@ impl Project {
         })
     }
 
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<BufferSemanticTokens>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.semantic_tokens(buffer, cx))
+    }
+
+    pub fn code_lens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.code_lens(buffer, cx))
+    }
+
+    pub fn resolve_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_code_lens(lens, buffer, cx)
+        })
+    }
+
+    pub fn apply_code_lens(
+        &self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        push_to_history: bool,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.apply_code_lens(buffer, lens, push_to_history, cx)
+        })
+    }
+
+    pub fn document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.document_links(buffer, cx))
+    }
+
+    pub fn resolve_document_link(
+        &self,
+        buffer: Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_document_link(link, buffer, cx)
+        })
+    }
+
+    pub fn folding_ranges(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Option<Task<Result<Vec<FoldingRange>>>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.folding_ranges(buffer, cx))
+    }
+
     pub fn search(
         &mut self,
         query: SearchQuery,
@@ -3043,6 +3356,23 @@ impl Project {
             // opened buffer, we will spawn a background task that retrieves all the
             // ranges in the buffer matched by the query.
             'outer: while let Some(matching_buffer_chunk) = chunks.next().await {
+                if query.is_structural() {
+                    // Structural queries match syntax trees, so wait for the buffers' parses.
+                    let parses = matching_buffer_chunk
+                        .iter()
+                        .map(|buffer| buffer.read_with(&cx, |buffer, _| buffer.parse_status()))
+                        .collect::<Result<Vec<_>>>()?
+                        .into_iter()
+                        .map(|mut parse_status| async move {
+                            while *parse_status.borrow() != ParseStatus::Idle {
+                                if parse_status.changed().await.is_err() {
+                                    break;
+                                }
+                            }
+                        });
+                    futures::future::join_all(parses).await;
+                }
+
                 let mut chunk_results = Vec::new();
                 for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
@@ -3587,6 +3917,87 @@ impl Project {
             .update_or_create_branch(repository, new_branch, cx)
     }
 
+    pub fn staged_paths(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<git::repository::RepoPath>>> {
+        self.worktree_store().read(cx).staged_paths(repository, cx)
+    }
+
+    pub fn stage_paths(
+        &self,
+        repository: ProjectPath,
+        paths: Vec<git::repository::RepoPath>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .stage_paths(repository, paths, cx)
+    }
+
+    pub fn unstage_paths(
+        &self,
+        repository: ProjectPath,
+        paths: Vec<git::repository::RepoPath>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .unstage_paths(repository, paths, cx)
+    }
+
+    pub fn commit(
+        &self,
+        repository: ProjectPath,
+        message: String,
+        amend: bool,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .commit(repository, message, amend, cx)
+    }
+
+    pub fn stash(
+        &self,
+        repository: ProjectPath,
+        message: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        self.worktree_store()
+            .read(cx)
+            .stash(repository, message, cx)
+    }
+
+    pub fn stash_pop(&self, repository: ProjectPath, cx: &AppContext) -> Task<Result<()>> {
+        self.worktree_store().read(cx).stash_pop(repository, cx)
+    }
+
+    pub fn git_log(
+        &self,
+        repository: ProjectPath,
+        path: Option<git::repository::RepoPath>,
+        after: Option<String>,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<git::commit::CommitLog>> {
+        self.worktree_store()
+            .read(cx)
+            .git_log(repository, path, after, limit, cx)
+    }
+
+    pub fn commit_diff(
+        &self,
+        repository: ProjectPath,
+        sha: String,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<git::repository::CommitFileDiff>>> {
+        self.worktree_store()
+            .read(cx)
+            .commit_diff(repository, sha, cx)
+    }
+
     pub fn blame_buffer(
         &self,
         buffer: &Model<Buffer>,
@@ -4199,6 +4610,10 @@ impl Project {
     pub fn buffer_store(&self) -> &Model<BufferStore> {
         &self.buffer_store
     }
+
+    pub fn bookmark_store(&self) -> &Model<BookmarkStore> {
+        &self.bookmark_store
+    }
 }
 
 fn deserialize_code_actions(code_actions: &HashMap<String, bool>) -> Vec<lsp::CodeActionKind> {
@@ -19,7 +19,13 @@ use pretty_assertions::{assert_eq, assert_matches};
 use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
-use std::{str::FromStr, sync::OnceLock};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        OnceLock,
+    },
+};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
 use task::{ResolvedTask, TaskContext};
@@ -2150,6 +2156,318 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_pulled_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = b;",
+            "b.rs": "let b = 1;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        inter_file_dependencies: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let previous_result_ids = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+        let previous_result_ids = previous_result_ids.clone();
+        move |params, _| {
+            let previous_result_ids = previous_result_ids.clone();
+            async move {
+                assert_eq!(
+                    params.text_document.uri,
+                    Url::from_file_path("/dir/a.rs").unwrap()
+                );
+                let previous_result_id = params.previous_result_id;
+                previous_result_ids.lock().push(previous_result_id.clone());
+                if let Some(result_id) = previous_result_id {
+                    return Ok(lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Unchanged(
+                            lsp::RelatedUnchangedDocumentDiagnosticReport {
+                                related_documents: None,
+                                unchanged_document_diagnostic_report:
+                                    lsp::UnchangedDocumentDiagnosticReport { result_id },
+                            },
+                        ),
+                    ));
+                }
+
+                let diagnostic = |line, start, end, severity, message: &str| lsp::Diagnostic {
+                    range: lsp::Range::new(
+                        lsp::Position::new(line, start),
+                        lsp::Position::new(line, end),
+                    ),
+                    severity: Some(severity),
+                    message: message.to_string(),
+                    ..Default::default()
+                };
+                Ok(lsp::DocumentDiagnosticReportResult::Report(
+                    lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                        related_documents: Some(
+                            [(
+                                Url::from_file_path("/dir/b.rs").unwrap(),
+                                lsp::DocumentDiagnosticReportKind::Full(
+                                    lsp::FullDocumentDiagnosticReport {
+                                        result_id: None,
+                                        items: vec![diagnostic(
+                                            0,
+                                            4,
+                                            5,
+                                            DiagnosticSeverity::WARNING,
+                                            "unused variable 'b'",
+                                        )],
+                                    },
+                                ),
+                            )]
+                            .into_iter()
+                            .collect(),
+                        ),
+                        full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                            result_id: Some("1".to_string()),
+                            items: vec![diagnostic(
+                                0,
+                                8,
+                                9,
+                                DiagnosticSeverity::ERROR,
+                                "unknown variable 'b'",
+                            )],
+                        },
+                    }),
+                ))
+            }
+        }
+    });
+
+    // Diagnostics are pulled once the buffer is opened with the server.
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(*previous_result_ids.lock(), [None]);
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("let a = ".to_string(), None),
+                ("b".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (";".to_string(), None),
+            ]
+        );
+    });
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 1,
+            }
+        );
+    });
+
+    // Edits are followed by a single pull, which reuses the result id of the last report.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "\n")], None, cx);
+        buffer.edit([(0..0, "\n")], None, cx);
+    });
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(*previous_result_ids.lock(), [None, Some("1".to_string())]);
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("\n\nlet a = ".to_string(), None),
+                ("b".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (";".to_string(), None),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_pulled_workspace_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = 1;",
+            "b.rs": "let b = 1;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let document_pulls = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+        let document_pulls = document_pulls.clone();
+        move |_, _| {
+            document_pulls.fetch_add(1, SeqCst);
+            async move {
+                Ok(lsp::DocumentDiagnosticReportResult::Report(
+                    lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                            result_id: Some("a".to_string()),
+                            items: Vec::new(),
+                        },
+                    }),
+                ))
+            }
+        }
+    });
+    // The previous result ids sent with each workspace pull.
+    let workspace_pulls = Arc::new(Mutex::new(Vec::<Vec<(String, String)>>::new()));
+    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>({
+        let workspace_pulls = workspace_pulls.clone();
+        move |params, _| {
+            let mut previous_result_ids = params
+                .previous_result_ids
+                .into_iter()
+                .map(|result_id| (result_id.uri.path().to_string(), result_id.value))
+                .collect::<Vec<_>>();
+            previous_result_ids.sort();
+            workspace_pulls.lock().push(previous_result_ids);
+            async move {
+                Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                    lsp::WorkspaceDiagnosticReport {
+                        items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                            lsp::WorkspaceFullDocumentDiagnosticReport {
+                                uri: Url::from_file_path("/dir/b.rs").unwrap(),
+                                version: None,
+                                full_document_diagnostic_report:
+                                    lsp::FullDocumentDiagnosticReport {
+                                        result_id: Some("b".to_string()),
+                                        items: vec![lsp::Diagnostic {
+                                            range: lsp::Range::new(
+                                                lsp::Position::new(0, 4),
+                                                lsp::Position::new(0, 5),
+                                            ),
+                                            severity: Some(DiagnosticSeverity::WARNING),
+                                            message: "unused variable 'b'".to_string(),
+                                            ..Default::default()
+                                        }],
+                                    },
+                            },
+                        )],
+                    },
+                ))
+            }
+        }
+    });
+
+    // Once the server starts, both the open document and the workspace are pulled.
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(document_pulls.load(SeqCst), 1);
+    assert_eq!(workspace_pulls.lock().len(), 1);
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 0,
+                warning_count: 1,
+            }
+        );
+    });
+
+    // Edits only pull the diagnostics of the edited document.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(document_pulls.load(SeqCst), 2);
+    assert_eq!(workspace_pulls.lock().len(), 1);
+
+    // Saving also pulls the diagnostics of the workspace, with the result ids of all documents.
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(document_pulls.load(SeqCst), 3);
+    assert_eq!(
+        workspace_pulls.lock().last().unwrap(),
+        &[
+            ("/dir/a.rs".to_string(), "a".to_string()),
+            ("/dir/b.rs".to_string(), "b".to_string()),
+        ]
+    );
+
+    // A refresh requested by the server pulls everything again. Closed documents are no longer
+    // sent with their result ids.
+    drop(handle);
+    drop(buffer);
+    cx.executor().run_until_parked();
+    fake_server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor().advance_clock(Duration::from_secs(1));
+    cx.executor().run_until_parked();
+    assert_eq!(workspace_pulls.lock().len(), 3);
+    assert_eq!(
+        workspace_pulls.lock().last().unwrap(),
+        &[("/dir/b.rs".to_string(), "b".to_string())]
+    );
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2633,14 +2951,15 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.ts": "",
+            "a.rs": "fn a() {}\n",
+            "b.rs": "fn b() {\n    crate::a();\n}\n",
         }),
     )
     .await;
@@ -2648,15 +2967,12 @@ async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(typescript_lang());
-    let mut fake_language_servers = language_registry.register_fake_lsp(
-        "TypeScript",
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
         FakeLspAdapter {
             capabilities: lsp::ServerCapabilities {
-                completion_provider: Some(lsp::CompletionOptions {
-                    trigger_characters: Some(vec![":".to_string()]),
-                    ..Default::default()
-                }),
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
                 ..Default::default()
             },
             ..Default::default()
@@ -2664,61 +2980,559 @@ async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
     );
 
     let (buffer, _handle) = project
-        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
         .await
         .unwrap();
 
-    let fake_server = fake_language_servers.next().await.unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    let item_for = |name: &str, path: &str, range: lsp::Range| lsp::CallHierarchyItem {
+        name: name.to_string(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path(path).unwrap(),
+        range,
+        selection_range: range,
+        data: None,
+    };
+    let a_item = item_for(
+        "a",
+        "/dir/a.rs",
+        lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+    );
+    let b_item = item_for(
+        "b",
+        "/dir/b.rs",
+        lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+    );
 
-    let text = "let a = b.fqn";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len(), DEFAULT_COMPLETION_CONTEXT, cx)
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>({
+        let a_item = a_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 3)
+                );
+                Ok(Some(vec![a_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>({
+        let a_item = a_item.clone();
+        move |params, _| {
+            let a_item = a_item.clone();
+            let b_item = b_item.clone();
+            async move {
+                assert_eq!(params.item, a_item);
+                Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                    from: b_item,
+                    from_ranges: vec![lsp::Range::new(
+                        lsp::Position::new(1, 11),
+                        lsp::Position::new(1, 12),
+                    )],
+                }]))
+            }
+        }
     });
 
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "fullyQualifiedName?".into(),
-                    insert_text: Some("fullyQualifiedName".into()),
-                    ..Default::default()
-                },
-            ])))
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, 3, cx)
         })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "fullyQualifiedName");
-    assert_eq!(
-        completions[0].old_range.to_offset(&snapshot),
-        text.len() - 3..text.len()
-    );
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name(), "a");
 
-    let text = "let a = \"atoms/cmp\"";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len() - 1, DEFAULT_COMPLETION_CONTEXT, cx)
+    let calls = project
+        .update(cx, |project, cx| project.incoming_calls(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    cx.update(|cx| {
+        let call = &calls[0];
+        assert_eq!(call.item.name(), "b");
+        let caller_buffer = call.item.location.buffer.read(cx);
+        assert_eq!(
+            caller_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(call.call_sites.len(), 1);
+        assert_eq!(call.call_sites[0].buffer, call.item.location.buffer);
+        assert_eq!(
+            call.call_sites[0].range.to_point(caller_buffer),
+            Point::new(1, 11)..Point::new(1, 12)
+        );
     });
+}
 
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "component".into(),
-                    ..Default::default()
-                },
-            ])))
-        })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "component");
-    assert_eq!(
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "trait Shape {}\n",
+            "b.rs": "struct Square;\nimpl Shape for Square {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                type_hierarchy_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    let item_for =
+        |name: &str, kind: lsp::SymbolKind, path: &str, range: lsp::Range| lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path(path).unwrap(),
+            range,
+            selection_range: range,
+            data: None,
+        };
+    let shape_item = item_for(
+        "Shape",
+        lsp::SymbolKind::INTERFACE,
+        "/dir/a.rs",
+        lsp::Range::new(lsp::Position::new(0, 6), lsp::Position::new(0, 11)),
+    );
+    let square_item = item_for(
+        "Square",
+        lsp::SymbolKind::STRUCT,
+        "/dir/b.rs",
+        lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 13)),
+    );
+
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>({
+        let shape_item = shape_item.clone();
+        move |params, _| {
+            let shape_item = shape_item.clone();
+            async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(0, 7)
+                );
+                Ok(Some(vec![shape_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>({
+        let shape_item = shape_item.clone();
+        let square_item = square_item.clone();
+        move |params, _| {
+            let shape_item = shape_item.clone();
+            let square_item = square_item.clone();
+            async move {
+                assert_eq!(params.item, shape_item);
+                Ok(Some(vec![square_item]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>({
+        let shape_item = shape_item.clone();
+        move |params, _| {
+            let shape_item = shape_item.clone();
+            async move {
+                assert_eq!(params.item.name, "Square");
+                Ok(Some(vec![shape_item]))
+            }
+        }
+    });
+
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, 7, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].name(), "Shape");
+
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(subtypes.len(), 1);
+    cx.update(|cx| {
+        let subtype = &subtypes[0];
+        assert_eq!(subtype.name(), "Square");
+        let subtype_buffer = subtype.location.buffer.read(cx);
+        assert_eq!(
+            subtype_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(
+            subtype.selection_range.to_point(subtype_buffer),
+            Point::new(0, 7)..Point::new(0, 13)
+        );
+    });
+
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&subtypes[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].name(), "Shape");
+    assert_eq!(supertypes[0].location.buffer, buffer);
+}
+
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn main() {\n    let x = 1;\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::VARIABLE,
+                                    lsp::SemanticTokenType::FUNCTION,
+                                ],
+                                token_modifiers: vec![lsp::SemanticTokenModifier::DECLARATION],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    let token =
+        |delta_line, delta_start, length, token_type, token_modifiers_bitset| lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset,
+        };
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        move |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".to_string()),
+                    data: vec![token(0, 3, 4, 1, 1), token(1, 8, 1, 0, 1)],
+                },
+            )))
+        },
+    );
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            // Drop the declaration modifier of the second token.
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".to_string()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 5,
+                        data: Some(vec![token(1, 8, 1, 0, 0)]),
+                    }],
+                },
+            )))
+        },
+    );
+
+    let describe = |tokens: &BufferSemanticTokens, cx: &AppContext| {
+        let buffer = buffer.read(cx);
+        tokens
+            .tokens
+            .iter()
+            .map(|token| {
+                (
+                    token.range.to_point(buffer),
+                    tokens.token_type(token).unwrap().to_string(),
+                    tokens
+                        .token_modifiers(token)
+                        .map(str::to_string)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        assert_eq!(
+            describe(&tokens, cx),
+            vec![
+                (
+                    Point::new(0, 3)..Point::new(0, 7),
+                    "function".to_string(),
+                    vec!["declaration".to_string()]
+                ),
+                (
+                    Point::new(1, 8)..Point::new(1, 9),
+                    "variable".to_string(),
+                    vec!["declaration".to_string()]
+                ),
+            ]
+        );
+    });
+
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    cx.update(|cx| {
+        assert_eq!(
+            describe(&tokens, cx),
+            vec![
+                (
+                    Point::new(0, 3)..Point::new(0, 7),
+                    "function".to_string(),
+                    vec!["declaration".to_string()]
+                ),
+                (
+                    Point::new(1, 8)..Point::new(1, 9),
+                    "variable".to_string(),
+                    Vec::new()
+                ),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "use a;\nuse b;\n\n/// Docs\n/// more docs\nfn main() {\n    1\n}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_with_lsp("/dir/a.rs", cx)
+        })
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fake_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+        let range = |start_line, end_line, kind| lsp::FoldingRange {
+            start_line,
+            start_character: None,
+            end_line,
+            end_character: None,
+            kind,
+            collapsed_text: None,
+        };
+        Ok(Some(vec![
+            range(5, 6, None),
+            range(0, 1, Some(lsp::FoldingRangeKind::Imports)),
+            range(3, 4, Some(lsp::FoldingRangeKind::Comment)),
+            // Single line ranges can't be folded.
+            range(6, 6, Some(lsp::FoldingRangeKind::Region)),
+        ]))
+    });
+
+    let ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&buffer, cx))
+        .unwrap()
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| (range.range.to_point(buffer), range.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Point::new(0, 6)..Point::new(1, 6),
+                    Some(FoldingRangeKind::Imports)
+                ),
+                (
+                    Point::new(3, 8)..Point::new(4, 13),
+                    Some(FoldingRangeKind::Comment)
+                ),
+                (Point::new(5, 11)..Point::new(6, 5), None),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![":".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let text = "let a = b.fqn";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len(), DEFAULT_COMPLETION_CONTEXT, cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "fullyQualifiedName?".into(),
+                    insert_text: Some("fullyQualifiedName".into()),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "fullyQualifiedName");
+    assert_eq!(
+        completions[0].old_range.to_offset(&snapshot),
+        text.len() - 3..text.len()
+    );
+
+    let text = "let a = \"atoms/cmp\"";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len() - 1, DEFAULT_COMPLETION_CONTEXT, cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "component".into(),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "component");
+    assert_eq!(
         completions[0].old_range.to_offset(&snapshot),
         text.len() - 4..text.len() - 1
     );
@@ -2920,6 +3734,155 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "function one() {}\nfunction two() {}\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["_run/function".into()],
+                    ..lsp::ExecuteCommandOptions::default()
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let (buffer, _handle) = project
+        .update(cx, |p, cx| p.open_local_buffer_with_lsp("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    // The second lens only gets its command when resolved.
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 12)),
+                command: None,
+                data: Some(json!({ "function": "two" })),
+            },
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 12)),
+                command: Some(lsp::Command {
+                    title: "1 reference".into(),
+                    command: "_show/references".into(),
+                    arguments: None,
+                }),
+                data: None,
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|mut lens, _| async move {
+        assert_eq!(lens.data, Some(json!({ "function": "two" })));
+        lens.command = Some(lsp::Command {
+            title: "Run".into(),
+            command: "_run/function".into(),
+            arguments: Some(vec![json!("two")]),
+        });
+        Ok(lens)
+    });
+
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            lenses
+                .iter()
+                .map(|lens| (lens.range.to_point(buffer), lens.title().unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Point::new(0, 9)..Point::new(0, 12), "1 reference"),
+                (Point::new(1, 9)..Point::new(1, 12), "Run"),
+            ]
+        );
+    });
+    // Only commands that the server advertises can be executed on it.
+    assert!(!lenses[0].executable);
+    assert!(lenses[1].executable);
+    let error = project
+        .update(cx, |project, cx| {
+            project.apply_code_lens(buffer.clone(), lenses[0].clone(), true, cx)
+        })
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("_show/references"));
+
+    // Running a lens executes its command, and collects the edits the server makes meanwhile.
+    fake_server.handle_request::<lsp::request::ExecuteCommand, _, _>({
+        let fake = fake_server.clone();
+        move |params, _| {
+            assert_eq!(params.command, "_run/function");
+            assert_eq!(params.arguments, vec![json!("two")]);
+            let fake = fake.clone();
+            async move {
+                fake.server
+                    .request::<lsp::request::ApplyWorkspaceEdit>(lsp::ApplyWorkspaceEditParams {
+                        label: None,
+                        edit: lsp::WorkspaceEdit {
+                            changes: Some(
+                                [(
+                                    lsp::Url::from_file_path("/dir/a.ts").unwrap(),
+                                    vec![lsp::TextEdit {
+                                        range: lsp::Range::new(
+                                            lsp::Position::new(0, 0),
+                                            lsp::Position::new(0, 0),
+                                        ),
+                                        new_text: "// ran\n".into(),
+                                    }],
+                                )]
+                                .into_iter()
+                                .collect(),
+                            ),
+                            ..Default::default()
+                        },
+                    })
+                    .await
+                    .unwrap();
+                Ok(Some(json!(null)))
+            }
+        }
+    });
+
+    let transaction = project
+        .update(cx, |project, cx| {
+            project.apply_code_lens(buffer.clone(), lenses[1].clone(), true, cx)
+        })
+        .await
+        .unwrap();
+    assert!(transaction.0.contains_key(&buffer));
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "// ran\nfunction one() {}\nfunction two() {}\n"
+        );
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_save_file(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3644,6 +4607,123 @@ async fn test_buffer_file_changes_on_disk(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.txt": "one\ntwo\nthree\nfour\n",
+            "b.txt": "five\nsix\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let bookmark_store = project.read_with(cx, |project, _| project.bookmark_store().clone());
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+
+    bookmark_store.update(cx, |store, cx| {
+        store.toggle_bookmark(&buffer, 1, cx);
+        store.toggle_bookmark(&buffer, 3, cx);
+        store.name_bookmark(&buffer, 3, Some("last".into()), cx);
+    });
+    let bookmarks = |cx: &mut gpui::TestAppContext| {
+        bookmark_store.read_with(cx, |store, _| {
+            store
+                .bookmarks()
+                .into_iter()
+                .map(|bookmark| {
+                    (
+                        bookmark.abs_path.to_path_buf(),
+                        bookmark.row,
+                        bookmark.name.map(|name| name.to_string()),
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        bookmarks(cx),
+        [
+            (PathBuf::from("/dir/a.txt"), 1, None),
+            (PathBuf::from("/dir/a.txt"), 3, Some("last".to_string())),
+        ]
+    );
+
+    // Bookmarks follow the lines they were placed on.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    assert_eq!(
+        bookmarks(cx),
+        [
+            (PathBuf::from("/dir/a.txt"), 2, None),
+            (PathBuf::from("/dir/a.txt"), 4, Some("last".to_string())),
+        ]
+    );
+
+    // Bookmarks on joined lines are merged, keeping the name.
+    buffer.update(cx, |buffer, cx| {
+        let start = Point::new(1, 3).to_offset(buffer);
+        let end = Point::new(4, 0).to_offset(buffer);
+        buffer.edit([(start..end, " ")], None, cx)
+    });
+    assert_eq!(
+        bookmarks(cx),
+        [(PathBuf::from("/dir/a.txt"), 1, Some("last".to_string()))]
+    );
+
+    // Inserting a newline at the start of a bookmarked line moves the bookmark with the line.
+    buffer.update(cx, |buffer, cx| {
+        let start = Point::new(1, 0).to_offset(buffer);
+        buffer.edit([(start..start, "\n")], None, cx)
+    });
+    assert_eq!(
+        bookmarks(cx),
+        [(PathBuf::from("/dir/a.txt"), 2, Some("last".to_string()))]
+    );
+
+    // Toggling an existing bookmark removes it.
+    bookmark_store.update(cx, |store, cx| store.toggle_bookmark(&buffer, 2, cx));
+    assert!(bookmarks(cx).is_empty());
+
+    // Restored bookmarks are anchored in buffers that are already open.
+    bookmark_store.update(cx, |store, cx| {
+        store.set_bookmarks(
+            vec![
+                Bookmark {
+                    abs_path: Path::new("/dir/a.txt").into(),
+                    row: 0,
+                    name: None,
+                },
+                Bookmark {
+                    abs_path: Path::new("/dir/b.txt").into(),
+                    row: 1,
+                    name: Some("six".into()),
+                },
+            ],
+            cx,
+        )
+    });
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    assert_eq!(
+        bookmarks(cx),
+        [
+            (PathBuf::from("/dir/a.txt"), 1, None),
+            (PathBuf::from("/dir/b.txt"), 1, Some("six".to_string())),
+        ]
+    );
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+    bookmark_store.read_with(cx, |store, _| {
+        assert_eq!(store.bookmarks_for_buffer(buffer_id).count(), 1)
+    });
+}
+
 #[gpui::test]
 async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4287,6 +5367,88 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "fn one() {\n    foo(a, b);\n}\n",
+            "two.rs": "fn two() {\n    foo(\n        x,\n        y,\n    );\n    foo(z);\n    foo(a, b);\n}\n",
+            "three.rs": "fn three() {}\n",
+            "four.rs": "fn four() {\n    bar(a, b);\n}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    // Open buffers are searched with their unsaved edits, once they're reparsed.
+    let buffer_3 = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/three.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    buffer_3.update(cx, |buffer, cx| {
+        buffer.edit([(12..12, "foo(c, d);")], None, cx);
+    });
+
+    let query = || {
+        SearchQuery::structural(
+            "foo($A, $B)",
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        search(&project, query(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![15..24]),
+            ("dir/two.rs".to_string(), vec![15..47, 65..74]),
+            ("dir/three.rs".to_string(), vec![12..21]),
+        ])
+    );
+
+    let buffer_1 = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+    let buffer_2 = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/two.rs", cx)
+        })
+        .await
+        .unwrap();
+    let query = query().with_replacement("bar($B, $A)".to_string());
+    buffer_1.update(cx, |buffer, _| {
+        assert_eq!(
+            query.replacement_for_range(&buffer.snapshot(), 15..24),
+            Some("bar(b, a)".to_string())
+        );
+    });
+    buffer_2.update(cx, |buffer, _| {
+        assert_eq!(
+            query.replacement_for_range(&buffer.snapshot(), 15..47),
+            Some("bar(y, x)".to_string())
+        );
+        // Ranges that aren't matches of the pattern have no replacement.
+        assert_eq!(
+            query.replacement_for_range(&buffer.snapshot(), 53..59),
+            None
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3,7 +3,7 @@ use anyhow::Result;
 use client::proto;
 use fancy_regex::{Captures, Regex, RegexBuilder};
 use gpui::Model;
-use language::{Buffer, BufferSnapshot, CharKind};
+use language::{Buffer, BufferSnapshot, CharKind, StructuralPattern};
 use smol::future::yield_now;
 use std::{
     borrow::Cow,
@@ -74,6 +74,14 @@ pub enum SearchQuery {
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    /// Matches code against the syntax trees of buffers, see [`StructuralPattern`].
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -160,8 +168,39 @@ impl SearchQuery {
         })
     }
 
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        buffers: Option<Vec<Model<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            buffers,
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchQuery) -> Result<Self> {
-        if message.regex {
+        if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -193,6 +232,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -204,6 +247,7 @@ impl SearchQuery {
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -247,14 +291,23 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                // Files are only parsed once opened, so skip the ones missing the pattern's text.
+                let Some(literal) = pattern.literal() else {
+                    return Ok(true);
+                };
+                let mut text = String::new();
+                BufReader::new(stream).read_to_string(&mut text)?;
+                Ok(text.contains(literal))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
@@ -280,6 +333,35 @@ impl SearchQuery {
                     None
                 }
             }
+            // The replacement depends on the syntax tree around the text.
+            SearchQuery::Structural { .. } => None,
+        }
+    }
+
+    /// Returns the replacement for a match of this `SearchQuery` in the given range of the buffer.
+    /// Unlike [`SearchQuery::replacement_for`], this supports structural queries.
+    pub fn replacement_for_range(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement,
+                ..
+            } => {
+                let replacement = replacement.as_ref()?;
+                let mat = pattern
+                    .search(buffer, range.clone())
+                    .into_iter()
+                    .find(|mat| mat.range == range)?;
+                Some(pattern.replace(replacement, buffer, &mat))
+            }
+            _ => {
+                let text = buffer.text_for_range(range).collect::<String>();
+                self.replacement_for(&text).map(Cow::into_owned)
+            }
         }
     }
 
@@ -337,6 +419,16 @@ impl SearchQuery {
                 }
             }
 
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                matches.extend(
+                    pattern
+                        .search(buffer, range)
+                        .into_iter()
+                        .map(|mat| mat.range.start - range_offset..mat.range.end - range_offset),
+                );
+            }
+
             Self::Regex {
                 regex, multiline, ..
             } => {
@@ -392,6 +484,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -399,6 +492,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -410,6 +504,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -417,6 +514,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -454,7 +555,9 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 }
@@ -10,6 +10,10 @@ use futures::{
     future::{BoxFuture, Shared},
     FutureExt, SinkExt,
 };
+use git::{
+    commit::CommitLog,
+    repository::{CommitFileDiff, CommitSummary, GitRepository, RepoPath},
+};
 use gpui::{
     AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
 };
@@ -74,6 +78,14 @@ impl WorktreeStore {
         client.add_model_request_handler(Self::handle_expand_project_entry);
         client.add_model_request_handler(Self::handle_git_branches);
         client.add_model_request_handler(Self::handle_update_branch);
+        client.add_model_request_handler(Self::handle_git_staged_paths);
+        client.add_model_request_handler(Self::handle_git_stage);
+        client.add_model_request_handler(Self::handle_git_unstage);
+        client.add_model_request_handler(Self::handle_git_commit);
+        client.add_model_request_handler(Self::handle_git_stash);
+        client.add_model_request_handler(Self::handle_git_stash_pop);
+        client.add_model_request_handler(Self::handle_git_log);
+        client.add_model_request_handler(Self::handle_git_commit_diff);
     }
 
     pub fn local(retain_worktrees: bool, fs: Arc<dyn Fs>) -> Self {
@@ -984,6 +996,307 @@ impl WorktreeStore {
         }
     }
 
+    pub fn staged_paths(
+        &self,
+        repository: ProjectPath,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<RepoPath>>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.staged_paths() }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                let response = client
+                    .request(proto::GitStagedPaths {
+                        project_id,
+                        repository: Some(repository),
+                    })
+                    .await?;
+                Ok(response
+                    .paths
+                    .into_iter()
+                    .map(|path| RepoPath::new(path.into()))
+                    .collect())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn stage_paths(
+        &self,
+        repository: ProjectPath,
+        paths: Vec<RepoPath>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.stage_paths(&paths) }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                client
+                    .request(proto::GitStage {
+                        project_id,
+                        repository: Some(repository),
+                        paths: paths
+                            .iter()
+                            .map(|path| path.to_string_lossy().to_string())
+                            .collect(),
+                    })
+                    .await?;
+                Ok(())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn unstage_paths(
+        &self,
+        repository: ProjectPath,
+        paths: Vec<RepoPath>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.unstage_paths(&paths) }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                client
+                    .request(proto::GitUnstage {
+                        project_id,
+                        repository: Some(repository),
+                        paths: paths
+                            .iter()
+                            .map(|path| path.to_string_lossy().to_string())
+                            .collect(),
+                    })
+                    .await?;
+                Ok(())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn commit(
+        &self,
+        repository: ProjectPath,
+        message: String,
+        amend: bool,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.commit(&message, amend) }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                client
+                    .request(proto::GitCommit {
+                        project_id,
+                        repository: Some(repository),
+                        message,
+                        amend,
+                    })
+                    .await?;
+                Ok(())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn stash(
+        &self,
+        repository: ProjectPath,
+        message: Option<String>,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.stash(message.as_deref()) }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                client
+                    .request(proto::GitStash {
+                        project_id,
+                        repository: Some(repository),
+                        message,
+                    })
+                    .await?;
+                Ok(())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn stash_pop(&self, repository: ProjectPath, cx: &AppContext) -> Task<Result<()>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.stash_pop() }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                client
+                    .request(proto::GitStashPop {
+                        project_id,
+                        repository: Some(repository),
+                    })
+                    .await?;
+                Ok(())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn git_log(
+        &self,
+        repository: ProjectPath,
+        path: Option<RepoPath>,
+        after: Option<String>,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<CommitLog>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx.background_executor().spawn(async move {
+                Ok(CommitLog {
+                    commits: repo.log(path.as_ref(), after.as_deref(), limit)?,
+                    remote_url: repo.remote_url("origin"),
+                })
+            }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                let response = client
+                    .request(proto::GitLog {
+                        project_id,
+                        repository: Some(repository),
+                        after,
+                        limit: limit as u64,
+                        path: path.map(|path| path.to_string_lossy().to_string()),
+                    })
+                    .await?;
+                Ok(CommitLog {
+                    commits: response
+                        .commits
+                        .into_iter()
+                        .map(|commit| CommitSummary {
+                            sha: commit.sha.into(),
+                            subject: commit.subject.into(),
+                            author_name: commit.author_name.into(),
+                            author_email: commit.author_email.into(),
+                            commit_timestamp: commit.commit_timestamp,
+                        })
+                        .collect(),
+                    remote_url: response.remote_url,
+                })
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    pub fn commit_diff(
+        &self,
+        repository: ProjectPath,
+        sha: String,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileDiff>>> {
+        match self.git_repository(&repository, cx) {
+            Ok(GitRepositoryHandle::Local(repo)) => cx
+                .background_executor()
+                .spawn(async move { repo.commit_diff(&sha) }),
+            Ok(GitRepositoryHandle::Remote {
+                client,
+                project_id,
+                repository,
+            }) => cx.background_executor().spawn(async move {
+                let response = client
+                    .request(proto::GitCommitDiff {
+                        project_id,
+                        repository: Some(repository),
+                        sha,
+                    })
+                    .await?;
+                Ok(response
+                    .files
+                    .into_iter()
+                    .map(|file| CommitFileDiff {
+                        path: RepoPath::new(file.path.into()),
+                        old_text: file.old_text,
+                        new_text: file.new_text,
+                        is_binary: file.is_binary,
+                        is_too_large: file.is_too_large,
+                        is_deleted: file.is_deleted,
+                    })
+                    .collect())
+            }),
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+
+    fn git_repository(
+        &self,
+        repository: &ProjectPath,
+        cx: &AppContext,
+    ) -> Result<GitRepositoryHandle> {
+        let worktree = self
+            .worktree_for_id(repository.worktree_id, cx)
+            .context("No worktree found for ProjectPath")?;
+
+        match worktree.read(cx) {
+            Worktree::Local(local_worktree) => {
+                let worktree_error = |error| {
+                    format!(
+                        "{} for worktree {}",
+                        error,
+                        local_worktree.abs_path().to_string_lossy()
+                    )
+                };
+
+                let entry = local_worktree
+                    .git_entry(repository.path.clone())
+                    .with_context(|| worktree_error("No git entry found"))?;
+
+                let repo = local_worktree
+                    .get_local_repo(&entry)
+                    .with_context(|| worktree_error("No repository found"))?
+                    .repo()
+                    .clone();
+
+                Ok(GitRepositoryHandle::Local(repo))
+            }
+            Worktree::Remote(remote_worktree) => Ok(GitRepositoryHandle::Remote {
+                client: remote_worktree.client(),
+                project_id: remote_worktree.project_id(),
+                repository: proto::ProjectPath {
+                    worktree_id: repository.worktree_id.to_proto(),
+                    path: repository.path.to_string_lossy().to_string(), // Root path
+                },
+            }),
+        }
+    }
+
     async fn filter_paths(
         fs: &Arc<dyn Fs>,
         mut input: Receiver<MatchingEntry>,
@@ -1160,6 +1473,180 @@ impl WorktreeStore {
 
         Ok(proto::Ack {})
     }
+
+    pub async fn handle_git_staged_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStagedPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitStagedPathsResponse> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let paths = this
+            .read_with(&cx, |this, cx| this.staged_paths(repository, cx))?
+            .await?;
+
+        Ok(proto::GitStagedPathsResponse {
+            paths: paths
+                .into_iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_stage(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStage>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let paths = envelope
+            .payload
+            .paths
+            .into_iter()
+            .map(|path| RepoPath::new(path.into()))
+            .collect();
+
+        this.read_with(&cx, |this, cx| this.stage_paths(repository, paths, cx))?
+            .await?;
+
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_unstage(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitUnstage>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let paths = envelope
+            .payload
+            .paths
+            .into_iter()
+            .map(|path| RepoPath::new(path.into()))
+            .collect();
+
+        this.read_with(&cx, |this, cx| this.unstage_paths(repository, paths, cx))?
+            .await?;
+
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_commit(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitCommit>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let message = envelope.payload.message;
+        let amend = envelope.payload.amend;
+
+        this.read_with(&cx, |this, cx| this.commit(repository, message, amend, cx))?
+            .await?;
+
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStash>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let message = envelope.payload.message;
+
+        this.read_with(&cx, |this, cx| this.stash(repository, message, cx))?
+            .await?;
+
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_stash_pop(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitStashPop>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+
+        this.read_with(&cx, |this, cx| this.stash_pop(repository, cx))?
+            .await?;
+
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_git_log(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitLog>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitLogResponse> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let path = envelope.payload.path.map(|path| RepoPath::new(path.into()));
+        let after = envelope.payload.after;
+        let limit = envelope.payload.limit as usize;
+
+        let log = this
+            .read_with(&cx, |this, cx| {
+                this.git_log(repository, path, after, limit, cx)
+            })?
+            .await?;
+
+        Ok(proto::GitLogResponse {
+            remote_url: log.remote_url,
+            commits: log
+                .commits
+                .into_iter()
+                .map(|commit| proto::GitCommitSummary {
+                    sha: commit.sha.to_string(),
+                    subject: commit.subject.to_string(),
+                    author_name: commit.author_name.to_string(),
+                    author_email: commit.author_email.to_string(),
+                    commit_timestamp: commit.commit_timestamp,
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn handle_git_commit_diff(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GitCommitDiff>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::GitCommitDiffResponse> {
+        let repository = repository_from_proto(envelope.payload.repository)?;
+        let sha = envelope.payload.sha;
+
+        let files = this
+            .read_with(&cx, |this, cx| this.commit_diff(repository, sha, cx))?
+            .await?;
+
+        Ok(proto::GitCommitDiffResponse {
+            files: files
+                .into_iter()
+                .map(|file| proto::GitCommitFileDiff {
+                    path: file.path.to_string_lossy().to_string(),
+                    old_text: file.old_text,
+                    new_text: file.new_text,
+                    is_binary: file.is_binary,
+                    is_too_large: file.is_too_large,
+                    is_deleted: file.is_deleted,
+                })
+                .collect(),
+        })
+    }
+}
+
+enum GitRepositoryHandle {
+    Local(Arc<dyn GitRepository>),
+    Remote {
+        client: AnyProtoClient,
+        project_id: u64,
+        repository: proto::ProjectPath,
+    },
+}
+
+fn repository_from_proto(repository: Option<proto::ProjectPath>) -> Result<ProjectPath> {
+    let repository = repository.context("missing repository")?;
+    Ok(ProjectPath {
+        worktree_id: WorktreeId::from_proto(repository.worktree_id),
+        path: Path::new(&repository.path).into(),
+    })
 }
 
 #[derive(Clone, Debug)]
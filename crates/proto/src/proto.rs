@@ -206,14 +206,30 @@ messages!(
     (GetProjectSymbolsResponse, Background),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
     (GetSignatureHelp, Background),
     (GetSignatureHelpResponse, Background),
+    (GetSubtypes, Background),
+    (GetSubtypesResponse, Background),
+    (GetSupertypes, Background),
+    (GetSupertypesResponse, Background),
     (GetSupermavenApiKey, Background),
     (GetSupermavenApiKeyResponse, Background),
     (GetTypeDefinition, Background),
     (GetTypeDefinitionResponse, Background),
     (GetImplementation, Background),
     (GetImplementationResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetIncomingCallsResponse, Background),
+    (GetOutgoingCalls, Background),
+    (GetOutgoingCallsResponse, Background),
     (GetLlmToken, Background),
     (GetLlmTokenResponse, Background),
     (GetStagedText, Foreground),
@@ -251,11 +267,17 @@ messages!(
     (Ping, Foreground),
     (PrepareRename, Background),
     (PrepareRenameResponse, Background),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
     (ProjectEntryResponse, Foreground),
     (CountLanguageModelTokens, Background),
     (CountLanguageModelTokensResponse, Background),
     (RefreshLlmToken, Background),
+    (RefreshCodeLens, Foreground),
     (RefreshInlayHints, Foreground),
+    (RefreshSemanticTokens, Foreground),
     (RejoinChannelBuffers, Foreground),
     (RejoinChannelBuffersResponse, Foreground),
     (RejoinRoom, Foreground),
@@ -273,6 +295,10 @@ messages!(
     (RequestContact, Foreground),
     (ResolveCompletionDocumentation, Background),
     (ResolveCompletionDocumentationResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
     (ResolveInlayHint, Background),
     (ResolveInlayHintResponse, Background),
     (RespondToChannelInvite, Foreground),
@@ -360,6 +386,17 @@ messages!(
     (GitBranches, Background),
     (GitBranchesResponse, Background),
     (UpdateGitBranch, Background),
+    (GitStagedPaths, Background),
+    (GitStagedPathsResponse, Background),
+    (GitStage, Background),
+    (GitUnstage, Background),
+    (GitCommit, Background),
+    (GitStash, Background),
+    (GitStashPop, Background),
+    (GitLog, Background),
+    (GitLogResponse, Background),
+    (GitCommitDiff, Background),
+    (GitCommitDiffResponse, Background),
     (ListToolchains, Foreground),
     (ListToolchainsResponse, Foreground),
     (ActivateToolchain, Foreground),
@@ -406,6 +443,8 @@ request_messages!(
     (GetDefinition, GetDefinitionResponse),
     (GetDeclaration, GetDeclarationResponse),
     (GetImplementation, GetImplementationResponse),
+    (GetIncomingCalls, GetIncomingCallsResponse),
+    (GetOutgoingCalls, GetOutgoingCallsResponse),
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
     (GetHover, GetHoverResponse),
     (GetLlmToken, GetLlmTokenResponse),
@@ -413,7 +452,13 @@ request_messages!(
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
     (GetReferences, GetReferencesResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
     (GetSignatureHelp, GetSignatureHelpResponse),
+    (GetSubtypes, GetSubtypesResponse),
+    (GetSupertypes, GetSupertypesResponse),
     (GetStagedText, GetStagedTextResponse),
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
@@ -440,8 +485,12 @@ request_messages!(
     (PerformRename, PerformRenameResponse),
     (Ping, Ack),
     (PrepareRename, PrepareRenameResponse),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
     (CountLanguageModelTokens, CountLanguageModelTokensResponse),
+    (RefreshCodeLens, Ack),
     (RefreshInlayHints, Ack),
+    (RefreshSemanticTokens, Ack),
     (RejoinChannelBuffers, RejoinChannelBuffersResponse),
     (RejoinRoom, RejoinRoomResponse),
     (ReloadBuffers, ReloadBuffersResponse),
@@ -456,6 +505,8 @@ request_messages!(
         ResolveCompletionDocumentation,
         ResolveCompletionDocumentationResponse
     ),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
     (ResolveInlayHint, ResolveInlayHintResponse),
     (RespondToChannelInvite, Ack),
     (RespondToContactRequest, Ack),
@@ -492,6 +543,14 @@ request_messages!(
     (LanguageServerPromptRequest, LanguageServerPromptResponse),
     (GitBranches, GitBranchesResponse),
     (UpdateGitBranch, Ack),
+    (GitStagedPaths, GitStagedPathsResponse),
+    (GitStage, Ack),
+    (GitUnstage, Ack),
+    (GitCommit, Ack),
+    (GitStash, Ack),
+    (GitStashPop, Ack),
+    (GitLog, GitLogResponse),
+    (GitCommitDiff, GitCommitDiffResponse),
     (ListToolchains, ListToolchainsResponse),
     (ActivateToolchain, Ack),
     (ActiveToolchain, ActiveToolchainResponse),
@@ -525,11 +584,19 @@ entity_messages!(
     GetDefinition,
     GetDeclaration,
     GetImplementation,
+    GetIncomingCalls,
+    GetOutgoingCalls,
     GetDocumentHighlights,
     GetHover,
     GetProjectSymbols,
     GetReferences,
+    GetCodeLens,
+    GetDocumentLinks,
+    GetFoldingRanges,
+    GetSemanticTokens,
     GetSignatureHelp,
+    GetSubtypes,
+    GetSupertypes,
     GetStagedText,
     GetTypeDefinition,
     InlayHints,
@@ -545,11 +612,17 @@ entity_messages!(
     OpenBufferForSymbol,
     PerformRename,
     PrepareRename,
+    PrepareCallHierarchy,
+    PrepareTypeHierarchy,
+    RefreshCodeLens,
     RefreshInlayHints,
+    RefreshSemanticTokens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
     ResolveCompletionDocumentation,
+    ResolveCodeLens,
+    ResolveDocumentLink,
     ResolveInlayHint,
     SaveBuffer,
     StartLanguageServer,
@@ -581,6 +654,14 @@ entity_messages!(
     LanguageServerPromptRequest,
     GitBranches,
     UpdateGitBranch,
+    GitStagedPaths,
+    GitStage,
+    GitUnstage,
+    GitCommit,
+    GitStash,
+    GitStashPop,
+    GitLog,
+    GitCommitDiff,
     ListToolchains,
     ActivateToolchain,
     ActiveToolchain,
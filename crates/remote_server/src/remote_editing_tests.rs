@@ -1226,6 +1226,217 @@ async fn test_remote_git_branches(cx: &mut TestAppContext, server_cx: &mut TestA
     assert_eq!(server_branch.as_ref(), "totally-new-branch");
 }
 
+#[gpui::test]
+async fn test_remote_git_stage_and_commit(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                ".git": {},
+                "README.md": "# project 1",
+                "main.rs": "fn main() {}",
+            },
+        }),
+    )
+    .await;
+
+    let (project, _headless_project) = init_test(&fs, cx, server_cx).await;
+
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+
+    let worktree_id = cx.update(|cx| worktree.read(cx).id());
+    let root_path = ProjectPath::root_path(worktree_id);
+    cx.run_until_parked();
+
+    project
+        .update(cx, |project, cx| {
+            project.stage_paths(
+                root_path.clone(),
+                vec![Path::new("README.md").into(), Path::new("main.rs").into()],
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.unstage_paths(root_path.clone(), vec![Path::new("main.rs").into()], cx)
+        })
+        .await
+        .unwrap();
+
+    let staged_paths = project
+        .update(cx, |project, cx| {
+            project.staged_paths(root_path.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        staged_paths
+            .iter()
+            .map(|path| path.as_path())
+            .collect::<Vec<_>>(),
+        [Path::new("README.md")]
+    );
+
+    project
+        .update(cx, |project, cx| {
+            project.commit(
+                root_path.clone(),
+                "Add a readme\n\nWith some details".to_string(),
+                false,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    let staged_paths = project
+        .update(cx, |project, cx| {
+            project.staged_paths(root_path.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(staged_paths.is_empty());
+
+    // Committing with nothing staged fails on the server and the error reaches the client.
+    assert!(project
+        .update(cx, |project, cx| {
+            project.commit(root_path.clone(), "Empty".to_string(), false, cx)
+        })
+        .await
+        .is_err());
+
+    project
+        .update(cx, |project, cx| {
+            project.commit(root_path.clone(), "Add a README".to_string(), true, cx)
+        })
+        .await
+        .unwrap();
+
+    let log = project
+        .update(cx, |project, cx| {
+            project.git_log(root_path.clone(), None, None, 10, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        log.commits
+            .iter()
+            .map(|commit| commit.subject.as_ref())
+            .collect::<Vec<_>>(),
+        ["Add a README"]
+    );
+}
+
+#[gpui::test]
+async fn test_remote_git_history(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                ".git": {},
+                "README.md": "# project 1",
+                "main.rs": "fn main() {}",
+            },
+        }),
+    )
+    .await;
+    fs.set_index_for_repo(
+        Path::new("/code/project1/.git"),
+        &[
+            (Path::new("README.md"), "# project 1".to_string()),
+            (Path::new("main.rs"), "fn main() {}".to_string()),
+        ],
+    );
+
+    let (project, _headless_project) = init_test(&fs, cx, server_cx).await;
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = cx.update(|cx| worktree.read(cx).id());
+    let root_path = ProjectPath::root_path(worktree_id);
+    cx.run_until_parked();
+
+    for (path, message) in [("README.md", "Add a README"), ("main.rs", "Add main")] {
+        project
+            .update(cx, |project, cx| {
+                project.stage_paths(root_path.clone(), vec![Path::new(path).into()], cx)
+            })
+            .await
+            .unwrap();
+        project
+            .update(cx, |project, cx| {
+                project.commit(root_path.clone(), message.to_string(), false, cx)
+            })
+            .await
+            .unwrap();
+    }
+
+    let log = project
+        .update(cx, |project, cx| {
+            project.git_log(root_path.clone(), None, None, 10, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        log.commits
+            .iter()
+            .map(|commit| commit.subject.as_ref())
+            .collect::<Vec<_>>(),
+        ["Add main", "Add a README"]
+    );
+
+    let page = project
+        .update(cx, |project, cx| {
+            project.git_log(
+                root_path.clone(),
+                None,
+                Some(log.commits[0].sha.to_string()),
+                10,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(page.commits, log.commits[1..]);
+
+    let file_log = project
+        .update(cx, |project, cx| {
+            project.git_log(
+                root_path.clone(),
+                Some(Path::new("README.md").into()),
+                None,
+                10,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(file_log.commits, log.commits[1..]);
+
+    let diff = project
+        .update(cx, |project, cx| {
+            project.commit_diff(root_path.clone(), log.commits[0].sha.to_string(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(diff.len(), 1);
+    assert_eq!(diff[0].path.as_path(), Path::new("main.rs"));
+    assert_eq!(diff[0].old_text, None);
+    assert_eq!(diff[0].new_text.as_deref(), Some("fn main() {}"));
+}
+
 pub async fn init_test(
     server_fs: &Arc<FakeFs>,
     cx: &mut TestAppContext,
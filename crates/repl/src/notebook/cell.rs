@@ -1,20 +1,31 @@
 #![allow(unused, dead_code)]
 use std::sync::Arc;
 
-use editor::{Editor, EditorMode, MultiBuffer};
+use anyhow::Result;
+use editor::{Editor, EditorEvent, EditorMode, MultiBuffer};
 use futures::future::Shared;
-use gpui::{prelude::*, AppContext, Hsla, Task, TextStyleRefinement, View};
+use gpui::{
+    prelude::*, AppContext, ClickEvent, EventEmitter, Hsla, Model, Subscription, Task,
+    TextStyleRefinement, View, WeakView,
+};
 use language::{Buffer, Language, LanguageRegistry};
 use markdown_preview::{markdown_parser::parse_markdown, markdown_renderer::render_markdown_block};
 use nbformat::v4::{CellId, CellMetadata, CellType};
+use runtimelib::{ExecutionState, JupyterMessageContent};
+use serde_json::json;
 use settings::Settings as _;
 use theme::ThemeSettings;
 use ui::{prelude::*, IconButtonShape};
 use util::ResultExt;
+use workspace::Workspace;
 
 use crate::{
     notebook::{CODE_BLOCK_INSET, GUTTER_WIDTH},
-    outputs::{plain::TerminalOutput, user_error::ErrorView, Output},
+    outputs::{
+        plain::TerminalOutput, user_error::ErrorView, ExecutionMessage, ExecutionStatus,
+        ExecutionView, Output,
+    },
+    Session,
 };
 
 #[derive(Copy, Clone, PartialEq, PartialOrd)]
@@ -80,6 +91,114 @@ pub enum Cell {
     Raw(View<RawCell>),
 }
 
+/// Events that cells emit for their notebook.
+pub enum CellEvent {
+    /// The user asked to run the cell.
+    Run,
+    /// The source or the outputs of the cell changed.
+    Changed,
+    /// The editor of the cell was focused.
+    Focused,
+}
+
+/// Splits a cell's source into lines the way nbformat stores it.
+pub(crate) fn source_lines(source: &str) -> Vec<String> {
+    source.split_inclusive('\n').map(String::from).collect()
+}
+
+/// Creates an empty cell of the given type with a new id.
+pub(crate) fn new_nbformat_cell(cell_type: CellType) -> Result<nbformat::v4::Cell> {
+    let mut cell = json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "metadata": {},
+        "source": [],
+    });
+    match cell_type {
+        CellType::Code => {
+            cell["cell_type"] = "code".into();
+            cell["execution_count"] = serde_json::Value::Null;
+            cell["outputs"] = json!([]);
+        }
+        CellType::Markdown => cell["cell_type"] = "markdown".into(),
+        CellType::Raw => cell["cell_type"] = "raw".into(),
+    }
+    Ok(serde_json::from_value(cell)?)
+}
+
+/// Converts a message of an execution into the output nbformat stores for it, if any.
+fn nbformat_output(message: &JupyterMessageContent) -> Option<nbformat::v4::Output> {
+    let output = match message {
+        JupyterMessageContent::StreamContent(stream) => json!({
+            "output_type": "stream",
+            "name": stream.name,
+            "text": stream.text,
+        }),
+        JupyterMessageContent::DisplayData(display_data) => json!({
+            "output_type": "display_data",
+            "data": display_data.data,
+            "metadata": display_data.metadata,
+        }),
+        JupyterMessageContent::ExecuteResult(result) => json!({
+            "output_type": "execute_result",
+            "execution_count": result.execution_count,
+            "data": result.data,
+            "metadata": result.metadata,
+        }),
+        JupyterMessageContent::ErrorOutput(error) => json!({
+            "output_type": "error",
+            "ename": error.ename,
+            "evalue": error.evalue,
+            "traceback": error.traceback,
+        }),
+        _ => return None,
+    };
+    serde_json::from_value(output).log_err()
+}
+
+fn cell_editor(buffer: Model<Buffer>, cx: &mut WindowContext) -> View<Editor> {
+    let multi_buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+
+    cx.new_view(|cx| {
+        let mut editor = Editor::new(
+            EditorMode::AutoHeight { max_lines: 1024 },
+            multi_buffer,
+            None,
+            false,
+            cx,
+        );
+
+        let theme = ThemeSettings::get_global(cx);
+
+        let refinement = TextStyleRefinement {
+            font_family: Some(theme.buffer_font.family.clone()),
+            font_size: Some(theme.buffer_font_size.into()),
+            color: Some(cx.theme().colors().editor_foreground),
+            background_color: Some(gpui::transparent_black()),
+            ..Default::default()
+        };
+
+        editor.set_show_gutter(false, cx);
+        editor.set_text_style_refinement(refinement);
+        editor
+    })
+}
+
+/// Keeps the source of a cell in sync with its editor.
+fn subscribe_to_editor<T: EventEmitter<CellEvent>>(
+    editor: &View<Editor>,
+    set_source: fn(&mut T, String),
+    cx: &mut ViewContext<T>,
+) -> Subscription {
+    cx.subscribe(editor, move |this, editor, event, cx| match event {
+        EditorEvent::BufferEdited => {
+            set_source(this, editor.read(cx).text(cx));
+            cx.emit(CellEvent::Changed);
+        }
+        EditorEvent::Focused => cx.emit(CellEvent::Focused),
+        _ => {}
+    })
+}
+
 fn convert_outputs(outputs: &Vec<nbformat::v4::Output>, cx: &mut WindowContext) -> Vec<Output> {
     outputs
         .into_iter()
@@ -117,37 +236,44 @@ impl Cell {
                 ..
             } => {
                 let source = source.join("");
+                // Attachments aren't rendered yet, but are kept when saving the notebook.
+                let attachments = serde_json::to_value(cell)
+                    .ok()
+                    .and_then(|cell| cell.get("attachments").cloned())
+                    .filter(|attachments| !attachments.is_null());
 
                 let view = cx.new_view(|cx| {
-                    let markdown_parsing_task = {
-                        let languages = languages.clone();
-                        let source = source.clone();
-
-                        cx.spawn(|this, mut cx| async move {
-                            let parsed_markdown = cx
-                                .background_executor()
-                                .spawn(async move {
-                                    parse_markdown(&source, None, Some(languages)).await
-                                })
-                                .await;
-
-                            this.update(&mut cx, |cell: &mut MarkdownCell, _| {
-                                cell.parsed_markdown = Some(parsed_markdown);
-                            })
-                            .log_err();
-                        })
-                    };
-
-                    MarkdownCell {
-                        markdown_parsing_task,
+                    let buffer = cx.new_model(|cx| Buffer::local(source.clone(), cx));
+                    let editor = cell_editor(buffer, cx);
+                    let subscriptions = vec![
+                        subscribe_to_editor(
+                            &editor,
+                            |cell: &mut MarkdownCell, source| cell.source = source,
+                            cx,
+                        ),
+                        cx.subscribe(&editor, |this, _, event, cx| {
+                            if let EditorEvent::Blurred = event {
+                                this.stop_editing(cx);
+                            }
+                        }),
+                    ];
+
+                    let mut cell = MarkdownCell {
+                        markdown_parsing_task: Task::ready(()),
                         languages: languages.clone(),
                         id: id.clone(),
                         metadata: metadata.clone(),
-                        source: source.clone(),
+                        attachments,
+                        editing: source.is_empty(),
+                        source,
+                        editor,
                         parsed_markdown: None,
                         selected: false,
                         cell_position: None,
-                    }
+                        _subscriptions: subscriptions,
+                    };
+                    cell.parse_markdown(cx);
+                    cell
                 });
 
                 Cell::Markdown(view)
@@ -161,35 +287,13 @@ impl Cell {
             } => Cell::Code(cx.new_view(|cx| {
                 let text = source.join("");
 
-                let buffer = cx.new_model(|cx| Buffer::local(text.clone(), cx));
-                let multi_buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer.clone(), cx));
-
-                let editor_view = cx.new_view(|cx| {
-                    let mut editor = Editor::new(
-                        EditorMode::AutoHeight { max_lines: 1024 },
-                        multi_buffer,
-                        None,
-                        false,
-                        cx,
-                    );
-
-                    let theme = ThemeSettings::get_global(cx);
-
-                    let refinement = TextStyleRefinement {
-                        font_family: Some(theme.buffer_font.family.clone()),
-                        font_size: Some(theme.buffer_font_size.into()),
-                        color: Some(cx.theme().colors().editor_foreground),
-                        background_color: Some(gpui::transparent_black()),
-                        ..Default::default()
-                    };
-
-                    editor.set_text(text, cx);
-                    editor.set_show_gutter(false, cx);
-                    editor.set_text_style_refinement(refinement);
-
-                    // editor.set_read_only(true);
-                    editor
-                });
+                let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+                let editor_view = cell_editor(buffer.clone(), cx);
+                let editor_subscription = subscribe_to_editor(
+                    &editor_view,
+                    |cell: &mut CodeCell, source| cell.source = source,
+                    cx,
+                );
 
                 let buffer = buffer.clone();
                 let language_task = cx.spawn(|this, mut cx| async move {
@@ -207,24 +311,96 @@ impl Cell {
                     source: source.join(""),
                     editor: editor_view,
                     outputs: convert_outputs(outputs, cx),
+                    nbformat_outputs: outputs.clone(),
+                    clear_outputs_on_next_output: false,
+                    execution: None,
                     selected: false,
                     language_task,
                     cell_position: None,
+                    _editor_subscription: editor_subscription,
+                    _execution_subscription: None,
                 }
             })),
             nbformat::v4::Cell::Raw {
                 id,
                 metadata,
                 source,
-            } => Cell::Raw(cx.new_view(|_| RawCell {
-                id: id.clone(),
-                metadata: metadata.clone(),
-                source: source.join(""),
-                selected: false,
-                cell_position: None,
+            } => Cell::Raw(cx.new_view(|cx| {
+                let source = source.join("");
+                let buffer = cx.new_model(|cx| Buffer::local(source.clone(), cx));
+                let editor = cell_editor(buffer, cx);
+                let editor_subscription = subscribe_to_editor(
+                    &editor,
+                    |cell: &mut RawCell, source| cell.source = source,
+                    cx,
+                );
+
+                RawCell {
+                    id: id.clone(),
+                    metadata: metadata.clone(),
+                    source,
+                    editor,
+                    selected: false,
+                    cell_position: None,
+                    _editor_subscription: editor_subscription,
+                }
             })),
         }
     }
+
+    pub fn id(&self, cx: &AppContext) -> CellId {
+        match self {
+            Cell::Code(cell) => cell.read(cx).id().clone(),
+            Cell::Markdown(cell) => cell.read(cx).id().clone(),
+            Cell::Raw(cell) => cell.read(cx).id().clone(),
+        }
+    }
+
+    /// The editor of the cell, for moving the focus into the cell.
+    pub fn editor(&self, cx: &AppContext) -> View<Editor> {
+        match self {
+            Cell::Code(cell) => cell.read(cx).editor.clone(),
+            Cell::Markdown(cell) => cell.read(cx).editor.clone(),
+            Cell::Raw(cell) => cell.read(cx).editor.clone(),
+        }
+    }
+
+    /// Converts the cell back to nbformat, for saving the notebook.
+    pub fn to_nbformat(&self, cx: &AppContext) -> Result<nbformat::v4::Cell> {
+        match self {
+            Cell::Code(cell) => {
+                let cell = cell.read(cx);
+                Ok(nbformat::v4::Cell::Code {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    execution_count: cell.execution_count,
+                    source: source_lines(&cell.source),
+                    outputs: cell.nbformat_outputs.clone(),
+                })
+            }
+            Cell::Markdown(cell) => {
+                let cell = cell.read(cx);
+                let mut markdown = json!({
+                    "cell_type": "markdown",
+                    "id": cell.id,
+                    "metadata": cell.metadata,
+                    "source": source_lines(&cell.source),
+                });
+                if let Some(attachments) = cell.attachments.clone() {
+                    markdown["attachments"] = attachments;
+                }
+                Ok(serde_json::from_value(markdown)?)
+            }
+            Cell::Raw(cell) => {
+                let cell = cell.read(cx);
+                Ok(nbformat::v4::Cell::Raw {
+                    id: cell.id.clone(),
+                    metadata: cell.metadata.clone(),
+                    source: source_lines(&cell.source),
+                })
+            }
+        }
+    }
 }
 
 pub trait RenderableCell: Render {
@@ -320,12 +496,59 @@ pub trait RunnableCell: RenderableCell {
 pub struct MarkdownCell {
     id: CellId,
     metadata: CellMetadata,
+    attachments: Option<serde_json::Value>,
     source: String,
+    editor: View<Editor>,
+    /// Whether the source is shown in the editor instead of being rendered.
+    editing: bool,
     parsed_markdown: Option<markdown_preview::markdown_elements::ParsedMarkdown>,
     markdown_parsing_task: Task<()>,
     selected: bool,
     cell_position: Option<CellPosition>,
     languages: Arc<LanguageRegistry>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<CellEvent> for MarkdownCell {}
+
+impl MarkdownCell {
+    fn parse_markdown(&mut self, cx: &mut ViewContext<Self>) {
+        let languages = self.languages.clone();
+        let source = self.source.clone();
+
+        self.markdown_parsing_task = cx.spawn(|this, mut cx| async move {
+            let parsed_markdown = cx
+                .background_executor()
+                .spawn(async move { parse_markdown(&source, None, Some(languages)).await })
+                .await;
+
+            this.update(&mut cx, |cell: &mut MarkdownCell, cx| {
+                cell.parsed_markdown = Some(parsed_markdown);
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn start_editing(&mut self, cx: &mut ViewContext<Self>) {
+        self.editing = true;
+        cx.focus_view(&self.editor);
+        cx.notify();
+    }
+
+    /// Renders the markdown again, unless the cell is empty.
+    pub fn stop_editing(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.editing || self.source.trim().is_empty() {
+            return;
+        }
+        self.editing = false;
+        self.parse_markdown(cx);
+        cx.notify();
+    }
 }
 
 impl RenderableCell for MarkdownCell {
@@ -372,6 +595,38 @@ impl RenderableCell for MarkdownCell {
 
 impl Render for MarkdownCell {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if self.editing {
+            return v_flex()
+                .size_full()
+                .children(self.cell_position_spacer(true, cx))
+                .child(
+                    h_flex()
+                        .w_full()
+                        .pr_6()
+                        .rounded_sm()
+                        .items_start()
+                        .gap(DynamicSpacing::Base08.rems(cx))
+                        .bg(self.selected_bg_color(cx))
+                        .child(self.gutter(cx))
+                        .child(
+                            div().py_1p5().w_full().child(
+                                div()
+                                    .flex()
+                                    .size_full()
+                                    .flex_1()
+                                    .py_3()
+                                    .px_5()
+                                    .rounded_lg()
+                                    .border_1()
+                                    .border_color(cx.theme().colors().border)
+                                    .bg(cx.theme().colors().editor_background)
+                                    .child(div().w_full().child(self.editor.clone())),
+                            ),
+                        ),
+                )
+                .children(self.cell_position_spacer(false, cx));
+        }
+
         let Some(parsed) = self.parsed_markdown.as_ref() else {
             return div();
         };
@@ -394,12 +649,17 @@ impl Render for MarkdownCell {
                     .child(self.gutter(cx))
                     .child(
                         v_flex()
+                            .id("markdown-cell")
                             .size_full()
                             .flex_1()
                             .p_3()
                             .font_ui(cx)
                             .text_size(TextSize::Default.rems(cx))
-                            //
+                            .on_click(cx.listener(|this, event: &ClickEvent, cx| {
+                                if event.up.click_count == 2 {
+                                    this.start_editing(cx);
+                                }
+                            }))
                             .children(parsed.children.iter().map(|child| {
                                 div().relative().child(div().relative().child(
                                     render_markdown_block(child, &mut markdown_render_context),
@@ -418,22 +678,111 @@ pub struct CodeCell {
     execution_count: Option<i32>,
     source: String,
     editor: View<editor::Editor>,
+    /// The outputs the notebook was loaded with.
     outputs: Vec<Output>,
+    /// The outputs as they're saved to the notebook.
+    nbformat_outputs: Vec<nbformat::v4::Output>,
+    clear_outputs_on_next_output: bool,
+    /// The latest execution of the cell, which replaces the loaded outputs.
+    execution: Option<View<ExecutionView>>,
     selected: bool,
     cell_position: Option<CellPosition>,
     language_task: Task<()>,
+    _editor_subscription: Subscription,
+    _execution_subscription: Option<Subscription>,
 }
 
+impl EventEmitter<CellEvent> for CodeCell {}
+
 impl CodeCell {
-    pub fn is_dirty(&self, cx: &AppContext) -> bool {
-        self.editor.read(cx).buffer().read(cx).is_dirty(cx)
-    }
     pub fn has_outputs(&self) -> bool {
-        !self.outputs.is_empty()
+        !self.outputs.is_empty() || self.execution.is_some()
     }
 
     pub fn clear_outputs(&mut self) {
         self.outputs.clear();
+        self.nbformat_outputs.clear();
+        self.execution = None;
+        self._execution_subscription = None;
+        self.execution_count = None;
+    }
+
+    /// Runs the source of the cell in the session, replacing the outputs of the cell.
+    pub fn execute(
+        &mut self,
+        session: &View<Session>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.source.trim().is_empty() {
+            return;
+        }
+
+        let execution_view =
+            cx.new_view(|cx| ExecutionView::new(ExecutionStatus::Unknown, workspace, cx));
+        self._execution_subscription =
+            Some(cx.subscribe(&execution_view, Self::on_execution_message));
+        self.clear_outputs();
+        self.execution = Some(execution_view.clone());
+
+        let code = self.source.clone();
+        session.update(cx, |session, cx| {
+            session.execute_in_view(code, execution_view, cx);
+        });
+        cx.emit(CellEvent::Changed);
+        cx.notify();
+    }
+
+    fn on_execution_message(
+        &mut self,
+        _: View<ExecutionView>,
+        event: &ExecutionMessage,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match &event.0 {
+            JupyterMessageContent::ExecuteInput(input) => {
+                self.execution_count = serde_json::to_value(&input.execution_count)
+                    .ok()
+                    .and_then(|count| count.as_i64())
+                    .and_then(|count| i32::try_from(count).ok());
+            }
+            JupyterMessageContent::ClearOutput(options) => {
+                if options.wait {
+                    self.clear_outputs_on_next_output = true;
+                } else {
+                    self.nbformat_outputs.clear();
+                }
+            }
+            JupyterMessageContent::Status(status) => {
+                if let ExecutionState::Idle = status.execution_state {
+                    cx.emit(CellEvent::Changed);
+                }
+            }
+            message => {
+                let Some(output) = nbformat_output(message) else {
+                    return;
+                };
+                if self.clear_outputs_on_next_output {
+                    self.clear_outputs_on_next_output = false;
+                    self.nbformat_outputs.clear();
+                }
+                // Consecutive stream outputs are stored as one, like Jupyter does.
+                if let (
+                    Some(nbformat::v4::Output::Stream {
+                        name: last_name,
+                        text: last_text,
+                    }),
+                    nbformat::v4::Output::Stream { name, text },
+                ) = (self.nbformat_outputs.last_mut(), &output)
+                {
+                    if last_name == name {
+                        last_text.0.push_str(&text.0);
+                        return;
+                    }
+                }
+                self.nbformat_outputs.push(output);
+            }
+        }
     }
 
     fn output_control(&self) -> Option<CellControlType> {
@@ -510,8 +859,8 @@ impl RenderableCell for CodeCell {
             CellControl::new("rerun-cell", CellControlType::RerunCell)
         } else {
             CellControl::new("run-cell", CellControlType::RunCell)
-                .on_click(cx.listener(move |this, _, cx| this.run(cx)))
         };
+        let cell_control = cell_control.on_click(cx.listener(move |this, _, cx| this.run(cx)));
 
         Some(cell_control)
     }
@@ -536,8 +885,9 @@ impl RenderableCell for CodeCell {
 }
 
 impl RunnableCell for CodeCell {
+    /// Asks the notebook to run the cell, since the notebook owns the session.
     fn run(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Running code cell: {}", self.id);
+        cx.emit(CellEvent::Run);
     }
 
     fn execution_count(&self) -> Option<i32> {
@@ -605,6 +955,9 @@ impl Render for CodeCell {
                                 .border_1()
                                 // .border_color(cx.theme().colors().border)
                                 // .bg(cx.theme().colors().editor_background)
+                                .when_some(self.execution.clone(), |this, execution| {
+                                    this.child(div().w_full().child(execution))
+                                })
                                 .child(div().w_full().children(self.outputs.iter().map(
                                     |output| {
                                         let content = match output {
@@ -656,10 +1009,14 @@ pub struct RawCell {
     id: CellId,
     metadata: CellMetadata,
     source: String,
+    editor: View<Editor>,
     selected: bool,
     cell_position: Option<CellPosition>,
+    _editor_subscription: Subscription,
 }
 
+impl EventEmitter<CellEvent> for RawCell {}
+
 impl RenderableCell for RawCell {
     const CELL_TYPE: CellType = CellType::Raw;
 
@@ -721,10 +1078,59 @@ impl Render for RawCell {
                             .p_3()
                             .font_ui(cx)
                             .text_size(TextSize::Default.rems(cx))
-                            .child(self.source.clone()),
+                            .child(self.editor.clone()),
                     ),
             )
             // TODO: Move base cell render into trait impl so we don't have to repeat this
             .children(self.cell_position_spacer(false, cx))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_lines() {
+        assert_eq!(source_lines(""), Vec::<String>::new());
+        assert_eq!(source_lines("x = 1"), vec!["x = 1"]);
+        assert_eq!(
+            source_lines("x = 1\nprint(x)\n"),
+            vec!["x = 1\n", "print(x)\n"]
+        );
+        assert_eq!(source_lines("\n\nx"), vec!["\n", "\n", "x"]);
+    }
+
+    #[test]
+    fn test_new_nbformat_cell() {
+        let nbformat::v4::Cell::Code {
+            id,
+            execution_count,
+            source,
+            outputs,
+            ..
+        } = new_nbformat_cell(CellType::Code).unwrap()
+        else {
+            panic!("expected a code cell");
+        };
+        assert_eq!(execution_count, None);
+        assert!(source.is_empty());
+        assert!(outputs.is_empty());
+
+        let nbformat::v4::Cell::Code { id: other_id, .. } =
+            new_nbformat_cell(CellType::Code).unwrap()
+        else {
+            panic!("expected a code cell");
+        };
+        assert_ne!(id, other_id);
+
+        assert!(matches!(
+            new_nbformat_cell(CellType::Markdown).unwrap(),
+            nbformat::v4::Cell::Markdown { .. }
+        ));
+        assert!(matches!(
+            new_nbformat_cell(CellType::Raw).unwrap(),
+            nbformat::v4::Cell::Raw { .. }
+        ));
+    }
+}
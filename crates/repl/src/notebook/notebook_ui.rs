@@ -1,47 +1,49 @@
-#![allow(unused, dead_code)]
 use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
 use std::{path::PathBuf, sync::Arc};
 
-use anyhow::{Context as _, Result};
-use client::proto::ViewId;
+use anyhow::{anyhow, Context as _, Result};
 use collections::HashMap;
 use feature_flags::{FeatureFlagAppExt as _, NotebookFeatureFlag};
 use futures::future::Shared;
-use futures::FutureExt;
+use futures::{FutureExt, StreamExt as _};
 use gpui::{
     actions, list, prelude::*, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView,
-    ListScrollEvent, ListState, Model, Point, Task, View,
+    ListState, Model, ModelContext, Point, Subscription, Task, View, WeakView,
 };
 use language::{Language, LanguageRegistry};
 use project::{Project, ProjectEntryId, ProjectPath};
+use serde::Serialize as _;
 use ui::{prelude::*, Tooltip};
+use util::ResultExt as _;
 use workspace::item::{ItemEvent, TabContentParams};
 use workspace::searchable::SearchableItemHandle;
-use workspace::{Item, ItemHandle, ProjectItem, ToolbarItemLocation};
-use workspace::{ToolbarItemEvent, ToolbarItemView};
+use workspace::{Item, ProjectItem, Workspace};
 
-use super::{Cell, CellPosition, RenderableCell};
+use super::{new_nbformat_cell, Cell, CellEvent, CellPosition, RenderableCell};
+use crate::repl_store::ReplStore;
+use crate::session::SessionEvent;
+use crate::{KernelSpecification, Session};
 
 use nbformat::v4::CellId;
-use nbformat::v4::Metadata as NotebookMetadata;
+use nbformat::v4::CellType;
 
 actions!(
     notebook,
     [
-        OpenNotebook,
+        RunCell,
         RunAll,
         ClearOutputs,
         MoveCellUp,
         MoveCellDown,
         AddMarkdownBlock,
         AddCodeBlock,
+        DeleteCell,
     ]
 );
 
-pub(crate) const MAX_TEXT_BLOCK_WIDTH: f32 = 9999.0;
-pub(crate) const SMALL_SPACING_SIZE: f32 = 8.0;
 pub(crate) const MEDIUM_SPACING_SIZE: f32 = 12.0;
-pub(crate) const LARGE_SPACING_SIZE: f32 = 16.0;
 pub(crate) const GUTTER_WIDTH: f32 = 19.0;
 pub(crate) const CODE_BLOCK_INSET: f32 = MEDIUM_SPACING_SIZE;
 pub(crate) const CONTROL_SIZE: f32 = 20.0;
@@ -67,16 +69,32 @@ pub fn init(cx: &mut AppContext) {
 pub struct NotebookEditor {
     languages: Arc<LanguageRegistry>,
     project: Model<Project>,
+    workspace: Option<WeakView<Workspace>>,
 
     focus_handle: FocusHandle,
     notebook_item: Model<NotebookItem>,
+    notebook_language: Shared<Task<Option<Arc<Language>>>>,
+    language: Option<Arc<Language>>,
 
-    remote_id: Option<ViewId>,
     cell_list: ListState,
 
     selected_cell_index: usize,
     cell_order: Vec<CellId>,
     cell_map: HashMap<CellId, Cell>,
+    cell_subscriptions: HashMap<CellId, Subscription>,
+
+    /// The session the code cells run in, started when a cell runs for the first time.
+    session: Option<View<Session>>,
+    /// Whether the cells changed since the notebook was loaded or saved.
+    dirty: bool,
+    /// Whether the notebook changed, on disk or in another pane, while the cells were dirty.
+    conflict: bool,
+    /// The version of the notebook item the cells were loaded from or saved to.
+    loaded_version: usize,
+    _language_task: Task<()>,
+    _session_subscription: Option<Subscription>,
+    _watch_task: Task<()>,
+    _notebook_item_subscription: Subscription,
 }
 
 impl NotebookEditor {
@@ -88,63 +106,308 @@ impl NotebookEditor {
         let focus_handle = cx.focus_handle();
 
         let languages = project.read(cx).languages().clone();
-        let language_name = notebook_item.read(cx).language_name();
 
         let notebook_language = notebook_item.read(cx).notebook_language();
         let notebook_language = cx.spawn(|_, _| notebook_language).shared();
-
-        let mut cell_order = vec![]; // Vec<CellId>
-        let mut cell_map = HashMap::default(); // HashMap<CellId, Cell>
-
-        for (index, cell) in notebook_item
-            .read(cx)
-            .notebook
-            .clone()
-            .cells
-            .iter()
-            .enumerate()
-        {
-            let cell_id = cell.id();
-            cell_order.push(cell_id.clone());
-            cell_map.insert(
-                cell_id.clone(),
-                Cell::load(cell, &languages, notebook_language.clone(), cx),
-            );
-        }
+        let language_task = cx.spawn({
+            let notebook_language = notebook_language.clone();
+            |this, mut cx| async move {
+                let language = notebook_language.await;
+                this.update(&mut cx, |this, _| this.language = language)
+                    .ok();
+            }
+        });
 
         let view = cx.view().downgrade();
-        let cell_count = cell_order.len();
-
-        let this = cx.view();
-        let cell_list = ListState::new(
-            cell_count,
-            gpui::ListAlignment::Top,
-            px(1000.),
-            move |ix, cx| {
-                view.upgrade()
-                    .and_then(|notebook_handle| {
-                        notebook_handle.update(cx, |notebook, cx| {
-                            notebook
-                                .cell_order
-                                .get(ix)
-                                .and_then(|cell_id| notebook.cell_map.get(cell_id))
-                                .map(|cell| notebook.render_cell(ix, cell, cx).into_any_element())
-                        })
+
+        let cell_list = ListState::new(0, gpui::ListAlignment::Top, px(1000.), move |ix, cx| {
+            view.upgrade()
+                .and_then(|notebook_handle| {
+                    notebook_handle.update(cx, |notebook, cx| {
+                        notebook
+                            .cell_order
+                            .get(ix)
+                            .and_then(|cell_id| notebook.cell_map.get(cell_id))
+                            .map(|cell| notebook.render_cell(ix, cell, cx).into_any_element())
                     })
-                    .unwrap_or_else(|| div().into_any())
-            },
-        );
+                })
+                .unwrap_or_else(|| div().into_any())
+        });
+
+        // Other panes showing the same notebook save and reload it through the shared item.
+        let notebook_item_subscription = cx.observe(&notebook_item, |this, notebook_item, cx| {
+            if notebook_item.read(cx).version != this.loaded_version {
+                this.notebook_changed(cx);
+            }
+        });
 
-        Self {
+        let mut this = Self {
             project,
             languages: languages.clone(),
+            workspace: None,
             focus_handle,
             notebook_item,
-            remote_id: None,
+            notebook_language,
+            language: None,
             cell_list,
             selected_cell_index: 0,
-            cell_order: cell_order.clone(),
-            cell_map: cell_map.clone(),
+            cell_order: Vec::new(),
+            cell_map: HashMap::default(),
+            cell_subscriptions: HashMap::default(),
+            session: None,
+            dirty: false,
+            conflict: false,
+            loaded_version: 0,
+            _language_task: language_task,
+            _session_subscription: None,
+            _watch_task: Task::ready(()),
+            _notebook_item_subscription: notebook_item_subscription,
+        };
+        this.load_cells(cx);
+        this.watch_notebook_file(cx);
+        this
+    }
+
+    /// Updates the notebook item when the notebook file changes on disk, which reloads the
+    /// cells of every pane showing it.
+    fn watch_notebook_file(&mut self, cx: &mut ViewContext<Self>) {
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        let fs = self.project.read(cx).fs().clone();
+        self._watch_task = cx.spawn(|this, mut cx| async move {
+            let (mut events, _watcher) = fs.watch(&abs_path, Duration::from_millis(100)).await;
+            while events.next().await.is_some() {
+                let Some(file_content) = fs.load(&abs_path).await.log_err() else {
+                    continue;
+                };
+                let Some(notebook) = parse_notebook(&file_content).log_err() else {
+                    continue;
+                };
+                let updated = this.update(&mut cx, |this, cx| {
+                    // Our own saves leave the file matching the notebook item.
+                    let saved_content = serialize_notebook(&this.notebook_item.read(cx).notebook);
+                    if saved_content.ok().as_ref() == Some(&file_content) {
+                        return;
+                    }
+                    this.notebook_item.update(cx, |notebook_item, cx| {
+                        notebook_item.set_notebook(notebook, cx)
+                    });
+                });
+                if updated.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Reloads the cells after the notebook item changed, or, if they have unsaved changes,
+    /// marks them as conflicting with it.
+    fn notebook_changed(&mut self, cx: &mut ViewContext<Self>) {
+        if self.dirty {
+            self.conflict = true;
+        } else {
+            self.load_cells(cx);
+        }
+        cx.emit(());
+    }
+
+    /// Replaces the cells with the ones of the notebook item.
+    fn load_cells(&mut self, cx: &mut ViewContext<Self>) {
+        self.loaded_version = self.notebook_item.read(cx).version;
+        self.conflict = false;
+        self.cell_order.clear();
+        self.cell_map.clear();
+        self.cell_subscriptions.clear();
+
+        let cells = self.notebook_item.read(cx).notebook.cells.clone();
+        for cell in &cells {
+            let cell_id = cell.id().clone();
+            let cell = Cell::load(cell, &self.languages, self.notebook_language.clone(), cx);
+            self.cell_subscriptions.insert(
+                cell_id.clone(),
+                Self::subscribe_to_cell(cell_id.clone(), &cell, cx),
+            );
+            self.cell_order.push(cell_id.clone());
+            self.cell_map.insert(cell_id, cell);
+        }
+
+        self.cell_list.reset(self.cell_order.len());
+        self.selected_cell_index = self
+            .selected_cell_index
+            .min(self.cell_order.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    fn subscribe_to_cell(cell_id: CellId, cell: &Cell, cx: &mut ViewContext<Self>) -> Subscription {
+        match cell {
+            Cell::Code(cell) => cx.subscribe(cell, move |this, _, event, cx| {
+                this.on_cell_event(&cell_id, event, cx)
+            }),
+            Cell::Markdown(cell) => cx.subscribe(cell, move |this, _, event, cx| {
+                this.on_cell_event(&cell_id, event, cx)
+            }),
+            Cell::Raw(cell) => cx.subscribe(cell, move |this, _, event, cx| {
+                this.on_cell_event(&cell_id, event, cx)
+            }),
+        }
+    }
+
+    fn on_cell_event(&mut self, cell_id: &CellId, event: &CellEvent, cx: &mut ViewContext<Self>) {
+        let Some(index) = self.cell_order.iter().position(|id| id == cell_id) else {
+            return;
+        };
+        match event {
+            CellEvent::Run => self.run_cell(index, cx),
+            CellEvent::Changed => self.mark_dirty(cx),
+            CellEvent::Focused => {
+                if index != self.selected_cell_index {
+                    self.set_selected_index(index, false, cx);
+                    cx.notify();
+                }
+            }
+        }
+    }
+
+    fn mark_dirty(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.dirty {
+            self.dirty = true;
+            cx.emit(());
+        }
+        cx.notify();
+    }
+
+    fn kernel_specification(&self, cx: &AppContext) -> Option<KernelSpecification> {
+        let store = ReplStore::global(cx);
+        let store = store.read(cx);
+        let notebook_item = self.notebook_item.read(cx);
+        let worktree_id = notebook_item.project_path.worktree_id;
+
+        // Prefer the kernel the notebook was last run with.
+        let kernel_name = notebook_item
+            .notebook
+            .metadata
+            .kernelspec
+            .as_ref()
+            .map(|kernelspec| kernelspec.name.as_str());
+        if let Some(kernel_name) = kernel_name {
+            let kernel_specification = store
+                .kernel_specifications_for_worktree(worktree_id)
+                .find(|specification| specification.name().as_ref() == kernel_name);
+            if let Some(kernel_specification) = kernel_specification {
+                return Some(kernel_specification.clone());
+            }
+        }
+
+        store.active_kernelspec(worktree_id, self.language.clone(), cx)
+    }
+
+    fn session(&mut self, cx: &mut ViewContext<Self>) -> Result<View<Session>> {
+        if let Some(session) = self.session.clone() {
+            return Ok(session);
+        }
+
+        let store = ReplStore::global(cx);
+        if !store.read(cx).is_enabled() {
+            return Err(anyhow!("the REPL is disabled"));
+        }
+        let kernel_specification = self
+            .kernel_specification(cx)
+            .context("no kernel found for the notebook")?;
+        let fs = store.read(cx).fs().clone();
+        let telemetry = store.read(cx).telemetry().clone();
+        let owner_id = cx.view().entity_id();
+        // Run the notebook next to it, like Jupyter does.
+        let working_directory = self
+            .notebook_item
+            .read(cx)
+            .path
+            .parent()
+            .map(Path::to_path_buf);
+
+        let session = cx.new_view(|cx| {
+            Session::detached(
+                owner_id,
+                working_directory,
+                fs,
+                telemetry,
+                kernel_specification,
+                cx,
+            )
+        });
+        self._session_subscription =
+            Some(cx.subscribe(&session, |this, _, event, cx| match event {
+                SessionEvent::Shutdown(_) => {
+                    this.session = None;
+                    cx.notify();
+                }
+            }));
+        self.session = Some(session.clone());
+        Ok(session)
+    }
+
+    fn run_cell(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(Cell::Code(cell)) = self
+            .cell_order
+            .get(index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(workspace) = self.workspace.clone() else {
+            return;
+        };
+
+        match self.session(cx) {
+            Ok(session) => {
+                cell.update(cx, |cell, cx| cell.execute(&session, workspace, cx));
+            }
+            Err(error) => {
+                workspace
+                    .update(cx, |workspace, cx| workspace.show_error(&error, cx))
+                    .ok();
+            }
+        }
+    }
+
+    /// Runs the selected cell, or renders it if it's a markdown cell, and selects the next one.
+    fn run_selected_cell(&mut self, cx: &mut ViewContext<Self>) {
+        let index = self.selected_cell_index;
+        match self
+            .cell_order
+            .get(index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+            .cloned()
+        {
+            Some(Cell::Code(_)) => self.run_cell(index, cx),
+            Some(Cell::Markdown(cell)) => cell.update(cx, |cell, cx| cell.stop_editing(cx)),
+            Some(Cell::Raw(_)) | None => {}
+        }
+
+        if index + 1 < self.cell_count() {
+            self.set_selected_index(index + 1, true, cx);
+            self.focus_cell(index + 1, cx);
+        } else {
+            cx.focus(&self.focus_handle);
+        }
+        cx.notify();
+    }
+
+    fn focus_cell(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(cell) = self
+            .cell_order
+            .get(index)
+            .and_then(|cell_id| self.cell_map.get(cell_id))
+        else {
+            return;
+        };
+        match cell {
+            Cell::Markdown(markdown) if !markdown.read(cx).is_editing() => {
+                cx.focus(&self.focus_handle);
+            }
+            cell => {
+                let editor = cell.editor(cx);
+                cx.focus_view(&editor);
+            }
         }
     }
 
@@ -159,37 +422,140 @@ impl NotebookEditor {
     }
 
     fn clear_outputs(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.has_outputs(cx) {
+            return;
+        }
         for cell in self.cell_map.values() {
             if let Cell::Code(code_cell) = cell {
-                code_cell.update(cx, |cell, _cx| {
+                code_cell.update(cx, |cell, cx| {
                     cell.clear_outputs();
+                    cx.notify();
                 });
             }
         }
+        self.mark_dirty(cx);
     }
 
     fn run_cells(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Cells would all run here, if that was implemented!");
-    }
-
-    fn open_notebook(&mut self, _: &OpenNotebook, _cx: &mut ViewContext<Self>) {
-        println!("Open notebook triggered");
+        for index in 0..self.cell_order.len() {
+            self.run_cell(index, cx);
+        }
     }
 
     fn move_cell_up(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Move cell up triggered");
+        let index = self.selected_cell_index;
+        if index == 0 || index >= self.cell_order.len() {
+            return;
+        }
+        self.cell_order.swap(index - 1, index);
+        self.cell_list.splice(index - 1..index + 1, 2);
+        self.set_selected_index(index - 1, true, cx);
+        self.mark_dirty(cx);
     }
 
     fn move_cell_down(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Move cell down triggered");
+        let index = self.selected_cell_index;
+        if index + 1 >= self.cell_order.len() {
+            return;
+        }
+        self.cell_order.swap(index, index + 1);
+        self.cell_list.splice(index..index + 2, 2);
+        self.set_selected_index(index + 1, true, cx);
+        self.mark_dirty(cx);
     }
 
     fn add_markdown_block(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Add markdown block triggered");
+        self.insert_cell(CellType::Markdown, cx);
     }
 
     fn add_code_block(&mut self, cx: &mut ViewContext<Self>) {
-        println!("Add code block triggered");
+        self.insert_cell(CellType::Code, cx);
+    }
+
+    /// Inserts an empty cell below the selected one and moves the focus into it.
+    fn insert_cell(&mut self, cell_type: CellType, cx: &mut ViewContext<Self>) {
+        let Some(nbformat_cell) = new_nbformat_cell(cell_type).log_err() else {
+            return;
+        };
+        let index = if self.cell_order.is_empty() {
+            0
+        } else {
+            self.selected_cell_index + 1
+        };
+
+        let cell_id = nbformat_cell.id().clone();
+        let cell = Cell::load(
+            &nbformat_cell,
+            &self.languages,
+            self.notebook_language.clone(),
+            cx,
+        );
+        self.cell_subscriptions.insert(
+            cell_id.clone(),
+            Self::subscribe_to_cell(cell_id.clone(), &cell, cx),
+        );
+        self.cell_order.insert(index, cell_id.clone());
+        self.cell_map.insert(cell_id, cell);
+        self.cell_list.splice(index..index, 1);
+
+        self.set_selected_index(index, true, cx);
+        self.focus_cell(index, cx);
+        self.mark_dirty(cx);
+    }
+
+    fn delete_cell(&mut self, cx: &mut ViewContext<Self>) {
+        let index = self.selected_cell_index;
+        if index >= self.cell_order.len() {
+            return;
+        }
+        let cell_id = self.cell_order.remove(index);
+        self.cell_map.remove(&cell_id);
+        self.cell_subscriptions.remove(&cell_id);
+        self.cell_list.splice(index..index + 1, 0);
+
+        let index = index.min(self.cell_order.len().saturating_sub(1));
+        self.set_selected_index(index, true, cx);
+        cx.focus(&self.focus_handle);
+        self.mark_dirty(cx);
+    }
+
+    /// Returns the notebook with the current cells, for saving it.
+    fn to_nbformat(&self, cx: &AppContext) -> Result<nbformat::v4::Notebook> {
+        let mut notebook = self.notebook_item.read(cx).notebook.clone();
+        notebook.cells = self
+            .cell_order
+            .iter()
+            .filter_map(|cell_id| self.cell_map.get(cell_id))
+            .map(|cell| cell.to_nbformat(cx))
+            .collect::<Result<_>>()?;
+        Ok(notebook)
+    }
+
+    fn write_notebook(
+        &mut self,
+        abs_path: PathBuf,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        let notebook = match self.to_nbformat(cx) {
+            Ok(notebook) => notebook,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let fs = self.project.read(cx).fs().clone();
+
+        cx.spawn(|this, mut cx| async move {
+            fs.atomic_write(abs_path, serialize_notebook(&notebook)?)
+                .await?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, cx| {
+                    notebook_item.set_notebook(notebook, cx)
+                });
+                this.loaded_version = this.notebook_item.read(cx).version;
+                this.dirty = false;
+                this.conflict = false;
+                cx.emit(());
+                cx.notify();
+            })
+        })
     }
 
     fn cell_count(&self) -> usize {
@@ -443,13 +809,14 @@ impl Render for NotebookEditor {
         div()
             .key_context("notebook")
             .track_focus(&self.focus_handle)
-            .on_action(cx.listener(|this, &OpenNotebook, cx| this.open_notebook(&OpenNotebook, cx)))
             .on_action(cx.listener(|this, &ClearOutputs, cx| this.clear_outputs(cx)))
+            .on_action(cx.listener(|this, &RunCell, cx| this.run_selected_cell(cx)))
             .on_action(cx.listener(|this, &RunAll, cx| this.run_cells(cx)))
             .on_action(cx.listener(|this, &MoveCellUp, cx| this.move_cell_up(cx)))
             .on_action(cx.listener(|this, &MoveCellDown, cx| this.move_cell_down(cx)))
             .on_action(cx.listener(|this, &AddMarkdownBlock, cx| this.add_markdown_block(cx)))
             .on_action(cx.listener(|this, &AddCodeBlock, cx| this.add_code_block(cx)))
+            .on_action(cx.listener(|this, &DeleteCell, cx| this.delete_cell(cx)))
             .on_action(cx.listener(Self::select_next))
             .on_action(cx.listener(Self::select_previous))
             .on_action(cx.listener(Self::select_first))
@@ -488,6 +855,9 @@ pub struct NotebookItem {
     notebook: nbformat::v4::Notebook,
     // Store our version of the notebook in memory (cell_order, cell_map)
     id: ProjectEntryId,
+    /// Incremented whenever the notebook is replaced, so that the editors showing it can tell
+    /// whether their cells are up to date.
+    version: usize,
 }
 
 impl project::ProjectItem for NotebookItem {
@@ -507,24 +877,8 @@ impl project::ProjectItem for NotebookItem {
                     .read_with(&cx, |project, cx| project.absolute_path(&path, cx))?
                     .ok_or_else(|| anyhow::anyhow!("Failed to find the absolute path"))?;
 
-                // todo: watch for changes to the file
                 let file_content = fs.load(&abs_path.as_path()).await?;
-                let notebook = nbformat::parse_notebook(&file_content);
-
-                let notebook = match notebook {
-                    Ok(nbformat::Notebook::V4(notebook)) => notebook,
-                    // 4.1 - 4.4 are converted to 4.5
-                    Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
-                        // todo!(): Decide if we want to mutate the notebook by including Cell IDs
-                        // and any other conversions
-                        let notebook = nbformat::upgrade_legacy_notebook(legacy_notebook)?;
-                        notebook
-                    }
-                    // Bad notebooks and notebooks v4.0 and below are not supported
-                    Err(e) => {
-                        anyhow::bail!("Failed to parse notebook: {:?}", e);
-                    }
-                };
+                let notebook = parse_notebook(&file_content)?;
 
                 let id = project
                     .update(&mut cx, |project, cx| project.entry_for_path(&path, cx))?
@@ -537,6 +891,7 @@ impl project::ProjectItem for NotebookItem {
                     languages,
                     notebook,
                     id,
+                    version: 0,
                 })
             }))
         } else {
@@ -557,7 +912,39 @@ impl project::ProjectItem for NotebookItem {
     }
 }
 
+/// Parses a notebook, upgrading notebooks older than nbformat 4.5.
+fn parse_notebook(content: &str) -> Result<nbformat::v4::Notebook> {
+    match nbformat::parse_notebook(content) {
+        Ok(nbformat::Notebook::V4(notebook)) => Ok(notebook),
+        // 4.1 - 4.4 are converted to 4.5, which gives their cells ids
+        Ok(nbformat::Notebook::Legacy(legacy_notebook)) => {
+            Ok(nbformat::upgrade_legacy_notebook(legacy_notebook)?)
+        }
+        // Bad notebooks and notebooks v4.0 and below are not supported
+        Err(e) => {
+            anyhow::bail!("Failed to parse notebook: {:?}", e);
+        }
+    }
+}
+
+/// Serializes a notebook the way Jupyter does, indented by one space.
+fn serialize_notebook(notebook: &nbformat::v4::Notebook) -> Result<String> {
+    let mut json = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut json, formatter);
+    notebook.serialize(&mut serializer)?;
+    let mut json = String::from_utf8(json)?;
+    json.push('\n');
+    Ok(json)
+}
+
 impl NotebookItem {
+    fn set_notebook(&mut self, notebook: nbformat::v4::Notebook, cx: &mut ModelContext<Self>) {
+        self.notebook = notebook;
+        self.version += 1;
+        cx.notify();
+    }
+
     pub fn language_name(&self) -> Option<String> {
         self.notebook
             .metadata
@@ -644,6 +1031,11 @@ impl Item for NotebookEditor {
     where
         Self: Sized,
     {
+        // The new pane loads its cells from the notebook item, which doesn't have the unsaved
+        // changes of this one.
+        if self.dirty {
+            return None;
+        }
         Some(cx.new_view(|cx| Self::new(self.project.clone(), self.notebook_item.clone(), cx)))
     }
 
@@ -695,47 +1087,77 @@ impl Item for NotebookEditor {
         // TODO
     }
 
-    // TODO
+    fn added_to_workspace(&mut self, workspace: &mut Workspace, _: &mut ViewContext<Self>) {
+        self.workspace = Some(workspace.weak_handle());
+    }
+
+    fn to_item_events(_event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(ItemEvent::UpdateTab)
+    }
+
     fn can_save(&self, _cx: &AppContext) -> bool {
-        false
+        true
     }
-    // TODO
+
     fn save(
         &mut self,
         _format: bool,
         _project: Model<Project>,
-        _cx: &mut ViewContext<Self>,
+        cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("save() must be implemented if can_save() returns true")
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        self.write_notebook(abs_path, cx)
     }
 
-    // TODO
     fn save_as(
         &mut self,
-        _project: Model<Project>,
-        _path: ProjectPath,
-        _cx: &mut ViewContext<Self>,
-    ) -> Task<Result<()>> {
-        unimplemented!("save_as() must be implemented if can_save() returns true")
-    }
-    // TODO
-    fn reload(
-        &mut self,
-        _project: Model<Project>,
-        _cx: &mut ViewContext<Self>,
+        project: Model<Project>,
+        path: ProjectPath,
+        cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
-        unimplemented!("reload() must be implemented if can_save() returns true")
+        let Some(abs_path) = project.read(cx).absolute_path(&path, cx) else {
+            return Task::ready(Err(anyhow!("Failed to find the absolute path")));
+        };
+        let write = self.write_notebook(abs_path.clone(), cx);
+
+        cx.spawn(|this, mut cx| async move {
+            write.await?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, _| {
+                    notebook_item.path = abs_path;
+                    notebook_item.project_path = path;
+                });
+                this.watch_notebook_file(cx);
+                cx.emit(());
+            })
+        })
     }
 
-    fn is_dirty(&self, cx: &AppContext) -> bool {
-        self.cell_map.values().any(|cell| {
-            if let Cell::Code(code_cell) = cell {
-                code_cell.read(cx).is_dirty(cx)
-            } else {
-                false
-            }
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let abs_path = self.notebook_item.read(cx).path.clone();
+        let fs = project.read(cx).fs().clone();
+
+        cx.spawn(|this, mut cx| async move {
+            let file_content = fs.load(&abs_path).await?;
+            let notebook = parse_notebook(&file_content)?;
+            this.update(&mut cx, |this, cx| {
+                this.notebook_item.update(cx, |notebook_item, cx| {
+                    notebook_item.set_notebook(notebook, cx)
+                });
+                this.dirty = false;
+                this.load_cells(cx);
+                cx.emit(());
+            })
         })
     }
+
+    fn is_dirty(&self, _cx: &AppContext) -> bool {
+        self.dirty
+    }
+
+    fn has_conflict(&self, _cx: &AppContext) -> bool {
+        self.conflict
+    }
 }
 
 // TODO: Implement this to allow us to persist to the database, etc:
@@ -755,3 +1177,214 @@ impl ProjectItem for NotebookEditor {
         Self::new(project, item, cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use indoc::indoc;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_edit_and_save_notebook(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({ "notebook.ipynb": notebook_with_source("print(1)") }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let project_path = ProjectPath {
+            worktree_id,
+            path: Path::new("notebook.ipynb").into(),
+        };
+        let notebook_item = cx
+            .update(|cx| NotebookItem::try_open(&project, &project_path, cx))
+            .unwrap()
+            .await
+            .unwrap();
+        let (notebook, cx) = cx
+            .add_window_view(|cx| NotebookEditor::new(project.clone(), notebook_item.clone(), cx));
+        cx.run_until_parked();
+        assert!(!notebook.update(cx, |notebook, cx| notebook.is_dirty(cx)));
+
+        // Editing a cell makes the notebook dirty, and a dirty notebook can't be split.
+        set_first_cell_source(&notebook, "print(2)", cx);
+        assert!(notebook.update(cx, |notebook, cx| notebook.is_dirty(cx)));
+        assert!(notebook
+            .update(cx, |notebook, cx| notebook.clone_on_split(None, cx))
+            .is_none());
+
+        // Saving writes the edited cells to disk.
+        notebook
+            .update(cx, |notebook, cx| notebook.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        assert!(!notebook.update(cx, |notebook, cx| notebook.is_dirty(cx)));
+        let saved =
+            parse_notebook(&fs.load("/dir/notebook.ipynb".as_ref()).await.unwrap()).unwrap();
+        assert!(matches!(
+            &saved.cells[0],
+            nbformat::v4::Cell::Code { source, .. } if source.join("") == "print(2)"
+        ));
+
+        // Saving in one pane reloads the other one.
+        let split = notebook
+            .update(cx, |notebook, cx| notebook.clone_on_split(None, cx))
+            .unwrap();
+        set_first_cell_source(&notebook, "print(3)", cx);
+        notebook
+            .update(cx, |notebook, cx| notebook.save(false, project.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(first_cell_source(&split, cx), "print(3)");
+        assert!(!split.update(cx, |split, cx| split.is_dirty(cx)));
+
+        // Changes on disk reload clean panes, and conflict with unsaved changes.
+        set_first_cell_source(&split, "print(4)", cx);
+        fs.insert_file(
+            "/dir/notebook.ipynb",
+            notebook_with_source("print(5)").into_bytes(),
+        )
+        .await;
+        cx.executor().advance_clock(Duration::from_millis(200));
+        cx.run_until_parked();
+        assert_eq!(first_cell_source(&notebook, cx), "print(5)");
+        assert!(!notebook.update(cx, |notebook, cx| notebook.has_conflict(cx)));
+        assert_eq!(first_cell_source(&split, cx), "print(4)");
+        assert!(split.update(cx, |split, cx| split.has_conflict(cx)));
+    }
+
+    fn notebook_with_source(source: &str) -> String {
+        serde_json::to_string_pretty(&json!({
+            "cells": [{
+                "cell_type": "code",
+                "execution_count": null,
+                "id": "cell",
+                "metadata": {},
+                "outputs": [],
+                "source": [source],
+            }],
+            "metadata": {
+                "kernelspec": {
+                    "display_name": "Python 3",
+                    "language": "python",
+                    "name": "python3",
+                },
+            },
+            "nbformat": 4,
+            "nbformat_minor": 5,
+        }))
+        .unwrap()
+    }
+
+    fn first_cell_source(notebook: &View<NotebookEditor>, cx: &mut VisualTestContext) -> String {
+        notebook.update(cx, |notebook, cx| {
+            notebook.cell_map[&notebook.cell_order[0]]
+                .editor(cx)
+                .read(cx)
+                .text(cx)
+        })
+    }
+
+    fn set_first_cell_source(
+        notebook: &View<NotebookEditor>,
+        source: &str,
+        cx: &mut VisualTestContext,
+    ) {
+        let editor = notebook.update(cx, |notebook, cx| {
+            notebook.cell_map[&notebook.cell_order[0]].editor(cx)
+        });
+        editor.update(cx, |editor, cx| editor.set_text(source, cx));
+        cx.run_until_parked();
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[test]
+    fn test_notebook_round_trip() {
+        let content = indoc! {r##"
+            {
+             "cells": [
+              {
+               "cell_type": "markdown",
+               "id": "intro",
+               "metadata": {},
+               "source": [
+                "# Title\n",
+                "Some text"
+               ]
+              },
+              {
+               "cell_type": "code",
+               "execution_count": 1,
+               "id": "print-one",
+               "metadata": {
+                "tags": ["example"]
+               },
+               "outputs": [
+                {
+                 "name": "stdout",
+                 "output_type": "stream",
+                 "text": [
+                  "1\n"
+                 ]
+                }
+               ],
+               "source": [
+                "print(1)"
+               ]
+              }
+             ],
+             "metadata": {
+              "kernelspec": {
+               "display_name": "Python 3",
+               "language": "python",
+               "name": "python3"
+              }
+             },
+             "nbformat": 4,
+             "nbformat_minor": 5
+            }
+        "##};
+
+        let notebook = parse_notebook(content).unwrap();
+        let serialized = serialize_notebook(&notebook).unwrap();
+        assert!(serialized.starts_with("{\n \"cells\": [\n  {\n"));
+        assert!(serialized.ends_with("}\n"));
+
+        let reparsed = parse_notebook(&serialized).unwrap();
+        assert_eq!(
+            reparsed
+                .cells
+                .iter()
+                .map(|cell| cell.id().to_string())
+                .collect::<Vec<_>>(),
+            vec!["intro", "print-one"]
+        );
+        assert_eq!(
+            reparsed
+                .metadata
+                .kernelspec
+                .map(|kernelspec| kernelspec.name),
+            Some("python3".to_string())
+        );
+        assert_eq!(serialize_notebook(&reparsed).unwrap(), serialized);
+    }
+}
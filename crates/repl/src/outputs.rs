@@ -37,8 +37,8 @@ use std::time::Duration;
 
 use editor::{Editor, MultiBuffer};
 use gpui::{
-    percentage, Animation, AnimationExt, AnyElement, ClipboardItem, Model, Render, Transformation,
-    View, WeakView,
+    percentage, Animation, AnimationExt, AnyElement, ClipboardItem, EventEmitter, Model, Render,
+    Transformation, View, WeakView,
 };
 use language::Buffer;
 use runtimelib::{ExecutionState, JupyterMessageContent, MimeBundle, MimeType};
@@ -313,6 +313,12 @@ pub struct ExecutionView {
     pub status: ExecutionStatus,
 }
 
+/// Emitted for every Jupyter message the view accepts, so that its owner can keep
+/// a copy of the raw outputs, e.g. to save them to a notebook.
+pub struct ExecutionMessage(pub JupyterMessageContent);
+
+impl EventEmitter<ExecutionMessage> for ExecutionView {}
+
 impl ExecutionView {
     pub fn new(
         status: ExecutionStatus,
@@ -328,6 +334,8 @@ impl ExecutionView {
 
     /// Accept a Jupyter message belonging to this execution
     pub fn push_message(&mut self, message: &JupyterMessageContent, cx: &mut ViewContext<Self>) {
+        cx.emit(ExecutionMessage(message.clone()));
+
         let output: Output = match message {
             JupyterMessageContent::ExecuteResult(result) => Output::new(
                 &result.data,
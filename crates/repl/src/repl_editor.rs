@@ -56,7 +56,7 @@ pub fn assign_kernelspec(
                 move |_this, _session, event, cx| match event {
                     SessionEvent::Shutdown(shutdown_event) => {
                         store.update(cx, |store, _cx| {
-                            store.remove_session(shutdown_event.entity_id());
+                            store.remove_session(*shutdown_event);
                         });
                     }
                 }
@@ -123,7 +123,7 @@ pub fn run(editor: WeakView<Editor>, move_down: bool, cx: &mut WindowContext) ->
                     move |_this, _session, event, cx| match event {
                         SessionEvent::Shutdown(shutdown_event) => {
                             store.update(cx, |store, _cx| {
-                                store.remove_session(shutdown_event.entity_id());
+                                store.remove_session(*shutdown_event);
                             });
                         }
                     }
@@ -18,7 +18,8 @@ use editor::{
 };
 use futures::FutureExt as _;
 use gpui::{
-    div, prelude::*, EventEmitter, Model, Render, Subscription, Task, View, ViewContext, WeakView,
+    div, prelude::*, EntityId, EventEmitter, Model, Render, Subscription, Task, View, ViewContext,
+    WeakView,
 };
 use language::Point;
 use project::Fs;
@@ -26,16 +27,24 @@ use runtimelib::{
     ExecuteRequest, ExecutionState, InterruptRequest, JupyterMessage, JupyterMessageContent,
     ShutdownRequest,
 };
-use std::{env::temp_dir, ops::Range, sync::Arc, time::Duration};
+use std::{env::temp_dir, mem, ops::Range, path::PathBuf, sync::Arc, time::Duration};
 use theme::ActiveTheme;
 use ui::{prelude::*, IconButtonShape, Tooltip};
 use util::ResultExt as _;
 
 pub struct Session {
     fs: Arc<dyn Fs>,
-    editor: WeakView<Editor>,
+    /// The editor whose code runs in this session, if the session isn't a notebook's.
+    editor: Option<WeakView<Editor>>,
+    /// The entity the session belongs to, such as its editor or notebook.
+    owner_id: EntityId,
+    working_directory: Option<PathBuf>,
     pub kernel: Kernel,
     blocks: HashMap<String, EditorBlock>,
+    /// Executions whose outputs are shown outside of the editor, such as in notebook cells.
+    execution_views: HashMap<String, View<ExecutionView>>,
+    /// Executions waiting for the kernel to start.
+    queued_messages: Vec<JupyterMessage>,
     pub kernel_specification: KernelSpecification,
     telemetry: Arc<Telemetry>,
     _buffer_subscription: Subscription,
@@ -216,9 +225,13 @@ impl Session {
 
         let mut session = Self {
             fs,
-            editor,
+            owner_id: editor.entity_id(),
+            editor: Some(editor),
+            working_directory: None,
             kernel: Kernel::StartingKernel(Task::ready(()).shared()),
             blocks: HashMap::default(),
+            execution_views: HashMap::default(),
+            queued_messages: Vec::new(),
             kernel_specification,
             _buffer_subscription: subscription,
             telemetry,
@@ -228,13 +241,43 @@ impl Session {
         session
     }
 
+    /// Creates a session that isn't attached to an editor. Its code is run with
+    /// [`Session::execute_in_view`], e.g. by the cells of a notebook.
+    pub fn detached(
+        owner_id: EntityId,
+        working_directory: Option<PathBuf>,
+        fs: Arc<dyn Fs>,
+        telemetry: Arc<Telemetry>,
+        kernel_specification: KernelSpecification,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut session = Self {
+            fs,
+            editor: None,
+            owner_id,
+            working_directory,
+            kernel: Kernel::StartingKernel(Task::ready(()).shared()),
+            blocks: HashMap::default(),
+            execution_views: HashMap::default(),
+            queued_messages: Vec::new(),
+            kernel_specification,
+            _buffer_subscription: Subscription::new(|| {}),
+            telemetry,
+        };
+
+        session.start_kernel(cx);
+        session
+    }
+
     fn start_kernel(&mut self, cx: &mut ViewContext<Self>) {
         let kernel_language = self.kernel_specification.language();
-        let entity_id = self.editor.entity_id();
+        let entity_id = self.owner_id;
         let working_directory = self
             .editor
-            .upgrade()
+            .as_ref()
+            .and_then(|editor| editor.upgrade())
             .and_then(|editor| editor.read(cx).working_directory(cx))
+            .or_else(|| self.working_directory.clone())
             .unwrap_or_else(temp_dir);
 
         self.telemetry.report_repl_event(
@@ -291,21 +334,25 @@ impl Session {
     pub fn kernel_errored(&mut self, error_message: String, cx: &mut ViewContext<Self>) {
         self.kernel(Kernel::ErroredLaunch(error_message.clone()), cx);
 
-        self.blocks.values().for_each(|block| {
-            block.execution_view.update(cx, |execution_view, cx| {
-                match execution_view.status {
-                    ExecutionStatus::Finished => {
-                        // Do nothing when the output was good
-                    }
-                    _ => {
-                        // All other cases, set the status to errored
-                        execution_view.status =
-                            ExecutionStatus::KernelErrored(error_message.clone())
+        self.blocks
+            .values()
+            .map(|block| &block.execution_view)
+            .chain(self.execution_views.values())
+            .for_each(|execution_view| {
+                execution_view.update(cx, |execution_view, cx| {
+                    match execution_view.status {
+                        ExecutionStatus::Finished => {
+                            // Do nothing when the output was good
+                        }
+                        _ => {
+                            // All other cases, set the status to errored
+                            execution_view.status =
+                                ExecutionStatus::KernelErrored(error_message.clone())
+                        }
                     }
-                }
-                cx.notify();
+                    cx.notify();
+                });
             });
-        });
     }
 
     fn on_buffer_event(
@@ -329,11 +376,7 @@ impl Session {
             });
 
             if !blocks_to_remove.is_empty() {
-                self.editor
-                    .update(cx, |editor, cx| {
-                        editor.remove_blocks(blocks_to_remove, None, cx);
-                    })
-                    .ok();
+                self.remove_blocks(blocks_to_remove, cx);
                 cx.notify();
             }
         }
@@ -347,17 +390,94 @@ impl Session {
         anyhow::Ok(())
     }
 
+    fn remove_blocks(&self, blocks: HashSet<CustomBlockId>, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = self.editor.as_ref() {
+            editor
+                .update(cx, |editor, cx| {
+                    editor.remove_blocks(blocks, None, cx);
+                })
+                .ok();
+        }
+    }
+
     pub fn clear_outputs(&mut self, cx: &mut ViewContext<Self>) {
         let blocks_to_remove: HashSet<CustomBlockId> =
             self.blocks.values().map(|block| block.block_id).collect();
 
-        self.editor
-            .update(cx, |editor, cx| {
-                editor.remove_blocks(blocks_to_remove, None, cx);
-            })
-            .ok();
+        self.remove_blocks(blocks_to_remove, cx);
 
         self.blocks.clear();
+        // The views belong to their owners, so only stop routing messages to them.
+        self.execution_views.clear();
+    }
+
+    fn execution_status(&self) -> ExecutionStatus {
+        match &self.kernel {
+            Kernel::Restarting => ExecutionStatus::Restarting,
+            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
+            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
+            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
+            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
+            Kernel::Shutdown => ExecutionStatus::Shutdown,
+        }
+    }
+
+    fn send_when_started(&mut self, message: JupyterMessage, cx: &mut ViewContext<Self>) {
+        match &self.kernel {
+            Kernel::RunningKernel(_) => {
+                self.send(message, cx).ok();
+            }
+            Kernel::StartingKernel(task) => {
+                // Queue up the execution to run after the kernel starts, keeping the
+                // executions in order
+                self.queued_messages.push(message);
+                if self.queued_messages.len() > 1 {
+                    return;
+                }
+                let task = task.clone();
+
+                cx.spawn(|this, mut cx| async move {
+                    task.await;
+                    this.update(&mut cx, |session, cx| {
+                        for message in mem::take(&mut session.queued_messages) {
+                            session.send(message, cx).ok();
+                        }
+                    })
+                    .ok();
+                })
+                .detach();
+            }
+            _ => {}
+        }
+    }
+
+    /// Runs the code, showing its outputs in the given view instead of in a block of the editor.
+    pub fn execute_in_view(
+        &mut self,
+        code: String,
+        execution_view: View<ExecutionView>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if code.is_empty() {
+            return;
+        }
+
+        let message: JupyterMessage = ExecuteRequest {
+            code,
+            ..ExecuteRequest::default()
+        }
+        .into();
+
+        let status = self.execution_status();
+        execution_view.update(cx, |execution_view, cx| {
+            execution_view.outputs.clear();
+            execution_view.status = status;
+            cx.notify();
+        });
+        self.execution_views
+            .insert(message.header.msg_id.clone(), execution_view);
+
+        self.send_when_started(message, cx);
     }
 
     pub fn execute(
@@ -368,7 +488,7 @@ impl Session {
         move_down: bool,
         cx: &mut ViewContext<Self>,
     ) {
-        let Some(editor) = self.editor.upgrade() else {
+        let Some(editor) = self.editor.as_ref().and_then(|editor| editor.upgrade()) else {
             return;
         };
 
@@ -396,24 +516,13 @@ impl Session {
             }
         });
 
-        self.editor
-            .update(cx, |editor, cx| {
-                editor.remove_blocks(blocks_to_remove, None, cx);
-            })
-            .ok();
+        self.remove_blocks(blocks_to_remove, cx);
 
-        let status = match &self.kernel {
-            Kernel::Restarting => ExecutionStatus::Restarting,
-            Kernel::RunningKernel(_) => ExecutionStatus::Queued,
-            Kernel::StartingKernel(_) => ExecutionStatus::ConnectingToKernel,
-            Kernel::ErroredLaunch(error) => ExecutionStatus::KernelErrored(error.clone()),
-            Kernel::ShuttingDown => ExecutionStatus::ShuttingDown,
-            Kernel::Shutdown => ExecutionStatus::Shutdown,
-        };
+        let status = self.execution_status();
 
         let parent_message_id = message.header.msg_id.clone();
         let session_view = cx.view().downgrade();
-        let weak_editor = self.editor.clone();
+        let weak_editor = editor.downgrade();
 
         let on_close: CloseBlockFn =
             Arc::new(move |block_id: CustomBlockId, cx: &mut WindowContext| {
@@ -434,7 +543,7 @@ impl Session {
             });
 
         let Ok(editor_block) =
-            EditorBlock::new(self.editor.clone(), anchor_range, status, on_close, cx)
+            EditorBlock::new(editor.downgrade(), anchor_range, status, on_close, cx)
         else {
             return;
         };
@@ -448,26 +557,7 @@ impl Session {
         self.blocks
             .insert(message.header.msg_id.clone(), editor_block);
 
-        match &self.kernel {
-            Kernel::RunningKernel(_) => {
-                self.send(message, cx).ok();
-            }
-            Kernel::StartingKernel(task) => {
-                // Queue up the execution as a task to run after the kernel starts
-                let task = task.clone();
-                let message = message.clone();
-
-                cx.spawn(|this, mut cx| async move {
-                    task.await;
-                    this.update(&mut cx, |session, cx| {
-                        session.send(message, cx).ok();
-                    })
-                    .ok();
-                })
-                .detach();
-            }
-            _ => {}
-        }
+        self.send_when_started(message, cx);
 
         if move_down {
             editor.update(cx, move |editor, cx| {
@@ -507,11 +597,15 @@ impl Session {
                     return;
                 };
 
-                self.blocks.iter_mut().for_each(|(_, block)| {
-                    block.execution_view.update(cx, |execution_view, cx| {
-                        execution_view.update_display_data(&update.data, &display_id, cx);
+                self.blocks
+                    .values()
+                    .map(|block| &block.execution_view)
+                    .chain(self.execution_views.values())
+                    .for_each(|execution_view| {
+                        execution_view.update(cx, |execution_view, cx| {
+                            execution_view.update_display_data(&update.data, &display_id, cx);
+                        });
                     });
-                });
                 return;
             }
             _ => {}
@@ -519,6 +613,10 @@ impl Session {
 
         if let Some(block) = self.blocks.get_mut(parent_message_id) {
             block.handle_message(message, cx);
+        } else if let Some(execution_view) = self.execution_views.get(parent_message_id) {
+            execution_view.update(cx, |execution_view, cx| {
+                execution_view.push_message(&message.content, cx);
+            });
         }
     }
 
@@ -536,7 +634,7 @@ impl Session {
 
     pub fn kernel(&mut self, kernel: Kernel, cx: &mut ViewContext<Self>) {
         if let Kernel::Shutdown = kernel {
-            cx.emit(SessionEvent::Shutdown(self.editor.clone()));
+            cx.emit(SessionEvent::Shutdown(self.owner_id));
         }
 
         let kernel_status = KernelStatus::from(&kernel).to_string();
@@ -629,7 +727,8 @@ impl Session {
 }
 
 pub enum SessionEvent {
-    Shutdown(WeakView<Editor>),
+    /// The kernel of the session shut down. Carries the id of the session's owner.
+    Shutdown(EntityId),
 }
 
 impl EventEmitter<SessionEvent> for Session {}
@@ -744,6 +744,17 @@ impl BufferSearchBar {
     pub fn replacement(&self, cx: &WindowContext) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
+
+    /// Fills an empty search history, such as with the queries of a previous session.
+    pub fn restore_search_history(&mut self, queries: impl IntoIterator<Item = String>) {
+        if !self.search_history.is_empty() {
+            return;
+        }
+        for query in queries {
+            self.search_history
+                .add(&mut self.search_history_cursor, query);
+        }
+    }
     pub fn query_suggestion(&mut self, cx: &mut ViewContext<Self>) -> Option<String> {
         self.active_searchable_item
             .as_ref()
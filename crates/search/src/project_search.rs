@@ -1,7 +1,7 @@
 use crate::{
     buffer_search::Deploy, BufferSearchBar, FocusSearch, NextHistoryQuery, PreviousHistoryQuery,
     ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive,
-    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
@@ -74,6 +74,9 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -575,6 +578,14 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(option);
+        // Regex and structural patterns are different query languages, so only one can be active.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -965,7 +976,32 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                open_buffers,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1631,6 +1667,13 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        focus_handle.clone(),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
                     )),
             );
 
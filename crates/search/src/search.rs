@@ -27,6 +27,7 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleStructural,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -47,6 +48,7 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const STRUCTURAL = 0b10000;
     }
 }
 
@@ -57,6 +59,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "Match Case Sensitively",
             SearchOptions::INCLUDE_IGNORED => "Also search files ignored by configuration",
             SearchOptions::REGEX => "Use Regular Expressions",
+            SearchOptions::STRUCTURAL => "Match Code Structurally",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -67,6 +70,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::Sliders,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::STRUCTURAL => ui::IconName::Code,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -77,6 +81,7 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::STRUCTURAL => Box::new(ToggleStructural),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -91,6 +96,7 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
         options
     }
 
@@ -1,8 +1,10 @@
 mod cloud;
+mod local;
 mod ollama;
 mod open_ai;
 
 pub use cloud::*;
+pub use local::*;
 pub use ollama::*;
 pub use open_ai::*;
 use sha2::{Digest, Sha256};
@@ -42,6 +44,10 @@ impl Embedding {
         self.0.len()
     }
 
+    pub(crate) fn values(&self) -> &[f32] {
+        &self.0
+    }
+
     pub fn similarity(&self, others: &[Embedding]) -> (f32, usize) {
         debug_assert!(others.iter().all(|other| self.0.len() == other.0.len()));
         others
@@ -0,0 +1,313 @@
+use anyhow::{anyhow, Context as _, Result};
+use futures::{future::BoxFuture, FutureExt};
+use sha2::{Digest, Sha256};
+use std::{fmt::Write as _, path::Path, sync::Arc};
+use tokenizers::{PaddingParams, Tokenizer, TruncationParams};
+use tract_onnx::prelude::*;
+
+use crate::{Embedding, EmbeddingProvider, TextToEmbed};
+
+/// The name of the ONNX model file in a local model's directory.
+pub const LOCAL_EMBEDDING_MODEL_FILE_NAME: &str = "model.onnx";
+/// The name of the tokenizer file in a local model's directory.
+pub const LOCAL_EMBEDDING_TOKENIZER_FILE_NAME: &str = "tokenizer.json";
+
+/// The maximum number of tokens of a text. Longer texts are truncated.
+const MAX_SEQUENCE_LENGTH: usize = 512;
+
+/// Computes embeddings on the CPU with a sentence-embedding model exported to ONNX,
+/// such as `all-MiniLM-L6-v2`, without any network access.
+///
+/// The model is loaded from a directory containing the model as `model.onnx`
+/// and its Hugging Face tokenizer as `tokenizer.json`. The model has to take
+/// `input_ids`, `attention_mask` and optionally `token_type_ids`, and output the
+/// token embeddings first. They're mean-pooled over the text's tokens.
+pub struct LocalEmbeddingProvider {
+    model: Arc<LocalEmbeddingModel>,
+}
+
+struct LocalEmbeddingModel {
+    tokenizer: Tokenizer,
+    plan: TypedRunnableModel<TypedModel>,
+    inputs: Vec<ModelInput>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ModelInput {
+    InputIds,
+    AttentionMask,
+    TokenTypeIds,
+}
+
+impl LocalEmbeddingProvider {
+    /// Returns whether the directory contains the files of a local model.
+    pub fn is_model_dir(model_dir: &Path) -> bool {
+        model_dir.join(LOCAL_EMBEDDING_MODEL_FILE_NAME).is_file()
+            && model_dir
+                .join(LOCAL_EMBEDDING_TOKENIZER_FILE_NAME)
+                .is_file()
+    }
+
+    /// Returns a digest of the model and tokenizer files in the directory, which identifies
+    /// the embeddings the model computes. This reads the whole model, so do it in the background.
+    pub fn model_digest(model_dir: &Path) -> Result<String> {
+        let mut hasher = Sha256::new();
+        for file_name in [
+            LOCAL_EMBEDDING_MODEL_FILE_NAME,
+            LOCAL_EMBEDDING_TOKENIZER_FILE_NAME,
+        ] {
+            let contents = std::fs::read(model_dir.join(file_name))
+                .with_context(|| format!("failed to read the embedding model's {file_name}"))?;
+            hasher.update((contents.len() as u64).to_le_bytes());
+            hasher.update(contents);
+        }
+        let mut digest = String::new();
+        for byte in &hasher.finalize()[..8] {
+            write!(digest, "{byte:02x}")?;
+        }
+        Ok(digest)
+    }
+
+    /// Loads the model in the given directory. This is slow, so do it in the background.
+    pub fn new(model_dir: &Path) -> Result<Self> {
+        let mut tokenizer =
+            Tokenizer::from_file(model_dir.join(LOCAL_EMBEDDING_TOKENIZER_FILE_NAME))
+                .map_err(|error| anyhow!(error))
+                .context("failed to load the embedding model's tokenizer")?;
+        tokenizer.with_padding(Some(PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: MAX_SEQUENCE_LENGTH,
+                ..Default::default()
+            }))
+            .map_err(|error| anyhow!(error))?;
+
+        let mut model = tract_onnx::onnx()
+            .model_for_path(model_dir.join(LOCAL_EMBEDDING_MODEL_FILE_NAME))
+            .context("failed to load the embedding model")?;
+        let inputs = model
+            .input_outlets()?
+            .iter()
+            .map(|outlet| match model.node(outlet.node).name.as_str() {
+                "input_ids" => Ok(ModelInput::InputIds),
+                "attention_mask" => Ok(ModelInput::AttentionMask),
+                "token_type_ids" => Ok(ModelInput::TokenTypeIds),
+                name => Err(anyhow!("unsupported embedding model input {name:?}")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if !inputs.contains(&ModelInput::InputIds) || !inputs.contains(&ModelInput::AttentionMask) {
+            return Err(anyhow!(
+                "the embedding model has to take input_ids and attention_mask"
+            ));
+        }
+
+        // Exported models don't always declare the shapes of their inputs, so declare them here,
+        // leaving the batch size and the sequence length to be set by each run.
+        let batch_size = model.symbols.sym("batch_size");
+        let sequence_length = model.symbols.sym("sequence_length");
+        for ix in 0..inputs.len() {
+            model.set_input_fact(
+                ix,
+                InferenceFact::dt_shape(
+                    i64::datum_type(),
+                    tvec!(batch_size.to_dim(), sequence_length.to_dim()),
+                ),
+            )?;
+        }
+        let plan = model
+            .into_optimized()?
+            .into_runnable()
+            .context("failed to prepare the embedding model")?;
+
+        Ok(Self {
+            model: Arc::new(LocalEmbeddingModel {
+                tokenizer,
+                plan,
+                inputs,
+            }),
+        })
+    }
+}
+
+impl LocalEmbeddingModel {
+    fn embed(&self, texts: Vec<String>) -> Result<Vec<Embedding>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts, true)
+            .map_err(|error| anyhow!(error))?;
+        let batch_size = encodings.len();
+        let sequence_length = encodings[0].len();
+
+        let input = |values: fn(&tokenizers::Encoding) -> &[u32]| -> Result<TValue> {
+            let values = encodings
+                .iter()
+                .flat_map(|encoding| values(encoding).iter().map(|value| *value as i64))
+                .collect::<Vec<_>>();
+            let array =
+                tract_ndarray::Array2::from_shape_vec((batch_size, sequence_length), values)?;
+            Ok(Tensor::from(array).into())
+        };
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|model_input| match model_input {
+                ModelInput::InputIds => input(|encoding| encoding.get_ids()),
+                ModelInput::AttentionMask => input(|encoding| encoding.get_attention_mask()),
+                ModelInput::TokenTypeIds => input(|encoding| encoding.get_type_ids()),
+            })
+            .collect::<Result<TVec<_>>>()?;
+
+        let outputs = self.plan.run(inputs)?;
+        let token_embeddings = outputs
+            .first()
+            .ok_or_else(|| anyhow!("the embedding model has no outputs"))?
+            .to_array_view::<f32>()?
+            .into_dimensionality::<tract_ndarray::Ix3>()
+            .context("the embedding model's output isn't token embeddings")?;
+
+        let dimensions = token_embeddings.shape()[2];
+        Ok(encodings
+            .iter()
+            .enumerate()
+            .map(|(text_ix, encoding)| {
+                let mut embedding = vec![0f32; dimensions];
+                let mut token_count = 0;
+                for (token_ix, mask) in encoding.get_attention_mask().iter().enumerate() {
+                    if *mask == 0 {
+                        continue;
+                    }
+                    token_count += 1;
+                    for (dimension, value) in embedding.iter_mut().enumerate() {
+                        *value += token_embeddings[[text_ix, token_ix, dimension]];
+                    }
+                }
+                for value in &mut embedding {
+                    *value /= token_count.max(1) as f32;
+                }
+                Embedding::new(embedding)
+            })
+            .collect())
+    }
+}
+
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    fn embed<'a>(&'a self, texts: &'a [TextToEmbed<'a>]) -> BoxFuture<'a, Result<Vec<Embedding>>> {
+        let model = self.model.clone();
+        let texts = texts
+            .iter()
+            .map(|to_embed| to_embed.text.to_string())
+            .collect::<Vec<_>>();
+        smol::unblock(move || model.embed(texts)).boxed()
+    }
+
+    fn batch_size(&self) -> usize {
+        // Texts are padded to the longest one of their batch, so keep batches small.
+        8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// A model whose token embeddings are looked up in a table, with the vocabulary
+    /// `[PAD]`, `[UNK]`, `hello` and `world`. The padding token's embedding is `[100, 100]`,
+    /// so that it shows up in the embeddings if padding isn't left out of the pooling.
+    fn fixture_model_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixture/local-model")
+    }
+
+    fn assert_embeddings_eq(actual: &[Embedding], expected: &[[f32; 2]]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (actual, expected) in actual.iter().zip(expected) {
+            let expected = Embedding::new(expected.to_vec());
+            assert!(
+                actual
+                    .values()
+                    .iter()
+                    .zip(expected.values())
+                    .all(|(actual, expected)| (actual - expected).abs() < 1e-5),
+                "expected {expected:?}, got {actual:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_local_embedding_model() {
+        assert!(LocalEmbeddingProvider::is_model_dir(&fixture_model_dir()));
+        assert!(!LocalEmbeddingProvider::is_model_dir(
+            &fixture_model_dir().join("missing")
+        ));
+
+        let provider = LocalEmbeddingProvider::new(&fixture_model_dir()).unwrap();
+        let model = &provider.model;
+        assert_eq!(
+            model.inputs,
+            [ModelInput::InputIds, ModelInput::AttentionMask]
+        );
+
+        // Texts of different lengths are padded to the same length within a batch, and the
+        // padding is left out when the token embeddings are averaged.
+        let embeddings = model
+            .embed(vec![
+                "hello world".into(),
+                "hello".into(),
+                "World".into(),
+                "goodbye".into(),
+            ])
+            .unwrap();
+        assert_embeddings_eq(&embeddings, &[[2., 2.], [1., 0.], [3., 4.], [0., 1.]]);
+
+        // Texts are truncated to the maximum sequence length.
+        let long_text = "hello ".repeat(MAX_SEQUENCE_LENGTH) + &"world ".repeat(10);
+        let embeddings = model.embed(vec![long_text, "world".into()]).unwrap();
+        assert_embeddings_eq(&embeddings, &[[1., 0.], [3., 4.]]);
+
+        assert!(model.embed(Vec::new()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_local_embedding_model_digest() {
+        let digest = LocalEmbeddingProvider::model_digest(&fixture_model_dir()).unwrap();
+        assert_eq!(digest.len(), 16);
+        assert_eq!(
+            LocalEmbeddingProvider::model_digest(&fixture_model_dir()).unwrap(),
+            digest
+        );
+
+        // Another tokenizer gives other embeddings, and so another digest.
+        let model_dir = tempfile::tempdir().unwrap();
+        for file_name in [
+            LOCAL_EMBEDDING_MODEL_FILE_NAME,
+            LOCAL_EMBEDDING_TOKENIZER_FILE_NAME,
+        ] {
+            std::fs::copy(
+                fixture_model_dir().join(file_name),
+                model_dir.path().join(file_name),
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            LocalEmbeddingProvider::model_digest(model_dir.path()).unwrap(),
+            digest
+        );
+        std::fs::write(
+            model_dir.path().join(LOCAL_EMBEDDING_TOKENIZER_FILE_NAME),
+            "{}",
+        )
+        .unwrap();
+        assert_ne!(
+            LocalEmbeddingProvider::model_digest(model_dir.path()).unwrap(),
+            digest
+        );
+        assert!(
+            LocalEmbeddingProvider::model_digest(&fixture_model_dir().join("missing")).is_err()
+        );
+    }
+}
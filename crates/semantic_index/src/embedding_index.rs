@@ -2,6 +2,7 @@ use crate::{
     chunking::{self, Chunk},
     embedding::{Embedding, EmbeddingProvider, TextToEmbed},
     indexing::{IndexingEntryHandle, IndexingEntrySet},
+    search_index::{ChunkTerms, SearchIndex},
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::Bound;
@@ -18,7 +19,14 @@ use project::{Entry, UpdatedEntriesSet, Worktree};
 use serde::{Deserialize, Serialize};
 use smol::channel;
 use smol::future::FutureExt;
-use std::{cmp::Ordering, future::Future, iter, path::Path, sync::Arc, time::Duration};
+use std::{
+    cmp::Ordering,
+    future::Future,
+    iter,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 use util::ResultExt;
 use worktree::Snapshot;
 
@@ -26,6 +34,7 @@ pub struct EmbeddingIndex {
     worktree: Model<Worktree>,
     db_connection: heed::Env,
     db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+    search_index: SearchIndex,
     fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -38,6 +47,7 @@ impl EmbeddingIndex {
         fs: Arc<dyn Fs>,
         db_connection: heed::Env,
         embedding_db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+        search_index: SearchIndex,
         language_registry: Arc<LanguageRegistry>,
         embedding_provider: Arc<dyn EmbeddingProvider>,
         entry_ids_being_indexed: Arc<IndexingEntrySet>,
@@ -47,6 +57,7 @@ impl EmbeddingIndex {
             fs,
             db_connection,
             db: embedding_db,
+            search_index,
             language_registry,
             embedding_provider,
             entry_ids_being_indexed,
@@ -57,6 +68,10 @@ impl EmbeddingIndex {
         &self.db
     }
 
+    pub fn search_index(&self) -> &SearchIndex {
+        &self.search_index
+    }
+
     pub fn index_entries_changed_on_disk(
         &self,
         cx: &AppContext,
@@ -68,11 +83,13 @@ impl EmbeddingIndex {
         let worktree = self.worktree.read(cx).snapshot();
         let worktree_abs_path = worktree.abs_path().clone();
         let scan = self.scan_entries(worktree, cx);
-        let chunk = self.chunk_files(worktree_abs_path, scan.updated_entries, cx);
+        let chunk = self.chunk_files(worktree_abs_path.clone(), scan.updated_entries, cx);
         let embed = Self::embed_files(self.embedding_provider.clone(), chunk.files, cx);
-        let persist = self.persist_embeddings(scan.deleted_entry_ranges, embed.files, cx);
+        let load_terms = self.load_chunk_terms(worktree_abs_path, scan.unsearchable_files, cx);
+        let persist =
+            self.persist_embeddings(scan.deleted_entry_ranges, embed.files, load_terms.files, cx);
         async move {
-            futures::try_join!(scan.task, chunk.task, embed.task, persist)?;
+            futures::try_join!(scan.task, chunk.task, embed.task, load_terms.task, persist)?;
             Ok(())
         }
         .boxed()
@@ -90,11 +107,13 @@ impl EmbeddingIndex {
         let worktree = self.worktree.read(cx).snapshot();
         let worktree_abs_path = worktree.abs_path().clone();
         let scan = self.scan_updated_entries(worktree, updated_entries.clone(), cx);
-        let chunk = self.chunk_files(worktree_abs_path, scan.updated_entries, cx);
+        let chunk = self.chunk_files(worktree_abs_path.clone(), scan.updated_entries, cx);
         let embed = Self::embed_files(self.embedding_provider.clone(), chunk.files, cx);
-        let persist = self.persist_embeddings(scan.deleted_entry_ranges, embed.files, cx);
+        let load_terms = self.load_chunk_terms(worktree_abs_path, scan.unsearchable_files, cx);
+        let persist =
+            self.persist_embeddings(scan.deleted_entry_ranges, embed.files, load_terms.files, cx);
         async move {
-            futures::try_join!(scan.task, chunk.task, embed.task, persist)?;
+            futures::try_join!(scan.task, chunk.task, embed.task, load_terms.task, persist)?;
             Ok(())
         }
         .boxed()
@@ -103,8 +122,10 @@ impl EmbeddingIndex {
     fn scan_entries(&self, worktree: Snapshot, cx: &AppContext) -> ScanEntries {
         let (updated_entries_tx, updated_entries_rx) = channel::bounded(512);
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
+        let (unsearchable_files_tx, unsearchable_files_rx) = channel::bounded(512);
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let search_index = self.search_index;
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
         let task = cx.background_executor().spawn(async move {
             let txn = db_connection
@@ -123,6 +144,7 @@ impl EmbeddingIndex {
                 let entry_db_key = db_key_for_path(&entry.path);
 
                 let mut saved_mtime = None;
+                let mut unsearchable_file = None;
                 while let Some(db_entry) = db_entries.peek() {
                     match db_entry {
                         Ok((db_path, db_embedded_file)) => match (*db_path).cmp(&entry_db_key) {
@@ -146,7 +168,16 @@ impl EmbeddingIndex {
                                         .await?;
                                 }
                                 saved_mtime = db_embedded_file.mtime;
-                                db_entries.next();
+                                // Files embedded before the search index existed are added to
+                                // it without embedding them again.
+                                let is_unsearchable = saved_mtime == entry.mtime
+                                    && !search_index.contains_file(&txn, db_path)?;
+                                if is_unsearchable {
+                                    unsearchable_file =
+                                        db_entries.next().transpose()?.map(|(_, file)| file);
+                                } else {
+                                    db_entries.next();
+                                }
                                 break;
                             }
                             Ordering::Greater => {
@@ -160,6 +191,9 @@ impl EmbeddingIndex {
                 if entry.mtime != saved_mtime {
                     let handle = entries_being_indexed.insert(entry.id);
                     updated_entries_tx.send((entry.clone(), handle)).await?;
+                } else if let Some(file) = unsearchable_file {
+                    let handle = entries_being_indexed.insert(entry.id);
+                    unsearchable_files_tx.send((file, handle)).await?;
                 }
             }
 
@@ -176,6 +210,7 @@ impl EmbeddingIndex {
         ScanEntries {
             updated_entries: updated_entries_rx,
             deleted_entry_ranges: deleted_entry_ranges_rx,
+            unsearchable_files: unsearchable_files_rx,
             task,
         }
     }
@@ -188,6 +223,7 @@ impl EmbeddingIndex {
     ) -> ScanEntries {
         let (updated_entries_tx, updated_entries_rx) = channel::bounded(512);
         let (deleted_entry_ranges_tx, deleted_entry_ranges_rx) = channel::bounded(128);
+        let (_, unsearchable_files_rx) = channel::bounded(1);
         let entries_being_indexed = self.entry_ids_being_indexed.clone();
         let task = cx.background_executor().spawn(async move {
             for (path, entry_id, status) in updated_entries.iter() {
@@ -220,6 +256,7 @@ impl EmbeddingIndex {
         ScanEntries {
             updated_entries: updated_entries_rx,
             deleted_entry_ranges: deleted_entry_ranges_rx,
+            unsearchable_files: unsearchable_files_rx,
             task,
         }
     }
@@ -327,11 +364,13 @@ impl EmbeddingIndex {
                         chunks: Vec::new(),
                     };
 
+                    let mut chunk_terms = Vec::new();
                     let mut embedded_all_chunks = true;
                     for (chunk, embedding) in
                         chunked_file.chunks.into_iter().zip(embeddings.by_ref())
                     {
                         if let Some(embedding) = embedding {
+                            chunk_terms.push(ChunkTerms::new(&chunked_file.text[chunk.range.clone()]));
                             embedded_file
                                 .chunks
                                 .push(EmbeddedChunk { chunk, embedding });
@@ -342,7 +381,7 @@ impl EmbeddingIndex {
 
                     if embedded_all_chunks {
                         embedded_files_tx
-                            .send((embedded_file, chunked_file.handle))
+                            .send((embedded_file, chunk_terms, chunked_file.handle))
                             .await?;
                     }
                 }
@@ -356,14 +395,52 @@ impl EmbeddingIndex {
         }
     }
 
+    /// Loads the text of files that were embedded before, to add them to the search index.
+    fn load_chunk_terms(
+        &self,
+        worktree_abs_path: Arc<Path>,
+        files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+        cx: &AppContext,
+    ) -> LoadChunkTerms {
+        let fs = self.fs.clone();
+        let (files_tx, files_rx) = channel::bounded(512);
+        let task = cx.background_executor().spawn(async move {
+            while let Ok((file, handle)) = files.recv().await {
+                let Some(text) = fs.load(&worktree_abs_path.join(&file.path)).await.ok() else {
+                    continue;
+                };
+                let chunk_terms = file
+                    .chunks
+                    .iter()
+                    .map(|chunk| text.get(chunk.chunk.range.clone()).map(ChunkTerms::new))
+                    .collect::<Option<Vec<_>>>();
+                if let Some(chunk_terms) = chunk_terms {
+                    files_tx.send((file, chunk_terms, handle)).await?;
+                }
+            }
+            Ok(())
+        });
+
+        LoadChunkTerms {
+            files: files_rx,
+            task,
+        }
+    }
+
     fn persist_embeddings(
         &self,
         mut deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
-        mut embedded_files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+        mut embedded_files: channel::Receiver<(EmbeddedFile, Vec<ChunkTerms>, IndexingEntryHandle)>,
+        mut searchable_files: channel::Receiver<(
+            EmbeddedFile,
+            Vec<ChunkTerms>,
+            IndexingEntryHandle,
+        )>,
         cx: &AppContext,
     ) -> Task<Result<()>> {
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let search_index = self.search_index;
 
         cx.background_executor().spawn(async move {
             loop {
@@ -376,15 +453,26 @@ impl EmbeddingIndex {
                             let end = deletion_range.1.as_ref().map(|end| end.as_str());
                             log::debug!("deleting embeddings in range {:?}", &(start, end));
                             db.delete_range(&mut txn, &(start, end))?;
+                            search_index.remove_range(&mut txn, (start, end))?;
                             txn.commit()?;
                         }
                     },
                     file = embedded_files.next() => {
-                        if let Some((file, _)) = file {
+                        if let Some((file, chunk_terms, _)) = file {
                             let mut txn = db_connection.write_txn()?;
                             log::debug!("saving embedding for file {:?}", file.path);
                             let key = db_key_for_path(&file.path);
                             db.put(&mut txn, &key, &file)?;
+                            search_index.insert_file(&mut txn, &key, &file.chunks, &chunk_terms)?;
+                            txn.commit()?;
+                        }
+                    },
+                    file = searchable_files.next() => {
+                        if let Some((file, chunk_terms, _)) = file {
+                            let mut txn = db_connection.write_txn()?;
+                            log::debug!("adding file {:?} to the search index", file.path);
+                            let key = db_key_for_path(&file.path);
+                            search_index.insert_file(&mut txn, &key, &file.chunks, &chunk_terms)?;
                             txn.commit()?;
                         }
                     },
@@ -435,6 +523,7 @@ impl EmbeddingIndex {
 struct ScanEntries {
     updated_entries: channel::Receiver<(Entry, IndexingEntryHandle)>,
     deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
+    unsearchable_files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
     task: Task<Result<()>>,
 }
 
@@ -452,10 +541,15 @@ pub struct ChunkedFile {
 }
 
 pub struct EmbedFiles {
-    pub files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+    pub files: channel::Receiver<(EmbeddedFile, Vec<ChunkTerms>, IndexingEntryHandle)>,
     pub task: Task<Result<()>>,
 }
 
+struct LoadChunkTerms {
+    files: channel::Receiver<(EmbeddedFile, Vec<ChunkTerms>, IndexingEntryHandle)>,
+    task: Task<Result<()>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EmbeddedFile {
     pub path: Arc<Path>,
@@ -472,3 +566,7 @@ pub struct EmbeddedChunk {
 fn db_key_for_path(path: &Arc<Path>) -> String {
     path.to_string_lossy().replace('/', "\0")
 }
+
+pub(crate) fn path_for_db_key(key: &str) -> PathBuf {
+    PathBuf::from(key.replace('\0', "/"))
+}
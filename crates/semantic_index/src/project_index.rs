@@ -1,10 +1,11 @@
 use crate::{
-    embedding::{EmbeddingProvider, TextToEmbed},
+    embedding::{Embedding, EmbeddingProvider, TextToEmbed},
+    embedding_index::path_for_db_key,
     summary_index::FileSummary,
     worktree_index::{WorktreeIndex, WorktreeIndexHandle},
 };
 use anyhow::{anyhow, Context, Result};
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use fs::Fs;
 use futures::{stream::StreamExt, FutureExt};
 use gpui::{
@@ -23,7 +24,7 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use util::ResultExt;
+use util::{paths::PathMatcher, ResultExt};
 
 #[derive(Debug)]
 pub struct SearchResult {
@@ -49,6 +50,53 @@ pub struct WorktreeSearchResult {
     pub range: Range<usize>,
     pub query_index: usize,
     pub score: f32,
+    pub keyword_score: f32,
+}
+
+#[derive(Default)]
+struct WorkerSearchResults {
+    vector_matches: Vec<WorktreeSearchResult>,
+    keyword_matches: Vec<WorktreeSearchResult>,
+}
+
+/// The number of chunks of a worktree above which the search only compares the queries to the
+/// chunks found through the approximate nearest neighbor index, instead of to all of them.
+const EXACT_SEARCH_MAX_CHUNK_COUNT: u64 = 20_000;
+/// How many keyword matches to consider for each requested result.
+const KEYWORD_CANDIDATES_PER_RESULT: usize = 4;
+const DEFAULT_KEYWORD_WEIGHT: f32 = 0.3;
+
+#[derive(Clone, Debug)]
+pub struct SearchOptions {
+    pub limit: usize,
+    /// How much matching the queries' keywords counts towards a result's score, between 0 and 1.
+    /// The rest of the score is the similarity of the result's embedding to the queries'.
+    pub keyword_weight: f32,
+    /// When not empty, only the files matching these globs are searched.
+    pub files_to_include: PathMatcher,
+    /// The files matching these globs aren't searched.
+    pub files_to_exclude: PathMatcher,
+}
+
+impl SearchOptions {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            keyword_weight: DEFAULT_KEYWORD_WEIGHT,
+            files_to_include: PathMatcher::default(),
+            files_to_exclude: PathMatcher::default(),
+        }
+    }
+
+    /// Paths are matched both relative to their worktree and prefixed by the worktree's root name,
+    /// like in project search.
+    fn is_included(&self, root_name: &str, path: &Path) -> bool {
+        let full_path = Path::new(root_name).join(path);
+        let matches =
+            |matcher: &PathMatcher| matcher.is_match(path) || matcher.is_match(&full_path);
+        (self.files_to_include.sources().is_empty() || matches(&self.files_to_include))
+            && !matches(&self.files_to_exclude)
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -67,6 +115,7 @@ pub struct ProjectIndex {
     last_status: Status,
     status_tx: channel::Sender<()>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
+    exact_search_max_chunk_count: u64,
     _maintain_status: Task<()>,
     _subscription: Subscription,
 }
@@ -90,6 +139,7 @@ impl ProjectIndex {
             status_tx,
             last_status: Status::Idle,
             embedding_provider,
+            exact_search_max_chunk_count: EXACT_SEARCH_MAX_CHUNK_COUNT,
             _subscription: cx.subscribe(&project, Self::handle_project_event),
             _maintain_status: cx.spawn(|this, mut cx| async move {
                 while status_rx.next().await.is_some() {
@@ -234,66 +284,176 @@ impl ProjectIndex {
         limit: usize,
         cx: &AppContext,
     ) -> Task<Result<Vec<SearchResult>>> {
-        let (chunks_tx, chunks_rx) = channel::bounded(1024);
-        let mut worktree_scan_tasks = Vec::new();
-        for worktree_index in self.worktree_indices.values() {
-            let worktree_index = worktree_index.clone();
-            let chunks_tx = chunks_tx.clone();
-            worktree_scan_tasks.push(cx.spawn(|cx| async move {
-                let index = match worktree_index {
-                    WorktreeIndexHandle::Loading { index } => {
-                        index.clone().await.map_err(|error| anyhow!(error))?
-                    }
-                    WorktreeIndexHandle::Loaded { index } => index.clone(),
-                };
-
-                index
-                    .read_with(&cx, |index, cx| {
-                        let worktree_id = index.worktree().read(cx).id();
-                        let db_connection = index.db_connection().clone();
-                        let db = *index.embedding_index().db();
-                        cx.background_executor().spawn(async move {
-                            let txn = db_connection
-                                .read_txn()
-                                .context("failed to create read transaction")?;
-                            let db_entries = db.iter(&txn).context("failed to iterate database")?;
-                            for db_entry in db_entries {
-                                let (_key, db_embedded_file) = db_entry?;
-                                for chunk in db_embedded_file.chunks {
-                                    chunks_tx
-                                        .send((worktree_id, db_embedded_file.path.clone(), chunk))
-                                        .await?;
-                                }
-                            }
-                            anyhow::Ok(())
-                        })
-                    })?
-                    .await
-            }));
-        }
-        drop(chunks_tx);
+        self.search_with_options(queries, SearchOptions::new(limit), cx)
+    }
 
+    /// Searches the indexed chunks, ranking them by a combination of the similarity of their
+    /// embeddings to the queries' and of how well they match the queries' keywords.
+    pub fn search_with_options(
+        &self,
+        queries: Vec<String>,
+        options: SearchOptions,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let worktree_indices = self.worktree_indices.values().cloned().collect::<Vec<_>>();
         let project = self.project.clone();
         let embedding_provider = self.embedding_provider.clone();
+        let exact_search_max_chunk_count = self.exact_search_max_chunk_count;
         cx.spawn(|cx| async move {
             #[cfg(debug_assertions)]
             let embedding_query_start = std::time::Instant::now();
             log::info!("Searching for {queries:?}");
-            let queries: Vec<TextToEmbed> = queries
+            let queries: Arc<[String]> = queries.into();
+            let queries_to_embed: Vec<TextToEmbed> = queries
                 .iter()
                 .map(|s| TextToEmbed::new(s.as_str()))
                 .collect();
 
-            let query_embeddings = embedding_provider.embed(&queries[..]).await?;
+            let query_embeddings = embedding_provider.embed(&queries_to_embed[..]).await?;
             if query_embeddings.len() != queries.len() {
                 return Err(anyhow!(
                     "The number of query embeddings does not match the number of queries"
                 ));
             }
+            let query_embeddings: Arc<[Embedding]> = query_embeddings.into();
+
+            let (chunks_tx, chunks_rx) = channel::bounded(1024);
+            let mut worktree_scan_tasks = Vec::new();
+            for worktree_index in worktree_indices {
+                let chunks_tx = chunks_tx.clone();
+                let queries = queries.clone();
+                let query_embeddings = query_embeddings.clone();
+                let options = options.clone();
+                worktree_scan_tasks.push(cx.spawn(|cx| async move {
+                    let index = match worktree_index {
+                        WorktreeIndexHandle::Loading { index } => {
+                            index.clone().await.map_err(|error| anyhow!(error))?
+                        }
+                        WorktreeIndexHandle::Loaded { index } => index.clone(),
+                    };
+
+                    index
+                        .read_with(&cx, |index, cx| {
+                            let worktree = index.worktree().read(cx);
+                            let worktree_id = worktree.id();
+                            let root_name = worktree.root_name().to_string();
+                            let db_connection = index.db_connection().clone();
+                            let db = *index.embedding_index().db();
+                            let search_index = *index.embedding_index().search_index();
+                            cx.background_executor().spawn(async move {
+                                let txn = db_connection
+                                    .read_txn()
+                                    .context("failed to create read transaction")?;
+                                let is_included =
+                                    |path: &Path| options.is_included(&root_name, path);
+
+                                // A chunk's keyword score is its best score for any of the queries.
+                                let mut keyword_scores =
+                                    HashMap::<String, HashMap<u32, f32>>::default();
+                                for query in queries.iter() {
+                                    let keyword_matches = search_index.keyword_search(
+                                        &txn,
+                                        query,
+                                        options.limit * KEYWORD_CANDIDATES_PER_RESULT,
+                                        &|path_key| is_included(&path_for_db_key(path_key)),
+                                    )?;
+                                    for keyword_match in keyword_matches {
+                                        let score = keyword_scores
+                                            .entry(keyword_match.path_key)
+                                            .or_default()
+                                            .entry(keyword_match.chunk_ix)
+                                            .or_default();
+                                        *score = score.max(keyword_match.score);
+                                    }
+                                }
+
+                                if search_index.chunk_count(&txn)? <= exact_search_max_chunk_count {
+                                    let db_entries =
+                                        db.iter(&txn).context("failed to iterate database")?;
+                                    for db_entry in db_entries {
+                                        let (key, db_embedded_file) = db_entry?;
+                                        if !is_included(&db_embedded_file.path) {
+                                            continue;
+                                        }
+                                        let file_keyword_scores = keyword_scores.get(key);
+                                        for (chunk_ix, chunk) in
+                                            db_embedded_file.chunks.into_iter().enumerate()
+                                        {
+                                            let keyword_score = file_keyword_scores
+                                                .and_then(|scores| {
+                                                    scores.get(&(chunk_ix as u32)).copied()
+                                                })
+                                                .unwrap_or(0.);
+                                            chunks_tx
+                                                .send((
+                                                    worktree_id,
+                                                    db_embedded_file.path.clone(),
+                                                    chunk,
+                                                    keyword_score,
+                                                ))
+                                                .await?;
+                                        }
+                                    }
+                                } else {
+                                    let mut candidates = HashMap::<String, HashSet<u32>>::default();
+                                    for query_embedding in query_embeddings.iter() {
+                                        for (path_key, chunk_ixs) in search_index
+                                            .nearest_neighbor_candidates(&txn, query_embedding)?
+                                        {
+                                            candidates
+                                                .entry(path_key)
+                                                .or_default()
+                                                .extend(chunk_ixs);
+                                        }
+                                    }
+                                    for (path_key, scores) in &keyword_scores {
+                                        candidates
+                                            .entry(path_key.clone())
+                                            .or_default()
+                                            .extend(scores.keys().copied());
+                                    }
+
+                                    for (path_key, chunk_ixs) in candidates {
+                                        let Some(db_embedded_file) = db.get(&txn, &path_key)?
+                                        else {
+                                            continue;
+                                        };
+                                        if !is_included(&db_embedded_file.path) {
+                                            continue;
+                                        }
+                                        let file_keyword_scores = keyword_scores.get(&path_key);
+                                        for chunk_ix in chunk_ixs {
+                                            let Some(chunk) =
+                                                db_embedded_file.chunks.get(chunk_ix as usize)
+                                            else {
+                                                continue;
+                                            };
+                                            let keyword_score = file_keyword_scores
+                                                .and_then(|scores| scores.get(&chunk_ix).copied())
+                                                .unwrap_or(0.);
+                                            chunks_tx
+                                                .send((
+                                                    worktree_id,
+                                                    db_embedded_file.path.clone(),
+                                                    chunk.clone(),
+                                                    keyword_score,
+                                                ))
+                                                .await?;
+                                        }
+                                    }
+                                }
+                                anyhow::Ok(())
+                            })
+                        })?
+                        .await
+                }));
+            }
+            drop(chunks_tx);
 
+            let limit = options.limit;
             let mut results_by_worker = Vec::new();
             for _ in 0..cx.background_executor().num_cpus() {
-                results_by_worker.push(Vec::<WorktreeSearchResult>::new());
+                results_by_worker.push(WorkerSearchResults::default());
             }
 
             #[cfg(debug_assertions)]
@@ -302,28 +462,36 @@ impl ProjectIndex {
                 .scoped(|cx| {
                     for results in results_by_worker.iter_mut() {
                         cx.spawn(async {
-                            while let Ok((worktree_id, path, chunk)) = chunks_rx.recv().await {
+                            while let Ok((worktree_id, path, chunk, keyword_score)) =
+                                chunks_rx.recv().await
+                            {
                                 let (score, query_index) =
                                     chunk.embedding.similarity(&query_embeddings);
+                                let result = WorktreeSearchResult {
+                                    worktree_id,
+                                    path,
+                                    range: chunk.chunk.range,
+                                    query_index,
+                                    score,
+                                    keyword_score,
+                                };
+
+                                // Keyword matches are few, so keep all of them. Their final
+                                // score depends on the best keyword score of all worktrees.
+                                if keyword_score > 0. {
+                                    results.keyword_matches.push(result);
+                                    continue;
+                                }
 
-                                let ix = match results.binary_search_by(|probe| {
+                                let ix = match results.vector_matches.binary_search_by(|probe| {
                                     score.partial_cmp(&probe.score).unwrap_or(Ordering::Equal)
                                 }) {
                                     Ok(ix) | Err(ix) => ix,
                                 };
                                 if ix < limit {
-                                    results.insert(
-                                        ix,
-                                        WorktreeSearchResult {
-                                            worktree_id,
-                                            path: path.clone(),
-                                            range: chunk.chunk.range.clone(),
-                                            query_index,
-                                            score,
-                                        },
-                                    );
-                                    if results.len() > limit {
-                                        results.pop();
+                                    results.vector_matches.insert(ix, result);
+                                    if results.vector_matches.len() > limit {
+                                        results.vector_matches.pop();
                                     }
                                 }
                             }
@@ -336,18 +504,41 @@ impl ProjectIndex {
                 scan_task.log_err();
             }
 
+            let max_keyword_score = results_by_worker
+                .iter()
+                .flat_map(|results| &results.keyword_matches)
+                .map(|result| result.keyword_score)
+                .fold(0., f32::max);
+            let keyword_weight = if max_keyword_score > 0. {
+                options.keyword_weight.clamp(0., 1.)
+            } else {
+                0.
+            };
+
             project.read_with(&cx, |project, cx| {
                 let mut search_results = Vec::with_capacity(results_by_worker.len() * limit);
                 for worker_results in results_by_worker {
-                    search_results.extend(worker_results.into_iter().filter_map(|result| {
-                        Some(SearchResult {
-                            worktree: project.worktree_for_id(result.worktree_id, cx)?,
-                            path: result.path,
-                            range: result.range,
-                            score: result.score,
-                            query_index: result.query_index,
-                        })
-                    }));
+                    search_results.extend(
+                        worker_results
+                            .vector_matches
+                            .into_iter()
+                            .chain(worker_results.keyword_matches)
+                            .filter_map(|result| {
+                                let keyword_score = if max_keyword_score > 0. {
+                                    result.keyword_score / max_keyword_score
+                                } else {
+                                    0.
+                                };
+                                Some(SearchResult {
+                                    worktree: project.worktree_for_id(result.worktree_id, cx)?,
+                                    path: result.path,
+                                    range: result.range,
+                                    score: (1. - keyword_weight) * result.score
+                                        + keyword_weight * keyword_score,
+                                    query_index: result.query_index,
+                                })
+                            }),
+                    );
                 }
                 search_results.sort_unstable_by(|a, b| {
                     b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
@@ -371,6 +562,11 @@ impl ProjectIndex {
         })
     }
 
+    #[cfg(test)]
+    pub fn set_exact_search_max_chunk_count(&mut self, count: u64) {
+        self.exact_search_max_chunk_count = count;
+    }
+
     #[cfg(test)]
     pub fn path_count(&self, cx: &AppContext) -> Result<u64> {
         let mut result = 0;
@@ -0,0 +1,614 @@
+//! Keyword and approximate nearest neighbor indices over the embedded chunks of a worktree.
+//!
+//! Both indices are stored in the same LMDB environment as the embeddings, so they're updated
+//! in the same transactions as the embeddings they refer to.
+//!
+//! - The keyword index is an inverted index from terms to the chunks containing them, which is
+//!   used to rank chunks with [BM25](https://en.wikipedia.org/wiki/Okapi_BM25).
+//! - The vector index hashes embeddings with random hyperplanes
+//!   ([locality-sensitive hashing](https://en.wikipedia.org/wiki/Locality-sensitive_hashing)),
+//!   so that similar embeddings end up in the same buckets. The hyperplanes are derived from
+//!   fixed seeds, so they don't need to be stored.
+//!
+//! Postings and buckets are keyed by a fixed-size hash of the file's path key rather than the path
+//! key itself, so that appending a term or bucket to a long path doesn't exceed LMDB's key size
+//! limit.
+
+use crate::embedding::Embedding;
+use crate::embedding_index::EmbeddedChunk;
+use anyhow::Result;
+use collections::{Bound, HashMap, HashSet};
+use heed::types::{SerdeBincode, Str};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// The number of hash tables of the vector index. More tables find more neighbors, but make the
+/// index bigger.
+const HASH_TABLE_COUNT: usize = 8;
+/// The number of hyperplanes, and thus bits, of each hash table's hashes.
+const HASH_BIT_COUNT: usize = 16;
+
+/// Controls how quickly the score of a term saturates with its frequency in a chunk.
+const BM25_K1: f32 = 1.2;
+/// Controls how much a chunk's length normalizes the score of its terms.
+const BM25_B: f32 = 0.75;
+
+const MIN_TERM_LENGTH: usize = 2;
+const MAX_TERM_LENGTH: usize = 64;
+
+const STATS_KEY: &str = "stats";
+
+#[derive(Clone, Copy)]
+pub struct SearchIndex {
+    files: heed::Database<Str, SerdeBincode<IndexedFile>>,
+    postings: heed::Database<Str, SerdeBincode<Vec<Posting>>>,
+    buckets: heed::Database<Str, SerdeBincode<Vec<u32>>>,
+    /// Maps the hashes that postings and buckets are keyed by back to path keys.
+    path_keys: heed::Database<Str, Str>,
+    stats: heed::Database<Str, SerdeBincode<SearchIndexStats>>,
+}
+
+/// What was indexed for a file, so that it can be removed from the index again.
+#[derive(Debug, Serialize, Deserialize)]
+struct IndexedFile {
+    terms: Vec<String>,
+    buckets: Vec<String>,
+    chunk_lengths: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Posting {
+    chunk_ix: u32,
+    term_frequency: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SearchIndexStats {
+    chunk_count: u64,
+    total_length: u64,
+}
+
+/// The terms of a chunk's text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChunkTerms {
+    pub term_frequencies: HashMap<String, u32>,
+    pub length: u32,
+}
+
+impl ChunkTerms {
+    pub fn new(text: &str) -> Self {
+        let mut terms = Self::default();
+        for term in tokenize(text) {
+            *terms.term_frequencies.entry(term).or_default() += 1;
+            terms.length += 1;
+        }
+        terms
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeywordMatch {
+    pub path_key: String,
+    pub chunk_ix: u32,
+    pub score: f32,
+}
+
+impl SearchIndex {
+    pub fn create(
+        db_connection: &heed::Env,
+        txn: &mut heed::RwTxn,
+        name: &str,
+    ) -> heed::Result<Self> {
+        // Prepend something that wouldn't be found at the beginning of an absolute path, so we
+        // don't get db key namespace conflicts with embeddings, which use the abs path as a key.
+        Ok(Self {
+            files: db_connection.create_database(txn, Some(&format!("keywords-{name}")))?,
+            postings: db_connection.create_database(txn, Some(&format!("postings-{name}")))?,
+            buckets: db_connection.create_database(txn, Some(&format!("buckets-{name}")))?,
+            path_keys: db_connection.create_database(txn, Some(&format!("path-keys-{name}")))?,
+            stats: db_connection.create_database(txn, Some(&format!("search-stats-{name}")))?,
+        })
+    }
+
+    pub fn contains_file(&self, txn: &heed::RoTxn, path_key: &str) -> Result<bool> {
+        Ok(self.files.get(txn, path_key)?.is_some())
+    }
+
+    pub fn chunk_count(&self, txn: &heed::RoTxn) -> Result<u64> {
+        Ok(self
+            .stats
+            .get(txn, STATS_KEY)?
+            .unwrap_or_default()
+            .chunk_count)
+    }
+
+    /// Indexes the chunks of a file, replacing what was indexed for it before.
+    pub fn insert_file(
+        &self,
+        txn: &mut heed::RwTxn,
+        path_key: &str,
+        chunks: &[EmbeddedChunk],
+        chunk_terms: &[ChunkTerms],
+    ) -> Result<()> {
+        debug_assert_eq!(chunks.len(), chunk_terms.len());
+        self.remove_file(txn, path_key)?;
+        let path_hash = path_hash(path_key);
+        self.path_keys.put(txn, &path_hash, path_key)?;
+
+        let mut postings = HashMap::<&str, Vec<Posting>>::default();
+        for (chunk_ix, terms) in chunk_terms.iter().enumerate() {
+            for (term, term_frequency) in &terms.term_frequencies {
+                postings.entry(term.as_str()).or_default().push(Posting {
+                    chunk_ix: chunk_ix as u32,
+                    term_frequency: *term_frequency,
+                });
+            }
+        }
+        for (term, postings) in &postings {
+            self.postings
+                .put(txn, &posting_key(term, &path_hash), postings)?;
+        }
+
+        let mut buckets = HashMap::<String, Vec<u32>>::default();
+        for (chunk_ix, chunk) in chunks.iter().enumerate() {
+            for (table, hash) in embedding_hashes(&chunk.embedding).into_iter().enumerate() {
+                buckets
+                    .entry(bucket_prefix(table, hash))
+                    .or_default()
+                    .push(chunk_ix as u32);
+            }
+        }
+        for (bucket, chunk_ixs) in &buckets {
+            self.buckets
+                .put(txn, &format!("{bucket}{path_hash}"), chunk_ixs)?;
+        }
+
+        let chunk_lengths = chunk_terms
+            .iter()
+            .map(|terms| terms.length)
+            .collect::<Vec<_>>();
+        let mut stats = self.stats.get(txn, STATS_KEY)?.unwrap_or_default();
+        stats.chunk_count += chunk_lengths.len() as u64;
+        stats.total_length += chunk_lengths
+            .iter()
+            .map(|length| *length as u64)
+            .sum::<u64>();
+        self.stats.put(txn, STATS_KEY, &stats)?;
+
+        self.files.put(
+            txn,
+            path_key,
+            &IndexedFile {
+                terms: postings.into_keys().map(ToString::to_string).collect(),
+                buckets: buckets.into_keys().collect(),
+                chunk_lengths,
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_file(&self, txn: &mut heed::RwTxn, path_key: &str) -> Result<()> {
+        let Some(file) = self.files.get(txn, path_key)? else {
+            return Ok(());
+        };
+
+        let path_hash = path_hash(path_key);
+        for term in &file.terms {
+            self.postings.delete(txn, &posting_key(term, &path_hash))?;
+        }
+        for bucket in &file.buckets {
+            self.buckets.delete(txn, &format!("{bucket}{path_hash}"))?;
+        }
+        self.path_keys.delete(txn, &path_hash)?;
+
+        let mut stats = self.stats.get(txn, STATS_KEY)?.unwrap_or_default();
+        stats.chunk_count = stats
+            .chunk_count
+            .saturating_sub(file.chunk_lengths.len() as u64);
+        stats.total_length = stats
+            .total_length
+            .saturating_sub(file.chunk_lengths.iter().map(|length| *length as u64).sum());
+        self.stats.put(txn, STATS_KEY, &stats)?;
+
+        self.files.delete(txn, path_key)?;
+        Ok(())
+    }
+
+    pub fn remove_range(
+        &self,
+        txn: &mut heed::RwTxn,
+        range: (Bound<&str>, Bound<&str>),
+    ) -> Result<()> {
+        let path_keys = self
+            .files
+            .range(txn, &range)?
+            .map(|entry| Ok(entry?.0.to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        for path_key in path_keys {
+            self.remove_file(txn, &path_key)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the chunks that best match the terms of the given query, ranked by their BM25 score.
+    /// Only the chunks of files whose keys pass the filter are considered.
+    pub fn keyword_search(
+        &self,
+        txn: &heed::RoTxn,
+        query: &str,
+        limit: usize,
+        filter: &dyn Fn(&str) -> bool,
+    ) -> Result<Vec<KeywordMatch>> {
+        let stats = self.stats.get(txn, STATS_KEY)?.unwrap_or_default();
+        if stats.chunk_count == 0 {
+            return Ok(Vec::new());
+        }
+        let chunk_count = stats.chunk_count as f32;
+        let average_length = (stats.total_length as f32 / chunk_count).max(1.);
+
+        let terms = tokenize(query).collect::<HashSet<_>>();
+        let mut chunk_lengths = HashMap::<String, Option<Vec<u32>>>::default();
+        let mut scores = HashMap::<(String, u32), f32>::default();
+        for term in terms {
+            let mut postings = Vec::new();
+            let mut document_frequency = 0;
+            for entry in self.postings.prefix_iter(txn, &posting_key(&term, ""))? {
+                let (key, term_postings) = entry?;
+                document_frequency += term_postings.len();
+                let Some(path_key) = self.path_keys.get(txn, &key[term.len() + 1..])? else {
+                    continue;
+                };
+                if filter(path_key) {
+                    postings.push((path_key.to_string(), term_postings));
+                }
+            }
+
+            let document_frequency = document_frequency as f32;
+            let inverse_document_frequency =
+                ((chunk_count - document_frequency + 0.5) / (document_frequency + 0.5) + 1.).ln();
+            for (path_key, term_postings) in postings {
+                if !chunk_lengths.contains_key(&path_key) {
+                    let lengths = self
+                        .files
+                        .get(txn, &path_key)?
+                        .map(|file| file.chunk_lengths);
+                    chunk_lengths.insert(path_key.clone(), lengths);
+                }
+                let Some(lengths) = &chunk_lengths[&path_key] else {
+                    continue;
+                };
+
+                for posting in term_postings {
+                    let length = lengths
+                        .get(posting.chunk_ix as usize)
+                        .copied()
+                        .unwrap_or_default() as f32;
+                    let term_frequency = posting.term_frequency as f32;
+                    let score = inverse_document_frequency * term_frequency * (BM25_K1 + 1.)
+                        / (term_frequency
+                            + BM25_K1 * (1. - BM25_B + BM25_B * length / average_length));
+                    *scores
+                        .entry((path_key.clone(), posting.chunk_ix))
+                        .or_default() += score;
+                }
+            }
+        }
+
+        let mut matches = scores
+            .into_iter()
+            .map(|((path_key, chunk_ix), score)| KeywordMatch {
+                path_key,
+                chunk_ix,
+                score,
+            })
+            .collect::<Vec<_>>();
+        matches.sort_unstable_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.path_key.cmp(&b.path_key))
+                .then_with(|| a.chunk_ix.cmp(&b.chunk_ix))
+        });
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    /// Returns the chunks whose embeddings are likely to be similar to the given one, by file key.
+    ///
+    /// Besides the buckets of the embedding itself, this probes the buckets whose hashes differ by
+    /// one bit, which finds more neighbors without needing more hash tables.
+    pub fn nearest_neighbor_candidates(
+        &self,
+        txn: &heed::RoTxn,
+        embedding: &Embedding,
+    ) -> Result<HashMap<String, HashSet<u32>>> {
+        let mut candidates = HashMap::<String, HashSet<u32>>::default();
+        for (table, hash) in embedding_hashes(embedding).into_iter().enumerate() {
+            let probes = iter_probes(hash).map(|probed_hash| bucket_prefix(table, probed_hash));
+            for prefix in probes {
+                for entry in self.buckets.prefix_iter(txn, &prefix)? {
+                    let (key, chunk_ixs) = entry?;
+                    let Some(path_key) = self.path_keys.get(txn, &key[prefix.len()..])? else {
+                        continue;
+                    };
+                    candidates
+                        .entry(path_key.to_string())
+                        .or_default()
+                        .extend(chunk_ixs);
+                }
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+fn iter_probes(hash: u16) -> impl Iterator<Item = u16> {
+    std::iter::once(hash).chain((0..HASH_BIT_COUNT).map(move |bit| hash ^ (1 << bit)))
+}
+
+fn posting_key(term: &str, path_hash: &str) -> String {
+    format!("{term}\0{path_hash}")
+}
+
+/// Returns the 128-bit hash, in hex, that postings and buckets key a file's entries by.
+fn path_hash(path_key: &str) -> String {
+    blake3::hash(path_key.as_bytes()).to_hex()[..32].to_string()
+}
+
+fn bucket_prefix(table: usize, hash: u16) -> String {
+    format!("{table}:{hash:04x}\0")
+}
+
+/// Hashes the embedding once per hash table, with one bit per hyperplane telling which side of
+/// the hyperplane the embedding is on.
+fn embedding_hashes(embedding: &Embedding) -> [u16; HASH_TABLE_COUNT] {
+    let values = embedding.values();
+    let mut hashes = [0; HASH_TABLE_COUNT];
+    for (table, hash) in hashes.iter_mut().enumerate() {
+        for bit in 0..HASH_BIT_COUNT {
+            let mut rng = SplitMix64::new(((table * HASH_BIT_COUNT + bit) as u64) << 32);
+            let dot_product: f32 = values
+                .iter()
+                .map(|value| value * rng.next_component())
+                .sum();
+            if dot_product > 0. {
+                *hash |= 1 << bit;
+            }
+        }
+    }
+    hashes
+}
+
+/// A small deterministic random number generator to derive the hyperplanes from their seeds.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Returns a value uniformly distributed between -1 and 1.
+    fn next_component(&mut self) -> f32 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 23) as f32 - 1.
+    }
+}
+
+/// Splits text into lowercase terms. Identifiers are indexed as a whole, as well as by their
+/// `snake_case` and `camelCase` words, so that searching for a word finds identifiers using it.
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .flat_map(|identifier| {
+            let words = identifier_words(identifier);
+            let whole = (words.len() > 1).then(|| identifier.trim_matches('_').to_lowercase());
+            whole
+                .into_iter()
+                .chain(words.into_iter().map(|word| word.to_lowercase()))
+        })
+        .filter(|term| (MIN_TERM_LENGTH..=MAX_TERM_LENGTH).contains(&term.chars().count()))
+}
+
+fn identifier_words(identifier: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for part in identifier.split('_').filter(|part| !part.is_empty()) {
+        let mut word_start = 0;
+        let mut prev: Option<char> = None;
+        let mut chars = part.char_indices().peekable();
+        while let Some((ix, c)) = chars.next() {
+            if let Some(prev) = prev {
+                let next_is_lowercase = chars.peek().map_or(false, |(_, next)| next.is_lowercase());
+                let is_boundary = c.is_uppercase()
+                    && (prev.is_lowercase()
+                        || prev.is_numeric()
+                        || (prev.is_uppercase() && next_is_lowercase));
+                if is_boundary {
+                    words.push(&part[word_start..ix]);
+                    word_start = ix;
+                }
+            }
+            prev = Some(c);
+        }
+        words.push(&part[word_start..]);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::Chunk;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fn parseHTTPResponse(raw_input: &str) -> a").collect::<Vec<_>>(),
+            [
+                "fn",
+                "parsehttpresponse",
+                "parse",
+                "http",
+                "response",
+                "raw_input",
+                "raw",
+                "input",
+                "str"
+            ]
+        );
+        assert_eq!(
+            ChunkTerms::new("the cat saw the dog").term_frequencies,
+            HashMap::from_iter([
+                ("the".to_string(), 2),
+                ("cat".to_string(), 1),
+                ("saw".to_string(), 1),
+                ("dog".to_string(), 1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_embedding_hashes() {
+        let embedding = Embedding::new(vec![1., 0.5, -0.25, 0.1]);
+        let similar = Embedding::new(vec![1., 0.5, -0.2, 0.1]);
+        let opposite = Embedding::new(vec![-1., -0.5, 0.25, -0.1]);
+
+        let hashes = embedding_hashes(&embedding);
+        assert_eq!(hashes, embedding_hashes(&embedding));
+        let matching_tables = |other: &Embedding| {
+            hashes
+                .iter()
+                .zip(embedding_hashes(other))
+                .filter(|(a, b)| **a == *b)
+                .count()
+        };
+        assert!(matching_tables(&similar) > HASH_TABLE_COUNT / 2);
+        assert_eq!(matching_tables(&opposite), 0);
+    }
+
+    #[test]
+    fn test_search_index() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_connection = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(16 * 1024 * 1024)
+                .max_dbs(8)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = db_connection.write_txn().unwrap();
+        let index = SearchIndex::create(&db_connection, &mut txn, "/project").unwrap();
+
+        let files = [
+            (
+                "src\0parser.rs",
+                vec![
+                    ("fn parse_token(input: &str) -> Token", vec![1., 0., 0.]),
+                    ("fn skip_whitespace(input: &str)", vec![0., 1., 0.]),
+                ],
+            ),
+            (
+                "src\0lexer.rs",
+                vec![("struct Lexer { tokens: Vec<Token> }", vec![0., 0., 1.])],
+            ),
+            (
+                "docs\0tokens.md",
+                vec![("Tokens are parsed by the token parser.", vec![0.9, 0.1, 0.])],
+            ),
+        ];
+        for (path_key, chunks) in &files {
+            let chunk_terms = chunks
+                .iter()
+                .map(|(text, _)| ChunkTerms::new(text))
+                .collect::<Vec<_>>();
+            let chunks = chunks
+                .iter()
+                .map(|(text, embedding)| EmbeddedChunk {
+                    chunk: Chunk {
+                        range: 0..text.len(),
+                        digest: Default::default(),
+                    },
+                    embedding: Embedding::new(embedding.clone()),
+                })
+                .collect::<Vec<_>>();
+            index
+                .insert_file(&mut txn, path_key, &chunks, &chunk_terms)
+                .unwrap();
+        }
+        assert_eq!(index.chunk_count(&txn).unwrap(), 4);
+
+        let results = index
+            .keyword_search(&txn, "parse token", 10, &|_| true)
+            .unwrap()
+            .into_iter()
+            .map(|keyword_match| (keyword_match.path_key, keyword_match.chunk_ix))
+            .collect::<Vec<_>>();
+        assert_eq!(results[0], ("src\0parser.rs".to_string(), 0));
+        assert!(results.contains(&("docs\0tokens.md".to_string(), 0)));
+        assert!(!results.contains(&("src\0parser.rs".to_string(), 1)));
+
+        let results = index
+            .keyword_search(&txn, "parse token", 10, &|path_key| {
+                !path_key.starts_with("src\0")
+            })
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path_key, "docs\0tokens.md");
+
+        let candidates = index
+            .nearest_neighbor_candidates(&txn, &Embedding::new(vec![1., 0.05, 0.]))
+            .unwrap();
+        assert!(candidates["src\0parser.rs"].contains(&0));
+
+        // Re-indexing a file replaces its previous entries.
+        index
+            .insert_file(&mut txn, "src\0lexer.rs", &[], &[])
+            .unwrap();
+        assert_eq!(index.chunk_count(&txn).unwrap(), 3);
+        assert!(index
+            .keyword_search(&txn, "lexer", 10, &|_| true)
+            .unwrap()
+            .is_empty());
+
+        index
+            .remove_range(&mut txn, (Bound::Included("src"), Bound::Unbounded))
+            .unwrap();
+        assert_eq!(index.chunk_count(&txn).unwrap(), 1);
+        assert!(!index.contains_file(&txn, "src\0parser.rs").unwrap());
+        assert!(index.contains_file(&txn, "docs\0tokens.md").unwrap());
+        assert!(index
+            .nearest_neighbor_candidates(&txn, &Embedding::new(vec![1., 0.05, 0.]))
+            .unwrap()
+            .get("src\0parser.rs")
+            .is_none());
+
+        // Appending a long term to a long path key would exceed LMDB's maximum key size of 511
+        // bytes, but postings are keyed by the path key's hash.
+        let long_path_key = format!("{}\0deep.rs", "nested/".repeat(69));
+        index
+            .insert_file(
+                &mut txn,
+                &long_path_key,
+                &[EmbeddedChunk {
+                    chunk: Chunk {
+                        range: 0..10,
+                        digest: Default::default(),
+                    },
+                    embedding: Embedding::new(vec![0., 1., 0.]),
+                }],
+                &[ChunkTerms::new("fn deeply_nested_function()")],
+            )
+            .unwrap();
+        let results = index
+            .keyword_search(&txn, "deeply_nested_function", 10, &|_| true)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path_key, long_path_key);
+        assert!(index
+            .nearest_neighbor_candidates(&txn, &Embedding::new(vec![0., 1., 0.]))
+            .unwrap()
+            .contains_key(&long_path_key));
+    }
+}
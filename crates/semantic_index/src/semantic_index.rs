@@ -4,6 +4,7 @@ mod embedding_index;
 mod indexing;
 mod project_index;
 mod project_index_debug_view;
+mod search_index;
 mod summary_backlog;
 mod summary_index;
 mod worktree_index;
@@ -24,7 +25,7 @@ use util::ResultExt as _;
 use workspace::Workspace;
 
 pub use embedding::*;
-pub use project_index::{LoadedSearchResult, ProjectIndex, SearchResult, Status};
+pub use project_index::{LoadedSearchResult, ProjectIndex, SearchOptions, SearchResult, Status};
 pub use project_index_debug_view::ProjectIndexDebugView;
 pub use summary_index::FileSummary;
 
@@ -49,7 +50,7 @@ impl SemanticDb {
                 unsafe {
                     heed::EnvOpenOptions::new()
                         .map_size(1024 * 1024 * 1024)
-                        .max_dbs(3000)
+                        .max_dbs(8000)
                         .open(db_path)
                 }
             })
@@ -280,6 +281,7 @@ mod tests {
     use settings::SettingsStore;
     use smol::{channel, stream::StreamExt};
     use std::{future, path::Path, sync::Arc};
+    use util::paths::PathMatcher;
 
     fn init_test(cx: &mut TestAppContext) {
         env_logger::try_init().ok();
@@ -440,6 +442,150 @@ mod tests {
         assert!(content.contains("garbage in, garbage out"));
     }
 
+    #[gpui::test]
+    async fn test_search_with_options(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        init_test(cx);
+        cx.update(|cx| {
+            // This functionality is staff-flagged.
+            cx.update_flags(true, vec![]);
+        });
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        // The query is embedded like the text that is close to it, the opposite of the text that
+        // is far from it, and the text that only matches its keyword is in between.
+        let mut semantic_index = SemanticDb::new(
+            temp_dir.path().into(),
+            Arc::new(TestEmbeddingProvider::new(16, |text| {
+                let embedding = if text == "zebra" || text.contains("close") {
+                    vec![1., 0.2]
+                } else if text.contains("far") {
+                    vec![-1., -0.2]
+                } else {
+                    vec![0., 1.]
+                };
+                Ok(Embedding::new(embedding))
+            })),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                "a": {
+                    "close.txt": "close to the query",
+                    "keyword.txt": "a zebra crossing",
+                },
+                "b": {
+                    "far.txt": "far from the query",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/project".as_ref()], cx).await;
+        let project_index =
+            cx.update(|cx| semantic_index.create_project_index(project.clone(), cx));
+        cx.run_until_parked();
+        while cx
+            .update(|cx| semantic_index.remaining_summaries(&project.downgrade(), cx))
+            .unwrap()
+            > 0
+        {
+            cx.run_until_parked();
+        }
+
+        let search = |options: SearchOptions, cx: &mut TestAppContext| {
+            let results = cx.update(|cx| {
+                project_index
+                    .read(cx)
+                    .search_with_options(vec!["zebra".into()], options, cx)
+            });
+            async move {
+                results
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|result| result.path.to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            }
+        };
+        let matcher = |globs: &[&str]| {
+            PathMatcher::new(
+                &globs
+                    .iter()
+                    .map(|glob| glob.to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap()
+        };
+
+        // The similarity of the embeddings weighs the most, then the keyword match.
+        assert_eq!(
+            search(SearchOptions::new(10), cx).await,
+            ["a/close.txt", "a/keyword.txt", "b/far.txt"]
+        );
+        assert_eq!(
+            search(
+                SearchOptions {
+                    keyword_weight: 1.,
+                    ..SearchOptions::new(10)
+                },
+                cx
+            )
+            .await[0],
+            "a/keyword.txt"
+        );
+
+        // Paths are filtered both relative to the worktree and with the worktree's name.
+        assert_eq!(
+            search(
+                SearchOptions {
+                    files_to_include: matcher(&["a/**"]),
+                    ..SearchOptions::new(10)
+                },
+                cx
+            )
+            .await,
+            ["a/close.txt", "a/keyword.txt"]
+        );
+        assert_eq!(
+            search(
+                SearchOptions {
+                    files_to_exclude: matcher(&["project/a/keyword.txt", "b/*"]),
+                    ..SearchOptions::new(10)
+                },
+                cx
+            )
+            .await,
+            ["a/close.txt"]
+        );
+
+        // Above the chunk count of the exact search, only the chunks found through the nearest
+        // neighbor index or through their keywords are compared to the query.
+        project_index.update(cx, |project_index, _| {
+            project_index.set_exact_search_max_chunk_count(0)
+        });
+        assert_eq!(
+            search(SearchOptions::new(10), cx).await,
+            ["a/close.txt", "a/keyword.txt"]
+        );
+        assert_eq!(
+            search(
+                SearchOptions {
+                    files_to_exclude: matcher(&["a/close.txt"]),
+                    ..SearchOptions::new(10)
+                },
+                cx
+            )
+            .await,
+            ["a/keyword.txt"]
+        );
+    }
+
     #[gpui::test]
     async fn test_embed_files(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
@@ -498,7 +644,7 @@ mod tests {
 
         let mut embedded_files_rx = embed_files_task.files;
         let mut embedded_files = Vec::new();
-        while let Some((embedded_file, _)) = embedded_files_rx.next().await {
+        while let Some((embedded_file, _, _)) = embedded_files_rx.next().await {
             embedded_files.push(embedded_file);
         }
 
@@ -0,0 +1,629 @@
+use std::cmp::Reverse;
+
+use anyhow::{anyhow, Result};
+use collections::{HashMap, HashSet};
+
+/// A Hunspell-compatible dictionary, parsed from an `.aff` and a `.dic` file.
+///
+/// Prefixes, suffixes and their cross products are supported, along with the
+/// `TRY`, `REP`, `AF`, `FORBIDDENWORD`, `NOSUGGEST` and `NEEDAFFIX`
+/// directives. Compounding and morphological analysis are not.
+pub struct Dictionary {
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    forbidden_flag: Option<Flag>,
+    no_suggest_flag: Option<Flag>,
+    need_affix_flag: Option<Flag>,
+}
+
+type Flag = u32;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FlagFormat {
+    Single,
+    Long,
+    Numeric,
+}
+
+struct Affix {
+    flag: Flag,
+    cross_product: bool,
+    strip: String,
+    add: String,
+    condition: Vec<ConditionChar>,
+}
+
+enum ConditionChar {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Case {
+    Lower,
+    Capitalized,
+    Upper,
+    Mixed,
+}
+
+impl Dictionary {
+    /// Parses a dictionary from the contents of its `.aff` and `.dic` files.
+    pub fn parse(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        // The encoding is declared inside the affix file, and the `SET` line is ASCII.
+        let encoding = decode(aff, "ISO8859-1")
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SET "))
+            .map(|encoding| encoding.trim().to_uppercase())
+            .unwrap_or_else(|| "ISO8859-1".to_string());
+        let aff = decode(aff, &encoding);
+        let dic = decode(dic, &encoding);
+
+        let mut dictionary = Self {
+            words: HashMap::default(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            try_chars: Vec::new(),
+            replacements: Vec::new(),
+            forbidden_flag: None,
+            no_suggest_flag: None,
+            need_affix_flag: None,
+        };
+        let mut flag_format = FlagFormat::Single;
+        let mut flag_aliases = Vec::<Vec<Flag>>::new();
+        let mut cross_products = HashMap::<(bool, Flag), bool>::default();
+
+        for line in aff.lines() {
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            match parts.as_slice() {
+                ["FLAG", "long", ..] => flag_format = FlagFormat::Long,
+                ["FLAG", "num", ..] => flag_format = FlagFormat::Numeric,
+                ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+                ["FORBIDDENWORD", flag, ..] => {
+                    dictionary.forbidden_flag = parse_flags(flag, flag_format).first().copied()
+                }
+                ["NOSUGGEST", flag, ..] => {
+                    dictionary.no_suggest_flag = parse_flags(flag, flag_format).first().copied()
+                }
+                ["NEEDAFFIX", flag, ..] => {
+                    dictionary.need_affix_flag = parse_flags(flag, flag_format).first().copied()
+                }
+                ["AF", count] if count.parse::<usize>().is_ok() => {}
+                ["AF", flags, ..] => flag_aliases.push(parse_flags(flags, flag_format)),
+                ["REP", count] if count.parse::<usize>().is_ok() => {}
+                ["REP", from, to, ..] => dictionary
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                [kind @ ("PFX" | "SFX"), flag, cross_product, count]
+                    if count.parse::<usize>().is_ok() =>
+                {
+                    if let Some(flag) = parse_flags(flag, flag_format).first() {
+                        cross_products.insert((*kind == "PFX", *flag), *cross_product == "Y");
+                    }
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let is_prefix = *kind == "PFX";
+                    let Some(flag) = parse_flags(flag, flag_format).first().copied() else {
+                        continue;
+                    };
+                    // Continuation classes after the affix text are not supported.
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag,
+                        cross_product: cross_products
+                            .get(&(is_prefix, flag))
+                            .copied()
+                            .unwrap_or(false),
+                        strip: if *strip == "0" { "" } else { strip }.to_string(),
+                        add: if add == "0" { "" } else { add }.to_string(),
+                        condition: parse_condition(rest.first().copied().unwrap_or(".")),
+                    };
+                    if is_prefix {
+                        dictionary.prefixes.push(affix);
+                    } else {
+                        dictionary.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut lines = dic.lines();
+        let first_line = lines
+            .next()
+            .ok_or_else(|| anyhow!("empty dictionary file"))?;
+        if first_line.trim().parse::<usize>().is_err() {
+            return Err(anyhow!("dictionary file does not start with a word count"));
+        }
+        for line in lines {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = split_entry(entry);
+            let flags = match flags {
+                Some(flags) if !flag_aliases.is_empty() => flags
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| flag_aliases.get(index.checked_sub(1)?))
+                    .cloned()
+                    .unwrap_or_default(),
+                Some(flags) => parse_flags(flags, flag_format),
+                None => Vec::new(),
+            };
+            dictionary.words.entry(word).or_default().push(flags);
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Returns whether the given word is spelled correctly.
+    ///
+    /// Capitalized and uppercase words are also accepted when their
+    /// lowercase form is in the dictionary.
+    pub fn check(&self, word: &str) -> bool {
+        if self.check_exact(word) {
+            return true;
+        }
+        match Case::of(word) {
+            Case::Capitalized => self.check_exact(&word.to_lowercase()),
+            Case::Upper => {
+                let lowercase = word.to_lowercase();
+                self.check_exact(&lowercase) || self.check_exact(&capitalize(&lowercase))
+            }
+            Case::Lower | Case::Mixed => false,
+        }
+    }
+
+    /// Returns up to `limit` correctly spelled words that are close to the
+    /// given one, from the most to the least likely.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let case = Case::of(word);
+        let lowercase = word.to_lowercase();
+        let mut seen = HashSet::default();
+        let mut suggestions = Vec::new();
+        let mut push = |candidate: String, suggestions: &mut Vec<String>| {
+            if suggestions.len() < limit
+                && candidate != word
+                && seen.insert(candidate.clone())
+                && candidate.split(' ').all(|part| self.check_suggestion(part))
+            {
+                suggestions.push(candidate);
+            }
+        };
+
+        if case != Case::Capitalized {
+            push(capitalize(&lowercase), &mut suggestions);
+        }
+        for (from, to) in &self.replacements {
+            for (ix, _) in lowercase.match_indices(from.as_str()) {
+                let mut candidate = lowercase.clone();
+                candidate.replace_range(ix..ix + from.len(), to);
+                push(candidate, &mut suggestions);
+            }
+        }
+        for candidate in self.edits(&lowercase) {
+            push(candidate, &mut suggestions);
+        }
+        for candidate in self.similar_words(&lowercase) {
+            push(candidate, &mut suggestions);
+        }
+
+        suggestions
+            .into_iter()
+            .map(|suggestion| match case {
+                Case::Capitalized if Case::of(&suggestion) == Case::Lower => {
+                    capitalize(&suggestion)
+                }
+                Case::Upper => suggestion.to_uppercase(),
+                _ => suggestion,
+            })
+            .collect()
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(entries) = self.words.get(word) {
+            if entries
+                .iter()
+                .any(|flags| has_flag(flags, self.forbidden_flag))
+            {
+                return false;
+            }
+            if entries
+                .iter()
+                .any(|flags| !has_flag(flags, self.need_affix_flag))
+            {
+                return true;
+            }
+        }
+        self.check_affixed(word)
+    }
+
+    fn check_suggestion(&self, word: &str) -> bool {
+        self.check(word)
+            && self.words.get(word).map_or(true, |entries| {
+                entries
+                    .iter()
+                    .all(|flags| !has_flag(flags, self.no_suggest_flag))
+            })
+    }
+
+    fn check_affixed(&self, word: &str) -> bool {
+        for suffix in &self.suffixes {
+            let Some(stem) = suffix.remove_suffix(word) else {
+                continue;
+            };
+            if self.has_stem(&stem, &[suffix.flag]) {
+                return true;
+            }
+            if suffix.cross_product {
+                for prefix in self.prefixes.iter().filter(|prefix| prefix.cross_product) {
+                    if let Some(root) = prefix.remove_prefix(&stem) {
+                        if self.has_stem(&root, &[prefix.flag, suffix.flag]) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        self.prefixes.iter().any(|prefix| {
+            prefix
+                .remove_prefix(word)
+                .map_or(false, |stem| self.has_stem(&stem, &[prefix.flag]))
+        })
+    }
+
+    fn has_stem(&self, stem: &str, required_flags: &[Flag]) -> bool {
+        self.words.get(stem).map_or(false, |entries| {
+            entries.iter().any(|flags| {
+                !has_flag(flags, self.forbidden_flag)
+                    && required_flags.iter().all(|flag| flags.contains(flag))
+            })
+        })
+    }
+
+    /// Returns the words one edit away from the given one, ordered so that the
+    /// most common kinds of typos come first.
+    fn edits(&self, word: &str) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let alphabet = if self.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            self.try_chars
+                .iter()
+                .copied()
+                .filter(|character| !character.is_uppercase())
+                .collect::<Vec<_>>()
+        };
+        let mut edits = Vec::new();
+        for ix in 0..chars.len().saturating_sub(1) {
+            let mut swapped = chars.clone();
+            swapped.swap(ix, ix + 1);
+            edits.push(swapped.iter().collect());
+        }
+        for ix in 0..chars.len() {
+            for &character in &alphabet {
+                if character != chars[ix] {
+                    let mut replaced = chars.clone();
+                    replaced[ix] = character;
+                    edits.push(replaced.iter().collect());
+                }
+            }
+        }
+        for ix in 0..chars.len() {
+            let mut removed = chars.clone();
+            removed.remove(ix);
+            edits.push(removed.iter().collect());
+        }
+        for ix in 0..=chars.len() {
+            for &character in &alphabet {
+                let mut inserted = chars.clone();
+                inserted.insert(ix, character);
+                edits.push(inserted.iter().collect());
+            }
+        }
+        for ix in 1..chars.len() {
+            let (left, right) = chars.split_at(ix);
+            edits.push(format!(
+                "{} {}",
+                left.iter().collect::<String>(),
+                right.iter().collect::<String>()
+            ));
+        }
+        edits
+    }
+
+    /// Returns dictionary stems within a couple of edits of the given word,
+    /// from the closest to the furthest.
+    fn similar_words(&self, word: &str) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let max_distance = if chars.len() <= 4 { 1 } else { 2 };
+        let mut candidates = self
+            .words
+            .keys()
+            .filter_map(|candidate| {
+                let candidate_chars = candidate.to_lowercase().chars().collect::<Vec<_>>();
+                if candidate_chars.len().abs_diff(chars.len()) > max_distance {
+                    return None;
+                }
+                let distance = edit_distance(&chars, &candidate_chars);
+                (distance <= max_distance).then(|| (distance, candidate.clone()))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(distance, candidate)| {
+            (*distance, Reverse(common_prefix_len(word, candidate)))
+        });
+        candidates
+            .into_iter()
+            .map(|(_, candidate)| candidate)
+            .collect()
+    }
+}
+
+impl Affix {
+    fn remove_suffix(&self, word: &str) -> Option<String> {
+        let stem = word.strip_suffix(self.add.as_str())?;
+        if stem.is_empty() {
+            return None;
+        }
+        let stem = format!("{stem}{}", self.strip);
+        let stem_chars = stem.chars().rev().collect::<Vec<_>>();
+        condition_matches(self.condition.iter().rev(), &stem_chars).then_some(stem)
+    }
+
+    fn remove_prefix(&self, word: &str) -> Option<String> {
+        let stem = word.strip_prefix(self.add.as_str())?;
+        if stem.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{stem}", self.strip);
+        let stem_chars = stem.chars().collect::<Vec<_>>();
+        condition_matches(self.condition.iter(), &stem_chars).then_some(stem)
+    }
+}
+
+impl Case {
+    fn of(word: &str) -> Self {
+        let mut chars = word.chars().filter(|character| character.is_alphabetic());
+        let Some(first) = chars.next() else {
+            return Case::Lower;
+        };
+        let (mut has_upper, mut has_lower) = (false, false);
+        for character in chars {
+            has_upper |= character.is_uppercase();
+            has_lower |= character.is_lowercase();
+        }
+        match (first.is_uppercase(), has_upper, has_lower) {
+            (false, false, _) => Case::Lower,
+            (true, false, _) => Case::Capitalized,
+            (true, true, false) => Case::Upper,
+            _ => Case::Mixed,
+        }
+    }
+}
+
+fn condition_matches<'a>(
+    mut condition: impl Iterator<Item = &'a ConditionChar>,
+    chars: &[char],
+) -> bool {
+    let mut chars = chars.iter();
+    condition.all(|expected| {
+        let Some(character) = chars.next() else {
+            return false;
+        };
+        match expected {
+            ConditionChar::Any => true,
+            ConditionChar::Char(expected) => character == expected,
+            ConditionChar::Set {
+                chars: set,
+                negated,
+            } => set.contains(character) != *negated,
+        }
+    })
+}
+
+fn parse_condition(condition: &str) -> Vec<ConditionChar> {
+    let mut result = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(character) = chars.next() {
+        match character {
+            '.' => result.push(ConditionChar::Any),
+            '[' => {
+                let mut set = Vec::new();
+                let mut negated = false;
+                for character in chars.by_ref() {
+                    match character {
+                        '^' if set.is_empty() && !negated => negated = true,
+                        ']' => break,
+                        character => set.push(character),
+                    }
+                }
+                result.push(ConditionChar::Set {
+                    chars: set,
+                    negated,
+                });
+            }
+            character => result.push(ConditionChar::Char(character)),
+        }
+    }
+    result
+}
+
+fn parse_flags(flags: &str, format: FlagFormat) -> Vec<Flag> {
+    match format {
+        FlagFormat::Single => flags.chars().map(|flag| flag as Flag).collect(),
+        FlagFormat::Long => flags
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|pair| {
+                pair.iter()
+                    .fold(0, |flag, &character| (flag << 16) | character as Flag)
+            })
+            .collect(),
+        FlagFormat::Numeric => flags
+            .split(',')
+            .filter_map(|flag| flag.trim().parse().ok())
+            .collect(),
+    }
+}
+
+/// Splits a `.dic` entry into its word and flags, honoring escaped slashes.
+fn split_entry(entry: &str) -> (String, Option<&str>) {
+    let mut word = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((ix, character)) = chars.next() {
+        match character {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            '/' => return (word, Some(&entry[ix + 1..])),
+            character => word.push(character),
+        }
+    }
+    (word, None)
+}
+
+fn has_flag(flags: &[Flag], flag: Option<Flag>) -> bool {
+    flag.map_or(false, |flag| flags.contains(&flag))
+}
+
+/// Decodes a dictionary file. Only UTF-8 and ISO8859-1 are supported; other
+/// encodings are decoded as UTF-8.
+fn decode(bytes: &[u8], encoding: &str) -> String {
+    match encoding {
+        "ISO8859-1" | "ISO-8859-1" => bytes.iter().map(|&byte| byte as char).collect(),
+        _ => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(a, b)| a == b).count()
+}
+
+/// Computes the optimal string alignment distance between two words, which
+/// counts swapped neighboring characters as a single edit.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (ix, row) in rows.iter_mut().enumerate() {
+        row[0] = ix;
+    }
+    for (jx, cell) in rows[0].iter_mut().enumerate() {
+        *cell = jx;
+    }
+    for ix in 1..=a.len() {
+        for jx in 1..=b.len() {
+            let cost = usize::from(a[ix - 1] != b[jx - 1]);
+            let mut distance = (rows[ix - 1][jx] + 1)
+                .min(rows[ix][jx - 1] + 1)
+                .min(rows[ix - 1][jx - 1] + cost);
+            if ix > 1 && jx > 1 && a[ix - 1] == b[jx - 2] && a[ix - 2] == b[jx - 1] {
+                distance = distance.min(rows[ix - 2][jx - 2] + 1);
+            }
+            rows[ix][jx] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+        SET UTF-8
+        TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+        NOSUGGEST !
+        FORBIDDENWORD ?
+
+        REP 1
+        REP f ph
+
+        PFX U Y 1
+        PFX U 0 un .
+
+        SFX S Y 3
+        SFX S y ies [^aeiou]y
+        SFX S 0 s [aeiou]y
+        SFX S 0 s [^y]
+
+        SFX D Y 2
+        SFX D 0 d e
+        SFX D 0 ed [^e]
+    ";
+
+    const DIC: &str = "
+        9
+        comment/S
+        lock/UDS
+        parse/D
+        query/S
+        key/S
+        Zed
+        telephone
+        heck/!
+        colour/?
+    ";
+
+    fn dictionary() -> Dictionary {
+        let unindent = |text: &str| {
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        Dictionary::parse(unindent(AFF).as_bytes(), unindent(DIC).as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        let dictionary = dictionary();
+        for word in [
+            "comment", "comments", "lock", "locks", "locked", "unlock", "unlocked", "unlocks",
+            "parsed", "queries", "keys", "Zed", "Comment", "COMMENTS",
+        ] {
+            assert!(dictionary.check(word), "{word} should be accepted");
+        }
+        for word in [
+            "commnet", "parseed", "querys", "keies", "zed", "unparse", "colour", "cOMMENT",
+        ] {
+            assert!(!dictionary.check(word), "{word} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("commnet", 3), ["comment"]);
+        assert_eq!(dictionary.suggest("Unlcoked", 3), ["Unlocked"]);
+        assert_eq!(dictionary.suggest("telefone", 3), ["telephone"]);
+        assert_eq!(dictionary.suggest("zed", 3), ["Zed"]);
+        assert_eq!(dictionary.suggest("QUERYS", 3), ["QUERY"]);
+        assert_eq!(dictionary.suggest("lockkey", 3), ["lock key"]);
+        assert!(dictionary.check("heck"));
+        assert!(dictionary.suggest("hekc", 3).is_empty());
+    }
+
+    #[test]
+    fn test_long_flags_and_aliases() {
+        let aff = "SET UTF-8\nFLAG long\nAF 1\nAF Aa\nSFX Aa Y 1\nSFX Aa 0 s .";
+        let dictionary = Dictionary::parse(aff.as_bytes(), "1\nword/1".as_bytes()).unwrap();
+        assert!(dictionary.check("word"));
+        assert!(dictionary.check("words"));
+        assert!(!dictionary.check("wordss"));
+    }
+}
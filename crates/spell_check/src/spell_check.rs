@@ -0,0 +1,616 @@
+mod dictionary;
+mod spell_check_settings;
+mod words;
+
+use std::{ops::Range, path::PathBuf, rc::Rc, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+use editor::{CodeActionProvider, Editor, ExcerptId};
+use fs::Fs;
+use gpui::{AppContext, Context as _, Model, ModelContext, Subscription, Task, WindowContext};
+use language::{
+    Buffer, BufferEvent, BufferId, BufferSnapshot, Diagnostic, DiagnosticEntry, LanguageServerId,
+    PointUtf16, Unclipped,
+};
+use lsp::DiagnosticSeverity;
+use project::{
+    buffer_store::BufferStoreEvent, CodeAction, Project, ProjectTransaction, Worktree, WorktreeId,
+};
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsStore};
+use util::{ResultExt, TryFutureExt};
+use workspace::Workspace;
+
+pub use dictionary::Dictionary;
+pub use spell_check_settings::SpellCheckSettings;
+
+/// The source of the diagnostics reported for misspelled words.
+pub const SPELL_CHECK_SOURCE: &str = "spell-check";
+
+/// The id under which spelling diagnostics are stored, next to those reported
+/// by language servers.
+///
+/// No language server has this id, as ids are given out counting up from 0. The id only keeps
+/// spelling diagnostics apart in buffers and diagnostic summaries, so that they are replaced
+/// independently of the diagnostics of real servers. Nothing is ever sent to a server with it:
+/// the code actions that carry it are applied by the spell checker itself, and looking
+/// up a server or its status by this id finds nothing.
+pub const SPELL_CHECK_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX - 1);
+
+const CHECK_DEBOUNCE: Duration = Duration::from_millis(300);
+const MAX_SUGGESTIONS: usize = 5;
+
+pub fn init(cx: &mut AppContext) {
+    SpellCheckSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        let project = workspace.project().clone();
+        if !project.read(cx).is_local() {
+            return;
+        }
+
+        let spell_checker = cx.new_model(|cx| SpellChecker::new(project, cx));
+        let workspace_handle = cx.view().clone();
+        // Items are added again when they move between panes, which replaces their provider.
+        cx.subscribe(&workspace_handle, move |_, _, event, cx| {
+            if let workspace::Event::ItemAdded { item } = event {
+                if let Some(editor) = item.act_as::<Editor>(cx) {
+                    editor.update(cx, |editor, cx| {
+                        editor.push_code_action_provider(
+                            Rc::new(SpellCheckCodeActionProvider {
+                                spell_checker: spell_checker.clone(),
+                            }),
+                            cx,
+                        );
+                    });
+                }
+            }
+        })
+        .detach();
+    })
+    .detach();
+}
+
+/// Checks the spelling of the open buffers in a local project, and reports
+/// misspelled words as diagnostics.
+pub struct SpellChecker {
+    project: Model<Project>,
+    settings: SpellCheckSettings,
+    dictionaries: Arc<[Arc<Dictionary>]>,
+    project_words: HashMap<WorktreeId, Arc<HashSet<String>>>,
+    buffers: HashMap<BufferId, CheckedBuffer>,
+    _load_dictionaries: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct CheckedBuffer {
+    buffer: Model<Buffer>,
+    _check: Task<Option<()>>,
+    _subscription: Subscription,
+}
+
+impl SpellChecker {
+    fn new(project: Model<Project>, cx: &mut ModelContext<Self>) -> Self {
+        let buffer_store = project.read(cx).buffer_store().clone();
+        let subscriptions = vec![
+            cx.subscribe(&buffer_store, |this, _, event, cx| match event {
+                BufferStoreEvent::BufferAdded(buffer) => this.track_buffer(buffer.clone(), cx),
+                BufferStoreEvent::BufferDropped(buffer_id) => {
+                    this.buffers.remove(buffer_id);
+                }
+                BufferStoreEvent::BufferChangedFilePath { buffer, .. } => {
+                    this.schedule_check(buffer.clone(), cx)
+                }
+            }),
+            cx.subscribe(&project, |this, project, event, cx| match event {
+                project::Event::WorktreeAdded(worktree_id) => {
+                    if let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) {
+                        this.load_project_words(worktree, cx);
+                    }
+                }
+                project::Event::WorktreeRemoved(worktree_id) => {
+                    this.project_words.remove(worktree_id);
+                }
+                project::Event::WorktreeUpdatedEntries(worktree_id, entries) => {
+                    let dictionary_path = paths::local_dictionary_file_relative_path();
+                    if entries
+                        .iter()
+                        .any(|(path, _, _)| path.as_ref() == dictionary_path)
+                    {
+                        if let Some(worktree) = project.read(cx).worktree_for_id(*worktree_id, cx) {
+                            this.load_project_words(worktree, cx);
+                        }
+                    }
+                }
+                _ => {}
+            }),
+            cx.observe_global::<SettingsStore>(|this, cx| {
+                let settings = SpellCheckSettings::get_global(cx);
+                if *settings != this.settings {
+                    this.settings = settings.clone();
+                    this.load_dictionaries(cx);
+                }
+            }),
+        ];
+
+        let mut this = Self {
+            project: project.clone(),
+            settings: SpellCheckSettings::get_global(cx).clone(),
+            dictionaries: Arc::default(),
+            project_words: HashMap::default(),
+            buffers: HashMap::default(),
+            _load_dictionaries: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.load_dictionaries(cx);
+        for worktree in project.read(cx).worktrees(cx).collect::<Vec<_>>() {
+            this.load_project_words(worktree, cx);
+        }
+        for buffer in project.read(cx).opened_buffers(cx) {
+            this.track_buffer(buffer, cx);
+        }
+        this
+    }
+
+    /// Returns suggested replacements for a misspelled word.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        for dictionary in self.dictionaries.iter() {
+            for suggestion in dictionary.suggest(word, MAX_SUGGESTIONS) {
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions.truncate(MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// Adds a word to the dictionary of the worktree containing the given
+    /// buffer, which is stored in its `.zed` directory.
+    pub fn add_to_project_dictionary(
+        &mut self,
+        buffer: &Model<Buffer>,
+        word: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = buffer.read(cx).file().and_then(|file| {
+            self.project
+                .read(cx)
+                .worktree_for_id(file.worktree_id(cx), cx)
+        }) else {
+            return Task::ready(Err(anyhow!("buffer is not part of a worktree")));
+        };
+        let worktree_id = worktree.read(cx).id();
+        let dictionary_path = worktree
+            .read(cx)
+            .abs_path()
+            .join(paths::local_dictionary_file_relative_path());
+
+        let mut words = self
+            .project_words
+            .get(&worktree_id)
+            .map(|words| words.as_ref().clone())
+            .unwrap_or_default();
+        words.insert(word.clone());
+        self.project_words.insert(worktree_id, Arc::new(words));
+        self.check_all_buffers(cx);
+
+        let fs = self.project.read(cx).fs().clone();
+        cx.background_executor().spawn(async move {
+            let mut contents = fs.load(&dictionary_path).await.unwrap_or_default();
+            if !contents.is_empty() && !contents.ends_with('\n') {
+                contents.push('\n');
+            }
+            contents.push_str(&word);
+            contents.push('\n');
+            if let Some(parent) = dictionary_path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(dictionary_path.clone(), contents)
+                .await
+                .with_context(|| format!("writing {dictionary_path:?}"))
+        })
+    }
+
+    fn load_dictionaries(&mut self, cx: &mut ModelContext<Self>) {
+        let settings = self.settings.clone();
+        let fs = self.project.read(cx).fs().clone();
+        let load = cx.background_executor().spawn(async move {
+            let mut dictionaries = Vec::new();
+            if settings.enabled {
+                for name in &settings.dictionaries {
+                    match load_dictionary(fs.as_ref(), name).await {
+                        Ok(dictionary) => dictionaries.push(Arc::new(dictionary)),
+                        Err(error) => {
+                            log::error!("failed to load spell check dictionary {name}: {error:#}")
+                        }
+                    }
+                }
+            }
+            dictionaries
+        });
+        self._load_dictionaries = cx.spawn(|this, mut cx| async move {
+            let dictionaries = load.await;
+            this.update(&mut cx, |this, cx| {
+                this.dictionaries = dictionaries.into();
+                this.check_all_buffers(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn load_project_words(&mut self, worktree: Model<Worktree>, cx: &mut ModelContext<Self>) {
+        let worktree_id = worktree.read(cx).id();
+        let dictionary_path = worktree
+            .read(cx)
+            .abs_path()
+            .join(paths::local_dictionary_file_relative_path());
+        let fs = self.project.read(cx).fs().clone();
+        cx.spawn(|this, mut cx| async move {
+            let contents = fs.load(&dictionary_path).await.unwrap_or_default();
+            let words = contents
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(str::to_string)
+                .collect::<HashSet<_>>();
+            this.update(&mut cx, |this, cx| {
+                this.project_words.insert(worktree_id, Arc::new(words));
+                this.check_all_buffers(cx);
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn track_buffer(&mut self, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let subscription = cx.subscribe(&buffer, |this, buffer, event, cx| match event {
+            BufferEvent::Reparsed | BufferEvent::LanguageChanged => this.schedule_check(buffer, cx),
+            _ => {}
+        });
+        self.buffers.insert(
+            buffer.read(cx).remote_id(),
+            CheckedBuffer {
+                buffer: buffer.clone(),
+                _check: Task::ready(None),
+                _subscription: subscription,
+            },
+        );
+        self.schedule_check(buffer, cx);
+    }
+
+    fn check_all_buffers(&mut self, cx: &mut ModelContext<Self>) {
+        let buffers = self
+            .buffers
+            .values()
+            .map(|checked| checked.buffer.clone())
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.schedule_check(buffer, cx);
+        }
+    }
+
+    fn schedule_check(&mut self, buffer: Model<Buffer>, cx: &mut ModelContext<Self>) {
+        // Without dictionaries, only buffers with stale diagnostics need to be checked.
+        if self.dictionaries.is_empty()
+            && buffer
+                .read(cx)
+                .snapshot()
+                .diagnostic_groups(Some(SPELL_CHECK_SERVER_ID))
+                .is_empty()
+        {
+            return;
+        }
+        let Some(checked) = self.buffers.get_mut(&buffer.read(cx).remote_id()) else {
+            return;
+        };
+        checked._check = cx.spawn(|this, mut cx| {
+            async move {
+                cx.background_executor().timer(CHECK_DEBOUNCE).await;
+                let Some((snapshot, abs_path, dictionaries, project_words, lsp_store)) = this
+                    .update(&mut cx, |this, cx| {
+                        let buffer = buffer.read(cx);
+                        let file = buffer.file()?;
+                        let abs_path = file.as_local()?.abs_path(cx);
+                        let project_words = this
+                            .project_words
+                            .get(&file.worktree_id(cx))
+                            .cloned()
+                            .unwrap_or_default();
+                        Some((
+                            buffer.snapshot(),
+                            abs_path,
+                            this.dictionaries.clone(),
+                            project_words,
+                            this.project.read(cx).lsp_store(),
+                        ))
+                    })?
+                else {
+                    return Ok(());
+                };
+
+                let (snapshot, entries) = cx
+                    .background_executor()
+                    .spawn(async move {
+                        let entries =
+                            spelling_diagnostics(&snapshot, &dictionaries, &project_words);
+                        (snapshot, entries)
+                    })
+                    .await;
+
+                // The buffer will be checked again once its new contents have been parsed.
+                if buffer.read_with(&cx, |buffer, _| {
+                    buffer.version().changed_since(snapshot.version())
+                })? {
+                    return Ok(());
+                }
+                lsp_store.update(&mut cx, |lsp_store, cx| {
+                    lsp_store.update_diagnostic_entries(
+                        SPELL_CHECK_SERVER_ID,
+                        abs_path,
+                        None,
+                        entries,
+                        cx,
+                    )
+                })?
+            }
+            .log_err()
+        });
+    }
+}
+
+/// Returns a diagnostic for each word in the buffer's comments, strings and
+/// prose that is in none of the given dictionaries.
+fn spelling_diagnostics(
+    snapshot: &BufferSnapshot,
+    dictionaries: &[Arc<Dictionary>],
+    project_words: &HashSet<String>,
+) -> Vec<DiagnosticEntry<Unclipped<PointUtf16>>> {
+    if dictionaries.is_empty() {
+        return Vec::new();
+    }
+
+    let mut entries = Vec::new();
+    for range in snapshot.spell_check_ranges(0..snapshot.len()) {
+        let text = snapshot.text_for_range(range.clone()).collect::<String>();
+        for word_range in words::words(&text) {
+            let word = &text[word_range.clone()];
+            if project_words.contains(word)
+                || project_words.contains(&word.to_lowercase())
+                || dictionaries.iter().any(|dictionary| dictionary.check(word))
+            {
+                continue;
+            }
+
+            let start = snapshot.offset_to_point_utf16(range.start + word_range.start);
+            let end = snapshot.offset_to_point_utf16(range.start + word_range.end);
+            entries.push(DiagnosticEntry {
+                range: Unclipped(start)..Unclipped(end),
+                diagnostic: Diagnostic {
+                    source: Some(SPELL_CHECK_SOURCE.to_string()),
+                    severity: DiagnosticSeverity::WARNING,
+                    message: format!("Unknown word \"{word}\""),
+                    group_id: entries.len(),
+                    is_primary: true,
+                    ..Diagnostic::default()
+                },
+            });
+        }
+    }
+    entries
+}
+
+async fn load_dictionary(fs: &dyn Fs, name: &str) -> Result<Dictionary> {
+    let dirs = dictionary_dirs();
+    for dir in &dirs {
+        let aff_path = dir.join(format!("{name}.aff"));
+        let dic_path = dir.join(format!("{name}.dic"));
+        if fs.is_file(&aff_path).await && fs.is_file(&dic_path).await {
+            let aff = fs.load_bytes(&aff_path).await?;
+            let dic = fs.load_bytes(&dic_path).await?;
+            return Dictionary::parse(&aff, &dic).with_context(|| format!("parsing {dic_path:?}"));
+        }
+    }
+    Err(anyhow!("no {name}.aff and {name}.dic files in {dirs:?}"))
+}
+
+/// Returns the directories that dictionaries are looked up in, in order.
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![paths::dictionaries_dir().clone()];
+    if cfg!(target_os = "macos") {
+        dirs.push(util::paths::home_dir().join("Library/Spelling"));
+        dirs.push(PathBuf::from("/Library/Spelling"));
+    } else if cfg!(not(target_os = "windows")) {
+        dirs.extend(
+            [
+                "/usr/share/hunspell",
+                "/usr/share/myspell",
+                "/usr/share/myspell/dicts",
+                "/usr/local/share/hunspell",
+            ]
+            .map(PathBuf::from),
+        );
+    }
+    dirs
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellCheckAction {
+    Replace { text: String },
+    AddToProjectDictionary { word: String },
+}
+
+struct SpellCheckCodeActionProvider {
+    spell_checker: Model<SpellChecker>,
+}
+
+impl CodeActionProvider for SpellCheckCodeActionProvider {
+    fn id(&self) -> &'static str {
+        SPELL_CHECK_SOURCE
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Model<Buffer>,
+        range: Range<language::Anchor>,
+        cx: &mut WindowContext,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let snapshot = buffer.read(cx).snapshot();
+        let spell_checker = self.spell_checker.read(cx);
+        let mut actions = Vec::new();
+        for entry in snapshot.diagnostics_in_range::<_, usize>(range, false) {
+            if entry.diagnostic.source.as_deref() != Some(SPELL_CHECK_SOURCE) {
+                continue;
+            }
+            let word = snapshot
+                .text_for_range(entry.range.clone())
+                .collect::<String>();
+            let range =
+                snapshot.anchor_before(entry.range.start)..snapshot.anchor_after(entry.range.end);
+            let suggestions = spell_checker.suggestions(&word);
+            let code_action = |title: String, action: SpellCheckAction| CodeAction {
+                server_id: SPELL_CHECK_SERVER_ID,
+                range: range.clone(),
+                lsp_action: lsp::CodeAction {
+                    title,
+                    kind: Some(lsp::CodeActionKind::QUICKFIX),
+                    data: serde_json::to_value(action).log_err(),
+                    ..Default::default()
+                },
+            };
+            for suggestion in suggestions {
+                actions.push(code_action(
+                    format!("Change to \"{suggestion}\""),
+                    SpellCheckAction::Replace { text: suggestion },
+                ));
+            }
+            actions.push(code_action(
+                format!("Add \"{word}\" to project dictionary"),
+                SpellCheckAction::AddToProjectDictionary { word },
+            ));
+        }
+        Task::ready(Ok(actions))
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Model<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        push_to_history: bool,
+        cx: &mut WindowContext,
+    ) -> Task<Result<ProjectTransaction>> {
+        let spell_check_action = match action
+            .lsp_action
+            .data
+            .context("spell check action has no data")
+            .and_then(|data| Ok(serde_json::from_value::<SpellCheckAction>(data)?))
+        {
+            Ok(spell_check_action) => spell_check_action,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        match spell_check_action {
+            SpellCheckAction::Replace { text } => {
+                let transaction = buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, text)], None, cx);
+                    if buffer.end_transaction(cx).is_some() {
+                        let transaction = buffer.finalize_last_transaction().unwrap().clone();
+                        if !push_to_history {
+                            buffer.forget_transaction(transaction.id);
+                        }
+                        Some(transaction)
+                    } else {
+                        None
+                    }
+                });
+                let mut project_transaction = ProjectTransaction::default();
+                if let Some(transaction) = transaction {
+                    project_transaction.0.insert(buffer, transaction);
+                }
+                Task::ready(Ok(project_transaction))
+            }
+            SpellCheckAction::AddToProjectDictionary { word } => {
+                let add_word = self.spell_checker.update(cx, |spell_checker, cx| {
+                    spell_checker.add_to_project_dictionary(&buffer, word, cx)
+                });
+                cx.background_executor().spawn(async move {
+                    add_word.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use language::{Language, LanguageConfig};
+
+    #[gpui::test]
+    async fn test_spelling_diagnostics(cx: &mut TestAppContext) {
+        let language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_highlights_query(
+            r#"
+            (line_comment) @comment
+            (string_literal) @string
+            "#,
+        )
+        .unwrap();
+        let dictionary = Dictionary::parse(
+            b"SET UTF-8\nSFX S Y 1\nSFX S 0 s .",
+            b"5\nreturn/S\nthe\nnumber\nof\nline/S",
+        )
+        .unwrap();
+        let text = [
+            "// Returns the numbr of linse.",
+            "fn count_lines() -> usize {",
+            "    let message = \"the nummber of lines\";",
+            "    0",
+            "}",
+        ]
+        .join("\n");
+
+        let buffer =
+            cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+        cx.executor().run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        let project_words = HashSet::from_iter(["numbr".to_string()]);
+        let diagnostics = spelling_diagnostics(&snapshot, &[Arc::new(dictionary)], &project_words)
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.range.start.0..entry.range.end.0,
+                    entry.diagnostic.message,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            [
+                (
+                    PointUtf16::new(0, 24)..PointUtf16::new(0, 29),
+                    "Unknown word \"linse\"".to_string()
+                ),
+                (
+                    PointUtf16::new(2, 23)..PointUtf16::new(2, 30),
+                    "Unknown word \"nummber\"".to_string()
+                ),
+            ]
+        );
+    }
+}
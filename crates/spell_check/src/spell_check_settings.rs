@@ -0,0 +1,36 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SpellCheckSettings {
+    pub enabled: bool,
+    pub dictionaries: Vec<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SpellCheckSettingsContent {
+    /// Whether to check the spelling of comments, strings and Markdown prose.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The names of the Hunspell dictionaries to check against, such as `en_US`.
+    /// Each dictionary is loaded from a pair of `.aff` and `.dic` files in Zed's
+    /// `dictionaries` config directory, or in the system's Hunspell directories.
+    ///
+    /// Default: ["en_US"]
+    pub dictionaries: Option<Vec<String>>,
+}
+
+impl Settings for SpellCheckSettings {
+    const KEY: Option<&'static str> = Some("spell_check");
+
+    type FileContent = SpellCheckSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
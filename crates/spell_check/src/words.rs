@@ -0,0 +1,121 @@
+use std::ops::Range;
+
+/// Returns the byte ranges of the words in the given prose that should be
+/// spell checked.
+///
+/// Identifiers are split into their `snake_case` and `camelCase` parts, while
+/// URLs, paths, email addresses, acronyms, single letters and anything
+/// containing digits are skipped.
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    for token in tokens(text) {
+        let token_text = &text[token.clone()];
+        if token_text.contains("://")
+            || token_text.starts_with("www.")
+            || token_text.contains('@')
+            || token_text.contains('\\')
+            || token_text.matches('/').count() > 1
+        {
+            continue;
+        }
+
+        let mut run_start = None;
+        let mut chars = token_text.char_indices().peekable();
+        while let Some((ix, character)) = chars.next() {
+            let next = chars.peek().map(|(_, next)| *next);
+            let is_word_char = character.is_alphanumeric()
+                || (is_apostrophe(character)
+                    && run_start.is_some()
+                    && next.map_or(false, char::is_alphabetic));
+            if is_word_char {
+                run_start.get_or_insert(ix);
+            }
+            if let Some(start) = run_start {
+                if !is_word_char {
+                    push_run(token_text, start..ix, token.start, &mut words);
+                    run_start = None;
+                } else if next.is_none() {
+                    push_run(token_text, start..token_text.len(), token.start, &mut words);
+                }
+            }
+        }
+    }
+    words
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut offset = 0;
+    text.split(char::is_whitespace).filter_map(move |token| {
+        let start = offset;
+        offset += token.len();
+        // Skip the whitespace character that separated this token from the next one.
+        offset += text[offset..].chars().next().map_or(0, char::len_utf8);
+        (!token.is_empty()).then_some(start..start + token.len())
+    })
+}
+
+/// Splits a run of letters and apostrophes into its `camelCase` parts.
+fn push_run(text: &str, run: Range<usize>, offset: usize, words: &mut Vec<Range<usize>>) {
+    let run_text = &text[run.clone()];
+    if run_text.chars().any(|character| character.is_numeric()) {
+        return;
+    }
+
+    let chars = run_text.char_indices().collect::<Vec<_>>();
+    let mut part_start = 0;
+    for (index, &(ix, character)) in chars.iter().enumerate().skip(1) {
+        let previous = chars[index - 1].1;
+        let next = chars.get(index + 1).map(|(_, next)| *next);
+        let starts_part = character.is_uppercase()
+            && (previous.is_lowercase()
+                || (previous.is_uppercase() && next.map_or(false, char::is_lowercase)));
+        if starts_part {
+            push_word(run_text, part_start..ix, run.start + offset, words);
+            part_start = ix;
+        }
+    }
+    push_word(
+        run_text,
+        part_start..run_text.len(),
+        run.start + offset,
+        words,
+    );
+}
+
+fn push_word(text: &str, word: Range<usize>, offset: usize, words: &mut Vec<Range<usize>>) {
+    let word_text = &text[word.clone()];
+    let is_acronym = !word_text.chars().any(char::is_lowercase);
+    if word_text.chars().count() > 1 && !is_acronym {
+        words.push(word.start + offset..word.end + offset);
+    }
+}
+
+fn is_apostrophe(character: char) -> bool {
+    character == '\'' || character == '’'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words_in(text: &str) -> Vec<&str> {
+        words(text).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words_in("// Returns the buffer's first   line, or\tnone."),
+            ["Returns", "the", "buffer's", "first", "line", "or", "none"]
+        );
+        assert_eq!(
+            words_in("parseHTTPResponse tab_size 'quoted' I"),
+            ["parse", "Response", "tab", "size", "quoted"]
+        );
+        assert_eq!(
+            words_in("See https://zed.dev, a@b.com, crates/editor/src and utf8 JSON"),
+            ["See", "and"]
+        );
+        assert_eq!(words_in("¿Qué tal?"), ["Qué", "tal"]);
+    }
+}
@@ -1319,6 +1319,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal contents have no syntax tree.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
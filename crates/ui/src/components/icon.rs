@@ -140,6 +140,7 @@ pub enum IconName {
     Book,
     BookCopy,
     BookPlus,
+    Bookmark,
     CaseSensitive,
     Check,
     ChevronDown,
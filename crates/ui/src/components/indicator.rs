@@ -114,3 +114,48 @@ impl ComponentPreview for Indicator {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{hsla, size, SnapshotTolerance, TestAppContext};
+
+    struct Indicators;
+
+    impl Render for Indicators {
+        fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .flex()
+                .gap_1()
+                .p_1()
+                .bg(hsla(0., 0., 1., 1.))
+                .child(Indicator::dot().color(Color::Custom(hsla(0., 1., 0.5, 1.))))
+                .child(
+                    Indicator::dot()
+                        .color(Color::Custom(hsla(240. / 360., 1., 0.5, 1.)))
+                        .border_color(Color::Custom(hsla(0., 0., 0., 1.))),
+                )
+                .child(
+                    div()
+                        .w(px(16.))
+                        .child(Indicator::bar().color(Color::Custom(hsla(
+                            120. / 360.,
+                            1.,
+                            0.5,
+                            1.,
+                        )))),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_indicator_snapshot(cx: &mut TestAppContext) {
+        cx.assert_view_snapshot(
+            size(px(48.), px(16.)),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots/indicator.png"),
+            SnapshotTolerance::default(),
+            |_| Indicators,
+        );
+    }
+}
@@ -77,6 +77,7 @@ impl Vim {
             self.change_list.pop();
         }
         self.change_list.push(new_positions);
+        self.save_change_list(cx);
     }
 }
 
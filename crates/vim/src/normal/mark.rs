@@ -1,20 +1,29 @@
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::Path, sync::Arc};
 
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor, ToPoint as _,
 };
 use gpui::ViewContext;
-use language::SelectionGoal;
+use language::{Point, SelectionGoal};
+use multi_buffer::MultiBufferRow;
 
 use crate::{
     motion::{self, Motion},
-    state::Mode,
+    persistence::local_abs_path,
+    state::{GlobalMark, Mode},
     Vim,
 };
 
+/// Returns the name of a global mark, which is a single uppercase letter.
+fn global_mark_name(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    let name = chars.next()?;
+    (chars.next().is_none() && name.is_ascii_uppercase()).then_some(name)
+}
+
 impl Vim {
     pub fn create_mark(&mut self, text: Arc<str>, tail: bool, cx: &mut ViewContext<Self>) {
         let Some(anchors) = self.update_editor(cx, |_, editor, _| {
@@ -27,10 +36,37 @@ impl Vim {
         }) else {
             return;
         };
+        if let Some((name, anchor)) = global_mark_name(&text).zip(anchors.first().copied()) {
+            self.create_global_mark(name, anchor, cx);
+        }
         self.marks.insert(text.to_string(), anchors);
         self.clear_operator(cx);
     }
 
+    fn create_global_mark(&mut self, name: char, anchor: Anchor, cx: &mut ViewContext<Self>) {
+        let Some(mark) = self
+            .update_editor(cx, |_, editor, cx| {
+                let multi_buffer = editor.buffer().read(cx);
+                let point = anchor.to_point(&multi_buffer.snapshot(cx));
+                let (buffer, point, _) = multi_buffer.point_to_buffer_point(point, cx)?;
+                let abs_path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+                let anchor = buffer.read(cx).anchor_before(point);
+                Some(GlobalMark {
+                    abs_path: abs_path.into(),
+                    point,
+                    anchor: Some((buffer.downgrade(), anchor)),
+                })
+            })
+            .flatten()
+        else {
+            return;
+        };
+        Vim::update_globals(cx, |globals, cx| {
+            globals.global_marks.insert(name, mark);
+            globals.save_global_marks(cx);
+        });
+    }
+
     // When handling an action, you must create visual marks if you will switch to normal
     // mode without the default selection behavior.
     pub(crate) fn store_visual_marks(&mut self, cx: &mut ViewContext<Self>) {
@@ -85,7 +121,33 @@ impl Vim {
                     .collect::<Vec<Anchor>>()
             }),
             "." => self.change_list.last().cloned(),
-            _ => self.marks.get(&*text).cloned(),
+            _ => match global_mark_name(&text)
+                .and_then(|name| Vim::globals(cx).global_marks.get(&name).cloned())
+            {
+                Some(mark) => {
+                    let point = mark.point(cx);
+                    let anchors = self
+                        .update_editor(cx, |_, editor, cx| {
+                            if local_abs_path(editor, cx)?.as_path() != &*mark.abs_path {
+                                return None;
+                            }
+                            let snapshot = editor.buffer().read(cx).snapshot(cx);
+                            let point = snapshot.clip_point(point, Bias::Left);
+                            Some(vec![snapshot.anchor_before(point)])
+                        })
+                        .flatten();
+                    if anchors.is_none() {
+                        // Operators can't span files, so only plain jumps
+                        // open the mark's file.
+                        if self.active_operator().is_none() {
+                            self.open_global_mark(mark.abs_path, point, line, cx);
+                        }
+                        return;
+                    }
+                    anchors
+                }
+                None => self.marks.get(&*text).cloned(),
+            },
         };
 
         let Some(anchors) = anchors else { return };
@@ -124,6 +186,38 @@ impl Vim {
             });
         }
     }
+
+    fn open_global_mark(
+        &mut self,
+        abs_path: Arc<Path>,
+        point: Point,
+        line: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace(cx) else {
+            return;
+        };
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(abs_path.to_path_buf(), true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let item = open_task.await?;
+            let Some(editor) = cx.update(|cx| item.act_as::<Editor>(cx))? else {
+                return Ok(());
+            };
+            editor.update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let mut point = snapshot.clip_point(point, Bias::Left);
+                if line {
+                    point.column = snapshot.indent_size_for_line(MultiBufferRow(point.row)).len;
+                }
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
 }
 
 pub fn jump_motion(
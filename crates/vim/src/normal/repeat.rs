@@ -4,6 +4,7 @@ use crate::{
     insert::NormalBefore,
     motion::Motion,
     normal::InsertBefore,
+    persistence,
     state::{Mode, Operator, RecordedSelection, ReplayableAction, VimGlobals},
     Vim,
 };
@@ -56,7 +57,8 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Vim>) {
     Vim::action(editor, cx, |vim, _: &ToggleRecord, cx| {
         let globals = Vim::globals(cx);
         if let Some(char) = globals.recording_register.take() {
-            globals.last_recorded_register = Some(char)
+            globals.last_recorded_register = Some(char);
+            cx.global::<VimGlobals>().save_recording(char, cx);
         } else {
             vim.push_operator(Operator::RecordRegister, cx);
         }
@@ -153,6 +155,8 @@ impl Vim {
         let globals = Vim::globals(cx);
         globals.recording_register = Some(register);
         globals.recordings.remove(&register);
+        globals.persisted_recordings.remove(&register);
+        globals.recorded_bindings.clear();
         globals.ignore_current_insertion = true;
         self.clear_operator(cx)
     }
@@ -168,6 +172,15 @@ impl Vim {
             };
             register = last;
         }
+        if !globals.recordings.contains_key(&register) {
+            if let Some(actions) = globals.persisted_recordings.remove(&register) {
+                if let Some(actions) = persistence::restore_recording(actions, cx) {
+                    Vim::globals(cx).recordings.insert(register, actions);
+                }
+            }
+        }
+
+        let globals = Vim::globals(cx);
         let Some(actions) = globals.recordings.get(&register) else {
             return;
         };
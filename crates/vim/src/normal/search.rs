@@ -122,12 +122,14 @@ impl Vim {
         };
         let count = Vim::take_count(cx).unwrap_or(1);
         let prior_selections = self.editor_selections(cx);
+        let search_history = Vim::globals(cx).search_history.clone();
         pane.update(cx, |pane, cx| {
             if let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() {
                 search_bar.update(cx, |search_bar, cx| {
                     if !search_bar.show(cx) {
                         return;
                     }
+                    search_bar.restore_search_history(search_history);
                     let query = search_bar.query(cx);
 
                     search_bar.select_query(cx);
@@ -187,8 +189,12 @@ impl Vim {
                 let prior_mode = self.search.prior_mode;
                 let prior_operator = self.search.prior_operator.take();
 
-                let query = search_bar.query(cx).into();
-                Vim::globals(cx).registers.insert('/', query);
+                let query = search_bar.query(cx);
+                Vim::update_globals(cx, |globals, cx| {
+                    globals.registers.insert('/', query.clone().into());
+                    globals.save_registers(cx);
+                    globals.add_to_search_history(query, cx);
+                });
                 Some((prior_selections, prior_mode, prior_operator))
             })
         });
@@ -0,0 +1,788 @@
+use std::{ops::Range, path::PathBuf, time::Duration};
+
+use anyhow::{Context as _, Result};
+use db::{define_connection, query, sqlez_macros::sql};
+use editor::{Bias, Editor, ToPoint as _};
+use gpui::{Action, AppContext, KeyBinding, Keystroke, ViewContext, WindowContext};
+use language::Point;
+use serde::{Deserialize, Serialize};
+use util::ResultExt as _;
+
+use crate::{
+    state::{GlobalMark, RecordedBinding, Register, ReplayableAction, VimGlobals},
+    Vim,
+};
+
+/// The number of changes per file that are kept across sessions.
+const MAX_PERSISTED_CHANGES: usize = 100;
+
+const CHANGE_LIST_SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+const REGISTER_SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Registers with more text than this aren't kept across sessions.
+const MAX_PERSISTED_REGISTER_LEN: usize = 64 * 1024;
+
+/// A macro action as it's stored in the database.
+///
+/// Actions can't be serialized in general, so an action carrying data is
+/// stored as the keystrokes of the binding that triggered it while the macro
+/// was recorded, and rebuilt from the keymap when the macro is replayed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SerializedReplayableAction {
+    Action {
+        name: String,
+        keystrokes: Option<String>,
+    },
+    Insertion {
+        text: String,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+define_connection!(
+    // Current schema shape using pseudo-rust syntax:
+    // vim_registers(
+    //   name: String, // the register's character
+    //   contents: String,
+    //   clipboard_selections: Option<String>, // JSON
+    // )
+    //
+    // vim_recordings(
+    //   name: String,
+    //   actions: String, // JSON list of `SerializedReplayableAction`
+    // )
+    //
+    // vim_global_marks(
+    //   name: String,
+    //   path: PathBuf,
+    //   buffer_row: u32,
+    //   buffer_column: u32,
+    // )
+    //
+    // vim_change_lists(
+    //   path: PathBuf,
+    //   ix: u32,
+    //   buffer_row: u32,
+    //   buffer_column: u32,
+    // )
+    //
+    // vim_search_history(
+    //   ix: u32,
+    //   search_query: String,
+    // )
+    //
+    // vim_command_history(
+    //   ix: u32,
+    //   command_query: String,
+    // )
+    pub static ref DB: VimDb<()> =
+        &[sql!(
+            CREATE TABLE vim_registers(
+                name TEXT PRIMARY KEY,
+                contents TEXT NOT NULL,
+                clipboard_selections TEXT
+            ) STRICT;
+
+            CREATE TABLE vim_recordings(
+                name TEXT PRIMARY KEY,
+                actions TEXT NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_global_marks(
+                name TEXT PRIMARY KEY,
+                path BLOB NOT NULL,
+                buffer_row INTEGER NOT NULL,
+                buffer_column INTEGER NOT NULL
+            ) STRICT;
+
+            CREATE TABLE vim_change_lists(
+                path BLOB NOT NULL,
+                ix INTEGER NOT NULL,
+                buffer_row INTEGER NOT NULL,
+                buffer_column INTEGER NOT NULL,
+                PRIMARY KEY(path, ix)
+            ) STRICT;
+
+            CREATE TABLE vim_search_history(
+                ix INTEGER PRIMARY KEY,
+                search_query TEXT NOT NULL
+            ) STRICT;
+        ),
+        sql!(
+            CREATE TABLE vim_command_history(
+                ix INTEGER PRIMARY KEY,
+                command_query TEXT NOT NULL
+            ) STRICT;
+        )];
+);
+
+impl VimDb {
+    query! {
+        pub fn registers() -> Result<Vec<(String, String, Option<String>)>> {
+            SELECT name, contents, clipboard_selections FROM vim_registers
+        }
+    }
+
+    pub async fn set_registers(
+        &self,
+        registers: Vec<(String, String, Option<String>)>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("set_registers", || {
+                conn.exec(sql!(DELETE FROM vim_registers))?().context("Clearing old registers")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_registers(name, contents, clipboard_selections)
+                        VALUES (?, ?, ?)
+                    ))
+                    .context("Preparing register insertion")?;
+                for register in registers {
+                    insert(register)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn recordings() -> Result<Vec<(String, String)>> {
+            SELECT name, actions FROM vim_recordings
+        }
+    }
+
+    query! {
+        pub async fn save_recording(name: String, actions: String) -> Result<()> {
+            INSERT OR REPLACE INTO vim_recordings(name, actions) VALUES (?, ?)
+        }
+    }
+
+    query! {
+        pub async fn delete_recording(name: String) -> Result<()> {
+            DELETE FROM vim_recordings WHERE name = ?
+        }
+    }
+
+    query! {
+        pub fn global_marks() -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT name, path, buffer_row, buffer_column FROM vim_global_marks
+        }
+    }
+
+    pub async fn set_global_marks(&self, marks: Vec<(String, PathBuf, u32, u32)>) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("set_global_marks", || {
+                conn.exec(sql!(DELETE FROM vim_global_marks))?()
+                    .context("Clearing old global marks")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_global_marks(name, path, buffer_row, buffer_column)
+                        VALUES (?, ?, ?, ?)
+                    ))
+                    .context("Preparing global mark insertion")?;
+                for mark in marks {
+                    insert(mark)?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn change_list(path: PathBuf) -> Result<Vec<(u32, u32)>> {
+            SELECT buffer_row, buffer_column FROM vim_change_lists
+            WHERE path = ?
+            ORDER BY ix
+        }
+    }
+
+    pub async fn set_change_list(&self, path: PathBuf, positions: Vec<(u32, u32)>) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("set_change_list", || {
+                conn.exec_bound(sql!(DELETE FROM vim_change_lists WHERE path = ?))?(path.as_path())
+                    .context("Clearing old change list")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_change_lists(path, ix, buffer_row, buffer_column)
+                        VALUES (?, ?, ?, ?)
+                    ))
+                    .context("Preparing change list insertion")?;
+                for (ix, (row, column)) in positions.into_iter().enumerate() {
+                    insert((path.as_path(), ix as u32, row, column))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn search_history() -> Result<Vec<String>> {
+            SELECT search_query FROM vim_search_history ORDER BY ix
+        }
+    }
+
+    pub async fn set_search_history(&self, queries: Vec<String>) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("set_search_history", || {
+                conn.exec(sql!(DELETE FROM vim_search_history))?()
+                    .context("Clearing old search history")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_search_history(ix, search_query) VALUES (?, ?)
+                    ))
+                    .context("Preparing search history insertion")?;
+                for (ix, query) in queries.into_iter().enumerate() {
+                    insert((ix as u32, query))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn command_history() -> Result<Vec<String>> {
+            SELECT command_query FROM vim_command_history ORDER BY ix
+        }
+    }
+
+    pub async fn set_command_history(&self, queries: Vec<String>) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("set_command_history", || {
+                conn.exec(sql!(DELETE FROM vim_command_history))?()
+                    .context("Clearing old command history")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO vim_command_history(ix, command_query) VALUES (?, ?)
+                    ))
+                    .context("Preparing command history insertion")?;
+                for (ix, query) in queries.into_iter().enumerate() {
+                    insert((ix as u32, query))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+impl VimGlobals {
+    /// Loads the registers, global marks, macros and search and command
+    /// histories saved by previous sessions. State from the current session takes precedence.
+    pub(crate) fn restore_persisted_state(&mut self) {
+        if !self.persist_state || self.persisted_state_restored {
+            return;
+        }
+        self.persisted_state_restored = true;
+
+        for (name, contents, clipboard_selections) in DB.registers().log_err().unwrap_or_default() {
+            let Some(name) = name
+                .chars()
+                .next()
+                .filter(|name| is_persisted_register(*name))
+            else {
+                continue;
+            };
+            self.registers.entry(name).or_insert_with(|| Register {
+                text: contents.into(),
+                clipboard_selections: clipboard_selections
+                    .and_then(|selections| serde_json::from_str(&selections).log_err()),
+            });
+        }
+
+        for (name, actions) in DB.recordings().log_err().unwrap_or_default() {
+            let Some(name) = name.chars().next() else {
+                continue;
+            };
+            if self.recordings.contains_key(&name) {
+                continue;
+            }
+            if let Some(actions) = serde_json::from_str(&actions).log_err() {
+                self.persisted_recordings.insert(name, actions);
+            }
+        }
+
+        for (name, path, row, column) in DB.global_marks().log_err().unwrap_or_default() {
+            let Some(name) = name.chars().next() else {
+                continue;
+            };
+            self.global_marks.entry(name).or_insert_with(|| GlobalMark {
+                abs_path: path.into(),
+                point: Point::new(row, column),
+                anchor: None,
+            });
+        }
+
+        if self.search_history.is_empty() {
+            self.search_history = DB.search_history().log_err().unwrap_or_default();
+        }
+        if self.command_history.is_empty() {
+            self.command_history = DB.command_history().log_err().unwrap_or_default();
+        }
+    }
+
+    /// Saves the named registers and the last search once they've stopped
+    /// changing for a moment.
+    pub(crate) fn save_registers(&mut self, cx: &mut AppContext) {
+        if !self.persist_state {
+            return;
+        }
+        self.register_save_generation += 1;
+        let generation = self.register_save_generation;
+        cx.spawn(|cx| async move {
+            cx.background_executor().timer(REGISTER_SAVE_DEBOUNCE).await;
+            let registers = cx.update(|cx| {
+                let globals = cx.global::<VimGlobals>();
+                (globals.register_save_generation == generation)
+                    .then(|| globals.persisted_registers())
+            })?;
+            if let Some(registers) = registers {
+                DB.set_registers(registers).await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn persisted_registers(&self) -> Vec<(String, String, Option<String>)> {
+        self.registers
+            .iter()
+            .filter(|(name, register)| {
+                is_persisted_register(**name) && register.text.len() <= MAX_PERSISTED_REGISTER_LEN
+            })
+            .map(|(name, register)| {
+                let clipboard_selections = register
+                    .clipboard_selections
+                    .as_ref()
+                    .and_then(|selections| serde_json::to_string(selections).log_err());
+                (
+                    name.to_string(),
+                    register.text.to_string(),
+                    clipboard_selections,
+                )
+            })
+            .collect()
+    }
+
+    /// Saves the macro in the given register, or deletes the saved one if the
+    /// macro can't be restored in a later session.
+    pub(crate) fn save_recording(&self, register: char, cx: &AppContext) {
+        if !self.persist_state {
+            return;
+        }
+        let actions = self.recordings.get(&register).and_then(|actions| {
+            let actions = actions
+                .iter()
+                .map(|action| self.serialize_action(action, cx))
+                .collect::<Option<Vec<_>>>();
+            if actions.is_none() {
+                log::info!("not saving the macro in register {register}: it has actions that can't be restored");
+            }
+            serde_json::to_string(&actions?).log_err()
+        });
+
+        let name = register.to_string();
+        match actions {
+            Some(actions) => cx
+                .background_executor()
+                .spawn(DB.save_recording(name, actions))
+                .detach_and_log_err(cx),
+            None => cx
+                .background_executor()
+                .spawn(DB.delete_recording(name))
+                .detach_and_log_err(cx),
+        }
+    }
+
+    pub(crate) fn save_global_marks(&self, cx: &AppContext) {
+        if !self.persist_state {
+            return;
+        }
+        let marks = self
+            .global_marks
+            .iter()
+            .map(|(name, mark)| {
+                let point = mark.point(cx);
+                (
+                    name.to_string(),
+                    mark.abs_path.to_path_buf(),
+                    point.row,
+                    point.column,
+                )
+            })
+            .collect();
+        cx.background_executor()
+            .spawn(DB.set_global_marks(marks))
+            .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn save_search_history(&self, cx: &AppContext) {
+        if !self.persist_state {
+            return;
+        }
+        cx.background_executor()
+            .spawn(DB.set_search_history(self.search_history.clone()))
+            .detach_and_log_err(cx);
+    }
+
+    pub(crate) fn save_command_history(&self, cx: &AppContext) {
+        if !self.persist_state {
+            return;
+        }
+        cx.background_executor()
+            .spawn(DB.set_command_history(self.command_history.clone()))
+            .detach_and_log_err(cx);
+    }
+
+    /// Remembers the binding that triggered an action while a macro is being
+    /// recorded, preferring the one whose last keystroke was just typed.
+    pub(crate) fn observe_binding(
+        &mut self,
+        action: &dyn Action,
+        keystroke: &Keystroke,
+        bindings: Vec<KeyBinding>,
+    ) {
+        if self.replayer.is_some()
+            || self
+                .recorded_bindings
+                .iter()
+                .any(|binding| binding.action.partial_eq(action))
+        {
+            return;
+        }
+        let binding = bindings
+            .iter()
+            .rev()
+            .find(|binding| {
+                binding.keystrokes().last().map_or(false, |last| {
+                    last.key == keystroke.key && last.modifiers == keystroke.modifiers
+                })
+            })
+            .or(bindings.last());
+        if let Some(binding) = binding {
+            self.recorded_bindings.push(RecordedBinding {
+                action: action.boxed_clone(),
+                keystrokes: binding
+                    .keystrokes()
+                    .iter()
+                    .map(Keystroke::unparse)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            });
+        }
+    }
+
+    fn serialize_action(
+        &self,
+        action: &ReplayableAction,
+        cx: &AppContext,
+    ) -> Option<SerializedReplayableAction> {
+        match action {
+            ReplayableAction::Action(action) => {
+                let name = action.name().to_string();
+                // Actions without data can be rebuilt from their name alone.
+                let rebuilds_from_name = cx
+                    .build_action(&name, None)
+                    .map_or(false, |built| built.partial_eq(action.as_ref()));
+                let keystrokes = if rebuilds_from_name {
+                    None
+                } else {
+                    let binding = self
+                        .recorded_bindings
+                        .iter()
+                        .find(|binding| binding.action.partial_eq(action.as_ref()))?;
+                    Some(binding.keystrokes.clone())
+                };
+                Some(SerializedReplayableAction::Action { name, keystrokes })
+            }
+            ReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Some(SerializedReplayableAction::Insertion {
+                text: text.to_string(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            }),
+        }
+    }
+}
+
+/// Whether a register is kept across sessions. The unnamed, numbered and
+/// small delete registers change with every edit, and the clipboard registers
+/// are owned by the system, so only the named registers and the last search are.
+pub(crate) fn is_persisted_register(name: char) -> bool {
+    name.is_ascii_lowercase() || name == '/'
+}
+
+/// Rebuilds the actions of a macro saved by a previous session, returning
+/// `None` if one of them is no longer bound in the keymap.
+pub(crate) fn restore_recording(
+    actions: Vec<SerializedReplayableAction>,
+    cx: &WindowContext,
+) -> Option<Vec<ReplayableAction>> {
+    actions
+        .into_iter()
+        .map(|action| match action {
+            SerializedReplayableAction::Action {
+                name,
+                keystrokes: None,
+            } => cx
+                .build_action(&name, None)
+                .log_err()
+                .map(ReplayableAction::Action),
+            SerializedReplayableAction::Action {
+                name,
+                keystrokes: Some(keystrokes),
+            } => {
+                let keystrokes = keystrokes
+                    .split(' ')
+                    .map(Keystroke::parse)
+                    .collect::<Result<Vec<_>>>()
+                    .log_err()?;
+                let Some(binding) = cx
+                    .all_bindings_for_input(&keystrokes)
+                    .into_iter()
+                    .find(|binding| binding.action().name() == name)
+                else {
+                    log::info!("can't restore macro action {name}: it's no longer bound");
+                    return None;
+                };
+                Some(ReplayableAction::Action(binding.action().boxed_clone()))
+            }
+            SerializedReplayableAction::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Some(ReplayableAction::Insertion {
+                text: text.into(),
+                utf16_range_to_replace,
+            }),
+        })
+        .collect()
+}
+
+/// Returns the path of the file an editor is showing, unless it's showing a
+/// multibuffer or a file that isn't on the local file system.
+pub(crate) fn local_abs_path(editor: &Editor, cx: &AppContext) -> Option<PathBuf> {
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let abs_path = buffer.read(cx).file()?.as_local()?.abs_path(cx);
+    Some(abs_path)
+}
+
+impl Vim {
+    /// Restores the change list that previous sessions saved for a file,
+    /// reading it from the database in the background.
+    pub(crate) fn restore_change_list(&mut self, abs_path: PathBuf, cx: &mut ViewContext<Self>) {
+        if !Vim::globals(cx).persist_state {
+            return;
+        }
+        let positions = cx
+            .background_executor()
+            .spawn(async move { DB.change_list(abs_path) });
+        cx.spawn(|this, mut cx| async move {
+            let positions = positions.await.log_err().unwrap_or_default();
+            if positions.is_empty() {
+                return;
+            }
+            this.update(&mut cx, |this, cx| {
+                let persisted_changes = this.update_editor(cx, |_, editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    positions
+                        .into_iter()
+                        .map(|(row, column)| {
+                            let point = snapshot.clip_point(Point::new(row, column), Bias::Left);
+                            vec![snapshot.anchor_before(point)]
+                        })
+                        .collect::<Vec<_>>()
+                });
+                if let Some(mut change_list) = persisted_changes {
+                    // Changes made while the list was loading are the most recent ones.
+                    change_list.append(&mut this.change_list);
+                    this.change_list = change_list;
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Saves the change list of the editor's file, along with the global
+    /// marks, whose positions may have moved with the changes.
+    pub(crate) fn save_change_list(&mut self, cx: &mut ViewContext<Self>) {
+        if !Vim::globals(cx).persist_state {
+            return;
+        }
+        self.change_list_save_task = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(CHANGE_LIST_SAVE_DEBOUNCE)
+                .await;
+            let save = this
+                .update(&mut cx, |this, cx| {
+                    cx.global::<VimGlobals>().save_global_marks(cx);
+                    let change_list = this.change_list.clone();
+                    this.update_editor(cx, |_, editor, cx| {
+                        let abs_path = local_abs_path(editor, cx)?;
+                        let snapshot = editor.buffer().read(cx).snapshot(cx);
+                        let skip = change_list.len().saturating_sub(MAX_PERSISTED_CHANGES);
+                        let positions = change_list
+                            .iter()
+                            .skip(skip)
+                            .filter_map(|anchors| {
+                                let point = anchors.first()?.to_point(&snapshot);
+                                Some((point.row, point.column))
+                            })
+                            .collect();
+                        Some(DB.set_change_list(abs_path, positions))
+                    })
+                    .flatten()
+                })
+                .ok()
+                .flatten();
+            if let Some(save) = save {
+                save.await.log_err();
+            }
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{
+        persistence::{
+            SerializedReplayableAction, VimDb, MAX_PERSISTED_REGISTER_LEN, REGISTER_SAVE_DEBOUNCE,
+        },
+        state::{Mode, VimGlobals},
+        test::VimTestContext,
+        Vim,
+    };
+
+    #[gpui::test]
+    async fn test_vim_db() {
+        let db = VimDb(db::open_test_db("test_vim_db").await);
+
+        db.set_registers(vec![
+            ("a".to_string(), "one".to_string(), None),
+            ("/".to_string(), "two".to_string(), None),
+        ])
+        .await
+        .unwrap();
+        db.set_registers(vec![("a".to_string(), "three".to_string(), None)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.registers().unwrap(),
+            [("a".to_string(), "three".to_string(), None)]
+        );
+
+        let actions = vec![
+            SerializedReplayableAction::Action {
+                name: "vim::PushOperator".to_string(),
+                keystrokes: Some("d".to_string()),
+            },
+            SerializedReplayableAction::Insertion {
+                text: "hi".to_string(),
+                utf16_range_to_replace: Some(0..1),
+            },
+        ];
+        db.save_recording("q".to_string(), serde_json::to_string(&actions).unwrap())
+            .await
+            .unwrap();
+        let recordings = db.recordings().unwrap();
+        assert_eq!(recordings.len(), 1);
+        assert_eq!(recordings[0].0, "q");
+        assert_eq!(
+            serde_json::from_str::<Vec<SerializedReplayableAction>>(&recordings[0].1).unwrap(),
+            actions
+        );
+        db.delete_recording("q".to_string()).await.unwrap();
+        assert!(db.recordings().unwrap().is_empty());
+
+        db.set_global_marks(vec![("A".to_string(), PathBuf::from("/root/a.rs"), 3, 4)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.global_marks().unwrap(),
+            [("A".to_string(), PathBuf::from("/root/a.rs"), 3, 4)]
+        );
+
+        db.set_change_list(PathBuf::from("/root/a.rs"), vec![(1, 0), (5, 2)])
+            .await
+            .unwrap();
+        db.set_change_list(PathBuf::from("/root/b.rs"), vec![(7, 1)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.change_list(PathBuf::from("/root/a.rs")).unwrap(),
+            [(1, 0), (5, 2)]
+        );
+
+        db.set_search_history(vec!["foo".to_string(), "bar".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(db.search_history().unwrap(), ["foo", "bar"]);
+
+        db.set_command_history(vec!["w".to_string(), "%s/a/b".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(db.command_history().unwrap(), ["w", "%s/a/b"]);
+    }
+
+    #[gpui::test]
+    async fn test_registers_and_macros_across_sessions(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|cx| Vim::globals(cx).persist_state = true);
+
+        cx.set_state("ˇone two three four", Mode::Normal);
+        cx.simulate_keystrokes("\" a y w");
+        cx.simulate_keystrokes("q q d w q");
+        cx.assert_state("ˇtwo three four", Mode::Normal);
+        cx.simulate_keystrokes(": / f o u r enter");
+        cx.assert_state("two three ˇfour", Mode::Normal);
+        cx.simulate_keystrokes("0");
+        cx.update(|cx| {
+            Vim::globals(cx)
+                .registers
+                .insert('b', "b".repeat(MAX_PERSISTED_REGISTER_LEN + 1).into());
+        });
+        cx.simulate_keystrokes("\" c y w");
+        cx.executor().advance_clock(REGISTER_SAVE_DEBOUNCE);
+        cx.run_until_parked();
+
+        // Start over, as if Zed had been restarted.
+        cx.update(|cx| {
+            *Vim::globals(cx) = VimGlobals {
+                persist_state: true,
+                ..Default::default()
+            };
+            Vim::globals(cx).restore_persisted_state();
+
+            // Only the named registers that aren't too large are kept.
+            let register = Vim::globals(cx).registers.get(&'c').cloned();
+            assert_eq!(register.map(|register| register.text), Some("two ".into()));
+            assert!(!Vim::globals(cx).registers.contains_key(&'b'));
+            assert!(!Vim::globals(cx).registers.contains_key(&'"'));
+        });
+
+        cx.simulate_keystrokes("@ q");
+        cx.run_until_parked();
+        cx.assert_state("ˇthree four", Mode::Normal);
+        cx.update(|cx| {
+            let register = Vim::globals(cx).registers.get(&'a').cloned();
+            assert_eq!(register.map(|register| register.text), Some("one ".into()));
+            assert_eq!(Vim::globals(cx).command_history, ["/four"]);
+        });
+
+        // Commands from the previous session can be recalled.
+        cx.simulate_keystrokes(": up enter");
+        cx.assert_state("three ˇfour", Mode::Normal);
+    }
+}
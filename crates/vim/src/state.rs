@@ -1,8 +1,10 @@
 use std::borrow::BorrowMut;
+use std::path::Path;
 use std::{fmt::Display, ops::Range, sync::Arc};
 
 use crate::command::command_interceptor;
 use crate::normal::repeat::Replayer;
+use crate::persistence::{is_persisted_register, SerializedReplayableAction};
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use crate::{UseSystemClipboard, Vim, VimSettings};
@@ -10,9 +12,10 @@ use collections::HashMap;
 use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
 use editor::{Anchor, ClipboardSelection, Editor};
 use gpui::{
-    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, View, WeakView,
+    Action, AppContext, BorrowAppContext, ClipboardEntry, ClipboardItem, Global, View, WeakModel,
+    WeakView,
 };
-use language::Point;
+use language::{Buffer, Point, ToPoint as _};
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore};
 use ui::{SharedString, ViewContext};
@@ -147,6 +150,47 @@ impl From<String> for Register {
     }
 }
 
+/// An uppercase mark, which remembers its file so that it can be jumped to
+/// from any editor.
+#[derive(Clone, Debug)]
+pub struct GlobalMark {
+    pub abs_path: Arc<Path>,
+    pub point: Point,
+    /// Keeps the mark in place while its buffer is edited.
+    pub anchor: Option<(WeakModel<Buffer>, language::Anchor)>,
+}
+
+impl GlobalMark {
+    pub fn point(&self, cx: &AppContext) -> Point {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| {
+                let buffer = buffer.upgrade()?;
+                let point = anchor.to_point(&buffer.read(cx).snapshot());
+                Some(point)
+            })
+            .unwrap_or(self.point)
+    }
+}
+
+/// The key binding that triggered an action while a macro was being recorded.
+pub struct RecordedBinding {
+    pub action: Box<dyn Action>,
+    pub keystrokes: String,
+}
+
+impl Clone for RecordedBinding {
+    fn clone(&self) -> Self {
+        Self {
+            action: self.action.boxed_clone(),
+            keystrokes: self.keystrokes.clone(),
+        }
+    }
+}
+
+/// The number of entries remembered in the search and command histories.
+const MAX_HISTORY_LEN: usize = 50;
+
 #[derive(Default, Clone)]
 pub struct VimGlobals {
     pub last_find: Option<Motion>,
@@ -174,6 +218,23 @@ pub struct VimGlobals {
     pub last_yank: Option<SharedString>,
     pub registers: HashMap<char, Register>,
     pub recordings: HashMap<char, Vec<ReplayableAction>>,
+    /// Macros saved by a previous session, which are turned back into actions
+    /// the first time they're replayed.
+    pub persisted_recordings: HashMap<char, Vec<SerializedReplayableAction>>,
+    /// The bindings of the actions in the macro being recorded, so that the
+    /// actions can be rebuilt from the keymap in a later session.
+    pub recorded_bindings: Vec<RecordedBinding>,
+    pub global_marks: HashMap<char, GlobalMark>,
+    pub search_history: Vec<String>,
+    pub command_history: Vec<String>,
+
+    /// Whether registers, global marks, macros and history are saved across
+    /// sessions.
+    pub persist_state: bool,
+    pub persisted_state_restored: bool,
+    /// Incremented on every change to the saved registers, so that only the
+    /// last of a burst of changes is written.
+    pub register_save_generation: usize,
 
     pub focused_vim: Option<WeakView<Vim>>,
 }
@@ -181,26 +242,50 @@ impl Global for VimGlobals {}
 
 impl VimGlobals {
     pub(crate) fn register(cx: &mut AppContext) {
-        cx.set_global(VimGlobals::default());
+        cx.set_global(VimGlobals {
+            // Tests share a single database, so they opt into persistence explicitly.
+            persist_state: !cfg!(test),
+            ..Default::default()
+        });
+        if Vim::enabled(cx) {
+            Vim::globals(cx).restore_persisted_state();
+        }
 
         cx.observe_keystrokes(|event, cx| {
             let Some(action) = event.action.as_ref().map(|action| action.boxed_clone()) else {
                 return;
             };
+            if Vim::globals(cx).recording_register.is_some() {
+                let bindings = cx.bindings_for_action(action.as_ref());
+                Vim::globals(cx).observe_binding(action.as_ref(), &event.keystroke, bindings);
+            }
             Vim::globals(cx).observe_action(action.boxed_clone())
         })
         .detach();
 
         cx.observe_global::<SettingsStore>(move |cx| {
             if Vim::enabled(cx) {
+                Vim::globals(cx).restore_persisted_state();
                 CommandPaletteFilter::update_global(cx, |filter, _| {
                     filter.show_namespace(Vim::NAMESPACE);
                 });
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
                     interceptor.set(Box::new(command_interceptor));
+                    interceptor.set_history(
+                        Box::new(|cx| cx.global::<VimGlobals>().command_history.clone()),
+                        Box::new(|query, cx| {
+                            Vim::update_globals(cx, |globals, cx| {
+                                globals.add_to_command_history(query.to_string(), cx)
+                            })
+                        }),
+                    );
                 });
             } else {
-                *Vim::globals(cx) = VimGlobals::default();
+                let persist_state = Vim::globals(cx).persist_state;
+                *Vim::globals(cx) = VimGlobals {
+                    persist_state,
+                    ..Default::default()
+                };
                 CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
                     interceptor.clear();
                 });
@@ -286,6 +371,11 @@ impl VimGlobals {
                 }
             }
         }
+        if register.map_or(false, |register| {
+            is_persisted_register(register.to_ascii_lowercase())
+        }) {
+            self.save_registers(cx);
+        }
     }
 
     pub(crate) fn read_register(
@@ -394,6 +484,30 @@ impl VimGlobals {
         }
     }
 
+    pub(crate) fn add_to_search_history(&mut self, query: String, cx: &AppContext) {
+        if query.is_empty() {
+            return;
+        }
+        self.search_history.retain(|previous| previous != &query);
+        self.search_history.push(query);
+        if self.search_history.len() > MAX_HISTORY_LEN {
+            self.search_history.remove(0);
+        }
+        self.save_search_history(cx);
+    }
+
+    pub(crate) fn add_to_command_history(&mut self, query: String, cx: &AppContext) {
+        if query.is_empty() {
+            return;
+        }
+        self.command_history.retain(|previous| previous != &query);
+        self.command_history.push(query);
+        if self.command_history.len() > MAX_HISTORY_LEN {
+            self.command_history.remove(0);
+        }
+        self.save_command_history(cx);
+    }
+
     pub fn focused_vim(&self) -> Option<View<Vim>> {
         self.focused_vim.as_ref().and_then(|vim| vim.upgrade())
     }
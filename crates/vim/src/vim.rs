@@ -13,6 +13,7 @@ mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod rewrap;
 mod state;
@@ -27,7 +28,7 @@ use editor::{
 };
 use gpui::{
     actions, impl_actions, Action, AppContext, Axis, Entity, EventEmitter, KeyContext,
-    KeystrokeEvent, Render, Subscription, View, ViewContext, WeakView,
+    KeystrokeEvent, Render, Subscription, Task, View, ViewContext, WeakView,
 };
 use insert::{NormalBefore, TemporaryNormal};
 use language::{CursorShape, Point, Selection, SelectionGoal, TransactionId};
@@ -83,6 +84,7 @@ actions!(
         OpenDefaultKeymap,
         MaximizePane,
         ResetPaneSizes,
+        ToggleCommandPalette,
     ]
 );
 
@@ -120,6 +122,11 @@ pub fn init(cx: &mut AppContext) {
             });
         });
 
+        // Vim's `:` opens the command palette as a command line, with the history of `:` commands.
+        workspace.register_action(|workspace, _: &ToggleCommandPalette, cx| {
+            command_palette::CommandPalette::toggle_with_history(workspace, "", cx)
+        });
+
         workspace.register_action(|workspace, _: &ResetPaneSizes, cx| {
             workspace.reset_pane_sizes(cx);
         });
@@ -209,6 +216,7 @@ pub(crate) struct Vim {
     pub(crate) stored_visual_mode: Option<(Mode, Vec<bool>)>,
     pub(crate) change_list: Vec<Vec<Anchor>>,
     pub(crate) change_list_position: Option<usize>,
+    change_list_save_task: Option<Task<()>>,
 
     pub(crate) current_tx: Option<TransactionId>,
     pub(crate) current_anchor: Option<Selection<Anchor>>,
@@ -255,6 +263,7 @@ impl Vim {
             stored_visual_mode: None,
             change_list: Vec::new(),
             change_list_position: None,
+            change_list_save_task: None,
             current_tx: None,
             current_anchor: None,
             undo_modes: HashMap::default(),
@@ -311,6 +320,9 @@ impl Vim {
 
     fn activate(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
         let vim = Vim::new(cx);
+        if let Some(abs_path) = persistence::local_abs_path(editor, cx) {
+            vim.update(cx, |vim, cx| vim.restore_change_list(abs_path, cx));
+        }
 
         editor.register_addon(VimAddon { view: vim.clone() });
 
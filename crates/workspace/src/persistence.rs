@@ -1,6 +1,9 @@
 pub mod model;
 
-use std::{path::Path, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::{anyhow, bail, Context, Result};
 use client::DevServerProjectId;
@@ -8,7 +11,7 @@ use db::{define_connection, query, sqlez::connection::Connection, sqlez_macros::
 use gpui::{point, size, Axis, Bounds, WindowBounds, WindowId};
 
 use language::{LanguageName, Toolchain};
-use project::WorktreeId;
+use project::{Bookmark, WorktreeId};
 use remote::ssh_session::SshProjectId;
 use sqlez::{
     bindable::{Bind, Column, StaticColumnCount},
@@ -383,6 +386,17 @@ define_connection! {
     sql!(
         ALTER TABLE toolchains ADD COLUMN raw_json TEXT DEFAULT "{}";
     ),
+    sql!(
+        CREATE TABLE bookmarks (
+            workspace_id INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            buffer_row INTEGER NOT NULL,
+            name TEXT,
+            PRIMARY KEY (workspace_id, path, buffer_row),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+        ) STRICT;
+    ),
     ];
 }
 
@@ -1152,6 +1166,61 @@ impl WorkspaceDb {
             Ok(())
         }).await
     }
+
+    pub(crate) async fn bookmarks(&self, workspace_id: WorkspaceId) -> Result<Vec<Bookmark>> {
+        self.write(move |conn| {
+            let mut select = conn
+                .select_bound(sql!(
+                    SELECT path, buffer_row, name FROM bookmarks
+                    WHERE workspace_id = ?
+                    ORDER BY path, buffer_row
+                ))
+                .context("Preparing bookmarks query")?;
+
+            let bookmarks: Vec<(PathBuf, u32, Option<String>)> = select(workspace_id)?;
+            Ok(bookmarks
+                .into_iter()
+                .map(|(path, row, name)| Bookmark {
+                    abs_path: path.into(),
+                    row,
+                    name: name.map(Into::into),
+                })
+                .collect())
+        })
+        .await
+    }
+
+    pub(crate) async fn set_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        bookmarks: Vec<Bookmark>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("set_bookmarks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM bookmarks WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("Clearing old bookmarks")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT INTO bookmarks(workspace_id, path, buffer_row, name)
+                        VALUES (?, ?, ?, ?)
+                    ))
+                    .context("Preparing bookmark insertion")?;
+                for bookmark in bookmarks {
+                    insert((
+                        workspace_id,
+                        bookmark.abs_path,
+                        bookmark.row,
+                        bookmark.name.as_deref(),
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -2024,4 +2093,60 @@ mod tests {
 
         assert_eq!(workspace.center_group, new_workspace.center_group);
     }
+
+    #[gpui::test]
+    async fn test_bookmarks() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_bookmarks").await);
+        let workspace = SerializedWorkspace {
+            id: WorkspaceId(1),
+            location: SerializedWorkspaceLocation::from_local_paths(["/tmp"]),
+            center_group: Default::default(),
+            window_bounds: Default::default(),
+            display: Default::default(),
+            docks: Default::default(),
+            centered_layout: false,
+            session_id: None,
+            window_id: None,
+        };
+        db.save_workspace(workspace.clone()).await;
+
+        let bookmark = |path: &str, row, name: Option<&str>| Bookmark {
+            abs_path: Path::new(path).into(),
+            row,
+            name: name.map(|name| name.to_string().into()),
+        };
+        db.set_bookmarks(
+            workspace.id,
+            vec![
+                bookmark("/tmp/b.rs", 3, None),
+                bookmark("/tmp/a.rs", 10, Some("main")),
+                bookmark("/tmp/a.rs", 2, None),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.bookmarks(workspace.id).await.unwrap(),
+            [
+                bookmark("/tmp/a.rs", 2, None),
+                bookmark("/tmp/a.rs", 10, Some("main")),
+                bookmark("/tmp/b.rs", 3, None),
+            ]
+        );
+
+        // Setting bookmarks replaces the previous ones.
+        db.set_bookmarks(workspace.id, vec![bookmark("/tmp/b.rs", 4, None)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.bookmarks(workspace.id).await.unwrap(),
+            [bookmark("/tmp/b.rs", 4, None)]
+        );
+
+        // Bookmarks are deleted along with their workspace.
+        db.delete_workspace_by_id(workspace.id).await.unwrap();
+        assert!(db.bookmarks(workspace.id).await.unwrap().is_empty());
+    }
 }
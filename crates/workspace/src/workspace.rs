@@ -62,7 +62,8 @@ use persistence::{
 };
 use postage::stream::Stream;
 use project::{
-    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
+    BookmarkStoreEvent, DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath,
+    Worktree, WorktreeId,
 };
 use remote::{ssh_session::ConnectionIdentifier, SshClientDelegate, SshConnectionOptions};
 use serde::Deserialize;
@@ -774,6 +775,7 @@ pub struct Workspace {
     bounds: Bounds<Pixels>,
     centered_layout: bool,
     bounds_save_task_queued: Option<Task<()>>,
+    bookmarks_save_task_queued: Option<Task<()>>,
     on_prompt_for_new_path: Option<PromptForNewPath>,
     on_prompt_for_open_path: Option<PromptForOpenPath>,
     serializable_items_tx: UnboundedSender<Box<dyn SerializableItemHandle>>,
@@ -982,6 +984,7 @@ impl Workspace {
             Self::serialize_items(&this, serializable_items_rx, &mut cx).await
         });
 
+        let bookmark_store = project.read(cx).bookmark_store().clone();
         let subscriptions = vec![
             cx.observe_window_activation(Self::on_window_activation_changed),
             cx.observe_window_bounds(move |this, cx| {
@@ -1032,6 +1035,9 @@ impl Workspace {
                 this.serialize_workspace(cx);
                 cx.notify();
             }),
+            cx.subscribe(&bookmark_store, |this, _, _: &BookmarkStoreEvent, cx| {
+                this.serialize_bookmarks(cx)
+            }),
             cx.on_release(|this, window, cx| {
                 this.app_state.workspace_store.update(cx, |store, _| {
                     let window = window.downcast::<Self>().unwrap();
@@ -1079,6 +1085,7 @@ impl Workspace {
             bounds: Default::default(),
             centered_layout: false,
             bounds_save_task_queued: None,
+            bookmarks_save_task_queued: None,
             on_prompt_for_new_path: None,
             on_prompt_for_open_path: None,
             serializable_items_tx,
@@ -1184,6 +1191,11 @@ impl Workspace {
                     })?
                     .await;
             }
+            let bookmarks = DB.bookmarks(workspace_id).await?;
+            project_handle.update(&mut cx, |this, cx| {
+                this.bookmark_store()
+                    .update(cx, |store, cx| store.set_bookmarks(bookmarks, cx))
+            })?;
             let window = if let Some(window) = requesting_window {
                 cx.update_window(window.into(), |_, cx| {
                     cx.replace_root_view(|cx| {
@@ -4127,6 +4139,27 @@ impl Workspace {
         }
     }
 
+    fn serialize_bookmarks(&mut self, cx: &mut ViewContext<Self>) {
+        if self.bookmarks_save_task_queued.is_some() {
+            return;
+        }
+        self.bookmarks_save_task_queued = Some(cx.spawn(|this, mut cx| async move {
+            cx.background_executor()
+                .timer(Duration::from_millis(100))
+                .await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(database_id) = this.database_id {
+                    let bookmarks = this.project.read(cx).bookmark_store().read(cx).bookmarks();
+                    cx.background_executor()
+                        .spawn(DB.set_bookmarks(database_id, bookmarks))
+                        .detach_and_log_err(cx);
+                }
+                this.bookmarks_save_task_queued.take();
+            })
+            .ok();
+        }));
+    }
+
     fn serialize_workspace_internal(&self, cx: &mut WindowContext) -> Task<()> {
         let Some(database_id) = self.database_id() else {
             return Task::ready(());
@@ -5681,6 +5714,11 @@ pub fn open_ssh_project(
                 })?
                 .await;
         }
+        let bookmarks = DB.bookmarks(workspace_id).await?;
+        project.update(&mut cx, |this, cx| {
+            this.bookmark_store()
+                .update(cx, |store, cx| store.set_bookmarks(bookmarks, cx))
+        })?;
         let mut project_paths_to_open = vec![];
         let mut project_path_errors = vec![];
 
@@ -20,7 +20,7 @@ use futures::{
 use fuzzy::CharBag;
 use git::GitHostingProviderRegistry;
 use git::{
-    repository::{GitFileStatus, GitRepository, RepoPath},
+    repository::{GitFileStatus, GitRepository, RepoPath, UnmergedStatus},
     status::GitStatus,
     COOKIES, DOT_GIT, FSMONITOR_DAEMON, GITIGNORE,
 };
@@ -2484,7 +2484,7 @@ impl Snapshot {
                 let statuses = cursor.start().1 - prev_statuses;
 
                 result[entry_ix].git_status = if statuses.conflict > 0 {
-                    Some(GitFileStatus::Conflict)
+                    Some(GitFileStatus::Conflict(UnmergedStatus::BothModified))
                 } else if statuses.modified > 0 {
                     Some(GitFileStatus::Modified)
                 } else if statuses.added > 0 {
@@ -3598,7 +3598,7 @@ impl sum_tree::Item for Entry {
             match status {
                 GitFileStatus::Added => statuses.added = 1,
                 GitFileStatus::Modified => statuses.modified = 1,
-                GitFileStatus::Conflict => statuses.conflict = 1,
+                GitFileStatus::Conflict(_) => statuses.conflict = 1,
             }
         }
 
@@ -5596,7 +5596,25 @@ fn git_status_from_proto(git_status: Option<i32>) -> Option<GitFileStatus> {
         proto::GitStatus::from_i32(status).map(|status| match status {
             proto::GitStatus::Added => GitFileStatus::Added,
             proto::GitStatus::Modified => GitFileStatus::Modified,
-            proto::GitStatus::Conflict => GitFileStatus::Conflict,
+            proto::GitStatus::Conflict => GitFileStatus::Conflict(UnmergedStatus::BothModified),
+            proto::GitStatus::ConflictBothAdded => {
+                GitFileStatus::Conflict(UnmergedStatus::BothAdded)
+            }
+            proto::GitStatus::ConflictBothDeleted => {
+                GitFileStatus::Conflict(UnmergedStatus::BothDeleted)
+            }
+            proto::GitStatus::ConflictAddedByUs => {
+                GitFileStatus::Conflict(UnmergedStatus::AddedByUs)
+            }
+            proto::GitStatus::ConflictAddedByThem => {
+                GitFileStatus::Conflict(UnmergedStatus::AddedByThem)
+            }
+            proto::GitStatus::ConflictDeletedByUs => {
+                GitFileStatus::Conflict(UnmergedStatus::DeletedByUs)
+            }
+            proto::GitStatus::ConflictDeletedByThem => {
+                GitFileStatus::Conflict(UnmergedStatus::DeletedByThem)
+            }
         })
     })
 }
@@ -5605,7 +5623,15 @@ fn git_status_to_proto(status: GitFileStatus) -> i32 {
     match status {
         GitFileStatus::Added => proto::GitStatus::Added as i32,
         GitFileStatus::Modified => proto::GitStatus::Modified as i32,
-        GitFileStatus::Conflict => proto::GitStatus::Conflict as i32,
+        GitFileStatus::Conflict(status) => match status {
+            UnmergedStatus::BothModified => proto::GitStatus::Conflict as i32,
+            UnmergedStatus::BothAdded => proto::GitStatus::ConflictBothAdded as i32,
+            UnmergedStatus::BothDeleted => proto::GitStatus::ConflictBothDeleted as i32,
+            UnmergedStatus::AddedByUs => proto::GitStatus::ConflictAddedByUs as i32,
+            UnmergedStatus::AddedByThem => proto::GitStatus::ConflictAddedByThem as i32,
+            UnmergedStatus::DeletedByUs => proto::GitStatus::ConflictDeletedByUs as i32,
+            UnmergedStatus::DeletedByThem => proto::GitStatus::ConflictDeletedByThem as i32,
+        },
     }
 }
 
@@ -4,7 +4,10 @@ use crate::{
 };
 use anyhow::Result;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
-use git::{repository::GitFileStatus, GITIGNORE};
+use git::{
+    repository::{GitFileStatus, UnmergedStatus},
+    GITIGNORE,
+};
 use gpui::{BorrowAppContext, ModelContext, Task, TestAppContext};
 use parking_lot::Mutex;
 use postage::stream::Stream;
@@ -2648,7 +2651,10 @@ async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
         &[
             (Path::new("a/b/c1.txt"), GitFileStatus::Added),
             (Path::new("a/d/e2.txt"), GitFileStatus::Modified),
-            (Path::new("g/h2.txt"), GitFileStatus::Conflict),
+            (
+                Path::new("g/h2.txt"),
+                GitFileStatus::Conflict(UnmergedStatus::BothAdded),
+            ),
         ],
     );
 
@@ -2671,7 +2677,10 @@ async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
     check_propagated_statuses(
         &snapshot,
         &[
-            (Path::new(""), Some(GitFileStatus::Conflict)),
+            (
+                Path::new(""),
+                Some(GitFileStatus::Conflict(UnmergedStatus::BothModified)),
+            ),
             (Path::new("a"), Some(GitFileStatus::Modified)),
             (Path::new("a/b"), Some(GitFileStatus::Added)),
             (Path::new("a/b/c1.txt"), Some(GitFileStatus::Added)),
@@ -2680,8 +2689,14 @@ async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
             (Path::new("a/d/e2.txt"), Some(GitFileStatus::Modified)),
             (Path::new("f"), None),
             (Path::new("f/no-status.txt"), None),
-            (Path::new("g"), Some(GitFileStatus::Conflict)),
-            (Path::new("g/h2.txt"), Some(GitFileStatus::Conflict)),
+            (
+                Path::new("g"),
+                Some(GitFileStatus::Conflict(UnmergedStatus::BothModified)),
+            ),
+            (
+                Path::new("g/h2.txt"),
+                Some(GitFileStatus::Conflict(UnmergedStatus::BothAdded)),
+            ),
         ],
     );
 
@@ -2696,7 +2711,10 @@ async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
             (Path::new("a/d/e2.txt"), Some(GitFileStatus::Modified)),
             (Path::new("f"), None),
             (Path::new("f/no-status.txt"), None),
-            (Path::new("g"), Some(GitFileStatus::Conflict)),
+            (
+                Path::new("g"),
+                Some(GitFileStatus::Conflict(UnmergedStatus::BothModified)),
+            ),
         ],
     );
 
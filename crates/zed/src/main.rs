@@ -446,11 +446,14 @@ fn main() {
         tab_switcher::init(cx);
         outline::init(cx);
         project_symbols::init(cx);
+        bookmarks::init(cx);
         project_panel::init(Assets, cx);
         git_ui::git_panel::init(cx);
         outline_panel::init(Assets, cx);
+        hierarchy_panel::init(cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
+        spell_check::init(cx);
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
         search::init(cx);
         vim::init(cx);
@@ -26,6 +26,7 @@ use gpui::{
     PathPromptOptions, PromptLevel, ReadGlobal, Task, TitlebarOptions, View, ViewContext,
     VisualContext, WindowKind, WindowOptions,
 };
+use hierarchy_panel::HierarchyPanel;
 pub use open_listener::*;
 use outline_panel::OutlinePanel;
 use paths::{local_settings_file_relative_path, local_tasks_file_relative_path};
@@ -245,6 +246,7 @@ pub fn initialize_workspace(
         cx.spawn(|workspace_handle, mut cx| async move {
             let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
             let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+            let hierarchy_panel = HierarchyPanel::load(workspace_handle.clone(), cx.clone());
             let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
             let channels_panel =
                 collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
@@ -258,6 +260,7 @@ pub fn initialize_workspace(
             let (
                 project_panel,
                 outline_panel,
+                hierarchy_panel,
                 terminal_panel,
                 channels_panel,
                 chat_panel,
@@ -265,6 +268,7 @@ pub fn initialize_workspace(
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
+                hierarchy_panel,
                 terminal_panel,
                 channels_panel,
                 chat_panel,
@@ -274,6 +278,7 @@ pub fn initialize_workspace(
             workspace_handle.update(&mut cx, |workspace, cx| {
                 workspace.add_panel(project_panel, cx);
                 workspace.add_panel(outline_panel, cx);
+                workspace.add_panel(hierarchy_panel, cx);
                 workspace.add_panel(terminal_panel, cx);
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
@@ -3481,6 +3486,8 @@ mod tests {
             git_ui::init(cx);
             project_panel::init((), cx);
             outline_panel::init((), cx);
+            hierarchy_panel::init(cx);
+            spell_check::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
@@ -12,8 +12,9 @@ use fs::Fs;
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures::channel::{mpsc, oneshot};
 use futures::future::join_all;
-use futures::{FutureExt, SinkExt, StreamExt};
-use gpui::{AppContext, AsyncAppContext, Global, WindowHandle};
+use futures::{Future, FutureExt, SinkExt, StreamExt};
+use git_ui::file_diff_view::FileDiffView;
+use gpui::{AppContext, AsyncAppContext, BackgroundExecutor, Global, WindowHandle};
 use language::{Bias, Point};
 use recent_projects::{open_ssh_project, SshSettings};
 use remote::SshConnectionOptions;
@@ -260,6 +261,7 @@ pub async fn handle_cli_connection(
             CliRequest::Open {
                 urls,
                 paths,
+                diff_paths,
                 wait,
                 open_new_workspace,
                 env,
@@ -285,6 +287,23 @@ pub async fn handle_cli_connection(
                     return;
                 }
 
+                if !diff_paths.is_empty() {
+                    let errored = open_diff_views(
+                        paths,
+                        diff_paths,
+                        open_new_workspace,
+                        wait,
+                        &responses,
+                        env,
+                        &app_state,
+                        &mut cx,
+                    )
+                    .await;
+                    let status = if errored { 1 } else { 0 };
+                    responses.send(CliResponse::Exit { status }).log_err();
+                    return;
+                }
+
                 let open_workspace_result = open_workspaces(
                     paths,
                     open_new_workspace,
@@ -480,24 +499,8 @@ async fn open_local_workspace(
                     } else {
                         let _ = futures::future::try_join_all(item_release_futures).await;
                     };
-                }
-                .fuse();
-
-                futures::pin_mut!(wait);
-
-                loop {
-                    // Repeatedly check if CLI is still open to avoid wasting resources
-                    // waiting for files or workspaces to close.
-                    let mut timer = background.timer(Duration::from_secs(1)).fuse();
-                    futures::select_biased! {
-                        _ = wait => break,
-                        _ = timer => {
-                            if responses.send(CliResponse::Ping).is_err() {
-                                break;
-                            }
-                        }
-                    }
-                }
+                };
+                wait_while_cli_is_open(wait, responses, &background).await;
             }
         }
         Err(error) => {
@@ -512,6 +515,135 @@ async fn open_local_workspace(
     errored
 }
 
+/// Opens a side-by-side comparison for each `[old, new]` pair of files.
+///
+/// The comparisons are opened in the workspace for `paths`. If there are none, they're opened
+/// in an existing window or a new, empty one, and the compared files are added to its project
+/// as single-file worktrees, like the files passed to `zed <file>`.
+async fn open_diff_views(
+    paths: Vec<String>,
+    diff_paths: Vec<[String; 2]>,
+    open_new_workspace: Option<bool>,
+    wait: bool,
+    responses: &IpcSender<CliResponse>,
+    env: Option<HashMap<String, String>>,
+    app_state: &Arc<AppState>,
+    cx: &mut AsyncAppContext,
+) -> bool {
+    let workspace_paths = paths.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+
+    let open_options = OpenOptions {
+        open_new_workspace,
+        env,
+        ..Default::default()
+    };
+    let workspace = match cx
+        .update(|cx| workspace::open_paths(&workspace_paths, app_state.clone(), open_options, cx))
+    {
+        Ok(task) => task.await.map(|(workspace, _)| workspace),
+        Err(error) => Err(error),
+    };
+    let workspace = match workspace {
+        Ok(workspace) => workspace,
+        Err(error) => {
+            responses
+                .send(CliResponse::Stderr {
+                    message: format!("error opening {workspace_paths:?}: {error}"),
+                })
+                .log_err();
+            return true;
+        }
+    };
+
+    // As with `zed <file>`, the files are only shown in the project panel of a new workspace.
+    let visible = workspace
+        .update(cx, |workspace, cx| {
+            workspace
+                .project()
+                .read(cx)
+                .visible_worktrees(cx)
+                .next()
+                .is_none()
+        })
+        .unwrap_or(false);
+
+    let mut errored = false;
+    let mut view_release_futures = Vec::new();
+    for [old_path, new_path] in diff_paths {
+        let view = workspace.update(cx, |workspace, cx| {
+            FileDiffView::open(
+                PathBuf::from(&old_path),
+                PathBuf::from(&new_path),
+                visible,
+                workspace,
+                cx,
+            )
+        });
+        let view = match view {
+            Ok(task) => task.await,
+            Err(error) => Err(error),
+        };
+        match view {
+            Ok(view) => {
+                cx.update(|cx| {
+                    let released = oneshot::channel();
+                    view.on_release(
+                        cx,
+                        Box::new(move |_| {
+                            let _ = released.0.send(());
+                        }),
+                    )
+                    .detach();
+                    view_release_futures.push(released.1);
+                })
+                .log_err();
+            }
+            Err(error) => {
+                responses
+                    .send(CliResponse::Stderr {
+                        message: format!("error comparing {old_path:?} and {new_path:?}: {error}"),
+                    })
+                    .log_err();
+                errored = true;
+            }
+        }
+    }
+
+    if wait && !view_release_futures.is_empty() {
+        let background = cx.background_executor().clone();
+        let wait = async move {
+            let _ = futures::future::try_join_all(view_release_futures).await;
+        };
+        wait_while_cli_is_open(wait, responses, &background).await;
+    }
+
+    errored
+}
+
+/// Waits for `wait` to complete, returning early if the CLI disconnects.
+async fn wait_while_cli_is_open(
+    wait: impl Future<Output = ()>,
+    responses: &IpcSender<CliResponse>,
+    background: &BackgroundExecutor,
+) {
+    let wait = wait.fuse();
+    futures::pin_mut!(wait);
+
+    loop {
+        // Repeatedly check if CLI is still open to avoid wasting resources
+        // waiting for files or workspaces to close.
+        let mut timer = background.timer(Duration::from_secs(1)).fuse();
+        futures::select_biased! {
+            _ = wait => break,
+            _ = timer => {
+                if responses.send(CliResponse::Ping).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
 pub async fn derive_paths_with_position(
     fs: &dyn Fs,
     path_strings: impl IntoIterator<Item = impl AsRef<str>>,
@@ -531,18 +663,23 @@ pub async fn derive_paths_with_position(
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{path::PathBuf, sync::Arc};
 
     use cli::{
         ipc::{self},
         CliResponse,
     };
     use editor::Editor;
+    use futures::FutureExt as _;
+    use git_ui::file_diff_view::FileDiffView;
     use gpui::TestAppContext;
     use serde_json::json;
-    use workspace::{AppState, Workspace};
+    use workspace::{AppState, CloseActiveItem, Workspace};
 
-    use crate::zed::{open_listener::open_local_workspace, tests::init_test};
+    use crate::zed::{
+        open_listener::{open_diff_views, open_local_workspace},
+        tests::init_test,
+    };
 
     #[gpui::test]
     async fn test_open_workspace_with_directory(cx: &mut TestAppContext) {
@@ -645,6 +782,84 @@ mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_open_diff_views(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+
+        app_state
+            .fs
+            .as_fake()
+            .insert_tree(
+                "/root",
+                json!({
+                    "dir": {
+                        "old.txt": "one\ntwo\n",
+                        "new.txt": "one\n2\n",
+                        "other.txt": "",
+                    },
+                }),
+            )
+            .await;
+
+        let (response_tx, _response_rx) = ipc::channel::<CliResponse>().unwrap();
+        let mut wait = cx.spawn({
+            let app_state = app_state.clone();
+            |mut cx| async move {
+                open_diff_views(
+                    Vec::new(),
+                    vec![["/root/dir/old.txt".into(), "/root/dir/new.txt".into()]],
+                    None,
+                    true,
+                    &response_tx,
+                    None,
+                    &app_state,
+                    &mut cx,
+                )
+                .await
+            }
+        });
+        cx.run_until_parked();
+
+        // Only the compared files are added to the project, not the directory containing them.
+        assert_eq!(cx.windows().len(), 1);
+        let workspace = cx.windows()[0].downcast::<Workspace>().unwrap();
+        workspace
+            .update(cx, |workspace, cx| {
+                let project = workspace.project().read(cx);
+                let mut worktree_paths = project
+                    .visible_worktrees(cx)
+                    .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                    .collect::<Vec<_>>();
+                worktree_paths.sort();
+                assert_eq!(
+                    worktree_paths,
+                    [
+                        PathBuf::from("/root/dir/new.txt"),
+                        PathBuf::from("/root/dir/old.txt"),
+                    ]
+                );
+                assert!(workspace.active_item_as::<FileDiffView>(cx).is_some());
+                assert_eq!(workspace.items(cx).count(), 1);
+            })
+            .unwrap();
+
+        // With `--wait`, the CLI is released once the comparison is closed.
+        assert!((&mut wait).now_or_never().is_none());
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.close_active_item(&CloseActiveItem { save_intent: None }, cx)
+                })
+            })
+            .unwrap()
+            .unwrap()
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        let errored = (&mut wait).now_or_never().unwrap();
+        assert!(!errored);
+    }
+
     async fn open_workspace_file(
         path: &str,
         open_new_workspace: Option<bool>,